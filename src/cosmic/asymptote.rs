@@ -0,0 +1,256 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::{Frame, Orbit};
+use snafu::{ensure, ResultExt};
+
+use super::{AstroError, AstroPhysicsSnafu, InfeasibleAsymptoteSnafu, NonPositiveC3Snafu};
+use crate::linalg::Vector3;
+use crate::time::Epoch;
+use crate::utils::between_0_360;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Tangential injection from a circular parking orbit onto a departure hyperbola, computed by [departure_injection].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HyperbolicDeparture {
+    /// State on the circular parking orbit at the injection point
+    pub parking: Orbit,
+    /// State on the departure hyperbola right after the injection, i.e. at its periapsis
+    pub hyperbola: Orbit,
+    /// Argument of latitude of the injection point on the parking orbit, in degrees
+    pub aol_deg: f64,
+    /// Magnitude of the impulsive injection, in km/s
+    pub delta_v_km_s: f64,
+}
+
+impl fmt::Display for HyperbolicDeparture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "injection at AoL = {:.3} deg with Δv = {:.6} km/s onto {:x}",
+            self.aol_deg, self.delta_v_km_s, self.hyperbola
+        )
+    }
+}
+
+/// Periapsis conditions of an arrival hyperbola, computed by [arrival_periapsis].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HyperbolicArrival {
+    /// State at periapsis of the arrival hyperbola
+    pub hyperbola: Orbit,
+    /// B-Plane B⋅T, in km
+    pub b_t_km: f64,
+    /// B-Plane B⋅R, in km
+    pub b_r_km: f64,
+    /// Angle between the incoming and outgoing asymptotes, in degrees
+    pub turn_angle_deg: f64,
+}
+
+impl fmt::Display for HyperbolicArrival {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "B⋅T = {:.3} km  B⋅R = {:.3} km  turn angle = {:.3} deg at periapsis {:x}",
+            self.b_t_km, self.b_r_km, self.turn_angle_deg, self.hyperbola
+        )
+    }
+}
+
+/// Returns the unit vector of the provided right ascension and declination, in degrees.
+fn radec_unit(ra_deg: f64, dec_deg: f64) -> Vector3<f64> {
+    let (sin_ra, cos_ra) = ra_deg.to_radians().sin_cos();
+    let (sin_dec, cos_dec) = dec_deg.to_radians().sin_cos();
+    Vector3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec)
+}
+
+/// Designs the tangential injection from a circular parking orbit onto the departure hyperbola whose outgoing asymptote
+/// has the provided C3 (in km^2/s^2), right ascension (RLA) and declination (DLA) in degrees, in the provided frame.
+///
+/// The parking orbit is defined by its inclination and its altitude above the mean equatorial radius of the frame.
+/// Its plane must contain the asymptote, which yields the two returned solutions: one per node of the parking orbit,
+/// with the injection happening at the periapsis of each hyperbola.
+///
+/// Errors if the C3 is not positive, or if the declination of the asymptote exceeds the inclination of the parking orbit
+/// (or its supplement for retrograde orbits), since reaching it would require a plane change.
+///
+/// Reference: Vallado, 4th Ed., section 12.4, and Curtis, 3rd Ed., section 8.7.
+#[allow(clippy::too_many_arguments)]
+pub fn departure_injection(
+    c3_km2_s2: f64,
+    rla_deg: f64,
+    dla_deg: f64,
+    parking_inc_deg: f64,
+    parking_alt_km: f64,
+    epoch: Epoch,
+    frame: Frame,
+) -> Result<[HyperbolicDeparture; 2], AstroError> {
+    ensure!(c3_km2_s2 > 0.0, NonPositiveC3Snafu { c3_km2_s2 });
+
+    let mu_km3_s2 = frame.mu_km3_s2().context(AstroPhysicsSnafu)?;
+    let rp_km = frame
+        .mean_equatorial_radius_km()
+        .context(AstroPhysicsSnafu)?
+        + parking_alt_km;
+
+    let (sin_inc, cos_inc) = parking_inc_deg.to_radians().sin_cos();
+    let (sin_dla, cos_dla) = dla_deg.to_radians().sin_cos();
+
+    // The parking plane contains the asymptote iff sin(i) cos(δ) sin(Ω - α) = -cos(i) sin(δ)
+    let min_inc_deg = dla_deg.abs();
+    let sin_raan_offset = if sin_inc.abs() < 1e-12 {
+        if sin_dla.abs() > 1e-12 {
+            None
+        } else {
+            // Equatorial parking orbit and asymptote: the node is undefined, pick the direction of the asymptote
+            Some(0.0)
+        }
+    } else {
+        Some(-cos_inc * sin_dla / (sin_inc * cos_dla))
+    };
+    let sin_raan_offset = match sin_raan_offset {
+        Some(x) if x.abs() <= 1.0 + 1e-12 => x.clamp(-1.0, 1.0),
+        _ => {
+            return InfeasibleAsymptoteSnafu {
+                dla_deg,
+                min_inc_deg,
+                max_inc_deg: 180.0 - min_inc_deg,
+            }
+            .fail()
+        }
+    };
+
+    let raan_offset_rad = sin_raan_offset.asin();
+    let s_hat = radec_unit(rla_deg, dla_deg);
+
+    // Hyperbola geometry: the outgoing asymptote is at the true anomaly ν∞ from periapsis
+    let v_inf_km_s = c3_km2_s2.sqrt();
+    let ecc = 1.0 + rp_km * c3_km2_s2 / mu_km3_s2;
+    let ta_inf_rad = (-1.0 / ecc).acos();
+    let vp_km_s = (c3_km2_s2 + 2.0 * mu_km3_s2 / rp_km).sqrt();
+    let vc_km_s = (mu_km3_s2 / rp_km).sqrt();
+
+    let solution_for = |raan_rad: f64| -> HyperbolicDeparture {
+        let (sin_raan, cos_raan) = raan_rad.sin_cos();
+        let h_hat = Vector3::new(sin_inc * sin_raan, -sin_inc * cos_raan, cos_inc);
+        let node_hat = Vector3::new(cos_raan, sin_raan, 0.0);
+
+        // Periapsis direction, rotated back from the asymptote by ν∞ in the orbital plane
+        let p_hat = ta_inf_rad.cos() * s_hat - ta_inf_rad.sin() * h_hat.cross(&s_hat);
+        let q_hat = h_hat.cross(&p_hat);
+
+        let radius_km = rp_km * p_hat;
+        let state_with_speed = |speed_km_s: f64| {
+            let velocity_km_s = speed_km_s * q_hat;
+            Orbit::new(
+                radius_km.x,
+                radius_km.y,
+                radius_km.z,
+                velocity_km_s.x,
+                velocity_km_s.y,
+                velocity_km_s.z,
+                epoch,
+                frame,
+            )
+        };
+
+        HyperbolicDeparture {
+            parking: state_with_speed(vc_km_s),
+            hyperbola: state_with_speed(vp_km_s),
+            aol_deg: between_0_360(
+                node_hat
+                    .cross(&p_hat)
+                    .dot(&h_hat)
+                    .atan2(node_hat.dot(&p_hat))
+                    .to_degrees(),
+            ),
+            delta_v_km_s: vp_km_s - vc_km_s,
+        }
+    };
+
+    let rla_rad = rla_deg.to_radians();
+    let solutions = [
+        solution_for(rla_rad + raan_offset_rad),
+        solution_for(rla_rad + PI - raan_offset_rad),
+    ];
+
+    debug!(
+        "departure with v∞ = {v_inf_km_s:.6} km/s from rp = {rp_km:.3} km: {} | {}",
+        solutions[0], solutions[1]
+    );
+
+    Ok(solutions)
+}
+
+/// Computes the periapsis conditions of the arrival hyperbola given the incoming hyperbolic excess velocity vector (km/s)
+/// expressed in the provided frame, the periapsis altitude above the mean equatorial radius of the frame, and the orientation
+/// of the B vector in the B-Plane measured from the T axis towards the R axis (in degrees, e.g. zero for a B vector along T).
+///
+/// The B-Plane axes follow the [super::BPlane] convention: S along the incoming asymptote, T = S × Z, and R = S × T.
+pub fn arrival_periapsis(
+    v_inf_km_s: Vector3<f64>,
+    periapsis_alt_km: f64,
+    b_plane_angle_deg: f64,
+    periapsis_epoch: Epoch,
+    frame: Frame,
+) -> Result<HyperbolicArrival, AstroError> {
+    let c3_km2_s2 = v_inf_km_s.norm_squared();
+    ensure!(c3_km2_s2 > 0.0, NonPositiveC3Snafu { c3_km2_s2 });
+
+    let mu_km3_s2 = frame.mu_km3_s2().context(AstroPhysicsSnafu)?;
+    let rp_km = frame
+        .mean_equatorial_radius_km()
+        .context(AstroPhysicsSnafu)?
+        + periapsis_alt_km;
+
+    let s_hat = v_inf_km_s / v_inf_km_s.norm();
+    let t_vec = s_hat.cross(&Vector3::z());
+    let t_hat = t_vec / t_vec.norm();
+    let r_hat = s_hat.cross(&t_hat);
+
+    let ecc = 1.0 + rp_km * c3_km2_s2 / mu_km3_s2;
+    let ta_inf_rad = (-1.0 / ecc).acos();
+    let b_mag_km = rp_km * (1.0 + 2.0 * mu_km3_s2 / (rp_km * c3_km2_s2)).sqrt();
+
+    let (sin_theta, cos_theta) = b_plane_angle_deg.to_radians().sin_cos();
+    let b_hat = cos_theta * t_hat + sin_theta * r_hat;
+    let h_hat = b_hat.cross(&s_hat);
+
+    // The incoming asymptote is at the true anomaly -ν∞ from periapsis
+    let p_hat = -ta_inf_rad.cos() * s_hat - ta_inf_rad.sin() * h_hat.cross(&s_hat);
+    let q_hat = h_hat.cross(&p_hat);
+
+    let radius_km = rp_km * p_hat;
+    let velocity_km_s = (c3_km2_s2 + 2.0 * mu_km3_s2 / rp_km).sqrt() * q_hat;
+
+    Ok(HyperbolicArrival {
+        hyperbola: Orbit::new(
+            radius_km.x,
+            radius_km.y,
+            radius_km.z,
+            velocity_km_s.x,
+            velocity_km_s.y,
+            velocity_km_s.z,
+            periapsis_epoch,
+            frame,
+        ),
+        b_t_km: b_mag_km * cos_theta,
+        b_r_km: b_mag_km * sin_theta,
+        turn_angle_deg: (2.0 * (1.0 / ecc).asin()).to_degrees(),
+    })
+}
@@ -0,0 +1,230 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::almanac::Almanac;
+use anise::errors::OrientationSnafu;
+use anise::prelude::{Frame, Orbit};
+use nalgebra::UnitQuaternion;
+use snafu::prelude::*;
+use std::sync::Arc;
+
+use crate::dynamics::{DynamicsAlmanacSnafu, DynamicsAttitudeSnafu, DynamicsError};
+use crate::linalg::{Matrix3, Vector3};
+use crate::time::Epoch;
+
+/// Errors of the attitude ephemerides
+#[derive(Debug, PartialEq, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum AttitudeError {
+    #[snafu(display("no attitude at {epoch}: ephemeris only covers {start} to {end}"))]
+    OutOfCoverage {
+        epoch: Epoch,
+        start: Epoch,
+        end: Epoch,
+    },
+    #[snafu(display("invalid attitude ephemeris: {msg}"))]
+    InvalidAttitude { msg: String },
+}
+
+/// A time-tagged attitude ephemeris of a spacecraft, e.g. read from a CCSDS AEM, interpolated with SLERP between its samples.
+///
+/// Each sample is stored as the rotation from the body frame to the reference frame, i.e. `q * v_body` is the vector `v_body` expressed in the reference frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttitudeEphemeris {
+    /// Name of the object, if known
+    pub object_name: Option<String>,
+    /// Orientation ID of the reference frame of the quaternions, e.g. J2000
+    pub orientation_id: i32,
+    samples: Vec<(Epoch, UnitQuaternion<f64>)>,
+}
+
+impl AttitudeEphemeris {
+    /// Builds an attitude ephemeris from the body to reference frame quaternions, which are sorted by epoch.
+    /// If several samples share the same epoch (e.g. at the boundary of two AEM segments), only the first one is kept.
+    pub fn new(
+        mut samples: Vec<(Epoch, UnitQuaternion<f64>)>,
+        orientation_id: i32,
+    ) -> Result<Self, AttitudeError> {
+        ensure!(
+            !samples.is_empty(),
+            InvalidAttitudeSnafu {
+                msg: "no attitude sample"
+            }
+        );
+        samples.sort_by(|(e1, _), (e2, _)| e1.cmp(e2));
+        samples.dedup_by_key(|(epoch, _)| *epoch);
+
+        Ok(Self {
+            object_name: None,
+            orientation_id,
+            samples,
+        })
+    }
+
+    /// Sets the name of the object of this ephemeris
+    pub fn with_object_name(mut self, name: String) -> Self {
+        self.object_name = Some(name);
+        self
+    }
+
+    /// Epoch of the first sample
+    pub fn start(&self) -> Epoch {
+        self.samples[0].0
+    }
+
+    /// Epoch of the last sample
+    pub fn end(&self) -> Epoch {
+        self.samples[self.samples.len() - 1].0
+    }
+
+    /// All of the samples of this ephemeris, sorted by epoch
+    pub fn samples(&self) -> &[(Epoch, UnitQuaternion<f64>)] {
+        &self.samples
+    }
+
+    /// Returns the rotation from the body frame to the reference frame at the provided epoch, interpolated with SLERP between the two nearest samples.
+    pub fn quaternion_at(&self, epoch: Epoch) -> Result<UnitQuaternion<f64>, AttitudeError> {
+        ensure!(
+            epoch >= self.start() && epoch <= self.end(),
+            OutOfCoverageSnafu {
+                epoch,
+                start: self.start(),
+                end: self.end(),
+            }
+        );
+
+        let idx = match self.samples.binary_search_by(|(e, _)| e.cmp(&epoch)) {
+            Ok(idx) => return Ok(self.samples[idx].1),
+            Err(idx) => idx,
+        };
+
+        let (e0, q0) = self.samples[idx - 1];
+        let (e1, mut q1) = self.samples[idx];
+        // Quaternions q and -q are the same rotation: interpolate along the shortest path.
+        if q0.coords.dot(&q1.coords) < 0.0 {
+            q1 = UnitQuaternion::new_unchecked(-q1.into_inner());
+        }
+        let t = (epoch - e0).to_seconds() / (e1 - e0).to_seconds();
+
+        Ok(q0
+            .try_slerp(&q1, t, f64::EPSILON)
+            .unwrap_or_else(|| q0.nlerp(&q1, t)))
+    }
+
+    /// Returns the DCM from the body frame to the reference frame at the provided epoch.
+    pub fn dcm_to_inertial(&self, epoch: Epoch) -> Result<Matrix3<f64>, AttitudeError> {
+        Ok(self.quaternion_at(epoch)?.to_rotation_matrix().into_inner())
+    }
+
+    /// Returns the reference frame of this ephemeris, centered on the center of the provided frame.
+    pub fn reference_frame(&self, center: Frame) -> Frame {
+        Frame::new(center.ephemeris_id, self.orientation_id)
+    }
+}
+
+/// A flat plate of the surface of the spacecraft. Only the side along its normal is exposed, so the two faces of a solar array are two plates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plate {
+    /// Outward normal of the plate in the body frame
+    pub normal_body: Vector3<f64>,
+    pub area_m2: f64,
+}
+
+impl Plate {
+    /// Projected area in m^2 of this plate seen from the provided direction in the body frame, zero if the plate faces away.
+    pub fn projected_area_m2(&self, direction_body: &Vector3<f64>) -> f64 {
+        let cos_angle = self
+            .normal_body
+            .normalize()
+            .dot(&direction_body.normalize());
+        self.area_m2 * cos_angle.max(0.0)
+    }
+}
+
+/// The N-plate model of the surface of the spacecraft, whose projected area depends on its attitude.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlateModel {
+    pub plates: Vec<Plate>,
+}
+
+impl PlateModel {
+    /// Projected area in m^2 of all of the plates seen from the provided direction in the body frame.
+    ///
+    /// Self-shadowing between plates is not modeled.
+    pub fn projected_area_m2(&self, direction_body: &Vector3<f64>) -> f64 {
+        self.plates
+            .iter()
+            .map(|plate| plate.projected_area_m2(direction_body))
+            .sum()
+    }
+
+    /// Projected area in m^2 seen from the provided direction in the reference frame of the attitude ephemeris, at the provided epoch.
+    ///
+    /// For solar radiation pressure, the direction is the Sun as seen from the spacecraft; for drag, it is the velocity relative to the atmosphere.
+    pub fn projected_area_at_m2(
+        &self,
+        attitude: &AttitudeEphemeris,
+        epoch: Epoch,
+        direction: &Vector3<f64>,
+    ) -> Result<f64, AttitudeError> {
+        let direction_body = attitude
+            .quaternion_at(epoch)?
+            .inverse_transform_vector(direction);
+        Ok(self.projected_area_m2(&direction_body))
+    }
+}
+
+/// A plate model oriented by the attitude ephemeris of the spacecraft.
+///
+/// When set on the solar radiation pressure or drag force models, its projected area replaces the constant area of the spacecraft.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrientedPlates {
+    pub plates: PlateModel,
+    pub attitude: Arc<AttitudeEphemeris>,
+}
+
+impl OrientedPlates {
+    /// Projected area in m^2 seen from the provided direction in the frame of the orbit, at the epoch of the orbit.
+    pub(crate) fn projected_area_m2(
+        &self,
+        orbit: &Orbit,
+        direction: &Vector3<f64>,
+        almanac: &Almanac,
+    ) -> Result<f64, DynamicsError> {
+        let direction = if self.attitude.orientation_id == orbit.frame.orientation_id {
+            *direction
+        } else {
+            let rot = almanac
+                .rotate(
+                    orbit.frame,
+                    self.attitude.reference_frame(orbit.frame),
+                    orbit.epoch,
+                )
+                .context(OrientationSnafu {
+                    action: "rotating into the frame of the attitude ephemeris",
+                })
+                .context(DynamicsAlmanacSnafu {
+                    action: "computing the projected area of the plates",
+                })?;
+            rot.rot_mat * direction
+        };
+        self.plates
+            .projected_area_at_m2(&self.attitude, orbit.epoch, &direction)
+            .context(DynamicsAttitudeSnafu)
+    }
+}
@@ -24,8 +24,9 @@ use snafu::ResultExt;
 
 pub use super::{Frame, Orbit, Spacecraft};
 use crate::errors::{EventAlmanacSnafu, EventError};
-use crate::md::EventEvaluator;
+use crate::md::{EventEvaluator, EventPrediction};
 use crate::time::{Duration, Unit};
+use std::f64::consts::{PI, TAU};
 use std::fmt;
 use std::sync::Arc;
 
@@ -88,6 +89,18 @@ impl EclipseLocator {
         }
     }
 
+    /// Creates an eclipse event from this eclipse locator, whose arcs are the eclipses.
+    ///
+    /// The event happens when the occultation reaches the provided threshold, between 0.0 (visibility) and 1.0 (umbra), e.g. 0.02 for the start of the penumbra.
+    /// The threshold must be larger than the value precision of the event, i.e. 0.005.
+    pub fn to_eclipse_event(&self, threshold: f64) -> EclipseEvent {
+        EclipseEvent {
+            e_loc: self.clone(),
+            threshold,
+            max_ecc: 0.01,
+        }
+    }
+
     /// Creates a penumbra event from this eclipse locator
     // Evaluation of the event, returns 0.0 for umbra, 1.0 for visibility (no shadow) and some value in between for penumbra
     pub fn to_penumbra_event(&self) -> PenumbraEvent {
@@ -187,3 +200,113 @@ impl EventEvaluator<Spacecraft> for PenumbraEvent {
         ))
     }
 }
+
+/// An event to find the eclipses, whose value is the occultation minus the threshold, i.e. positive in shadow.
+///
+/// For near-circular orbits around the only shadow body, the entry and exit of each eclipse are predicted from a cylindrical shadow model,
+/// such that the event search only refines each of these predictions instead of searching the whole trajectory.
+pub struct EclipseEvent {
+    e_loc: EclipseLocator,
+    /// Occultation at which the eclipse starts, between 0.0 (visibility) and 1.0 (umbra)
+    pub threshold: f64,
+    /// Maximum eccentricity for which the eclipses are predicted, defaults to 0.01. Set to zero to always use the generic event search.
+    pub max_ecc: f64,
+}
+
+impl EclipseEvent {
+    /// Sets the maximum eccentricity for which the eclipses are predicted
+    pub fn with_max_ecc(mut self, max_ecc: f64) -> Self {
+        self.max_ecc = max_ecc;
+        self
+    }
+}
+
+impl fmt::Display for EclipseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "eclipse event ({}) {}", self.threshold, self.e_loc)
+    }
+}
+
+impl EventEvaluator<Spacecraft> for EclipseEvent {
+    fn eval(&self, sc: &Spacecraft, almanac: Arc<Almanac>) -> Result<f64, EventError> {
+        let occult = self
+            .e_loc
+            .compute(sc.orbit, almanac)
+            .context(EventAlmanacSnafu)?
+            .factor();
+
+        Ok(occult - self.threshold)
+    }
+
+    /// Stop searching when the time has converged to less than 0.1 seconds
+    fn epoch_precision(&self) -> Duration {
+        0.1 * Unit::Second
+    }
+
+    fn value_precision(&self) -> f64 {
+        0.005
+    }
+
+    fn eval_string(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Result<String, EventError> {
+        Ok(format!(
+            "{}",
+            self.e_loc
+                .compute(state.orbit, almanac)
+                .context(EventAlmanacSnafu)?
+        ))
+    }
+
+    /// Predicts the entry and exit of the eclipse over the next revolution from the beta angle, assuming a circular orbit and a cylindrical shadow.
+    fn predict(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Option<EventPrediction> {
+        let orbit = state.orbit;
+        let [shadow_body] = self.e_loc.shadow_bodies.as_slice() else {
+            return None;
+        };
+        if !orbit.frame.ephem_origin_match(*shadow_body) || orbit.ecc().ok()? >= self.max_ecc {
+            return None;
+        }
+
+        let body_radius_km = orbit.frame.mean_equatorial_radius_km().ok()?;
+        let sma_km = orbit.sma_km().ok()?;
+        let period = orbit.period().ok()?;
+        let light_source = almanac
+            .transform(self.e_loc.light_source, orbit.frame, orbit.epoch, None)
+            .ok()?;
+
+        // In-plane basis, where the anomaly is zero at the current position
+        let p_hat = orbit.radius_km.normalize();
+        let q_hat = orbit
+            .radius_km
+            .cross(&orbit.velocity_km_s)
+            .normalize()
+            .cross(&p_hat);
+        let s_hat = light_source.radius_km.normalize();
+
+        // The projection of the light source direction in the orbit plane has a norm of cos(beta)
+        let (s_p, s_q) = (p_hat.dot(&s_hat), q_hat.dot(&s_hat));
+        let cos_beta = s_p.hypot(s_q);
+        // In shadow when the spacecraft is behind the body and within its radius of the shadow axis
+        let cos_half_arc = (1.0 - (body_radius_km / sma_km).powi(2)).sqrt() / cos_beta;
+
+        let margin = period * 0.02;
+        let mut epochs = Vec::new();
+        if cos_half_arc < 1.0 {
+            let half_arc = cos_half_arc.acos();
+            if period * (half_arc / PI) <= margin * 2 {
+                // Grazing eclipse: the brackets of the entry and exit would overlap.
+                return None;
+            }
+            let anti_source = s_q.atan2(s_p) + PI;
+            for anomaly in [anti_source - half_arc, anti_source + half_arc] {
+                epochs.push(orbit.epoch + period * (anomaly.rem_euclid(TAU) / TAU));
+            }
+            epochs.sort();
+        }
+
+        Some(EventPrediction {
+            epochs,
+            margin,
+            valid_until: orbit.epoch + period,
+        })
+    }
+}
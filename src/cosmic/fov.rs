@@ -0,0 +1,275 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::almanac::Almanac;
+use anise::constants::celestial_objects::SUN;
+use anise::errors::OrientationSnafu;
+use snafu::ResultExt;
+
+use super::attitude::AttitudeEphemeris;
+use super::{Frame, Orbit, Spacecraft};
+use crate::errors::{
+    EventAlmanacSnafu, EventAttitudeSnafu, EventError, EventPhysicsSnafu, EventTrajSnafu,
+};
+use crate::linalg::{Matrix3, Vector3};
+use crate::md::prelude::Traj;
+use crate::md::EventEvaluator;
+use crate::time::Duration;
+use std::fmt;
+use std::sync::Arc;
+
+/// Attitude law of the spacecraft, which defines the body frame in which the boresight of an instrument is set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttitudeLaw {
+    /// The body frame is aligned with the frame of the orbit of the spacecraft
+    Inertial,
+    /// The body Z axis points to the center of the central body, the Y axis is opposite to the orbital momentum, and the X axis completes the frame
+    /// (i.e. along the velocity for a circular orbit).
+    Nadir,
+    /// The body Z axis points to the Sun, the Y axis is along the cross product of the Z axis and the orbital momentum, and the X axis completes the frame.
+    SunPointing,
+    /// The body frame is given by a time-tagged attitude ephemeris, e.g. read from a CCSDS AEM, which must cover the epochs searched
+    Ephemeris(Arc<AttitudeEphemeris>),
+}
+
+impl AttitudeLaw {
+    /// Returns the DCM from the body frame to the frame of the orbit of the spacecraft.
+    pub fn dcm_to_inertial(
+        &self,
+        orbit: &Orbit,
+        almanac: &Almanac,
+    ) -> Result<Matrix3<f64>, EventError> {
+        let h_hat = orbit.radius_km.cross(&orbit.velocity_km_s).normalize();
+        let (z_hat, y_hat) = match self {
+            Self::Inertial => return Ok(Matrix3::identity()),
+            Self::Ephemeris(ephem) => {
+                let dcm = ephem
+                    .dcm_to_inertial(orbit.epoch)
+                    .context(EventAttitudeSnafu)?;
+                if ephem.orientation_id == orbit.frame.orientation_id {
+                    return Ok(dcm);
+                }
+                let rot = almanac
+                    .rotate(ephem.reference_frame(orbit.frame), orbit.frame, orbit.epoch)
+                    .context(OrientationSnafu {
+                        action: "rotating the attitude ephemeris into the frame of the orbit",
+                    })
+                    .context(EventAlmanacSnafu)?;
+                return Ok(rot.rot_mat * dcm);
+            }
+            Self::Nadir => (-orbit.radius_km.normalize(), -h_hat),
+            Self::SunPointing => {
+                let sun = almanac
+                    .transform(orbit.frame.with_ephem(SUN), orbit.frame, orbit.epoch, None)
+                    .context(EventAlmanacSnafu)?;
+                let z_hat = (sun.radius_km - orbit.radius_km).normalize();
+                let mut y_vec = z_hat.cross(&h_hat);
+                if y_vec.norm() < f64::EPSILON {
+                    // The Sun is along the orbital momentum, use the X axis of the frame instead
+                    y_vec = z_hat.cross(&Vector3::x());
+                }
+                (z_hat, y_vec.normalize())
+            }
+        };
+        let x_hat = y_hat.cross(&z_hat);
+        Ok(Matrix3::from_columns(&[x_hat, y_hat, z_hat]))
+    }
+}
+
+/// Target of an instrument, or center of a keep-out cone.
+#[derive(Clone)]
+pub enum FovTarget {
+    /// Any ephemeris of the almanac, e.g. the Moon or the Sun
+    Body(Frame),
+    /// A fixed direction given by its right ascension and declination in degrees, in the orientation of the frame of the spacecraft (e.g. a star)
+    RaDec { ra_deg: f64, dec_deg: f64 },
+    /// Another spacecraft, whose trajectory must cover the epochs searched
+    Spacecraft(Box<Traj<Spacecraft>>),
+}
+
+impl FovTarget {
+    /// Returns the vector from the spacecraft to this target in the frame of the spacecraft, in km, or the unit vector of a fixed direction.
+    fn relative_to(&self, orbit: &Orbit, almanac: &Almanac) -> Result<Vector3<f64>, EventError> {
+        match self {
+            Self::Body(frame) => {
+                let target = almanac
+                    .transform(*frame, orbit.frame, orbit.epoch, None)
+                    .context(EventAlmanacSnafu)?;
+                Ok(target.radius_km - orbit.radius_km)
+            }
+            Self::RaDec { ra_deg, dec_deg } => {
+                let (sin_ra, cos_ra) = ra_deg.to_radians().sin_cos();
+                let (sin_dec, cos_dec) = dec_deg.to_radians().sin_cos();
+                Ok(Vector3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec))
+            }
+            Self::Spacecraft(traj) => {
+                let mut target = traj.at(orbit.epoch).context(EventTrajSnafu)?.orbit;
+                if target.frame != orbit.frame {
+                    target = almanac
+                        .transform_to(target, orbit.frame, None)
+                        .context(EventAlmanacSnafu)?;
+                }
+                Ok(target.radius_km - orbit.radius_km)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FovTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Body(frame) => write!(f, "{frame:x}"),
+            Self::RaDec { ra_deg, dec_deg } => write!(f, "RA = {ra_deg} deg, Dec = {dec_deg} deg"),
+            Self::Spacecraft(traj) => match &traj.name {
+                Some(name) => write!(f, "spacecraft {name}"),
+                None => write!(f, "spacecraft"),
+            },
+        }
+    }
+}
+
+/// A cone around the direction of a target which the boresight must avoid, e.g. 10 degrees from the Sun.
+#[derive(Clone)]
+pub struct KeepOutCone {
+    pub center: FovTarget,
+    pub half_angle_deg: f64,
+}
+
+/// An event on the access of an instrument fixed on the spacecraft to a target.
+///
+/// The event evaluates to the smallest margin in degrees among all of the constraints: the target within the field of view of the instrument,
+/// the boresight out of each keep-out cone, and the line of sight to the target clear of the limb of the central body, if configured.
+/// Hence, it is positive when the target can be observed, and the arcs of `Traj::find_arcs` are the observation windows.
+#[derive(Clone)]
+pub struct FovAccessEvent {
+    /// Attitude law of the spacecraft
+    pub attitude: AttitudeLaw,
+    /// Boresight of the instrument in the body frame
+    pub boresight: Vector3<f64>,
+    /// Half angle of the field of view of the instrument, in degrees
+    pub half_angle_deg: f64,
+    pub target: FovTarget,
+    pub keep_outs: Vec<KeepOutCone>,
+    /// If set, the line of sight to the target must clear the limb of the central body of the spacecraft by this angle, in degrees
+    pub limb_margin_deg: Option<f64>,
+    pub epoch_precision: Duration,
+}
+
+impl FovAccessEvent {
+    /// Creates a new access event of the instrument with the provided boresight (in the body frame) and half angle to the target, without any keep-out cone nor limb avoidance.
+    pub fn new(
+        attitude: AttitudeLaw,
+        boresight: Vector3<f64>,
+        half_angle_deg: f64,
+        target: FovTarget,
+    ) -> Self {
+        Self {
+            attitude,
+            boresight: boresight.normalize(),
+            half_angle_deg,
+            target,
+            keep_outs: Vec::new(),
+            limb_margin_deg: None,
+            epoch_precision: Duration::from_seconds(1.0),
+        }
+    }
+
+    /// Adds a keep-out cone of the provided half angle (in degrees) around the center
+    pub fn with_keep_out(mut self, center: FovTarget, half_angle_deg: f64) -> Self {
+        self.keep_outs.push(KeepOutCone {
+            center,
+            half_angle_deg,
+        });
+        self
+    }
+
+    /// Requires the line of sight to the target to clear the limb of the central body by the provided margin, in degrees
+    pub fn with_limb_avoidance(mut self, margin_deg: f64) -> Self {
+        self.limb_margin_deg = Some(margin_deg);
+        self
+    }
+
+    /// Returns the margins of each constraint in degrees: field of view, each keep-out cone, and limb avoidance if configured.
+    pub fn margins(&self, sc: &Spacecraft, almanac: Arc<Almanac>) -> Result<Vec<f64>, EventError> {
+        let orbit = sc.orbit;
+        let boresight = self.attitude.dcm_to_inertial(&orbit, &almanac)? * self.boresight;
+        let target = self.target.relative_to(&orbit, &almanac)?;
+
+        let mut margins = Vec::with_capacity(self.keep_outs.len() + 2);
+        margins.push(self.half_angle_deg - angle_deg(&boresight, &target));
+
+        for keep_out in &self.keep_outs {
+            let center = keep_out.center.relative_to(&orbit, &almanac)?;
+            margins.push(angle_deg(&boresight, &center) - keep_out.half_angle_deg);
+        }
+
+        if let Some(limb_margin_deg) = self.limb_margin_deg {
+            let body_radius_km = orbit
+                .frame
+                .mean_equatorial_radius_km()
+                .context(EventPhysicsSnafu)?;
+            let limb_deg = (body_radius_km / orbit.rmag_km())
+                .min(1.0)
+                .asin()
+                .to_degrees();
+            margins.push(angle_deg(&-orbit.radius_km, &target) - limb_deg - limb_margin_deg);
+        }
+
+        Ok(margins)
+    }
+}
+
+/// Angle between two vectors, in degrees
+fn angle_deg(a: &Vector3<f64>, b: &Vector3<f64>) -> f64 {
+    a.cross(b).norm().atan2(a.dot(b)).to_degrees()
+}
+
+impl fmt::Display for FovAccessEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "FOV access of {} within {} deg ({} keep-out cones)",
+            self.target,
+            self.half_angle_deg,
+            self.keep_outs.len()
+        )
+    }
+}
+
+impl EventEvaluator<Spacecraft> for FovAccessEvent {
+    fn eval(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Result<f64, EventError> {
+        Ok(self
+            .margins(state, almanac)?
+            .into_iter()
+            .fold(f64::INFINITY, f64::min))
+    }
+
+    fn epoch_precision(&self) -> Duration {
+        self.epoch_precision
+    }
+
+    /// Finds the edges of the observation windows within a milli-degree
+    fn value_precision(&self) -> f64 {
+        1e-3
+    }
+
+    fn eval_string(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Result<String, EventError> {
+        let margins = self.margins(state, almanac)?;
+        Ok(format!("{self}: margins = {margins:.3?} deg"))
+    }
+}
@@ -64,10 +64,21 @@ where
         Err(DynamicsError::StateTransitionMatrixUnset)
     }
 
-    /// Return this state as a vector for the propagation/estimation
-    /// By default, this is not implemented. This function must be implemented when filtering on this state.
+    /// Resets the STM of this state to identity.
+    /// By default, this calls `set_stm` with the identity matrix.
     fn reset_stm(&mut self) {
-        unimplemented!()
+        self.set_stm(OMatrix::<f64, Self::Size, Self::Size>::identity());
+    }
+
+    /// Sets the STM of this state, e.g. to the product of the step STMs.
+    /// By default, this writes the STM (column major) after the state in the vector of this state, and sets this state from that vector.
+    /// States whose vector does not hold the STM after the state, or which only set their STM from the vector if it is enabled, must override this.
+    fn set_stm(&mut self, stm: OMatrix<f64, Self::Size, Self::Size>) {
+        let mut vector = self.to_vector();
+        for (idx, stm_val) in stm.iter().enumerate() {
+            vector[idx + Self::Size::dim()] = *stm_val;
+        }
+        self.set(self.epoch(), &vector);
     }
 
     /// Unsets the STM for this state
@@ -140,6 +151,20 @@ pub enum AstroError {
     PartialsUndefined,
     #[snafu(display("Orbit is not hyperbolic so there is no hyperbolic anomaly."))]
     NotHyperbolic,
+    #[snafu(display("Orbit is not elliptical so the mean longitude is undefined."))]
+    NotElliptical,
+    #[snafu(display(
+        "C3 must be strictly positive to define an asymptote, got {c3_km2_s2} km^2/s^2"
+    ))]
+    NonPositiveC3 { c3_km2_s2: f64 },
+    #[snafu(display("asymptote declination of {dla_deg} deg is out of reach of the parking orbit without a plane change: inclination must be between {min_inc_deg} and {max_inc_deg} deg"))]
+    InfeasibleAsymptote {
+        dla_deg: f64,
+        min_inc_deg: f64,
+        max_inc_deg: f64,
+    },
+    #[snafu(display("Kepler's equation has no solution for a mean anomaly of {ma_deg} deg and an eccentricity of {ecc}"))]
+    KeplerEquation { ma_deg: f64, ecc: f64 },
     #[snafu(display("physics error occured during astro computation: {source}"))]
     AstroPhysics { source: PhysicsError },
     #[snafu(display("ANISE Almanac error occured during astro computation: {source}"))]
@@ -153,10 +178,18 @@ pub enum AstroError {
 mod orbitdual;
 pub use self::orbitdual::*;
 
+// Re-Export the Orbit extensions
+mod orbit_ext;
+pub use self::orbit_ext::*;
+
 // Re-Export B Plane
 mod bplane;
 pub use self::bplane::*;
 
+// Re-Export the hyperbolic asymptote design
+mod asymptote;
+pub use self::asymptote::*;
+
 // Re-Export spacecraft
 mod spacecraft;
 pub use self::spacecraft::*;
@@ -164,6 +197,18 @@ pub use self::spacecraft::*;
 /// The eclipse module allows finding eclipses and (conversely) visibility between a state and another one (e.g. a planet or the Sun).
 pub mod eclipse;
 
+/// The synodic module computes synodic periods and finds the solar conjunctions and oppositions of a body as seen from an observer.
+pub mod synodic;
+
+/// The attitude module provides time-tagged attitude ephemerides, e.g. from a CCSDS AEM, and the N-plate model of the surface of a spacecraft.
+pub mod attitude;
+
+/// The rotating module provides the rotating frames of two bodies centered on their barycenter or one of their libration points, e.g. Sun-Earth L2.
+pub mod rotating;
+
+/// The fov module provides the access events of an instrument fixed on a spacecraft to a target, subject to keep-out cones and limb avoidance.
+pub mod fov;
+
 /// Speed of light in meters per second
 pub const SPEED_OF_LIGHT_M_S: f64 = SPEED_OF_LIGHT_KM_S * 1e3;
 pub use anise::constants::SPEED_OF_LIGHT_KM_S;
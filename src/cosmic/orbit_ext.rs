@@ -0,0 +1,496 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{AstroError, AstroPhysicsSnafu, Epoch, Frame, Orbit, Spacecraft};
+use crate::errors::{FromAlmanacSnafu, FromPhysicsSnafu, NyxError};
+use crate::io::tle::Tle;
+use crate::linalg::{Matrix3, Vector3, Vector6};
+use crate::propagators::kepler_universal;
+use crate::utils::between_0_360;
+use anise::almanac::Almanac;
+use anise::astro::PhysicsResult;
+use anise::constants::celestial_objects::SUN;
+use anise::constants::frames::{EARTH_J2000, MOON_J2000};
+use snafu::ResultExt;
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+
+/// Equinoctial orbital elements, which remain defined for circular and equatorial orbits, unlike the classical Keplerian elements.
+///
+/// This is the direct set: it is only singular for retrograde equatorial orbits. Reference: Broucke and Cefola, 1972.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EquinoctialElements {
+    /// Semi-major axis, in km
+    pub sma_km: f64,
+    /// e sin(ω + Ω)
+    pub h: f64,
+    /// e cos(ω + Ω)
+    pub k: f64,
+    /// tan(i/2) sin(Ω)
+    pub p: f64,
+    /// tan(i/2) cos(Ω)
+    pub q: f64,
+    /// Mean longitude M + ω + Ω, in degrees between 0 and 360
+    pub lambda_deg: f64,
+}
+
+impl EquinoctialElements {
+    /// Returns the eccentricity (no unit)
+    pub fn ecc(&self) -> f64 {
+        (self.h.powi(2) + self.k.powi(2)).sqrt()
+    }
+
+    /// Returns the inclination in degrees
+    pub fn inc_deg(&self) -> f64 {
+        (2.0 * (self.p.powi(2) + self.q.powi(2)).sqrt().atan()).to_degrees()
+    }
+
+    /// Returns the longitude of periapsis ω + Ω in degrees between 0 and 360
+    pub fn lonper_deg(&self) -> f64 {
+        between_0_360(self.h.atan2(self.k).to_degrees())
+    }
+}
+
+impl fmt::Display for EquinoctialElements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sma = {:.6} km\th = {:.9}\tk = {:.9}\tp = {:.9}\tq = {:.9}\tλ = {:.6} deg",
+            self.sma_km, self.h, self.k, self.p, self.q, self.lambda_deg
+        )
+    }
+}
+
+/// Additional orbital computations on top of those provided by ANISE's `Orbit`.
+pub trait OrbitExt {
+    /// Returns the altitude of periapsis (or perigee around Earth) above the mean equatorial radius of the frame, in kilometers.
+    fn periapsis_altitude_km(&self) -> PhysicsResult<f64>;
+
+    /// Returns the altitude of apoapsis (or apogee around Earth) above the mean equatorial radius of the frame, in kilometers.
+    fn apoapsis_altitude_km(&self) -> PhysicsResult<f64>;
+
+    /// Returns the specific mechanical energy of this orbit, in km^2/s^2.
+    fn specific_energy(&self) -> PhysicsResult<f64>;
+
+    /// Returns the time derivative of the magnitude of the radius vector, i.e. r⋅v/|r|, in km/s.
+    fn radius_rate_km_s(&self) -> f64;
+
+    /// Returns the speed on this orbit at the provided radius from the vis-viva equation, in km/s.
+    ///
+    /// Errors if the radius is not positive or cannot be reached on this orbit, e.g. beyond apoapsis.
+    fn velocity_at_radius(&self, r_km: f64) -> Result<f64, NyxError>;
+
+    /// Returns the angle between the radius vectors of this orbit and the other orbit, in degrees between 0 and 180.
+    ///
+    /// Both orbits must be expressed in the same frame, e.g. for the phasing of a chaser and a target.
+    fn angle_to(&self, other: &Self) -> Result<f64, NyxError>;
+
+    /// Returns the angle between the radius vector of this orbit and the direction of the Sun from the central body, in degrees between 0 and 180.
+    ///
+    /// This is zero above the sub-solar point and 180 degrees above the anti-solar point.
+    fn phase_angle_deg(&self, almanac: &Almanac) -> Result<f64, NyxError>;
+
+    /// Returns the fraction of the solar disk visible from this orbit, from 1.0 when fully lit to 0.0 in the umbra, and in between in the penumbra.
+    ///
+    /// This uses the same conical shadow geometry as the eclipse events, with both the Earth and the Moon as shadow bodies, and returns the darkest of both.
+    fn illumination_fraction(&self, almanac: &Almanac) -> Result<f64, NyxError>;
+
+    /// Returns the equinoctial elements of this orbit, which are continuous across circular and equatorial geometries.
+    ///
+    /// Errors if the orbit is not elliptical because the mean longitude is then undefined.
+    fn equinoctial(&self) -> Result<EquinoctialElements, AstroError>;
+
+    /// Returns a copy of this orbit whose position and velocity are both rotated by the provided direction cosine matrix.
+    ///
+    /// The time derivative of the rotation is ignored, so this is only exact for inertial rotations. The epoch and frame are unchanged:
+    /// the caller is responsible for tracking the frame in which the returned state is expressed.
+    fn rotate_position_velocity(&self, dcm: Matrix3<f64>) -> Self;
+
+    /// Builds an orbit from its right ascension and declination (in degrees), range (in km), and their rates (in degrees per second and km/s),
+    /// all expressed in the provided frame, e.g. from a topocentric observation in a station centered frame.
+    ///
+    /// This is the inverse of the `right_ascension_deg` and `declination_deg` computations.
+    #[allow(clippy::too_many_arguments)]
+    fn from_radec(
+        ra_deg: f64,
+        dec_deg: f64,
+        range_km: f64,
+        ra_dot_deg_s: f64,
+        dec_dot_deg_s: f64,
+        range_rate_km_s: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Self;
+
+    /// Builds an orbit from its Keplerian elements where the anomaly is the mean anomaly (in degrees) instead of the true anomaly, e.g. from a TLE.
+    ///
+    /// Kepler's equation is solved with a Newton-Raphson iteration, for both elliptical and hyperbolic orbits.
+    /// Errors if the orbit is parabolic, or if the frame does not have a gravitational parameter.
+    #[allow(clippy::too_many_arguments)]
+    fn keplerian_mean(
+        sma_km: f64,
+        ecc: f64,
+        inc_deg: f64,
+        raan_deg: f64,
+        aop_deg: f64,
+        ma_deg: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Result<Self, AstroError>;
+
+    /// Returns this orbit propagated to the provided epoch (before or after the epoch of this orbit) in two-body dynamics.
+    ///
+    /// Kepler's problem is solved with universal variables, like the [crate::propagators::AnalyticPropagator], so all conic types are
+    /// supported, including circular and equatorial orbits. Errors if the universal anomaly does not converge, or if the frame does
+    /// not have a gravitational parameter.
+    fn two_body_at(&self, epoch: Epoch) -> Result<Self, NyxError>
+    where
+        Self: Sized;
+
+    /// Builds an orbit from a two line element set (TLE) at its epoch, in the EME2000 frame of the almanac.
+    ///
+    /// The TLE is propagated with SGP4 and its TEME state is rotated into EME2000, cf. [crate::io::tle::Tle] to propagate it to other epochs.
+    fn from_tle(line1: &str, line2: &str, almanac: &Almanac) -> Result<Self, NyxError>;
+
+    /// Returns a spacecraft of this orbit with its STM set to identity, which enables the computation of the STM when propagated.
+    ///
+    /// ANISE's `Orbit` cannot store an STM, so there are no STM accessors on the orbit itself: orbit-only studies propagate this
+    /// spacecraft with its default parameters and fetch the position and velocity block of its STM with `Spacecraft::orbit_stm`.
+    fn to_spacecraft_with_stm(&self) -> Spacecraft;
+
+    /// Returns the position (km) and velocity (km/s) of this orbit as a six-vector, e.g. to exchange states with external tools.
+    ///
+    /// This is the same as ANISE's `to_cartesian_pos_vel`.
+    fn position_velocity_vector(&self) -> Vector6<f64>;
+
+    /// Builds an orbit from a six-vector of its position (km) and velocity (km/s), the inverse of `position_velocity_vector`.
+    fn from_position_velocity(pos_vel: Vector6<f64>, epoch: Epoch, frame: Frame) -> Self;
+
+    /// Returns whether this orbit and the other orbit are in the same frame and each of their Cartesian components match within the tolerances.
+    ///
+    /// Orbits in different frames are never equal, even if their components match. The frames are compared by their ephemeris and orientation IDs
+    /// only, so that the same frame with or without its gravitational parameter matches. The epochs are not compared.
+    fn approx_eq(&self, other: &Self, pos_tol_km: f64, vel_tol_km_s: f64) -> bool;
+}
+
+impl OrbitExt for Orbit {
+    fn periapsis_altitude_km(&self) -> PhysicsResult<f64> {
+        Ok(self.periapsis_km()? - self.frame.mean_equatorial_radius_km()?)
+    }
+
+    fn apoapsis_altitude_km(&self) -> PhysicsResult<f64> {
+        Ok(self.apoapsis_km()? - self.frame.mean_equatorial_radius_km()?)
+    }
+
+    fn specific_energy(&self) -> PhysicsResult<f64> {
+        Ok(0.5 * self.vmag_km_s().powi(2) - self.frame.mu_km3_s2()? / self.rmag_km())
+    }
+
+    fn radius_rate_km_s(&self) -> f64 {
+        self.radius_km.dot(&self.velocity_km_s) / self.rmag_km()
+    }
+
+    fn velocity_at_radius(&self, r_km: f64) -> Result<f64, NyxError> {
+        if r_km <= 0.0 {
+            return Err(NyxError::MathDomain {
+                msg: format!("radius must be strictly positive, got {r_km} km"),
+            });
+        }
+        let mu_km3_s2 = self.frame.mu_km3_s2().context(FromPhysicsSnafu {
+            action: "vis-viva requires the gravitational parameter",
+        })?;
+        let energy = self.specific_energy().context(FromPhysicsSnafu {
+            action: "computing specific energy",
+        })?;
+
+        let v_sq = 2.0 * (energy + mu_km3_s2 / r_km);
+        if v_sq < 0.0 {
+            return Err(NyxError::MathDomain {
+                msg: format!("radius of {r_km} km is beyond the apoapsis of this orbit"),
+            });
+        }
+        Ok(v_sq.sqrt())
+    }
+
+    fn angle_to(&self, other: &Self) -> Result<f64, NyxError> {
+        if self.frame.ephemeris_id != other.frame.ephemeris_id
+            || self.frame.orientation_id != other.frame.orientation_id
+        {
+            return Err(NyxError::CustomError {
+                msg: format!(
+                    "angle between orbits requires the same frame, got {} and {}",
+                    self.frame, other.frame
+                ),
+            });
+        }
+        Ok(angle_between_deg(&self.radius_km, &other.radius_km))
+    }
+
+    fn phase_angle_deg(&self, almanac: &Almanac) -> Result<f64, NyxError> {
+        let sun = almanac
+            .transform(self.frame.with_ephem(SUN), self.frame, self.epoch, None)
+            .context(FromAlmanacSnafu {
+                action: "computing the Sun direction for the phase angle",
+            })?;
+        Ok(angle_between_deg(&self.radius_km, &sun.radius_km))
+    }
+
+    fn illumination_fraction(&self, almanac: &Almanac) -> Result<f64, NyxError> {
+        let mut illumination: f64 = 1.0;
+        for shadow_body in [EARTH_J2000, MOON_J2000] {
+            let shadow_frame = almanac
+                .frame_from_uid(shadow_body)
+                .context(FromAlmanacSnafu {
+                    action: "fetching the shadow body for the illumination fraction",
+                })?;
+            let occultation =
+                almanac
+                    .solar_eclipsing(shadow_frame, *self, None)
+                    .context(FromAlmanacSnafu {
+                        action: "computing the occultation for the illumination fraction",
+                    })?;
+            illumination = illumination.min(1.0 - occultation.factor());
+        }
+        Ok(illumination)
+    }
+
+    fn equinoctial(&self) -> Result<EquinoctialElements, AstroError> {
+        let mu_km3_s2 = self.frame.mu_km3_s2().context(AstroPhysicsSnafu)?;
+        let sma_km = self.sma_km().context(AstroPhysicsSnafu)?;
+        if sma_km <= 0.0 || self.ecc().context(AstroPhysicsSnafu)? >= 1.0 {
+            return Err(AstroError::NotElliptical);
+        }
+
+        let r = self.radius_km;
+        let v = self.velocity_km_s;
+        let hvec = r.cross(&v);
+        let hmag = hvec.norm();
+        let p = hvec.x / (hmag + hvec.z);
+        let q = -hvec.y / (hmag + hvec.z);
+
+        // In-plane basis of the equinoctial frame, f pointing to the zero true longitude
+        let denom = 1.0 + p.powi(2) + q.powi(2);
+        let f_hat = Vector3::new(1.0 - p.powi(2) + q.powi(2), 2.0 * p * q, -2.0 * p) / denom;
+        let g_hat = Vector3::new(2.0 * p * q, 1.0 + p.powi(2) - q.powi(2), 2.0 * q) / denom;
+
+        let evec = ((v.norm_squared() - mu_km3_s2 / r.norm()) * r - r.dot(&v) * v) / mu_km3_s2;
+        let h = evec.dot(&g_hat);
+        let k = evec.dot(&f_hat);
+
+        // Mean longitude through the eccentric longitude, from GMAT's CartesianToEquinoctial
+        let x1 = r.dot(&f_hat);
+        let y1 = r.dot(&g_hat);
+        let root = (1.0 - h.powi(2) - k.powi(2)).sqrt();
+        let beta = 1.0 / (1.0 + root);
+        let sin_f = h + ((1.0 - h.powi(2) * beta) * y1 - h * k * beta * x1) / (sma_km * root);
+        let cos_f = k + ((1.0 - k.powi(2) * beta) * x1 - h * k * beta * y1) / (sma_km * root);
+        let ecc_lon = sin_f.atan2(cos_f);
+
+        Ok(EquinoctialElements {
+            sma_km,
+            h,
+            k,
+            p,
+            q,
+            lambda_deg: between_0_360((ecc_lon + h * cos_f - k * sin_f).to_degrees()),
+        })
+    }
+
+    fn rotate_position_velocity(&self, dcm: Matrix3<f64>) -> Self {
+        let mut rotated = *self;
+        rotated.radius_km = dcm * self.radius_km;
+        rotated.velocity_km_s = dcm * self.velocity_km_s;
+        rotated
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn from_radec(
+        ra_deg: f64,
+        dec_deg: f64,
+        range_km: f64,
+        ra_dot_deg_s: f64,
+        dec_dot_deg_s: f64,
+        range_rate_km_s: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Self {
+        let (sin_ra, cos_ra) = ra_deg.to_radians().sin_cos();
+        let (sin_dec, cos_dec) = dec_deg.to_radians().sin_cos();
+
+        // Line of sight unit vector and its partials with respect to the RA and Dec angles
+        let los = Vector3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec);
+        let dlos_dra = Vector3::new(-cos_dec * sin_ra, cos_dec * cos_ra, 0.0);
+        let dlos_ddec = Vector3::new(-sin_dec * cos_ra, -sin_dec * sin_ra, cos_dec);
+
+        let radius_km = range_km * los;
+        let velocity_km_s = range_rate_km_s * los
+            + range_km
+                * (ra_dot_deg_s.to_radians() * dlos_dra + dec_dot_deg_s.to_radians() * dlos_ddec);
+
+        Orbit::new(
+            radius_km.x,
+            radius_km.y,
+            radius_km.z,
+            velocity_km_s.x,
+            velocity_km_s.y,
+            velocity_km_s.z,
+            epoch,
+            frame,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn keplerian_mean(
+        sma_km: f64,
+        ecc: f64,
+        inc_deg: f64,
+        raan_deg: f64,
+        aop_deg: f64,
+        ma_deg: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Result<Self, AstroError> {
+        let ta_rad = true_anomaly_from_mean(ma_deg.to_radians(), ecc)
+            .ok_or(AstroError::KeplerEquation { ma_deg, ecc })?;
+
+        Orbit::try_keplerian(
+            sma_km,
+            ecc,
+            inc_deg,
+            raan_deg,
+            aop_deg,
+            ta_rad.to_degrees(),
+            epoch,
+            frame,
+        )
+        .context(AstroPhysicsSnafu)
+    }
+
+    fn two_body_at(&self, epoch: Epoch) -> Result<Self, NyxError> {
+        let mu_km3_s2 = self.frame.mu_km3_s2().context(FromPhysicsSnafu {
+            action: "two-body propagation requires the gravitational parameter",
+        })?;
+        let dt_s = (epoch - self.epoch).to_seconds();
+
+        let (radius_km, velocity_km_s) =
+            kepler_universal(self.radius_km, self.velocity_km_s, dt_s, mu_km3_s2).ok_or_else(
+                || NyxError::MaxIterReached {
+                    msg: format!("universal anomaly did not converge when propagating to {epoch}"),
+                },
+            )?;
+
+        let mut orbit = *self;
+        orbit.epoch = epoch;
+        orbit.radius_km = radius_km;
+        orbit.velocity_km_s = velocity_km_s;
+        Ok(orbit)
+    }
+
+    fn from_tle(line1: &str, line2: &str, almanac: &Almanac) -> Result<Self, NyxError> {
+        let tle = Tle::from_lines(line1, line2)?;
+        let eme2k = almanac
+            .frame_from_uid(EARTH_J2000)
+            .map_err(|e| NyxError::CustomError {
+                msg: format!("fetching EME2000 for the TLE: {e}"),
+            })?;
+
+        Ok(tle.orbit_at(tle.epoch, eme2k)?)
+    }
+
+    fn to_spacecraft_with_stm(&self) -> Spacecraft {
+        Spacecraft::from(*self).with_stm()
+    }
+
+    fn position_velocity_vector(&self) -> Vector6<f64> {
+        self.to_cartesian_pos_vel()
+    }
+
+    fn from_position_velocity(pos_vel: Vector6<f64>, epoch: Epoch, frame: Frame) -> Self {
+        Self::from_cartesian_pos_vel(pos_vel, epoch, frame)
+    }
+
+    fn approx_eq(&self, other: &Self, pos_tol_km: f64, vel_tol_km_s: f64) -> bool {
+        if self.frame.ephemeris_id != other.frame.ephemeris_id
+            || self.frame.orientation_id != other.frame.orientation_id
+        {
+            return false;
+        }
+
+        (self.radius_km - other.radius_km)
+            .iter()
+            .all(|delta| delta.abs() <= pos_tol_km)
+            && (self.velocity_km_s - other.velocity_km_s)
+                .iter()
+                .all(|delta| delta.abs() <= vel_tol_km_s)
+    }
+}
+
+/// Solves Kepler's equation for the true anomaly in radians, given the mean anomaly in radians.
+fn true_anomaly_from_mean(ma_rad: f64, ecc: f64) -> Option<f64> {
+    if ecc < 1.0 {
+        let ea = eccentric_anomaly_from_mean(ma_rad, ecc)?;
+        let (sin_half, cos_half) = (ea / 2.0).sin_cos();
+        Some(2.0 * ((1.0 + ecc).sqrt() * sin_half).atan2((1.0 - ecc).sqrt() * cos_half))
+    } else if ecc > 1.0 {
+        let ha = hyperbolic_anomaly_from_mean(ma_rad, ecc)?;
+        Some(2.0 * (((ecc + 1.0) / (ecc - 1.0)).sqrt() * (ha / 2.0).tanh()).atan())
+    } else {
+        None
+    }
+}
+
+/// Solves Kepler's equation for the eccentric anomaly in radians, on the same revolution as the provided mean anomaly in radians.
+fn eccentric_anomaly_from_mean(ma_rad: f64, ecc: f64) -> Option<f64> {
+    const MAX_ITER: usize = 100;
+    const TOL: f64 = 1e-14;
+    // Solve within [-pi; pi] and add the revolutions back
+    let wrapped_rad = (ma_rad + PI).rem_euclid(TAU) - PI;
+    let mut ea = if ecc > 0.8 {
+        PI.copysign(wrapped_rad)
+    } else {
+        wrapped_rad
+    };
+    for _ in 0..MAX_ITER {
+        let delta = (ea - ecc * ea.sin() - wrapped_rad) / (1.0 - ecc * ea.cos());
+        ea -= delta;
+        if delta.abs() < TOL {
+            return Some(ea + (ma_rad - wrapped_rad));
+        }
+    }
+    None
+}
+
+/// Solves the hyperbolic Kepler's equation for the hyperbolic anomaly in radians, given the mean anomaly in radians.
+fn hyperbolic_anomaly_from_mean(ma_rad: f64, ecc: f64) -> Option<f64> {
+    const MAX_ITER: usize = 100;
+    const TOL: f64 = 1e-14;
+    let mut ha = (ma_rad / ecc).asinh();
+    for _ in 0..MAX_ITER {
+        let delta = (ecc * ha.sinh() - ha - ma_rad) / (ecc * ha.cosh() - 1.0);
+        ha -= delta;
+        if delta.abs() < TOL {
+            return Some(ha);
+        }
+    }
+    None
+}
+
+/// Angle between two vectors in degrees, computed with atan2 to remain accurate for nearly (anti-)parallel vectors.
+fn angle_between_deg(a: &Vector3<f64>, b: &Vector3<f64>) -> f64 {
+    a.cross(b).norm().atan2(a.dot(b)).to_degrees()
+}
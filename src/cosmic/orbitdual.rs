@@ -26,6 +26,7 @@ use crate::cosmic::AstroPhysicsSnafu;
 use crate::linalg::{Vector3, U7};
 use crate::md::StateParameter;
 use crate::time::Epoch;
+use crate::utils::between_pm_180;
 use crate::TimeTagged;
 use hyperdual::linalg::norm;
 use hyperdual::{Float, OHyperdual};
@@ -56,6 +57,9 @@ pub struct OrbitDual {
     pub dt: Epoch,
     /// Frame contains everything we need to compute state information
     pub frame: Frame,
+    /// Set to compute the argument of periapsis and the right ascension of the ascending node of degenerate (circular or equatorial)
+    /// orbits from the equinoctial elements instead of defaulting them to zero.
+    pub equinoctial_fallback: bool,
 }
 
 impl From<Orbit> for OrbitDual {
@@ -70,6 +74,7 @@ impl From<Orbit> for OrbitDual {
             vz: OHyperdual::from_slice(&[orbit.velocity_km_s.z, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]),
             dt: orbit.epoch,
             frame: orbit.frame,
+            equinoctial_fallback: false,
         }
     }
 }
@@ -119,6 +124,16 @@ impl fmt::Display for OrbitPartial {
 }
 
 impl OrbitDual {
+    /// Returns a copy of this orbit whose AoP and RAAN fall back to the non-singular equinoctial elements when the geometry is degenerate.
+    ///
+    /// The RAAN of an equatorial orbit is then the angle of the (p, q) vector, and the AoP of a circular orbit is the angle of the (h, k) vector minus that RAAN,
+    /// such that both remain consistent with the true longitude across the singularity.
+    /// For retrograde equatorial orbits, where the equinoctial elements are singular, the RAAN is zero and the AoP is measured from the X axis.
+    pub fn with_equinoctial_fallback(mut self) -> Self {
+        self.equinoctial_fallback = true;
+        self
+    }
+
     pub fn partial_for(&self, param: StateParameter) -> Result<OrbitPartial, AstroError> {
         match param {
             StateParameter::X => Ok(OrbitPartial {
@@ -146,6 +161,7 @@ impl OrbitDual {
                 param: StateParameter::VZ,
             }),
             StateParameter::Rmag => Ok(self.rmag_km()),
+            StateParameter::RangeRate => Ok(self.radius_rate_km_s()),
             StateParameter::Vmag => Ok(self.vmag_km_s()),
             StateParameter::HX => Ok(self.hx()),
             StateParameter::HY => Ok(self.hy()),
@@ -158,8 +174,18 @@ impl OrbitDual {
             StateParameter::AoP => Ok(self.aop_deg().context(AstroPhysicsSnafu)?),
             StateParameter::AoL => Ok(self.aol_deg().context(AstroPhysicsSnafu)?),
             StateParameter::RAAN => Ok(self.raan_deg()),
-            StateParameter::Periapsis => Ok(self.periapsis_km().context(AstroPhysicsSnafu)?),
-            StateParameter::Apoapsis => Ok(self.apoapsis_km().context(AstroPhysicsSnafu)?),
+            StateParameter::Periapsis | StateParameter::PeriapsisRadius => {
+                Ok(self.periapsis_km().context(AstroPhysicsSnafu)?)
+            }
+            StateParameter::Apoapsis | StateParameter::ApoapsisRadius => {
+                Ok(self.apoapsis_km().context(AstroPhysicsSnafu)?)
+            }
+            StateParameter::PeriapsisAltitude => {
+                Ok(self.periapsis_altitude_km().context(AstroPhysicsSnafu)?)
+            }
+            StateParameter::ApoapsisAltitude => {
+                Ok(self.apoapsis_altitude_km().context(AstroPhysicsSnafu)?)
+            }
             StateParameter::TrueLongitude => Ok(self.tlong_deg().context(AstroPhysicsSnafu)?),
             StateParameter::FlightPathAngle => Ok(self.fpa_deg().context(AstroPhysicsSnafu)?),
             StateParameter::MeanAnomaly => Ok(self.ma_deg().context(AstroPhysicsSnafu)?),
@@ -178,6 +204,11 @@ impl OrbitDual {
                 Ok(self.semi_minor_axis_km().context(AstroPhysicsSnafu)?)
             }
             StateParameter::TrueAnomaly => Ok(self.ta_deg().context(AstroPhysicsSnafu)?),
+            StateParameter::EquinoctialH => Ok(self.equinoctial_h().context(AstroPhysicsSnafu)?),
+            StateParameter::EquinoctialK => Ok(self.equinoctial_k().context(AstroPhysicsSnafu)?),
+            StateParameter::EquinoctialP => Ok(self.equinoctial_p()),
+            StateParameter::EquinoctialQ => Ok(self.equinoctial_q()),
+            StateParameter::MeanLongitude => self.mean_longitude_deg(),
             _ => Err(AstroError::PartialsUndefined),
         }
     }
@@ -190,6 +221,14 @@ impl OrbitDual {
         }
     }
 
+    /// Returns the time derivative of the magnitude of the radius vector, i.e. r⋅v/|r|, in km/s
+    pub fn radius_rate_km_s(&self) -> OrbitPartial {
+        OrbitPartial {
+            param: StateParameter::RangeRate,
+            dual: self.radius().dot(&self.velocity()) / self.rmag_km().dual,
+        }
+    }
+
     /// Returns the magnitude of the velocity vector in km/s
     pub fn vmag_km_s(&self) -> OrbitPartial {
         OrbitPartial {
@@ -286,9 +325,21 @@ impl OrbitDual {
     }
 
     /// Returns the inclination in degrees
+    ///
+    /// Computed as atan2(sqrt(hx^2 + hy^2), hz), which remains accurate close to both prograde and retrograde equatorial orbits where acos(hz / hmag) loses precision.
+    /// The inclination of an exactly equatorial orbit (0 or 180 degrees) is not differentiable: its partials are then zero, which matches central finite differences.
     pub fn inc_deg(&self) -> OrbitPartial {
+        let hvec = self.hvec();
+        let h_perp_sq = hvec[0].powi(2) + hvec[1].powi(2);
+        let inc = if h_perp_sq.real() > 0.0 {
+            h_perp_sq.sqrt().atan2(hvec[2])
+        } else if hvec[2].real() < 0.0 {
+            OHyperdual::from(PI)
+        } else {
+            OHyperdual::from(0.0)
+        };
         OrbitPartial {
-            dual: (self.hvec()[(2, 0)] / self.hmag().dual).acos().to_degrees(),
+            dual: inc.to_degrees(),
             param: StateParameter::Inclination,
         }
     }
@@ -302,7 +353,26 @@ impl OrbitDual {
         )
         .cross(&self.hvec());
         let aop = (n.dot(&self.evec()?) / (norm(&n) * self.ecc()?.dual)).acos();
-        if aop.is_nan() {
+        if aop.is_nan() && self.equinoctial_fallback && self.hz().real() < 0.0 {
+            // Retrograde equatorial orbit: the equinoctial elements are singular, and the RAAN is zero,
+            // so the AoP is measured from the X axis in the (clockwise) direction of motion.
+            let evec = self.evec()?;
+            Ok(OrbitPartial {
+                dual: wrap_dual_deg((-evec[1]).atan2(evec[0]).to_degrees()),
+                param: StateParameter::AoP,
+            })
+        } else if aop.is_nan() && self.equinoctial_fallback {
+            // Longitude of periapsis from the equinoctial elements, minus the (possibly also degenerate) RAAN
+            let lonper = self
+                .equinoctial_h()?
+                .dual
+                .atan2(self.equinoctial_k()?.dual)
+                .to_degrees();
+            Ok(OrbitPartial {
+                dual: wrap_dual_deg(lonper - self.raan_deg().dual),
+                param: StateParameter::AoP,
+            })
+        } else if aop.is_nan() {
             warn!("AoP is NaN");
             Ok(OrbitPartial {
                 dual: OHyperdual::from(0.0),
@@ -330,7 +400,23 @@ impl OrbitDual {
         )
         .cross(&self.hvec());
         let raan = (n[(0, 0)] / norm(&n)).acos();
-        if raan.is_nan() {
+        if raan.is_nan() && self.equinoctial_fallback && self.hz().real() < 0.0 {
+            // Retrograde equatorial orbit: the equinoctial elements are singular, so the RAAN is set to zero.
+            OrbitPartial {
+                dual: OHyperdual::from(0.0),
+                param: StateParameter::RAAN,
+            }
+        } else if raan.is_nan() && self.equinoctial_fallback {
+            OrbitPartial {
+                dual: wrap_dual_deg(
+                    self.equinoctial_p()
+                        .dual
+                        .atan2(self.equinoctial_q().dual)
+                        .to_degrees(),
+                ),
+                param: StateParameter::RAAN,
+            }
+        } else if raan.is_nan() {
             warn!("RAAN is NaN");
             OrbitPartial {
                 dual: OHyperdual::from(0.0),
@@ -436,6 +522,24 @@ impl OrbitDual {
         })
     }
 
+    /// Returns the altitude of periapsis above the mean equatorial radius of the frame, in kilometers.
+    pub fn periapsis_altitude_km(&self) -> PhysicsResult<OrbitPartial> {
+        Ok(OrbitPartial {
+            dual: self.periapsis_km()?.dual
+                - OHyperdual::from(self.frame.mean_equatorial_radius_km()?),
+            param: StateParameter::PeriapsisAltitude,
+        })
+    }
+
+    /// Returns the altitude of apoapsis above the mean equatorial radius of the frame, in kilometers.
+    pub fn apoapsis_altitude_km(&self) -> PhysicsResult<OrbitPartial> {
+        Ok(OrbitPartial {
+            dual: self.apoapsis_km()?.dual
+                - OHyperdual::from(self.frame.mean_equatorial_radius_km()?),
+            param: StateParameter::ApoapsisAltitude,
+        })
+    }
+
     /// Returns the eccentric anomaly in degrees
     ///
     /// This is a conversion from GMAT's StateConversionUtil::TrueToEccentricAnomaly
@@ -633,6 +737,88 @@ impl OrbitDual {
         })
     }
 
+    /// Returns the unit vectors (f, g) of the equinoctial frame, both in the orbital plane, where f is the direction of zero true longitude.
+    fn equinoctial_basis(&self) -> (Vector3<OHyperdual<f64, U7>>, Vector3<OHyperdual<f64, U7>>) {
+        let p = self.equinoctial_p().dual;
+        let q = self.equinoctial_q().dual;
+        let one = OHyperdual::from(1.0);
+        let two = OHyperdual::from(2.0);
+        let denom = one + p.powi(2) + q.powi(2);
+        let f = Vector3::new(
+            (one - p.powi(2) + q.powi(2)) / denom,
+            two * p * q / denom,
+            -two * p / denom,
+        );
+        let g = Vector3::new(
+            two * p * q / denom,
+            (one + p.powi(2) - q.powi(2)) / denom,
+            two * q / denom,
+        );
+        (f, g)
+    }
+
+    /// Returns the equinoctial h element, i.e. e sin(ω + Ω) (no unit)
+    pub fn equinoctial_h(&self) -> PhysicsResult<OrbitPartial> {
+        let (_, g) = self.equinoctial_basis();
+        Ok(OrbitPartial {
+            dual: self.evec()?.dot(&g),
+            param: StateParameter::EquinoctialH,
+        })
+    }
+
+    /// Returns the equinoctial k element, i.e. e cos(ω + Ω) (no unit)
+    pub fn equinoctial_k(&self) -> PhysicsResult<OrbitPartial> {
+        let (f, _) = self.equinoctial_basis();
+        Ok(OrbitPartial {
+            dual: self.evec()?.dot(&f),
+            param: StateParameter::EquinoctialK,
+        })
+    }
+
+    /// Returns the equinoctial p element, i.e. tan(i/2) sin(Ω) (no unit). This is singular for retrograde equatorial orbits.
+    pub fn equinoctial_p(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: self.hx().dual / (self.hmag().dual + self.hz().dual),
+            param: StateParameter::EquinoctialP,
+        }
+    }
+
+    /// Returns the equinoctial q element, i.e. tan(i/2) cos(Ω) (no unit). This is singular for retrograde equatorial orbits.
+    pub fn equinoctial_q(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: -self.hy().dual / (self.hmag().dual + self.hz().dual),
+            param: StateParameter::EquinoctialQ,
+        }
+    }
+
+    /// Returns the mean longitude in degrees, i.e. M + ω + Ω, only defined for elliptical orbits. The value is not wrapped.
+    ///
+    /// Algorithm from GMAT's CartesianToEquinoctial, computed through the eccentric longitude.
+    pub fn mean_longitude_deg(&self) -> Result<OrbitPartial, AstroError> {
+        let ecc = self.ecc().context(AstroPhysicsSnafu)?;
+        if ecc.real() >= 1.0 {
+            return Err(AstroError::NotElliptical);
+        }
+        let h = self.equinoctial_h().context(AstroPhysicsSnafu)?.dual;
+        let k = self.equinoctial_k().context(AstroPhysicsSnafu)?.dual;
+        let sma = self.sma_km().context(AstroPhysicsSnafu)?.dual;
+        let (f, g) = self.equinoctial_basis();
+        let x1 = self.radius().dot(&f);
+        let y1 = self.radius().dot(&g);
+
+        let one = OHyperdual::from(1.0);
+        let root = (one - h.powi(2) - k.powi(2)).sqrt();
+        let beta = one / (one + root);
+        let sin_f = h + ((one - h.powi(2) * beta) * y1 - h * k * beta * x1) / (sma * root);
+        let cos_f = k + ((one - k.powi(2) * beta) * x1 - h * k * beta * y1) / (sma * root);
+        let ecc_lon = sin_f.atan2(cos_f);
+
+        Ok(OrbitPartial {
+            dual: (ecc_lon + h * cos_f - k * sin_f).to_degrees(),
+            param: StateParameter::MeanLongitude,
+        })
+    }
+
     /// Returns the hyperbolic anomaly in degrees between 0 and 360.0
     pub fn hyperbolic_anomaly_deg(&self) -> Result<OrbitPartial, AstroError> {
         let ecc = self.ecc().context(AstroPhysicsSnafu)?;
@@ -655,6 +841,17 @@ impl OrbitDual {
     }
 }
 
+/// Wraps an angle in degrees between 0 and 360.0 without altering its partials
+fn wrap_dual_deg(mut angle: OHyperdual<f64, U7>) -> OHyperdual<f64, U7> {
+    while angle.real() < 0.0 {
+        angle += OHyperdual::from(360.0);
+    }
+    while angle.real() >= 360.0 {
+        angle = angle - OHyperdual::from(360.0);
+    }
+    angle
+}
+
 impl TimeTagged for OrbitDual {
     fn epoch(&self) -> Epoch {
         self.dt
@@ -664,3 +861,45 @@ impl TimeTagged for OrbitDual {
         self.dt = epoch
     }
 }
+
+/// Validates the hyperdual partials of the provided parameter against central finite differences of its value.
+///
+/// Each of the six Cartesian components of the orbit is perturbed by +/- `step` (in km or km/s), and the resulting difference quotient
+/// is compared to the corresponding partial from [`OrbitDual::partial_for`]. The error of each component is relative to the magnitude of the
+/// analytical partial, or absolute if that magnitude is below one. Differences of angles are wrapped to +/- 180 degrees.
+///
+/// Returns the largest error across all six components, or an error if the parameter is not supported for this orbit.
+pub fn check_partials(orbit: Orbit, param: StateParameter, step: f64) -> Result<f64, AstroError> {
+    let analytic = OrbitDual::from(orbit).partial_for(param)?;
+    let expected = [
+        analytic.wtr_x(),
+        analytic.wtr_y(),
+        analytic.wtr_z(),
+        analytic.wtr_vx(),
+        analytic.wtr_vy(),
+        analytic.wtr_vz(),
+    ];
+
+    let value_at = |component: usize, delta: f64| -> Result<f64, AstroError> {
+        let mut perturbed = orbit;
+        if component < 3 {
+            perturbed.radius_km[component] += delta;
+        } else {
+            perturbed.velocity_km_s[component - 3] += delta;
+        }
+        Ok(OrbitDual::from(perturbed).partial_for(param)?.real())
+    };
+
+    let mut max_err = 0.0_f64;
+    for (component, partial) in expected.iter().enumerate() {
+        let mut diff = value_at(component, step)? - value_at(component, -step)?;
+        if param.unit() == "deg" {
+            diff = between_pm_180(diff);
+        }
+        let numerical = diff / (2.0 * step);
+        let err = (numerical - partial).abs() / partial.abs().max(1.0);
+        max_err = max_err.max(err);
+    }
+
+    Ok(max_err)
+}
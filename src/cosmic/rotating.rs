@@ -0,0 +1,223 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::almanac::Almanac;
+use anise::constants::frames::{EARTH_J2000, SUN_J2000};
+use snafu::ResultExt;
+use std::fmt;
+
+use super::{Frame, Orbit, Spacecraft};
+use crate::dynamics::{Cr3bp, LibrationPoint};
+use crate::errors::{FromAlmanacSnafu, NyxError};
+use crate::linalg::{Matrix3, Vector3, Vector6};
+use crate::md::prelude::Traj;
+use crate::time::Epoch;
+use crate::State;
+
+/// Origin of a rotating frame of two bodies
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RotatingOrigin {
+    /// Barycenter of the primary and the secondary
+    Barycenter,
+    /// One of the libration points of the primary and the secondary
+    Libration(LibrationPoint),
+}
+
+/// A rotating frame built from the instantaneous geometry of a secondary body relative to a primary body, e.g. the Sun-Earth L2 frame.
+///
+/// The X axis points from the primary to the secondary, the Z axis is along the angular momentum of the secondary, and the Y axis completes the frame.
+/// The origin is at the barycenter or at a libration point, whose position is scaled with the instantaneous distance between both bodies
+/// (the collinear points are found from the quintic equations, cf. [Cr3bp::lagrange_point]).
+///
+/// States in this frame are dimensional: positions in km and velocities in km/s relative to the rotating axes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RotatingFrame {
+    pub primary: Frame,
+    pub secondary: Frame,
+    pub origin: RotatingOrigin,
+}
+
+/// Instantaneous geometry of a rotating frame, expressed in the primary frame.
+struct RotatingGeometry {
+    /// DCM from the rotating axes to the axes of the primary frame
+    dcm: Matrix3<f64>,
+    /// Angular velocity of the rotating axes, in rad/s, expressed in the rotating axes
+    omega_rad_s: Vector3<f64>,
+    /// Position of the origin relative to the primary, in km, in the rotating axes
+    origin_km: Vector3<f64>,
+    /// Rate of change of that position in the rotating axes due to the pulsation of the frame, in km/s
+    origin_rate_km_s: Vector3<f64>,
+}
+
+impl RotatingFrame {
+    pub fn new(primary: Frame, secondary: Frame, origin: RotatingOrigin) -> Self {
+        Self {
+            primary,
+            secondary,
+            origin,
+        }
+    }
+
+    /// The Sun-Earth rotating frame centered on L2
+    pub fn sun_earth_l2() -> Self {
+        Self::new(
+            SUN_J2000,
+            EARTH_J2000,
+            RotatingOrigin::Libration(LibrationPoint::L2),
+        )
+    }
+
+    fn geometry(&self, epoch: Epoch, almanac: &Almanac) -> Result<RotatingGeometry, NyxError> {
+        let secondary = almanac
+            .transform(self.secondary, self.primary, epoch, None)
+            .context(FromAlmanacSnafu {
+                action: "computing the geometry of the rotating frame",
+            })?;
+
+        let mut gms = [0.0; 2];
+        for (ii, frame) in [self.primary, self.secondary].iter().enumerate() {
+            gms[ii] = almanac
+                .frame_from_uid(*frame)
+                .map_err(|e| NyxError::CustomError {
+                    msg: format!("fetching the gravitational parameter of {frame}: {e}"),
+                })?
+                .mu_km3_s2()
+                .map_err(|e| NyxError::CustomError {
+                    msg: format!("fetching the gravitational parameter of {frame}: {e}"),
+                })?;
+        }
+
+        let r_km = secondary.radius_km;
+        let v_km_s = secondary.velocity_km_s;
+        let h = r_km.cross(&v_km_s);
+        let distance_km = r_km.norm();
+        let distance_rate_km_s = r_km.dot(&v_km_s) / distance_km;
+
+        let x_hat = r_km / distance_km;
+        let z_hat = h / h.norm();
+        let y_hat = z_hat.cross(&x_hat);
+
+        let system = Cr3bp::new(gms[0], gms[1], distance_km);
+        // Normalized position of the origin relative to the primary, which is at (-μ, 0, 0) in the CR3BP frame
+        let origin_nd = match self.origin {
+            RotatingOrigin::Barycenter => Vector3::zeros(),
+            RotatingOrigin::Libration(point) => system.lagrange_point(point),
+        } + Vector3::new(system.mu, 0.0, 0.0);
+
+        Ok(RotatingGeometry {
+            dcm: Matrix3::from_columns(&[x_hat, y_hat, z_hat]),
+            omega_rad_s: Vector3::new(0.0, 0.0, h.norm() / distance_km.powi(2)),
+            origin_km: distance_km * origin_nd,
+            origin_rate_km_s: distance_rate_km_s * origin_nd,
+        })
+    }
+
+    /// Returns the state of the provided orbit in this rotating frame: position (km) and velocity (km/s) relative to the origin, in the rotating axes.
+    pub fn to_rotating(&self, orbit: Orbit, almanac: &Almanac) -> Result<Vector6<f64>, NyxError> {
+        let orbit = almanac
+            .transform_to(orbit, self.primary, None)
+            .context(FromAlmanacSnafu {
+                action: "converting the orbit into the primary frame of the rotating frame",
+            })?;
+        let geom = self.geometry(orbit.epoch, almanac)?;
+
+        let r_rot_km = geom.dcm.transpose() * orbit.radius_km;
+        let v_rot_km_s = geom.dcm.transpose() * orbit.velocity_km_s
+            - geom.omega_rad_s.cross(&r_rot_km)
+            - geom.origin_rate_km_s;
+        let rho_km = r_rot_km - geom.origin_km;
+
+        Ok(Vector6::new(
+            rho_km.x,
+            rho_km.y,
+            rho_km.z,
+            v_rot_km_s.x,
+            v_rot_km_s.y,
+            v_rot_km_s.z,
+        ))
+    }
+
+    /// Builds the orbit in the requested frame from a state in this rotating frame. This is the inverse of `to_rotating`.
+    pub fn from_rotating(
+        &self,
+        state: &Vector6<f64>,
+        epoch: Epoch,
+        frame: Frame,
+        almanac: &Almanac,
+    ) -> Result<Orbit, NyxError> {
+        let geom = self.geometry(epoch, almanac)?;
+
+        let r_rot_km = geom.origin_km + state.fixed_rows::<3>(0);
+        let v_rot_km_s =
+            state.fixed_rows::<3>(3) + geom.omega_rad_s.cross(&r_rot_km) + geom.origin_rate_km_s;
+
+        let radius_km = geom.dcm * r_rot_km;
+        let velocity_km_s = geom.dcm * v_rot_km_s;
+
+        let primary = almanac
+            .frame_from_uid(self.primary)
+            .map_err(|e| NyxError::CustomError {
+                msg: format!("fetching the primary frame {}: {e}", self.primary),
+            })?;
+
+        almanac
+            .transform_to(
+                Orbit::new(
+                    radius_km.x,
+                    radius_km.y,
+                    radius_km.z,
+                    velocity_km_s.x,
+                    velocity_km_s.y,
+                    velocity_km_s.z,
+                    epoch,
+                    primary,
+                ),
+                frame,
+                None,
+            )
+            .context(FromAlmanacSnafu {
+                action: "converting the orbit from the rotating frame",
+            })
+    }
+
+    /// Returns each state of the trajectory in this rotating frame, cf. `to_rotating`.
+    pub fn traj_states(
+        &self,
+        traj: &Traj<Spacecraft>,
+        almanac: &Almanac,
+    ) -> Result<Vec<(Epoch, Vector6<f64>)>, NyxError> {
+        traj.states
+            .iter()
+            .map(|state| Ok((state.epoch(), self.to_rotating(state.orbit, almanac)?)))
+            .collect()
+    }
+}
+
+impl fmt::Display for RotatingFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let origin = match self.origin {
+            RotatingOrigin::Barycenter => "barycenter".to_string(),
+            RotatingOrigin::Libration(point) => format!("{point:?}"),
+        };
+        write!(
+            f,
+            "{:x}-{:x} rotating frame centered on {origin}",
+            self.primary, self.secondary
+        )
+    }
+}
@@ -16,6 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use anise::almanac::Almanac;
 use anise::astro::PhysicsResult;
 use anise::constants::frames::EARTH_J2000;
 pub use anise::prelude::Orbit;
@@ -25,21 +26,22 @@ use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use typed_builder::TypedBuilder;
 
-use super::{AstroPhysicsSnafu, BPlane, State};
-use crate::dynamics::guidance::Thruster;
-use crate::dynamics::DynamicsError;
+use super::{AstroPhysicsSnafu, BPlane, OrbitExt, State};
+use crate::dynamics::guidance::{Thruster, ThrusterLimits};
+use crate::dynamics::{DynamicsError, ForceModel};
 use crate::errors::{StateAstroSnafu, StateError};
 use crate::io::ConfigRepr;
-use crate::linalg::{Const, DimName, OMatrix, OVector};
+use crate::linalg::{Const, DimName, Matrix6, OMatrix, OVector};
 use crate::md::StateParameter;
 use crate::time::Epoch;
-use crate::utils::{cartesian_to_spherical, spherical_to_cartesian};
+use crate::utils::{between_pm_180, cartesian_to_spherical, spherical_to_cartesian};
 
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 use std::default::Default;
 use std::fmt;
 use std::ops::Add;
+use std::sync::Arc;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "python", pyclass)]
@@ -106,6 +108,10 @@ pub struct Spacecraft {
 
     #[builder(default, setter(strip_option))]
     pub thruster: Option<Thruster>,
+    /// Optional operational limits of the thruster (minimum throttle, minimum impulse bit, duty cycle)
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub thruster_limits: Option<ThrusterLimits>,
     /// Any extra information or extension that is needed for specific guidance laws
     #[builder(default)]
     #[serde(default)]
@@ -126,6 +132,7 @@ impl Default for Spacecraft {
             srp: SrpConfig::default(),
             drag: DragConfig::default(),
             thruster: None,
+            thruster_limits: None,
             mode: GuidanceMode::default(),
             stm: None,
         }
@@ -199,6 +206,61 @@ impl Default for DragConfig {
 }
 
 impl Spacecraft {
+    /// Ordering of the parameters in the state vector of a spacecraft (i.e. `to_vector` and `set`), and of the rows and columns of its STM.
+    /// The STM follows the state in the vector, in column-major order.
+    pub const STATE_LAYOUT: &[StateParameter] = &[
+        StateParameter::X,
+        StateParameter::Y,
+        StateParameter::Z,
+        StateParameter::VX,
+        StateParameter::VY,
+        StateParameter::VZ,
+        StateParameter::Cr,
+        StateParameter::Cd,
+        StateParameter::FuelMass,
+    ];
+
+    /// Returns whether the state layout has exactly one entry per dimension of the state, without duplicates.
+    fn state_layout_is_consistent() -> bool {
+        Self::STATE_LAYOUT.len() == <Self as State>::Size::dim()
+            && Self::STATE_LAYOUT
+                .iter()
+                .enumerate()
+                .all(|(i, param)| !Self::STATE_LAYOUT[..i].contains(param))
+    }
+
+    /// Value of a parameter of the state layout
+    fn layout_value(&self, param: StateParameter) -> f64 {
+        match param {
+            StateParameter::X => self.orbit.radius_km.x,
+            StateParameter::Y => self.orbit.radius_km.y,
+            StateParameter::Z => self.orbit.radius_km.z,
+            StateParameter::VX => self.orbit.velocity_km_s.x,
+            StateParameter::VY => self.orbit.velocity_km_s.y,
+            StateParameter::VZ => self.orbit.velocity_km_s.z,
+            StateParameter::Cr => self.srp.cr,
+            StateParameter::Cd => self.drag.cd,
+            StateParameter::FuelMass => self.fuel_mass_kg,
+            _ => unreachable!("{param} is not in the state layout"),
+        }
+    }
+
+    /// Sets a parameter of the state layout, the coefficient of reflectivity is clamped between 0 and 2.
+    fn set_layout_value(&mut self, param: StateParameter, val: f64) {
+        match param {
+            StateParameter::X => self.orbit.radius_km.x = val,
+            StateParameter::Y => self.orbit.radius_km.y = val,
+            StateParameter::Z => self.orbit.radius_km.z = val,
+            StateParameter::VX => self.orbit.velocity_km_s.x = val,
+            StateParameter::VY => self.orbit.velocity_km_s.y = val,
+            StateParameter::VZ => self.orbit.velocity_km_s.z = val,
+            StateParameter::Cr => self.srp.cr = val.clamp(0.0, 2.0),
+            StateParameter::Cd => self.drag.cd = val,
+            StateParameter::FuelMass => self.fuel_mass_kg = val,
+            _ => unreachable!("{param} is not in the state layout"),
+        }
+    }
+
     /// Initialize a spacecraft state from all of its parameters
     pub fn new(
         orbit: Orbit,
@@ -349,11 +411,32 @@ impl Spacecraft {
         self
     }
 
+    /// Returns the position and velocity block of the STM, i.e. the STM of the orbit alone, cf. `OrbitExt::to_spacecraft_with_stm`
+    pub fn orbit_stm(&self) -> Result<Matrix6<f64>, DynamicsError> {
+        Ok(self.stm()?.fixed_view::<6, 6>(0, 0).into_owned())
+    }
+
     /// Returns the total mass in kilograms
     pub fn mass_kg(&self) -> f64 {
         self.dry_mass_kg + self.fuel_mass_kg
     }
 
+    /// Returns the drag and solar radiation pressure accelerations in km/s^2 acting on this spacecraft, in its integration frame.
+    ///
+    /// These are the same contributions as the ones computed by the spacecraft dynamics during a propagation step, i.e. the force of each model
+    /// divided by the total mass of the spacecraft, cf. `ForceModel::accel`. This is useful to validate the force models without propagating.
+    pub fn drag_and_srp_accel(
+        &self,
+        drag: &dyn ForceModel,
+        srp: &dyn ForceModel,
+        almanac: Arc<Almanac>,
+    ) -> Result<(Vector3<f64>, Vector3<f64>), DynamicsError> {
+        Ok((
+            drag.accel(self, almanac.clone())?,
+            srp.accel(self, almanac)?,
+        ))
+    }
+
     /// Returns a copy of the state with the provided guidance mode
     pub fn with_guidance_mode(mut self, mode: GuidanceMode) -> Self {
         self.mode = mode;
@@ -452,23 +535,17 @@ impl State for Spacecraft {
         Self::default()
     }
 
-    /// The vector is organized as such:
+    /// The vector is organized as `Spacecraft::STATE_LAYOUT`, followed by the STM:
     /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9)]
     fn to_vector(&self) -> OVector<f64, Const<90>> {
+        debug_assert!(
+            Self::state_layout_is_consistent(),
+            "spacecraft state layout does not match its size"
+        );
         let mut vector = OVector::<f64, Const<90>>::zeros();
-        // Set the orbit state info
-        for (i, val) in self.orbit.radius_km.iter().enumerate() {
-            // Place the orbit state first, then skip three (Cr, Cd, Fuel), then copy orbit STM
-            vector[i] = *val;
+        for (i, param) in Self::STATE_LAYOUT.iter().enumerate() {
+            vector[i] = self.layout_value(*param);
         }
-        for (i, val) in self.orbit.velocity_km_s.iter().enumerate() {
-            // Place the orbit state first, then skip three (Cr, Cd, Fuel), then copy orbit STM
-            vector[i + 3] = *val;
-        }
-        // Set the spacecraft parameters
-        vector[6] = self.srp.cr;
-        vector[7] = self.drag.cd;
-        vector[8] = self.fuel_mass_kg;
         // Add the STM to the vector
         if let Some(stm) = self.stm {
             for (idx, stm_val) in stm.as_slice().iter().enumerate() {
@@ -478,12 +555,13 @@ impl State for Spacecraft {
         vector
     }
 
-    /// Vector is expected to be organized as such:
+    /// Vector is expected to be organized as `Spacecraft::STATE_LAYOUT`, followed by the STM:
     /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9)]
     fn set(&mut self, epoch: Epoch, vector: &OVector<f64, Const<90>>) {
-        let sc_state =
-            OVector::<f64, Self::Size>::from_column_slice(&vector.as_slice()[..Self::Size::dim()]);
-
+        debug_assert!(
+            Self::state_layout_is_consistent(),
+            "spacecraft state layout does not match its size"
+        );
         if self.stm.is_some() {
             let sc_full_stm = OMatrix::<f64, Self::Size, Self::Size>::from_column_slice(
                 &vector.as_slice()[Self::Size::dim()..],
@@ -492,14 +570,10 @@ impl State for Spacecraft {
             self.stm = Some(sc_full_stm);
         }
 
-        let radius_km = sc_state.fixed_rows::<3>(0).into_owned();
-        let vel_km_s = sc_state.fixed_rows::<3>(3).into_owned();
         self.orbit.epoch = epoch;
-        self.orbit.radius_km = radius_km;
-        self.orbit.velocity_km_s = vel_km_s;
-        self.srp.cr = sc_state[6].clamp(0.0, 2.0);
-        self.drag.cd = sc_state[7];
-        self.fuel_mass_kg = sc_state[8];
+        for (i, param) in Self::STATE_LAYOUT.iter().enumerate() {
+            self.set_layout_value(*param, vector[i]);
+        }
     }
 
     /// diag(STM) = [X,Y,Z,Vx,Vy,Vz,Cr,Cd,Fuel]
@@ -543,6 +617,11 @@ impl State for Spacecraft {
                 .apoapsis_km()
                 .context(AstroPhysicsSnafu)
                 .context(StateAstroSnafu { param }),
+            StateParameter::ApoapsisAltitude => self
+                .orbit
+                .apoapsis_altitude_km()
+                .context(AstroPhysicsSnafu)
+                .context(StateAstroSnafu { param }),
             StateParameter::AoL => self
                 .orbit
                 .aol_deg()
@@ -586,6 +665,31 @@ impl State for Spacecraft {
                 .energy_km2_s2()
                 .context(AstroPhysicsSnafu)
                 .context(StateAstroSnafu { param }),
+            StateParameter::EquinoctialH => Ok(self
+                .orbit
+                .equinoctial()
+                .context(StateAstroSnafu { param })?
+                .h),
+            StateParameter::EquinoctialK => Ok(self
+                .orbit
+                .equinoctial()
+                .context(StateAstroSnafu { param })?
+                .k),
+            StateParameter::EquinoctialP => Ok(self
+                .orbit
+                .equinoctial()
+                .context(StateAstroSnafu { param })?
+                .p),
+            StateParameter::EquinoctialQ => Ok(self
+                .orbit
+                .equinoctial()
+                .context(StateAstroSnafu { param })?
+                .q),
+            StateParameter::MeanLongitude => Ok(self
+                .orbit
+                .equinoctial()
+                .context(StateAstroSnafu { param })?
+                .lambda_deg),
             StateParameter::FlightPathAngle => self
                 .orbit
                 .fpa_deg()
@@ -602,6 +706,12 @@ impl State for Spacecraft {
                 .context(AstroPhysicsSnafu)
                 .context(StateAstroSnafu { param }),
             StateParameter::Longitude => Ok(self.orbit.longitude_deg()),
+            StateParameter::AscendingNodeLongitude => Ok(between_pm_180(
+                self.orbit
+                    .raan_deg()
+                    .context(AstroPhysicsSnafu)
+                    .context(StateAstroSnafu { param })?,
+            )),
             StateParameter::Hmag => self
                 .orbit
                 .hmag()
@@ -642,6 +752,11 @@ impl State for Spacecraft {
                 .periapsis_km()
                 .context(AstroPhysicsSnafu)
                 .context(StateAstroSnafu { param }),
+            StateParameter::PeriapsisAltitude => self
+                .orbit
+                .periapsis_altitude_km()
+                .context(AstroPhysicsSnafu)
+                .context(StateAstroSnafu { param }),
             StateParameter::Period => Ok(self
                 .orbit
                 .period()
@@ -655,6 +770,7 @@ impl State for Spacecraft {
                 .context(AstroPhysicsSnafu)
                 .context(StateAstroSnafu { param }),
             StateParameter::Rmag => Ok(self.orbit.rmag_km()),
+            StateParameter::RangeRate => Ok(self.orbit.radius_rate_km_s()),
             StateParameter::SemiMinorAxis => self
                 .orbit
                 .semi_minor_axis_km()
@@ -758,6 +874,10 @@ impl State for Spacecraft {
         Ok(())
     }
 
+    fn set_stm(&mut self, stm: OMatrix<f64, Self::Size, Self::Size>) {
+        self.stm = Some(stm);
+    }
+
     fn unset_stm(&mut self) {
         self.stm = None;
     }
@@ -901,6 +1021,22 @@ thruster:
     let deser_sc: Spacecraft = serde_yaml::from_str(s).unwrap();
     assert_eq!(sc_thruster, deser_sc);
 
+    // Check that the thruster limits may be specified, with the omitted ones defaulting to no limit.
+    let s_limits = format!(
+        "{}\nthruster_limits:\n    min_throttle: 0.1\n",
+        s.trim_end()
+    );
+    let deser_sc: Spacecraft = serde_yaml::from_str(&s_limits).unwrap();
+    assert_eq!(sc_thruster, deser_sc);
+    assert_eq!(
+        deser_sc.thruster_limits,
+        Some(ThrusterLimits {
+            min_throttle: Some(0.1),
+            ..Default::default()
+        })
+    );
+    assert_eq!(deser_sc.thruster, sc_thruster.thruster);
+
     // Tests the minimum definition which will set all of the defaults too
     let s = r#"
 orbit:
@@ -927,3 +1063,43 @@ fuel_mass_kg: 159.0
     let sc = Spacecraft::new(orbit, 500.0, 159.0, 0.0, 0.0, 1.8, 2.2);
     assert_eq!(sc, deser_sc);
 }
+
+#[test]
+fn test_state_layout_round_trip() {
+    use anise::constants::frames::EARTH_J2000;
+
+    assert!(Spacecraft::state_layout_is_consistent());
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::new(
+        -9042.862234,
+        18536.333069,
+        6999.957069,
+        -3.288789,
+        -2.226285,
+        1.646738,
+        epoch,
+        EARTH_J2000,
+    );
+    let mut sc = Spacecraft::new(orbit, 500.0, 159.0, 2.0, 3.0, 1.8, 2.2);
+    sc.enable_stm();
+    let mut stm = sc.stm().unwrap();
+    stm[(8, 3)] = 0.5;
+    sc.stm = Some(stm);
+
+    let vector = sc.to_vector();
+    for (i, param) in Spacecraft::STATE_LAYOUT.iter().enumerate() {
+        assert_eq!(vector[i], sc.value(*param).unwrap(), "{param}");
+    }
+    assert_eq!(vector[8], 159.0);
+
+    // Setting the vector into a different state of the same spacecraft recovers the original one.
+    let mut other = Spacecraft::new(orbit, 500.0, 1.0, 2.0, 3.0, 1.0, 1.0);
+    other.orbit.radius_km *= 2.0;
+    other.orbit.epoch = epoch - 1 * crate::time::Unit::Day;
+    other.enable_stm();
+    other.set(epoch, &vector);
+
+    assert_eq!(other, sc);
+    assert_eq!(other.to_vector(), vector);
+}
@@ -0,0 +1,235 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::almanac::Almanac;
+use anise::constants::celestial_objects::SUN;
+use anise::constants::frames::SUN_J2000;
+use snafu::ResultExt;
+
+use super::{Frame, Orbit, Spacecraft};
+use crate::errors::{EventAlmanacSnafu, EventError, FromAlmanacSnafu, FromPhysicsSnafu, NyxError};
+use crate::md::prelude::Traj;
+use crate::md::EventEvaluator;
+use crate::time::{Duration, Epoch, TimeSeries, Unit};
+use crate::State;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+/// Computes the synodic period of two bodies orbiting the Sun, i.e. the time between two consecutive conjunctions of these bodies as seen from the Sun.
+///
+/// The mean motions are computed from the osculating heliocentric orbits of both bodies at the provided epoch.
+pub fn synodic_period(
+    body_a: Frame,
+    body_b: Frame,
+    epoch: Epoch,
+    almanac: Arc<Almanac>,
+) -> Result<Duration, NyxError> {
+    let sun_j2k = almanac
+        .frame_from_uid(SUN_J2000)
+        .map_err(|e| NyxError::CustomError {
+            msg: format!("fetching the Sun gravitational parameter: {e}"),
+        })?;
+
+    let mut mean_motions = [0.0; 2];
+    for (ii, body) in [body_a, body_b].iter().enumerate() {
+        let orbit = almanac
+            .transform(*body, sun_j2k, epoch, None)
+            .context(FromAlmanacSnafu {
+                action: "computing the heliocentric orbit for the synodic period",
+            })?;
+
+        let period = orbit.period().context(FromPhysicsSnafu {
+            action: "computing the orbital period for the synodic period",
+        })?;
+
+        mean_motions[ii] = TAU / period.to_seconds();
+    }
+
+    let rel_mean_motion = (mean_motions[0] - mean_motions[1]).abs();
+    if rel_mean_motion < f64::EPSILON {
+        return Err(NyxError::MathDomain {
+            msg: format!(
+                "{body_a} and {body_b} have the same mean motion, the synodic period is infinite"
+            ),
+        });
+    }
+
+    Ok((TAU / rel_mean_motion) * Unit::Second)
+}
+
+/// Kind of alignment of a target body with the Sun, as seen from an observer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SolarAlignmentKind {
+    /// The target is in the direction of the Sun
+    Conjunction,
+    /// The target is in the opposite direction of the Sun
+    Opposition,
+}
+
+/// Solar conjunction or opposition of a target body as seen from an observer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SolarAlignment {
+    /// Epoch of the extremum of the Sun-observer-target angle
+    pub epoch: Epoch,
+    pub kind: SolarAlignmentKind,
+    /// Sun-observer-target angle at this epoch, in degrees. This is the minimum angle for a conjunction and the maximum angle for an opposition.
+    pub angle_deg: f64,
+}
+
+impl fmt::Display for SolarAlignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} @ {} (Sun-observer-target angle = {:.3} deg)",
+            self.kind, self.epoch, self.angle_deg
+        )
+    }
+}
+
+/// An event on the rate of change of the Sun-observer-target angle, where the state is the target as seen from the observer.
+///
+/// The event is zero at each extremum of this angle, i.e. at each conjunction and opposition of the target.
+pub struct SolarElongationRateEvent {
+    pub epoch_precision: Duration,
+}
+
+impl SolarElongationRateEvent {
+    /// Returns the Sun-observer-target angle in degrees and its rate of change in degrees per day.
+    fn elongation(&self, target: &Orbit, almanac: Arc<Almanac>) -> Result<(f64, f64), EventError> {
+        let sun = almanac
+            .transform(
+                target.frame.with_ephem(SUN),
+                target.frame,
+                target.epoch,
+                None,
+            )
+            .context(EventAlmanacSnafu)?;
+
+        let r = target.radius_km;
+        let r_dot = target.velocity_km_s;
+        let s = sun.radius_km;
+        let s_dot = sun.velocity_km_s;
+
+        let cos_theta = r.dot(&s) / (r.norm() * s.norm());
+        let sin_theta = r.cross(&s).norm() / (r.norm() * s.norm());
+        let cos_theta_dot = (r_dot.dot(&s) + r.dot(&s_dot)) / (r.norm() * s.norm())
+            - cos_theta * (r.dot(&r_dot) / r.norm_squared() + s.dot(&s_dot) / s.norm_squared());
+
+        let theta_dot_rad_s = -cos_theta_dot / sin_theta;
+
+        Ok((
+            sin_theta.atan2(cos_theta).to_degrees(),
+            theta_dot_rad_s.to_degrees() * Unit::Day.in_seconds(),
+        ))
+    }
+}
+
+impl fmt::Display for SolarElongationRateEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sun-observer-target angle extremum")
+    }
+}
+
+impl EventEvaluator<Spacecraft> for SolarElongationRateEvent {
+    fn eval(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Result<f64, EventError> {
+        Ok(self.elongation(&state.orbit, almanac)?.1)
+    }
+
+    fn epoch_precision(&self) -> Duration {
+        self.epoch_precision
+    }
+
+    /// Stop searching when the angle changes by less than 1 micro-degree per day
+    fn value_precision(&self) -> f64 {
+        1e-6
+    }
+
+    fn unit(&self) -> &'static str {
+        "deg/day"
+    }
+
+    fn eval_string(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Result<String, EventError> {
+        let (angle_deg, rate_deg_day) = self.elongation(&state.orbit, almanac)?;
+        Ok(format!(
+            "Sun-observer-target angle = {angle_deg:.6} deg ({rate_deg_day:.6} deg/day)"
+        ))
+    }
+}
+
+/// Searches the solar conjunctions and oppositions of the target body as seen from the observer between the start and end epochs.
+///
+/// The ephemeris of the target relative to the observer is sampled every `sample_step` into a trajectory, and the extrema of the Sun-observer-target angle are found with the
+/// Brent solver of the event finder. A conjunction is reported when that angle is below `threshold_deg`, and an opposition when it is above 180 degrees minus `threshold_deg`.
+///
+/// # Limitations
+/// The same heuristics as `Traj::find` apply: the search span should not include more than about fifty extrema, and the sample step should be much smaller than the synodic period.
+pub fn solar_alignments(
+    observer: Frame,
+    target: Frame,
+    start: Epoch,
+    end: Epoch,
+    sample_step: Duration,
+    threshold_deg: f64,
+    almanac: Arc<Almanac>,
+) -> Result<Vec<SolarAlignment>, EventError> {
+    // Build the pseudo-trajectory of the target as seen from the observer
+    let mut traj = Traj::new();
+    for epoch in TimeSeries::inclusive(start, end, sample_step) {
+        let state = almanac
+            .transform(target, observer, epoch, None)
+            .context(EventAlmanacSnafu)?;
+        traj.states.push(Spacecraft::from(state));
+    }
+    traj.finalize();
+
+    let event = SolarElongationRateEvent {
+        epoch_precision: 30 * Unit::Second,
+    };
+
+    let extrema = match traj.find(&event, almanac.clone()) {
+        Ok(extrema) => extrema,
+        Err(EventError::NotFound { .. }) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+
+    let mut alignments = Vec::new();
+    for extremum in extrema {
+        let (angle_deg, _) = event.elongation(&extremum.state.orbit, almanac.clone())?;
+
+        let kind = if angle_deg < threshold_deg {
+            SolarAlignmentKind::Conjunction
+        } else if angle_deg > 180.0 - threshold_deg {
+            SolarAlignmentKind::Opposition
+        } else {
+            debug!(
+                "extremum of {angle_deg:.3} deg @ {} is neither a conjunction nor an opposition",
+                extremum.state.epoch()
+            );
+            continue;
+        };
+
+        alignments.push(SolarAlignment {
+            epoch: extremum.state.epoch(),
+            kind,
+            angle_deg,
+        });
+    }
+
+    Ok(alignments)
+}
@@ -0,0 +1,853 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Dynamics, DynamicsError, OrbitalDynamics, SpacecraftDynamics};
+use crate::cosmic::{Frame, Orbit, Spacecraft};
+use crate::errors::{FromAlmanacSnafu, NyxError};
+use crate::linalg::{
+    Const, DMatrix, DVector, DimName, Matrix3, Matrix6, OMatrix, OVector, Vector3, Vector6,
+};
+use crate::propagators::{ErrorControl, IntegratorOptions, Propagator};
+use crate::time::{Epoch, Unit};
+use crate::State;
+use anise::almanac::Almanac;
+use anise::constants::celestial_objects::{EARTH_MOON_BARYCENTER, MOON, SUN};
+use anise::constants::frames::{EARTH_J2000, MOON_J2000};
+use anise::constants::orientations::J2000;
+use snafu::ResultExt;
+use std::f64::consts::PI;
+use std::fmt;
+use std::sync::Arc;
+
+/// Mean Earth-Moon distance used as the characteristic length of the Earth-Moon system, in km.
+pub const EARTH_MOON_MEAN_DISTANCE_KM: f64 = 384_400.0;
+
+/// Maximum number of iterations of the differential correctors.
+const MAX_ITERATIONS: usize = 50;
+/// Convergence tolerance on the normalized constraints of the periodic orbit corrector.
+const PERIODIC_TOLERANCE: f64 = 1e-9;
+/// Convergence tolerance on the continuity constraints of the ephemeris multiple shooting (km and km/s).
+const CONTINUITY_TOLERANCE: f64 = 1e-6;
+/// Maximum amplitude step (normalized) of the planar Lyapunov continuation.
+const LYAPUNOV_CONTINUATION_STEP: f64 = 5e-3;
+
+/// The five libration points of a circular restricted three-body problem.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LibrationPoint {
+    /// Collinear point between both primaries
+    L1,
+    /// Collinear point beyond the secondary
+    L2,
+    /// Collinear point beyond the primary, opposite to the secondary
+    L3,
+    /// Triangular point leading the secondary
+    L4,
+    /// Triangular point trailing the secondary
+    L5,
+}
+
+impl LibrationPoint {
+    fn is_collinear(&self) -> bool {
+        matches!(self, Self::L1 | Self::L2 | Self::L3)
+    }
+}
+
+/// A circular restricted three-body problem (CR3BP) system.
+///
+/// States of this system are normalized and expressed in the barycentric rotating frame: the primary is at (-μ, 0, 0), the secondary at (1 - μ, 0, 0),
+/// the unit of length is the distance between both primaries and the unit of time is such that their mean motion is one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cr3bp {
+    /// Mass ratio μ = m2 / (m1 + m2)
+    pub mu: f64,
+    /// Characteristic length, i.e. the distance between the primaries, in km
+    pub lstar_km: f64,
+    /// Characteristic time, i.e. the inverse of the mean motion of the primaries, in seconds
+    pub tstar_s: f64,
+}
+
+impl Cr3bp {
+    /// Initializes a CR3BP system from the gravitational parameters of the primary and secondary, and the distance between them.
+    pub fn new(primary_gm_km3_s2: f64, secondary_gm_km3_s2: f64, distance_km: f64) -> Self {
+        let gm_km3_s2 = primary_gm_km3_s2 + secondary_gm_km3_s2;
+        Self {
+            mu: secondary_gm_km3_s2 / gm_km3_s2,
+            lstar_km: distance_km,
+            tstar_s: (distance_km.powi(3) / gm_km3_s2).sqrt(),
+        }
+    }
+
+    /// Initializes the Earth-Moon system from the gravitational parameters of the Almanac and the mean Earth-Moon distance.
+    pub fn earth_moon(almanac: &Almanac) -> Result<Self, NyxError> {
+        let mut gms = [0.0; 2];
+        for (ii, uid) in [EARTH_J2000, MOON_J2000].iter().enumerate() {
+            gms[ii] = almanac
+                .frame_from_uid(*uid)
+                .map_err(|e| NyxError::CustomError {
+                    msg: format!("fetching the gravitational parameter of {uid}: {e}"),
+                })?
+                .mu_km3_s2()
+                .map_err(|e| NyxError::CustomError {
+                    msg: format!("fetching the gravitational parameter of {uid}: {e}"),
+                })?;
+        }
+
+        Ok(Self::new(gms[0], gms[1], EARTH_MOON_MEAN_DISTANCE_KM))
+    }
+
+    /// Sum of the gravitational parameters of both primaries, in km^3/s^2
+    pub fn gm_km3_s2(&self) -> f64 {
+        self.lstar_km.powi(3) / self.tstar_s.powi(2)
+    }
+
+    /// Characteristic velocity of this system, in km/s
+    pub fn vstar_km_s(&self) -> f64 {
+        self.lstar_km / self.tstar_s
+    }
+
+    /// Returns the dynamics of this system, to be used with the usual propagators.
+    pub fn dynamics(&self) -> Cr3bpDynamics {
+        Cr3bpDynamics { system: *self }
+    }
+
+    /// Returns the normalized position of the requested libration point.
+    pub fn lagrange_point(&self, point: LibrationPoint) -> Vector3<f64> {
+        match point {
+            LibrationPoint::L4 => Vector3::new(0.5 - self.mu, 0.75_f64.sqrt(), 0.0),
+            LibrationPoint::L5 => Vector3::new(0.5 - self.mu, -(0.75_f64.sqrt()), 0.0),
+            collinear => Vector3::new(self.collinear_point(collinear).0, 0.0, 0.0),
+        }
+    }
+
+    /// Returns the normalized positions of L1 through L5.
+    pub fn lagrange_points(&self) -> [Vector3<f64>; 5] {
+        [
+            LibrationPoint::L1,
+            LibrationPoint::L2,
+            LibrationPoint::L3,
+            LibrationPoint::L4,
+            LibrationPoint::L5,
+        ]
+        .map(|point| self.lagrange_point(point))
+    }
+
+    /// Returns the x coordinate of a collinear point and its distance γ to the closest primary, found by Newton iterations on the quintic equations.
+    fn collinear_point(&self, point: LibrationPoint) -> (f64, f64) {
+        let mu = self.mu;
+        let (quintic, mut gamma): ([f64; 6], f64) = match point {
+            LibrationPoint::L1 => (
+                [1.0, -(3.0 - mu), 3.0 - 2.0 * mu, -mu, 2.0 * mu, -mu],
+                (mu / 3.0).cbrt(),
+            ),
+            LibrationPoint::L2 => (
+                [1.0, 3.0 - mu, 3.0 - 2.0 * mu, -mu, -2.0 * mu, -mu],
+                (mu / 3.0).cbrt(),
+            ),
+            _ => (
+                [
+                    1.0,
+                    2.0 + mu,
+                    1.0 + 2.0 * mu,
+                    -(1.0 - mu),
+                    -2.0 * (1.0 - mu),
+                    -(1.0 - mu),
+                ],
+                1.0 - 7.0 * mu / 12.0,
+            ),
+        };
+
+        for _ in 0..MAX_ITERATIONS {
+            let (mut f, mut df) = (0.0, 0.0);
+            for coeff in quintic {
+                df = df * gamma + f;
+                f = f * gamma + coeff;
+            }
+            let step = f / df;
+            gamma -= step;
+            if step.abs() < 1e-15 {
+                break;
+            }
+        }
+
+        let x = match point {
+            LibrationPoint::L1 => 1.0 - mu - gamma,
+            LibrationPoint::L2 => 1.0 - mu + gamma,
+            _ => -mu - gamma,
+        };
+        (x, gamma)
+    }
+
+    /// Computes the normalized equations of motion in the rotating frame.
+    pub fn eom(&self, state: &Vector6<f64>) -> Vector6<f64> {
+        let mu = self.mu;
+        let (x, y, z) = (state[0], state[1], state[2]);
+        let r1_3 = ((x + mu).powi(2) + y * y + z * z).powf(1.5);
+        let r2_3 = ((x - 1.0 + mu).powi(2) + y * y + z * z).powf(1.5);
+
+        Vector6::new(
+            state[3],
+            state[4],
+            state[5],
+            2.0 * state[4] + x - (1.0 - mu) * (x + mu) / r1_3 - mu * (x - 1.0 + mu) / r2_3,
+            -2.0 * state[3] + y - (1.0 - mu) * y / r1_3 - mu * y / r2_3,
+            -(1.0 - mu) * z / r1_3 - mu * z / r2_3,
+        )
+    }
+
+    /// Computes the Jacobian of the normalized equations of motion, i.e. the A matrix of the variational equations.
+    pub fn jacobian(&self, state: &Vector6<f64>) -> Matrix6<f64> {
+        let mu = self.mu;
+        let pos = state.fixed_rows::<3>(0).into_owned();
+        let from_primary = pos + Vector3::new(mu, 0.0, 0.0);
+        let from_secondary = pos - Vector3::new(1.0 - mu, 0.0, 0.0);
+
+        let point_mass_grad = |rel: Vector3<f64>, gm: f64| -> Matrix3<f64> {
+            let r = rel.norm();
+            -gm * (Matrix3::identity() / r.powi(3) - 3.0 * rel * rel.transpose() / r.powi(5))
+        };
+
+        let mut uxx = point_mass_grad(from_primary, 1.0 - mu) + point_mass_grad(from_secondary, mu);
+        uxx[(0, 0)] += 1.0;
+        uxx[(1, 1)] += 1.0;
+
+        let mut jac = Matrix6::zeros();
+        jac.fixed_view_mut::<3, 3>(0, 3)
+            .copy_from(&Matrix3::identity());
+        jac.fixed_view_mut::<3, 3>(3, 0).copy_from(&uxx);
+        jac[(3, 4)] = 2.0;
+        jac[(4, 3)] = -2.0;
+        jac
+    }
+
+    /// Computes the Jacobi constant of the provided normalized state.
+    pub fn jacobi_constant(&self, state: &Vector6<f64>) -> f64 {
+        let mu = self.mu;
+        let (x, y, z) = (state[0], state[1], state[2]);
+        let r1 = ((x + mu).powi(2) + y * y + z * z).sqrt();
+        let r2 = ((x - 1.0 + mu).powi(2) + y * y + z * z).sqrt();
+
+        x * x + y * y + 2.0 * (1.0 - mu) / r1 + 2.0 * mu / r2
+            - state.fixed_rows::<3>(3).norm_squared()
+    }
+
+    /// Builds the spacecraft state used to propagate the provided normalized state with the CR3BP dynamics.
+    ///
+    /// The orbit is tagged in the Earth-Moon barycenter frame but its components are the normalized rotating-frame coordinates,
+    /// and each second of propagation corresponds to one normalized time unit.
+    pub fn spacecraft(&self, state: &Vector6<f64>, epoch: Epoch) -> Spacecraft {
+        Spacecraft::from(Orbit::new(
+            state[0],
+            state[1],
+            state[2],
+            state[3],
+            state[4],
+            state[5],
+            epoch,
+            Frame::new(EARTH_MOON_BARYCENTER, J2000),
+        ))
+    }
+
+    /// Computes a planar Lyapunov orbit around a collinear point with the requested x amplitude in km.
+    ///
+    /// The initial guess comes from the linearized motion about the libration point. Large amplitudes are reached by natural
+    /// parameter continuation from the small amplitude solution.
+    pub fn planar_lyapunov(
+        &self,
+        point: LibrationPoint,
+        ax_km: f64,
+        almanac: Arc<Almanac>,
+    ) -> Result<Cr3bpPeriodicOrbit, NyxError> {
+        if !point.is_collinear() {
+            return Err(NyxError::MathDomain {
+                msg: format!(
+                    "planar Lyapunov orbits only exist around collinear points, not {point:?}"
+                ),
+            });
+        }
+
+        let x_l = self.lagrange_point(point).x;
+        let mu = self.mu;
+        let c2 = mu / (x_l - 1.0 + mu).abs().powi(3) + (1.0 - mu) / (x_l + mu).abs().powi(3);
+        let lambda = ((2.0 - c2 + (9.0 * c2 * c2 - 8.0 * c2).sqrt()) / 2.0).sqrt();
+        let k = (lambda.powi(2) + 1.0 + 2.0 * c2) / (2.0 * lambda);
+
+        let ax = ax_km / self.lstar_km;
+        let steps = (ax / LYAPUNOV_CONTINUATION_STEP).ceil().max(1.0) as usize;
+
+        let mut half_period = PI / lambda;
+        let mut vy = 0.0;
+        let mut state = Vector6::zeros();
+        for step in 1..=steps {
+            let ax_i = ax * step as f64 / steps as f64;
+            vy = if step == 1 {
+                k * ax_i * lambda
+            } else {
+                // Scale the previous solution with the amplitude
+                vy * step as f64 / (step - 1) as f64
+            };
+
+            let guess = Vector6::new(x_l - ax_i, 0.0, 0.0, 0.0, vy, 0.0);
+            (state, half_period) =
+                self.correct_half_period(guess, half_period, &[4], &[1, 3], almanac.clone())?;
+            vy = state[4];
+        }
+
+        Ok(Cr3bpPeriodicOrbit {
+            system: *self,
+            point,
+            state,
+            period: 2.0 * half_period,
+        })
+    }
+
+    /// Computes a halo orbit around L1 or L2 with the requested out-of-plane amplitude in km, for the northern or southern family.
+    ///
+    /// The initial guess is Richardson's third order approximation; the z component is kept fixed during the correction.
+    pub fn halo(
+        &self,
+        point: LibrationPoint,
+        az_km: f64,
+        northern: bool,
+        almanac: Arc<Almanac>,
+    ) -> Result<Cr3bpPeriodicOrbit, NyxError> {
+        if !matches!(point, LibrationPoint::L1 | LibrationPoint::L2) {
+            return Err(NyxError::MathDomain {
+                msg: format!(
+                    "halo orbit initial guesses are only available around L1 and L2, not {point:?}"
+                ),
+            });
+        }
+
+        let (guess, half_period) = self.richardson_halo(point, az_km, northern)?;
+        let (state, half_period) =
+            self.correct_half_period(guess, half_period, &[0, 4], &[1, 3, 5], almanac)?;
+
+        Ok(Cr3bpPeriodicOrbit {
+            system: *self,
+            point,
+            state,
+            period: 2.0 * half_period,
+        })
+    }
+
+    /// Richardson's third order analytical approximation of a halo orbit, returns the normalized initial state and half period.
+    fn richardson_halo(
+        &self,
+        point: LibrationPoint,
+        az_km: f64,
+        northern: bool,
+    ) -> Result<(Vector6<f64>, f64), NyxError> {
+        let mu = self.mu;
+        let (x_l, g) = self.collinear_point(point);
+        let cn = |n: i32| -> f64 {
+            let sign = if n % 2 == 0 { 1.0 } else { -1.0 };
+            match point {
+                LibrationPoint::L1 => {
+                    (mu + sign * (1.0 - mu) * g.powi(n + 1) / (1.0 - g).powi(n + 1)) / g.powi(3)
+                }
+                _ => {
+                    (sign * mu + sign * (1.0 - mu) * g.powi(n + 1) / (1.0 + g).powi(n + 1))
+                        / g.powi(3)
+                }
+            }
+        };
+        let (c2, c3, c4) = (cn(2), cn(3), cn(4));
+
+        let lam = ((2.0 - c2 + (9.0 * c2 * c2 - 8.0 * c2).sqrt()) / 2.0).sqrt();
+        let lam2 = lam * lam;
+        let k = 2.0 * lam / (lam2 + 1.0 - c2);
+        let k2 = k * k;
+        let d1 = 3.0 * lam2 / k * (k * (6.0 * lam2 - 1.0) - 2.0 * lam);
+        let d2 = 8.0 * lam2 / k * (k * (11.0 * lam2 - 1.0) - 2.0 * lam);
+
+        let a21 = 3.0 * c3 * (k2 - 2.0) / (4.0 * (1.0 + 2.0 * c2));
+        let a22 = 3.0 * c3 / (4.0 * (1.0 + 2.0 * c2));
+        let a23 =
+            -3.0 * c3 * lam / (4.0 * k * d1) * (3.0 * k.powi(3) * lam - 6.0 * k * (k - lam) + 4.0);
+        let a24 = -3.0 * c3 * lam / (4.0 * k * d1) * (2.0 + 3.0 * k * lam);
+        let b21 = -3.0 * c3 * lam / (2.0 * d1) * (3.0 * k * lam - 4.0);
+        let b22 = 3.0 * c3 * lam / d1;
+        let d21 = -c3 / (2.0 * lam2);
+
+        let a31 = -9.0 * lam / (4.0 * d2) * (4.0 * c3 * (k * a23 - b21) + k * c4 * (4.0 + k2))
+            + (9.0 * lam2 + 1.0 - c2) / (2.0 * d2)
+                * (3.0 * c3 * (2.0 * a23 - k * b21) + c4 * (2.0 + 3.0 * k2));
+        let a32 = -1.0 / d2
+            * (9.0 * lam / 4.0 * (4.0 * c3 * (k * a24 - b22) + k * c4)
+                + 1.5 * (9.0 * lam2 + 1.0 - c2) * (c3 * (k * b22 + d21 - 2.0 * a24) - c4));
+        let b31 = 3.0 / (8.0 * d2)
+            * (8.0 * lam * (3.0 * c3 * (k * b21 - 2.0 * a23) - c4 * (2.0 + 3.0 * k2))
+                + (9.0 * lam2 + 1.0 + 2.0 * c2)
+                    * (4.0 * c3 * (k * a23 - b21) + k * c4 * (4.0 + k2)));
+        let b32 = 1.0 / d2
+            * (9.0 * lam * (c3 * (k * b22 + d21 - 2.0 * a24) - c4)
+                + 3.0 / 8.0
+                    * (9.0 * lam2 + 1.0 + 2.0 * c2)
+                    * (4.0 * c3 * (k * a24 - b22) + k * c4));
+        let d31 = 3.0 / (64.0 * lam2) * (4.0 * c3 * a24 + c4);
+        let d32 = 3.0 / (64.0 * lam2) * (4.0 * c3 * (a23 - d21) + c4 * (4.0 + k2));
+
+        let s_den = 2.0 * lam * (lam * (1.0 + k2) - 2.0 * k);
+        let s1 = (1.5 * c3 * (2.0 * a21 * (k2 - 2.0) - a23 * (k2 + 2.0) - 2.0 * k * b21)
+            - 3.0 / 8.0 * c4 * (3.0 * k.powi(4) - 8.0 * k2 + 8.0))
+            / s_den;
+        let s2 =
+            (1.5 * c3 * (2.0 * a22 * (k2 - 2.0) + a24 * (k2 + 2.0) + 2.0 * k * b22 + 5.0 * d21)
+                + 3.0 / 8.0 * c4 * (12.0 - k2))
+                / s_den;
+        let l1 = -1.5 * c3 * (2.0 * a21 + a23 + 5.0 * d21) - 3.0 / 8.0 * c4 * (12.0 - k2)
+            + 2.0 * lam2 * s1;
+        let l2 = 1.5 * c3 * (a24 - 2.0 * a22) + 9.0 / 8.0 * c4 + 2.0 * lam2 * s2;
+
+        // Amplitudes are normalized by the distance from the libration point to the closest primary.
+        let az = az_km / self.lstar_km / g;
+        let ax_sq = -(lam2 - c2 + l2 * az * az) / l1;
+        if ax_sq <= 0.0 {
+            return Err(NyxError::MathDomain {
+                msg: format!("no halo orbit of {az_km} km out-of-plane amplitude around {point:?}"),
+            });
+        }
+        let ax = ax_sq.sqrt();
+        let omega = 1.0 + s1 * ax * ax + s2 * az * az;
+        let dn = if northern { 1.0 } else { -1.0 };
+
+        // Evaluated at τ = 0
+        let x = a21 * ax * ax + a22 * az * az - ax
+            + (a23 * ax * ax - a24 * az * az)
+            + (a31 * ax.powi(3) - a32 * ax * az * az);
+        let z = dn * az - 2.0 * dn * d21 * ax * az + dn * (d32 * az * ax * ax - d31 * az.powi(3));
+        let vy = lam
+            * omega
+            * (k * ax
+                + 2.0 * (b21 * ax * ax - b22 * az * az)
+                + 3.0 * (b31 * ax.powi(3) - b32 * ax * az * az));
+
+        Ok((
+            Vector6::new(x_l + g * x, 0.0, g * z, 0.0, g * vy, 0.0),
+            PI / (lam * omega),
+        ))
+    }
+
+    /// Differential corrector of symmetric periodic orbits: propagates the state with its STM for half a period, and varies the `free`
+    /// components of the initial state and the half period until the `targets` components of the final state are zero.
+    fn correct_half_period(
+        &self,
+        mut state: Vector6<f64>,
+        mut half_period: f64,
+        free: &[usize],
+        targets: &[usize],
+        almanac: Arc<Almanac>,
+    ) -> Result<(Vector6<f64>, f64), NyxError> {
+        let prop = Propagator::rk89(self.dynamics(), Self::integrator_options());
+        let epoch = Epoch::from_tdb_seconds(0.0);
+
+        for _ in 0..MAX_ITERATIONS {
+            let final_sc = prop
+                .with(self.spacecraft(&state, epoch).with_stm(), almanac.clone())
+                .quiet()
+                .for_duration(half_period * Unit::Second)
+                .map_err(|e| NyxError::CustomError {
+                    msg: format!("propagating the CR3BP periodic orbit guess: {e}"),
+                })?;
+
+            let final_state = final_sc.orbit.to_cartesian_pos_vel();
+            let residuals =
+                DVector::from_iterator(targets.len(), targets.iter().map(|idx| final_state[*idx]));
+            if residuals.amax() < PERIODIC_TOLERANCE {
+                return Ok((state, half_period));
+            }
+
+            let stm = final_sc.stm().map_err(|e| NyxError::CustomError {
+                msg: format!("CR3BP periodic orbit correction: {e}"),
+            })?;
+            let final_rate = self.eom(&final_state);
+
+            // The last column accounts for the variation of the half period
+            let mut jac = DMatrix::zeros(targets.len(), free.len() + 1);
+            for (row, target) in targets.iter().enumerate() {
+                for (col, var) in free.iter().enumerate() {
+                    jac[(row, col)] = stm[(*target, *var)];
+                }
+                jac[(row, free.len())] = final_rate[*target];
+            }
+
+            let correction = jac.lu().solve(&(-residuals)).ok_or(NyxError::MathDomain {
+                msg: "singular Jacobian in the CR3BP periodic orbit correction".to_string(),
+            })?;
+
+            for (col, var) in free.iter().enumerate() {
+                state[*var] += correction[col];
+            }
+            half_period += correction[free.len()];
+        }
+
+        Err(NyxError::MaxIterReached {
+            msg: format!("{MAX_ITERATIONS} in the CR3BP periodic orbit correction"),
+        })
+    }
+
+    /// Integrator options suited for normalized states, where each second is one normalized time unit.
+    fn integrator_options() -> IntegratorOptions {
+        IntegratorOptions::with_adaptive_step_s(1e-6, 0.05, 1e-12, ErrorControl::RSSCartesianStep)
+    }
+
+    /// Converts a normalized rotating state into an Earth centered EME2000 state of the ephemeris model at the provided epoch.
+    ///
+    /// The rotating frame is built from the instantaneous geometry of the Moon: the x axis points from the Earth to the Moon, the z axis along the
+    /// angular momentum of the Moon, and the length and time units are scaled with the instantaneous Earth-Moon distance. This pulsating frame
+    /// matches the CR3BP frame when the orbit of the Moon is circular.
+    pub fn to_ephemeris(
+        &self,
+        state: &Vector6<f64>,
+        epoch: Epoch,
+        almanac: Arc<Almanac>,
+    ) -> Result<Orbit, NyxError> {
+        let moon = almanac
+            .transform(MOON_J2000, EARTH_J2000, epoch, None)
+            .context(FromAlmanacSnafu {
+                action: "computing the Earth-Moon rotating frame",
+            })?;
+        let eme2k = almanac
+            .frame_from_uid(EARTH_J2000)
+            .map_err(|e| NyxError::CustomError {
+                msg: format!("fetching the Earth J2000 frame: {e}"),
+            })?;
+
+        let r_moon = moon.radius_km;
+        let v_moon = moon.velocity_km_s;
+        let h_moon = r_moon.cross(&v_moon);
+        let lstar_km = r_moon.norm();
+        let lstar_dot_km_s = r_moon.dot(&v_moon) / lstar_km;
+        let theta_dot_rad_s = h_moon.norm() / lstar_km.powi(2);
+        let tstar_s = (lstar_km.powi(3) / self.gm_km3_s2()).sqrt();
+
+        let x_hat = r_moon / lstar_km;
+        let z_hat = h_moon / h_moon.norm();
+        let y_hat = z_hat.cross(&x_hat);
+        let dcm = Matrix3::from_columns(&[x_hat, y_hat, z_hat]);
+
+        // Earth centered position and velocity in the rotating axes
+        let pos_nd = Vector3::new(state[0] + self.mu, state[1], state[2]);
+        let vel_nd = state.fixed_rows::<3>(3).into_owned();
+        let rho_km = lstar_km * pos_nd;
+        let rho_dot_km_s = lstar_dot_km_s * pos_nd + (lstar_km / tstar_s) * vel_nd;
+        let transport = theta_dot_rad_s * Vector3::new(-rho_km.y, rho_km.x, 0.0);
+
+        let radius_km = dcm * rho_km;
+        let velocity_km_s = dcm * (rho_dot_km_s + transport);
+
+        Ok(Orbit::new(
+            radius_km.x,
+            radius_km.y,
+            radius_km.z,
+            velocity_km_s.x,
+            velocity_km_s.y,
+            velocity_km_s.z,
+            epoch,
+            eme2k,
+        ))
+    }
+}
+
+impl fmt::Display for Cr3bp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CR3BP (μ = {:.12}, L* = {} km, T* = {:.3} s)",
+            self.mu, self.lstar_km, self.tstar_s
+        )
+    }
+}
+
+/// A periodic orbit of a CR3BP system, defined by its normalized initial state on the x-z plane.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cr3bpPeriodicOrbit {
+    pub system: Cr3bp,
+    /// Libration point this orbit revolves around
+    pub point: LibrationPoint,
+    /// Normalized initial state in the rotating frame
+    pub state: Vector6<f64>,
+    /// Normalized period
+    pub period: f64,
+}
+
+impl Cr3bpPeriodicOrbit {
+    /// Dimensional period of this orbit
+    pub fn period_duration(&self) -> crate::time::Duration {
+        (self.period * self.system.tstar_s) * Unit::Second
+    }
+
+    /// Jacobi constant of this orbit
+    pub fn jacobi_constant(&self) -> f64 {
+        self.system.jacobi_constant(&self.state)
+    }
+
+    /// Computes the monodromy matrix of this orbit, i.e. its state transition matrix over one period.
+    pub fn monodromy(&self, almanac: Arc<Almanac>) -> Result<Matrix6<f64>, NyxError> {
+        let final_sc = Propagator::rk89(self.system.dynamics(), Cr3bp::integrator_options())
+            .with(
+                self.system
+                    .spacecraft(&self.state, Epoch::from_tdb_seconds(0.0))
+                    .with_stm(),
+                almanac,
+            )
+            .quiet()
+            .for_duration(self.period * Unit::Second)
+            .map_err(|e| NyxError::CustomError {
+                msg: format!("propagating the CR3BP periodic orbit: {e}"),
+            })?;
+
+        let stm = final_sc.stm().map_err(|e| NyxError::CustomError {
+            msg: format!("computing the monodromy matrix: {e}"),
+        })?;
+
+        Ok(stm.fixed_view::<6, 6>(0, 0).into_owned())
+    }
+
+    /// Stability index of this orbit, (|λ_max| + 1 / |λ_max|) / 2 where λ_max is the largest eigenvalue of the monodromy matrix.
+    ///
+    /// The orbit is linearly stable if this index is one.
+    pub fn stability_index(&self, almanac: Arc<Almanac>) -> Result<f64, NyxError> {
+        let lambda_max = self
+            .monodromy(almanac)?
+            .complex_eigenvalues()
+            .iter()
+            .map(|eigval| eigval.norm())
+            .fold(0.0, f64::max);
+
+        Ok((lambda_max + 1.0 / lambda_max) / 2.0)
+    }
+
+    /// Converts this orbit into the Earth centered ephemeris model starting at the provided epoch.
+    ///
+    /// The CR3BP orbit is sampled at `segments + 1` equally spaced nodes over one period, each node is converted with [`Cr3bp::to_ephemeris`],
+    /// and a minimum-norm multiple shooting corrects the node states until the trajectory is continuous in the Earth, Moon and Sun point mass model.
+    /// This is needed because the eccentricity of the lunar orbit and the Sun make the directly converted state depart the libration region within
+    /// one revolution. The returned nodes can be propagated from the first one.
+    pub fn to_ephemeris(
+        &self,
+        epoch: Epoch,
+        segments: usize,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<Orbit>, NyxError> {
+        if segments == 0 {
+            return Err(NyxError::MathDomain {
+                msg: "at least one segment is needed for the ephemeris conversion".to_string(),
+            });
+        }
+
+        // Sample the CR3BP orbit
+        let cr3bp_prop = Propagator::rk89(self.system.dynamics(), Cr3bp::integrator_options());
+        let step_nd = self.period / segments as f64;
+        let mut cr3bp_nodes = vec![self.state];
+        let mut sc = self
+            .system
+            .spacecraft(&self.state, Epoch::from_tdb_seconds(0.0));
+        for _ in 0..segments {
+            sc = cr3bp_prop
+                .with(sc, almanac.clone())
+                .quiet()
+                .for_duration(step_nd * Unit::Second)
+                .map_err(|e| NyxError::CustomError {
+                    msg: format!("sampling the CR3BP periodic orbit: {e}"),
+                })?;
+            cr3bp_nodes.push(sc.orbit.to_cartesian_pos_vel());
+        }
+
+        // Convert the nodes, using the Earth-Moon time unit at the start epoch for the segment duration.
+        let first = self
+            .system
+            .to_ephemeris(&cr3bp_nodes[0], epoch, almanac.clone())?;
+        let moon = almanac
+            .transform(MOON_J2000, EARTH_J2000, epoch, None)
+            .context(FromAlmanacSnafu {
+                action: "computing the Earth-Moon distance",
+            })?;
+        let tstar_s = (moon.rmag_km().powi(3) / self.system.gm_km3_s2()).sqrt();
+        let segment_dur = (step_nd * tstar_s) * Unit::Second;
+
+        let mut nodes = vec![first.to_cartesian_pos_vel()];
+        for (k, node) in cr3bp_nodes.iter().enumerate().skip(1) {
+            let orbit = self.system.to_ephemeris(
+                node,
+                epoch + segment_dur * (k as f64),
+                almanac.clone(),
+            )?;
+            nodes.push(orbit.to_cartesian_pos_vel());
+        }
+
+        let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::point_masses(
+            vec![MOON, SUN],
+        )));
+
+        let num_cstr = 6 * segments;
+        let num_vars = 6 * (segments + 1);
+        for _ in 0..MAX_ITERATIONS {
+            let mut defects = DVector::zeros(num_cstr);
+            let mut jac = DMatrix::zeros(num_cstr, num_vars);
+
+            for (k, window) in nodes.windows(2).enumerate() {
+                let node = window[0];
+                let start = Orbit::new(
+                    node[0],
+                    node[1],
+                    node[2],
+                    node[3],
+                    node[4],
+                    node[5],
+                    epoch + segment_dur * (k as f64),
+                    first.frame,
+                );
+                let end_sc = prop
+                    .with(Spacecraft::from(start).with_stm(), almanac.clone())
+                    .quiet()
+                    .for_duration(segment_dur)
+                    .map_err(|e| NyxError::CustomError {
+                        msg: format!("propagating segment #{k} of the ephemeris conversion: {e}"),
+                    })?;
+                let stm = end_sc.stm().map_err(|e| NyxError::CustomError {
+                    msg: format!("ephemeris conversion: {e}"),
+                })?;
+
+                let defect = end_sc.orbit.to_cartesian_pos_vel() - window[1];
+                for i in 0..6 {
+                    defects[6 * k + i] = defect[i];
+                    for j in 0..6 {
+                        jac[(6 * k + i, 6 * k + j)] = stm[(i, j)];
+                    }
+                    jac[(6 * k + i, 6 * (k + 1) + i)] = -1.0;
+                }
+            }
+
+            if defects.norm() < CONTINUITY_TOLERANCE {
+                return Ok(nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(k, node)| {
+                        Orbit::new(
+                            node[0],
+                            node[1],
+                            node[2],
+                            node[3],
+                            node[4],
+                            node[5],
+                            epoch + segment_dur * (k as f64),
+                            first.frame,
+                        )
+                    })
+                    .collect());
+            }
+
+            // Minimum norm update of all of the node states
+            let gram = &jac * jac.transpose();
+            let multipliers = gram.lu().solve(&(-defects)).ok_or(NyxError::MathDomain {
+                msg: "singular Gram matrix in the ephemeris conversion".to_string(),
+            })?;
+            let update = jac.transpose() * multipliers;
+            for (k, node) in nodes.iter_mut().enumerate() {
+                for i in 0..6 {
+                    node[i] += update[6 * k + i];
+                }
+            }
+        }
+
+        Err(NyxError::MaxIterReached {
+            msg: format!("{MAX_ITERATIONS} in the CR3BP to ephemeris multiple shooting"),
+        })
+    }
+}
+
+impl fmt::Display for Cr3bpPeriodicOrbit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} periodic orbit: x0 = {:.12}, z0 = {:.12}, vy0 = {:.12}, period = {:.9} ({} days), C = {:.9}",
+            self.point,
+            self.state[0],
+            self.state[2],
+            self.state[4],
+            self.period,
+            self.period_duration().to_unit(Unit::Day),
+            self.jacobi_constant()
+        )
+    }
+}
+
+/// Normalized CR3BP equations of motion, usable with the usual propagators.
+///
+/// The propagated spacecraft orbit holds the normalized rotating state (cf. [`Cr3bp::spacecraft`]): one second of propagation is one normalized time unit.
+/// The spacecraft parameters (Cr, Cd, fuel mass) are constant.
+#[derive(Copy, Clone, Debug)]
+pub struct Cr3bpDynamics {
+    pub system: Cr3bp,
+}
+
+impl fmt::Display for Cr3bpDynamics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} dynamics", self.system)
+    }
+}
+
+impl Dynamics for Cr3bpDynamics {
+    type HyperdualSize = Const<9>;
+    type StateType = Spacecraft;
+
+    fn eom(
+        &self,
+        delta_t_s: f64,
+        state: &OVector<f64, Const<90>>,
+        ctx: &Self::StateType,
+        almanac: Arc<Almanac>,
+    ) -> Result<OVector<f64, Const<90>>, DynamicsError> {
+        let osc_sc = ctx.set_with_delta_seconds(delta_t_s, state);
+        let mut d_x = OVector::<f64, Const<90>>::zeros();
+
+        let (rate, grad) = self.dual_eom(delta_t_s, &osc_sc, almanac)?;
+        for (i, val) in rate.iter().enumerate() {
+            d_x[i] = *val;
+        }
+
+        if let Some(stm) = osc_sc.stm {
+            // Variational equations: dΦ/dt = A Φ
+            let stm_dt = grad * stm;
+            for (i, val) in stm_dt.iter().copied().enumerate() {
+                d_x[i + <Spacecraft as State>::Size::dim()] = val;
+            }
+        }
+
+        Ok(d_x)
+    }
+
+    fn dual_eom(
+        &self,
+        _delta_t_s: f64,
+        osc_sc: &Self::StateType,
+        _almanac: Arc<Almanac>,
+    ) -> Result<(OVector<f64, Const<9>>, OMatrix<f64, Const<9>, Const<9>>), DynamicsError> {
+        let state = osc_sc.orbit.to_cartesian_pos_vel();
+        let mut d_x = OVector::<f64, Const<9>>::zeros();
+        d_x.fixed_rows_mut::<6>(0)
+            .copy_from(&self.system.eom(&state));
+
+        let mut grad = OMatrix::<f64, Const<9>, Const<9>>::zeros();
+        grad.fixed_view_mut::<6, 6>(0, 0)
+            .copy_from(&self.system.jacobian(&state));
+
+        Ok((d_x, grad))
+    }
+}
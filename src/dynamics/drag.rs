@@ -23,6 +23,7 @@ use snafu::ResultExt;
 use super::{
     DynamicsAlmanacSnafu, DynamicsAstroSnafu, DynamicsError, DynamicsPlanetarySnafu, ForceModel,
 };
+use crate::cosmic::attitude::OrientedPlates;
 use crate::cosmic::{AstroError, AstroPhysicsSnafu, Frame, Spacecraft};
 use crate::linalg::{Matrix4x3, Vector3};
 use std::fmt;
@@ -62,6 +63,10 @@ impl fmt::Display for ConstantDrag {
 }
 
 impl ForceModel for ConstantDrag {
+    fn has_partials(&self) -> bool {
+        true
+    }
+
     fn estimation_index(&self) -> Option<usize> {
         if self.estimate {
             Some(7)
@@ -102,9 +107,30 @@ pub struct Drag {
     pub drag_frame: Frame,
     /// Set to true to estimate the coefficient of drag
     pub estimate: bool,
+    /// Attitude dependent area of the spacecraft, seen along the velocity, used instead of the drag area of the spacecraft if set
+    pub plates: Option<OrientedPlates>,
 }
 
 impl Drag {
+    /// Uses the projected area of the provided plates, seen along the velocity relative to the atmosphere, instead of the drag area of the spacecraft.
+    pub fn with_plates(mut self, plates: OrientedPlates) -> Self {
+        self.plates = Some(plates);
+        self
+    }
+
+    /// Area in m^2 of the spacecraft exposed to the flow, in the provided direction of the velocity relative to the atmosphere.
+    fn area_m2(
+        &self,
+        ctx: &Spacecraft,
+        velocity: &Vector3<f64>,
+        almanac: &Almanac,
+    ) -> Result<f64, DynamicsError> {
+        match &self.plates {
+            Some(plates) => plates.projected_area_m2(&ctx.orbit, velocity, almanac),
+            None => Ok(ctx.drag.area_m2),
+        }
+    }
+
     /// Common exponential drag model for the Earth
     pub fn earth_exp(almanac: Arc<Almanac>) -> Result<Arc<Self>, DynamicsError> {
         Ok(Arc::new(Self {
@@ -119,6 +145,7 @@ impl Drag {
                 }
             })?,
             estimate: false,
+            plates: None,
         }))
     }
 
@@ -134,6 +161,7 @@ impl Drag {
                 }
             })?,
             estimate: false,
+            plates: None,
         }))
     }
 }
@@ -149,6 +177,10 @@ impl fmt::Display for Drag {
 }
 
 impl ForceModel for Drag {
+    fn has_partials(&self) -> bool {
+        true
+    }
+
     fn estimation_index(&self) -> Option<usize> {
         if self.estimate {
             Some(7)
@@ -166,12 +198,8 @@ impl ForceModel for Drag {
                 action: "transforming into drag frame",
             })?;
 
-        match self.density {
-            AtmDensity::Constant(rho) => {
-                let velocity = osc_drag_frame.velocity_km_s;
-                // Note the 1e3 factor to convert drag units from ((kg * km^2 * s^-2) / m^1) to (kg * km * s^-2)
-                Ok(-0.5 * 1e3 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
-            }
+        let (rho, velocity) = match self.density {
+            AtmDensity::Constant(rho) => (rho, osc_drag_frame.velocity_km_s),
 
             AtmDensity::Exponential {
                 rho0,
@@ -200,9 +228,7 @@ impl ForceModel for Drag {
                     })?
                     .velocity_km_s;
 
-                let velocity = velocity_integr_frame - osc_drag_frame.velocity_km_s;
-                // Note the 1e3 factor to convert drag units from ((kg * km^2 * s^-2) / m^1) to (kg * km * s^-2)
-                Ok(-0.5 * 1e3 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+                (rho, velocity_integr_frame - osc_drag_frame.velocity_km_s)
             }
 
             AtmDensity::StdAtm { max_alt_m } => {
@@ -238,11 +264,13 @@ impl ForceModel for Drag {
                     })?
                     .velocity_km_s;
 
-                let velocity = velocity_integr_frame - osc_drag_frame.velocity_km_s;
-                // Note the 1e3 factor to convert drag units from ((kg * km^2 * s^-2) / m^1) to (kg * km * s^-2)
-                Ok(-0.5 * 1e3 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+                (rho, velocity_integr_frame - osc_drag_frame.velocity_km_s)
             }
-        }
+        };
+
+        let area_m2 = self.area_m2(ctx, &velocity, &almanac)?;
+        // Note the 1e3 factor to convert drag units from ((kg * km^2 * s^-2) / m^1) to (kg * km * s^-2)
+        Ok(-0.5 * 1e3 * rho * ctx.drag.cd * area_m2 * velocity.norm() * velocity)
     }
 
     fn dual_eom(
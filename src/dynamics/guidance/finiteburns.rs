@@ -16,11 +16,12 @@ use anise::prelude::Almanac;
     You should have received a copy of the GNU Affero General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use hifitime::Epoch;
+use hifitime::{Duration, Epoch};
 
-use super::{GuidanceError, GuidanceLaw, Mnvr};
+use super::{GuidanceError, GuidanceLaw, Mnvr, Thruster, ThrusterLimits};
 use crate::cosmic::{GuidanceMode, Spacecraft};
 use crate::linalg::Vector3;
+use crate::polyfit::CommonPolynomial;
 use crate::State;
 use std::fmt;
 use std::sync::Arc;
@@ -29,12 +30,190 @@ use std::sync::Arc;
 pub struct FiniteBurns {
     /// Maneuvers should be provided in chronological order, first maneuver first in the list
     pub mnvrs: Vec<Mnvr>,
+    /// How each of the requested maneuvers is executed, only populated when the schedule accounts for the thruster limits
+    pub records: Vec<MnvrRecord>,
+}
+
+/// How a requested maneuver is executed given the limits of the thruster.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MnvrOutcome {
+    /// Executed as requested, possibly with a clamped throttle
+    Nominal,
+    /// Skipped because the throttle is below the minimum throttle and the policy clamps it to zero
+    SkippedBelowMinThrottle { throttle: f64, min_throttle: f64 },
+    /// Skipped because the impulse is below the minimum impulse bit of the thruster
+    SkippedBelowMinImpulseBit {
+        impulse_N_s: f64,
+        min_impulse_bit_N_s: f64,
+    },
+    /// Split into several firings because it exceeds the maximum continuous firing duration
+    Split { firings: usize, cooldown: Duration },
+}
+
+/// Record of the execution of a requested maneuver.
+#[derive(Clone, Debug)]
+pub struct MnvrRecord {
+    /// The maneuver as requested
+    pub requested: Mnvr,
+    /// The firings actually executed, empty if the maneuver was skipped
+    pub executed: Vec<Mnvr>,
+    /// Throttle level of the executed firings
+    pub throttle: f64,
+    /// Outcome of the maneuver
+    pub outcome: MnvrOutcome,
+}
+
+impl fmt::Display for MnvrRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.outcome {
+            MnvrOutcome::Nominal => write!(
+                f,
+                "maneuver on {} executed @ {:.2}%",
+                self.requested.start,
+                100.0 * self.throttle
+            ),
+            MnvrOutcome::SkippedBelowMinThrottle {
+                throttle,
+                min_throttle,
+            } => write!(
+                f,
+                "maneuver on {} skipped: throttle {:.2}% below minimum of {:.2}%",
+                self.requested.start,
+                100.0 * throttle,
+                100.0 * min_throttle
+            ),
+            MnvrOutcome::SkippedBelowMinImpulseBit {
+                impulse_N_s,
+                min_impulse_bit_N_s,
+            } => write!(
+                f,
+                "maneuver on {} skipped: impulse of {impulse_N_s:.3e} N s below minimum impulse bit of {min_impulse_bit_N_s:.3e} N s",
+                self.requested.start,
+            ),
+            MnvrOutcome::Split { firings, cooldown } => write!(
+                f,
+                "maneuver on {} split in {firings} firings separated by {cooldown} @ {:.2}%, ending on {}",
+                self.requested.start,
+                100.0 * self.throttle,
+                self.executed.last().unwrap().end
+            ),
+        }
+    }
 }
 
 impl FiniteBurns {
     /// Builds a schedule from the vector of maneuvers, must be provided in chronological order.
     pub fn from_mnvrs(mnvrs: Vec<Mnvr>) -> Arc<Self> {
-        Arc::new(Self { mnvrs })
+        Arc::new(Self {
+            mnvrs,
+            records: Vec::new(),
+        })
+    }
+
+    /// Returns the start and end epochs of each maneuver, e.g. to pin them in the propagator with `PropInstance::pin_epochs`.
+    pub fn epochs(&self) -> Vec<Epoch> {
+        self.mnvrs
+            .iter()
+            .flat_map(|mnvr| [mnvr.start, mnvr.end])
+            .collect()
+    }
+
+    /// Builds a schedule from the vector of maneuvers (in chronological order) accounting for the limits of the thruster
+    /// (e.g. the `thruster_limits` of the spacecraft):
+    /// + the throttle is clamped per the minimum throttle policy, and the maneuver is skipped if it is clamped to zero;
+    /// + maneuvers whose impulse is below the minimum impulse bit are skipped;
+    /// + maneuvers longer than the maximum firing duration are split into firings separated by the cooldown.
+    ///
+    /// Each maneuver is recorded in the `records` of the schedule. Skipped maneuvers are also reported as warnings.
+    /// Note that splitting a maneuver delays its end by the cooldowns, which may then overlap with the next maneuver.
+    #[allow(non_snake_case)]
+    pub fn with_thruster(
+        mnvrs: Vec<Mnvr>,
+        thruster: &Thruster,
+        limits: &ThrusterLimits,
+    ) -> Arc<Self> {
+        let mut executed_mnvrs = Vec::with_capacity(mnvrs.len());
+        let mut records = Vec::with_capacity(mnvrs.len());
+
+        for requested in mnvrs {
+            let throttle = limits.limited_throttle(requested.thrust_prct);
+            let mut record = MnvrRecord {
+                requested,
+                executed: Vec::new(),
+                throttle,
+                outcome: MnvrOutcome::Nominal,
+            };
+
+            if throttle == 0.0 && requested.thrust_prct > 0.0 {
+                record.outcome = MnvrOutcome::SkippedBelowMinThrottle {
+                    throttle: requested.thrust_prct,
+                    min_throttle: limits.min_throttle.unwrap(),
+                };
+                warn!("{record}");
+                records.push(record);
+                continue;
+            }
+
+            let impulse_N_s = thruster.impulse_N_s(throttle, requested.duration());
+            if let Some(min_impulse_bit_N_s) = limits.min_impulse_bit_N_s {
+                if impulse_N_s < min_impulse_bit_N_s {
+                    record.outcome = MnvrOutcome::SkippedBelowMinImpulseBit {
+                        impulse_N_s,
+                        min_impulse_bit_N_s,
+                    };
+                    warn!("{record}");
+                    records.push(record);
+                    continue;
+                }
+            }
+
+            let mut firing = requested;
+            firing.thrust_prct = throttle;
+
+            match limits.max_firing_duration {
+                Some(max_firing) if requested.duration() > max_firing => {
+                    let cooldown = limits.cooldown.unwrap_or(Duration::ZERO);
+                    let mut burnt = Duration::ZERO;
+                    let mut start = requested.start;
+                    while burnt < requested.duration() {
+                        let remaining = requested.duration() - burnt;
+                        let firing_dur = if remaining > max_firing {
+                            max_firing
+                        } else {
+                            remaining
+                        };
+                        let offset_s = burnt.to_seconds();
+                        record.executed.push(Mnvr {
+                            start,
+                            end: start + firing_dur,
+                            alpha_inplane_radians: shifted(firing.alpha_inplane_radians, offset_s),
+                            delta_outofplane_radians: shifted(
+                                firing.delta_outofplane_radians,
+                                offset_s,
+                            ),
+                            ..firing
+                        });
+                        burnt += firing_dur;
+                        start += firing_dur + cooldown;
+                    }
+                    record.outcome = MnvrOutcome::Split {
+                        firings: record.executed.len(),
+                        cooldown,
+                    };
+                    info!("{record}");
+                }
+                _ => record.executed.push(firing),
+            }
+
+            executed_mnvrs.extend(record.executed.iter().copied());
+            records.push(record);
+        }
+
+        Arc::new(Self {
+            mnvrs: executed_mnvrs,
+            records,
+        })
     }
 
     /// Find the maneuver with the closest start epoch that is less than or equal to the current epoch
@@ -48,6 +227,19 @@ impl FiniteBurns {
     }
 }
 
+/// Returns the polynomial `p(x + offset)`, used to continue the direction profile of a split maneuver.
+fn shifted(poly: CommonPolynomial, offset: f64) -> CommonPolynomial {
+    match poly {
+        CommonPolynomial::Constant(a) => CommonPolynomial::Constant(a),
+        CommonPolynomial::Linear(a, b) => CommonPolynomial::Linear(a, a * offset + b),
+        CommonPolynomial::Quadratic(a, b, c) => CommonPolynomial::Quadratic(
+            a,
+            2.0 * a * offset + b,
+            a * offset.powi(2) + b * offset + c,
+        ),
+    }
+}
+
 impl fmt::Display for FiniteBurns {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "FiniteBurns with {} maneuvers", self.mnvrs.len())
@@ -78,7 +270,8 @@ impl GuidanceLaw for FiniteBurns {
         match osc.mode() {
             GuidanceMode::Thrust => {
                 if let Some(next_mnvr) = self.maneuver_at(osc.epoch()) {
-                    if next_mnvr.start <= osc.epoch() {
+                    // Coast between maneuvers, e.g. during the cooldown of a split maneuver
+                    if next_mnvr.start <= osc.epoch() && osc.epoch() <= next_mnvr.end {
                         Ok(next_mnvr.thrust_prct)
                     } else {
                         Ok(0.0)
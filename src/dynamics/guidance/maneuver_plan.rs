@@ -0,0 +1,181 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::Almanac;
+
+use super::{GuidanceError, GuidanceLaw, NyxError};
+use crate::cosmic::{GuidanceMode, Spacecraft};
+use crate::linalg::Vector3;
+use crate::time::Epoch;
+use crate::State;
+use std::fmt;
+use std::sync::Arc;
+
+/// A window of a maneuver plan, during which the provided guidance law controls the spacecraft.
+#[derive(Clone)]
+pub struct ManeuverWindow {
+    /// Start epoch of this window
+    pub start: Epoch,
+    /// End epoch of this window (excluded)
+    pub end: Epoch,
+    /// Guidance law executed during this window
+    pub law: Arc<dyn GuidanceLaw>,
+}
+
+impl ManeuverWindow {
+    /// Returns whether the provided epoch is within this window (the end is excluded)
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        (self.start..self.end).contains(&epoch)
+    }
+}
+
+impl fmt::Display for ManeuverWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} for {} (until {}): {}",
+            self.start,
+            self.end - self.start,
+            self.end,
+            self.law
+        )
+    }
+}
+
+/// A maneuver plan is a list of windows, each executing its own guidance law, e.g. an apogee raising burn followed by
+/// an inclination change burn a few revolutions later. The spacecraft coasts outside of these windows.
+///
+/// The plan sets the guidance mode of the spacecraft at the end of each integration step: a window starting in the
+/// middle of a step is only executed from the end of that step. Use a fixed step integrator whose steps fall on the
+/// window bounds, or pin the `epochs` of the plan in the propagator, if the burns must start exactly on time.
+#[derive(Clone)]
+pub struct ManeuverPlan {
+    /// Windows of this plan, in chronological order and without overlaps
+    pub windows: Vec<ManeuverWindow>,
+}
+
+impl ManeuverPlan {
+    /// Builds a plan from the provided (start, end, guidance law) windows, which are sorted and validated.
+    /// Note: this returns an Arc so it can be plugged into the Spacecraft dynamics directly.
+    pub fn new(windows: Vec<(Epoch, Epoch, Arc<dyn GuidanceLaw>)>) -> Result<Arc<Self>, NyxError> {
+        let mut windows = windows
+            .into_iter()
+            .map(|(start, end, law)| ManeuverWindow { start, end, law })
+            .collect::<Vec<ManeuverWindow>>();
+        windows.sort_by_key(|window| window.start);
+
+        let me = Self { windows };
+        me.validate()?;
+        Ok(Arc::new(me))
+    }
+
+    /// Checks that the windows are chronological and do not overlap.
+    pub fn validate(&self) -> Result<(), NyxError> {
+        for (ii, window) in self.windows.iter().enumerate() {
+            if window.end <= window.start {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!(
+                        "maneuver window #{ii} ends ({}) before it starts ({})",
+                        window.end, window.start
+                    ),
+                });
+            }
+        }
+        for (ii, pair) in self.windows.windows(2).enumerate() {
+            if pair[1].start < pair[0].end {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!(
+                        "maneuver window #{} starting {} overlaps with #{ii} ending {}",
+                        ii + 1,
+                        pair[1].start,
+                        pair[0].end
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the start and end epochs of each window, e.g. to pin them in the propagator with `PropInstance::pin_epochs`
+    /// so that each burn starts and ends exactly on time.
+    pub fn epochs(&self) -> Vec<Epoch> {
+        self.windows
+            .iter()
+            .flat_map(|window| [window.start, window.end])
+            .collect()
+    }
+
+    /// Returns the window active at the provided epoch, if any.
+    pub fn window_at(&self, epoch: Epoch) -> Option<&ManeuverWindow> {
+        self.windows
+            .iter()
+            .take_while(|window| window.start <= epoch)
+            .last()
+            .filter(|window| window.contains(epoch))
+    }
+}
+
+impl fmt::Display for ManeuverPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ManeuverPlan with {} windows", self.windows.len())?;
+        for window in &self.windows {
+            write!(f, "\n\t{window}")?;
+        }
+        Ok(())
+    }
+}
+
+impl GuidanceLaw for ManeuverPlan {
+    fn direction(&self, osc: &Spacecraft) -> Result<Vector3<f64>, GuidanceError> {
+        match osc.mode() {
+            GuidanceMode::Thrust => match self.window_at(osc.epoch()) {
+                Some(window) => window.law.direction(osc),
+                None => Ok(Vector3::zeros()),
+            },
+            _ => Ok(Vector3::zeros()),
+        }
+    }
+
+    fn throttle(&self, osc: &Spacecraft) -> Result<f64, GuidanceError> {
+        match osc.mode() {
+            GuidanceMode::Thrust => match self.window_at(osc.epoch()) {
+                Some(window) => window.law.throttle(osc),
+                None => Ok(0.0),
+            },
+            _ => Ok(0.0),
+        }
+    }
+
+    fn next(&self, sc: &mut Spacecraft, almanac: Arc<Almanac>) {
+        match self.window_at(sc.epoch()) {
+            Some(window) => {
+                // The law of the window may decide to coast, e.g. if its objectives are achieved
+                sc.mut_mode(GuidanceMode::Thrust);
+                window.law.next(sc, almanac)
+            }
+            None => sc.mut_mode(GuidanceMode::Coast),
+        }
+    }
+
+    fn achieved(&self, osc: &Spacecraft) -> Result<bool, GuidanceError> {
+        match self.window_at(osc.epoch()) {
+            Some(window) => window.law.achieved(osc),
+            None => Err(GuidanceError::NoGuidanceObjectiveDefined),
+        }
+    }
+}
@@ -23,17 +23,28 @@ use anise::astro::PhysicsResult;
 use anise::errors::PhysicsError;
 use anise::math::rotation::DCM;
 use anise::prelude::Almanac;
+use hifitime::Duration;
 use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
 
 mod finiteburns;
-pub use finiteburns::FiniteBurns;
+pub use finiteburns::{FiniteBurns, MnvrOutcome, MnvrRecord};
+
+mod maneuver_plan;
+pub use maneuver_plan::{ManeuverPlan, ManeuverWindow};
 
 mod mnvr;
 pub use mnvr::Mnvr;
 
+mod power_table;
+pub use power_table::{PowerPoint, PowerSource, ThrusterPowerTable};
+
 mod ruggiero;
 pub use ruggiero::{Objective, Ruggiero, StateParameter};
+
+mod thrust_table;
 use snafu::Snafu;
+pub use thrust_table::{TableGap, ThrustDirection, ThrustSegment, ThrustTable};
 
 use std::fmt;
 use std::sync::Arc;
@@ -42,9 +53,13 @@ use std::sync::Arc;
 use pyo3::prelude::*;
 
 /// Defines a thruster with a maximum isp and a maximum thrust.
+///
+/// Use the builder to validate the configuration, e.g. `Thruster::builder().thrust_N(1.0).isp_s(220.0).build()?`.
+/// The operational limits of the thruster, if any, are set in the `thruster_limits` of the spacecraft, cf. [ThrusterLimits].
 #[cfg_attr(feature = "python", pyclass)]
 #[allow(non_snake_case)]
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, TypedBuilder)]
+#[builder(build_method(into = ThrusterBuildResult))]
 pub struct Thruster {
     /// The thrust is to be provided in Newtons
     pub thrust_N: f64,
@@ -52,6 +67,16 @@ pub struct Thruster {
     pub isp_s: f64,
 }
 
+/// Result of the thruster builder, which validates the thruster configuration.
+pub type ThrusterBuildResult = Result<Thruster, NyxError>;
+
+impl From<Thruster> for ThrusterBuildResult {
+    fn from(thruster: Thruster) -> Self {
+        thruster.validate()?;
+        Ok(thruster)
+    }
+}
+
 #[cfg_attr(feature = "python", pymethods)]
 impl Thruster {
     /// Returns the exhaust velocity v_e in meters per second
@@ -68,6 +93,133 @@ impl Thruster {
     }
 }
 
+impl Thruster {
+    /// Returns the total impulse in Newton seconds of a firing at the provided throttle for the provided duration.
+    #[allow(non_snake_case)]
+    pub fn impulse_N_s(&self, throttle: f64, duration: Duration) -> f64 {
+        throttle * self.thrust_N * duration.to_seconds().abs()
+    }
+
+    /// Checks that the thrust and Isp are strictly positive.
+    pub fn validate(&self) -> Result<(), NyxError> {
+        if !(self.thrust_N > 0.0 && self.thrust_N.is_finite()) {
+            return Err(NyxError::GuidanceConfigError {
+                msg: format!(
+                    "thrust must be strictly positive but got {} N",
+                    self.thrust_N
+                ),
+            });
+        }
+        if !(self.isp_s > 0.0 && self.isp_s.is_finite()) {
+            return Err(NyxError::GuidanceConfigError {
+                msg: format!("Isp must be strictly positive but got {} s", self.isp_s),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Operational limits of a thruster: minimum throttle, minimum impulse bit, and maximum continuous firing duration.
+///
+/// Use the builder to validate the configuration, e.g. `ThrusterLimits::builder().min_throttle(0.1).build()?`.
+/// The default has no limits.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize, TypedBuilder)]
+#[builder(build_method(into = ThrusterLimitsBuildResult))]
+pub struct ThrusterLimits {
+    /// Minimum throttle level in ]0; 1] at which this thruster can fire, if any
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub min_throttle: Option<f64>,
+    /// How throttle levels commanded below the minimum throttle are handled
+    #[builder(default)]
+    #[serde(default)]
+    pub min_throttle_policy: MinThrottlePolicy,
+    /// Minimum impulse bit in Newton seconds: finite burns with a smaller total impulse are skipped
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub min_impulse_bit_N_s: Option<f64>,
+    /// Maximum continuous firing duration: longer finite burns are split into several firings
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub max_firing_duration: Option<Duration>,
+    /// Cooldown between the firings of a split finite burn, defaults to zero
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub cooldown: Option<Duration>,
+}
+
+/// Defines how a throttle level commanded below the minimum throttle of a thruster is executed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MinThrottlePolicy {
+    /// The thruster does not fire
+    #[default]
+    ClampToZero,
+    /// The thruster fires at its minimum throttle
+    ClampToMin,
+}
+
+/// Result of the thruster limits builder, which validates the consistency of the limits.
+pub type ThrusterLimitsBuildResult = Result<ThrusterLimits, NyxError>;
+
+impl From<ThrusterLimits> for ThrusterLimitsBuildResult {
+    fn from(limits: ThrusterLimits) -> Self {
+        limits.validate()?;
+        Ok(limits)
+    }
+}
+
+impl ThrusterLimits {
+    /// Returns the throttle level executed by the thruster when the provided throttle is commanded,
+    /// accounting for the minimum throttle and its policy.
+    pub fn limited_throttle(&self, throttle: f64) -> f64 {
+        match self.min_throttle {
+            Some(min_throttle) if throttle > 0.0 && throttle < min_throttle => {
+                match self.min_throttle_policy {
+                    MinThrottlePolicy::ClampToZero => 0.0,
+                    MinThrottlePolicy::ClampToMin => min_throttle,
+                }
+            }
+            _ => throttle,
+        }
+    }
+
+    /// Checks that the limits are consistent.
+    pub fn validate(&self) -> Result<(), NyxError> {
+        if let Some(min_throttle) = self.min_throttle {
+            if !(min_throttle > 0.0 && min_throttle <= 1.0) {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!("minimum throttle must be in ]0; 1] but got {min_throttle}"),
+                });
+            }
+        }
+        if let Some(mib) = self.min_impulse_bit_N_s {
+            if !(mib > 0.0 && mib.is_finite()) {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!("minimum impulse bit must be strictly positive but got {mib} N s"),
+                });
+            }
+        }
+        if let Some(max_firing) = self.max_firing_duration {
+            if max_firing <= Duration::ZERO {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!(
+                        "maximum firing duration must be strictly positive but got {max_firing}"
+                    ),
+                });
+            }
+        }
+        if let Some(cooldown) = self.cooldown {
+            if cooldown < Duration::ZERO {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!("cooldown must be positive but got {cooldown}"),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
 /// The `GuidanceLaw` trait handles guidance laws, optimizations, and other such methods for
 /// controlling the overall thrust direction when tied to a `BaseSpacecraft`. For delta V control,
 /// tie the DeltaVctrl to a MissionArc.
@@ -161,10 +313,12 @@ pub enum GuidanceError {
     InvalidControl { param: StateParameter },
     #[snafu(display("guidance encountered {source}"))]
     GuidState { source: StateError },
+    #[snafu(display("thruster power table has no operating point"))]
+    EmptyPowerTable,
 }
 
 /// Local frame options, used notably for guidance laws.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LocalFrame {
     Inertial,
     RIC,
@@ -184,6 +338,14 @@ impl LocalFrame {
             LocalFrame::RCN => state.dcm_from_rcn_to_inertial(),
         }
     }
+
+    /// Expresses the provided state in this local frame, built from that same state.
+    ///
+    /// This is the local frame counterpart of the frame transformations of the Almanac, and the velocity accounts
+    /// for the rotation of the local frame like for rotating celestial frames.
+    pub fn to_local(&self, state: Orbit) -> PhysicsResult<Orbit> {
+        self.dcm_to_inertial(state)?.transpose() * state
+    }
 }
 
 #[test]
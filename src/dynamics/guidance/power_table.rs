@@ -0,0 +1,303 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::constants::frames::SUN_J2000;
+use anise::prelude::Almanac;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use super::{GuidanceError, NyxError, Thruster};
+use crate::cosmic::{Spacecraft, AU};
+use crate::dynamics::{DynamicsAlmanacSnafu, DynamicsError, DynamicsGuidanceSnafu};
+use crate::io::ConfigRepr;
+use std::fmt;
+use std::sync::Arc;
+
+/// Operating point of an electric thruster at a given input power.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PowerPoint {
+    /// Input power in kW
+    pub power_kW: f64,
+    /// Thrust at this power, in Newtons
+    pub thrust_N: f64,
+    /// Isp at this power, in seconds
+    pub isp_s: f64,
+}
+
+/// Source of the power available to the thruster.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PowerSource {
+    /// Constant input power, in kW
+    Constant { power_kW: f64 },
+    /// Solar array whose power decreases with the square of the distance to the Sun, power provided in kW at 1 AU
+    SolarArray { power_at_1au_kW: f64 },
+}
+
+/// Thrust and Isp of an electric thruster as a function of its input power.
+///
+/// The operating point is linearly interpolated in the table at each call of the equations of motion, and clamped to the
+/// first and last points of the table outside of its power range. When plugged into the spacecraft dynamics, this table
+/// overrides the thrust and Isp of the thruster of the spacecraft.
+///
+/// Deserialization, e.g. `ThrusterPowerTable::load`, sorts and validates the operating points like `ThrusterPowerTable::new`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "PowerTableSerde")]
+pub struct ThrusterPowerTable {
+    /// Operating points of the thruster, sorted by increasing power
+    pub points: Vec<PowerPoint>,
+    /// Source of the input power
+    pub source: PowerSource,
+}
+
+/// Unvalidated representation of a power table, as deserialized.
+#[derive(Deserialize)]
+struct PowerTableSerde {
+    points: Vec<PowerPoint>,
+    source: PowerSource,
+}
+
+impl TryFrom<PowerTableSerde> for ThrusterPowerTable {
+    type Error = NyxError;
+
+    fn try_from(table: PowerTableSerde) -> Result<Self, Self::Error> {
+        let mut points = table.points;
+        points.sort_by(|a, b| a.power_kW.total_cmp(&b.power_kW));
+        let me = Self {
+            points,
+            source: table.source,
+        };
+        me.validate()?;
+        Ok(me)
+    }
+}
+
+impl ThrusterPowerTable {
+    /// Builds a power table from the provided operating points, which are sorted and validated.
+    /// Note: this returns an Arc so it can be plugged into the Spacecraft dynamics directly.
+    pub fn new(points: Vec<PowerPoint>, source: PowerSource) -> Result<Arc<Self>, NyxError> {
+        Self::try_from(PowerTableSerde { points, source }).map(Arc::new)
+    }
+
+    /// Checks that the table is not empty, that its powers are unique, and that its thrusts and Isps are positive.
+    pub fn validate(&self) -> Result<(), NyxError> {
+        if self.points.is_empty() {
+            return Err(NyxError::GuidanceConfigError {
+                msg: "thruster power table is empty".to_string(),
+            });
+        }
+        for (ii, point) in self.points.iter().enumerate() {
+            if point.thrust_N < 0.0 || point.isp_s <= 0.0 {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!(
+                        "thruster power point #{ii} must have a non-negative thrust and a positive Isp, got {} N and {} s",
+                        point.thrust_N, point.isp_s
+                    ),
+                });
+            }
+        }
+        for (ii, pair) in self.points.windows(2).enumerate() {
+            if pair[1].power_kW <= pair[0].power_kW {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!(
+                        "thruster power points #{ii} and #{} are not sorted by strictly increasing power",
+                        ii + 1
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the thrust and Isp of the thruster at the provided input power, in kW.
+    /// Errors if the table has no operating point, which is only possible if its points were modified after validation.
+    #[allow(non_snake_case)]
+    pub fn operating_point(&self, power_kW: f64) -> Result<Thruster, GuidanceError> {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(GuidanceError::EmptyPowerTable),
+        };
+        if power_kW <= first.power_kW {
+            return Ok(Thruster {
+                thrust_N: first.thrust_N,
+                isp_s: first.isp_s,
+            });
+        } else if power_kW >= last.power_kW {
+            return Ok(Thruster {
+                thrust_N: last.thrust_N,
+                isp_s: last.isp_s,
+            });
+        }
+
+        let idx = self
+            .points
+            .iter()
+            .position(|point| point.power_kW > power_kW)
+            .unwrap();
+        let (lo, hi) = (self.points[idx - 1], self.points[idx]);
+        let t = (power_kW - lo.power_kW) / (hi.power_kW - lo.power_kW);
+
+        Ok(Thruster {
+            thrust_N: lo.thrust_N + t * (hi.thrust_N - lo.thrust_N),
+            isp_s: lo.isp_s + t * (hi.isp_s - lo.isp_s),
+        })
+    }
+
+    /// Returns the power available to the thruster of the provided spacecraft, in kW.
+    #[allow(non_snake_case)]
+    pub fn power_kW(&self, osc: &Spacecraft, almanac: Arc<Almanac>) -> Result<f64, DynamicsError> {
+        match self.source {
+            PowerSource::Constant { power_kW } => Ok(power_kW),
+            PowerSource::SolarArray { power_at_1au_kW } => {
+                let helio = almanac.transform_to(osc.orbit, SUN_J2000, None).context(
+                    DynamicsAlmanacSnafu {
+                        action: "computing heliocentric distance for thruster power",
+                    },
+                )?;
+                Ok(power_at_1au_kW * (AU / helio.rmag_km()).powi(2))
+            }
+        }
+    }
+
+    /// Returns the thrust and Isp of the thruster of the provided spacecraft given the power available to it.
+    pub fn thruster_at(
+        &self,
+        osc: &Spacecraft,
+        almanac: Arc<Almanac>,
+    ) -> Result<Thruster, DynamicsError> {
+        self.operating_point(self.power_kW(osc, almanac)?)
+            .context(DynamicsGuidanceSnafu)
+    }
+}
+
+impl ConfigRepr for ThrusterPowerTable {}
+
+impl fmt::Display for ThrusterPowerTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ThrusterPowerTable with {} points ({:?})",
+            self.points.len(),
+            self.source
+        )
+    }
+}
+
+#[cfg(test)]
+mod ut_power_table {
+    use super::*;
+
+    #[test]
+    fn interpolation_and_validation() {
+        let table = ThrusterPowerTable::new(
+            vec![
+                PowerPoint {
+                    power_kW: 4.5,
+                    thrust_N: 0.24,
+                    isp_s: 2500.0,
+                },
+                PowerPoint {
+                    power_kW: 0.5,
+                    thrust_N: 0.02,
+                    isp_s: 1200.0,
+                },
+                PowerPoint {
+                    power_kW: 1.5,
+                    thrust_N: 0.06,
+                    isp_s: 1600.0,
+                },
+            ],
+            PowerSource::Constant { power_kW: 1.0 },
+        )
+        .unwrap();
+
+        // Points are sorted
+        assert_eq!(table.points[0].power_kW, 0.5);
+
+        let mid = table.operating_point(1.0).unwrap();
+        assert!((mid.thrust_N - 0.04).abs() < 1e-12);
+        assert!((mid.isp_s - 1400.0).abs() < 1e-9);
+
+        let upper = table.operating_point(3.0).unwrap();
+        assert!((upper.thrust_N - 0.15).abs() < 1e-12);
+        assert!((upper.isp_s - 2050.0).abs() < 1e-9);
+
+        // Clamped outside of the table
+        assert_eq!(table.operating_point(0.1).unwrap().thrust_N, 0.02);
+        assert_eq!(table.operating_point(10.0).unwrap().isp_s, 2500.0);
+
+        let yaml = serde_yaml::to_string(table.as_ref()).unwrap();
+        let loaded: ThrusterPowerTable = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(&loaded, table.as_ref());
+
+        // Duplicated powers are rejected
+        assert!(ThrusterPowerTable::new(
+            vec![
+                PowerPoint {
+                    power_kW: 1.0,
+                    thrust_N: 0.02,
+                    isp_s: 1200.0,
+                },
+                PowerPoint {
+                    power_kW: 1.0,
+                    thrust_N: 0.06,
+                    isp_s: 1600.0,
+                },
+            ],
+            PowerSource::Constant { power_kW: 1.0 },
+        )
+        .is_err());
+
+        // Empty tables are rejected
+        assert!(ThrusterPowerTable::new(vec![], PowerSource::Constant { power_kW: 1.0 }).is_err());
+        let empty = ThrusterPowerTable {
+            points: vec![],
+            source: PowerSource::Constant { power_kW: 1.0 },
+        };
+        assert_eq!(
+            empty.operating_point(1.0),
+            Err(GuidanceError::EmptyPowerTable)
+        );
+
+        // Loading validates and sorts the table
+        assert!(serde_yaml::from_str::<ThrusterPowerTable>(
+            "points: []\nsource:\n  Constant:\n    power_kW: 1.0\n"
+        )
+        .is_err());
+        let unsorted: ThrusterPowerTable = serde_yaml::from_str(
+            r#"
+points:
+  - power_kW: 4.5
+    thrust_N: 0.24
+    isp_s: 2500.0
+  - power_kW: 0.5
+    thrust_N: 0.02
+    isp_s: 1200.0
+  - power_kW: 1.5
+    thrust_N: 0.06
+    isp_s: 1600.0
+source:
+  Constant:
+    power_kW: 1.0
+"#,
+        )
+        .unwrap();
+        assert_eq!(&unsorted, table.as_ref());
+    }
+}
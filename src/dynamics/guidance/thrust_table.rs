@@ -0,0 +1,302 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::Almanac;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use super::{GuidanceError, GuidanceLaw, GuidancePhysicsSnafu, LocalFrame, NyxError};
+use crate::cosmic::{GuidanceMode, Spacecraft};
+use crate::io::{duration_from_str, duration_to_str, epoch_from_str, epoch_to_str, ConfigRepr};
+use crate::linalg::Vector3;
+use crate::time::{Duration, Epoch};
+use crate::State;
+use std::fmt;
+use std::sync::Arc;
+
+/// Direction of the thrust during a segment of a thrust table.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ThrustDirection {
+    /// Unit vector in the inertial frame of the spacecraft state (it will be normalized)
+    Inertial { x: f64, y: f64, z: f64 },
+    /// Pitch (in-plane, towards +C) and yaw (out-of-plane, towards +N) angles from the velocity vector, in the VNC frame, in degrees
+    Vnc { pitch_deg: f64, yaw_deg: f64 },
+}
+
+impl ThrustDirection {
+    /// Returns the unit vector of this direction in the inertial frame of the provided state
+    pub fn to_inertial(&self, osc: &Spacecraft) -> Result<Vector3<f64>, GuidanceError> {
+        match *self {
+            Self::Inertial { x, y, z } => Ok(Vector3::new(x, y, z).normalize()),
+            Self::Vnc { pitch_deg, yaw_deg } => {
+                let (pitch, yaw) = (pitch_deg.to_radians(), yaw_deg.to_radians());
+                let vnc = Vector3::new(pitch.cos() * yaw.cos(), yaw.sin(), pitch.sin() * yaw.cos());
+                Ok(LocalFrame::VNC
+                    .dcm_to_inertial(osc.orbit)
+                    .context(GuidancePhysicsSnafu {
+                        action: "computing VNC frame",
+                    })?
+                    * vnc)
+            }
+        }
+    }
+}
+
+/// A segment of a thrust table, during which the thrust direction and throttle are constant.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThrustSegment {
+    /// Start epoch of this segment
+    #[serde(serialize_with = "epoch_to_str", deserialize_with = "epoch_from_str")]
+    pub start: Epoch,
+    /// Duration of this segment
+    #[serde(
+        serialize_with = "duration_to_str",
+        deserialize_with = "duration_from_str"
+    )]
+    pub duration: Duration,
+    /// Thrust direction during this segment
+    pub direction: ThrustDirection,
+    /// Throttle level between 0.0 and 1.0
+    pub throttle: f64,
+}
+
+impl ThrustSegment {
+    /// End epoch of this segment
+    pub fn end(&self) -> Epoch {
+        self.start + self.duration
+    }
+
+    /// Returns whether the provided epoch is within this segment (the end is excluded)
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        (self.start..self.end()).contains(&epoch)
+    }
+}
+
+/// Behavior of a thrust table between two segments or after the last one.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TableGap {
+    /// Coast between segments (default)
+    #[default]
+    Coast,
+    /// Keep the direction and throttle of the previous segment
+    HoldPrevious,
+}
+
+/// A guidance law executing a table of piecewise constant thrust segments, e.g. "thrust along +V for ten minutes, then at 30 degrees pitch for five minutes".
+///
+/// Nothing is interpolated between segments. Thrust tables may be loaded from YAML, in which case `validate` must be called prior to use.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThrustTable {
+    /// Segments of this table, in chronological order and without overlaps
+    pub segments: Vec<ThrustSegment>,
+    /// Behavior between segments
+    #[serde(default)]
+    pub gap: TableGap,
+}
+
+impl ThrustTable {
+    /// Builds a thrust table from the provided segments, which are sorted and validated.
+    /// Note: this returns an Arc so it can be plugged into the Spacecraft dynamics directly.
+    pub fn new(mut segments: Vec<ThrustSegment>, gap: TableGap) -> Result<Arc<Self>, NyxError> {
+        segments.sort_by_key(|seg| seg.start);
+        let me = Self { segments, gap };
+        me.validate()?;
+        Ok(Arc::new(me))
+    }
+
+    /// Builds a thrust table from segments defined as (start offset, duration, direction, throttle) from the reference epoch.
+    pub fn from_elapsed(
+        reference: Epoch,
+        segments: &[(Duration, Duration, ThrustDirection, f64)],
+        gap: TableGap,
+    ) -> Result<Arc<Self>, NyxError> {
+        Self::new(
+            segments
+                .iter()
+                .map(|(offset, duration, direction, throttle)| ThrustSegment {
+                    start: reference + *offset,
+                    duration: *duration,
+                    direction: *direction,
+                    throttle: *throttle,
+                })
+                .collect(),
+            gap,
+        )
+    }
+
+    /// Checks that the segments are chronological, do not overlap, and that their throttle is between 0 and 1.
+    pub fn validate(&self) -> Result<(), NyxError> {
+        for (ii, seg) in self.segments.iter().enumerate() {
+            if seg.duration.is_negative() {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!("thrust segment #{ii} is anti-chronological"),
+                });
+            }
+            if !(0.0..=1.0).contains(&seg.throttle) {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!(
+                        "thrust segment #{ii} throttle must be between 0 and 1, got {}",
+                        seg.throttle
+                    ),
+                });
+            }
+            if let ThrustDirection::Inertial { x, y, z } = seg.direction {
+                if Vector3::new(x, y, z).norm() < f64::EPSILON {
+                    return Err(NyxError::GuidanceConfigError {
+                        msg: format!("thrust segment #{ii} has a zero direction vector"),
+                    });
+                }
+            }
+        }
+        for (ii, pair) in self.segments.windows(2).enumerate() {
+            if pair[1].start < pair[0].end() {
+                return Err(NyxError::GuidanceConfigError {
+                    msg: format!(
+                        "thrust segment #{} starting {} overlaps with #{ii} ending {}",
+                        ii + 1,
+                        pair[1].start,
+                        pair[0].end()
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the segment to execute at the provided epoch, accounting for the gap behavior.
+    pub fn segment_at(&self, epoch: Epoch) -> Option<&ThrustSegment> {
+        // Find the last segment starting before or at this epoch
+        let seg = self
+            .segments
+            .iter()
+            .take_while(|seg| seg.start <= epoch)
+            .last()?;
+        if seg.contains(epoch) || self.gap == TableGap::HoldPrevious {
+            Some(seg)
+        } else {
+            None
+        }
+    }
+}
+
+impl ConfigRepr for ThrustTable {}
+
+impl fmt::Display for ThrustTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ThrustTable with {} segments ({:?} in gaps)",
+            self.segments.len(),
+            self.gap
+        )
+    }
+}
+
+impl GuidanceLaw for ThrustTable {
+    fn direction(&self, osc: &Spacecraft) -> Result<Vector3<f64>, GuidanceError> {
+        match osc.mode() {
+            GuidanceMode::Thrust => match self.segment_at(osc.epoch()) {
+                Some(seg) => seg.direction.to_inertial(osc),
+                None => Ok(Vector3::zeros()),
+            },
+            _ => Ok(Vector3::zeros()),
+        }
+    }
+
+    fn throttle(&self, osc: &Spacecraft) -> Result<f64, GuidanceError> {
+        match osc.mode() {
+            GuidanceMode::Thrust => match self.segment_at(osc.epoch()) {
+                Some(seg) => Ok(seg.throttle),
+                None => Ok(0.0),
+            },
+            _ => Ok(0.0),
+        }
+    }
+
+    fn next(&self, sc: &mut Spacecraft, _almanac: Arc<Almanac>) {
+        if self.segment_at(sc.epoch()).is_some() {
+            sc.mut_mode(GuidanceMode::Thrust)
+        } else {
+            sc.mut_mode(GuidanceMode::Coast)
+        }
+    }
+}
+
+#[cfg(test)]
+mod ut_thrust_table {
+    use super::*;
+    use crate::time::TimeUnits;
+
+    #[test]
+    fn validation_and_serde() {
+        let start = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+        let along_v = ThrustDirection::Vnc {
+            pitch_deg: 0.0,
+            yaw_deg: 0.0,
+        };
+        let pitched = ThrustDirection::Vnc {
+            pitch_deg: 30.0,
+            yaw_deg: 0.0,
+        };
+
+        let table = ThrustTable::from_elapsed(
+            start,
+            &[
+                (0.minutes(), 10.minutes(), along_v, 1.0),
+                (15.minutes(), 5.minutes(), pitched, 0.5),
+            ],
+            TableGap::Coast,
+        )
+        .unwrap();
+
+        assert_eq!(
+            table.segment_at(start + 5.minutes()).unwrap().direction,
+            along_v
+        );
+        assert!(table.segment_at(start + 12.minutes()).is_none());
+        assert_eq!(
+            table.segment_at(start + 16.minutes()).unwrap().throttle,
+            0.5
+        );
+        assert!(table.segment_at(start + 20.minutes()).is_none());
+        assert!(table.segment_at(start - 1.minutes()).is_none());
+
+        let yaml = serde_yaml::to_string(table.as_ref()).unwrap();
+        let loaded: ThrustTable = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(&loaded, table.as_ref());
+
+        // Overlapping segments are rejected
+        assert!(ThrustTable::from_elapsed(
+            start,
+            &[
+                (0.minutes(), 10.minutes(), along_v, 1.0),
+                (5.minutes(), 5.minutes(), pitched, 1.0),
+            ],
+            TableGap::Coast,
+        )
+        .is_err());
+
+        // Invalid throttle is rejected
+        assert!(ThrustTable::from_elapsed(
+            start,
+            &[(0.minutes(), 10.minutes(), along_v, 1.5)],
+            TableGap::Coast,
+        )
+        .is_err());
+    }
+}
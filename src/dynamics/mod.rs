@@ -16,6 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::cosmic::attitude::AttitudeError;
 use crate::cosmic::{AstroError, Orbit};
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, DimName, Matrix3, Matrix4x3, OMatrix, OVector, Vector3};
@@ -66,6 +67,14 @@ pub use self::drag::*;
 pub mod sph_harmonics;
 pub use self::sph_harmonics::*;
 
+/// Named force model stacks for the usual orbital regimes.
+pub mod presets;
+pub use self::presets::{moon_pa_frame, DynamicsPreset, PresetOptions};
+
+/// The circular restricted three-body problem: libration points, periodic orbits and conversion to the ephemeris model.
+pub mod cr3bp;
+pub use self::cr3bp::{Cr3bp, Cr3bpDynamics, Cr3bpPeriodicOrbit, LibrationPoint};
+
 /// The `Dynamics` trait handles and stores any equation of motion *and* the state is integrated.
 ///
 /// Its design is such that several of the provided dynamics can be combined fairly easily. However,
@@ -137,21 +146,101 @@ where
 /// The `ForceModel` trait handles immutable dynamics which return a force. Those will be divided by the mass of the spacecraft to compute the acceleration (F = ma).
 ///
 /// Examples include Solar Radiation Pressure, drag, etc., i.e. forces which do not need to save the current state, only act on it.
+///
+/// Custom force models may be added to the spacecraft dynamics with [SpacecraftDynamics::with_force_model]. The `Display`
+/// implementation is used as the name of the model in logs and errors. Only `eom` must be implemented: if the model does
+/// not provide its partials, these are computed by finite differencing (cf. [finite_difference_partials]).
 pub trait ForceModel: Send + Sync + fmt::Display {
     /// If a parameter of this force model is stored in the spacecraft state, then this function should return the index where this parameter is being affected
-    fn estimation_index(&self) -> Option<usize>;
+    fn estimation_index(&self) -> Option<usize> {
+        None
+    }
 
     /// Defines the equations of motion for this force model from the provided osculating state.
     fn eom(&self, ctx: &Spacecraft, almanac: Arc<Almanac>) -> Result<Vector3<f64>, DynamicsError>;
 
-    /// Force models must implement their partials, although those will only be called if the propagation requires the
-    /// computation of the STM. The `osc_ctx` is the osculating context, i.e. it changes for each sub-step of the integrator.
+    /// Returns the acceleration in km/s^2 imparted by this force model on the provided spacecraft, i.e. its force divided by the total mass of the spacecraft.
+    fn accel(
+        &self,
+        ctx: &Spacecraft,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vector3<f64>, DynamicsError> {
+        Ok(self.eom(ctx, almanac)? / ctx.mass_kg())
+    }
+
+    /// Returns whether this force model implements its partials in `dual_eom`. Models which do not are computed by finite
+    /// differencing, and a warning is logged when they are added to the spacecraft dynamics.
+    fn has_partials(&self) -> bool {
+        false
+    }
+
+    /// Returns the force and its partials, which will only be called if the propagation requires the computation of the STM.
+    /// The `osc_ctx` is the osculating context, i.e. it changes for each sub-step of the integrator.
     /// The last row corresponds to the partials of the parameter of this force model wrt the position, i.e. this only applies to conservative forces.
+    ///
+    /// By default, the partials are computed by finite differencing of `eom`: models overriding this function should also override `has_partials`.
     fn dual_eom(
         &self,
         osc_ctx: &Spacecraft,
         almanac: Arc<Almanac>,
-    ) -> Result<(Vector3<f64>, Matrix4x3<f64>), DynamicsError>;
+    ) -> Result<(Vector3<f64>, Matrix4x3<f64>), DynamicsError> {
+        finite_difference_partials(self, osc_ctx, almanac)
+    }
+}
+
+/// Computes the force of the provided model and its partials by central finite differencing of its `eom`, in the same layout as [ForceModel::dual_eom].
+///
+/// The position is perturbed by one meter. If the model has an estimation index of the coefficient of reflectivity or of drag,
+/// then the last row is the partial of the force with respect to that coefficient; otherwise, it is zero.
+pub fn finite_difference_partials<F: ForceModel + ?Sized>(
+    model: &F,
+    osc_ctx: &Spacecraft,
+    almanac: Arc<Almanac>,
+) -> Result<(Vector3<f64>, Matrix4x3<f64>), DynamicsError> {
+    const POS_STEP_KM: f64 = 1e-3;
+    const COEFF_STEP: f64 = 1e-4;
+
+    let force = model.eom(osc_ctx, almanac.clone())?;
+    let mut grad = Matrix4x3::<f64>::zeros();
+
+    for j in 0..3 {
+        let mut plus = *osc_ctx;
+        plus.orbit.radius_km[j] += POS_STEP_KM;
+        let mut minus = *osc_ctx;
+        minus.orbit.radius_km[j] -= POS_STEP_KM;
+
+        let partial = (model.eom(&plus, almanac.clone())? - model.eom(&minus, almanac.clone())?)
+            / (2.0 * POS_STEP_KM);
+
+        for i in 0..3 {
+            grad[(i, j)] = partial[i];
+        }
+    }
+
+    if let Some(idx) = model.estimation_index() {
+        let mut plus = *osc_ctx;
+        let mut minus = *osc_ctx;
+        match idx {
+            6 => {
+                plus.srp.cr += COEFF_STEP;
+                minus.srp.cr -= COEFF_STEP;
+            }
+            7 => {
+                plus.drag.cd += COEFF_STEP;
+                minus.drag.cd -= COEFF_STEP;
+            }
+            _ => return Ok((force, grad)),
+        }
+
+        let partial =
+            (model.eom(&plus, almanac.clone())? - model.eom(&minus, almanac)?) / (2.0 * COEFF_STEP);
+
+        for j in 0..3 {
+            grad[(3, j)] = partial[j];
+        }
+    }
+
+    Ok((force, grad))
 }
 
 /// The `AccelModel` trait handles immutable dynamics which return an acceleration. Those can be added directly to Orbital Dynamics for example.
@@ -194,4 +283,6 @@ pub enum DynamicsError {
         action: &'static str,
         source: PlanetaryDataError,
     },
+    #[snafu(display("dynamical model encountered an attitude error: {source}"))]
+    DynamicsAttitude { source: AttitudeError },
 }
@@ -0,0 +1,246 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::almanac::Almanac;
+use anise::constants::celestial_objects::{
+    EARTH, JUPITER_BARYCENTER, MARS_BARYCENTER, MERCURY, MOON, NEPTUNE_BARYCENTER,
+    SATURN_BARYCENTER, SUN, URANUS_BARYCENTER, VENUS,
+};
+use anise::constants::frames::{
+    EARTH_J2000, IAU_EARTH_FRAME, IAU_MOON_FRAME, MOON_J2000, MOON_PA_FRAME,
+};
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+use super::{
+    AccelModel, Drag, ForceModel, Harmonics, OrbitalDynamics, PointMasses, SolarPressure,
+    SpacecraftDynamics,
+};
+use crate::cosmic::Frame;
+use crate::io::gravity::HarmonicsMem;
+use crate::io::ConfigError;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Named force model stacks for the usual orbital regimes, cf. `SpacecraftDynamics::preset`.
+///
+/// The accuracy regimes below are indicative and assume a spacecraft whose drag and SRP coefficients and areas are known.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DynamicsPreset {
+    /// `LEO_PRECISE`: 70x70 Earth harmonics (JGM3), standard atmosphere 1976 drag, SRP shadowed by the Earth and the Moon, Sun and Moon point masses.
+    ///
+    /// Intended for precise orbit determination and propagation in low Earth orbit over a few days, at the meter level (dominated by the drag modeling).
+    /// The state must be Earth centered.
+    LeoPrecise,
+    /// `GEO`: 8x8 Earth harmonics (JGM3), SRP shadowed by the Earth and the Moon, Sun and Moon point masses.
+    ///
+    /// Intended for station keeping analyses of geostationary and other high Earth orbits over weeks, at the hundred meter level (dominated by the SRP modeling).
+    /// The state must be Earth centered.
+    Geo,
+    /// `CISLUNAR`: Moon and Sun point masses and 20x20 Moon harmonics (GRAIL), SRP shadowed by the Earth and the Moon.
+    ///
+    /// Intended for transfers and halo orbits in cislunar space, at the kilometer level over a few revolutions. The Moon harmonics only matter near the Moon.
+    /// The state must be Earth centered. The harmonics are computed in the IAU Moon frame unless `moon_gravity_frame` is set: this is only
+    /// an approximation of the principal axes frame of the GRAIL fields, use `LLO` for low lunar orbits.
+    Cislunar,
+    /// `LLO`: 60x60 Moon harmonics (GRAIL) in the Moon principal axes frame, Earth and Sun point masses, SRP shadowed by the Moon and the Earth.
+    ///
+    /// Intended for low lunar orbits over days to weeks. The state must be Moon centered, and the almanac must include the orientation
+    /// of the Moon principal axes frame (e.g. `moon_pa_de440_200625.bpc`), i.e. the DE libration angles.
+    LowLunarOrbit,
+    /// `HELIOCENTRIC`: point masses of all of the planets and the Moon, and SRP without any shadowing body.
+    ///
+    /// Intended for interplanetary cruise phases, far from any planet, at the kilometer level over months.
+    /// The state must be Sun centered.
+    Heliocentric,
+}
+
+impl DynamicsPreset {
+    /// Assembles the dynamics of this preset.
+    pub fn build(
+        self,
+        almanac: Arc<Almanac>,
+        options: PresetOptions,
+    ) -> Result<SpacecraftDynamics, Box<dyn Error>> {
+        let eme2k = almanac.frame_from_uid(EARTH_J2000)?;
+        let moon_j2k = almanac.frame_from_uid(MOON_J2000)?;
+
+        let mut accel_models: Vec<Arc<dyn AccelModel + Sync>> = Vec::new();
+        let mut force_models: Vec<Arc<dyn ForceModel>> = Vec::new();
+
+        match self {
+            Self::LeoPrecise | Self::Geo => {
+                let default_deg = if self == Self::LeoPrecise { 70 } else { 8 };
+                let degree = options.harmonics_degree.unwrap_or(default_deg);
+                let order = options.harmonics_order.unwrap_or(degree);
+
+                let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME)?;
+                let stor =
+                    HarmonicsMem::from_cof(&options.earth_gravity_path, degree, order, true)?;
+
+                accel_models.push(PointMasses::new(vec![MOON, SUN]));
+                accel_models.push(Harmonics::from_stor(iau_earth, stor));
+
+                if self == Self::LeoPrecise {
+                    force_models.push(Drag::std_atm1976(almanac.clone())?);
+                }
+                force_models.push(SolarPressure::new(vec![eme2k, moon_j2k], almanac)?);
+            }
+            Self::Cislunar => {
+                let degree = options.harmonics_degree.unwrap_or(20);
+                let order = options.harmonics_order.unwrap_or(degree);
+
+                let moon_frame = match options.moon_gravity_frame {
+                    Some(frame) => frame,
+                    None => almanac.frame_from_uid(IAU_MOON_FRAME)?,
+                };
+                let stor =
+                    HarmonicsMem::from_shadr(&options.moon_gravity_path, degree, order, true)?;
+
+                accel_models.push(PointMasses::new(vec![MOON, SUN]));
+                accel_models.push(Harmonics::from_stor(moon_frame, stor));
+
+                force_models.push(SolarPressure::new(vec![eme2k, moon_j2k], almanac)?);
+            }
+            Self::LowLunarOrbit => {
+                let degree = options.harmonics_degree.unwrap_or(60);
+                let order = options.harmonics_order.unwrap_or(degree);
+
+                let moon_frame = match options.moon_gravity_frame {
+                    Some(frame) => frame,
+                    None => moon_pa_frame(&almanac)?,
+                };
+                let stor =
+                    HarmonicsMem::from_shadr(&options.moon_gravity_path, degree, order, true)?;
+
+                accel_models.push(PointMasses::new(vec![EARTH, SUN]));
+                accel_models.push(Harmonics::from_stor(moon_frame, stor));
+
+                force_models.push(SolarPressure::new(vec![moon_j2k, eme2k], almanac)?);
+            }
+            Self::Heliocentric => {
+                accel_models.push(PointMasses::new(vec![
+                    MERCURY,
+                    VENUS,
+                    EARTH,
+                    MOON,
+                    MARS_BARYCENTER,
+                    JUPITER_BARYCENTER,
+                    SATURN_BARYCENTER,
+                    URANUS_BARYCENTER,
+                    NEPTUNE_BARYCENTER,
+                ]));
+
+                force_models.push(SolarPressure::new(vec![], almanac)?);
+            }
+        }
+
+        Ok(SpacecraftDynamics::from_models(
+            OrbitalDynamics::new(accel_models),
+            force_models,
+        ))
+    }
+}
+
+impl fmt::Display for DynamicsPreset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LeoPrecise => write!(f, "LEO_PRECISE"),
+            Self::Geo => write!(f, "GEO"),
+            Self::Cislunar => write!(f, "CISLUNAR"),
+            Self::LowLunarOrbit => write!(f, "LLO"),
+            Self::Heliocentric => write!(f, "HELIOCENTRIC"),
+        }
+    }
+}
+
+impl FromStr for DynamicsPreset {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "LEO_PRECISE" => Ok(Self::LeoPrecise),
+            "GEO" => Ok(Self::Geo),
+            "CISLUNAR" => Ok(Self::Cislunar),
+            "LLO" => Ok(Self::LowLunarOrbit),
+            "HELIOCENTRIC" => Ok(Self::Heliocentric),
+            _ => Err(ConfigError::InvalidConfig {
+                msg: format!(
+                    "unknown dynamics preset `{s}`, expected one of LEO_PRECISE, GEO, CISLUNAR, LLO, HELIOCENTRIC"
+                ),
+            }),
+        }
+    }
+}
+
+/// Overrides of the dynamics presets.
+#[derive(Clone, Debug, PartialEq, TypedBuilder)]
+#[builder(doc)]
+pub struct PresetOptions {
+    /// Degree of the harmonics of the Earth (LEO and GEO) or of the Moon (cislunar and LLO), defaults to that of the preset
+    #[builder(default, setter(strip_option))]
+    pub harmonics_degree: Option<usize>,
+    /// Order of the harmonics, defaults to the degree
+    #[builder(default, setter(strip_option))]
+    pub harmonics_order: Option<usize>,
+    /// Path to the gunzipped COF gravity field of the Earth
+    #[builder(default = "data/JGM3.cof.gz".to_string(), setter(into))]
+    pub earth_gravity_path: String,
+    /// Path to the gunzipped SHADR gravity field of the Moon
+    #[builder(default = "data/Luna_jggrx_1500e_sha.tab.gz".to_string(), setter(into))]
+    pub moon_gravity_path: String,
+    /// Frame in which the Moon gravity field is defined, defaults to the IAU Moon frame (cislunar) or the Moon principal axes frame (LLO)
+    #[builder(default, setter(strip_option))]
+    pub moon_gravity_frame: Option<Frame>,
+}
+
+impl Default for PresetOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// Returns the Moon principal axes frame, in which the GRAIL gravity fields are defined.
+///
+/// Its orientation is that of the DE libration angles, which must be loaded in the almanac (e.g. `moon_pa_de440_200625.bpc`).
+/// The gravitational parameter and the shape are those of the Moon in the planetary constants of the almanac.
+pub fn moon_pa_frame(almanac: &Almanac) -> Result<Frame, Box<dyn Error>> {
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000)?;
+    let mut moon_pa = MOON_PA_FRAME;
+    moon_pa.mu_km3_s2 = moon_j2k.mu_km3_s2;
+    moon_pa.shape = match moon_j2k.shape {
+        Some(shape) => Some(shape),
+        None => almanac.frame_from_uid(IAU_MOON_FRAME)?.shape,
+    };
+    Ok(moon_pa)
+}
+
+impl SpacecraftDynamics {
+    /// Builds the dynamics of the named preset (`LEO_PRECISE`, `GEO`, `CISLUNAR`, `LLO`, or `HELIOCENTRIC`), cf. [DynamicsPreset] for their content and intended accuracy.
+    ///
+    /// The returned dynamics may be further modified, e.g. to add a guidance law.
+    pub fn preset(
+        name: &str,
+        almanac: Arc<Almanac>,
+        options: PresetOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        DynamicsPreset::from_str(name)?.build(almanac, options)
+    }
+}
@@ -17,6 +17,7 @@
 */
 
 use super::{DynamicsAlmanacSnafu, DynamicsError, DynamicsPlanetarySnafu, ForceModel};
+use crate::cosmic::attitude::OrientedPlates;
 use crate::cosmic::eclipse::EclipseLocator;
 use crate::cosmic::{Frame, Spacecraft, AU, SPEED_OF_LIGHT_M_S};
 use crate::linalg::{Const, Matrix4x3, Vector3};
@@ -33,6 +34,9 @@ use std::sync::Arc;
 pub const SOLAR_FLUX_W_m2: f64 = 1367.0;
 
 /// Computation of solar radiation pressure is based on STK: http://help.agi.com/stk/index.htm#gator/eq-solar.htm .
+///
+/// The solar flux is scaled by the illumination factor computed by the eclipse locator, so the SRP force is
+/// zero when the spacecraft is in the umbra of any of the shadow bodies, and partially reduced in their penumbra.
 #[derive(Clone)]
 pub struct SolarPressure {
     /// solar flux at 1 AU, in W/m^2
@@ -40,6 +44,8 @@ pub struct SolarPressure {
     pub e_loc: EclipseLocator,
     /// Set to true to estimate the coefficient of reflectivity
     pub estimate: bool,
+    /// Attitude dependent area of the spacecraft, seen from the Sun, used instead of the SRP area of the spacecraft if set
+    pub plates: Option<OrientedPlates>,
 }
 
 impl SolarPressure {
@@ -69,9 +75,31 @@ impl SolarPressure {
             phi: SOLAR_FLUX_W_m2,
             e_loc,
             estimate: true,
+            plates: None,
         })
     }
 
+    /// Uses the projected area of the provided plates, seen from the Sun, instead of the SRP area of the spacecraft.
+    ///
+    /// The force remains along the Sun to spacecraft line, and the partials do not account for the variation of the projected area.
+    pub fn with_plates(mut self, plates: OrientedPlates) -> Self {
+        self.plates = Some(plates);
+        self
+    }
+
+    /// Area in m^2 of the spacecraft exposed to the Sun, in the provided direction from the spacecraft to the Sun.
+    fn area_m2(
+        &self,
+        ctx: &Spacecraft,
+        sun_direction: &Vector3<f64>,
+        almanac: &Almanac,
+    ) -> Result<f64, DynamicsError> {
+        match &self.plates {
+            Some(plates) => plates.projected_area_m2(&ctx.orbit, sun_direction, almanac),
+            None => Ok(ctx.srp.area_m2),
+        }
+    }
+
     /// Accounts for the shadowing of only one body and will set the solar flux at 1 AU to: Phi = 1367.0
     pub fn default(shadow_body: Frame, almanac: Arc<Almanac>) -> Result<Arc<Self>, DynamicsError> {
         Ok(Arc::new(Self::default_raw(vec![shadow_body], almanac)?))
@@ -108,6 +136,10 @@ impl SolarPressure {
 }
 
 impl ForceModel for SolarPressure {
+    fn has_partials(&self) -> bool {
+        true
+    }
+
     fn estimation_index(&self) -> Option<usize> {
         if self.estimate {
             Some(6)
@@ -127,6 +159,7 @@ impl ForceModel for SolarPressure {
             .radius_km;
 
         let r_sun_unit = r_sun / r_sun.norm();
+        let area_m2 = self.area_m2(ctx, &-r_sun_unit, &almanac)?;
 
         // ANISE returns the occultation percentage (or factor), which is the opposite as the illumination factor.
         let occult = self
@@ -145,7 +178,7 @@ impl ForceModel for SolarPressure {
         let flux_pressure = (k * self.phi / SPEED_OF_LIGHT_M_S) * (1.0 / r_sun_au).powi(2);
 
         // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
-        Ok(1e-3 * ctx.srp.cr * ctx.srp.area_m2 * flux_pressure * r_sun_unit)
+        Ok(1e-3 * ctx.srp.cr * area_m2 * flux_pressure * r_sun_unit)
     }
 
     fn dual_eom(
@@ -163,6 +196,8 @@ impl ForceModel for SolarPressure {
             })?
             .radius_km;
 
+        let area_m2 = self.area_m2(ctx, &-r_sun.normalize(), &almanac)?;
+
         let r_sun_d: Vector3<OHyperdual<f64, Const<9>>> = hyperspace_from_vector(&r_sun);
         let r_sun_unit = r_sun_d / norm(&r_sun_d);
 
@@ -187,8 +222,7 @@ impl ForceModel for SolarPressure {
                 * inv_r_sun_au_p2;
 
         // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
-        let dual_force_scalar =
-            OHyperdual::<f64, Const<9>>::from_real(1e-3 * ctx.srp.cr * ctx.srp.area_m2);
+        let dual_force_scalar = OHyperdual::<f64, Const<9>>::from_real(1e-3 * ctx.srp.cr * area_m2);
         let mut dual_force: Vector3<OHyperdual<f64, Const<9>>> = Vector3::zeros();
         dual_force[0] = dual_force_scalar * flux_pressure * r_sun_unit[0];
         dual_force[1] = dual_force_scalar * flux_pressure * r_sun_unit[1];
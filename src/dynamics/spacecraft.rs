@@ -19,7 +19,7 @@
 use anise::prelude::Almanac;
 use snafu::ResultExt;
 
-use super::guidance::{ra_dec_from_unit_vector, GuidanceError, GuidanceLaw};
+use super::guidance::{ra_dec_from_unit_vector, GuidanceError, GuidanceLaw, ThrusterPowerTable};
 use super::orbital::OrbitalDynamics;
 use super::{Dynamics, DynamicsGuidanceSnafu, ForceModel};
 pub use crate::cosmic::{GuidanceMode, Spacecraft, STD_GRAVITY};
@@ -62,6 +62,8 @@ pub struct SpacecraftDynamics {
     pub force_models: Vec<Arc<dyn ForceModel>>,
     pub guid_law: Option<Arc<dyn GuidanceLaw>>,
     pub decrement_mass: bool,
+    /// Optional thrust and Isp as a function of the input power, overriding those of the spacecraft thruster
+    pub power_table: Option<Arc<ThrusterPowerTable>>,
 }
 
 impl SpacecraftDynamics {
@@ -73,6 +75,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: Vec::new(),
             decrement_mass: true,
+            power_table: None,
         }
     }
 
@@ -87,6 +90,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: Vec::new(),
             decrement_mass: false,
+            power_table: None,
         }
     }
 
@@ -97,16 +101,19 @@ impl SpacecraftDynamics {
             guid_law: None,
             force_models: Vec::new(),
             decrement_mass: true,
+            power_table: None,
         }
     }
 
     /// Initialize new spacecraft dynamics with the provided orbital mechanics and with the provided force model.
     pub fn from_model(orbital_dyn: OrbitalDynamics, force_model: Arc<dyn ForceModel>) -> Self {
+        warn_finite_difference(&force_model);
         Self {
             orbital_dyn,
             guid_law: None,
             force_models: vec![force_model],
             decrement_mass: true,
+            power_table: None,
         }
     }
 
@@ -116,6 +123,7 @@ impl SpacecraftDynamics {
         force_models: Vec<Arc<dyn ForceModel>>,
     ) -> Self {
         let mut me = Self::new(orbital_dyn);
+        force_models.iter().for_each(warn_finite_difference);
         me.force_models = force_models;
         me
     }
@@ -135,8 +143,31 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: self.force_models.clone(),
             decrement_mass: self.decrement_mass,
+            power_table: self.power_table.clone(),
         }
     }
+
+    /// Clone these spacecraft dynamics and add the provided force model, e.g. a user defined one, to the existing force models.
+    pub fn with_force_model(&self, force_model: Arc<dyn ForceModel>) -> Self {
+        warn_finite_difference(&force_model);
+        let mut me = self.clone();
+        me.force_models.push(force_model);
+        me
+    }
+
+    /// Clone these spacecraft dynamics and set the power table of the thruster to the one provided.
+    pub fn with_power_table(&self, power_table: Arc<ThrusterPowerTable>) -> Self {
+        let mut me = self.clone();
+        me.power_table = Some(power_table);
+        me
+    }
+}
+
+/// Warns that the partials of the provided force model, which is being added to the dynamics, are computed by finite differencing.
+fn warn_finite_difference(force_model: &Arc<dyn ForceModel>) {
+    if !force_model.has_partials() {
+        warn!("{force_model} does not provide its partials, computing them by finite differencing");
+    }
 }
 
 #[cfg_attr(feature = "python", pymethods)]
@@ -271,7 +302,7 @@ impl Dynamics for SpacecraftDynamics {
         match ctx.stm {
             Some(stm) => {
                 // Call the gradient (also called the dual EOM function of the force models)
-                let (state, grad) = self.dual_eom(delta_t_s, &osc_sc, almanac)?;
+                let (state, grad) = self.dual_eom(delta_t_s, &osc_sc, almanac.clone())?;
 
                 // Apply the gradient to the STM
                 let stm_dt = stm * grad;
@@ -310,14 +341,17 @@ impl Dynamics for SpacecraftDynamics {
         // Now include the control as needed.
         if let Some(guid_law) = &self.guid_law {
             let (thrust_force, fuel_rate) = {
-                if osc_sc.thruster.is_none() {
+                // The power table provides the thruster, so the spacecraft only needs one without a power table
+                if self.power_table.is_none() && osc_sc.thruster.is_none() {
                     return Err(DynamicsError::DynamicsGuidance {
                         source: GuidanceError::NoThrustersDefined,
                     });
                 }
-                let thruster = osc_sc.thruster.unwrap();
-                let thrust_throttle_lvl =
-                    guid_law.throttle(&osc_sc).context(DynamicsGuidanceSnafu)?;
+                // Apply the minimum throttle of the thruster limits, which leaves out of range throttles unchanged
+                let throttle = guid_law.throttle(&osc_sc).context(DynamicsGuidanceSnafu)?;
+                let thrust_throttle_lvl = osc_sc
+                    .thruster_limits
+                    .map_or(throttle, |limits| limits.limited_throttle(throttle));
                 if !(0.0..=1.0).contains(&thrust_throttle_lvl) {
                     return Err(DynamicsError::DynamicsGuidance {
                         source: GuidanceError::ThrottleRatio {
@@ -325,7 +359,11 @@ impl Dynamics for SpacecraftDynamics {
                         },
                     });
                 } else if thrust_throttle_lvl > 0.0 {
-                    // Thrust arc
+                    // Thrust arc: only query the power table when thrusting
+                    let thruster = match &self.power_table {
+                        Some(power_table) => power_table.thruster_at(&osc_sc, almanac.clone())?,
+                        None => osc_sc.thruster.unwrap(),
+                    };
                     let thrust_inertial =
                         guid_law.direction(&osc_sc).context(DynamicsGuidanceSnafu)?;
                     if (thrust_inertial.norm() - 1.0).abs() > NORM_ERR {
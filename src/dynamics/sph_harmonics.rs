@@ -50,6 +50,10 @@ pub struct Harmonics {
 
 impl Harmonics {
     /// Create a new Harmonics dynamical model from the provided gravity potential storage instance.
+    ///
+    /// The compute frame must be the body fixed frame in which the coefficients are defined, e.g. `IAU_EARTH_FRAME` (or ITRF93) for JGM3 and EGM2008,
+    /// but the Moon principal axes frame (`MOON_PA_FRAME`) for the GRAIL fields. The acceleration (and its partials) are computed in that frame
+    /// and rotated back into the integration frame at each call. Using the wrong frame leads to errors at the kilometer level in low lunar orbit.
     pub fn from_stor(compute_frame: Frame, stor: HarmonicsMem) -> Arc<Self> {
         let degree_np2 = stor.max_degree_n() + 2;
         let mut a_nm = DMatrix::from_element(degree_np2 + 1, degree_np2 + 1, 0.0);
@@ -132,6 +136,11 @@ impl Harmonics {
             vr11_h,
         })
     }
+
+    /// Returns the frame in which the coefficients of this gravity field are defined and the acceleration is computed.
+    pub fn compute_frame(&self) -> Frame {
+        self.compute_frame
+    }
 }
 
 impl fmt::Display for Harmonics {
@@ -210,7 +219,7 @@ impl AccelModel for Harmonics {
         let mut rho_np1 = mu_km3_s2 / r_ * rho;
         let mut accel4: Vector4<f64> = Vector4::zeros();
 
-        for n in 1..max_degree {
+        for n in 1..=max_degree {
             let mut sum: Vector4<f64> = Vector4::zeros();
             rho_np1 *= rho;
 
@@ -331,7 +340,7 @@ impl AccelModel for Harmonics {
         let mut a3 = OHyperdual::<f64, U7>::from(0.0);
         let sqrt2 = OHyperdual::<f64, U7>::from(2.0.sqrt());
 
-        for n in 1..max_degree {
+        for n in 1..=max_degree {
             let mut sum0 = OHyperdual::from(0.0);
             let mut sum1 = OHyperdual::from(0.0);
             let mut sum2 = OHyperdual::from(0.0);
@@ -377,33 +386,21 @@ impl AccelModel for Harmonics {
             })?
             .rot_mat;
 
-        // Convert DCM to OHyperdual DCMs
-        let mut dcm_d = Matrix3::<OHyperdual<f64, U7>>::zeros();
-        for i in 0..3 {
-            for j in 0..3 {
-                dcm_d[(i, j)] = OHyperdual::from_fn(|k| {
-                    if k == 0 {
-                        dcm[(i, j)]
-                    } else if i + 1 == k {
-                        1.0
-                    } else {
-                        0.0
-                    }
-                })
-            }
-        }
-
-        let accel = dcm_d * Vector3::new(a0 + a3 * s_, a1 + a3 * t_, a2 + a3 * u_);
+        // The acceleration and its partials are computed with respect to the position in the gravity field frame.
+        let accel = Vector3::new(a0 + a3 * s_, a1 + a3 * t_, a2 + a3 * u_);
         // Extract data
-        let mut dx = Vector3::zeros();
-        let mut grad = Matrix3::zeros();
+        let mut accel_c = Vector3::zeros();
+        let mut grad_c = Matrix3::zeros();
         for i in 0..3 {
-            dx[i] += accel[i].real();
+            accel_c[i] += accel[i].real();
             // NOTE: Although the hyperdual state is of size 7, we're only setting the values up to 3 (Matrix3)
             for j in 1..4 {
-                grad[(i, j - 1)] += accel[i][j];
+                grad_c[(i, j - 1)] += accel[i][j];
             }
         }
+        // Rotate both back into the integration frame: with r_c = C^T r and a = C a_c, the partials are da/dr = C (da_c/dr_c) C^T.
+        let dx = dcm * accel_c;
+        let grad = dcm * grad_c * dcm.transpose();
         Ok((dx, grad))
     }
 }
@@ -16,6 +16,8 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::cosmic::attitude::AttitudeError;
+use crate::io::tle::TleError;
 use crate::md::trajectory::TrajError;
 use crate::md::StateParameter;
 pub use crate::md::TargetingError;
@@ -77,12 +79,19 @@ pub enum NyxError {
     GuidanceConfigError { msg: String },
     #[snafu(display("Config error: {source}"))]
     ConfigError { source: ConfigError },
+    #[snafu(display("TLE error: {source}"))]
+    Tle { source: TleError },
     #[snafu(display("issue due to Almanac: {action} {source}"))]
     FromAlmanacError {
         #[snafu(source(from(AlmanacError, Box::new)))]
         source: Box<AlmanacError>,
         action: &'static str,
     },
+    #[snafu(display("physics issue: {action} {source}"))]
+    FromPhysicsError {
+        source: PhysicsError,
+        action: &'static str,
+    },
 }
 
 impl From<TrajError> for NyxError {
@@ -97,6 +106,12 @@ impl From<ConfigError> for NyxError {
     }
 }
 
+impl From<TleError> for NyxError {
+    fn from(source: TleError) -> Self {
+        NyxError::Tle { source }
+    }
+}
+
 #[derive(Debug, PartialEq, Snafu)]
 #[snafu(visibility(pub(crate)))]
 pub enum StateError {
@@ -130,6 +145,8 @@ pub enum EventError {
     EventPhysicsError { source: PhysicsError },
     #[snafu(display("when computing an event in a trajectory {source}"))]
     EventTrajError { source: TrajError },
+    #[snafu(display("during event computation: {source}"))]
+    EventAttitudeError { source: AttitudeError },
     #[snafu(display("Event {event} not found between {start} and {end}"))]
     NotFound {
         start: Epoch,
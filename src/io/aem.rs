@@ -0,0 +1,226 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::attitude::{AttitudeEphemeris, AttitudeError};
+use crate::time::Epoch;
+use anise::constants::orientations::{ECLIPJ2000, J2000};
+use nalgebra::{Quaternion, UnitQuaternion};
+use snafu::prelude::*;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Errors of the parsing of CCSDS attitude ephemeris messages
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum AemError {
+    #[snafu(display("could not read AEM: {source}"))]
+    AemIo { source: std::io::Error },
+    #[snafu(display("invalid AEM on line {lno}: {msg}"))]
+    AemParse { lno: usize, msg: String },
+    #[snafu(display("invalid AEM: {source}"))]
+    AemAttitude { source: AttitudeError },
+}
+
+/// Metadata of one segment of an AEM
+struct AemMeta {
+    object_name: Option<String>,
+    ref_frame_a: Option<String>,
+    ref_frame_b: Option<String>,
+    a2b: bool,
+    time_system: String,
+    scalar_first: bool,
+}
+
+impl Default for AemMeta {
+    fn default() -> Self {
+        Self {
+            object_name: None,
+            ref_frame_a: None,
+            ref_frame_b: None,
+            a2b: true,
+            time_system: "UTC".to_string(),
+            scalar_first: false,
+        }
+    }
+}
+
+impl AemMeta {
+    /// Returns the orientation of the reference frame, and whether the quaternions rotate from the reference frame to the body frame.
+    ///
+    /// The reference frame is whichever of frames A and B is inertial, the other one is the body frame (or an instrument frame).
+    fn reference(&self, lno: usize) -> Result<(i32, bool), AemError> {
+        let (Some(frame_a), Some(frame_b)) = (&self.ref_frame_a, &self.ref_frame_b) else {
+            return Err(AemError::AemParse {
+                lno,
+                msg: "REF_FRAME_A and REF_FRAME_B must be set before the data".to_string(),
+            });
+        };
+        match (orientation_of(frame_a), orientation_of(frame_b)) {
+            (Some(orientation_id), None) => Ok((orientation_id, self.a2b)),
+            (None, Some(orientation_id)) => Ok((orientation_id, !self.a2b)),
+            _ => Err(AemError::AemParse {
+                lno,
+                msg: format!(
+                    "exactly one of `{frame_a}` and `{frame_b}` must be a supported inertial frame"
+                ),
+            }),
+        }
+    }
+}
+
+/// Orientation ID of the supported inertial reference frames of AEMs
+fn orientation_of(name: &str) -> Option<i32> {
+    match name {
+        "EME2000" | "J2000" | "ICRF" | "GCRF" => Some(J2000),
+        "ECLIPJ2000" => Some(ECLIPJ2000),
+        _ => None,
+    }
+}
+
+impl AttitudeEphemeris {
+    /// Reads the quaternions of a CCSDS attitude ephemeris message (AEM) in the KVN format.
+    ///
+    /// The direction of the rotation (ATTITUDE_DIR, A2B if unset) and the position of the scalar (QUATERNION_TYPE, LAST if unset) are read from the metadata of each segment,
+    /// and one of REF_FRAME_A and REF_FRAME_B must be an inertial frame. All of the segments must share the same reference frame.
+    pub fn from_aem_file<P: AsRef<Path>>(path: P) -> Result<Self, AemError> {
+        Self::from_aem_str(&read_to_string(path).context(AemIoSnafu)?)
+    }
+
+    /// Parses the contents of a CCSDS AEM in the KVN format, refer to `from_aem_file`.
+    pub fn from_aem_str(contents: &str) -> Result<Self, AemError> {
+        let mut meta = AemMeta::default();
+        let mut in_data = false;
+        let mut orientation_id = None;
+        let mut object_name = None;
+        let mut samples = Vec::new();
+
+        for (idx, line) in contents.lines().enumerate() {
+            let lno = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("COMMENT") {
+                continue;
+            }
+
+            match line {
+                "META_START" => {
+                    meta = AemMeta::default();
+                    in_data = false;
+                    continue;
+                }
+                "META_STOP" | "DATA_STOP" => continue,
+                "DATA_START" => {
+                    in_data = true;
+                    continue;
+                }
+                _ => {}
+            }
+
+            if !in_data {
+                if let Some((key, value)) = line.split_once('=') {
+                    let value = value.trim().to_string();
+                    match key.trim() {
+                        "OBJECT_NAME" => meta.object_name = Some(value),
+                        "REF_FRAME_A" => meta.ref_frame_a = Some(value),
+                        "REF_FRAME_B" => meta.ref_frame_b = Some(value),
+                        "ATTITUDE_DIR" => meta.a2b = value != "B2A",
+                        "TIME_SYSTEM" => meta.time_system = value,
+                        "QUATERNION_TYPE" => meta.scalar_first = value == "FIRST",
+                        "ATTITUDE_TYPE" if value != "QUATERNION" => {
+                            return Err(AemError::AemParse {
+                                lno,
+                                msg: format!("unsupported ATTITUDE_TYPE {value}"),
+                            })
+                        }
+                        _ => debug!("[line: {lno}] ignoring `{line}`"),
+                    }
+                }
+                continue;
+            }
+
+            let (seg_orientation_id, from_reference) = meta.reference(lno)?;
+            match orientation_id {
+                None => orientation_id = Some(seg_orientation_id),
+                Some(id) if id != seg_orientation_id => {
+                    return Err(AemError::AemParse {
+                        lno,
+                        msg: "all segments must share the same reference frame".to_string(),
+                    })
+                }
+                _ => {}
+            }
+            if object_name.is_none() {
+                object_name.clone_from(&meta.object_name);
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() != 5 {
+                return Err(AemError::AemParse {
+                    lno,
+                    msg: format!("expected an epoch and four quaternion components in `{line}`"),
+                });
+            }
+
+            let epoch =
+                Epoch::from_str(&format!("{} {}", parts[0], meta.time_system)).map_err(|e| {
+                    AemError::AemParse {
+                        lno,
+                        msg: format!("invalid epoch: {e}"),
+                    }
+                })?;
+
+            let mut q = [0.0; 4];
+            for (qi, part) in q.iter_mut().zip(&parts[1..]) {
+                *qi = part.parse().map_err(|e| AemError::AemParse {
+                    lno,
+                    msg: format!("invalid quaternion component `{part}`: {e}"),
+                })?;
+            }
+            let (qc, q1, q2, q3) = if meta.scalar_first {
+                (q[0], q[1], q[2], q[3])
+            } else {
+                (q[3], q[0], q[1], q[2])
+            };
+            let q_from_to = UnitQuaternion::from_quaternion(Quaternion::new(qc, q1, q2, q3));
+
+            // The CCSDS quaternion from frame A to frame B transforms the coordinates of a vector from A into B, so the rotation
+            // of nalgebra of the same quaternion maps the coordinates in B into A: it is the body to reference rotation when A is the reference.
+            samples.push((
+                epoch,
+                if from_reference {
+                    q_from_to
+                } else {
+                    q_from_to.inverse()
+                },
+            ));
+        }
+
+        let Some(orientation_id) = orientation_id else {
+            return Err(AemError::AemParse {
+                lno: contents.lines().count(),
+                msg: "no attitude data".to_string(),
+            });
+        };
+
+        let ephem = Self::new(samples, orientation_id).context(AemAttitudeSnafu)?;
+        Ok(match object_name {
+            Some(name) => ephem.with_object_name(name),
+            None => ephem,
+        })
+    }
+}
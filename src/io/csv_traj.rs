@@ -0,0 +1,363 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::EpochRepr;
+use crate::cosmic::{Frame, Orbit};
+use crate::linalg::Vector3;
+use crate::md::prelude::Traj;
+use crate::time::{Epoch, TimeScale};
+use crate::Spacecraft;
+use flate2::read::GzDecoder;
+use snafu::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+use typed_builder::TypedBuilder;
+
+/// Errors of the loading of a trajectory from a CSV state table
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum CsvTrajError {
+    #[snafu(display("could not read CSV state table: {source}"))]
+    CsvIo { source: std::io::Error },
+    #[snafu(display("invalid value `{value}` on line {lno} in column `{column}`: {msg}"))]
+    CsvValue {
+        lno: usize,
+        column: String,
+        value: String,
+        msg: String,
+    },
+    #[snafu(display("invalid CSV state table on line {lno}: {msg}"))]
+    CsvLine { lno: usize, msg: String },
+    #[snafu(display("invalid CSV column mapping: {msg}"))]
+    CsvMapping { msg: String },
+}
+
+/// A column of a CSV state table, either by its header or by its zero based index
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CsvColumn {
+    Name(String),
+    Index(usize),
+}
+
+impl From<&str> for CsvColumn {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<usize> for CsvColumn {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+/// Delimiter of the columns of a CSV state table
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CsvDelimiter {
+    /// Comma separated values, the spaces around each value are ignored
+    #[default]
+    Comma,
+    /// Values separated by any number of spaces or tabs
+    Whitespace,
+}
+
+/// Unit of the positions of a CSV state table, the velocities are in this unit per second
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LengthUnit {
+    Meter,
+    #[default]
+    Kilometer,
+}
+
+impl LengthUnit {
+    /// Returns the number of kilometers in this unit
+    pub fn to_km(&self) -> f64 {
+        match self {
+            Self::Meter => 1e-3,
+            Self::Kilometer => 1.0,
+        }
+    }
+}
+
+/// Frame of the states of a CSV state table
+#[derive(Clone, Debug)]
+pub enum CsvFrame {
+    /// All of the states are in this frame
+    Fixed(Frame),
+    /// The frame of each state is named in this column, and the names are mapped to their frame.
+    /// All of the states must still be in the same frame.
+    Column {
+        column: CsvColumn,
+        frames: HashMap<String, Frame>,
+    },
+}
+
+/// Mapping of the columns of a CSV state table (e.g. an ephemeris exported by another tool) to the states of a trajectory.
+///
+/// Lines starting with the comment character and empty lines are skipped, and quoted values are not supported.
+/// Files compressed with gzip are decompressed transparently.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(doc)]
+pub struct CsvTrajCfg {
+    /// Column of the epochs
+    #[builder(setter(into))]
+    pub epoch_column: CsvColumn,
+    /// Representation of the epochs, e.g. Gregorian strings or MJD
+    #[builder(default)]
+    pub epoch_repr: EpochRepr,
+    /// Time scale of the epochs
+    #[builder(default = TimeScale::UTC)]
+    pub time_scale: TimeScale,
+    /// Columns of the X, Y, and Z components of the position
+    pub position_columns: [CsvColumn; 3],
+    /// Columns of the X, Y, and Z components of the velocity
+    pub velocity_columns: [CsvColumn; 3],
+    /// Unit of the positions, the velocities are in this unit per second
+    #[builder(default)]
+    pub length_unit: LengthUnit,
+    pub frame: CsvFrame,
+    #[builder(default)]
+    pub delimiter: CsvDelimiter,
+    /// Set to false if the first line is data instead of the column headers, in which case the columns must be mapped by index
+    #[builder(default = true)]
+    pub has_headers: bool,
+    #[builder(default = Some('#'))]
+    pub comment: Option<char>,
+}
+
+impl CsvTrajCfg {
+    /// Loads the trajectory from the CSV state table at the provided path, which may be compressed with gzip.
+    ///
+    /// Note that the trajectory is a spacecraft trajectory whose orbits are read from the table, with the default spacecraft parameters.
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> Result<Traj<Spacecraft>, CsvTrajError> {
+        let mut reader = BufReader::new(File::open(path).context(CsvIoSnafu)?);
+        // Detect gzip from its magic number rather than from the file extension
+        let is_gzip = reader
+            .fill_buf()
+            .context(CsvIoSnafu)?
+            .starts_with(&[0x1f, 0x8b]);
+        if is_gzip {
+            self.parse(BufReader::new(GzDecoder::new(reader)))
+        } else {
+            self.parse(reader)
+        }
+    }
+
+    /// Parses the trajectory from the provided CSV state table, refer to `load`.
+    pub fn parse<R: BufRead>(&self, reader: R) -> Result<Traj<Spacecraft>, CsvTrajError> {
+        let mut headers: Option<Vec<String>> = None;
+        let mut indexes: Option<Indexes> = None;
+        let mut traj = Traj::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let lno = idx + 1;
+            let line = line.context(CsvIoSnafu)?;
+            let trimmed = line.trim();
+            if trimmed.is_empty()
+                || self
+                    .comment
+                    .map_or(false, |comment| trimmed.starts_with(comment))
+            {
+                continue;
+            }
+
+            let values = match self.delimiter {
+                CsvDelimiter::Comma => trimmed.split(',').map(str::trim).collect::<Vec<&str>>(),
+                CsvDelimiter::Whitespace => trimmed.split_whitespace().collect(),
+            };
+
+            if self.has_headers && headers.is_none() {
+                headers = Some(values.iter().map(|value| value.to_string()).collect());
+                continue;
+            }
+
+            if indexes.is_none() {
+                indexes = Some(self.indexes(headers.as_deref())?);
+            }
+            let indexes = indexes.as_ref().unwrap();
+
+            let column_name = |col: usize| match &headers {
+                Some(headers) => headers
+                    .get(col)
+                    .cloned()
+                    .unwrap_or_else(|| format!("#{col}")),
+                None => format!("#{col}"),
+            };
+
+            let value_of = |col: usize| {
+                values.get(col).copied().context(CsvLineSnafu {
+                    lno,
+                    msg: format!(
+                        "missing column `{}`, found only {} values",
+                        column_name(col),
+                        values.len()
+                    ),
+                })
+            };
+
+            let parse_f64 = |col: usize| -> Result<f64, CsvTrajError> {
+                let value = value_of(col)?;
+                value.parse::<f64>().map_err(|e| CsvTrajError::CsvValue {
+                    lno,
+                    column: column_name(col),
+                    value: value.to_string(),
+                    msg: e.to_string(),
+                })
+            };
+
+            // Epoch
+            let epoch_str = value_of(indexes.epoch)?;
+            let epoch = self
+                .parse_epoch(epoch_str)
+                .map_err(|msg| CsvTrajError::CsvValue {
+                    lno,
+                    column: column_name(indexes.epoch),
+                    value: epoch_str.to_string(),
+                    msg,
+                })?;
+
+            // Frame
+            let frame = match (&self.frame, indexes.frame) {
+                (CsvFrame::Fixed(frame), _) => *frame,
+                (CsvFrame::Column { frames, .. }, Some(col)) => {
+                    let name = value_of(col)?;
+                    *frames.get(name).ok_or_else(|| CsvTrajError::CsvValue {
+                        lno,
+                        column: column_name(col),
+                        value: name.to_string(),
+                        msg: format!(
+                            "unknown frame name, expected one of {:?}",
+                            frames.keys().collect::<Vec<&String>>()
+                        ),
+                    })?
+                }
+                (CsvFrame::Column { .. }, None) => unreachable!(),
+            };
+
+            let to_km = self.length_unit.to_km();
+            let mut radius_km = Vector3::zeros();
+            let mut velocity_km_s = Vector3::zeros();
+            for i in 0..3 {
+                radius_km[i] = parse_f64(indexes.position[i])? * to_km;
+                velocity_km_s[i] = parse_f64(indexes.velocity[i])? * to_km;
+            }
+
+            if let Some(first) = traj.states.first() {
+                ensure!(
+                    first.orbit.frame == frame,
+                    CsvLineSnafu {
+                        lno,
+                        msg: format!(
+                            "state in {frame} but the previous states are in {}",
+                            first.orbit.frame
+                        )
+                    }
+                );
+            }
+
+            traj.states.push(Spacecraft::from(Orbit::new(
+                radius_km.x,
+                radius_km.y,
+                radius_km.z,
+                velocity_km_s.x,
+                velocity_km_s.y,
+                velocity_km_s.z,
+                epoch,
+                frame,
+            )));
+        }
+
+        ensure!(
+            !traj.states.is_empty(),
+            CsvMappingSnafu {
+                msg: "no states in the table"
+            }
+        );
+
+        traj.finalize();
+
+        Ok(traj)
+    }
+
+    /// Parses an epoch in the representation and time scale of this table.
+    fn parse_epoch(&self, value: &str) -> Result<Epoch, String> {
+        match self.epoch_repr {
+            EpochRepr::Gregorian => if self.time_scale == TimeScale::UTC {
+                Epoch::from_gregorian_str(value)
+            } else {
+                Epoch::from_str(&format!("{value} {}", self.time_scale))
+            }
+            .map_err(|e| e.to_string()),
+            repr => {
+                let num = value.parse::<f64>().map_err(|e| e.to_string())?;
+                repr.epoch_from_f64(num, self.time_scale)
+                    .ok_or_else(|| format!("unsupported epoch representation {repr:?}"))
+            }
+        }
+    }
+
+    /// Returns the zero based indexes of the mapped columns.
+    fn indexes(&self, headers: Option<&[String]>) -> Result<Indexes, CsvTrajError> {
+        let index_of = |column: &CsvColumn| match column {
+            CsvColumn::Index(idx) => Ok(*idx),
+            CsvColumn::Name(name) => match headers {
+                Some(headers) => {
+                    headers
+                        .iter()
+                        .position(|header| header == name)
+                        .context(CsvMappingSnafu {
+                            msg: format!("no column `{name}` in the headers {headers:?}"),
+                        })
+                }
+                None => CsvMappingSnafu {
+                    msg: format!("column `{name}` is mapped by name but the table has no headers"),
+                }
+                .fail(),
+            },
+        };
+
+        Ok(Indexes {
+            epoch: index_of(&self.epoch_column)?,
+            position: [
+                index_of(&self.position_columns[0])?,
+                index_of(&self.position_columns[1])?,
+                index_of(&self.position_columns[2])?,
+            ],
+            velocity: [
+                index_of(&self.velocity_columns[0])?,
+                index_of(&self.velocity_columns[1])?,
+                index_of(&self.velocity_columns[2])?,
+            ],
+            frame: match &self.frame {
+                CsvFrame::Fixed(_) => None,
+                CsvFrame::Column { column, .. } => Some(index_of(column)?),
+            },
+        })
+    }
+}
+
+/// Zero based indexes of the mapped columns of a table
+struct Indexes {
+    epoch: usize,
+    position: [usize; 3],
+    velocity: [usize; 3],
+    frame: Option<usize>,
+}
@@ -0,0 +1,334 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{InputOutputError, StdIOSnafu};
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::md::StateParameter;
+use crate::State;
+use hifitime::TimeScale;
+use serde_json::{json, Map, Value};
+use snafu::ResultExt;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{channel, sync_channel, Receiver, SendError, Sender, SyncSender};
+use std::thread::{self, JoinHandle};
+use typed_builder::TypedBuilder;
+
+/// Destination of a JSON Lines stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonLinesSink {
+    /// File opened in append mode, e.g. for a visualizer tailing the file
+    File(PathBuf),
+    /// TCP socket address, e.g. `127.0.0.1:9000`
+    Tcp(String),
+    /// Unix domain socket path
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Behavior when the writer falls behind the producer of states.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Write every state, the producer is blocked while `max_pending` states are pending, bounding the memory used.
+    #[default]
+    Block,
+    /// Drop the oldest pending states beyond `max_pending`, e.g. for a live display where only recent states matter.
+    DropOldest,
+}
+
+/// Configuration of a JSON Lines stream of states.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(doc)]
+pub struct JsonLinesCfg {
+    pub sink: JsonLinesSink,
+    /// Additional parameters to include in each line, in addition to the epoch, frame, position, and velocity.
+    #[builder(default)]
+    pub params: Vec<StateParameter>,
+    #[builder(default)]
+    pub backpressure: Backpressure,
+    /// Maximum number of pending states: beyond it, the producer is blocked or the oldest states are dropped, depending on the backpressure.
+    #[builder(default = 1024)]
+    pub max_pending: usize,
+    /// Number of reconnection attempts to a socket before failing.
+    #[builder(default = 5)]
+    pub max_reconnects: usize,
+    /// Delay between two reconnection attempts.
+    #[builder(default = std::time::Duration::from_millis(200))]
+    pub reconnect_delay: std::time::Duration,
+}
+
+impl JsonLinesCfg {
+    /// Sets the additional parameters from their names, e.g. `["sma", "ecc", "fuel_mass"]`, as used in trajectory exports.
+    pub fn with_headers(mut self, headers: &[&str]) -> Result<Self, NyxError> {
+        self.params = headers
+            .iter()
+            .map(|hdr| StateParameter::from_str(hdr))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self)
+    }
+}
+
+/// Statistics of a JSON Lines stream.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsonLinesStats {
+    /// Number of states written
+    pub written: usize,
+    /// Number of states dropped because of backpressure
+    pub dropped: usize,
+    /// Number of successful reconnections to the socket
+    pub reconnects: usize,
+}
+
+/// Sending end of a JSON Lines stream.
+#[derive(Debug)]
+pub enum JsonLinesSender<S> {
+    /// Blocks the producer when the writer falls behind, cf. [Backpressure::Block]
+    Bounded(SyncSender<S>),
+    /// Never blocks the producer, cf. [Backpressure::DropOldest]
+    Unbounded(Sender<S>),
+}
+
+impl<S> JsonLinesSender<S> {
+    /// Sends a state to the writer, blocking if the channel is bounded and full.
+    pub fn send(&self, state: S) -> Result<(), SendError<S>> {
+        match self {
+            Self::Bounded(tx) => tx.send(state),
+            Self::Unbounded(tx) => tx.send(state),
+        }
+    }
+}
+
+impl<S> Clone for JsonLinesSender<S> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Bounded(tx) => Self::Bounded(tx.clone()),
+            Self::Unbounded(tx) => Self::Unbounded(tx.clone()),
+        }
+    }
+}
+
+/// Writes states as JSON Lines, one JSON object per state, flushing after each state.
+///
+/// Each line includes the epoch in UTC (ISO 8601), the frame, the position and velocity arrays, and the configured parameters,
+/// e.g. `{"epoch":"2024-01-01T00:00:00 UTC","frame":"Earth J2000","position_km":[...],"velocity_km_s":[...],"sma":7000.0}`.
+/// Parameters which cannot be computed for a state are set to null.
+pub struct JsonLinesWriter {
+    cfg: JsonLinesCfg,
+    out: Box<dyn Write + Send>,
+    stats: JsonLinesStats,
+}
+
+impl JsonLinesWriter {
+    /// Opens the sink of this configuration.
+    pub fn new(cfg: JsonLinesCfg) -> Result<Self, InputOutputError> {
+        let out = Self::open(&cfg.sink)?;
+        Ok(Self {
+            cfg,
+            out,
+            stats: JsonLinesStats::default(),
+        })
+    }
+
+    fn open(sink: &JsonLinesSink) -> Result<Box<dyn Write + Send>, InputOutputError> {
+        Ok(match sink {
+            JsonLinesSink::File(path) => {
+                let file: File = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .context(StdIOSnafu {
+                        action: "opening JSON Lines file",
+                    })?;
+                Box::new(BufWriter::new(file))
+            }
+            JsonLinesSink::Tcp(addr) => Box::new(BufWriter::new(
+                TcpStream::connect(addr).context(StdIOSnafu {
+                    action: "connecting to JSON Lines TCP socket",
+                })?,
+            )),
+            #[cfg(unix)]
+            JsonLinesSink::Unix(path) => Box::new(BufWriter::new(
+                UnixStream::connect(path).context(StdIOSnafu {
+                    action: "connecting to JSON Lines Unix socket",
+                })?,
+            )),
+        })
+    }
+
+    /// Returns the JSON object of the provided state.
+    pub fn to_json<S: State>(&self, state: &S) -> Value
+    where
+        DefaultAllocator:
+            Allocator<S::Size> + Allocator<S::Size, S::Size> + Allocator<S::VecLength>,
+    {
+        let orbit = state.orbit();
+        let mut obj = Map::new();
+        obj.insert(
+            "epoch".to_string(),
+            json!(format!(
+                "{} UTC",
+                state.epoch().to_time_scale(TimeScale::UTC).to_isoformat()
+            )),
+        );
+        obj.insert("frame".to_string(), json!(format!("{}", orbit.frame)));
+        obj.insert(
+            "position_km".to_string(),
+            json!([orbit.radius_km.x, orbit.radius_km.y, orbit.radius_km.z]),
+        );
+        obj.insert(
+            "velocity_km_s".to_string(),
+            json!([
+                orbit.velocity_km_s.x,
+                orbit.velocity_km_s.y,
+                orbit.velocity_km_s.z
+            ]),
+        );
+        for param in &self.cfg.params {
+            // Non finite values are serialized as null.
+            let value = state.value(*param).map_or(Value::Null, |val| json!(val));
+            obj.insert(format!("{param}"), value);
+        }
+        Value::Object(obj)
+    }
+
+    /// Writes this state as a single line and flushes it, reconnecting to the socket if needed.
+    pub fn write_state<S: State>(&mut self, state: &S) -> Result<(), InputOutputError>
+    where
+        DefaultAllocator:
+            Allocator<S::Size> + Allocator<S::Size, S::Size> + Allocator<S::VecLength>,
+    {
+        let line = format!("{}\n", self.to_json(state));
+
+        let mut attempt = 0;
+        loop {
+            match self
+                .out
+                .write_all(line.as_bytes())
+                .and_then(|_| self.out.flush())
+            {
+                Ok(()) => break,
+                Err(e) => {
+                    if matches!(self.cfg.sink, JsonLinesSink::File(_))
+                        || attempt >= self.cfg.max_reconnects
+                    {
+                        return Err(InputOutputError::StdIOError {
+                            source: e,
+                            action: "writing JSON Lines",
+                        });
+                    }
+                    attempt += 1;
+                    warn!(
+                        "JSON Lines stream to {:?} failed ({e}), reconnecting (attempt {attempt})",
+                        self.cfg.sink
+                    );
+                    thread::sleep(self.cfg.reconnect_delay);
+                    match Self::open(&self.cfg.sink) {
+                        Ok(out) => {
+                            self.out = out;
+                            self.stats.reconnects += 1;
+                        }
+                        Err(e) => warn!("{e}"),
+                    }
+                }
+            }
+        }
+
+        self.stats.written += 1;
+        Ok(())
+    }
+
+    /// Writes all of the states received until the channel is closed, applying the backpressure policy.
+    pub fn consume<S: State>(mut self, rx: Receiver<S>) -> Result<JsonLinesStats, InputOutputError>
+    where
+        DefaultAllocator:
+            Allocator<S::Size> + Allocator<S::Size, S::Size> + Allocator<S::VecLength>,
+    {
+        while let Ok(first) = rx.recv() {
+            let mut pending: VecDeque<S> = VecDeque::from([first]);
+            pending.extend(rx.try_iter());
+
+            if self.cfg.backpressure == Backpressure::DropOldest
+                && pending.len() > self.cfg.max_pending
+            {
+                let excess = pending.len() - self.cfg.max_pending;
+                pending.drain(..excess);
+                self.stats.dropped += excess;
+            }
+
+            for state in pending {
+                self.write_state(&state)?;
+            }
+        }
+
+        info!(
+            "JSON Lines stream to {:?} closed: {} states written, {} dropped",
+            self.cfg.sink, self.stats.written, self.stats.dropped
+        );
+
+        Ok(self.stats)
+    }
+
+    /// Returns the channel of states to this writer, bounded to `max_pending` states if the backpressure blocks the producer.
+    pub fn channel<S: State>(&self) -> (JsonLinesSender<S>, Receiver<S>)
+    where
+        DefaultAllocator:
+            Allocator<S::Size> + Allocator<S::Size, S::Size> + Allocator<S::VecLength>,
+    {
+        match self.cfg.backpressure {
+            Backpressure::Block => {
+                let (tx, rx) = sync_channel(self.cfg.max_pending);
+                (JsonLinesSender::Bounded(tx), rx)
+            }
+            Backpressure::DropOldest => {
+                let (tx, rx) = channel();
+                (JsonLinesSender::Unbounded(tx), rx)
+            }
+        }
+    }
+
+    /// Spawns a thread consuming the states sent on the returned channel, e.g. forwarded from `for_duration_with_channel` of a propagator.
+    /// The thread returns once all of the senders are dropped.
+    #[allow(clippy::type_complexity)]
+    pub fn spawn<S: State + 'static>(
+        self,
+    ) -> (
+        JsonLinesSender<S>,
+        JoinHandle<Result<JsonLinesStats, InputOutputError>>,
+    )
+    where
+        DefaultAllocator:
+            Allocator<S::Size> + Allocator<S::Size, S::Size> + Allocator<S::VecLength>,
+    {
+        let (tx, rx) = self.channel();
+        let handle = thread::spawn(move || self.consume(rx));
+        (tx, handle)
+    }
+
+    /// Statistics of this stream so far.
+    pub fn stats(&self) -> JsonLinesStats {
+        self.stats
+    }
+}
@@ -16,16 +16,22 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::dynamics::guidance::LocalFrame;
 use crate::errors::NyxError;
 use crate::md::StateParameter;
 use crate::time::Epoch;
 
+use arrow::array::{
+    Array, ArrayRef, DurationNanosecondArray, DurationNanosecondBuilder, Float64Array,
+    Float64Builder, StringArray, StringBuilder,
+};
+use arrow::datatypes::{DataType, Field, TimeUnit};
 use arrow::error::ArrowError;
 use parquet::errors::ParquetError;
 use snafu::prelude::*;
 pub(crate) mod watermark;
 use hifitime::prelude::{Format, Formatter};
-use hifitime::Duration;
+use hifitime::{Duration, TimeScale, Unit};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Deserializer};
 use serde::{Serialize, Serializer};
@@ -34,18 +40,27 @@ use std::collections::{BTreeMap, HashMap};
 use std::convert::From;
 use std::fmt::Debug;
 use std::fs::File;
-use std::io::BufReader;
 use std::io::Error as IoError;
+use std::io::{BufReader, BufWriter};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 use typed_builder::TypedBuilder;
 
+/// Reads CCSDS attitude ephemeris messages (AEM)
+pub mod aem;
 /// Handles writing to an XYZV file
 pub mod cosmo;
+/// Loads trajectories from CSV state tables exported by other tools
+pub mod csv_traj;
 pub mod estimate;
 /// Handles loading of gravity models using files of NASA PDS and GMAT COF. Several gunzipped files are provided with nyx.
 pub mod gravity;
+/// Streams states as JSON Lines to a file or a socket, e.g. for live visualization
+pub mod jsonl;
 pub mod matrices;
+/// Parses two line element sets and propagates them with SGP4
+pub mod tle;
 pub mod tracking_data;
 pub mod trajectory_data;
 
@@ -77,6 +92,30 @@ pub struct ExportCfg {
     /// Set to true to append the timestamp to the filename
     #[builder(default)]
     pub timestamp: bool,
+    /// Value to write instead of a non-finite (NaN or infinite) covariance entry, e.g. if the filter diverged.
+    /// If unset, non-finite covariance entries are exported as null. In both cases, a warning is logged and the number of
+    /// non-finite entries is stored in the metadata of OD exports, cf. `NON_FINITE_COVAR_KEY`.
+    #[builder(default, setter(strip_option))]
+    pub covar_sentinel: Option<f64>,
+    /// Time scale of the exported epochs, defaults to UTC.
+    #[builder(default, setter(strip_option))]
+    pub time_scale: Option<TimeScale>,
+    /// Representation of the exported epochs, defaults to a Gregorian (ISO 8601) string.
+    /// Use `EpochRepr::NanosecondsPastJ2000` in TDB for a numeric epoch column which is exact over centuries.
+    #[builder(default)]
+    pub epoch_repr: EpochRepr,
+    /// Set to also export the epochs as seconds past J2000 in a 64 bit float column when they are exported as nanoseconds past J2000,
+    /// for compatibility with the readers of the former numeric epoch column. Note that this float column is only precise to about 100 ns
+    /// for epochs decades away from J2000.
+    #[builder(default)]
+    pub legacy_seconds_column: bool,
+    /// If set, the orbit determination export also includes the prefit and postfit residuals mapped into position and velocity deviations in this frame.
+    #[builder(default, setter(strip_option))]
+    pub residual_frame: Option<LocalFrame>,
+    /// Maximum number of rows per row group of the trajectory export. Smaller row groups allow readers to skip the data outside
+    /// of the requested epochs, provided that the epochs are exported as seconds or nanoseconds past J2000.
+    #[builder(default, setter(strip_option))]
+    pub row_group_size: Option<usize>,
 }
 
 impl ExportCfg {
@@ -108,6 +147,88 @@ impl ExportCfg {
         }
     }
 
+    /// Time scale in which epochs are exported.
+    pub fn epoch_time_scale(&self) -> TimeScale {
+        self.time_scale.unwrap_or(TimeScale::UTC)
+    }
+
+    /// Returns the field of the epoch column, labeled with its representation and time scale.
+    pub(crate) fn epoch_field(&self) -> Field {
+        let ts = self.epoch_time_scale();
+        match self.epoch_repr {
+            EpochRepr::Gregorian => Field::new(self.epoch_repr.label(ts), DataType::Utf8, false),
+            EpochRepr::NanosecondsPastJ2000 => Field::new(
+                self.epoch_repr.label(ts),
+                DataType::Duration(TimeUnit::Nanosecond),
+                false,
+            ),
+            _ => Field::new(self.epoch_repr.label(ts), DataType::Float64, false),
+        }
+    }
+
+    /// Returns whether the legacy seconds past J2000 column is exported after the epoch column.
+    fn has_legacy_seconds_column(&self) -> bool {
+        self.legacy_seconds_column && self.epoch_repr == EpochRepr::NanosecondsPastJ2000
+    }
+
+    /// Returns the fields of the epoch columns: the epoch column, followed by the legacy seconds past J2000 column if enabled.
+    pub(crate) fn epoch_fields(&self) -> Vec<Field> {
+        let mut fields = vec![self.epoch_field()];
+        if self.has_legacy_seconds_column() {
+            fields.push(Field::new(
+                EpochRepr::SecondsPastJ2000.label(self.epoch_time_scale()),
+                DataType::Float64,
+                false,
+            ));
+        }
+        fields
+    }
+
+    /// Builds the epoch column of the provided epochs in the configured time scale and representation.
+    pub(crate) fn epoch_column<I: Iterator<Item = Epoch>>(&self, epochs: I) -> ArrayRef {
+        let ts = self.epoch_time_scale();
+        match self.epoch_repr {
+            EpochRepr::Gregorian => {
+                let mut col = StringBuilder::new();
+                for epoch in epochs {
+                    col.append_value(epoch.to_time_scale(ts).to_isoformat());
+                }
+                Arc::new(col.finish())
+            }
+            EpochRepr::NanosecondsPastJ2000 => {
+                let mut col = DurationNanosecondBuilder::new();
+                for epoch in epochs {
+                    col.append_value(EpochRepr::j2000_nanoseconds(epoch, ts));
+                }
+                Arc::new(col.finish())
+            }
+            repr => {
+                let mut col = Float64Builder::new();
+                for epoch in epochs {
+                    col.append_value(repr.to_f64(epoch, ts).unwrap());
+                }
+                Arc::new(col.finish())
+            }
+        }
+    }
+
+    /// Builds the epoch columns of the provided epochs, matching the fields of `epoch_fields`.
+    pub(crate) fn epoch_columns<I: Iterator<Item = Epoch>>(&self, epochs: I) -> Vec<ArrayRef> {
+        if self.has_legacy_seconds_column() {
+            let epochs = epochs.collect::<Vec<Epoch>>();
+            let legacy = Self {
+                epoch_repr: EpochRepr::SecondsPastJ2000,
+                ..self.clone()
+            };
+            vec![
+                self.epoch_column(epochs.iter().copied()),
+                legacy.epoch_column(epochs.into_iter()),
+            ]
+        } else {
+            vec![self.epoch_column(epochs)]
+        }
+    }
+
     /// Modifies the provided path to include the timestamp if required.
     pub(crate) fn actual_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
         let mut path_buf = path.as_ref().to_path_buf();
@@ -131,6 +252,178 @@ impl ExportCfg {
     }
 }
 
+/// Representation of exported epochs, independent of their time scale.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EpochRepr {
+    /// ISO 8601 string without time scale suffix, e.g. `2017-01-01T00:00:00.000000000`
+    #[default]
+    Gregorian,
+    /// Modified Julian Date, in days
+    Mjd,
+    /// Julian Date, in days
+    Jd,
+    /// Seconds elapsed since 2000-01-01 12:00:00 in the time scale
+    SecondsPastJ2000,
+    /// Nanoseconds elapsed since 2000-01-01 12:00:00 in the time scale, stored as an Arrow duration (i.e. a 64 bit integer),
+    /// which is exact within 292 years of J2000, unlike seconds stored as a 64 bit float
+    NanosecondsPastJ2000,
+}
+
+impl EpochRepr {
+    /// Returns the label of an epoch column in this representation and the provided time scale, e.g. `Epoch MJD (GPST)`.
+    pub fn label(&self, ts: TimeScale) -> String {
+        match self {
+            Self::Gregorian => format!("Epoch ({ts})"),
+            Self::Mjd => format!("Epoch MJD ({ts})"),
+            Self::Jd => format!("Epoch JD ({ts})"),
+            Self::SecondsPastJ2000 => format!("Epoch seconds past J2000 ({ts})"),
+            Self::NanosecondsPastJ2000 => format!("Epoch nanoseconds past J2000 ({ts})"),
+        }
+    }
+
+    /// Returns the representation and time scale of an epoch column from its label, or None if this is not an epoch column.
+    pub fn from_label(label: &str) -> Option<(Self, TimeScale)> {
+        let (prefix, ts) = label.strip_suffix(')')?.rsplit_once(" (")?;
+        let repr = match prefix {
+            "Epoch" => Self::Gregorian,
+            "Epoch MJD" => Self::Mjd,
+            "Epoch JD" => Self::Jd,
+            "Epoch seconds past J2000" => Self::SecondsPastJ2000,
+            "Epoch nanoseconds past J2000" => Self::NanosecondsPastJ2000,
+            _ => return None,
+        };
+        Some((repr, TimeScale::from_str(ts).ok()?))
+    }
+
+    /// Returns the number of nanoseconds from J2000 in the provided time scale to this epoch, saturated at the bounds of an i64.
+    pub fn j2000_nanoseconds(epoch: Epoch, ts: TimeScale) -> i64 {
+        let ns = (epoch.to_time_scale(ts) - Epoch::from_gregorian_at_noon(2000, 1, 1, ts))
+            .total_nanoseconds();
+        ns.clamp(i64::MIN.into(), i64::MAX.into()) as i64
+    }
+
+    /// Returns the epoch from its numerical value in this representation and the provided time scale, or None if Gregorian.
+    ///
+    /// This is the inverse of `to_f64`, and the nanoseconds past J2000 are rounded to the nearest nanosecond.
+    pub fn epoch_from_f64(&self, value: f64, ts: TimeScale) -> Option<Epoch> {
+        let mjd_epoch = |days: f64| {
+            if ts == TimeScale::UTC {
+                Epoch::from_mjd_utc(days)
+            } else {
+                Epoch::from_gregorian_at_midnight(1858, 11, 17, ts) + days * Unit::Day
+            }
+        };
+
+        match self {
+            Self::Gregorian => None,
+            Self::Mjd => Some(mjd_epoch(value)),
+            Self::Jd => Some(mjd_epoch(value - 2_400_000.5)),
+            Self::SecondsPastJ2000 => {
+                Some(Epoch::from_gregorian_at_noon(2000, 1, 1, ts) + value * Unit::Second)
+            }
+            Self::NanosecondsPastJ2000 => Some(
+                Epoch::from_gregorian_at_noon(2000, 1, 1, ts)
+                    + Duration::from_total_nanoseconds(value.round() as i128),
+            ),
+        }
+    }
+
+    /// Returns the epochs stored in the provided column, whose representation and time scale are given by its label.
+    ///
+    /// Gregorian columns must be strings, nanoseconds past J2000 must be Arrow durations in nanoseconds, and the other
+    /// representations must be 64 bit floats.
+    pub fn epochs_from_column(
+        label: &str,
+        column: &dyn Array,
+    ) -> Result<Vec<Epoch>, InputOutputError> {
+        let (repr, ts) = Self::from_label(label).context(UnsupportedDataSnafu {
+            which: label.to_string(),
+        })?;
+
+        let type_mismatch = || InputOutputError::Inconsistency {
+            msg: format!("epoch column `{label}` is of type {}", column.data_type()),
+        };
+
+        match repr {
+            Self::Gregorian => {
+                let col = column
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .ok_or_else(type_mismatch)?;
+                col.iter()
+                    .map(|value| {
+                        let value = value.context(MissingDataSnafu {
+                            which: format!("epoch in `{label}`"),
+                        })?;
+                        let parsed = if ts == TimeScale::UTC {
+                            Epoch::from_gregorian_str(value)
+                        } else {
+                            Epoch::from_str(&format!("{value} {ts}"))
+                        };
+                        parsed.map_err(|e| InputOutputError::Inconsistency {
+                            msg: format!("{e} when parsing epoch `{value}`"),
+                        })
+                    })
+                    .collect()
+            }
+            Self::NanosecondsPastJ2000 => {
+                let col = column
+                    .as_any()
+                    .downcast_ref::<DurationNanosecondArray>()
+                    .ok_or_else(type_mismatch)?;
+                let j2000 = Epoch::from_gregorian_at_noon(2000, 1, 1, ts);
+                col.iter()
+                    .map(|value| {
+                        let ns = value.context(MissingDataSnafu {
+                            which: format!("epoch in `{label}`"),
+                        })?;
+                        Ok(j2000 + Duration::from_total_nanoseconds(ns.into()))
+                    })
+                    .collect()
+            }
+            _ => {
+                let col = column
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .ok_or_else(type_mismatch)?;
+                col.iter()
+                    .map(|value| {
+                        let value = value.context(MissingDataSnafu {
+                            which: format!("epoch in `{label}`"),
+                        })?;
+                        Ok(repr.epoch_from_f64(value, ts).unwrap())
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the numerical value of this epoch in this representation and the provided time scale, or None if Gregorian.
+    ///
+    /// MJD and JD in UTC follow the usual convention of days of 86400 seconds, i.e. they do not count leap seconds.
+    pub fn to_f64(&self, epoch: Epoch, ts: TimeScale) -> Option<f64> {
+        let mjd_days = || {
+            if ts == TimeScale::UTC {
+                epoch.to_mjd_utc_days()
+            } else {
+                (epoch.to_time_scale(ts) - Epoch::from_gregorian_at_midnight(1858, 11, 17, ts))
+                    .to_unit(Unit::Day)
+            }
+        };
+
+        match self {
+            Self::Gregorian => None,
+            Self::Mjd => Some(mjd_days()),
+            Self::Jd => Some(mjd_days() + 2_400_000.5),
+            Self::SecondsPastJ2000 => Some(
+                (epoch.to_time_scale(ts) - Epoch::from_gregorian_at_noon(2000, 1, 1, ts))
+                    .to_seconds(),
+            ),
+            Self::NanosecondsPastJ2000 => Some(Self::j2000_nanoseconds(epoch, ts) as f64),
+        }
+    }
+}
+
 #[cfg(feature = "python")]
 #[pymethods]
 impl ExportCfg {
@@ -165,6 +458,12 @@ pub enum ConfigError {
     #[snafu(display("failed to parse YAML configuration file: {source}"))]
     ParseError { source: serde_yaml::Error },
 
+    #[snafu(display("failed to write configuration file: {source}"))]
+    WriteError { source: io::Error },
+
+    #[snafu(display("failed to serialize configuration to YAML: {source}"))]
+    SerializeError { source: serde_yaml::Error },
+
     #[snafu(display("of invalid configuration: {msg}"))]
     InvalidConfig { msg: String },
 }
@@ -200,6 +499,11 @@ pub enum InputOutputError {
         source: ArrowError,
         action: &'static str,
     },
+    #[snafu(display("{action} encountered a bincode error: {source}"))]
+    BincodeError {
+        source: bincode::Error,
+        action: &'static str,
+    },
     #[snafu(display("error parsing `{data}` as Dhall config: {err}"))]
     ParseDhall { data: String, err: String },
     #[snafu(display("error serializing {what} to Dhall: {err}"))]
@@ -246,6 +550,17 @@ pub trait ConfigRepr: Debug + Sized + Serialize + DeserializeOwned {
         serde_yaml::from_reader(reader).context(ParseSnafu)
     }
 
+    /// Saves this configuration as YAML to the provided path, overwriting any existing file
+    fn save<P>(&self, path: P) -> Result<(), ConfigError>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::create(path).context(WriteSnafu)?;
+        let writer = BufWriter::new(file);
+
+        serde_yaml::to_writer(writer, self).context(SerializeSnafu)
+    }
+
     /// Builds a sequence of "Selves" from the provided string of a yaml
     fn loads_many(data: &str) -> Result<Vec<Self>, ConfigError> {
         debug!("Loading YAML:\n{data}");
@@ -349,3 +664,145 @@ impl From<NyxError> for ParsingError {
         Self::ExecutionError(error)
     }
 }
+
+#[cfg(test)]
+mod ut_epoch_repr {
+    use super::*;
+    use arrow::array::{Array, StringArray};
+
+    #[test]
+    fn epoch_columns_time_scales() {
+        // Default export remains readable by Nyx
+        let cfg = ExportCfg::default();
+        assert_eq!(cfg.epoch_field().name(), "Epoch (UTC)");
+        assert_eq!(cfg.epoch_field().data_type(), &DataType::Utf8);
+
+        let cfg = ExportCfg::builder()
+            .time_scale(TimeScale::GPST)
+            .epoch_repr(EpochRepr::Mjd)
+            .build();
+        assert_eq!(cfg.epoch_field().name(), "Epoch MJD (GPST)");
+        assert_eq!(cfg.epoch_field().data_type(), &DataType::Float64);
+
+        let offset_s = |epoch: Epoch, repr: EpochRepr, ts: TimeScale| {
+            (repr.to_f64(epoch, ts).unwrap() - repr.to_f64(epoch, TimeScale::UTC).unwrap())
+                * match repr {
+                    EpochRepr::SecondsPastJ2000 => 1.0,
+                    _ => 86_400.0,
+                }
+        };
+
+        // Around the leap second of 2016-12-31
+        let before = Epoch::from_gregorian_utc_hms(2016, 12, 31, 23, 59, 0);
+        let after = Epoch::from_gregorian_utc_hms(2017, 1, 1, 0, 1, 0);
+
+        for (epoch, leap_s) in [(before, 36.0), (after, 37.0)] {
+            for repr in [EpochRepr::Mjd, EpochRepr::Jd] {
+                let tai_utc = offset_s(epoch, repr, TimeScale::TAI);
+                let tt_utc = offset_s(epoch, repr, TimeScale::TT);
+                assert!(
+                    (tai_utc - leap_s).abs() < 1e-4,
+                    "{repr:?} TAI-UTC = {tai_utc}"
+                );
+                assert!(
+                    (tt_utc - leap_s - 32.184).abs() < 1e-4,
+                    "{repr:?} TT-UTC = {tt_utc}"
+                );
+            }
+
+            let tt_tai = EpochRepr::SecondsPastJ2000
+                .to_f64(epoch, TimeScale::TT)
+                .unwrap()
+                - EpochRepr::SecondsPastJ2000
+                    .to_f64(epoch, TimeScale::TAI)
+                    .unwrap();
+            assert!((tt_tai - 32.184).abs() < 1e-9);
+        }
+
+        // The Gregorian representation is the clock reading in the requested time scale
+        let cfg = ExportCfg::builder().time_scale(TimeScale::TAI).build();
+        assert_eq!(cfg.epoch_field().name(), "Epoch (TAI)");
+        let col = cfg.epoch_column([after].into_iter());
+        let col = col.as_any().downcast_ref::<StringArray>().unwrap();
+        assert!(col.value(0).starts_with("2017-01-01T00:01:37"));
+
+        // The MJD in UTC is the usual one
+        assert!(
+            (EpochRepr::Mjd
+                .to_f64(
+                    Epoch::from_gregorian_utc_at_midnight(2017, 1, 1),
+                    TimeScale::UTC
+                )
+                .unwrap()
+                - 57_754.0)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn nanoseconds_past_j2000_precision() {
+        let cfg = ExportCfg::builder()
+            .time_scale(TimeScale::TDB)
+            .epoch_repr(EpochRepr::NanosecondsPastJ2000)
+            .legacy_seconds_column(true)
+            .build();
+
+        let fields = cfg.epoch_fields();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name(), "Epoch nanoseconds past J2000 (TDB)");
+        assert_eq!(fields[1].name(), "Epoch seconds past J2000 (TDB)");
+        assert_eq!(
+            EpochRepr::from_label(fields[0].name()),
+            Some((EpochRepr::NanosecondsPastJ2000, TimeScale::TDB))
+        );
+        assert_eq!(EpochRepr::from_label("Tracking device"), None);
+
+        // Epochs every 1.000000007 seconds, thirty years after J2000
+        let start = Epoch::from_gregorian_at_noon(2030, 1, 1, TimeScale::TDB);
+        let step = Unit::Second * 1 + Unit::Nanosecond * 7;
+        let epochs = (0..1_000_i64)
+            .map(|i| start + step * i)
+            .collect::<Vec<Epoch>>();
+
+        let columns = cfg.epoch_columns(epochs.iter().copied());
+        assert_eq!(columns.len(), 2);
+
+        // The nanoseconds column round trips exactly
+        let exact = EpochRepr::epochs_from_column(fields[0].name(), &columns[0]).unwrap();
+        assert_eq!(exact, epochs);
+
+        // The seconds stored in a 64 bit float cannot represent these epochs to the nanosecond
+        let legacy = EpochRepr::epochs_from_column(fields[1].name(), &columns[1]).unwrap();
+        let max_err = epochs
+            .iter()
+            .zip(legacy)
+            .map(|(orig, loaded)| (loaded - *orig).abs())
+            .max()
+            .unwrap();
+        // Thirty years after J2000, one unit in the last place of the seconds is 2^-23 s, i.e. about 119 ns
+        let ulp = Unit::Second * 2.0_f64.powi(-23);
+        assert!(max_err > 10 * Unit::Nanosecond);
+        assert!(max_err <= ulp, "max error of the legacy column: {max_err}");
+
+        // Other representations and time scales are also read back
+        for (repr, ts, tol) in [
+            (EpochRepr::Gregorian, TimeScale::GPST, Unit::Nanosecond * 1),
+            (EpochRepr::Gregorian, TimeScale::UTC, Unit::Nanosecond * 1),
+            (EpochRepr::Mjd, TimeScale::UTC, Unit::Microsecond * 100),
+            (EpochRepr::Jd, TimeScale::TAI, Unit::Microsecond * 100),
+            (
+                EpochRepr::SecondsPastJ2000,
+                TimeScale::TT,
+                Unit::Microsecond * 1,
+            ),
+        ] {
+            let cfg = ExportCfg::builder().time_scale(ts).epoch_repr(repr).build();
+            let column = cfg.epoch_column(epochs.iter().copied());
+            let loaded = EpochRepr::epochs_from_column(cfg.epoch_field().name(), &column).unwrap();
+            for (orig, loaded) in epochs.iter().zip(loaded) {
+                assert!((loaded - *orig).abs() < tol, "{repr:?} in {ts}");
+            }
+        }
+    }
+}
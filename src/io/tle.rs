@@ -0,0 +1,236 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::Orbit;
+use crate::linalg::{Matrix3, Vector3};
+use crate::time::{Epoch, Unit};
+use anise::prelude::Frame;
+use snafu::prelude::*;
+use std::fmt;
+
+/// Errors of the parsing and propagation of two line element sets
+#[derive(Debug, PartialEq, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum TleError {
+    #[snafu(display("invalid TLE: {msg}"))]
+    TleParse { msg: String },
+    #[snafu(display("SGP4 propagation failed at {epoch}: {msg}"))]
+    Sgp4Propagation { epoch: Epoch, msg: String },
+}
+
+/// A two line element set (TLE), propagated with SGP4 (or SDP4 for deep space objects).
+///
+/// SGP4 produces states in the True Equator Mean Equinox (TEME) frame of date, which `orbit_at` converts into EME2000.
+#[derive(Clone, Debug)]
+pub struct Tle {
+    /// Epoch of the element set, in UTC
+    pub epoch: Epoch,
+    /// NORAD catalog number of the object
+    pub norad_id: u64,
+    elements: sgp4::Elements,
+}
+
+impl Tle {
+    /// Parses a TLE from its two lines, the checksums are verified.
+    pub fn from_lines(line1: &str, line2: &str) -> Result<Self, TleError> {
+        let elements =
+            sgp4::Elements::from_tle(None, line1.trim().as_bytes(), line2.trim().as_bytes())
+                .map_err(|e| TleError::TleParse { msg: e.to_string() })?;
+
+        // Ensures that the elements can be propagated, e.g. that the orbit is not decayed.
+        sgp4::Constants::from_elements(&elements)
+            .map_err(|e| TleError::TleParse { msg: e.to_string() })?;
+
+        Ok(Self {
+            epoch: parse_epoch(line1.trim())?,
+            norad_id: elements.norad_id,
+            elements,
+        })
+    }
+
+    /// Position (km) and velocity (km/s) in the TEME frame of date at the provided epoch
+    pub fn teme_at(&self, epoch: Epoch) -> Result<(Vector3<f64>, Vector3<f64>), TleError> {
+        let minutes = (epoch - self.epoch).to_unit(Unit::Minute);
+        let constants = sgp4::Constants::from_elements(&self.elements)
+            .map_err(|e| TleError::TleParse { msg: e.to_string() })?;
+        let prediction = constants
+            .propagate(sgp4::MinutesSinceEpoch(minutes))
+            .map_err(|e| TleError::Sgp4Propagation {
+                epoch,
+                msg: e.to_string(),
+            })?;
+
+        Ok((
+            Vector3::from(prediction.position),
+            Vector3::from(prediction.velocity),
+        ))
+    }
+
+    /// Propagates this TLE to the provided epoch and returns the state in the provided Earth centered EME2000 frame,
+    /// which should include the gravitational parameter of the Earth.
+    pub fn orbit_at(&self, epoch: Epoch, eme2000: Frame) -> Result<Orbit, TleError> {
+        let (radius_km, velocity_km_s) = self.teme_at(epoch)?;
+        // The rotation of TEME with respect to EME2000 is only due to precession and nutation, so its rate is negligible.
+        let dcm = teme_to_eme2000(epoch);
+        let radius_km = dcm * radius_km;
+        let velocity_km_s = dcm * velocity_km_s;
+
+        Ok(Orbit::new(
+            radius_km.x,
+            radius_km.y,
+            radius_km.z,
+            velocity_km_s.x,
+            velocity_km_s.y,
+            velocity_km_s.z,
+            epoch,
+            eme2000,
+        ))
+    }
+}
+
+impl fmt::Display for Tle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TLE of NORAD {} at {}", self.norad_id, self.epoch)
+    }
+}
+
+/// Parses the epoch of the first line of a TLE, in columns 19 to 32 as a two digit year and a fractional day of year.
+fn parse_epoch(line1: &str) -> Result<Epoch, TleError> {
+    let field = line1.get(18..32).ok_or_else(|| TleError::TleParse {
+        msg: "line 1 is too short to contain the epoch".to_string(),
+    })?;
+
+    let (year, day_of_year) = match (
+        field[..2].trim().parse::<i32>(),
+        field[2..].trim().parse::<f64>(),
+    ) {
+        (Ok(year), Ok(day_of_year)) => (year, day_of_year),
+        _ => {
+            return Err(TleError::TleParse {
+                msg: format!("invalid epoch `{field}`"),
+            })
+        }
+    };
+
+    // Two digit years from 57 onward are in the twentieth century, per the NORAD convention.
+    let year = if year < 57 { 2000 + year } else { 1900 + year };
+
+    Ok(Epoch::from_gregorian_utc_at_midnight(year, 1, 1) + (day_of_year - 1.0) * Unit::Day)
+}
+
+/// Direction cosine matrix from the TEME frame of date to EME2000 at the provided epoch.
+///
+/// This applies the equation of the equinoxes, the IAU 1980 nutation and the IAU 1976 precession, following Vallado's
+/// "Revisiting Spacetrack Report #3" (2006). The nutation series is truncated to its thirty largest terms, which
+/// limits the error of the rotation to a few milliarcseconds, well below the accuracy of any TLE.
+pub fn teme_to_eme2000(epoch: Epoch) -> Matrix3<f64> {
+    const ARCSEC_TO_RAD: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+    // Julian centuries of TT since J2000
+    let t = (epoch.to_jde_tt_days() - 2_451_545.0) / 36_525.0;
+    let (t2, t3) = (t * t, t * t * t);
+
+    // Delaunay arguments of the IAU 1980 nutation theory, in degrees
+    let d = 297.85036 + 445_267.111_480 * t - 0.001_914_2 * t2 + t3 / 189_474.0;
+    let m = 357.52772 + 35_999.050_340 * t - 0.000_160_3 * t2 - t3 / 300_000.0;
+    let mp = 134.96298 + 477_198.867_398 * t + 0.008_697_2 * t2 + t3 / 56_250.0;
+    let f = 93.27191 + 483_202.017_538 * t - 0.003_682_5 * t2 + t3 / 327_270.0;
+    let om = 125.04452 - 1_934.136_261 * t + 0.002_070_8 * t2 + t3 / 450_000.0;
+
+    let mut dpsi = 0.0;
+    let mut deps = 0.0;
+    for (mult, psi, psi_t, eps, eps_t) in NUTATION_1980 {
+        let arg =
+            (mult[0] * d + mult[1] * m + mult[2] * mp + mult[3] * f + mult[4] * om).to_radians();
+        dpsi += (psi + psi_t * t) * arg.sin();
+        deps += (eps + eps_t * t) * arg.cos();
+    }
+    // The coefficients are in units of 0.1 milliarcsecond
+    let dpsi = dpsi * 1e-4 * ARCSEC_TO_RAD;
+    let deps = deps * 1e-4 * ARCSEC_TO_RAD;
+
+    let mean_eps = (84_381.448 - 46.8150 * t - 0.000_59 * t2 + 0.001_813 * t3) * ARCSEC_TO_RAD;
+    let true_eps = mean_eps + deps;
+
+    // TEME to true of date: equation of the equinoxes
+    let eq_equinox = dpsi * mean_eps.cos();
+    let teme_to_tod = r3(-eq_equinox);
+    // True of date to mean of date: nutation
+    let tod_to_mod = r1(-mean_eps) * r3(dpsi) * r1(true_eps);
+    // Mean of date to EME2000: precession
+    let zeta = (2_306.2181 * t + 0.301_88 * t2 + 0.017_998 * t3) * ARCSEC_TO_RAD;
+    let theta = (2_004.3109 * t - 0.426_65 * t2 - 0.041_833 * t3) * ARCSEC_TO_RAD;
+    let z = (2_306.2181 * t + 1.094_68 * t2 + 0.018_203 * t3) * ARCSEC_TO_RAD;
+    let mod_to_eme2000 = r3(zeta) * r2(-theta) * r3(z);
+
+    mod_to_eme2000 * tod_to_mod * teme_to_tod
+}
+
+/// Rotation of the coordinate frame by the provided angle about the first axis
+fn r1(angle_rad: f64) -> Matrix3<f64> {
+    let (s, c) = angle_rad.sin_cos();
+    Matrix3::new(1.0, 0.0, 0.0, 0.0, c, s, 0.0, -s, c)
+}
+
+/// Rotation of the coordinate frame by the provided angle about the second axis
+fn r2(angle_rad: f64) -> Matrix3<f64> {
+    let (s, c) = angle_rad.sin_cos();
+    Matrix3::new(c, 0.0, -s, 0.0, 1.0, 0.0, s, 0.0, c)
+}
+
+/// Rotation of the coordinate frame by the provided angle about the third axis
+fn r3(angle_rad: f64) -> Matrix3<f64> {
+    let (s, c) = angle_rad.sin_cos();
+    Matrix3::new(c, s, 0.0, -s, c, 0.0, 0.0, 0.0, 1.0)
+}
+
+/// Largest terms of the IAU 1980 nutation series: multipliers of (D, M, M', F, Ω), then the longitude coefficients
+/// (constant and rate per century) and the obliquity coefficients, in units of 0.1 milliarcsecond.
+#[rustfmt::skip]
+const NUTATION_1980: [([f64; 5], f64, f64, f64, f64); 30] = [
+    ([0.0, 0.0, 0.0, 0.0, 1.0], -171_996.0, -174.2, 92_025.0, 8.9),
+    ([-2.0, 0.0, 0.0, 2.0, 2.0], -13_187.0, -1.6, 5_736.0, -3.1),
+    ([0.0, 0.0, 0.0, 2.0, 2.0], -2_274.0, -0.2, 977.0, -0.5),
+    ([0.0, 0.0, 0.0, 0.0, 2.0], 2_062.0, 0.2, -895.0, 0.5),
+    ([0.0, 1.0, 0.0, 0.0, 0.0], 1_426.0, -3.4, 54.0, -0.1),
+    ([0.0, 0.0, 1.0, 0.0, 0.0], 712.0, 0.1, -7.0, 0.0),
+    ([-2.0, 1.0, 0.0, 2.0, 2.0], -517.0, 1.2, 224.0, -0.6),
+    ([0.0, 0.0, 0.0, 2.0, 1.0], -386.0, -0.4, 200.0, 0.0),
+    ([0.0, 0.0, 1.0, 2.0, 2.0], -301.0, 0.0, 129.0, -0.1),
+    ([-2.0, -1.0, 0.0, 2.0, 2.0], 217.0, -0.5, -95.0, 0.3),
+    ([-2.0, 0.0, 1.0, 0.0, 0.0], -158.0, 0.0, 0.0, 0.0),
+    ([-2.0, 0.0, 0.0, 2.0, 1.0], 129.0, 0.1, -70.0, 0.0),
+    ([0.0, 0.0, -1.0, 2.0, 2.0], 123.0, 0.0, -53.0, 0.0),
+    ([2.0, 0.0, 0.0, 0.0, 0.0], 63.0, 0.0, 0.0, 0.0),
+    ([0.0, 0.0, 1.0, 0.0, 1.0], 63.0, 0.1, -33.0, 0.0),
+    ([2.0, 0.0, -1.0, 2.0, 2.0], -59.0, 0.0, 26.0, 0.0),
+    ([0.0, 0.0, -1.0, 0.0, 1.0], -58.0, -0.1, 32.0, 0.0),
+    ([0.0, 0.0, 1.0, 2.0, 1.0], -51.0, 0.0, 27.0, 0.0),
+    ([-2.0, 0.0, 2.0, 0.0, 0.0], 48.0, 0.0, 0.0, 0.0),
+    ([0.0, 0.0, -2.0, 2.0, 1.0], 46.0, 0.0, -24.0, 0.0),
+    ([2.0, 0.0, 0.0, 2.0, 2.0], -38.0, 0.0, 16.0, 0.0),
+    ([0.0, 0.0, 2.0, 2.0, 2.0], -31.0, 0.0, 13.0, 0.0),
+    ([0.0, 0.0, 2.0, 0.0, 0.0], 29.0, 0.0, 0.0, 0.0),
+    ([-2.0, 0.0, 1.0, 2.0, 2.0], 29.0, 0.0, -12.0, 0.0),
+    ([0.0, 0.0, 0.0, 2.0, 0.0], 26.0, 0.0, 0.0, 0.0),
+    ([-2.0, 0.0, 0.0, 2.0, 0.0], -22.0, 0.0, 0.0, 0.0),
+    ([0.0, 0.0, -1.0, 2.0, 1.0], 21.0, 0.0, -10.0, 0.0),
+    ([0.0, 2.0, 0.0, 0.0, 0.0], 17.0, -0.1, 0.0, 0.0),
+    ([2.0, 0.0, -1.0, 0.0, 1.0], 16.0, 0.0, -8.0, 0.0),
+    ([-2.0, 2.0, 0.0, 2.0, 2.0], -16.0, 0.1, 7.0, 0.0),
+];
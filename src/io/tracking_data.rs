@@ -29,7 +29,6 @@ use arrow::{
     array::{Float64Array, StringArray},
     record_batch::RecordBatchReader,
 };
-use hifitime::Epoch;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use snafu::prelude::*;
 use std::fs::File;
@@ -38,7 +37,7 @@ use std::{collections::HashMap, error::Error, fmt::Display, path::Path};
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-use super::{InputOutputError, StdIOSnafu};
+use super::{EpochRepr, InputOutputError, StdIOSnafu};
 
 /// A dynamic tracking arc allows loading a set of measurements from a parquet file and converting them
 /// to the concrete measurement type when desired.
@@ -102,13 +101,19 @@ impl DynamicTrackingArc {
         })?;
 
         // Check the schema
-        let mut has_epoch = false;
+        let mut epoch_label: Option<String> = None;
         let mut has_tracking_dev = false;
         let mut range_avail = false;
         let mut rate_avail = false;
         for field in &reader.schema().fields {
+            if let Some((repr, _)) = EpochRepr::from_label(field.name()) {
+                // Prefer the exact nanoseconds past J2000 over the legacy seconds column exported alongside it
+                if epoch_label.is_none() || repr == EpochRepr::NanosecondsPastJ2000 {
+                    epoch_label = Some(field.name().clone());
+                }
+                continue;
+            }
             match field.name().as_str() {
-                "Epoch (UTC)" => has_epoch = true,
                 "Tracking device" => has_tracking_dev = true,
                 "Range (km)" => range_avail = true,
                 "Doppler (km/s)" => rate_avail = true,
@@ -116,12 +121,9 @@ impl DynamicTrackingArc {
             }
         }
 
-        ensure!(
-            has_epoch,
-            MissingDataSnafu {
-                which: "Epoch (UTC)"
-            }
-        );
+        let epoch_label = epoch_label.context(MissingDataSnafu {
+            which: "epoch column, e.g. Epoch (UTC)",
+        })?;
 
         ensure!(
             has_tracking_dev,
@@ -184,12 +186,10 @@ impl DynamicTrackingArc {
                 .downcast_ref::<StringArray>()
                 .unwrap();
 
-            let epochs = batch
-                .column_by_name("Epoch (UTC)")
-                .unwrap()
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .unwrap();
+            let epochs = EpochRepr::epochs_from_column(
+                &epoch_label,
+                batch.column_by_name(&epoch_label).unwrap(),
+            )?;
 
             // Now read the data depending on what we're deserializing as
             match expected_type {
@@ -213,11 +213,7 @@ impl DynamicTrackingArc {
                         arc.measurements.push((
                             tracking_device.value(i).to_string(),
                             Msr::from_observation(
-                                Epoch::from_gregorian_str(epochs.value(i)).map_err(|e| {
-                                    InputOutputError::Inconsistency {
-                                        msg: format!("{e} when parsing epoch"),
-                                    }
-                                })?,
+                                epochs[i],
                                 OVector::<f64, Msr::MeasurementSize>::from_iterator([
                                     range_data.value(i),
                                     rate_data.value(i),
@@ -239,11 +235,7 @@ impl DynamicTrackingArc {
                         arc.measurements.push((
                             tracking_device.value(i).to_string(),
                             Msr::from_observation(
-                                Epoch::from_gregorian_str(epochs.value(i)).map_err(|e| {
-                                    InputOutputError::Inconsistency {
-                                        msg: format!("{e} when parsing epoch"),
-                                    }
-                                })?,
+                                epochs[i],
                                 OVector::<f64, Msr::MeasurementSize>::from_iterator([
                                     range_data.value(i)
                                 ]),
@@ -264,11 +256,7 @@ impl DynamicTrackingArc {
                         arc.measurements.push((
                             tracking_device.value(i).to_string(),
                             Msr::from_observation(
-                                Epoch::from_gregorian_str(epochs.value(i)).map_err(|e| {
-                                    InputOutputError::Inconsistency {
-                                        msg: format!("{e} when parsing epoch"),
-                                    }
-                                })?,
+                                epochs[i],
                                 OVector::<f64, Msr::MeasurementSize>::from_iterator([
                                     rate_data.value(i)
                                 ]),
@@ -17,18 +17,24 @@
 */
 
 use anise::frames::Frame;
-use arrow::array::StringArray;
 use arrow::{array::Float64Array, record_batch::RecordBatchReader};
-use hifitime::Epoch;
+use hifitime::{Epoch, TimeScale};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+use parquet::file::statistics::Statistics;
 use snafu::prelude::*;
 use std::fs::File;
 use std::{collections::HashMap, fmt::Display, path::Path};
+use typed_builder::TypedBuilder;
 
 use crate::{
     io::MissingDataSnafu,
     linalg::{allocator::Allocator, DefaultAllocator},
-    md::{prelude::Traj, trajectory::Interpolatable, StateParameter},
+    md::{
+        prelude::Traj,
+        trajectory::{InterpMethod, Interpolatable},
+        StateParameter,
+    },
 };
 
 #[cfg(feature = "python")]
@@ -46,7 +52,32 @@ use pyo3::class::basic::CompareOp;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-use super::{InputOutputError, ParquetSnafu, StdIOSnafu};
+use super::{
+    ArrowSnafu, EpochRepr, InconsistencySnafu, InputOutputError, ParquetSnafu, StdIOSnafu,
+};
+
+/// Configuration for reading a trajectory from parquet, e.g. to only load a time window of a very large file.
+#[derive(Copy, Clone, Debug, Default, TypedBuilder)]
+#[builder(doc)]
+pub struct TrajReadCfg {
+    /// Only read the states from this epoch (included), defaults to the start of the file
+    #[builder(default, setter(strip_option))]
+    pub start_epoch: Option<Epoch>,
+    /// Only read the states until this epoch (included), defaults to the end of the file
+    #[builder(default, setter(strip_option))]
+    pub end_epoch: Option<Epoch>,
+    /// Only keep every N-th state between the start and end epochs for quick-look loading, defaults to every state
+    #[builder(default, setter(strip_option))]
+    pub every_nth: Option<usize>,
+}
+
+impl TrajReadCfg {
+    /// Returns whether the provided epoch is within the requested epochs
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        !matches!(self.start_epoch, Some(start) if epoch < start)
+            && !matches!(self.end_epoch, Some(end) if epoch > end)
+    }
+}
 
 /// A dynamic trajectory allows loading a trajectory Parquet file and converting it
 /// to the concrete trajectory state type when desired.
@@ -102,8 +133,28 @@ impl TrajectoryLoader {
         DefaultAllocator:
             Allocator<S::VecLength> + Allocator<S::Size> + Allocator<S::Size, S::Size>,
     {
+        self.to_traj_with_cfg(TrajReadCfg::default())
+    }
+
+    /// Reads the states of the loaded parquet file matching the provided configuration and converts them to the provided concrete state.
+    ///
+    /// The file is read one record batch at a time and only the state columns are decoded, so very large files need not fit in memory.
+    /// If the epochs were exported as seconds or nanoseconds past J2000, the row groups entirely outside of the requested epochs are not read at all.
+    pub fn to_traj_with_cfg<S>(&self, cfg: TrajReadCfg) -> Result<Traj<S>, InputOutputError>
+    where
+        S: Interpolatable,
+        DefaultAllocator:
+            Allocator<S::VecLength> + Allocator<S::Size> + Allocator<S::Size, S::Size>,
+    {
+        ensure!(
+            cfg.every_nth != Some(0),
+            InconsistencySnafu {
+                msg: "cannot read every 0-th state"
+            }
+        );
+
         // Check the schema
-        let mut has_epoch = false; // Required
+        let mut epoch_col: Option<(String, EpochRepr, TimeScale)> = None; // Required
         let mut frame = None;
 
         let mut found_fields = vec![
@@ -120,19 +171,32 @@ impl TrajectoryLoader {
             action: "opening output trajectory file",
         })?;
 
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
-
-        let reader = builder.build().context(ParquetSnafu {
-            action: "building output trajectory file",
+        let mut builder = ParquetRecordBatchReaderBuilder::try_new(file).context(ParquetSnafu {
+            action: "reading output trajectory file",
         })?;
 
-        for field in &reader.schema().fields {
-            if field.name().as_str() == "Epoch (UTC)" {
-                has_epoch = true;
+        // Name of the column of each state parameter, defaulting to its display name for files without parameter metadata
+        let mut column_names = found_fields
+            .iter()
+            .map(|(param, _)| param.to_field(None).name().clone())
+            .collect::<Vec<String>>();
+
+        for field in &builder.schema().fields {
+            if let Some((repr, ts)) = EpochRepr::from_label(field.name()) {
+                // Prefer the exact nanoseconds past J2000 over the legacy seconds column exported alongside it
+                if !matches!(epoch_col, Some((_, EpochRepr::NanosecondsPastJ2000, _))) {
+                    epoch_col = Some((field.name().clone(), repr, ts));
+                }
             } else {
-                for potential_field in &mut found_fields {
-                    if field.name() == potential_field.0.to_field(None).name() {
+                for (i, potential_field) in found_fields.iter_mut().enumerate() {
+                    // Match on the parameter annotation when available, and on the display name otherwise
+                    let is_match = match field.metadata().get("parameter") {
+                        Some(param) => param == &format!("{:?}", potential_field.0),
+                        None => field.name() == &column_names[i],
+                    };
+                    if is_match {
                         potential_field.1 = true;
+                        column_names[i] = field.name().clone();
                         if potential_field.0 != StateParameter::FuelMass {
                             if let Some(frame_info) = field.metadata().get("Frame") {
                                 // Frame is expected to be serialized as Dhall.
@@ -153,12 +217,37 @@ impl TrajectoryLoader {
             }
         }
 
-        ensure!(
-            has_epoch,
-            MissingDataSnafu {
-                which: "Epoch (UTC)"
+        let (epoch_label, epoch_repr, epoch_ts) = match epoch_col {
+            Some(epoch_col) => epoch_col,
+            None => {
+                return MissingDataSnafu {
+                    which: "epoch column, e.g. Epoch (UTC)".to_string(),
+                }
+                .fail()
             }
-        );
+        };
+        if frame.is_none() {
+            // Rebuild the frame from its identifiers in the file metadata, without its gravitational parameter or shape.
+            if let (Some(ephemeris_id), Some(orientation_id)) = (
+                self.metadata.get("Frame ephemeris ID"),
+                self.metadata.get("Frame orientation ID"),
+            ) {
+                match (ephemeris_id.parse(), orientation_id.parse()) {
+                    (Ok(ephemeris_id), Ok(orientation_id)) => {
+                        let fallback = Frame::new(ephemeris_id, orientation_id);
+                        warn!("no serialized frame in the fields, using {fallback} from the file metadata");
+                        frame = Some(fallback);
+                    }
+                    _ => {
+                        return Err(InputOutputError::Inconsistency {
+                            msg: format!(
+                                "invalid frame identifiers in metadata: ephemeris `{ephemeris_id}`, orientation `{orientation_id}`"
+                            ),
+                        })
+                    }
+                }
+            }
+        }
 
         ensure!(
             frame.is_some(),
@@ -199,26 +288,124 @@ impl TrajectoryLoader {
             }
         }
 
+        // Only decode the epoch and state columns
+        let mut columns = vec![epoch_label.clone()];
+        for ((_, exists), name) in found_fields.iter().zip(&column_names) {
+            if *exists {
+                columns.push(name.clone());
+            }
+        }
+        let roots = builder
+            .schema()
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| columns.contains(field.name()))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<usize>>();
+        let mask = ProjectionMask::roots(builder.parquet_schema(), roots);
+        builder = builder.with_projection(mask);
+
+        // Skip the row groups outside of the requested epochs, using the statistics of the numeric epoch column
+        if matches!(
+            epoch_repr,
+            EpochRepr::SecondsPastJ2000 | EpochRepr::NanosecondsPastJ2000
+        ) && (cfg.start_epoch.is_some() || cfg.end_epoch.is_some())
+        {
+            let start_ns = cfg.start_epoch.map_or(i64::MIN, |epoch| {
+                EpochRepr::j2000_nanoseconds(epoch, epoch_ts)
+            });
+            let end_ns = cfg.end_epoch.map_or(i64::MAX, |epoch| {
+                EpochRepr::j2000_nanoseconds(epoch, epoch_ts)
+            });
+            let start_s = cfg.start_epoch.map_or(f64::NEG_INFINITY, |epoch| {
+                EpochRepr::SecondsPastJ2000.to_f64(epoch, epoch_ts).unwrap()
+            });
+            let end_s = cfg.end_epoch.map_or(f64::INFINITY, |epoch| {
+                EpochRepr::SecondsPastJ2000.to_f64(epoch, epoch_ts).unwrap()
+            });
+
+            if let Some(col_idx) = builder
+                .parquet_schema()
+                .columns()
+                .iter()
+                .position(|col| col.name() == epoch_label)
+            {
+                let row_groups = builder
+                    .metadata()
+                    .row_groups()
+                    .iter()
+                    .enumerate()
+                    .filter(
+                        |(_, row_group)| match row_group.column(col_idx).statistics() {
+                            Some(Statistics::Double(stats)) => {
+                                match (stats.min_opt(), stats.max_opt()) {
+                                    (Some(min_s), Some(max_s)) => {
+                                        *max_s >= start_s && *min_s <= end_s
+                                    }
+                                    _ => true,
+                                }
+                            }
+                            Some(Statistics::Int64(stats)) => {
+                                match (stats.min_opt(), stats.max_opt()) {
+                                    (Some(min_ns), Some(max_ns)) => {
+                                        *max_ns >= start_ns && *min_ns <= end_ns
+                                    }
+                                    _ => true,
+                                }
+                            }
+                            // Without statistics, this row group must be read.
+                            _ => true,
+                        },
+                    )
+                    .map(|(idx, _)| idx)
+                    .collect::<Vec<usize>>();
+
+                debug!(
+                    "reading {} of {} row groups of {}",
+                    row_groups.len(),
+                    builder.metadata().num_row_groups(),
+                    self.path
+                );
+                builder = builder.with_row_groups(row_groups);
+            }
+        }
+
+        let reader = builder.build().context(ParquetSnafu {
+            action: "building output trajectory file",
+        })?;
+
         // At this stage, we know that the measurement is valid and the conversion is supported.
-        let mut traj = Traj::default();
+        let mut traj = Traj {
+            name: self.name().map(|name| name.to_string()),
+            states: Vec::new(),
+            inertial_interp: None,
+            gaps: Vec::new(),
+            annotations: Vec::new(),
+            interpolation: InterpMethod::default(),
+            max_states: None,
+        };
+
+        // Number of states within the requested epochs so far, used to only keep every N-th state
+        let mut num_in_range = 0_usize;
 
         // Now convert each batch on the fly
         for maybe_batch in reader {
-            let batch = maybe_batch.unwrap();
+            let batch = maybe_batch.context(ArrowSnafu {
+                action: "reading trajectory record batch",
+            })?;
 
-            let epochs = batch
-                .column_by_name("Epoch (UTC)")
-                .unwrap()
-                .as_any()
-                .downcast_ref::<StringArray>()
-                .unwrap();
+            let epochs = EpochRepr::epochs_from_column(
+                &epoch_label,
+                batch.column_by_name(&epoch_label).unwrap(),
+            )?;
 
             let mut shared_data = vec![];
 
-            for (field, _) in found_fields.iter().take(found_fields.len() - 1) {
+            for name in column_names.iter().take(found_fields.len() - 1) {
                 shared_data.push(
                     batch
-                        .column_by_name(field.to_field(None).name())
+                        .column_by_name(name)
                         .unwrap()
                         .as_any()
                         .downcast_ref::<Float64Array>()
@@ -230,7 +417,7 @@ impl TrajectoryLoader {
                 // Read the fuel only if this is a spacecraft we're building
                 shared_data.push(
                     batch
-                        .column_by_name("fuel_mass (kg)")
+                        .column_by_name(column_names.last().unwrap())
                         .unwrap()
                         .as_any()
                         .downcast_ref::<Float64Array>()
@@ -238,16 +425,21 @@ impl TrajectoryLoader {
                 );
             }
 
-            // Grab the frame -- it should have been serialized with all of the data so we don't need to reload it.
-
             // Build the states
-            for i in 0..batch.num_rows() {
-                let mut state = S::zeros();
-                state.set_epoch(Epoch::from_gregorian_str(epochs.value(i)).map_err(|e| {
-                    InputOutputError::Inconsistency {
-                        msg: format!("{e} when parsing epoch"),
+            for (i, epoch) in epochs.into_iter().enumerate() {
+                if !cfg.contains(epoch) {
+                    continue;
+                }
+
+                num_in_range += 1;
+                if let Some(every_nth) = cfg.every_nth {
+                    if (num_in_range - 1) % every_nth != 0 {
+                        continue;
                     }
-                })?);
+                }
+
+                let mut state = S::zeros();
+                state.set_epoch(epoch);
                 state.set_frame(frame.unwrap()); // We checked it was set above with an ensure! call
                 state.unset_stm(); // We don't have any STM data, so let's unset this.
 
@@ -267,6 +459,16 @@ impl TrajectoryLoader {
         Ok(traj)
     }
 
+    /// Returns the name of the trajectory, if it was set when exported.
+    pub fn name(&self) -> Option<&str> {
+        self.metadata.get("Name").map(|name| name.as_str())
+    }
+
+    /// Returns the name of the frame of the trajectory, if it was stored in the file metadata.
+    pub fn frame_name(&self) -> Option<&str> {
+        self.metadata.get("Frame name").map(|name| name.as_str())
+    }
+
     fn repr(&self) -> Vec<String> {
         let mut r = Vec::new();
         r.push(format!("File: {}", self.path));
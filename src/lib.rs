@@ -78,7 +78,7 @@ pub mod linalg {
 }
 
 /// Re-export some useful things
-pub use self::cosmic::{Orbit, Spacecraft, State, TimeTagged};
+pub use self::cosmic::{Orbit, OrbitExt, Spacecraft, State, TimeTagged};
 
 #[cfg(feature = "python")]
 mod python;
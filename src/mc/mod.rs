@@ -18,9 +18,18 @@
 
 use rand::prelude::*;
 use rand_distr::{Distribution, Normal, Uniform};
-pub use rand_pcg::Pcg64Mcg;
 
 pub mod helpers;
+mod rng;
+pub use rng::NyxRng;
+
+/// The generator previously used by the Monte Carlo and noise models.
+#[deprecated(
+    since = "2.0.0",
+    note = "use NyxRng, the random number generator of all stochastic features of Nyx"
+)]
+pub type Pcg64Mcg = rand_pcg::Pcg64Mcg;
+
 mod montecarlo;
 
 pub use montecarlo::MonteCarlo;
@@ -16,7 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::Pcg64Mcg;
+use super::NyxRng;
 use crate::dynamics::Dynamics;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
@@ -32,7 +32,6 @@ use crate::State;
 use anise::almanac::Almanac;
 use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
 use log::info;
-use rand::SeedableRng;
 use rand_distr::Distribution;
 use rayon::prelude::ParallelIterator;
 use rayon::prelude::*;
@@ -48,7 +47,7 @@ pub struct MonteCarlo<S: Interpolatable, Distr: Distribution<DispersedState<S>>>
 where
     DefaultAllocator: Allocator<S::Size> + Allocator<S::Size, S::Size> + Allocator<S::VecLength>,
 {
-    /// Seed of the [64bit PCG random number generator](https://www.pcg-random.org/index.html)
+    /// Seed of the random number generator, cf. [NyxRng]
     pub seed: Option<u128>,
     /// Generator of states for the Monte Carlo run
     pub random_state: Distr,
@@ -287,8 +286,8 @@ where
     ) -> Vec<(usize, DispersedState<S>)> {
         // Setup the RNG
         let rng = match seed {
-            Some(seed) => Pcg64Mcg::new(seed),
-            None => Pcg64Mcg::from_entropy(),
+            Some(seed) => NyxRng::from_seed_u128(seed),
+            None => NyxRng::from_entropy(),
         };
 
         // Generate the states, forcing the borrow as specified in the `sample_iter` docs.
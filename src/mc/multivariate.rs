@@ -313,8 +313,8 @@ mod multivariate_ut {
         use anise::constants::frames::EARTH_J2000;
         use anise::prelude::Orbit;
 
+        use crate::mc::NyxRng;
         use crate::time::Epoch;
-        use rand_pcg::Pcg64Mcg;
 
         // Ensure that this worked: a 3 sigma deviation around 1 km means we shouldn't have 99.7% of samples within those bounds.
         // Create a reproducible fast seed
@@ -340,7 +340,7 @@ mod multivariate_ut {
         )
         .unwrap();
 
-        let rng = Pcg64Mcg::new(seed);
+        let rng = NyxRng::from_seed(seed);
         let init_rmag = state.orbit.rmag_km();
         let cnt_too_far: u16 = generator
             .sample_iter(rng)
@@ -371,7 +371,7 @@ mod multivariate_ut {
         use crate::Spacecraft;
         use crate::GMAT_EARTH_GM;
 
-        use rand_pcg::Pcg64Mcg;
+        use crate::mc::NyxRng;
 
         let eme2k = EARTH_J2000.with_mu_km3_s2(GMAT_EARTH_GM);
 
@@ -417,7 +417,7 @@ mod multivariate_ut {
         // Ensure that this worked: a 3 sigma deviation around 1 km means we shouldn't have 99.7% of samples within those bounds.
         // Create a reproducible fast seed
         let seed = 0;
-        let rng = Pcg64Mcg::new(seed);
+        let rng = NyxRng::from_seed(seed);
 
         let cnt_too_far: u16 = generator
             .sample_iter(rng)
@@ -448,8 +448,8 @@ mod multivariate_ut {
         use anise::constants::frames::EARTH_J2000;
         use anise::prelude::Orbit;
 
+        use crate::mc::NyxRng;
         use crate::time::Epoch;
-        use rand_pcg::Pcg64Mcg;
 
         let eme2k = EARTH_J2000.with_mu_km3_s2(GMAT_EARTH_GM);
 
@@ -474,7 +474,7 @@ mod multivariate_ut {
         // Ensure that this worked: a 3 sigma deviation around 1 km means we shouldn't have 99.7% of samples within those bounds.
         // Create a reproducible fast seed
         let seed = 0;
-        let rng = Pcg64Mcg::new(seed);
+        let rng = NyxRng::from_seed(seed);
 
         let cnt_too_far: u16 = generator
             .sample_iter(rng)
@@ -514,8 +514,8 @@ mod multivariate_ut {
         use anise::constants::frames::EARTH_J2000;
         use anise::prelude::Orbit;
 
+        use crate::mc::NyxRng;
         use crate::time::Epoch;
-        use rand_pcg::Pcg64Mcg;
 
         let eme2k = EARTH_J2000.with_mu_km3_s2(GMAT_EARTH_GM);
 
@@ -544,7 +544,7 @@ mod multivariate_ut {
         // Ensure that this worked: a 3 sigma deviation around 1 km means we shouldn't have 99.7% of samples within those bounds.
         // Create a reproducible fast seed
         let seed = 0;
-        let rng = Pcg64Mcg::new(seed);
+        let rng = NyxRng::from_seed(seed);
 
         let cnt_too_far: u16 = generator
             .sample_iter(rng)
@@ -37,7 +37,6 @@ use anise::constants::frames::EARTH_J2000;
 use arrow::array::{Array, Float64Builder, Int32Builder, StringBuilder};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
-use hifitime::TimeScale;
 use parquet::arrow::ArrowWriter;
 pub use rstats::Stats;
 use snafu::ensure;
@@ -253,14 +252,12 @@ where
         let path_buf = cfg.actual_path(path);
 
         // Build the schema
-        let mut hdrs = vec![
-            Field::new("Epoch (UTC)", DataType::Utf8, false),
-            Field::new("Monte Carlo Run Index", DataType::Int32, false),
-        ];
+        let mut hdrs = cfg.epoch_fields();
+        hdrs.push(Field::new("Monte Carlo Run Index", DataType::Int32, false));
 
         // Use the first successful run to build up some data shared for all
         let mut frame = EARTH_J2000;
-        let mut fields = match cfg.fields {
+        let mut fields = match cfg.fields.clone() {
             Some(fields) => fields,
             None => S::export_params(),
         };
@@ -315,15 +312,18 @@ where
             }
         );
 
-        let more_meta = Some(vec![(
-            "Frame".to_string(),
-            serde_dhall::serialize(&frame).to_string().map_err(|e| {
-                Box::new(InputOutputError::SerializeDhall {
-                    what: format!("frame `{frame}`"),
-                    err: e.to_string(),
-                })
-            })?,
-        )]);
+        let more_meta = Some(vec![
+            (
+                "Frame".to_string(),
+                serde_dhall::serialize(&frame).to_string().map_err(|e| {
+                    Box::new(InputOutputError::SerializeDhall {
+                        what: format!("frame `{frame}`"),
+                        err: e.to_string(),
+                    })
+                })?,
+            ),
+            ("frame".to_string(), frame.to_string()),
+        ]);
 
         for field in &fields {
             hdrs.push(field.to_field(more_meta.clone()));
@@ -343,15 +343,13 @@ where
         // Build all of the records
 
         // Epochs
-        let mut utc_epoch = StringBuilder::new();
-        let mut idx_col = Int32Builder::new();
-        for (sno, s) in all_states.iter().enumerate() {
-            utc_epoch.append_value(s.epoch().to_time_scale(TimeScale::UTC).to_isoformat());
+        record.extend(cfg.epoch_columns(all_states.iter().map(|s| s.epoch())));
 
-            // Copy this a bunch of times because all columns must have the same length
-            idx_col.append_value(run_indexes[sno]);
+        // Copy the run index a bunch of times because all columns must have the same length
+        let mut idx_col = Int32Builder::new();
+        for idx in &run_indexes {
+            idx_col.append_value(*idx);
         }
-        record.push(Arc::new(utc_epoch.finish()));
         record.push(Arc::new(idx_col.finish()));
 
         // Add all of the fields
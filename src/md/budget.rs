@@ -0,0 +1,479 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use arrow::array::{Array, Float64Builder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use csv::WriterBuilder;
+use parquet::arrow::ArrowWriter;
+
+use super::opti::solution::TargeterSolution;
+use super::trajectory::{ExportCfg, Traj};
+use super::{StateParameter, Vary};
+use crate::cosmic::{Spacecraft, STD_GRAVITY};
+use crate::dynamics::guidance::ManeuverPlan;
+use crate::errors::NyxError;
+use crate::io::watermark::pq_writer;
+use crate::time::{Epoch, TimeScale};
+use crate::State;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A maneuver accounted for in a delta-v budget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetManeuver {
+    /// Epoch of the maneuver, i.e. the start of the burn for finite burns
+    pub epoch: Epoch,
+    /// Category of this maneuver, e.g. "Orbit raising" or "Station keeping", on which the margin is configured
+    pub category: String,
+    /// Free form purpose of this specific maneuver
+    pub purpose: String,
+    /// Delta-v magnitude without margin, in km/s
+    pub dv_km_s: f64,
+}
+
+/// Aggregates the maneuvers of a mission timeline, from targeter solutions, maneuver plans, or any other source,
+/// into a delta-v and fuel budget.
+///
+/// Each category of maneuvers may have its own margin, as a percentage of the delta-v of the maneuvers of that category.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DvBudget {
+    /// Maneuvers of this budget, in the order in which they were added
+    pub maneuvers: Vec<BudgetManeuver>,
+    /// Margin of each category, in percent of the delta-v. Categories without a margin have a zero margin.
+    pub margins_prct: BTreeMap<String, f64>,
+}
+
+impl DvBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the margin of the provided category, in percent of the delta-v (e.g. 5.0 for 5%).
+    pub fn with_margin(mut self, category: &str, margin_prct: f64) -> Self {
+        self.margins_prct.insert(category.to_string(), margin_prct);
+        self
+    }
+
+    /// Margin of the provided category, in percent
+    pub fn margin_prct(&self, category: &str) -> f64 {
+        self.margins_prct.get(category).copied().unwrap_or(0.0)
+    }
+
+    /// Adds a maneuver of the provided delta-v magnitude, in km/s.
+    pub fn add(&mut self, epoch: Epoch, category: &str, purpose: &str, dv_km_s: f64) {
+        self.maneuvers.push(BudgetManeuver {
+            epoch,
+            category: category.to_string(),
+            purpose: purpose.to_string(),
+            dv_km_s: dv_km_s.abs(),
+        });
+    }
+
+    /// Adds the maneuver of a targeter solution.
+    ///
+    /// The delta-v of an impulsive solution is the norm of its velocity corrections. The delta-v of a finite burn solution
+    /// is computed from the fuel mass used between the corrected and achieved states with the rocket equation, so the
+    /// targeter must have been run with fuel depletion enabled.
+    pub fn add_targeter_solution<const V: usize, const O: usize>(
+        &mut self,
+        solution: &TargeterSolution<V, O>,
+        category: &str,
+        purpose: &str,
+    ) -> Result<(), NyxError> {
+        let dv_km_s = if solution.is_finite_burn() {
+            rocket_dv_km_s(&solution.corrected_state, &solution.achieved_state)?
+        } else {
+            solution
+                .variables
+                .iter()
+                .zip(solution.correction.iter())
+                .filter(|(var, _)| {
+                    matches!(
+                        var.component,
+                        Vary::VelocityX | Vary::VelocityY | Vary::VelocityZ
+                    )
+                })
+                .map(|(_, corr)| corr.powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+
+        self.add(solution.corrected_state.epoch(), category, purpose, dv_km_s);
+        Ok(())
+    }
+
+    /// Adds each window of a maneuver plan as its own maneuver, whose purpose is the guidance law of that window.
+    ///
+    /// The delta-v of each window is computed from the fuel mass used over that window in the provided trajectory,
+    /// which must be the propagation of that plan with fuel depletion enabled.
+    pub fn add_maneuver_plan(
+        &mut self,
+        plan: &ManeuverPlan,
+        traj: &Traj<Spacecraft>,
+        category: &str,
+    ) -> Result<(), NyxError> {
+        for window in &plan.windows {
+            let start = traj.at(window.start)?;
+            let end = traj.at(window.end)?;
+            let dv_km_s = rocket_dv_km_s(&start, &end)?;
+            self.add(window.start, category, &window.law.to_string(), dv_km_s);
+        }
+        Ok(())
+    }
+
+    /// Builds the budget report over the provided mass timeline, typically the trajectory of the mission.
+    ///
+    /// The maneuvers are sorted chronologically, and the fuel of each maneuver is computed with the rocket equation from
+    /// its delta-v including the margin of its category, the Isp of the thruster at that epoch, and the mass of the
+    /// spacecraft just before the maneuver. That mass is the smallest of the mass in the timeline and of the loaded mass
+    /// minus the fuel of the previous maneuvers of the budget, such that a coasting-only timeline may be used.
+    ///
+    /// The loaded fuel is the fuel mass of the first state of the timeline. If the cumulative fuel exceeds the loaded
+    /// fuel, a warning is logged with the epoch of the first maneuver which cannot be executed.
+    pub fn report(&self, mass_timeline: &Traj<Spacecraft>) -> Result<DvBudgetReport, NyxError> {
+        let initial = mass_timeline.first();
+        let loaded_fuel_kg = initial.fuel_mass_kg;
+        let loaded_mass_kg = initial.mass_kg();
+
+        let mut maneuvers = self.maneuvers.clone();
+        maneuvers.sort_by_key(|mnvr| mnvr.epoch);
+
+        let mut rows = Vec::with_capacity(maneuvers.len());
+        let mut cumulative_fuel_kg = 0.0;
+        let mut exceeded_at = None;
+
+        for mnvr in maneuvers {
+            let state = mass_timeline.at(mnvr.epoch)?;
+            let exhaust_velocity_km_s = exhaust_velocity_km_s(&state)?;
+
+            let margin_prct = self.margin_prct(&mnvr.category);
+            let dv_with_margin_km_s = mnvr.dv_km_s * (1.0 + margin_prct / 100.0);
+
+            let mass_kg = state.mass_kg().min(loaded_mass_kg - cumulative_fuel_kg);
+            let fuel_kg = mass_kg * (1.0 - (-dv_with_margin_km_s / exhaust_velocity_km_s).exp());
+            cumulative_fuel_kg += fuel_kg;
+
+            if cumulative_fuel_kg > loaded_fuel_kg && exceeded_at.is_none() {
+                warn!(
+                    "Delta-v budget exceeds the loaded fuel of {loaded_fuel_kg:.3} kg at {}: {cumulative_fuel_kg:.3} kg needed for `{}`",
+                    mnvr.epoch, mnvr.purpose
+                );
+                exceeded_at = Some(mnvr.epoch);
+            }
+
+            rows.push(DvBudgetRow {
+                maneuver: mnvr,
+                margin_prct,
+                dv_with_margin_km_s,
+                mass_kg,
+                fuel_kg,
+                cumulative_fuel_kg,
+            });
+        }
+
+        Ok(DvBudgetReport {
+            rows,
+            loaded_fuel_kg,
+            exceeded_at,
+        })
+    }
+}
+
+/// Exhaust velocity of the thruster of this spacecraft, in km/s
+fn exhaust_velocity_km_s(state: &Spacecraft) -> Result<f64, NyxError> {
+    match state.thruster {
+        Some(thruster) => Ok(thruster.isp_s * STD_GRAVITY * 1e-3),
+        None => Err(NyxError::StateParameterUnavailable {
+            param: StateParameter::Isp,
+            msg: format!("no thruster defined at {}", state.epoch()),
+        }),
+    }
+}
+
+/// Delta-v achieved between both states from the fuel mass used, in km/s
+fn rocket_dv_km_s(start: &Spacecraft, end: &Spacecraft) -> Result<f64, NyxError> {
+    Ok(exhaust_velocity_km_s(start)? * (start.mass_kg() / end.mass_kg()).ln())
+}
+
+/// A maneuver of the budget report with its margin and fuel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DvBudgetRow {
+    pub maneuver: BudgetManeuver,
+    /// Margin applied to this maneuver, in percent
+    pub margin_prct: f64,
+    /// Delta-v including the margin, in km/s
+    pub dv_with_margin_km_s: f64,
+    /// Mass of the spacecraft just before this maneuver, in kg
+    pub mass_kg: f64,
+    /// Fuel mass used by this maneuver including its margin, in kg
+    pub fuel_kg: f64,
+    /// Fuel mass used since the start of the timeline, including this maneuver, in kg
+    pub cumulative_fuel_kg: f64,
+}
+
+/// Subtotal of a category of maneuvers of the budget report.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DvBudgetSubtotal {
+    /// Number of maneuvers in this category
+    pub count: usize,
+    /// Delta-v without margin, in km/s
+    pub dv_km_s: f64,
+    /// Delta-v including the margin, in km/s
+    pub dv_with_margin_km_s: f64,
+    /// Fuel mass including the margin, in kg
+    pub fuel_kg: f64,
+}
+
+/// Delta-v and fuel budget of a mission timeline, in chronological order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DvBudgetReport {
+    pub rows: Vec<DvBudgetRow>,
+    /// Fuel mass loaded at the start of the timeline, in kg
+    pub loaded_fuel_kg: f64,
+    /// Epoch of the first maneuver for which the cumulative fuel exceeds the loaded fuel, if any
+    pub exceeded_at: Option<Epoch>,
+}
+
+impl DvBudgetReport {
+    /// Subtotals of each category, sorted by category name
+    pub fn subtotals(&self) -> BTreeMap<String, DvBudgetSubtotal> {
+        let mut subtotals = BTreeMap::<String, DvBudgetSubtotal>::new();
+        for row in &self.rows {
+            let subtotal = subtotals.entry(row.maneuver.category.clone()).or_default();
+            subtotal.count += 1;
+            subtotal.dv_km_s += row.maneuver.dv_km_s;
+            subtotal.dv_with_margin_km_s += row.dv_with_margin_km_s;
+            subtotal.fuel_kg += row.fuel_kg;
+        }
+        subtotals
+    }
+
+    /// Total of all of the maneuvers
+    pub fn total(&self) -> DvBudgetSubtotal {
+        let mut total = DvBudgetSubtotal::default();
+        for subtotal in self.subtotals().values() {
+            total.count += subtotal.count;
+            total.dv_km_s += subtotal.dv_km_s;
+            total.dv_with_margin_km_s += subtotal.dv_with_margin_km_s;
+            total.fuel_kg += subtotal.fuel_kg;
+        }
+        total
+    }
+
+    /// Fuel mass remaining at the end of the timeline, in kg, negative if the budget exceeds the loaded fuel
+    pub fn remaining_fuel_kg(&self) -> f64 {
+        self.loaded_fuel_kg - self.total().fuel_kg
+    }
+
+    /// Exports this report to a CSV file with one row per maneuver, followed by one subtotal row per category and the total.
+    ///
+    /// Subtotal rows have an empty epoch and their purpose is "Subtotal", or "Total" for the last row.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = path.as_ref().to_path_buf();
+        let mut wtr = WriterBuilder::new().from_path(&path_buf)?;
+
+        wtr.write_record([
+            "Epoch (UTC)",
+            "Category",
+            "Purpose",
+            "Delta-v (km/s)",
+            "Margin (%)",
+            "Delta-v with margin (km/s)",
+            "Mass (kg)",
+            "Fuel (kg)",
+            "Cumulative fuel (kg)",
+        ])?;
+
+        for row in &self.rows {
+            wtr.write_record([
+                row.maneuver
+                    .epoch
+                    .to_time_scale(TimeScale::UTC)
+                    .to_isoformat(),
+                row.maneuver.category.clone(),
+                row.maneuver.purpose.clone(),
+                format!("{}", row.maneuver.dv_km_s),
+                format!("{}", row.margin_prct),
+                format!("{}", row.dv_with_margin_km_s),
+                format!("{}", row.mass_kg),
+                format!("{}", row.fuel_kg),
+                format!("{}", row.cumulative_fuel_kg),
+            ])?;
+        }
+
+        let subtotals = self.subtotals();
+        let total = self.total();
+        for (category, subtotal, purpose) in subtotals
+            .iter()
+            .map(|(category, subtotal)| (category.as_str(), subtotal, "Subtotal"))
+            .chain([("", &total, "Total")])
+        {
+            wtr.write_record([
+                String::new(),
+                category.to_string(),
+                purpose.to_string(),
+                format!("{}", subtotal.dv_km_s),
+                String::new(),
+                format!("{}", subtotal.dv_with_margin_km_s),
+                String::new(),
+                format!("{}", subtotal.fuel_kg),
+                String::new(),
+            ])?;
+        }
+
+        wtr.flush()?;
+
+        info!("Delta-v budget written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+
+    /// Exports this report to a parquet file with one row per maneuver, the category subtotals are stored in the metadata.
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = cfg.epoch_fields();
+        hdrs.extend([
+            Field::new("Category", DataType::Utf8, false),
+            Field::new("Purpose", DataType::Utf8, false),
+        ]);
+        for (name, unit) in [
+            ("Delta-v (km/s)", "km/s"),
+            ("Margin (%)", "%"),
+            ("Delta-v with margin (km/s)", "km/s"),
+            ("Mass (kg)", "kg"),
+            ("Fuel (kg)", "kg"),
+            ("Cumulative fuel (kg)", "kg"),
+        ] {
+            let mut meta = HashMap::new();
+            meta.insert("unit".to_string(), unit.to_string());
+            hdrs.push(Field::new(name, DataType::Float64, false).with_metadata(meta));
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        record.extend(cfg.epoch_columns(self.rows.iter().map(|row| row.maneuver.epoch)));
+
+        let mut categories = StringBuilder::new();
+        let mut purposes = StringBuilder::new();
+        for row in &self.rows {
+            categories.append_value(&row.maneuver.category);
+            purposes.append_value(&row.maneuver.purpose);
+        }
+        record.push(Arc::new(categories.finish()));
+        record.push(Arc::new(purposes.finish()));
+
+        let getters: [fn(&DvBudgetRow) -> f64; 6] = [
+            |row| row.maneuver.dv_km_s,
+            |row| row.margin_prct,
+            |row| row.dv_with_margin_km_s,
+            |row| row.mass_kg,
+            |row| row.fuel_kg,
+            |row| row.cumulative_fuel_kg,
+        ];
+        for getter in getters {
+            let mut data = Float64Builder::new();
+            for row in &self.rows {
+                data.append_value(getter(row));
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "Delta-v budget".to_string());
+        metadata.insert(
+            "Loaded fuel (kg)".to_string(),
+            format!("{}", self.loaded_fuel_kg),
+        );
+        for (category, subtotal) in self.subtotals() {
+            metadata.insert(format!("Subtotal {category}"), format!("{subtotal}"));
+        }
+        metadata.insert("Total".to_string(), format!("{}", self.total()));
+        if let Some(epoch) = self.exceeded_at {
+            metadata.insert("Loaded fuel exceeded at".to_string(), epoch.to_string());
+        }
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata));
+
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!("Delta-v budget written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for DvBudgetSubtotal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} maneuver(s): {:.6} km/s ({:.6} km/s with margin), {:.3} kg",
+            self.count, self.dv_km_s, self.dv_with_margin_km_s, self.fuel_kg
+        )
+    }
+}
+
+impl fmt::Display for DvBudgetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Delta-v budget of {} maneuvers with {:.3} kg of loaded fuel",
+            self.rows.len(),
+            self.loaded_fuel_kg
+        )?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "\t{}\t[{}] {}\t{:.6} km/s (+{}%)\t{:.3} kg (cumulative {:.3} kg)",
+                row.maneuver.epoch,
+                row.maneuver.category,
+                row.maneuver.purpose,
+                row.maneuver.dv_km_s,
+                row.margin_prct,
+                row.fuel_kg,
+                row.cumulative_fuel_kg
+            )?;
+        }
+        for (category, subtotal) in self.subtotals() {
+            writeln!(f, "\t{category}: {subtotal}")?;
+        }
+        writeln!(f, "\tTotal: {}", self.total())?;
+        match self.exceeded_at {
+            Some(epoch) => write!(f, "\tLoaded fuel exceeded at {epoch}"),
+            None => write!(f, "\tRemaining fuel: {:.3} kg", self.remaining_fuel_kg()),
+        }
+    }
+}
@@ -108,6 +108,11 @@ where
                     EventEdge::Falling
                 } else if prev_value < value && value < next_value {
                     EventEdge::Rising
+                } else if event.is_discrete() && prev_value > next_value {
+                    // The value of a discrete event is flat on either side of the transition
+                    EventEdge::Falling
+                } else if event.is_discrete() && prev_value < next_value {
+                    EventEdge::Rising
                 } else {
                     warn!("could not determine edge of {} at {}", event, state.epoch(),);
                     EventEdge::Unclear
@@ -16,7 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use anise::prelude::Almanac;
+use anise::prelude::{Almanac, Frame};
 use hifitime::Duration;
 use snafu::ResultExt;
 use std::sync::Arc;
@@ -77,6 +77,14 @@ impl EventEvaluator<Spacecraft> for Event {
         self.value_precision
     }
 
+    fn unit(&self) -> &'static str {
+        self.parameter.unit()
+    }
+
+    fn obs_frame(&self) -> Option<Frame> {
+        self.obs_frame
+    }
+
     fn eval_string(
         &self,
         state: &Spacecraft,
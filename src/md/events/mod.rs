@@ -23,8 +23,9 @@ use super::StateParameter;
 use crate::errors::EventError;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
-use crate::time::{Duration, Unit};
+use crate::time::{Duration, Epoch, Unit};
 use crate::State;
+use anise::astro::PhysicsResult;
 use anise::prelude::{Almanac, Frame};
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
@@ -56,6 +57,46 @@ where
     fn eval_string(&self, state: &S, almanac: Arc<Almanac>) -> Result<String, EventError>;
     fn epoch_precision(&self) -> Duration;
     fn value_precision(&self) -> f64;
+
+    /// Analytically predicts the epochs of the crossings of this event after the provided state, if this event supports it for that state.
+    ///
+    /// When available, `Traj::find` only refines each predicted crossing with a Brent search bracketed by the margin of the prediction,
+    /// instead of searching the whole trajectory. By default, no prediction is available and the generic search is used.
+    fn predict(&self, _state: &S, _almanac: Arc<Almanac>) -> Option<EventPrediction> {
+        None
+    }
+
+    /// Returns whether this event is discrete, i.e. its evaluation is positive when a condition holds and negative otherwise (e.g. a binary indicator),
+    /// instead of a smooth function crossing zero.
+    ///
+    /// Discrete events are only ever searched by bisection to the epoch precision, since the Brent solver assumes a smooth event, cf. `Traj::find_transitions`.
+    /// By default, events are smooth.
+    fn is_discrete(&self) -> bool {
+        false
+    }
+
+    /// Returns the unit of the evaluation of this event, used to annotate the exported event columns.
+    /// By default, the evaluation is unitless.
+    fn unit(&self) -> &'static str {
+        "unitless"
+    }
+
+    /// Returns the frame in which this event is evaluated, if it differs from the frame of the evaluated states.
+    /// By default, events are evaluated in the frame of the states.
+    fn obs_frame(&self) -> Option<Frame> {
+        None
+    }
+}
+
+/// Analytical prediction of the crossings of an event, used to seed the event search, cf. `EventEvaluator::predict`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventPrediction {
+    /// Predicted epochs of the crossings, in chronological order
+    pub epochs: Vec<Epoch>,
+    /// Half width of the bracket around each predicted epoch, which must contain the actual crossing and no other
+    pub margin: Duration,
+    /// Epoch until which this prediction holds, i.e. all of the crossings until then are predicted
+    pub valid_until: Epoch,
 }
 
 /// Defines a state parameter event finder
@@ -154,6 +195,16 @@ impl Event {
         Self::new(StateParameter::Apoapsis, 180.0)
     }
 
+    /// Match the impact on the central body of the provided frame, i.e. when the radius reaches the mean equatorial radius of the frame plus the provided altitude.
+    ///
+    /// This is the event counterpart of the `min_altitude_km` of the integrator options.
+    pub fn impact(frame: Frame, min_altitude_km: f64) -> PhysicsResult<Self> {
+        Ok(Self::new(
+            StateParameter::Rmag,
+            frame.mean_equatorial_radius_km()? + min_altitude_km,
+        ))
+    }
+
     /// Match a specific event in another frame, using the default epoch precision and value.
     pub fn in_frame(parameter: StateParameter, desired_value: f64, target_frame: Frame) -> Self {
         warn!("Searching for an event in another frame is slow: you should instead convert the trajectory into that other frame");
@@ -27,14 +27,176 @@ use anise::almanac::Almanac;
 use rayon::prelude::*;
 use snafu::ResultExt;
 use std::iter::Iterator;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
 use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+/// Configuration of a bounded event search, cf. `Traj::find_bounded`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(doc)]
+pub struct EventSearchCfg {
+    /// Maximum number of interpolations of the trajectory over the whole search, defaults to unlimited.
+    #[builder(default, setter(strip_option))]
+    pub max_evaluations: Option<usize>,
+    /// Set this flag to true (from any thread) to stop the search as soon as possible; the events found so far are returned.
+    #[builder(default, setter(strip_option))]
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Skip the search in chunks where the event evaluated at the trajectory samples does not change sign.
+    /// This only evaluates the event at the samples of the trajectory (no interpolation), and assumes that the event does not cross zero twice between two samples.
+    #[builder(default)]
+    pub prefilter: bool,
+    /// Duration of each chunk of the trajectory searched in parallel, defaults to 1% of the duration of the trajectory.
+    #[builder(default, setter(strip_option))]
+    pub chunk: Option<Duration>,
+    /// Overlap of consecutive chunks as a fraction of the chunk duration, such that an event at the boundary of two chunks is within both of them.
+    #[builder(default = 0.1)]
+    pub chunk_overlap: f64,
+}
+
+impl Default for EventSearchCfg {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl EventSearchCfg {
+    /// Returns the duration of the chunks searched between the start and end epochs.
+    fn chunk_duration(&self, start: Epoch, end: Epoch) -> Duration {
+        self.chunk.unwrap_or((end - start) / 100)
+    }
+
+    /// Returns the duration of the overlap of consecutive chunks searched between the start and end epochs.
+    fn overlap_duration(&self, start: Epoch, end: Epoch) -> Duration {
+        self.chunk_duration(start, end) * self.chunk_overlap.max(0.0)
+    }
+
+    /// Returns the overlapping chunks searched between the start and end epochs, clamped to the end epoch.
+    fn chunks(&self, start: Epoch, end: Epoch) -> Vec<(Epoch, Epoch)> {
+        let step = self.chunk_duration(start, end);
+        let overlap = self.overlap_duration(start, end);
+        TimeSeries::exclusive(start, end, step)
+            .map(|epoch| (epoch, (epoch + step + overlap).min(end)))
+            .collect()
+    }
+}
+
+/// Sorts the events chronologically and removes those found more than once in the overlap of two adjacent chunks.
+///
+/// An event is a duplicate of the previous one if both have the same edge, both lie in the overlap of two adjacent chunks,
+/// and they are within twice the epoch precision of the event, since the crossing is found to within that precision from either chunk.
+/// Distinct crossings closer than the chunk overlap are therefore kept.
+fn sort_dedup_overlaps<S: Interpolatable>(
+    events: &mut Vec<EventDetails<S>>,
+    chunks: &[(Epoch, Epoch)],
+) where
+    DefaultAllocator: Allocator<S::VecLength> + Allocator<S::Size> + Allocator<S::Size, S::Size>,
+{
+    // Overlaps of adjacent chunks, in chronological order
+    let overlaps = chunks
+        .windows(2)
+        .map(|pair| (pair[1].0, pair[0].1))
+        .filter(|(start, end)| start < end)
+        .collect::<Vec<(Epoch, Epoch)>>();
+    let in_overlap = |epoch: Epoch| -> bool {
+        let idx = overlaps.partition_point(|(_, end)| *end < epoch);
+        overlaps
+            .get(idx)
+            .map_or(false, |(start, _)| *start <= epoch)
+    };
+
+    events.sort_by_key(|details| details.state.epoch());
+    events.dedup_by(|next, prev| {
+        next == prev
+            || (next.edge == prev.edge
+                && (next.state.epoch() - prev.state.epoch()).abs() <= prev.pm_duration * 2
+                && in_overlap(prev.state.epoch())
+                && in_overlap(next.state.epoch()))
+    });
+}
+
+/// Results of a bounded event search, which may be partial if the search was stopped early.
+#[derive(Clone, Debug)]
+pub struct EventSearchResults<S: Interpolatable>
+where
+    DefaultAllocator: Allocator<S::VecLength> + Allocator<S::Size> + Allocator<S::Size, S::Size>,
+{
+    /// Events found, in chronological order
+    pub events: Vec<EventDetails<S>>,
+    /// Set to true if the search stopped because the work budget was exhausted
+    pub budget_exhausted: bool,
+    /// Set to true if the search was cancelled
+    pub cancelled: bool,
+    /// Number of interpolations of the trajectory performed
+    pub evaluations: usize,
+    /// Number of chunks of the trajectory skipped by the prefilter
+    pub skipped_chunks: usize,
+}
+
+impl<S: Interpolatable> EventSearchResults<S>
+where
+    DefaultAllocator: Allocator<S::VecLength> + Allocator<S::Size> + Allocator<S::Size, S::Size>,
+{
+    /// Returns whether the search covered the whole trajectory
+    pub fn is_complete(&self) -> bool {
+        !self.budget_exhausted && !self.cancelled
+    }
+}
+
+/// Shared work budget and cancellation flag of a bounded search.
+struct SearchBudget<'a> {
+    max_evaluations: Option<usize>,
+    evaluations: AtomicUsize,
+    exhausted: AtomicBool,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> SearchBudget<'a> {
+    fn new(cfg: &'a EventSearchCfg) -> Self {
+        Self {
+            max_evaluations: cfg.max_evaluations,
+            evaluations: AtomicUsize::new(0),
+            exhausted: AtomicBool::new(false),
+            cancel: cfg.cancel.as_deref(),
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    fn exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the search may continue, and if so, deducts one evaluation from the budget.
+    fn spend(&self) -> bool {
+        if self.cancelled() || self.exhausted() {
+            return false;
+        }
+        let used = self.evaluations.fetch_add(1, Ordering::Relaxed);
+        if self.max_evaluations.is_some_and(|max| used >= max) {
+            self.exhausted.store(true, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    fn evaluations(&self) -> usize {
+        let used = self.evaluations.load(Ordering::Relaxed);
+        match self.max_evaluations {
+            Some(max) => used.min(max),
+            None => used,
+        }
+    }
+}
 
 impl<S: Interpolatable> Traj<S>
 where
     DefaultAllocator: Allocator<S::VecLength> + Allocator<S::Size> + Allocator<S::Size, S::Size>,
 {
     /// Find the exact state where the request event happens. The event function is expected to be monotone in the provided interval because we find the event using a Brent solver.
+    /// If the Brent solver does not converge, a bisection of the same bracket is attempted before reporting the event as not found.
     #[allow(clippy::identity_op)]
     pub fn find_bracketed<E>(
         &self,
@@ -43,11 +205,42 @@ where
         event: &E,
         almanac: Arc<Almanac>,
     ) -> Result<EventDetails<S>, EventError>
+    where
+        E: EventEvaluator<S>,
+    {
+        self.find_bracketed_within(start, end, event, almanac, None)
+    }
+
+    /// Brent search of the event between the start and end epochs, where each interpolation of the trajectory is deducted from the budget, if any.
+    /// If the budget is exhausted or the search is cancelled, the event is reported as not found.
+    /// If the Brent solver reaches its maximum number of iterations, the bracket is bisected instead.
+    #[allow(clippy::identity_op)]
+    fn find_bracketed_within<E>(
+        &self,
+        start: Epoch,
+        end: Epoch,
+        event: &E,
+        almanac: Arc<Almanac>,
+        budget: Option<&SearchBudget>,
+    ) -> Result<EventDetails<S>, EventError>
     where
         E: EventEvaluator<S>,
     {
         let max_iter = 50;
 
+        let interpolate = |epoch: Epoch| -> Result<S, EventError> {
+            if let Some(budget) = budget {
+                if !budget.spend() {
+                    return Err(EventError::NotFound {
+                        start,
+                        end,
+                        event: format!("{event}"),
+                    });
+                }
+            }
+            self.at(epoch).context(EventTrajSnafu {})
+        };
+
         // Helper lambdas, for f64s only
         let has_converged =
             |xa: f64, xb: f64| (xa - xb).abs() <= event.epoch_precision().to_seconds();
@@ -66,24 +259,28 @@ where
         let mut xa = 0.0;
         let mut xb = (xb_e - xa_e).to_seconds();
         // Evaluate the event at both bounds
-        let ya_state = self.at(xa_e).context(EventTrajSnafu {})?;
-        let yb_state = self.at(xb_e).context(EventTrajSnafu {})?;
+        let ya_state = interpolate(xa_e)?;
+        let yb_state = interpolate(xb_e)?;
         let mut ya = event.eval(&ya_state, almanac.clone())?;
         let mut yb = event.eval(&yb_state, almanac.clone())?;
+        // Kept for the bisection fallback
+        let (y_start, y_end) = (ya, yb);
 
-        // Check if we're already at the root
-        if ya.abs() <= event.value_precision().abs() {
-            debug!(
-                "{event} -- found with |{ya}| < {} @ {xa_e}",
-                event.value_precision().abs()
-            );
-            return EventDetails::new(ya_state, ya, event, self, almanac.clone());
-        } else if yb.abs() <= event.value_precision().abs() {
-            debug!(
-                "{event} -- found with |{yb}| < {} @ {xb_e}",
-                event.value_precision().abs()
-            );
-            return EventDetails::new(yb_state, yb, event, self, almanac.clone());
+        // Check if we're already at the root, unless the event is discrete: its value is flat and only its sign matters
+        if !event.is_discrete() {
+            if ya.abs() <= event.value_precision().abs() {
+                debug!(
+                    "{event} -- found with |{ya}| < {} @ {xa_e}",
+                    event.value_precision().abs()
+                );
+                return EventDetails::new(ya_state, ya, event, self, almanac.clone());
+            } else if yb.abs() <= event.value_precision().abs() {
+                debug!(
+                    "{event} -- found with |{yb}| < {} @ {xb_e}",
+                    event.value_precision().abs()
+                );
+                return EventDetails::new(yb_state, yb, event, self, almanac.clone());
+            }
         }
 
         // The Brent solver, from the roots crate (sadly could not directly integrate it here)
@@ -92,10 +289,11 @@ where
         let (mut xc, mut yc, mut xd) = (xa, ya, xa);
         let mut flag = true;
 
-        for _ in 0..max_iter {
+        // Discrete events skip the Brent solver and are directly bisected
+        let brent_iter = if event.is_discrete() { 0 } else { max_iter };
+        for _ in 0..brent_iter {
             if ya.abs() < event.value_precision().abs() {
-                // Can't fail, we got it earlier
-                let state = self.at(xa_e + xa * Unit::Second).unwrap();
+                let state = interpolate(xa_e + xa * Unit::Second)?;
                 debug!(
                     "{event} -- found with |{ya}| < {} @ {}",
                     event.value_precision().abs(),
@@ -104,8 +302,7 @@ where
                 return EventDetails::new(state, ya, event, self, almanac.clone());
             }
             if yb.abs() < event.value_precision().abs() {
-                // Can't fail, we got it earlier
-                let state = self.at(xa_e + xb * Unit::Second).unwrap();
+                let state = interpolate(xa_e + xb * Unit::Second)?;
                 debug!(
                     "{event} -- found with |{yb}| < {} @ {}",
                     event.value_precision().abs(),
@@ -139,18 +336,14 @@ where
             } else {
                 flag = false;
             }
-            let next_try = self
-                .at(xa_e + s * Unit::Second)
-                .context(EventTrajSnafu {})?;
+            let next_try = interpolate(xa_e + s * Unit::Second)?;
             let ys = event.eval(&next_try, almanac.clone())?;
             xd = xc;
             xc = xb;
             yc = yb;
             if ya * ys < 0.0 {
                 // Root bracketed between a and s
-                let next_try = self
-                    .at(xa_e + xa * Unit::Second)
-                    .context(EventTrajSnafu {})?;
+                let next_try = interpolate(xa_e + xa * Unit::Second)?;
                 let ya_p = event.eval(&next_try, almanac.clone())?;
                 let (_a, _ya, _b, _yb) = arrange(xa, ya_p, s, ys);
                 {
@@ -161,9 +354,7 @@ where
                 }
             } else {
                 // Root bracketed between s and b
-                let next_try = self
-                    .at(xa_e + xb * Unit::Second)
-                    .context(EventTrajSnafu {})?;
+                let next_try = interpolate(xa_e + xb * Unit::Second)?;
                 let yb_p = event.eval(&next_try, almanac.clone())?;
                 let (_a, _ya, _b, _yb) = arrange(s, ys, xb, yb_p);
                 {
@@ -174,7 +365,45 @@ where
                 }
             }
         }
-        error!("Brent solver failed after {max_iter} iterations");
+        if !event.is_discrete() {
+            warn!("{event} -- Brent solver failed after {max_iter} iterations, falling back to bisection");
+        }
+
+        // Bisection of the original bracket, which converges for any monotone event but only if the event changes sign in the bracket.
+        if y_start * y_end > 0.0 {
+            error!("{event} -- bisection impossible: no sign change between {start} and {end}");
+            return Err(EventError::NotFound {
+                start,
+                end,
+                event: format!("{event}"),
+            });
+        }
+
+        let max_bisections = 128;
+        let (mut lo, mut y_lo, mut hi) = (0.0, y_start, (xb_e - xa_e).to_seconds());
+        for _ in 0..max_bisections {
+            let mid = (lo + hi) / 2.0;
+            let state = interpolate(xa_e + mid * Unit::Second)?;
+            let y_mid = event.eval(&state, almanac.clone())?;
+            if (!event.is_discrete() && y_mid.abs() <= event.value_precision().abs())
+                || has_converged(lo, hi)
+            {
+                // Either the event value is within precision, or the sign change is within the epoch precision
+                debug!(
+                    "{event} -- found by bisection with {y_mid} @ {}",
+                    state.epoch()
+                );
+                return EventDetails::new(state, y_mid, event, self, almanac.clone());
+            }
+            if y_lo * y_mid < 0.0 {
+                hi = mid;
+            } else {
+                lo = mid;
+                y_lo = y_mid;
+            }
+        }
+
+        error!("{event} -- bisection failed after {max_bisections} iterations");
         Err(EventError::NotFound {
             start,
             end,
@@ -191,16 +420,41 @@ where
     /// The initial search step is 1% of the duration of the trajectory duration.
     /// For example, if the trajectory is 100 days long, then we split the trajectory into 100 chunks of 1 day and see whether
     /// the event is in there. If the event happens twice or more times within 1% of the trajectory duration, only the _one_ of
-    /// such events will be found.
+    /// such events will be found. Consecutive chunks overlap by 10% of their duration so that an event at the boundary of two chunks
+    /// is within both of them, and an event found in both is only reported once if both crossings are within twice the epoch precision
+    /// of the event. The chunk duration and overlap can be configured with `find_with_cfg`.
     ///
     /// If this heuristic fails to find any such events, then `find_minmax` is called on the event with a time precision of `Unit::Second`.
     /// Then we search only within the min and max bounds of the provided event.
+    ///
+    /// # Discrete events
+    /// If the event is discrete (cf. `EventEvaluator::is_discrete`), its transitions are found with `find_transitions` instead.
+    ///
+    /// # Predicted events
+    /// If the event predicts its crossings throughout the trajectory (cf. `EventEvaluator::predict`), e.g. the eclipses of a near-circular orbit,
+    /// then each crossing is only searched for within the bracket of its prediction, which requires far fewer interpolations of the trajectory.
+    /// If any predicted crossing is not found, then this falls back to the heuristic above.
     #[allow(clippy::identity_op)]
     pub fn find<E>(
         &self,
         event: &E,
         almanac: Arc<Almanac>,
     ) -> Result<Vec<EventDetails<S>>, EventError>
+    where
+        E: EventEvaluator<S>,
+    {
+        self.find_with_cfg(event, &EventSearchCfg::default(), almanac)
+    }
+
+    /// Find all of the states where the event happens, splitting the trajectory in the chunks of the provided search configuration, cf. `find`.
+    ///
+    /// Only the chunk duration and overlap of the configuration are used: unlike `find_bounded`, the search is neither budgeted nor prefiltered.
+    pub fn find_with_cfg<E>(
+        &self,
+        event: &E,
+        cfg: &EventSearchCfg,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<EventDetails<S>>, EventError>
     where
         E: EventEvaluator<S>,
     {
@@ -213,21 +467,135 @@ where
                 event: format!("{event}"),
             });
         }
-        let heuristic = (end_epoch - start_epoch) / 100;
+        let mut states = if event.is_discrete() {
+            let states = self.find_transitions(event, almanac)?;
+            if states.is_empty() {
+                return Err(EventError::NotFound {
+                    start: start_epoch,
+                    end: end_epoch,
+                    event: format!("{event}"),
+                });
+            }
+            states
+        } else {
+            match self.find_predicted(event, almanac.clone()) {
+                Some(states) if states.is_empty() => {
+                    return Err(EventError::NotFound {
+                        start: start_epoch,
+                        end: end_epoch,
+                        event: format!("{event}"),
+                    });
+                }
+                Some(states) => states,
+                None => self.find_heuristic(event, cfg, almanac)?,
+            }
+        };
+
+        // Reorder and remove exact duplicates: the heuristic search already removed the events found in two overlapping chunks.
+        states.sort_by_key(|details| details.state.epoch());
+        states.dedup();
+
+        match states.len() {
+            0 => info!("Event {event} not found"),
+            1 => info!("Event {event} found once on {}", states[0].state.epoch()),
+            _ => {
+                info!(
+                    "Event {event} found {} times from {} until {}",
+                    states.len(),
+                    states.first().unwrap().state.epoch(),
+                    states.last().unwrap().state.epoch()
+                )
+            }
+        };
+
+        Ok(states)
+    }
+
+    /// Find all of the transitions of a discrete event, i.e. the epochs when the sign of its evaluation changes, in chronological order.
+    ///
+    /// The event is evaluated at each sample of the trajectory, and each change of sign between two consecutive samples is bisected
+    /// until the transition is bracketed within the epoch precision of the event. Only the sign of the evaluation is used, so this
+    /// is robust to events which are flat over intervals, but a condition which holds for less than the duration between two samples may be missed.
+    ///
+    /// The state of each transition is the first one on the new side of the transition (within the epoch precision).
+    pub fn find_transitions<E>(
+        &self,
+        event: &E,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<EventDetails<S>>, EventError>
+    where
+        E: EventEvaluator<S>,
+    {
+        let sample_evals = self
+            .states
+            .par_iter()
+            .map(|state| event.eval(state, almanac.clone()))
+            .collect::<Result<Vec<f64>, EventError>>()?;
+
+        let precision = event.epoch_precision();
+
+        let mut transitions = (0..self.states.len().saturating_sub(1))
+            .into_par_iter()
+            .filter(|&i| (sample_evals[i] > 0.0) != (sample_evals[i + 1] > 0.0))
+            .map(|i| {
+                let mut lo = self.states[i].epoch();
+                let mut hi = self.states[i + 1].epoch();
+                let lo_holds = sample_evals[i] > 0.0;
+                let mut y_hi = sample_evals[i + 1];
+                while hi - lo > precision {
+                    let mid = lo + (hi - lo) * 0.5;
+                    let y_mid =
+                        event.eval(&self.at(mid).context(EventTrajSnafu {})?, almanac.clone())?;
+                    if (y_mid > 0.0) == lo_holds {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                        y_hi = y_mid;
+                    }
+                }
+                let state = self.at(hi).context(EventTrajSnafu {})?;
+                EventDetails::new(state, y_hi, event, self, almanac.clone())
+            })
+            .collect::<Result<Vec<EventDetails<S>>, EventError>>()?;
+
+        transitions.sort_by_key(|details| details.state.epoch());
+
+        info!("Event {event} transitions {} times", transitions.len());
+
+        Ok(transitions)
+    }
+
+    /// Generic search of the event, cf. `find`.
+    #[allow(clippy::identity_op)]
+    fn find_heuristic<E>(
+        &self,
+        event: &E,
+        cfg: &EventSearchCfg,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<EventDetails<S>>, EventError>
+    where
+        E: EventEvaluator<S>,
+    {
+        let start_epoch = self.first().epoch();
+        let end_epoch = self.last().epoch();
+        let heuristic = cfg.chunk_duration(start_epoch, end_epoch);
         info!("Searching for {event} with initial heuristic of {heuristic}");
 
         let (sender, receiver) = channel();
 
-        let epochs: Vec<Epoch> = TimeSeries::inclusive(start_epoch, end_epoch, heuristic).collect();
-        epochs.into_par_iter().for_each_with(sender, |s, epoch| {
-            if let Ok(event_state) =
-                self.find_bracketed(epoch, epoch + heuristic, event, almanac.clone())
-            {
-                s.send(event_state).unwrap()
-            };
-        });
+        let chunks = cfg.chunks(start_epoch, end_epoch);
+        chunks
+            .par_iter()
+            .for_each_with(sender, |s, (chunk_start, chunk_end)| {
+                if let Ok(event_state) =
+                    self.find_bracketed(*chunk_start, *chunk_end, event, almanac.clone())
+                {
+                    s.send(event_state).unwrap()
+                };
+            });
 
         let mut states: Vec<_> = receiver.iter().collect();
+        sort_dedup_overlaps(&mut states, &chunks);
 
         if states.is_empty() {
             warn!("Heuristic failed to find any {event} event, using slower approach");
@@ -301,24 +669,223 @@ where
                 }
             };
         }
-        // Remove duplicates and reorder
-        states.sort_by(|s1, s2| s1.state.epoch().partial_cmp(&s2.state.epoch()).unwrap());
-        states.dedup();
 
-        match states.len() {
-            0 => info!("Event {event} not found"),
-            1 => info!("Event {event} found once on {}", states[0].state.epoch()),
-            _ => {
-                info!(
-                    "Event {event} found {} times from {} until {}",
-                    states.len(),
-                    states.first().unwrap().state.epoch(),
-                    states.last().unwrap().state.epoch()
-                )
+        Ok(states)
+    }
+
+    /// Search of the event only around the crossings predicted by the event itself, cf. `EventEvaluator::predict`.
+    ///
+    /// The prediction is made from the state at the start of the trajectory, and then from the state at the end of the validity of each prediction.
+    /// Returns None if the event cannot be predicted throughout the trajectory, or if a predicted crossing is not found in its bracket,
+    /// in which case the generic search must be used.
+    fn find_predicted<E>(&self, event: &E, almanac: Arc<Almanac>) -> Option<Vec<EventDetails<S>>>
+    where
+        E: EventEvaluator<S>,
+    {
+        let start_epoch = self.first().epoch();
+        let end_epoch = self.last().epoch();
+
+        // Brackets of each predicted crossing, and whether that bracket was clipped to the trajectory
+        let mut brackets = Vec::new();
+        let mut epoch = start_epoch;
+        while epoch < end_epoch {
+            let state = self.at(epoch).ok()?;
+            let prediction = event.predict(&state, almanac.clone())?;
+            if prediction.valid_until <= epoch {
+                return None;
+            }
+            for predicted in prediction.epochs {
+                if predicted < epoch || predicted >= prediction.valid_until {
+                    continue;
+                }
+                let lower = predicted - prediction.margin;
+                let upper = predicted + prediction.margin;
+                if upper < start_epoch || lower > end_epoch {
+                    continue;
+                }
+                let clipped = lower < start_epoch || upper > end_epoch;
+                brackets.push((lower.max(start_epoch), upper.min(end_epoch), clipped));
+            }
+            epoch = prediction.valid_until;
+        }
+
+        info!(
+            "Searching for {event} around {} predicted crossings",
+            brackets.len()
+        );
+
+        let found: Vec<Option<Option<EventDetails<S>>>> = brackets
+            .into_par_iter()
+            .map(|(lower, upper, clipped)| {
+                match self.find_bracketed(lower, upper, event, almanac.clone()) {
+                    Ok(details) => Some(Some(details)),
+                    // The predicted crossing may be just outside of the trajectory
+                    Err(_) if clipped => Some(None),
+                    Err(_) => {
+                        warn!("{event} not found between {lower} and {upper} despite its prediction, using the generic search");
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Some(
+            found
+                .into_iter()
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .flatten()
+                .collect(),
+        )
+    }
+
+    /// Find all of the states where the event happens, each paired with the evaluation of the event at that state.
+    ///
+    /// This is the same search as `find`, and is useful to check that the event values are within the value precision of the event.
+    pub fn find_all_with_values<E>(
+        &self,
+        event: &E,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<(S, f64)>, EventError>
+    where
+        E: EventEvaluator<S>,
+    {
+        self.find(event, almanac.clone())?
+            .into_iter()
+            .map(|details| {
+                let value = event.eval(&details.state, almanac.clone())?;
+                Ok((details.state, value))
+            })
+            .collect()
+    }
+
+    /// Find all of the states where the event happens, within the work budget of the search configuration and until cancelled.
+    ///
+    /// The trajectory is split in the same chunks as `find`, so the events found are identical to those of `find` if the budget is ample,
+    /// but, unlike `find`, this does not fall back to a search around the extrema of the event if no event is found by the heuristic.
+    /// Hence, this returns an empty list of events instead of an error if there is no event.
+    ///
+    /// If the work budget is exhausted or the search is cancelled, the events found until then are returned, and the result is flagged accordingly.
+    pub fn find_bounded<E>(
+        &self,
+        event: &E,
+        cfg: &EventSearchCfg,
+        almanac: Arc<Almanac>,
+    ) -> Result<EventSearchResults<S>, EventError>
+    where
+        E: EventEvaluator<S>,
+    {
+        let start_epoch = self.first().epoch();
+        let end_epoch = self.last().epoch();
+        if start_epoch == end_epoch {
+            return Err(EventError::NotFound {
+                start: start_epoch,
+                end: end_epoch,
+                event: format!("{event}"),
+            });
+        }
+        info!("Searching for {event} with {cfg:?}");
+
+        let budget = SearchBudget::new(cfg);
+
+        // Evaluate the event at each sample of the trajectory, which does not require any interpolation.
+        let sample_evals: Vec<f64> = if cfg.prefilter {
+            self.states
+                .par_iter()
+                .map(|state| {
+                    if budget.cancelled() {
+                        f64::NAN
+                    } else {
+                        event.eval(state, almanac.clone()).unwrap_or(f64::NAN)
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let may_cross = |chunk_start: Epoch, chunk_end: Epoch| -> bool {
+            if !cfg.prefilter {
+                return true;
             }
+            // Include the samples bracketing the chunk
+            let first = self
+                .states
+                .partition_point(|state| state.epoch() <= chunk_start)
+                .saturating_sub(1);
+            let last = self
+                .states
+                .partition_point(|state| state.epoch() < chunk_end)
+                .min(self.states.len() - 1);
+            let evals = &sample_evals[first..=last];
+            if evals.iter().any(|val| val.is_nan()) {
+                // Cannot conclude, so search this chunk.
+                return true;
+            }
+            let (min, max) = evals
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), val| {
+                    (min.min(*val), max.max(*val))
+                });
+            let precision = event.value_precision().abs();
+            !(min > precision || max < -precision)
         };
 
-        Ok(states)
+        let (sender, receiver) = channel();
+        let skipped = AtomicUsize::new(0);
+
+        let chunks = cfg.chunks(start_epoch, end_epoch);
+        chunks
+            .par_iter()
+            .for_each_with(sender, |s, &(chunk_start, chunk_end)| {
+                if budget.cancelled() || budget.exhausted() {
+                    return;
+                }
+                if !may_cross(chunk_start, chunk_end) {
+                    skipped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                if let Ok(event_state) = self.find_bracketed_within(
+                    chunk_start,
+                    chunk_end,
+                    event,
+                    almanac.clone(),
+                    Some(&budget),
+                ) {
+                    s.send(event_state).unwrap()
+                };
+            });
+
+        let mut events: Vec<_> = receiver.iter().collect();
+        // Remove duplicates and reorder
+        sort_dedup_overlaps(&mut events, &chunks);
+
+        let results = EventSearchResults {
+            events,
+            budget_exhausted: budget.exhausted(),
+            cancelled: budget.cancelled(),
+            evaluations: budget.evaluations(),
+            skipped_chunks: skipped.into_inner(),
+        };
+
+        if results.is_complete() {
+            info!(
+                "Event {event} found {} times using {} evaluations ({} chunks skipped)",
+                results.events.len(),
+                results.evaluations,
+                results.skipped_chunks
+            );
+        } else {
+            warn!(
+                "Search for {event} stopped early (budget exhausted: {}, cancelled: {}) after {} evaluations with {} events found",
+                results.budget_exhausted,
+                results.cancelled,
+                results.evaluations,
+                results.events.len()
+            );
+        }
+
+        Ok(results)
     }
 
     /// Find the minimum and maximum of the provided event through the trajectory
@@ -385,6 +952,9 @@ where
     /// - Handles edge cases where the trajectory starts or ends with a rising or falling edge.
     /// - Prints debug information for each event and arc.
     ///
+    /// ## Discrete events
+    /// The edges of discrete events (cf. `EventEvaluator::is_discrete`) are found by bisection with `find_transitions`, and the arcs are the intervals where the condition holds.
+    ///
     /// ## Note
     /// If no zero crossing happens in the trajectory, i.e. the there is "event is true" _and_ "event is false",
     /// then this function checks whether the event is true at the start and end of the trajectory. If so, it means
@@ -503,3 +1073,67 @@ where
         Ok(arcs)
     }
 }
+
+#[cfg(test)]
+mod ut_search {
+    use super::{sort_dedup_overlaps, EventDetails, EventEdge, EventSearchCfg};
+    use crate::time::{Epoch, TimeUnits};
+    use crate::{Orbit, Spacecraft};
+    use anise::constants::frames::EARTH_J2000;
+
+    fn details(epoch: Epoch, edge: EventEdge) -> EventDetails<Spacecraft> {
+        let orbit = Orbit::cartesian(7000.0, 0.0, 0.0, 0.0, 7.5, 0.0, epoch, EARTH_J2000);
+        EventDetails {
+            state: orbit.into(),
+            edge,
+            value: 0.0,
+            prev_value: None,
+            next_value: None,
+            pm_duration: 1.milliseconds(),
+            repr: String::new(),
+        }
+    }
+
+    #[test]
+    fn dedup_only_in_chunk_overlaps() {
+        let start = Epoch::from_gregorian_tai_at_noon(2020, 1, 1);
+        // Chunks of 1 hour overlapping by 30 minutes
+        let cfg = EventSearchCfg::builder()
+            .chunk(1.hours())
+            .chunk_overlap(0.5)
+            .build();
+        let chunks = cfg.chunks(start, start + 5.hours());
+        assert_eq!(chunks.len(), 5);
+
+        let mut events = vec![
+            // Same edge, 10 minutes apart, i.e. closer than the overlap, outside of any overlap: both are kept
+            details(start + 5.minutes(), EventEdge::Rising),
+            details(start + 15.minutes(), EventEdge::Rising),
+            // Same crossing found by two adjacent chunks within the epoch precision: only one is kept
+            details(start + 70.minutes(), EventEdge::Falling),
+            details(start + 70.minutes() + 1.milliseconds(), EventEdge::Falling),
+            // Same edge, 10 minutes apart, both in the overlap of two chunks: both are kept
+            details(start + 125.minutes(), EventEdge::Rising),
+            details(start + 135.minutes(), EventEdge::Rising),
+        ];
+        // The events of parallel chunks are received in any order
+        events.reverse();
+
+        sort_dedup_overlaps(&mut events, &chunks);
+
+        let epochs = events
+            .iter()
+            .map(|details| details.state.orbit.epoch)
+            .collect::<Vec<Epoch>>();
+        assert_eq!(
+            epochs,
+            vec![
+                start + 5.minutes(),
+                start + 15.minutes(),
+                start + 70.minutes(),
+                start + 125.minutes(),
+                start + 135.minutes(),
+            ]
+        );
+    }
+}
@@ -27,9 +27,11 @@ pub mod prelude {
     pub use super::{
         targeter::*,
         trajectory::{ExportCfg, Interpolatable, Traj},
-        Event, ScTraj, StateParameter,
+        Event, EventSearchCfg, ScTraj, StateParameter,
+    };
+    pub use crate::cosmic::{
+        try_achieve_b_plane, BPlane, BPlaneTarget, GuidanceMode, OrbitDual, OrbitExt,
     };
-    pub use crate::cosmic::{try_achieve_b_plane, BPlane, BPlaneTarget, GuidanceMode, OrbitDual};
     pub use crate::dynamics::{
         Drag, Harmonics, OrbitalDynamics, PointMasses, SolarPressure, SpacecraftDynamics,
     };
@@ -47,8 +49,14 @@ pub mod prelude {
 
 pub mod trajectory;
 
+pub mod budget;
+pub use budget::{DvBudget, DvBudgetReport};
+
 pub(crate) mod events;
-pub use events::{Event, EventEvaluator};
+pub use crate::errors::EventError;
+pub use events::details::{EventArc, EventDetails, EventEdge};
+pub use events::search::{EventSearchCfg, EventSearchResults};
+pub use events::{Event, EventEvaluator, EventPrediction};
 
 pub mod objective;
 pub mod opti;
@@ -311,6 +311,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                 //     computation_dur: conv_dur,
                 //     variables: self.variables.clone(),
                 //     achieved_errors: err_vector,
+                //     achieved_values: achieved_vector,
                 //     achieved_objectives: self.objectives.clone(),
                 //     iterations: it,
                 // };
@@ -18,7 +18,11 @@
 
 // pub mod convert_impulsive;
 pub mod multipleshooting;
+/// Minimizes a scalar cost of the control variables of a targeter subject to its objectives, with a gradient projection method.
+pub mod optimizer;
 pub use multipleshooting::{ctrlnodes, multishoot};
+/// Finds periodic orbits in any dynamics with a differential corrector on the closure of selected state parameters.
+pub mod periodic;
 /// Uses a [Newton Raphson](https://en.wikipedia.org/wiki/Newton%27s_method_in_optimization) method where the Jacobian is computed via finite differencing.
 pub mod raphson_finite_diff;
 /// Uses a [Newton Raphson](https://en.wikipedia.org/wiki/Newton%27s_method_in_optimization) method where the Jacobian is computed via hyperdual numbers.
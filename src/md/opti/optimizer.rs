@@ -0,0 +1,472 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use snafu::{ensure, ResultExt};
+
+use super::solution::TargeterSolution;
+use super::targeter::Targeter;
+use crate::cosmic::{AstroAlmanacSnafu, AstroPhysicsSnafu};
+use crate::errors::TargetingError;
+use crate::linalg::{SMatrix, SVector, Vector6};
+use crate::md::{
+    prelude::*, AstroSnafu, PropSnafu, StateParameter, UnderdeterminedProblemSnafu,
+    VerificationSnafu,
+};
+use crate::pseudo_inverse;
+use rayon::prelude::*;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Minimizes a scalar cost of the control variables of a [Targeter] subject to its objectives, e.g. the magnitude of the delta-v
+/// when there are fewer objectives than variables and an infinite family of corrections achieves them.
+///
+/// The objectives are equality constraints, satisfied when achieved within their tolerance. The plain targeter solution is the first
+/// feasible point, from which a gradient projection method is used: the cost gradient (from central finite differences) is projected
+/// onto the null space of the Jacobian of the objectives (from the finite differencing of the targeter), a backtracking line search
+/// is performed along the projected gradient, and each trial point is restored onto the constraints with Newton steps.
+///
+/// The control variables are kept within their `min_value` and `max_value` bounds. Only impulsive corrections (position or velocity
+/// variables) are supported.
+#[derive(Clone)]
+pub struct TargeterOptimizer<'a, C, const V: usize, const O: usize>
+where
+    C: Fn(&SVector<f64, V>) -> f64 + Sync,
+{
+    /// The targeter whose objectives are the constraints, and whose variables are the control of the cost
+    pub targeter: Targeter<'a, V, O>,
+    /// The cost to minimize as a function of the control variables, in the units of the variables (e.g. km/s for velocity corrections)
+    pub cost: C,
+    /// Maximum number of gradient projection iterations
+    pub iterations: usize,
+    /// Maximum number of Newton steps to restore a trial point onto the constraints
+    pub restoration_iterations: usize,
+    /// Maximum number of halvings of the step in the line search
+    pub line_search_iterations: usize,
+    /// The optimization stops when the norm of the projected gradient of the cost falls below this value
+    pub gradient_tolerance: f64,
+}
+
+impl<'a, const V: usize, const O: usize> TargeterOptimizer<'a, fn(&SVector<f64, V>) -> f64, V, O> {
+    /// Create a new optimizer which minimizes the norm of the correction of the targeter, e.g. the delta-v magnitude of a `Targeter::delta_v`.
+    pub fn min_correction(targeter: Targeter<'a, V, O>) -> Self {
+        Self::new(targeter, |control| control.norm())
+    }
+}
+
+impl<'a, C, const V: usize, const O: usize> fmt::Display for TargeterOptimizer<'a, C, V, O>
+where
+    C: Fn(&SVector<f64, V>) -> f64 + Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Optimizer of the {}", self.targeter)
+    }
+}
+
+impl<'a, C, const V: usize, const O: usize> TargeterOptimizer<'a, C, V, O>
+where
+    C: Fn(&SVector<f64, V>) -> f64 + Sync,
+{
+    /// Create a new optimizer of the provided cost subject to the objectives of the targeter.
+    pub fn new(targeter: Targeter<'a, V, O>, cost: C) -> Self {
+        Self {
+            targeter,
+            cost,
+            iterations: 50,
+            restoration_iterations: 10,
+            line_search_iterations: 10,
+            gradient_tolerance: 1e-8,
+        }
+    }
+
+    /// Minimizes the cost of the correction applied at the correction epoch such that the objectives are achieved at the achievement epoch.
+    ///
+    /// Returns the best feasible solution found, which is the plain targeter solution if no cheaper feasible solution was found.
+    pub fn try_minimize(
+        &self,
+        initial_state: Spacecraft,
+        correction_epoch: Epoch,
+        achievement_epoch: Epoch,
+        almanac: Arc<Almanac>,
+    ) -> Result<OptimizerSolution<V, O>, TargetingError> {
+        ensure!(
+            !self.targeter.objectives.is_empty(),
+            UnderdeterminedProblemSnafu
+        );
+
+        for var in &self.targeter.variables {
+            var.valid()?;
+            if var.component.is_finite_burn() {
+                return Err(TargetingError::UnsupportedVariable {
+                    var: format!("{:?}", var.component),
+                });
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_instant = Instant::now();
+
+        // The plain targeter solution is the first feasible point
+        let targeter_sol = self.targeter.try_achieve_from(
+            initial_state,
+            correction_epoch,
+            achievement_epoch,
+            almanac.clone(),
+        )?;
+
+        let xi_start = self
+            .targeter
+            .prop
+            .with(initial_state, almanac.clone())
+            .quiet()
+            .until_epoch(correction_epoch)
+            .context(PropSnafu)?;
+
+        let targeter_cost = (self.cost)(&targeter_sol.correction);
+
+        // The correction of the targeter is accumulated over its iterations, so make sure that it is feasible as a single correction.
+        let mut control = targeter_sol.correction;
+        let mut current = self.evaluate(xi_start, &control, achievement_epoch, &almanac)?;
+        if !current.feasible {
+            let (restored_control, restored) =
+                self.restore(xi_start, control, current, achievement_epoch, &almanac)?;
+            ensure!(
+                restored.feasible,
+                VerificationSnafu {
+                    msg: "could not restore the targeter solution onto the objectives".to_string(),
+                }
+            );
+            control = restored_control;
+            current = restored;
+        }
+        let mut cost = (self.cost)(&control);
+
+        let mut history = Vec::with_capacity(self.iterations + 1);
+
+        for it in 0..self.iterations {
+            let jac = self.jacobian(xi_start, &control, &current, achievement_epoch, &almanac)?;
+            let jac_inv = pseudo_inverse!(&jac)?;
+
+            // Projection onto the null space of the Jacobian, i.e. the directions which do not change the objectives to first order
+            let projector = SMatrix::<f64, V, V>::identity() - jac_inv * jac;
+            let direction = -(projector * self.cost_gradient(&control));
+            let projected_gradient_norm = direction.norm();
+
+            history.push(OptimizerIteration {
+                control,
+                cost,
+                projected_gradient_norm,
+            });
+
+            info!(
+                "Optimizer -- Iteration #{it} -- cost = {cost:.6e}, projected gradient norm = {projected_gradient_norm:.3e}"
+            );
+
+            if projected_gradient_norm < self.gradient_tolerance {
+                info!("Optimizer -- CONVERGED in {it} iterations");
+                break;
+            }
+
+            // Scale the first trial step such that no variable moves by more than its maximum step
+            let mut step = 1.0_f64;
+            for (i, var) in self.targeter.variables.iter().enumerate() {
+                if (step * direction[i]).abs() > var.max_step {
+                    step = var.max_step / direction[i].abs();
+                }
+            }
+
+            let mut improved = false;
+            for _ in 0..self.line_search_iterations {
+                let trial_control = self.apply_bounds(control + step * direction);
+                let trial = self.evaluate(xi_start, &trial_control, achievement_epoch, &almanac)?;
+                let (trial_control, trial) =
+                    self.restore(xi_start, trial_control, trial, achievement_epoch, &almanac)?;
+
+                if trial.feasible {
+                    let trial_cost = (self.cost)(&trial_control);
+                    if trial_cost < cost {
+                        control = trial_control;
+                        current = trial;
+                        cost = trial_cost;
+                        improved = true;
+                        break;
+                    }
+                }
+                step *= 0.5;
+            }
+
+            if !improved {
+                info!("Optimizer -- no cheaper feasible point along the projected gradient after {it} iterations");
+                break;
+            }
+        }
+
+        // Record the last accepted iterate if the maximum number of iterations was reached
+        if history.last().map(|iterate| iterate.control) != Some(control) {
+            history.push(OptimizerIteration {
+                control,
+                cost,
+                projected_gradient_norm: f64::NAN,
+            });
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let computation_dur = Instant::now() - start_instant;
+        #[cfg(target_arch = "wasm32")]
+        let computation_dur = Duration::ZERO.into();
+
+        let solution = TargeterSolution {
+            corrected_state: self.corrected(xi_start, &control)?,
+            achieved_state: current.state,
+            correction: control,
+            computation_dur,
+            variables: self.targeter.variables,
+            achieved_errors: current.errors,
+            achieved_values: current.values,
+            achieved_objectives: self.targeter.objectives,
+            iterations: history.len(),
+        };
+
+        Ok(OptimizerSolution {
+            solution,
+            cost,
+            targeter_cost,
+            history,
+        })
+    }
+
+    /// Returns the control clamped within the bounds of each variable.
+    fn apply_bounds(&self, mut control: SVector<f64, V>) -> SVector<f64, V> {
+        for (i, var) in self.targeter.variables.iter().enumerate() {
+            var.ensure_bounds(&mut control[i]);
+        }
+        control
+    }
+
+    /// Central finite differencing of the cost with respect to each control variable.
+    fn cost_gradient(&self, control: &SVector<f64, V>) -> SVector<f64, V> {
+        let mut gradient = SVector::<f64, V>::zeros();
+        for (i, var) in self.targeter.variables.iter().enumerate() {
+            let mut plus = *control;
+            plus[i] += var.perturbation;
+            let mut minus = *control;
+            minus[i] -= var.perturbation;
+            gradient[i] = ((self.cost)(&plus) - (self.cost)(&minus)) / (2.0 * var.perturbation);
+        }
+        gradient
+    }
+
+    /// Applies Newton steps on the objectives until the control is feasible, or the maximum number of restoration iterations is reached.
+    fn restore(
+        &self,
+        xi_start: Spacecraft,
+        mut control: SVector<f64, V>,
+        mut current: Evaluation<O>,
+        achievement_epoch: Epoch,
+        almanac: &Arc<Almanac>,
+    ) -> Result<(SVector<f64, V>, Evaluation<O>), TargetingError> {
+        for _ in 0..self.restoration_iterations {
+            if current.feasible {
+                break;
+            }
+            let jac = self.jacobian(xi_start, &control, &current, achievement_epoch, almanac)?;
+            let jac_inv = pseudo_inverse!(&jac)?;
+            control = self.apply_bounds(control + jac_inv * current.errors);
+            current = self.evaluate(xi_start, &control, achievement_epoch, almanac)?;
+        }
+        Ok((control, current))
+    }
+
+    /// Jacobian of the scaled objectives with respect to the control, computed by finite differencing.
+    fn jacobian(
+        &self,
+        xi_start: Spacecraft,
+        control: &SVector<f64, V>,
+        current: &Evaluation<O>,
+        achievement_epoch: Epoch,
+        almanac: &Arc<Almanac>,
+    ) -> Result<SMatrix<f64, O, V>, TargetingError> {
+        let columns = (0..V)
+            .into_par_iter()
+            .map(|j| -> Result<SVector<f64, O>, TargetingError> {
+                let var = &self.targeter.variables[j];
+                let mut this_control = *control;
+                this_control[j] += var.perturbation;
+                let this_eval =
+                    self.evaluate(xi_start, &this_control, achievement_epoch, almanac)?;
+                Ok((this_eval.values - current.values) / var.perturbation)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut jac = SMatrix::<f64, O, V>::zeros();
+        for (j, column) in columns.iter().enumerate() {
+            for (i, obj) in self.targeter.objectives.iter().enumerate() {
+                jac[(i, j)] = obj.multiplicative_factor * column[i];
+            }
+        }
+        Ok(jac)
+    }
+
+    /// Applies the control to the state at the correction epoch.
+    fn corrected(
+        &self,
+        xi_start: Spacecraft,
+        control: &SVector<f64, V>,
+    ) -> Result<Spacecraft, TargetingError> {
+        let mut state_correction = Vector6::<f64>::zeros();
+        for (i, var) in self.targeter.variables.iter().enumerate() {
+            state_correction[var.component.vec_index()] += control[i];
+        }
+
+        let mut xi = xi_start;
+        if let Some(frame) = self.targeter.correction_frame {
+            // The following will error if the frame is not local
+            let dcm_local2inertial = frame
+                .dcm_to_inertial(xi.orbit)
+                .context(AstroPhysicsSnafu)
+                .context(AstroSnafu)?
+                .rot_mat;
+
+            let velocity_correction = dcm_local2inertial * state_correction.fixed_rows::<3>(3);
+            xi.orbit.apply_dv_km_s(velocity_correction);
+        } else {
+            xi = xi + state_correction;
+        }
+        Ok(xi)
+    }
+
+    /// Propagates the corrected state to the achievement epoch and assesses the objectives.
+    fn evaluate(
+        &self,
+        xi_start: Spacecraft,
+        control: &SVector<f64, V>,
+        achievement_epoch: Epoch,
+        almanac: &Arc<Almanac>,
+    ) -> Result<Evaluation<O>, TargetingError> {
+        let xi = self.corrected(xi_start, control)?;
+        let xf = self
+            .targeter
+            .prop
+            .with(xi, almanac.clone())
+            .quiet()
+            .until_epoch(achievement_epoch)
+            .context(PropSnafu)?;
+
+        let xf_dual_obj_frame = match &self.targeter.objective_frame {
+            Some(frame) => {
+                let orbit_obj_frame = almanac
+                    .transform_to(xf.orbit, *frame, None)
+                    .context(AstroAlmanacSnafu)
+                    .context(AstroSnafu)?;
+
+                OrbitDual::from(orbit_obj_frame)
+            }
+            None => OrbitDual::from(xf.orbit),
+        };
+
+        // Build the B-Plane once, if needed, and always in the objective frame
+        let b_plane = if self
+            .targeter
+            .objectives
+            .iter()
+            .any(|obj| obj.parameter.is_b_plane())
+        {
+            Some(BPlane::from_dual(xf_dual_obj_frame).context(AstroSnafu)?)
+        } else {
+            None
+        };
+
+        let mut values = SVector::<f64, O>::zeros();
+        let mut errors = SVector::<f64, O>::zeros();
+        let mut feasible = true;
+
+        for (i, obj) in self.targeter.objectives.iter().enumerate() {
+            let partial = if obj.parameter.is_b_plane() {
+                match obj.parameter {
+                    StateParameter::BdotR => b_plane.unwrap().b_r,
+                    StateParameter::BdotT => b_plane.unwrap().b_t,
+                    StateParameter::BLTOF => b_plane.unwrap().ltof_s,
+                    _ => unreachable!(),
+                }
+            } else {
+                xf_dual_obj_frame
+                    .partial_for(obj.parameter)
+                    .context(AstroSnafu)?
+            };
+
+            let achieved = partial.real();
+            let (ok, param_err) = obj.assess_value(achieved);
+            feasible &= ok;
+            values[i] = achieved;
+            errors[i] = param_err;
+        }
+
+        Ok(Evaluation {
+            state: xf,
+            values,
+            errors,
+            feasible,
+        })
+    }
+}
+
+/// Objectives achieved by a given control
+#[derive(Copy, Clone, Debug)]
+struct Evaluation<const O: usize> {
+    state: Spacecraft,
+    values: SVector<f64, O>,
+    errors: SVector<f64, O>,
+    feasible: bool,
+}
+
+/// An accepted iterate of the optimizer, which is always feasible
+#[derive(Copy, Clone, Debug)]
+pub struct OptimizerIteration<const V: usize> {
+    /// The control variables at this iteration
+    pub control: SVector<f64, V>,
+    /// The cost of this control
+    pub cost: f64,
+    /// The norm of the cost gradient projected onto the null space of the Jacobian of the objectives, zero at an optimum (NaN if not computed for the last iterate)
+    pub projected_gradient_norm: f64,
+}
+
+/// Defines the solution of the optimizer
+#[derive(Clone, Debug)]
+pub struct OptimizerSolution<const V: usize, const O: usize> {
+    /// The best feasible solution found, which can be applied with the targeter
+    pub solution: TargeterSolution<V, O>,
+    /// The cost of the best feasible solution
+    pub cost: f64,
+    /// The cost of the plain targeter solution, which the optimizer starts from
+    pub targeter_cost: f64,
+    /// The accepted iterates of the optimizer, starting with the plain targeter solution
+    pub history: Vec<OptimizerIteration<V>>,
+}
+
+impl<const V: usize, const O: usize> fmt::Display for OptimizerSolution<V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Optimizer solution: cost = {:.6e} (targeter cost = {:.6e}) after {} iterations",
+            self.cost,
+            self.targeter_cost,
+            self.history.len()
+        )?;
+        write!(f, "{}", self.solution)
+    }
+}
@@ -0,0 +1,474 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use nalgebra::Complex;
+use snafu::{ensure, ResultExt};
+
+use crate::cosmic::AstroAlmanacSnafu;
+use crate::errors::TargetingError;
+use crate::linalg::{DMatrix, Matrix6, RowVector6, SVector, Vector6};
+use crate::md::objective::Objective;
+use crate::md::AstroSnafu;
+use crate::md::{prelude::*, PropSnafu, UnderdeterminedProblemSnafu};
+pub use crate::md::{Variable, Vary};
+use crate::propagators::PropagationError;
+use crate::pseudo_inverse;
+use crate::utils::between_pm_180;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// A differential corrector which finds periodic orbits in any spacecraft dynamics, i.e. initial conditions such that selected parameters of the state repeat after a given time.
+///
+/// The closure objectives are the differences between the initial and the final values of the selected state parameters, e.g. the Cartesian state for a periodic orbit,
+/// or the eccentricity and argument of periapsis for a frozen orbit. The desired value of each closure objective is ignored: it is replaced by the initial value of that parameter
+/// at each iteration, and only its tolerance and scaling factors are used.
+///
+/// Like the targeter, the Jacobian of the closure with respect to the variables is computed from the STM of the propagation, so the dynamics must support the STM.
+/// When the closure is assessed in another frame than the integration frame (e.g. a body fixed frame for a repeat ground track), the partials are rotated into the integration frame.
+#[derive(Clone)]
+pub struct PeriodicOrbitSolver<'a, const V: usize, const O: usize> {
+    /// The propagator setup (kind, stages, etc.)
+    pub prop: &'a Propagator<SpacecraftDynamics>,
+    /// The parameters which must repeat after the period, with their tolerances
+    pub closure: [Objective; O],
+    /// An optional frame in which to assess the closure, defaults to the integration frame
+    pub closure_frame: Option<Frame>,
+    /// The components of the initial state to correct, must be position or velocity components
+    pub variables: [Variable; V],
+    /// Set to true to also correct the period, in which case the Jacobian includes the rate of the closure parameters at the end of the period
+    pub vary_period: bool,
+    /// Maximum number of iterations
+    pub iterations: usize,
+}
+
+impl<'a, const V: usize, const O: usize> fmt::Display for PeriodicOrbitSolver<'a, V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut objmsg = String::from("");
+        for obj in &self.closure {
+            objmsg.push_str(&format!("{:?} (± {:.1e}); ", obj.parameter, obj.tolerance));
+        }
+
+        let mut varmsg = String::from("");
+        for var in &self.variables {
+            varmsg.push_str(&format!("{var}; "));
+        }
+        if self.vary_period {
+            varmsg.push_str("period; ");
+        }
+
+        write!(
+            f,
+            "Periodic orbit solver:\n\tClosure: {objmsg}\n\tCorrect: {varmsg}"
+        )
+    }
+}
+
+impl<'a, const V: usize, const O: usize> PeriodicOrbitSolver<'a, V, O> {
+    /// Create a new periodic orbit solver which corrects the provided variables of the initial state until the closure parameters repeat after a fixed period.
+    pub fn new(
+        prop: &'a Propagator<SpacecraftDynamics>,
+        variables: [Variable; V],
+        closure: [Objective; O],
+    ) -> Self {
+        Self {
+            prop,
+            closure,
+            closure_frame: None,
+            variables,
+            vary_period: false,
+            iterations: 50,
+        }
+    }
+
+    /// Create a new periodic orbit solver where the closure is assessed in the provided frame, e.g. a body fixed frame for a repeat ground track.
+    pub fn in_frame(
+        prop: &'a Propagator<SpacecraftDynamics>,
+        variables: [Variable; V],
+        closure: [Objective; O],
+        closure_frame: Frame,
+    ) -> Self {
+        Self {
+            prop,
+            closure,
+            closure_frame: Some(closure_frame),
+            variables,
+            vary_period: false,
+            iterations: 50,
+        }
+    }
+
+    /// Returns a copy of this solver which also corrects the period.
+    pub fn with_period_correction(mut self) -> Self {
+        self.vary_period = true;
+        self
+    }
+
+    /// Corrects the initial guess until the closure parameters repeat after the period, which is also corrected if `vary_period` is set.
+    #[allow(clippy::identity_op)]
+    pub fn try_close(
+        &self,
+        initial_guess: Spacecraft,
+        period: Duration,
+        almanac: Arc<Almanac>,
+    ) -> Result<PeriodicOrbitSolution<V, O>, TargetingError> {
+        ensure!(!self.closure.is_empty(), UnderdeterminedProblemSnafu);
+
+        let mut xi = initial_guess;
+
+        // Store the total correction in a static vector
+        let mut total_correction = SVector::<f64, V>::zeros();
+
+        // Apply the initial guess
+        for (i, var) in self.variables.iter().enumerate() {
+            Self::apply_to(&mut xi, var, var.init_guess)?;
+            total_correction[i] += var.init_guess;
+        }
+
+        let mut period_s = period.to_seconds();
+        let num_vars = V + usize::from(self.vary_period);
+
+        let mut prev_err_norm = f64::INFINITY;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_instant = Instant::now();
+
+        for it in 0..=self.iterations {
+            // Enable the trajectory STM from the initial state, whose final value is the monodromy matrix.
+            xi.enable_stm();
+
+            let xf = self
+                .prop
+                .with(xi, almanac.clone())
+                .quiet()
+                .for_duration(period_s * Unit::Second)
+                .context(PropSnafu)?;
+
+            let monodromy = xf.stm().unwrap().fixed_view::<6, 6>(0, 0).into_owned();
+
+            let initial_values = self.closure_values(xi.orbit, almanac.clone())?;
+            let final_values = self.closure_values(xf.orbit, almanac.clone())?;
+
+            // The sensitivity of the final state to the period is the rate of the state at the end of the period.
+            let xf_rate = if self.vary_period {
+                Some(self.state_rate(&xf, almanac.clone())?)
+            } else {
+                None
+            };
+
+            let mut closure = self.closure;
+            let mut err_vector = SVector::<f64, O>::zeros();
+            let mut achieved_vector = SVector::<f64, O>::zeros();
+            let mut converged = true;
+
+            let mut jac = DMatrix::from_element(O, num_vars, 0.0);
+
+            for (i, obj) in closure.iter_mut().enumerate() {
+                let (initial_value, initial_grad) = initial_values[i];
+                let (final_value, final_grad) = final_values[i];
+
+                // The closure targets the initial value, and angles are compared modulo a full revolution.
+                obj.desired_value = initial_value;
+                let mut delta = final_value - initial_value;
+                if obj.parameter.unit() == "deg" {
+                    delta = between_pm_180(delta);
+                }
+                let achieved = initial_value + delta;
+
+                let (ok, param_err) = obj.assess_value(achieved);
+                if !ok {
+                    converged = false;
+                }
+                err_vector[i] = param_err;
+                achieved_vector[i] = achieved;
+
+                // Partials of the closure, i.e. final minus initial value, with respect to the initial state
+                let closure_partials = final_grad * monodromy - initial_grad;
+
+                for (j, var) in self.variables.iter().enumerate() {
+                    jac[(i, j)] =
+                        obj.multiplicative_factor * closure_partials[var.component.vec_index()];
+                }
+
+                if let Some(xf_rate) = xf_rate {
+                    jac[(i, V)] = obj.multiplicative_factor * (final_grad * xf_rate)[(0, 0)];
+                }
+            }
+
+            info!(
+                "Periodic orbit solver -- Iteration #{it} -- closure error norm {:.3e}",
+                err_vector.norm()
+            );
+
+            if converged {
+                #[cfg(not(target_arch = "wasm32"))]
+                let conv_dur = Instant::now() - start_instant;
+                #[cfg(target_arch = "wasm32")]
+                let conv_dur = Duration::ZERO.into();
+
+                let mut initial_state = xi;
+                initial_state.stm = None;
+
+                let sol = PeriodicOrbitSolution {
+                    initial_state,
+                    final_state: xf,
+                    period: period_s * Unit::Second,
+                    period_correction: (period_s - period.to_seconds()) * Unit::Second,
+                    correction: total_correction,
+                    variables: self.variables,
+                    closure,
+                    closure_errors: err_vector,
+                    achieved_values: achieved_vector,
+                    monodromy,
+                    iterations: it,
+                    computation_dur: conv_dur,
+                };
+                info!("Periodic orbit solver -- CONVERGED in {it} iterations");
+                return Ok(sol);
+            }
+
+            if (err_vector.norm() - prev_err_norm).abs() < 1e-10 {
+                return Err(TargetingError::CorrectionIneffective {
+                    cur_val: err_vector.norm(),
+                    prev_val: prev_err_norm,
+                    action: "No change in closure errors",
+                });
+            }
+            prev_err_norm = err_vector.norm();
+
+            debug!("Jacobian {}", jac);
+
+            // Perform the pseudo-inverse if needed, else just inverse
+            let jac_inv = pseudo_inverse!(&jac)?;
+
+            let mut delta = jac_inv * err_vector;
+
+            debug!("Error vector: {}\nRaw correction: {}", err_vector, delta);
+
+            for (i, var) in self.variables.iter().enumerate() {
+                // Choose the minimum step between the provided max step and the correction.
+                if delta[i].abs() > var.max_step {
+                    delta[i] = var.max_step * delta[i].signum();
+                } else if delta[i] > var.max_value {
+                    delta[i] = var.max_value;
+                } else if delta[i] < var.min_value {
+                    delta[i] = var.min_value;
+                }
+
+                Self::apply_to(&mut xi, var, delta[i])?;
+                total_correction[i] += delta[i];
+            }
+
+            if self.vary_period {
+                debug!("Period correction: {} s", delta[V]);
+                period_s += delta[V];
+            }
+        }
+
+        Err(TargetingError::TooManyIterations)
+    }
+
+    /// Applies the correction of the provided variable to the state.
+    fn apply_to(state: &mut Spacecraft, var: &Variable, value: f64) -> Result<(), TargetingError> {
+        match var.component {
+            Vary::PositionX => state.orbit.radius_km.x += value,
+            Vary::PositionY => state.orbit.radius_km.y += value,
+            Vary::PositionZ => state.orbit.radius_km.z += value,
+            Vary::VelocityX => state.orbit.velocity_km_s.x += value,
+            Vary::VelocityY => state.orbit.velocity_km_s.y += value,
+            Vary::VelocityZ => state.orbit.velocity_km_s.z += value,
+            _ => {
+                return Err(TargetingError::UnsupportedVariable {
+                    var: var.to_string(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the value of each closure parameter of the orbit, in the closure frame, and its gradient with respect to the orbit state in the integration frame.
+    fn closure_values(
+        &self,
+        orbit: Orbit,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<(f64, RowVector6<f64>)>, TargetingError> {
+        let (orbit_closure_frame, dcm) = match self.closure_frame {
+            Some(frame) => {
+                let orbit_closure_frame = almanac
+                    .transform_to(orbit, frame, None)
+                    .context(AstroAlmanacSnafu)
+                    .context(AstroSnafu)?;
+
+                let dcm = almanac
+                    .rotate(orbit.frame, frame, orbit.epoch)
+                    .context(AstroAlmanacSnafu)
+                    .context(AstroSnafu)?
+                    .state_dcm();
+
+                (orbit_closure_frame, dcm)
+            }
+            None => (orbit, Matrix6::identity()),
+        };
+
+        let orbit_dual = OrbitDual::from(orbit_closure_frame);
+
+        let mut values = Vec::with_capacity(O);
+        for obj in &self.closure {
+            let partial = orbit_dual.partial_for(obj.parameter).context(AstroSnafu)?;
+            let grad = RowVector6::new(
+                partial.wtr_x(),
+                partial.wtr_y(),
+                partial.wtr_z(),
+                partial.wtr_vx(),
+                partial.wtr_vy(),
+                partial.wtr_vz(),
+            );
+            values.push((partial.real(), grad * dcm));
+        }
+
+        Ok(values)
+    }
+
+    /// Returns the rate of the position and velocity of the state from the dynamics of the propagator.
+    fn state_rate(
+        &self,
+        state: &Spacecraft,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vector6<f64>, TargetingError> {
+        let mut ctx = *state;
+        ctx.stm = None;
+
+        let rate = self
+            .prop
+            .dynamics
+            .eom(0.0, &ctx.to_vector(), &ctx, almanac)
+            .map_err(|source| TargetingError::PropError {
+                source: PropagationError::Dynamics { source },
+            })?;
+
+        Ok(rate.fixed_rows::<6>(0).into_owned())
+    }
+}
+
+/// A periodic orbit found by the `PeriodicOrbitSolver`
+#[derive(Clone, Debug)]
+pub struct PeriodicOrbitSolution<const V: usize, const O: usize> {
+    /// The corrected initial state of the periodic orbit
+    pub initial_state: Spacecraft,
+    /// The state after one period, whose STM is the monodromy matrix
+    pub final_state: Spacecraft,
+    /// The corrected period
+    pub period: Duration,
+    /// The correction applied to the initial guess of the period, zero if the period is not corrected
+    pub period_correction: Duration,
+    /// The correction applied to the initial state
+    pub correction: SVector<f64, V>,
+    /// The corrected components of the initial state
+    pub variables: [Variable; V],
+    /// The closure objectives, whose desired values are the values of the closure parameters in the initial state
+    pub closure: [Objective; O],
+    /// The closure errors, scaled by the multiplicative and additive factors of each objective
+    pub closure_errors: SVector<f64, O>,
+    /// The values of the closure parameters after one period
+    pub achieved_values: SVector<f64, O>,
+    /// The monodromy matrix, i.e. the state transition matrix of the position and velocity over one period
+    pub monodromy: Matrix6<f64>,
+    /// The number of iterations required
+    pub iterations: usize,
+    /// Computation duration
+    pub computation_dur: std::time::Duration,
+}
+
+impl<const V: usize, const O: usize> PeriodicOrbitSolution<V, O> {
+    /// Returns the eigenvalues of the monodromy matrix, which characterize the stability of the orbit.
+    pub fn eigenvalues(&self) -> Vec<Complex<f64>> {
+        self.monodromy
+            .complex_eigenvalues()
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Stability index of this orbit, (|λ_max| + 1 / |λ_max|) / 2 where λ_max is the largest eigenvalue of the monodromy matrix.
+    ///
+    /// The orbit is linearly stable if this index is one.
+    pub fn stability_index(&self) -> f64 {
+        let lambda_max = self
+            .eigenvalues()
+            .iter()
+            .map(|eigval| eigval.norm())
+            .fold(0.0, f64::max);
+
+        (lambda_max + 1.0 / lambda_max) / 2.0
+    }
+
+    /// Returns the position and velocity differences between the initial and final states, in km and km/s, in the integration frame.
+    pub fn state_closure(&self) -> (f64, f64) {
+        (
+            (self.final_state.orbit.radius_km - self.initial_state.orbit.radius_km).norm(),
+            (self.final_state.orbit.velocity_km_s - self.initial_state.orbit.velocity_km_s).norm(),
+        )
+    }
+}
+
+impl<const V: usize, const O: usize> fmt::Display for PeriodicOrbitSolution<V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut closuremsg = format!(
+            "\n\t\t{:<16} {:>16} {:>16} {:>12} {:>10}",
+            "Parameter", "Initial", "Final", "Error", "Tolerance"
+        );
+        for (i, obj) in self.closure.iter().enumerate() {
+            closuremsg.push_str(&format!(
+                "\n\t\t{:<16} {:>16.6} {:>16.6} {:>12.3e} {:>10.1e}",
+                format!("{:?}", obj.parameter),
+                obj.desired_value,
+                self.achieved_values[i],
+                self.closure_errors[i],
+                obj.tolerance,
+            ));
+        }
+
+        let mut corrmsg = String::new();
+        for (i, var) in self.variables.iter().enumerate() {
+            corrmsg.push_str(&format!(
+                "\n\t\t{:?} = {:e}",
+                var.component, self.correction[i]
+            ));
+        }
+
+        let mut eigmsg = String::new();
+        for eigval in self.eigenvalues() {
+            eigmsg.push_str(&format!("\n\t\t{:.6} {:+.6}i", eigval.re, eigval.im));
+        }
+
+        writeln!(
+            f,
+            "Periodic orbit (converged in {:.3} seconds, {} iterations) of period {} (corrected by {}):\n\tClosure:{}\n\tCorrection:{}\n\tMonodromy eigenvalues (stability index = {:.6}):{}\n\tInitial state:\n\t\t{}\n\t\t{:x}",
+            self.computation_dur.as_secs_f64(),
+            self.iterations,
+            self.period,
+            self.period_correction,
+            closuremsg,
+            corrmsg,
+            self.stability_index(),
+            eigmsg,
+            self.initial_state,
+            self.initial_state
+        )
+    }
+}
@@ -246,6 +246,7 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
 
             // Build the error vector
             let mut err_vector = SVector::<f64, O>::zeros();
+            let mut achieved_vector = SVector::<f64, O>::zeros();
             let mut converged = true;
 
             // Build the B-Plane once, if needed, and always in the objective frame
@@ -283,6 +284,7 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
                     converged = false;
                 }
                 err_vector[i] = param_err;
+                achieved_vector[i] = achieved;
 
                 objmsg.push(format!(
                     "\t{:?}: achieved = {:>width$.prec$}\t desired = {:>width$.prec$}\t scaled error = {:>width$.prec$}",
@@ -525,6 +527,7 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
                     computation_dur: conv_dur,
                     variables: self.variables,
                     achieved_errors: err_vector,
+                    achieved_values: achieved_vector,
                     achieved_objectives: self.objectives,
                     iterations: it,
                 };
@@ -155,6 +155,7 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
 
             // Build the error vector
             let mut err_vector = SVector::<f64, O>::zeros();
+            let mut achieved_vector = SVector::<f64, O>::zeros();
             let mut converged = true;
 
             // Build the B-Plane once, if needed, and always in the objective frame
@@ -192,6 +193,7 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
                     converged = false;
                 }
                 err_vector[i] = param_err;
+                achieved_vector[i] = achieved;
 
                 objmsg.push(format!(
                     "\t{:?}: achieved = {:>width$.prec$}\t desired = {:>width$.prec$}\t scaled error = {:>width$.prec$}",
@@ -260,6 +262,7 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
                     computation_dur: conv_dur,
                     variables: self.variables,
                     achieved_errors: err_vector,
+                    achieved_values: achieved_vector,
                     achieved_objectives: self.objectives,
                     iterations: it,
                 };
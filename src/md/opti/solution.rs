@@ -39,8 +39,10 @@ pub struct TargeterSolution<const V: usize, const O: usize> {
     pub correction: SVector<f64, V>,
     /// The kind of correction (position or velocity)
     pub variables: [Variable; V],
-    /// The errors achieved
+    /// The errors achieved, scaled by the multiplicative and additive factors of each objective
     pub achieved_errors: SVector<f64, O>,
+    /// The values of the objective parameters achieved, in the frame of the objectives
+    pub achieved_values: SVector<f64, O>,
     /// The objectives set in the targeter
     pub achieved_objectives: [Objective; O],
     /// The number of iterations required
@@ -50,6 +52,15 @@ pub struct TargeterSolution<const V: usize, const O: usize> {
 }
 
 impl<const V: usize, const O: usize> TargeterSolution<V, O> {
+    /// Returns whether each objective was achieved within its tolerance
+    pub fn objectives_met(&self) -> [bool; O] {
+        core::array::from_fn(|i| {
+            self.achieved_objectives[i]
+                .assess_value(self.achieved_values[i])
+                .0
+        })
+    }
+
     /// Returns whether this solution is a finite burn solution or not
     pub fn is_finite_burn(&self) -> bool {
         for var in &self.variables {
@@ -154,14 +165,20 @@ impl<const V: usize, const O: usize> TargeterSolution<V, O> {
 
 impl<const V: usize, const O: usize> fmt::Display for TargeterSolution<V, O> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut objmsg = String::from("");
+        let mut objmsg = format!(
+            "\n\t\t{:<16} {:>16} {:>16} {:>12} {:>10}  Met",
+            "Parameter", "Desired", "Achieved", "Error", "Tolerance"
+        );
+        let met = self.objectives_met();
         for (i, obj) in self.achieved_objectives.iter().enumerate() {
             objmsg.push_str(&format!(
-                "\n\t\t{:?} = {:.3} (wanted {:.3} ± {:.1e})",
-                obj.parameter,
-                obj.desired_value + self.achieved_errors[i],
+                "\n\t\t{:<16} {:>16.6} {:>16.6} {:>12.3e} {:>10.1e}  {}",
+                format!("{:?}", obj.parameter),
                 obj.desired_value,
-                obj.tolerance
+                self.achieved_values[i],
+                self.achieved_errors[i],
+                obj.tolerance,
+                if met[i] { "yes" } else { "NO" }
             ));
         }
 
@@ -18,6 +18,7 @@
 
 use snafu::ResultExt;
 
+use crate::cosmic::AstroAlmanacSnafu;
 use crate::dynamics::guidance::LocalFrame;
 use crate::errors::TargetingError;
 use crate::md::objective::Objective;
@@ -206,7 +207,8 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
         }
     }
 
-    /// Create a new Targeter which will apply an impulsive delta-v correction.
+    /// Create a new Targeter whose objectives are evaluated in the provided frame, e.g. a Moon centered frame, instead of the propagation frame.
+    /// The achieved state is transformed into this frame with the almanac before the objectives are computed.
     pub fn in_frame(
         prop: &'a Propagator<SpacecraftDynamics>,
         variables: [Variable; V],
@@ -223,6 +225,12 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
         }
     }
 
+    /// Evaluate the objectives of this targeter in the provided frame instead of the propagation frame, e.g. `Targeter::delta_v(..).with_objective_frame(moon_j2k)`.
+    pub fn with_objective_frame(mut self, objective_frame: Frame) -> Self {
+        self.objective_frame = Some(objective_frame);
+        self
+    }
+
     /// Create a new Targeter which will apply an impulsive delta-v correction on the specified components of the VNC frame.
     pub fn vnc_with_components(
         prop: &'a Propagator<SpacecraftDynamics>,
@@ -273,7 +281,7 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
                 println!("{mnvr}");
                 let mut prop = self.prop.clone();
                 prop.dynamics = prop.dynamics.with_guidance_law(Arc::new(mnvr));
-                prop.with(solution.corrected_state, almanac)
+                prop.with(solution.corrected_state, almanac.clone())
                     .until_epoch_with_traj(solution.achieved_state.epoch())
                     .context(PropSnafu)?
             }
@@ -281,14 +289,24 @@ impl<'a, const V: usize, const O: usize> Targeter<'a, V, O> {
                 // This isn't a finite burn maneuver, let's just apply the correction
                 // Propagate until achievement epoch
                 self.prop
-                    .with(solution.corrected_state, almanac)
+                    .with(solution.corrected_state, almanac.clone())
                     .until_epoch_with_traj(solution.achieved_state.epoch())
                     .context(PropSnafu)?
             }
         };
 
-        // Build the partials
-        let xf_dual = OrbitDual::from(xf.orbit);
+        // Build the partials in the objective frame, if any
+        let xf_dual = match &self.objective_frame {
+            Some(frame) => {
+                let orbit_obj_frame = almanac
+                    .transform_to(xf.orbit, *frame, None)
+                    .context(AstroAlmanacSnafu)
+                    .context(AstroSnafu)?;
+
+                OrbitDual::from(orbit_obj_frame)
+            }
+            None => OrbitDual::from(xf.orbit),
+        };
 
         let mut is_bplane_tgt = false;
         for obj in &self.objectives {
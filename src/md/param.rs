@@ -36,8 +36,12 @@ pub enum StateParameter {
     AoP,
     /// Apoapsis, shortcut for TA == 180.0
     Apoapsis,
+    /// Longitude of the ascending node in the frame of the state (deg), e.g. the geodetic longitude of the equator crossing in a body fixed frame
+    AscendingNodeLongitude,
     /// Radius of apoapsis (km)
     ApoapsisRadius,
+    /// Altitude of apoapsis above the mean equatorial radius (km)
+    ApoapsisAltitude,
     /// B-Plane B⋅R
     BdotR,
     /// B-Plane B⋅T
@@ -62,6 +66,14 @@ pub enum StateParameter {
     Eccentricity,
     /// Specific energy
     Energy,
+    /// Equinoctial h element, e sin(ω + Ω) (no unit)
+    EquinoctialH,
+    /// Equinoctial k element, e cos(ω + Ω) (no unit)
+    EquinoctialK,
+    /// Equinoctial p element, tan(i/2) sin(Ω) (no unit)
+    EquinoctialP,
+    /// Equinoctial q element, tan(i/2) cos(Ω) (no unit)
+    EquinoctialQ,
     /// Flight path angle (deg)
     FlightPathAngle,
     /// fuel mass in kilograms
@@ -90,16 +102,22 @@ pub enum StateParameter {
     Isp,
     /// Mean anomaly (deg)
     MeanAnomaly,
+    /// Mean longitude, M + ω + Ω (deg)
+    MeanLongitude,
     /// Periapsis, shortcut for TA == 0.0
     Periapsis,
     /// Radius of periapse (km)
     PeriapsisRadius,
+    /// Altitude of periapse above the mean equatorial radius (km)
+    PeriapsisAltitude,
     /// Orbital period (s)
     Period,
     /// Right ascension (deg)
     RightAscension,
     /// Right ascension of the ascending node (deg)
     RAAN,
+    /// Time derivative of the norm of the radius vector, r⋅v/|r| (km/s)
+    RangeRate,
     /// Norm of the radius vector
     Rmag,
     /// Semi parameter (km)
@@ -137,10 +155,15 @@ impl StateParameter {
     /// Returns the default event finding precision in the unit of that parameter
     pub fn default_event_precision(&self) -> f64 {
         match self {
-            Self::Eccentricity => 1e-5,
+            Self::Eccentricity
+            | Self::EquinoctialH
+            | Self::EquinoctialK
+            | Self::EquinoctialP
+            | Self::EquinoctialQ => 1e-5,
             // Non anomaly angles
             Self::AoL
             | Self::AoP
+            | Self::AscendingNodeLongitude
             | Self::Declination
             | Self::Latitude
             | Self::Longitude
@@ -155,12 +178,14 @@ impl StateParameter {
             Self::Apoapsis
             | Self::Periapsis
             | Self::MeanAnomaly
+            | Self::MeanLongitude
             | Self::EccentricAnomaly
             | Self::HyperbolicAnomaly
             | Self::TrueAnomaly => 1e-3,
 
             // Distances
-            Self::ApoapsisRadius
+            Self::ApoapsisAltitude
+            | Self::ApoapsisRadius
             | Self::BdotR
             | Self::BdotT
             | Self::Height
@@ -168,6 +193,7 @@ impl StateParameter {
             | Self::HX
             | Self::HY
             | Self::HZ
+            | Self::PeriapsisAltitude
             | Self::PeriapsisRadius
             | Self::Rmag
             | Self::SemiParameter
@@ -178,7 +204,7 @@ impl StateParameter {
             | Self::Z => 1e-3,
 
             // Velocities
-            Self::C3 | Self::VX | Self::VY | Self::VZ | Self::Vmag => 1e-3,
+            Self::C3 | Self::RangeRate | Self::VX | Self::VY | Self::VZ | Self::Vmag => 1e-3,
 
             // Special
             Self::Energy => 1e-3,
@@ -217,6 +243,7 @@ impl StateParameter {
             // Angles
             Self::AoL
             | Self::AoP
+            | Self::AscendingNodeLongitude
             | Self::Declination
             | Self::Latitude
             | Self::Longitude
@@ -229,12 +256,14 @@ impl StateParameter {
             | Self::Apoapsis
             | Self::Periapsis
             | Self::MeanAnomaly
+            | Self::MeanLongitude
             | Self::EccentricAnomaly
             | Self::HyperbolicAnomaly
             | Self::TrueAnomaly => "deg",
 
             // Distances
-            Self::ApoapsisRadius
+            Self::ApoapsisAltitude
+            | Self::ApoapsisRadius
             | Self::BdotR
             | Self::BdotT
             | Self::Height
@@ -242,6 +271,7 @@ impl StateParameter {
             | Self::HX
             | Self::HY
             | Self::HZ
+            | Self::PeriapsisAltitude
             | Self::PeriapsisRadius
             | Self::Rmag
             | Self::SemiParameter
@@ -252,7 +282,7 @@ impl StateParameter {
             | Self::Z => "km",
 
             // Velocities
-            Self::VX | Self::VY | Self::VZ | Self::Vmag => "km/s",
+            Self::RangeRate | Self::VX | Self::VY | Self::VZ | Self::Vmag => "km/s",
 
             Self::C3 | Self::Energy => "km^2/s^2",
 
@@ -287,12 +317,19 @@ impl StateParameter {
         self.to_field_generic(true, more_meta)
     }
 
-    /// Returns the parquet field of this parameter
+    /// Returns the parquet field of this parameter, annotated with its unit and parameter name (e.g. `SMA`).
+    ///
+    /// The frame entries of the additional metadata (`Frame` and `frame`) are only kept for the orbital parameters,
+    /// since the spacecraft parameters (e.g. the fuel mass) do not depend on the frame.
     fn to_field_generic(self, is_sigma: bool, more_meta: Option<Vec<(String, String)>>) -> Field {
         let mut meta = HashMap::new();
         meta.insert("unit".to_string(), self.unit().to_string());
+        meta.insert("parameter".to_string(), format!("{self:?}"));
         if let Some(more_data) = more_meta {
             for (k, v) in more_data {
+                if !self.is_orbital() && k.eq_ignore_ascii_case("frame") {
+                    continue;
+                }
                 meta.insert(k, v);
             }
         }
@@ -326,6 +363,7 @@ impl FromStr for StateParameter {
             "periapsis" => Ok(Self::Periapsis),
             "aol" => Ok(Self::AoL),
             "aop" => Ok(Self::AoP),
+            "asc_node_longitude" => Ok(Self::AscendingNodeLongitude),
             "bltof" => Ok(Self::BLTOF),
             "bdotr" => Ok(Self::BdotR),
             "bdott" => Ok(Self::BdotT),
@@ -335,9 +373,14 @@ impl FromStr for StateParameter {
             "declin" => Ok(Self::Declination),
             "dry_mass" => Ok(Self::DryMass),
             "apoapsis_radius" => Ok(Self::ApoapsisRadius),
+            "apoapsis_altitude" => Ok(Self::ApoapsisAltitude),
             "ea" => Ok(Self::EccentricAnomaly),
             "ecc" => Ok(Self::Eccentricity),
             "energy" => Ok(Self::Energy),
+            "equinoctial_h" => Ok(Self::EquinoctialH),
+            "equinoctial_k" => Ok(Self::EquinoctialK),
+            "equinoctial_p" => Ok(Self::EquinoctialP),
+            "equinoctial_q" => Ok(Self::EquinoctialQ),
             "fpa" => Ok(Self::FlightPathAngle),
             "fuel_mass" => Ok(Self::FuelMass),
             "guidance_mode" | "mode" => Ok(Self::GuidanceMode),
@@ -352,10 +395,13 @@ impl FromStr for StateParameter {
             "inc" => Ok(Self::Inclination),
             "isp" => Ok(Self::Isp),
             "ma" => Ok(Self::MeanAnomaly),
+            "mean_longitude" => Ok(Self::MeanLongitude),
             "periapsis_radius" => Ok(Self::PeriapsisRadius),
+            "periapsis_altitude" => Ok(Self::PeriapsisAltitude),
             "period" => Ok(Self::Period),
             "right_asc" => Ok(Self::RightAscension),
             "raan" => Ok(Self::RAAN),
+            "range_rate" => Ok(Self::RangeRate),
             "rmag" => Ok(Self::Rmag),
             "semi_parameter" => Ok(Self::SemiParameter),
             "semi_minor" => Ok(Self::SemiMinorAxis),
@@ -385,6 +431,7 @@ impl fmt::Display for StateParameter {
             Self::Periapsis => "periapsis",
             Self::AoL => "aol",
             Self::AoP => "aop",
+            Self::AscendingNodeLongitude => "asc_node_longitude",
             Self::BLTOF => "BLToF",
             Self::BdotR => "BdotR",
             Self::BdotT => "BdotT",
@@ -395,9 +442,14 @@ impl fmt::Display for StateParameter {
             Self::DryMass => "dry_mass",
             Self::Epoch => "epoch",
             Self::ApoapsisRadius => "apoapsis_radius",
+            Self::ApoapsisAltitude => "apoapsis_altitude",
             Self::EccentricAnomaly => "ea",
             Self::Eccentricity => "ecc",
             Self::Energy => "energy",
+            Self::EquinoctialH => "equinoctial_h",
+            Self::EquinoctialK => "equinoctial_k",
+            Self::EquinoctialP => "equinoctial_p",
+            Self::EquinoctialQ => "equinoctial_q",
             Self::FlightPathAngle => "fpa",
             Self::FuelMass => "fuel_mass",
             Self::GuidanceMode => "guidance_mode",
@@ -412,10 +464,13 @@ impl fmt::Display for StateParameter {
             Self::Inclination => "inc",
             Self::Isp => "isp",
             Self::MeanAnomaly => "ma",
+            Self::MeanLongitude => "mean_longitude",
             Self::PeriapsisRadius => "periapsis_radius",
+            Self::PeriapsisAltitude => "periapsis_altitude",
             Self::Period => "period",
             Self::RightAscension => "right_asc",
             Self::RAAN => "raan",
+            Self::RangeRate => "range_rate",
             Self::Rmag => "rmag",
             Self::SemiParameter => "semi_parameter",
             Self::SemiMinorAxis => "semi_minor",
@@ -452,6 +507,7 @@ mod ut_state_param {
             StateParameter::Periapsis,
             StateParameter::AoL,
             StateParameter::AoP,
+            StateParameter::AscendingNodeLongitude,
             StateParameter::BdotR,
             StateParameter::BdotT,
             StateParameter::BLTOF,
@@ -461,9 +517,14 @@ mod ut_state_param {
             StateParameter::Declination,
             StateParameter::DryMass,
             StateParameter::ApoapsisRadius,
+            StateParameter::ApoapsisAltitude,
             StateParameter::EccentricAnomaly,
             StateParameter::Eccentricity,
             StateParameter::Energy,
+            StateParameter::EquinoctialH,
+            StateParameter::EquinoctialK,
+            StateParameter::EquinoctialP,
+            StateParameter::EquinoctialQ,
             StateParameter::FlightPathAngle,
             StateParameter::FuelMass,
             StateParameter::GuidanceMode,
@@ -478,10 +539,13 @@ mod ut_state_param {
             StateParameter::Inclination,
             StateParameter::Isp,
             StateParameter::MeanAnomaly,
+            StateParameter::MeanLongitude,
             StateParameter::PeriapsisRadius,
+            StateParameter::PeriapsisAltitude,
             StateParameter::Period,
             StateParameter::RightAscension,
             StateParameter::RAAN,
+            StateParameter::RangeRate,
             StateParameter::Rmag,
             StateParameter::SemiParameter,
             StateParameter::SemiMinorAxis,
@@ -0,0 +1,174 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::Traj;
+use crate::cosmic::Spacecraft;
+use crate::errors::{EventError, EventStateSnafu, EventTrajSnafu, StateError};
+use crate::md::{EventEvaluator, StateParameter};
+use crate::time::{Duration, Unit};
+use crate::utils::between_pm_x;
+use crate::State;
+use anise::almanac::Almanac;
+use snafu::ResultExt;
+use std::fmt;
+use std::sync::Arc;
+
+/// Crossing of a specific anomaly. The anomaly is wrapped around the target, such that the only root is at the target
+/// (the discontinuity half a revolution away is rejected by the event search).
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct AnomalyEvent {
+    anomaly: StateParameter,
+    offset_deg: f64,
+    target_deg: f64,
+}
+
+impl fmt::Display for AnomalyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = {} deg",
+            self.anomaly,
+            (self.target_deg + self.offset_deg).rem_euclid(360.0)
+        )
+    }
+}
+
+impl EventEvaluator<Spacecraft> for AnomalyEvent {
+    fn eval(&self, state: &Spacecraft, _almanac: Arc<Almanac>) -> Result<f64, EventError> {
+        let anomaly_deg = state.value(self.anomaly).context(EventStateSnafu {
+            param: self.anomaly,
+        })?;
+        Ok(between_pm_x(
+            anomaly_deg - self.offset_deg - self.target_deg,
+            180.0,
+        ))
+    }
+
+    fn eval_string(
+        &self,
+        state: &Spacecraft,
+        _almanac: Arc<Almanac>,
+    ) -> Result<String, EventError> {
+        let anomaly_deg = state.value(self.anomaly).context(EventStateSnafu {
+            param: self.anomaly,
+        })?;
+        Ok(format!("{} = {anomaly_deg:.3} deg", self.anomaly))
+    }
+
+    #[allow(clippy::identity_op)]
+    fn epoch_precision(&self) -> Duration {
+        1 * Unit::Millisecond
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.anomaly.default_event_precision()
+    }
+
+    fn unit(&self) -> &'static str {
+        "deg"
+    }
+}
+
+/// Locates the states at equally spaced anomalies between consecutive states of a trajectory, cf. `Traj::every_anomaly`.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct AnomalySampler {
+    anomaly: StateParameter,
+    offset_deg: f64,
+    spacing_deg: f64,
+}
+
+impl AnomalySampler {
+    /// The anchor is either the anomaly in which the samples are equally spaced starting from periapsis (true, mean or eccentric anomaly),
+    /// or the periapsis or apoapsis, in which case the samples are equally spaced in true anomaly starting from that apse.
+    pub(crate) fn new(states_per_rev: usize, anchor: StateParameter) -> Result<Self, EventError> {
+        let (anomaly, offset_deg) = match anchor {
+            StateParameter::TrueAnomaly
+            | StateParameter::MeanAnomaly
+            | StateParameter::EccentricAnomaly => (anchor, 0.0),
+            StateParameter::Periapsis => (StateParameter::TrueAnomaly, 0.0),
+            StateParameter::Apoapsis => (StateParameter::TrueAnomaly, 180.0),
+            _ => {
+                return Err(EventError::EventStateError {
+                    param: anchor,
+                    source: StateError::Unavailable { param: anchor },
+                })
+            }
+        };
+
+        Ok(Self {
+            anomaly,
+            offset_deg,
+            spacing_deg: 360.0 / states_per_rev.max(1) as f64,
+        })
+    }
+
+    /// Anomaly of this state counted from the anchor, in [0; 360) degrees.
+    fn anomaly_deg(&self, state: &Spacecraft) -> Result<f64, EventError> {
+        Ok((state.value(self.anomaly).context(EventStateSnafu {
+            param: self.anomaly,
+        })? - self.offset_deg)
+            .rem_euclid(360.0))
+    }
+
+    /// Returns whether this state is at one of the sampled anomalies, to within the event precision.
+    pub(crate) fn is_sample(&self, state: &Spacecraft) -> Result<bool, EventError> {
+        let anomaly_deg = self.anomaly_deg(state)?;
+        let from_sample_deg = between_pm_x(anomaly_deg, self.spacing_deg / 2.0);
+        Ok(from_sample_deg.abs() <= self.anomaly.default_event_precision())
+    }
+
+    /// Returns the samples crossed after the previous state and up to the next state (included), both of which must be states of the trajectory.
+    ///
+    /// The anomaly is assumed to increase between both states by less than one revolution. The interval is split until the anomaly increases
+    /// by less than a quarter revolution, such that each sample is bracketed without ambiguity.
+    pub(crate) fn samples_between(
+        &self,
+        traj: &Traj<Spacecraft>,
+        prev: &Spacecraft,
+        next: &Spacecraft,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<Spacecraft>, EventError> {
+        let prev_deg = self.anomaly_deg(prev)?;
+        let delta_deg = (self.anomaly_deg(next)? - prev_deg).rem_euclid(360.0);
+
+        let span = next.epoch() - prev.epoch();
+        if delta_deg >= 90.0 && span > 1 * Unit::Second {
+            let mid = traj.at(prev.epoch() + span / 2).context(EventTrajSnafu)?;
+            let mut samples = self.samples_between(traj, prev, &mid, almanac.clone())?;
+            samples.extend(self.samples_between(traj, &mid, next, almanac)?);
+            return Ok(samples);
+        }
+
+        let mut samples = Vec::new();
+        let mut target_deg = ((prev_deg / self.spacing_deg).floor() + 1.0) * self.spacing_deg;
+        while target_deg - prev_deg <= delta_deg {
+            let event = AnomalyEvent {
+                anomaly: self.anomaly,
+                offset_deg: self.offset_deg,
+                target_deg: target_deg.rem_euclid(360.0),
+            };
+            samples.push(
+                traj.find_bracketed(prev.epoch(), next.epoch(), &event, almanac.clone())?
+                    .state,
+            );
+            target_deg += self.spacing_deg;
+        }
+
+        Ok(samples)
+    }
+}
@@ -0,0 +1,238 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::Frame;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::{InterpMethod, Traj};
+use crate::cosmic::{DragConfig, GuidanceMode, Spacecraft, SrpConfig};
+use crate::dynamics::guidance::{Thruster, ThrusterLimits};
+use crate::io::{BincodeSnafu, InputOutputError, StdIOSnafu};
+use crate::time::{Duration, Epoch};
+use crate::Orbit;
+
+/// Magic bytes at the start of a binary trajectory file, followed by the version of the format.
+const BIN_MAGIC: &[u8; 6] = b"NYXTRJ";
+const BIN_VERSION: u8 = 1;
+
+/// Spacecraft state of a binary trajectory. The STM is not stored.
+#[derive(Serialize, Deserialize)]
+struct BinState {
+    /// TAI epoch, as the centuries and nanoseconds of its duration past the reference epoch of hifitime
+    epoch_tai: (i16, u64),
+    /// Index of the frame in the frames of the trajectory
+    frame: u16,
+    /// Position and velocity, in km and km/s
+    cartesian: [f64; 6],
+    dry_mass_kg: f64,
+    fuel_mass_kg: f64,
+    srp: SrpConfig,
+    drag: DragConfig,
+    thruster: Option<Thruster>,
+    thruster_limits: Option<ThrusterLimits>,
+    mode: GuidanceMode,
+}
+
+/// Serializable representation of a spacecraft trajectory, cf. `Traj::save_bin`.
+#[derive(Serialize, Deserialize)]
+struct BinTraj {
+    name: Option<String>,
+    /// Frames of the states, with their gravitational parameter and shape such that loading requires no Almanac
+    frames: Vec<Frame>,
+    states: Vec<BinState>,
+    gaps: Vec<((i16, u64), (i16, u64))>,
+    annotations: Vec<((i16, u64), String)>,
+    interpolation: InterpMethod,
+}
+
+fn epoch_to_parts(epoch: Epoch) -> (i16, u64) {
+    epoch.to_tai_duration().to_parts()
+}
+
+fn epoch_from_parts((centuries, nanoseconds): (i16, u64)) -> Epoch {
+    Epoch::from_tai_duration(Duration::from_parts(centuries, nanoseconds))
+}
+
+impl Traj<Spacecraft> {
+    /// Saves this trajectory in a compact binary file, for fast caching of intermediate trajectories.
+    ///
+    /// The states are stored with their epoch in TAI, Cartesian state, frame, masses, and spacecraft configuration, but
+    /// without their STM, using [bincode](https://docs.rs/bincode). This format is specific to Nyx and may change between
+    /// versions: use Parquet or CCSDS OEM to exchange trajectories.
+    pub fn save_bin<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, InputOutputError> {
+        let path_buf = path.as_ref().to_path_buf();
+
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut states = Vec::with_capacity(self.states.len());
+        for state in &self.states {
+            let frame = state.orbit.frame;
+            let frame_idx = match frames.iter().position(|known| *known == frame) {
+                Some(idx) => idx,
+                None => {
+                    frames.push(frame);
+                    frames.len() - 1
+                }
+            };
+
+            let cartesian = state.orbit.to_cartesian_pos_vel();
+
+            states.push(BinState {
+                epoch_tai: epoch_to_parts(state.orbit.epoch),
+                frame: frame_idx as u16,
+                cartesian: [
+                    cartesian[0],
+                    cartesian[1],
+                    cartesian[2],
+                    cartesian[3],
+                    cartesian[4],
+                    cartesian[5],
+                ],
+                dry_mass_kg: state.dry_mass_kg,
+                fuel_mass_kg: state.fuel_mass_kg,
+                srp: state.srp,
+                drag: state.drag,
+                thruster: state.thruster,
+                thruster_limits: state.thruster_limits,
+                mode: state.mode,
+            });
+        }
+
+        let bin_traj = BinTraj {
+            name: self.name.clone(),
+            frames,
+            states,
+            gaps: self
+                .gaps
+                .iter()
+                .map(|(start, end)| (epoch_to_parts(*start), epoch_to_parts(*end)))
+                .collect(),
+            annotations: self
+                .annotations
+                .iter()
+                .map(|(epoch, note)| (epoch_to_parts(*epoch), note.clone()))
+                .collect(),
+            interpolation: self.interpolation,
+        };
+
+        let file = File::create(&path_buf).context(StdIOSnafu {
+            action: "creating binary trajectory file",
+        })?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(BIN_MAGIC)
+            .and_then(|_| writer.write_all(&[BIN_VERSION]))
+            .context(StdIOSnafu {
+                action: "writing binary trajectory header",
+            })?;
+
+        bincode::serialize_into(&mut writer, &bin_traj).context(BincodeSnafu {
+            action: "serializing binary trajectory",
+        })?;
+
+        writer.flush().context(StdIOSnafu {
+            action: "flushing binary trajectory file",
+        })?;
+
+        info!(
+            "Binary trajectory with {} states written to {}",
+            self.states.len(),
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+
+    /// Loads a trajectory saved with `save_bin`.
+    ///
+    /// The frames are those of the saved states, with their gravitational parameter and shape. The trajectory is not
+    /// interpolated through an inertial frame, even if the saved one was, cf. `with_inertial_interpolation`.
+    pub fn load_bin<P: AsRef<Path>>(path: P) -> Result<Self, InputOutputError> {
+        let file = File::open(path.as_ref()).context(StdIOSnafu {
+            action: "opening binary trajectory file",
+        })?;
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0_u8; 7];
+        reader.read_exact(&mut header).context(StdIOSnafu {
+            action: "reading binary trajectory header",
+        })?;
+        if &header[..6] != BIN_MAGIC || header[6] != BIN_VERSION {
+            return Err(InputOutputError::Inconsistency {
+                msg: format!(
+                    "{} is not a binary trajectory of version {BIN_VERSION}",
+                    path.as_ref().display()
+                ),
+            });
+        }
+
+        let bin_traj: BinTraj = bincode::deserialize_from(reader).context(BincodeSnafu {
+            action: "deserializing binary trajectory",
+        })?;
+
+        let frames = bin_traj.frames;
+
+        let mut traj = Traj::new();
+        traj.name = bin_traj.name;
+        traj.interpolation = bin_traj.interpolation;
+        traj.states.reserve(bin_traj.states.len());
+        for state in bin_traj.states {
+            let frame = *frames.get(state.frame as usize).ok_or_else(|| {
+                InputOutputError::Inconsistency {
+                    msg: format!("unknown frame index {} in binary trajectory", state.frame),
+                }
+            })?;
+            let [x, y, z, vx, vy, vz] = state.cartesian;
+
+            traj.states.push(Spacecraft {
+                orbit: Orbit::new(
+                    x,
+                    y,
+                    z,
+                    vx,
+                    vy,
+                    vz,
+                    epoch_from_parts(state.epoch_tai),
+                    frame,
+                ),
+                dry_mass_kg: state.dry_mass_kg,
+                fuel_mass_kg: state.fuel_mass_kg,
+                srp: state.srp,
+                drag: state.drag,
+                thruster: state.thruster,
+                thruster_limits: state.thruster_limits,
+                mode: state.mode,
+                stm: None,
+            });
+        }
+        traj.gaps = bin_traj
+            .gaps
+            .into_iter()
+            .map(|(start, end)| (epoch_from_parts(start), epoch_from_parts(end)))
+            .collect();
+        traj.annotations = bin_traj
+            .annotations
+            .into_iter()
+            .map(|(epoch, note)| (epoch_from_parts(epoch), note))
+            .collect();
+
+        Ok(traj)
+    }
+}
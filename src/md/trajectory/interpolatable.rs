@@ -16,7 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use anise::math::interpolation::{hermite_eval, InterpolationError};
+use anise::math::interpolation::{hermite_eval, lagrange_eval, InterpolationError};
 
 pub(crate) const INTERPOLATION_SAMPLES: usize = 13;
 
@@ -28,6 +28,18 @@ use crate::time::Epoch;
 use crate::{Orbit, Spacecraft, State};
 
 use enum_iterator::all;
+use serde::{Deserialize, Serialize};
+
+/// Interpolation method of the states of a trajectory, cf. `Traj::set_interpolation`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InterpMethod {
+    /// Hermite interpolation of the position using the velocity as its derivative, and of the velocity alone
+    #[default]
+    Hermite,
+    /// Lagrange interpolation of each component of the position and of the velocity from their values only,
+    /// e.g. for states whose velocity is not the exact derivative of the position
+    Lagrange,
+}
 
 /// States that can be interpolated should implement this trait.
 pub trait Interpolatable: State
@@ -39,6 +51,26 @@ where
     /// Interpolates a new state at the provided epochs given a slice of states.
     fn interpolate(self, epoch: Epoch, states: &[Self]) -> Result<Self, InterpolationError>;
 
+    /// Interpolates a new state at the provided epoch given a slice of states, using the values of the states only (not their derivatives).
+    fn interpolate_lagrange(
+        self,
+        epoch: Epoch,
+        states: &[Self],
+    ) -> Result<Self, InterpolationError>;
+
+    /// Interpolates a new state at the provided epoch with the provided method.
+    fn interpolate_with(
+        self,
+        epoch: Epoch,
+        states: &[Self],
+        method: InterpMethod,
+    ) -> Result<Self, InterpolationError> {
+        match method {
+            InterpMethod::Hermite => self.interpolate(epoch, states),
+            InterpMethod::Lagrange => self.interpolate_lagrange(epoch, states),
+        }
+    }
+
     /// Returns the frame of this state
     fn frame(&self) -> Frame;
 
@@ -94,13 +126,47 @@ impl Interpolatable for Spacecraft {
             self.orbit.frame,
         );
 
-        // Fuel is linearly interpolated -- should really be a Lagrange interpolation here
-        let first = states.first().unwrap();
-        let last = states.last().unwrap();
-        let fuel_kg_dt =
-            (last.fuel_mass_kg - first.fuel_mass_kg) / (last.epoch() - first.epoch()).to_seconds();
+        self.interpolate_fuel(epoch, states);
 
-        self.fuel_mass_kg += fuel_kg_dt * (epoch - first.epoch()).to_seconds();
+        Ok(self)
+    }
+
+    fn interpolate_lagrange(
+        mut self,
+        epoch: Epoch,
+        states: &[Self],
+    ) -> Result<Self, InterpolationError> {
+        let mut epochs_tdb = [0.0; INTERPOLATION_SAMPLES];
+        let mut components = [[0.0; INTERPOLATION_SAMPLES]; 6];
+
+        for (cno, state) in states.iter().enumerate() {
+            for i in 0..3 {
+                components[i][cno] = state.orbit.radius_km[i];
+                components[i + 3][cno] = state.orbit.velocity_km_s[i];
+            }
+            epochs_tdb[cno] = state.epoch().to_et_seconds();
+        }
+
+        let n = states.len();
+
+        let mut pos_vel = [0.0; 6];
+        for (i, component) in components.iter().enumerate() {
+            (pos_vel[i], _) =
+                lagrange_eval(&epochs_tdb[..n], &component[..n], epoch.to_et_seconds())?;
+        }
+
+        self.orbit = Orbit::new(
+            pos_vel[0],
+            pos_vel[1],
+            pos_vel[2],
+            pos_vel[3],
+            pos_vel[4],
+            pos_vel[5],
+            epoch,
+            self.orbit.frame,
+        );
+
+        self.interpolate_fuel(epoch, states);
 
         Ok(self)
     }
@@ -131,6 +197,7 @@ impl Interpolatable for Spacecraft {
                             | StateParameter::Height
                             | StateParameter::Latitude
                             | StateParameter::Longitude
+                            | StateParameter::AscendingNodeLongitude
                     )
             })
             .collect::<Vec<StateParameter>>();
@@ -154,3 +221,15 @@ impl Interpolatable for Spacecraft {
         .concat()
     }
 }
+
+impl Spacecraft {
+    /// Linearly interpolates the fuel mass between the first and last states -- should really be a Lagrange interpolation here
+    fn interpolate_fuel(&mut self, epoch: Epoch, states: &[Self]) {
+        let first = states.first().unwrap();
+        let last = states.last().unwrap();
+        let fuel_kg_dt =
+            (last.fuel_mass_kg - first.fuel_mass_kg) / (last.epoch() - first.epoch()).to_seconds();
+
+        self.fuel_mass_kg += fuel_kg_dt * (epoch - first.epoch()).to_seconds();
+    }
+}
@@ -19,14 +19,19 @@
 use anise::math::interpolation::InterpolationError;
 use snafu::prelude::*;
 
+mod anomaly;
+mod binary;
 mod interpolatable;
+mod node_drift;
 mod sc_traj;
 mod traj;
 mod traj_it;
 
-pub use interpolatable::Interpolatable;
+pub(crate) use anomaly::AnomalySampler;
 pub(crate) use interpolatable::INTERPOLATION_SAMPLES;
-pub use traj::Traj;
+pub use interpolatable::{InterpMethod, Interpolatable};
+pub use node_drift::{NodeCrossing, NodeDriftReport};
+pub use traj::{GapPolicy, InertialInterpolation, ParamStats, Traj};
 
 pub use crate::io::ExportCfg;
 
@@ -53,4 +58,14 @@ pub enum TrajError {
     },
     #[snafu(display("Interpolation failed: {source}"))]
     Interpolation { source: InterpolationError },
+    #[snafu(display("Interpolation through the inertial frame failed at {epoch}: {msg}"))]
+    InertialInterpolation { epoch: Epoch, msg: String },
+    #[snafu(display(
+        "No interpolation data at {epoch}: within the trajectory gap from {start} to {end}"
+    ))]
+    WithinGap {
+        epoch: Epoch,
+        start: Epoch,
+        end: Epoch,
+    },
 }
@@ -0,0 +1,233 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::{Almanac, Frame};
+use arrow::array::{Array, Float64Builder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+
+use super::{ExportCfg, Traj};
+use crate::cosmic::Spacecraft;
+use crate::errors::{EventError, NyxError};
+use crate::io::watermark::pq_writer;
+use crate::md::prelude::StateParameter;
+use crate::md::Event;
+use crate::polyfit::Polynomial;
+use crate::time::{Epoch, Unit};
+use crate::utils::between_pm_180;
+use crate::State;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Ascending node crossing of the equator of a body fixed frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NodeCrossing {
+    pub epoch: Epoch,
+    /// Geodetic longitude of the crossing, between -180 and +180 degrees
+    pub longitude_deg: f64,
+}
+
+/// Longitude drift of the ascending node from one revolution to the next, as used for repeat ground track maintenance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeDriftReport {
+    /// Body fixed frame in which the crossings were computed
+    pub frame: Frame,
+    /// Ascending node crossings, in chronological order
+    pub crossings: Vec<NodeCrossing>,
+    /// Change in the node longitude between consecutive crossings, between -180 and +180 degrees
+    pub drift_deg: Vec<f64>,
+    /// Linear fit of the per-revolution drift as a function of the revolution number since the first crossing
+    pub trend: Polynomial<2>,
+}
+
+impl NodeDriftReport {
+    /// Builds the report from the ascending node crossings, which must be in chronological order.
+    ///
+    /// At least three crossings are needed to fit the trend of the drift.
+    pub fn from_crossings(frame: Frame, crossings: Vec<NodeCrossing>) -> Result<Self, NyxError> {
+        // The drift is wrapped such that a crossing of the anti-meridian does not cause a 360 degree jump.
+        let drift_deg = crossings
+            .windows(2)
+            .map(|pair| between_pm_180(pair[1].longitude_deg - pair[0].longitude_deg))
+            .collect::<Vec<f64>>();
+
+        let revs = (0..drift_deg.len())
+            .map(|rev| rev as f64)
+            .collect::<Vec<f64>>();
+        let trend = Polynomial::fit(&revs, &drift_deg)?;
+
+        Ok(Self {
+            frame,
+            crossings,
+            drift_deg,
+            trend,
+        })
+    }
+
+    /// Mean drift of the node longitude per revolution, in degrees
+    pub fn mean_drift_deg(&self) -> f64 {
+        self.drift_deg.iter().sum::<f64>() / (self.drift_deg.len() as f64)
+    }
+
+    /// Rate of change of the per-revolution drift from the fitted trend, in degrees per revolution per revolution
+    pub fn drift_rate_deg(&self) -> f64 {
+        self.trend.coefficients[1]
+    }
+
+    /// Node longitudes accumulated from the first crossing without wrapping, in degrees
+    pub fn unwrapped_longitudes_deg(&self) -> Vec<f64> {
+        let mut longitude_deg = self.crossings[0].longitude_deg;
+        let mut unwrapped = vec![longitude_deg];
+        for drift_deg in &self.drift_deg {
+            longitude_deg += drift_deg;
+            unwrapped.push(longitude_deg);
+        }
+        unwrapped
+    }
+
+    /// Exports this report to a parquet file with one row per crossing, the drift to the next crossing is null on the last row.
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = cfg.epoch_fields();
+        for (name, nullable) in [
+            (format!("{}", StateParameter::AscendingNodeLongitude), false),
+            ("Unwrapped node longitude (deg)".to_string(), false),
+            ("Drift to next crossing (deg)".to_string(), true),
+        ] {
+            let mut meta = HashMap::new();
+            meta.insert("unit".to_string(), "deg".to_string());
+            meta.insert("Frame".to_string(), self.frame.to_string());
+            hdrs.push(Field::new(name, DataType::Float64, nullable).with_metadata(meta));
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        record.extend(cfg.epoch_columns(self.crossings.iter().map(|crossing| crossing.epoch)));
+
+        let mut longitudes = Float64Builder::new();
+        let mut unwrapped = Float64Builder::new();
+        let mut drifts = Float64Builder::new();
+        for (ii, lon_deg) in self.unwrapped_longitudes_deg().iter().enumerate() {
+            longitudes.append_value(self.crossings[ii].longitude_deg);
+            unwrapped.append_value(*lon_deg);
+            drifts.append_option(self.drift_deg.get(ii).copied());
+        }
+        record.push(Arc::new(longitudes.finish()));
+        record.push(Arc::new(unwrapped.finish()));
+        record.push(Arc::new(drifts.finish()));
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "Node drift report".to_string());
+        metadata.insert("Drift trend".to_string(), format!("{}", self.trend));
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata));
+
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props).unwrap();
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!("Node drift report written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for NodeDriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Node drift in {} over {} crossings: mean drift {:.6} deg/rev, drift rate {:.3e} deg/rev^2",
+            self.frame,
+            self.crossings.len(),
+            self.mean_drift_deg(),
+            self.drift_rate_deg()
+        )?;
+        for (ii, crossing) in self.crossings.iter().enumerate() {
+            write!(f, "\t{}\t{:.6} deg", crossing.epoch, crossing.longitude_deg)?;
+            if let Some(drift_deg) = self.drift_deg.get(ii) {
+                write!(f, "\t(drift {drift_deg:.6} deg)")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Traj<Spacecraft> {
+    /// Computes the drift of the longitude of the ascending node from one revolution to the next in the provided body fixed frame.
+    ///
+    /// The trajectory is converted into the body fixed frame and the ascending node crossings are searched with `find`,
+    /// so the same heuristic limitations apply: the trajectory should not include more than about fifty revolutions.
+    pub fn node_drift(
+        &self,
+        body_fixed_frame: Frame,
+        almanac: Arc<Almanac>,
+    ) -> Result<NodeDriftReport, Box<dyn Error>> {
+        let traj = self.to_frame(body_fixed_frame, almanac.clone())?;
+
+        let equator = Event::specific(
+            StateParameter::Z,
+            0.0,
+            StateParameter::Z.default_event_precision(),
+            Unit::Millisecond,
+        );
+
+        let crossings = match traj.find(&equator, almanac) {
+            Ok(crossings) => crossings,
+            Err(EventError::NotFound { .. }) => Vec::new(),
+            Err(e) => return Err(Box::new(e)),
+        };
+
+        let ascending = crossings
+            .iter()
+            // Only keep the northbound crossings
+            .filter(|crossing| crossing.state.orbit.velocity_km_s.z > 0.0)
+            .map(|crossing| {
+                Ok(NodeCrossing {
+                    epoch: crossing.state.epoch(),
+                    longitude_deg: crossing
+                        .state
+                        .value(StateParameter::AscendingNodeLongitude)?,
+                })
+            })
+            .collect::<Result<Vec<NodeCrossing>, Box<dyn Error>>>()?;
+
+        Ok(NodeDriftReport::from_crossings(
+            body_fixed_frame,
+            ascending,
+        )?)
+    }
+}
@@ -17,20 +17,28 @@
 */
 
 use anise::astro::Aberration;
+use anise::constants::frames::EARTH_J2000;
 use anise::constants::orientations::J2000;
 use anise::errors::AlmanacError;
 use anise::prelude::{Almanac, Frame, Orbit};
-use hifitime::TimeSeries;
+use hifitime::{TimeScale, TimeSeries};
+use serde_json::json;
 use snafu::ResultExt;
 
+use super::anomaly::AnomalySampler;
 use super::TrajError;
-use super::{ExportCfg, Traj};
+use super::{ExportCfg, InertialInterpolation, InterpMethod, Traj};
 use crate::cosmic::Spacecraft;
-use crate::errors::{FromAlmanacSnafu, NyxError};
+use crate::dynamics::{Dynamics, SpacecraftDynamics};
+use crate::errors::{
+    EventError, EventStateSnafu, FromAlmanacSnafu, FromPhysicsSnafu, NyxError, StateError,
+};
 use crate::io::watermark::prj_name_ver;
+use crate::linalg::Matrix6;
 use crate::md::prelude::StateParameter;
-use crate::md::EventEvaluator;
-use crate::time::{Duration, Epoch, Format, Formatter, TimeUnits};
+use crate::md::{Event, EventEdge, EventEvaluator, EventSearchCfg};
+use crate::od::GroundStation;
+use crate::time::{Duration, Epoch, Format, Formatter, TimeUnits, Unit};
 use crate::State;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
@@ -76,9 +84,21 @@ impl Traj<Spacecraft> {
             states.push(sc_template.with_orbit(orbit));
         }
 
-        Ok(Self { name, states })
+        Ok(Self {
+            name,
+            states,
+            inertial_interp: None,
+            gaps: Vec::new(),
+            annotations: Vec::new(),
+            interpolation: InterpMethod::default(),
+            max_states: None,
+        })
     }
     /// Allows converting the source trajectory into the (almost) equivalent trajectory in another frame
+    ///
+    /// If the states have an STM, it is assumed to map deviations from the first state of the trajectory, and it is rotated
+    /// into the new frame as `Φ' = R(t) Φ R(t_0)^-1` where `R` is the 6x6 rotation of the orbital state into the new frame.
+    /// Hence, a covariance mapped with the STMs of the new trajectory is that of the original trajectory rotated into the new frame (cf. `orbit_covariances`).
     #[allow(clippy::map_clone)]
     pub fn to_frame(&self, new_frame: Frame, almanac: Arc<Almanac>) -> Result<Self, NyxError> {
         if self.states.is_empty() {
@@ -92,6 +112,8 @@ impl Traj<Spacecraft> {
         #[cfg(not(target_arch = "wasm32"))]
         let start_instant = Instant::now();
         let mut traj = Self::new();
+        // Inverse rotation of the orbital state at the start of the trajectory, only needed to rotate the STM.
+        let mut dcm_t0_inv: Option<Matrix6<f64>> = None;
         for state in &self.states {
             let new_orbit =
                 almanac
@@ -99,9 +121,54 @@ impl Traj<Spacecraft> {
                     .context(FromAlmanacSnafu {
                         action: "transforming trajectory into new frame",
                     })?;
-            traj.states.push(state.with_orbit(new_orbit));
+            let mut new_state = state.with_orbit(new_orbit);
+
+            if let Some(stm) = state.stm {
+                let dcm_t0_inv = match dcm_t0_inv {
+                    Some(dcm) => dcm,
+                    None => {
+                        let dcm = almanac
+                            .rotate(self.first().orbit.frame, new_frame, self.first().epoch())
+                            .context(FromAlmanacSnafu {
+                                action: "rotating initial STM into new frame",
+                            })?
+                            .transpose()
+                            .state_dcm();
+                        dcm_t0_inv = Some(dcm);
+                        dcm
+                    }
+                };
+                let dcm_t = almanac
+                    .rotate(state.orbit.frame, new_frame, state.epoch())
+                    .context(FromAlmanacSnafu {
+                        action: "rotating STM into new frame",
+                    })?
+                    .state_dcm();
+
+                let mut new_stm = stm;
+                new_stm
+                    .fixed_view_mut::<6, 6>(0, 0)
+                    .copy_from(&(dcm_t * stm.fixed_view::<6, 6>(0, 0) * dcm_t0_inv));
+                new_stm
+                    .fixed_view_mut::<6, 3>(0, 6)
+                    .copy_from(&(dcm_t * stm.fixed_view::<6, 3>(0, 6)));
+                new_stm
+                    .fixed_view_mut::<3, 6>(6, 0)
+                    .copy_from(&(stm.fixed_view::<3, 6>(6, 0) * dcm_t0_inv));
+                new_state.stm = Some(new_stm);
+            }
+
+            traj.states.push(new_state);
         }
         traj.finalize();
+        traj.gaps = self.gaps.clone();
+        traj.annotations = self.annotations.clone();
+        traj.interpolation = self.interpolation;
+
+        // Interpolating in a rotating frame is inaccurate, so the new trajectory is interpolated in the J2000 orientation of the new frame.
+        if InertialInterpolation::is_rotating(new_frame) {
+            traj = traj.with_inertial_interpolation(new_frame.with_orient(J2000), almanac);
+        }
 
         #[cfg(not(target_arch = "wasm32"))]
         info!(
@@ -121,6 +188,218 @@ impl Traj<Spacecraft> {
         Ok(traj)
     }
 
+    /// Maps the covariance of the orbital state at the start of this trajectory to each state of the trajectory using their STM, i.e. `P(t) = Φ(t, t_0) P_0 Φ(t, t_0)^T`.
+    ///
+    /// The states must have an STM computed from the start of the trajectory, e.g. by propagating a state with its STM enabled.
+    pub fn orbit_covariances(
+        &self,
+        init_covar: &Matrix6<f64>,
+    ) -> Result<Vec<(Epoch, Matrix6<f64>)>, NyxError> {
+        self.states
+            .iter()
+            .map(|state| match state.stm {
+                Some(stm) => {
+                    let orbit_stm = stm.fixed_view::<6, 6>(0, 0);
+                    Ok((
+                        state.epoch(),
+                        orbit_stm * init_covar * orbit_stm.transpose(),
+                    ))
+                }
+                None => Err(NyxError::Trajectory {
+                    source: TrajError::CreationError {
+                        msg: format!("no STM in state at {}", state.epoch()),
+                    },
+                }),
+            })
+            .collect()
+    }
+
+    /// Returns the number of revolutions flown in this trajectory, i.e. the number of passages at the anchor, either the periapsis
+    /// (`StateParameter::Periapsis`) or the ascending node (`StateParameter::AoL`), plus the fractions of the first and last orbits.
+    ///
+    /// The passages are found with the event finder to within the provided epoch precision, so the trajectory may be sampled sparsely,
+    /// and open orbits are supported. The fraction of the first orbit is the anomaly (true anomaly or argument of latitude) left to sweep
+    /// from the first state to the first passage, and that of the last orbit is the anomaly swept from the last passage to the last state,
+    /// both divided by 360 degrees. For example, a trajectory spanning two and a half orbital periods and starting at periapsis returns 2.5.
+    /// The first and last states count as passages if their anomaly is within the default event precision of the anchor.
+    ///
+    /// # Limitations
+    /// For closed orbits, the trajectory is searched in chunks of an eighth of the osculating period of its first state, so all passages
+    /// are found only if the period does not change much over the trajectory and, for the ascending node, if the ascending and descending
+    /// nodes are more than an eighth of a period apart.
+    pub fn revolutions(
+        &self,
+        anchor: StateParameter,
+        epoch_precision: Unit,
+        almanac: Arc<Almanac>,
+    ) -> Result<f64, EventError> {
+        let (event, anomaly) = match anchor {
+            StateParameter::Periapsis => (Event::periapsis(), StateParameter::TrueAnomaly),
+            // The ascending node is the northward crossing of the equator of the frame
+            StateParameter::AoL => (Event::new(StateParameter::Z, 0.0), StateParameter::AoL),
+            _ => {
+                return Err(EventError::EventStateError {
+                    param: anchor,
+                    source: StateError::Unavailable { param: anchor },
+                })
+            }
+        };
+        let event = Event {
+            epoch_precision,
+            ..event
+        };
+        let anomaly_deg = |state: &Spacecraft| -> Result<f64, EventError> {
+            Ok(state
+                .value(anomaly)
+                .context(EventStateSnafu { param: anomaly })?
+                .rem_euclid(360.0))
+        };
+        let at_anchor = |anomaly_deg: f64| -> bool {
+            anomaly_deg.min(360.0 - anomaly_deg) <= anomaly.default_event_precision()
+        };
+
+        let cfg = match self.first().orbit.period() {
+            Ok(period) => EventSearchCfg::builder().chunk(period / 8).build(),
+            // Open orbits pass at most once at the anchor
+            Err(_) => EventSearchCfg::default(),
+        };
+        let mut passages = match self.find_with_cfg(&event, &cfg, almanac) {
+            Ok(events) => events
+                .iter()
+                .filter(|details| details.edge == EventEdge::Rising)
+                .map(|details| details.state.epoch())
+                .collect::<Vec<Epoch>>(),
+            Err(EventError::NotFound { .. }) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let tolerance = event.epoch_precision();
+        let (first, last) = (self.first().epoch(), self.last().epoch());
+        let (first_deg, last_deg) = (anomaly_deg(self.first())?, anomaly_deg(self.last())?);
+
+        let first_fraction = if at_anchor(first_deg) {
+            if passages
+                .first()
+                .map_or(true, |epoch| *epoch - first > tolerance)
+            {
+                passages.insert(0, first);
+            }
+            0.0
+        } else {
+            (360.0 - first_deg) / 360.0
+        };
+        let last_fraction = if at_anchor(last_deg) {
+            if passages
+                .last()
+                .map_or(true, |epoch| last - *epoch > tolerance)
+            {
+                passages.push(last);
+            }
+            0.0
+        } else {
+            last_deg / 360.0
+        };
+
+        if passages.is_empty() {
+            Ok((last_deg - first_deg).rem_euclid(360.0) / 360.0)
+        } else {
+            Ok((passages.len() - 1) as f64 + first_fraction + last_fraction)
+        }
+    }
+
+    /// Samples this trajectory at equally spaced anomalies instead of equally spaced epochs, i.e. `states_per_rev` states per revolution.
+    ///
+    /// The anchor sets where the samples are placed:
+    /// + `TrueAnomaly`, `MeanAnomaly` or `EccentricAnomaly`: equally spaced in that anomaly, starting at periapsis;
+    /// + `Periapsis` or `Apoapsis`: equally spaced in true anomaly, starting at that apse. For example, one state per revolution anchored at the
+    ///   apoapsis returns every apoapsis passage.
+    ///
+    /// Each sample is located with the event finder (to within a millisecond and the default event precision of the anomaly), between consecutive
+    /// states of the trajectory, so the trajectory must be sampled densely enough that the anomaly increases by less than one revolution between
+    /// consecutive states, which is always the case for propagated trajectories. Use `PropInstance::for_duration_every_anomaly` to sample a
+    /// propagation without storing the whole trajectory.
+    pub fn every_anomaly(
+        &self,
+        states_per_rev: usize,
+        anchor: StateParameter,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<Spacecraft>, EventError> {
+        let sampler = AnomalySampler::new(states_per_rev, anchor)?;
+
+        let mut samples = Vec::new();
+        if sampler.is_sample(self.first())? {
+            samples.push(*self.first());
+        }
+        for pair in self.states.windows(2) {
+            // Nothing is sampled within the gaps of the trajectory
+            if self.gaps.contains(&(pair[0].epoch(), pair[1].epoch())) {
+                continue;
+            }
+            samples.extend(sampler.samples_between(self, &pair[0], &pair[1], almanac.clone())?);
+        }
+
+        Ok(samples)
+    }
+
+    /// Returns the accumulated delta-v in km/s, computed from the fuel mass changes between consecutive states and the
+    /// Isp of the spacecraft thruster (Tsiolkovsky rocket equation).
+    ///
+    /// # Limitations
+    /// This does not account for non-gravitational accelerations which do not consume fuel (e.g. drag or SRP): use
+    /// `integrated_dv` to integrate the non-gravitational accelerations of the dynamics instead.
+    pub fn accumulated_dv(&self) -> Result<f64, Box<dyn Error>> {
+        let mut dv_km_s = 0.0;
+        for pair in self.states.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            if next.fuel_mass_kg < prev.fuel_mass_kg {
+                let thruster = prev.thruster.ok_or(StateError::NoThrusterAvail)?;
+                let ve_km_s = thruster.exhaust_velocity_m_s() * 1e-3;
+                dv_km_s += ve_km_s * (prev.mass_kg() / next.mass_kg()).ln();
+            }
+        }
+
+        Ok(dv_km_s)
+    }
+
+    /// Returns the delta-v in km/s imparted by the non-gravitational accelerations of the provided dynamics, i.e. its force models
+    /// (e.g. drag or SRP) and its guidance law, integrated over the trajectory with the trapezoidal rule at the provided cadence.
+    ///
+    /// The acceleration is the norm of the difference between the acceleration of the dynamics and that of its orbital dynamics,
+    /// evaluated at the states interpolated every `cadence` and at the last state. The cadence must be short compared to the
+    /// variations of the accelerations, e.g. to the duration of the burns.
+    pub fn integrated_dv(
+        &self,
+        dynamics: &SpacecraftDynamics,
+        cadence: Duration,
+        almanac: Arc<Almanac>,
+    ) -> Result<f64, Box<dyn Error>> {
+        let accel_km_s2 = |state: &Spacecraft| -> Result<f64, Box<dyn Error>> {
+            let mut state = *state;
+            // Evaluate the accelerations without the STM
+            state.stm = None;
+            let total = dynamics.eom(0.0, &state.to_vector(), &state, almanac.clone())?;
+            let gravity = dynamics.orbital_dyn.eom(&state.orbit, almanac.clone())?;
+            Ok((total.fixed_rows::<3>(3) - gravity.fixed_rows::<3>(3)).norm())
+        };
+
+        let mut samples = self.every(cadence).collect::<Vec<Spacecraft>>();
+        if samples.last().map(|state| state.epoch()) != Some(self.last().epoch()) {
+            samples.push(*self.last());
+        }
+
+        let mut dv_km_s = 0.0;
+        let mut prev: Option<(Epoch, f64)> = None;
+        for state in &samples {
+            let accel = accel_km_s2(state)?;
+            if let Some((prev_epoch, prev_accel)) = prev {
+                dv_km_s += 0.5 * (prev_accel + accel) * (state.epoch() - prev_epoch).to_seconds();
+            }
+            prev = Some((state.epoch(), accel));
+        }
+
+        Ok(dv_km_s)
+    }
+
     /// A shortcut to `to_parquet_with_cfg`
     pub fn to_parquet_with_step<P: AsRef<Path>>(
         &self,
@@ -140,6 +419,156 @@ impl Traj<Spacecraft> {
         Ok(())
     }
 
+    /// Returns the topocentric look angles of this trajectory seen from the provided station, at each step, as tuples of the epoch,
+    /// the azimuth (degrees, clockwise from North), the elevation (degrees) and the range (km).
+    ///
+    /// The station is fixed in the provided body fixed frame, and its geodetic latitude and longitude set up its South-East-Zenith frame
+    /// (cf. `GroundStation::sez_state_of`). The elevation is not masked: negative elevations are returned when the spacecraft is below the horizon.
+    pub fn look_angles(
+        &self,
+        station: Orbit,
+        body_fixed_frame: Frame,
+        step: Duration,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<(Epoch, f64, f64, f64)>, NyxError> {
+        let station = almanac
+            .transform_to(station, body_fixed_frame, None)
+            .context(FromAlmanacSnafu {
+                action: "transforming the station into the body fixed frame",
+            })?;
+        let station = GroundStation::from_point(
+            "look angles".to_string(),
+            station.latitude_deg().context(FromPhysicsSnafu {
+                action: "computing the geodetic latitude of the station",
+            })?,
+            station.longitude_deg(),
+            station.height_km().context(FromPhysicsSnafu {
+                action: "computing the geodetic height of the station",
+            })?,
+            body_fixed_frame,
+        );
+
+        self.every(step)
+            .map(|state| {
+                let sez = station.sez_state_of(state.orbit, &almanac).map_err(|e| {
+                    NyxError::CustomError {
+                        msg: format!("computing the look angles at {}: {e}", state.epoch()),
+                    }
+                })?;
+                let range_km = sez.fixed_rows::<3>(0).norm();
+                let azimuth_deg = sez[1].atan2(-sez[0]).to_degrees().rem_euclid(360.0);
+                let elevation_deg = (sez[2] / range_km).asin().to_degrees();
+
+                Ok((state.epoch(), azimuth_deg, elevation_deg, range_km))
+            })
+            .collect()
+    }
+
+    /// Exports this trajectory as a CZML document for playback in Cesium, sampled at the provided step.
+    ///
+    /// The document holds the clock of the animation and one packet of the spacecraft, whose position samples are epoch-tagged Cartesian
+    /// coordinates in meters in the Earth centered inertial frame (EME2000, `INERTIAL` in Cesium), interpolated with a Lagrange polynomial by Cesium.
+    pub fn to_czml<P: AsRef<Path>>(
+        &self,
+        path: P,
+        step: Duration,
+        almanac: Arc<Almanac>,
+    ) -> Result<PathBuf, NyxError> {
+        if self.states.is_empty() {
+            return Err(NyxError::CustomError {
+                msg: "cannot export an empty trajectory to CZML".to_string(),
+            });
+        }
+
+        let eme2k = almanac
+            .frame_from_uid(EARTH_J2000)
+            .map_err(|e| NyxError::CustomError {
+                msg: format!("fetching the Earth J2000 frame for CZML: {e}"),
+            })?;
+
+        let start = self.first().epoch();
+        let end = self.last().epoch();
+
+        let mut cartesian = Vec::new();
+        for state in self.every(step) {
+            let orbit =
+                almanac
+                    .transform_to(state.orbit, eme2k, None)
+                    .context(FromAlmanacSnafu {
+                        action: "converting the trajectory into EME2000 for CZML",
+                    })?;
+            cartesian.push((state.epoch() - start).to_seconds());
+            cartesian.extend(orbit.radius_km.iter().map(|x_km| x_km * 1e3));
+        }
+
+        let iso8601 = |epoch: Epoch| {
+            let fmt = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
+            format!(
+                "{}Z",
+                Formatter::new(epoch.to_time_scale(TimeScale::UTC), fmt)
+            )
+        };
+        let interval = format!("{}/{}", iso8601(start), iso8601(end));
+        let name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| "spacecraft".to_string());
+
+        let document = json!([
+            {
+                "id": "document",
+                "name": name,
+                "version": "1.0",
+                "generator": prj_name_ver(),
+                "clock": {
+                    "interval": interval,
+                    "currentTime": iso8601(start),
+                    "multiplier": 60,
+                    "range": "LOOP_STOP",
+                    "step": "SYSTEM_CLOCK_MULTIPLIER"
+                }
+            },
+            {
+                "id": name,
+                "name": name,
+                "availability": interval,
+                "position": {
+                    "epoch": iso8601(start),
+                    "referenceFrame": "INERTIAL",
+                    "interpolationAlgorithm": "LAGRANGE",
+                    "interpolationDegree": 5,
+                    "cartesian": cartesian
+                },
+                "point": {
+                    "pixelSize": 6,
+                    "color": { "rgba": [255, 255, 0, 255] }
+                },
+                "path": {
+                    "width": 1,
+                    "leadTime": 0,
+                    "material": { "solidColor": { "color": { "rgba": [255, 255, 0, 128] } } }
+                }
+            }
+        ]);
+
+        let path_buf = path.as_ref().to_path_buf();
+        let file = File::create(&path_buf).map_err(|e| NyxError::CustomError {
+            msg: format!("creating CZML file {}: {e}", path_buf.display()),
+        })?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &document).map_err(|e| {
+            NyxError::CustomError {
+                msg: format!("writing CZML file {}: {e}", path_buf.display()),
+            }
+        })?;
+
+        info!(
+            "Trajectory written to {} with {} position samples",
+            path_buf.display(),
+            cartesian.len() / 4
+        );
+        Ok(path_buf)
+    }
+
     /// Exports this trajectory to the provided filename in parquet format with only the epoch, the geodetic latitude, longitude, and height at one state per minute.
     /// Must provide a body fixed frame to correctly compute the latitude and longitude.
     #[allow(clippy::identity_op)]
@@ -379,30 +808,30 @@ impl Traj<Spacecraft> {
 
         writeln!(writer, "CENTER_NAME = {center}",).map_err(err_hdlr)?;
 
-        writeln!(writer, "TIME_SYSTEM = {}", first_orbit.epoch.time_scale).map_err(err_hdlr)?;
+        // Epochs are written in the requested time scale, or in that of the trajectory.
+        let time_scale = cfg.time_scale.unwrap_or(first_orbit.epoch.time_scale);
+        let fmt_epoch =
+            |epoch: Epoch| Formatter::new(epoch.to_time_scale(time_scale), iso8601_no_ts);
 
-        writeln!(
-            writer,
-            "START_TIME = {}",
-            Formatter::new(states[0].epoch(), iso8601_no_ts)
-        )
-        .map_err(err_hdlr)?;
+        writeln!(writer, "TIME_SYSTEM = {time_scale}").map_err(err_hdlr)?;
+
+        writeln!(writer, "START_TIME = {}", fmt_epoch(states[0].epoch())).map_err(err_hdlr)?;
         writeln!(
             writer,
             "USEABLE_START_TIME = {}",
-            Formatter::new(states[0].epoch(), iso8601_no_ts)
+            fmt_epoch(states[0].epoch())
         )
         .map_err(err_hdlr)?;
         writeln!(
             writer,
             "USEABLE_STOP_TIME = {}",
-            Formatter::new(states[states.len() - 1].epoch(), iso8601_no_ts)
+            fmt_epoch(states[states.len() - 1].epoch())
         )
         .map_err(err_hdlr)?;
         writeln!(
             writer,
             "STOP_TIME = {}",
-            Formatter::new(states[states.len() - 1].epoch(), iso8601_no_ts)
+            fmt_epoch(states[states.len() - 1].epoch())
         )
         .map_err(err_hdlr)?;
 
@@ -420,7 +849,7 @@ impl Traj<Spacecraft> {
             writeln!(
                 writer,
                 "{} {:E} {:E} {:E} {:E} {:E} {:E}",
-                Formatter::new(state.epoch, iso8601_no_ts),
+                fmt_epoch(state.epoch),
                 state.radius_km.x,
                 state.radius_km.y,
                 state.radius_km.z,
@@ -448,7 +877,7 @@ impl Traj<Spacecraft> {
 mod ut_ccsds_oem {
 
     use crate::md::prelude::{OrbitalDynamics, Propagator, SpacecraftDynamics};
-    use crate::time::{Epoch, TimeUnits};
+    use crate::time::{Epoch, TimeScale, TimeUnits};
     use crate::Spacecraft;
     use crate::{io::ExportCfg, md::prelude::Traj, Orbit};
     use anise::almanac::Almanac;
@@ -551,6 +980,18 @@ mod ut_ccsds_oem {
 
         assert_eq!(traj_reloaded, traj);
 
+        // Export with epochs in TT, which must be labeled as such and read back as the same instants.
+        let cfg = ExportCfg::builder().time_scale(TimeScale::TT).build();
+        let out_path = traj.to_oem_file(path.clone(), cfg).unwrap();
+        let oem = std::fs::read_to_string(&out_path).unwrap();
+        assert!(oem.contains("TIME_SYSTEM = TT"));
+        let traj_reloaded: Traj<Spacecraft> = Traj::from_oem_file(out_path, None).unwrap();
+        assert_eq!(traj_reloaded.states.len(), traj.states.len());
+        for (reloaded, orig) in traj_reloaded.states.iter().zip(&traj.states) {
+            assert_eq!(reloaded.orbit.epoch.time_scale, TimeScale::TT);
+            assert!((reloaded.orbit.epoch - orig.orbit.epoch).abs() < 1.microseconds());
+        }
+
         // Now export after trimming one state on either end
         let cfg = ExportCfg::builder()
             .timestamp(true)
@@ -18,7 +18,7 @@
 
 use super::traj_it::TrajIterator;
 use super::{ExportCfg, InterpolationSnafu, INTERPOLATION_SAMPLES};
-use super::{Interpolatable, TrajError};
+use super::{InterpMethod, Interpolatable, TrajError};
 use crate::errors::NyxError;
 use crate::io::watermark::pq_writer;
 use crate::io::InputOutputError;
@@ -28,10 +28,11 @@ use crate::md::prelude::{GuidanceMode, StateParameter};
 use crate::md::EventEvaluator;
 use crate::time::{Duration, Epoch, TimeSeries, TimeUnits};
 use anise::almanac::Almanac;
+use anise::constants::orientations::{ECLIPJ2000, J2000};
+use anise::prelude::Frame;
 use arrow::array::{Array, Float64Builder, StringBuilder};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
-use hifitime::TimeScale;
 use parquet::arrow::ArrowWriter;
 use snafu::ResultExt;
 use std::collections::HashMap;
@@ -53,6 +54,97 @@ where
     pub name: Option<String>,
     /// We use a vector because we know that the states are produced in a chronological manner (the direction does not matter).
     pub states: Vec<S>,
+    /// If set, the states are interpolated in this inertial frame instead of the frame in which they are stored,
+    /// cf. `with_inertial_interpolation`.
+    pub inertial_interp: Option<InertialInterpolation>,
+    /// Time gaps of this trajectory, as the epochs of the states bounding each gap. The trajectory is not interpolated
+    /// within these gaps, cf. `join_with_policy`.
+    pub gaps: Vec<(Epoch, Epoch)>,
+    /// Chronological annotations of this trajectory, e.g. the configuration changes applied during its propagation,
+    /// cf. `PropInstance::for_duration_with_timeline`. These are exported in the metadata of the Parquet file.
+    pub annotations: Vec<(Epoch, String)>,
+    /// Method used to interpolate the states of this trajectory, defaults to Hermite interpolation,
+    /// cf. `set_interpolation`.
+    pub interpolation: InterpMethod,
+    /// If set, the trajectory is decimated whenever it holds more than this number of states, cf. `with_max_states`.
+    pub max_states: Option<usize>,
+}
+
+/// Policy on the time gap between two trajectories joined together, cf. `Traj::join_with_policy`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GapPolicy {
+    /// Joining trajectories with a time gap returns an error
+    Error,
+    /// A warning is logged and the joined trajectory interpolates across the gap, this is the behavior of `Traj + Traj`
+    #[default]
+    WarnAndInterpolate,
+    /// The states bounding the gap are recorded as markers of the gap: the joined trajectory returns an error when queried
+    /// strictly within the gap, and does not interpolate across it
+    InsertBoundaryMarkers,
+}
+
+/// Inertial frame through which the states of a trajectory stored in a rotating frame are interpolated.
+///
+/// In a rotating frame, like a body fixed frame or the rotating frame of a three body system, the Cartesian coordinates of
+/// even a conic are highly oscillatory, which the Hermite interpolation of the trajectory cannot capture accurately.
+#[derive(Clone)]
+pub struct InertialInterpolation {
+    /// Inertial frame in which to interpolate, typically the parent frame of the rotating frame
+    pub frame: Frame,
+    pub almanac: Arc<Almanac>,
+}
+
+impl PartialEq for InertialInterpolation {
+    /// Only the frames are compared
+    fn eq(&self, other: &Self) -> bool {
+        self.frame == other.frame
+    }
+}
+
+impl InertialInterpolation {
+    /// Returns whether the orientation of this frame rotates with respect to the inertial J2000 and ecliptic J2000 frames.
+    pub fn is_rotating(frame: Frame) -> bool {
+        frame.orientation_id != J2000 && frame.orientation_id != ECLIPJ2000
+    }
+}
+
+/// Statistics of a state parameter sampled over a trajectory, cf. `Traj::param_stats`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParamStats {
+    /// The parameter of these statistics
+    pub param: StateParameter,
+    /// Smallest sampled value
+    pub min: f64,
+    /// Epoch of the smallest sampled value
+    pub min_epoch: Epoch,
+    /// Largest sampled value
+    pub max: f64,
+    /// Epoch of the largest sampled value
+    pub max_epoch: Epoch,
+    /// Mean of the sampled values
+    pub mean: f64,
+    /// Standard deviation of the sampled values
+    pub std_dev: f64,
+    /// Number of samples
+    pub samples: usize,
+}
+
+impl fmt::Display for ParamStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = self.param.unit();
+        write!(
+            f,
+            "{:?}: min {} {unit} ({}), max {} {unit} ({}), mean {} {unit}, std dev {} {unit} over {} samples",
+            self.param,
+            self.min,
+            self.min_epoch,
+            self.max,
+            self.max_epoch,
+            self.mean,
+            self.std_dev,
+            self.samples
+        )
+    }
 }
 
 impl<S: Interpolatable> Traj<S>
@@ -63,14 +155,88 @@ where
         Self {
             name: None,
             states: Vec::new(),
+            inertial_interp: None,
+            gaps: Vec::new(),
+            annotations: Vec::new(),
+            interpolation: InterpMethod::default(),
+            max_states: None,
         }
     }
+
+    /// Sets the method used to interpolate the states of this trajectory.
+    ///
+    /// Hermite interpolation (the default) uses the velocity as the derivative of the position and is the most accurate on
+    /// smooth dynamics. Lagrange interpolation only uses the values of each component, which is preferable when the
+    /// velocity of the states is not consistent with their position, e.g. for some externally provided ephemerides.
+    pub fn set_interpolation(&mut self, method: InterpMethod) {
+        self.interpolation = method;
+    }
+
+    /// Interpolates the states of this trajectory in the provided inertial frame, and rotates the interpolated states back
+    /// into the frame of the trajectory. Use this when the trajectory is stored in a rotating frame.
+    ///
+    /// Note that `to_frame` sets this up automatically when converting into a rotating frame.
+    pub fn with_inertial_interpolation(
+        mut self,
+        inertial_frame: Frame,
+        almanac: Arc<Almanac>,
+    ) -> Self {
+        self.inertial_interp = Some(InertialInterpolation {
+            frame: inertial_frame,
+            almanac,
+        });
+        self
+    }
+
+    /// Caps the number of states of this trajectory, e.g. to bound the memory used by long high fidelity propagations.
+    ///
+    /// Whenever the trajectory holds more than `max_states` states (including when it is finalized, e.g. at the end of a
+    /// propagation or when joined with another trajectory), every other interior state is dropped until it fits. The first
+    /// and last states, and the states bounding the time gaps, are always kept. The interpolation error grows with the
+    /// time between the states, so pick a cap which keeps the steps of the trajectory short compared to its dynamics.
+    pub fn with_max_states(mut self, max_states: usize) -> Self {
+        self.max_states = Some(max_states);
+        self.decimate();
+        self
+    }
+
     /// Orders the states, can be used to store the states out of order
     pub fn finalize(&mut self) {
         // Remove duplicate epochs
         self.states.dedup_by(|a, b| a.epoch().eq(&b.epoch()));
         // And sort
         self.states.sort_by_key(|a| a.epoch());
+        self.decimate();
+    }
+
+    /// Drops every other interior state until the trajectory holds at most `max_states` states, if set.
+    fn decimate(&mut self) {
+        let Some(max_states) = self.max_states else {
+            return;
+        };
+        // The endpoints are always kept
+        let max_states = max_states.max(2);
+        let gaps = &self.gaps;
+        while self.states.len() > max_states {
+            let num_states = self.states.len();
+            let mut idx = 0;
+            self.states.retain(|state| {
+                let keep = idx % 2 == 0
+                    || idx == num_states - 1
+                    || gaps
+                        .iter()
+                        .any(|(start, end)| state.epoch() == *start || state.epoch() == *end);
+                idx += 1;
+                keep
+            });
+
+            if self.states.len() == num_states {
+                warn!(
+                    "cannot decimate the trajectory to {max_states} states without dropping the bounds of its time gaps"
+                );
+                break;
+            }
+        }
     }
 
     /// Evaluate the trajectory at this specific epoch.
@@ -78,6 +244,17 @@ where
         if self.states.is_empty() || self.first().epoch() > epoch || self.last().epoch() < epoch {
             return Err(TrajError::NoInterpolationData { epoch });
         }
+        if let Some((start, end)) = self
+            .gaps
+            .iter()
+            .find(|(start, end)| *start < epoch && epoch < *end)
+        {
+            return Err(TrajError::WithinGap {
+                epoch,
+                start: *start,
+                end: *end,
+            });
+        }
         match self
             .states
             .binary_search_by(|state| state.epoch().cmp(&epoch))
@@ -111,10 +288,46 @@ where
                 for idx in first_idx..last_idx {
                     states.push(self.states[idx]);
                 }
+                // Never interpolate across a gap
+                for (start, end) in &self.gaps {
+                    if epoch <= *start {
+                        states.retain(|state| state.epoch() <= *start);
+                    } else {
+                        states.retain(|state| state.epoch() >= *end);
+                    }
+                }
 
-                self.states[idx]
-                    .interpolate(epoch, &states)
-                    .context(InterpolationSnafu)
+                match &self.inertial_interp {
+                    None => self.states[idx]
+                        .interpolate_with(epoch, &states, self.interpolation)
+                        .context(InterpolationSnafu),
+                    Some(interp) => {
+                        let frame = self.states[idx].frame();
+                        let transform = |state: &mut S, to_frame: Frame| -> Result<(), TrajError> {
+                            let orbit = interp
+                                .almanac
+                                .transform_to(state.orbit(), to_frame, None)
+                                .map_err(|e| TrajError::InertialInterpolation {
+                                    epoch: state.epoch(),
+                                    msg: e.to_string(),
+                                })?;
+                            state.set_orbit(orbit);
+                            Ok(())
+                        };
+
+                        for state in states.iter_mut() {
+                            transform(state, interp.frame)?;
+                        }
+                        let mut template = self.states[idx];
+                        transform(&mut template, interp.frame)?;
+
+                        let mut interpolated = template
+                            .interpolate_with(epoch, &states, self.interpolation)
+                            .context(InterpolationSnafu)?;
+                        transform(&mut interpolated, frame)?;
+                        Ok(interpolated)
+                    }
+                }
             }
         }
     }
@@ -147,6 +360,37 @@ where
         }
     }
 
+    /// Returns the bounds of sliding windows of the provided width, starting every `step` from the start of this trajectory.
+    /// The bounds can then be fed to `every_between` to compute rolling statistics along the trajectory.
+    ///
+    /// All windows have the requested width: if the windows do not land on the end of the trajectory, a last window ending
+    /// on the last state is added, such that the whole trajectory is covered. If the trajectory is shorter than the width,
+    /// a single window spanning the trajectory is returned. No window is returned if the width or the step is not positive.
+    pub fn windows(&self, width: Duration, step: Duration) -> impl Iterator<Item = (Epoch, Epoch)> {
+        let mut bounds = Vec::new();
+        if self.states.is_empty() || width <= Duration::ZERO || step <= Duration::ZERO {
+            return bounds.into_iter();
+        }
+
+        let (first, last) = (self.first().epoch(), self.last().epoch());
+        if last - first <= width {
+            bounds.push((first, last));
+            return bounds.into_iter();
+        }
+
+        let mut start = first;
+        while start + width <= last {
+            bounds.push((start, start + width));
+            start += step;
+        }
+
+        if bounds.last().map(|(_, end)| *end) != Some(last) {
+            bounds.push((last - width, last));
+        }
+
+        bounds.into_iter()
+    }
+
     /// Store this trajectory arc to a parquet file with the default configuration (depends on the state type, search for `export_params` in the documentation for details).
     pub fn to_parquet_simple<P: AsRef<Path>>(
         &self,
@@ -181,20 +425,23 @@ where
         let path_buf = cfg.actual_path(path);
 
         // Build the schema
-        let mut hdrs = vec![Field::new("Epoch (UTC)", DataType::Utf8, false)];
+        let mut hdrs = cfg.epoch_fields();
 
         let frame = self.states[0].frame();
-        let more_meta = Some(vec![(
-            "Frame".to_string(),
-            serde_dhall::serialize(&frame).to_string().map_err(|e| {
-                Box::new(InputOutputError::SerializeDhall {
-                    what: format!("frame `{frame}`"),
-                    err: e.to_string(),
-                })
-            })?,
-        )]);
-
-        let mut fields = match cfg.fields {
+        let more_meta = Some(vec![
+            (
+                "Frame".to_string(),
+                serde_dhall::serialize(&frame).to_string().map_err(|e| {
+                    Box::new(InputOutputError::SerializeDhall {
+                        what: format!("frame `{frame}`"),
+                        err: e.to_string(),
+                    })
+                })?,
+            ),
+            ("frame".to_string(), frame.to_string()),
+        ]);
+
+        let mut fields = match cfg.fields.clone() {
             Some(fields) => fields,
             None => S::export_params(),
         };
@@ -208,7 +455,14 @@ where
 
         if let Some(events) = events.as_ref() {
             for event in events {
-                let field = Field::new(format!("{event}"), DataType::Float64, false);
+                let mut meta = HashMap::new();
+                meta.insert("unit".to_string(), event.unit().to_string());
+                meta.insert(
+                    "frame".to_string(),
+                    event.obs_frame().unwrap_or(frame).to_string(),
+                );
+                let field =
+                    Field::new(format!("{event}"), DataType::Float64, false).with_metadata(meta);
                 hdrs.push(field);
             }
         }
@@ -231,11 +485,7 @@ where
         // Build all of the records
 
         // Epochs
-        let mut utc_epoch = StringBuilder::new();
-        for s in &states {
-            utc_epoch.append_value(s.epoch().to_time_scale(TimeScale::UTC).to_isoformat());
-        }
-        record.push(Arc::new(utc_epoch.finish()));
+        record.extend(cfg.epoch_columns(states.iter().map(|s| s.epoch())));
 
         // Add all of the fields
         for field in fields {
@@ -277,6 +527,29 @@ where
         // Serialize all of the devices and add that to the parquet file too.
         let mut metadata = HashMap::new();
         metadata.insert("Purpose".to_string(), "Trajectory data".to_string());
+        if let Some(name) = &self.name {
+            metadata.insert("Name".to_string(), name.clone());
+        }
+        // Frame identifiers, used as a fallback when the fields do not carry the serialized frame.
+        metadata.insert("Frame name".to_string(), format!("{frame}"));
+        metadata.insert(
+            "Frame ephemeris ID".to_string(),
+            format!("{}", frame.ephemeris_id),
+        );
+        metadata.insert(
+            "Frame orientation ID".to_string(),
+            format!("{}", frame.orientation_id),
+        );
+        if !self.annotations.is_empty() {
+            metadata.insert(
+                "Annotations".to_string(),
+                self.annotations
+                    .iter()
+                    .map(|(epoch, note)| format!("{epoch}: {note}"))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            );
+        }
         if let Some(add_meta) = cfg.metadata {
             for (k, v) in add_meta {
                 metadata.insert(k, v);
@@ -289,7 +562,17 @@ where
         let mut writer = ArrowWriter::try_new(file, schema.clone(), props).unwrap();
 
         let batch = RecordBatch::try_new(schema, record)?;
-        writer.write(&batch)?;
+        match cfg.row_group_size {
+            Some(row_group_size) if row_group_size > 0 => {
+                // Flushing after each chunk closes its row group.
+                for offset in (0..batch.num_rows()).step_by(row_group_size) {
+                    let len = row_group_size.min(batch.num_rows() - offset);
+                    writer.write(&batch.slice(offset, len))?;
+                    writer.flush()?;
+                }
+            }
+            _ => writer.write(&batch)?,
+        }
         writer.close()?;
 
         // Return the path this was written to
@@ -301,6 +584,80 @@ where
         Ok(path_buf)
     }
 
+    /// Joins the other trajectory to this one, and handles a time gap between the end of this trajectory and the start of the other one with the provided policy.
+    /// Returns an error if the frames don't match. The states of the other trajectory up to the end of this trajectory are ignored.
+    pub fn join_with_policy(&self, other: &Self, policy: GapPolicy) -> Result<Self, NyxError> {
+        if self.first().frame() != other.first().frame() {
+            return Err(NyxError::Trajectory {
+                source: TrajError::CreationError {
+                    msg: format!(
+                        "Frame mismatch in add operation: {} != {}",
+                        self.first().frame(),
+                        other.first().frame()
+                    ),
+                },
+            });
+        }
+
+        let mut me = self.clone();
+
+        if self.last().epoch() < other.first().epoch() {
+            let (start, end) = (self.last().epoch(), other.first().epoch());
+            match policy {
+                GapPolicy::Error => {
+                    return Err(NyxError::Trajectory {
+                        source: TrajError::CreationError {
+                            msg: format!(
+                                "time-gap of {} starting at {start} between the trajectories",
+                                end - start
+                            ),
+                        },
+                    })
+                }
+                GapPolicy::WarnAndInterpolate => {
+                    warn!(
+                        "Resulting merged trajectory will have a time-gap of {} starting at {start}",
+                        end - start
+                    );
+                }
+                GapPolicy::InsertBoundaryMarkers => {
+                    info!(
+                        "Merged trajectory will not be interpolated in its time-gap of {} starting at {start}",
+                        end - start
+                    );
+                    me.gaps.push((start, end));
+                }
+            }
+        }
+
+        // Now start adding the other segments while correcting the index
+        for state in other
+            .states
+            .iter()
+            .copied()
+            .filter(|s| s.epoch() > self.last().epoch())
+        {
+            me.states.push(state);
+        }
+        me.gaps.extend(
+            other
+                .gaps
+                .iter()
+                .copied()
+                .filter(|(start, _)| *start >= self.last().epoch()),
+        );
+        me.annotations.extend(
+            other
+                .annotations
+                .iter()
+                .filter(|(epoch, _)| *epoch > self.last().epoch())
+                .cloned(),
+        );
+        me.finalize();
+
+        Ok(me)
+    }
+
     /// Allows resampling this trajectory at a fixed interval instead of using the propagator step size.
     /// This may lead to aliasing due to the Nyquist–Shannon sampling theorem.
     pub fn resample(&self, step: Duration) -> Result<Self, NyxError> {
@@ -313,6 +670,11 @@ where
         }
 
         let mut traj = Self::new();
+        traj.inertial_interp = self.inertial_interp.clone();
+        traj.gaps = self.gaps.clone();
+        traj.annotations = self.annotations.clone();
+        traj.interpolation = self.interpolation;
+        traj.max_states = self.max_states;
         for state in self.every(step) {
             traj.states.push(state);
         }
@@ -334,6 +696,11 @@ where
         }
 
         let mut traj = Self::new();
+        traj.inertial_interp = self.inertial_interp.clone();
+        traj.gaps = self.gaps.clone();
+        traj.annotations = self.annotations.clone();
+        traj.interpolation = self.interpolation;
+        traj.max_states = self.max_states;
         for epoch in epochs {
             traj.states.push(self.at(*epoch)?);
         }
@@ -343,6 +710,93 @@ where
         Ok(traj)
     }
 
+    /// Returns the path length flown along this trajectory in kilometers, by integrating the velocity magnitude
+    /// with the trapezoidal rule on states sampled (and interpolated) every `step`.
+    pub fn arc_length(&self, step: Duration) -> Result<f64, NyxError> {
+        if self.states.is_empty() {
+            return Err(NyxError::Trajectory {
+                source: TrajError::CreationError {
+                    msg: "No trajectory to compute the arc length of".to_string(),
+                },
+            });
+        }
+
+        let mut samples: Vec<S> = self.every(step).collect();
+        // The time series may not land exactly on the last state
+        if samples.last().map(|s| s.epoch()) != Some(self.last().epoch()) {
+            samples.push(*self.last());
+        }
+
+        let mut length_km = 0.0;
+        for pair in samples.windows(2) {
+            let dt_s = (pair[1].epoch() - pair[0].epoch()).to_seconds();
+            length_km += 0.5 * dt_s * (pair[0].orbit().vmag_km_s() + pair[1].orbit().vmag_km_s());
+        }
+
+        Ok(length_km)
+    }
+
+    /// Returns the minimum (and its epoch), maximum (and its epoch), mean and standard deviation of the provided parameter
+    /// over states sampled (and interpolated) every `step`, including the last state.
+    ///
+    /// The extrema are those of the samples: use the event finding, e.g. `find_minmax`, for their precise epochs.
+    pub fn param_stats(
+        &self,
+        param: StateParameter,
+        step: Duration,
+    ) -> Result<ParamStats, NyxError> {
+        if self.states.is_empty() {
+            return Err(NyxError::Trajectory {
+                source: TrajError::CreationError {
+                    msg: "No trajectory to compute the statistics of".to_string(),
+                },
+            });
+        }
+
+        let mut samples: Vec<S> = self.every(step).collect();
+        // The time series may not land exactly on the last state
+        if samples.last().map(|s| s.epoch()) != Some(self.last().epoch()) {
+            samples.push(*self.last());
+        }
+
+        let mut stats = ParamStats {
+            param,
+            min: f64::INFINITY,
+            min_epoch: self.first().epoch(),
+            max: f64::NEG_INFINITY,
+            max_epoch: self.first().epoch(),
+            mean: 0.0,
+            std_dev: 0.0,
+            samples: samples.len(),
+        };
+
+        let mut values = Vec::with_capacity(samples.len());
+        for state in &samples {
+            let value = state
+                .value(param)
+                .map_err(|e| NyxError::StateParameterUnavailable {
+                    param,
+                    msg: e.to_string(),
+                })?;
+            if value < stats.min {
+                stats.min = value;
+                stats.min_epoch = state.epoch();
+            }
+            if value > stats.max {
+                stats.max = value;
+                stats.max_epoch = state.epoch();
+            }
+            values.push(value);
+        }
+
+        let count = values.len() as f64;
+        stats.mean = values.iter().sum::<f64>() / count;
+        stats.std_dev =
+            (values.iter().map(|v| (v - stats.mean).powi(2)).sum::<f64>() / count).sqrt();
+
+        Ok(stats)
+    }
+
     /// Export the difference in RIC from of this trajectory compare to the "other" trajectory in parquet format.
     ///
     /// # Notes
@@ -360,12 +814,13 @@ where
         let path_buf = cfg.actual_path(path);
 
         // Build the schema
-        let mut hdrs = vec![Field::new("Epoch (UTC)", DataType::Utf8, false)];
+        let mut hdrs = cfg.epoch_fields();
 
         // Add the RIC headers
         for coord in ["X", "Y", "Z"] {
             let mut meta = HashMap::new();
             meta.insert("unit".to_string(), "km".to_string());
+            meta.insert("frame".to_string(), "RIC".to_string());
 
             let field = Field::new(
                 format!("Delta {coord} (RIC) (km)"),
@@ -380,6 +835,7 @@ where
         for coord in ["x", "y", "z"] {
             let mut meta = HashMap::new();
             meta.insert("unit".to_string(), "km/s".to_string());
+            meta.insert("frame".to_string(), "RIC".to_string());
 
             let field = Field::new(
                 format!("Delta V{coord} (RIC) (km/s)"),
@@ -392,16 +848,19 @@ where
         }
 
         let frame = self.states[0].frame();
-        let more_meta = Some(vec![(
-            "Frame".to_string(),
-            serde_dhall::serialize(&frame)
-                .to_string()
-                .unwrap_or(frame.to_string()),
-        )]);
+        let more_meta = Some(vec![
+            (
+                "Frame".to_string(),
+                serde_dhall::serialize(&frame)
+                    .to_string()
+                    .unwrap_or(frame.to_string()),
+            ),
+            ("frame".to_string(), frame.to_string()),
+        ]);
 
         let mut cfg = cfg;
 
-        let mut fields = match cfg.fields {
+        let mut fields = match cfg.fields.clone() {
             Some(fields) => fields,
             None => S::export_params(),
         };
@@ -456,11 +915,7 @@ where
         // Build all of the records
 
         // Epochs (both match for self and others)
-        let mut utc_epoch = StringBuilder::new();
-        for s in &self_states {
-            utc_epoch.append_value(s.epoch().to_time_scale(TimeScale::UTC).to_isoformat());
-        }
-        record.push(Arc::new(utc_epoch.finish()));
+        record.extend(cfg.epoch_columns(self_states.iter().map(|s| s.epoch())));
 
         // Add the RIC data
         for coord_no in 0..6 {
@@ -513,6 +968,149 @@ where
         );
         Ok(path_buf)
     }
+
+    /// Export the difference in the frame of this trajectory compared to the "other" trajectory in parquet format, along with the RSS of the position and velocity differences.
+    ///
+    /// Both trajectories are sampled every `cfg.step` (defaults to one minute) over their overlap, and must be in the same frame.
+    pub fn diff_to_parquet<P: AsRef<Path>>(
+        &self,
+        other: &Self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let tick = Epoch::now().unwrap();
+        info!("Exporting trajectory difference to parquet file...");
+
+        let frame = self.first().frame();
+        if frame != other.first().frame() {
+            return Err(Box::new(NyxError::Trajectory {
+                source: TrajError::CreationError {
+                    msg: format!(
+                        "Frame mismatch in difference: {frame} != {}",
+                        other.first().frame()
+                    ),
+                },
+            }));
+        }
+
+        // Grab the path here before we move stuff.
+        let path_buf = cfg.actual_path(path);
+
+        // Build the schema
+        let mut hdrs = cfg.epoch_fields();
+
+        for (name, unit) in [
+            ("Delta X (km)", "km"),
+            ("Delta Y (km)", "km"),
+            ("Delta Z (km)", "km"),
+            ("Delta Vx (km/s)", "km/s"),
+            ("Delta Vy (km/s)", "km/s"),
+            ("Delta Vz (km/s)", "km/s"),
+            ("RSS position (km)", "km"),
+            ("RSS velocity (km/s)", "km/s"),
+        ] {
+            let mut meta = HashMap::new();
+            meta.insert("unit".to_string(), unit.to_string());
+            meta.insert("Frame".to_string(), frame.to_string());
+            meta.insert("frame".to_string(), frame.to_string());
+
+            hdrs.push(Field::new(name, DataType::Float64, false).with_metadata(meta));
+        }
+
+        // Build the schema
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        // Ensure the times match.
+        let start = if self.first().epoch() > other.first().epoch() {
+            self.first().epoch()
+        } else {
+            other.first().epoch()
+        };
+
+        let end = if self.last().epoch() > other.last().epoch() {
+            other.last().epoch()
+        } else {
+            self.last().epoch()
+        };
+
+        // Build the states iterator
+        let step = cfg.step.unwrap_or_else(|| 1.minutes());
+        let self_states = self.every_between(step, start, end).collect::<Vec<S>>();
+        let other_states = other.every_between(step, start, end).collect::<Vec<S>>();
+
+        // Build an array of all the differences
+        let diffs = self_states
+            .iter()
+            .zip(other_states.iter())
+            .map(|(self_state, other_state)| {
+                let self_orbit = self_state.orbit();
+                let other_orbit = other_state.orbit();
+                (
+                    self_orbit.radius_km - other_orbit.radius_km,
+                    self_orbit.velocity_km_s - other_orbit.velocity_km_s,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Epochs (both match for self and others)
+        record.extend(cfg.epoch_columns(self_states.iter().map(|s| s.epoch())));
+
+        for coord_no in 0..3 {
+            let mut data = Float64Builder::new();
+            for (dr, _) in &diffs {
+                data.append_value(dr[coord_no]);
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
+        for coord_no in 0..3 {
+            let mut data = Float64Builder::new();
+            for (_, dv) in &diffs {
+                data.append_value(dv[coord_no]);
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
+        let mut rss_pos = Float64Builder::new();
+        let mut rss_vel = Float64Builder::new();
+        for (dr, dv) in &diffs {
+            rss_pos.append_value(dr.norm());
+            rss_vel.append_value(dv.norm());
+        }
+        record.push(Arc::new(rss_pos.finish()));
+        record.push(Arc::new(rss_vel.finish()));
+
+        info!("Serialized {} states differences", self_states.len());
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "Trajectory difference data".to_string(),
+        );
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata));
+
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props).unwrap();
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        // Return the path this was written to
+        let tock_time = Epoch::now().unwrap() - tick;
+        info!(
+            "Trajectory difference written to {} in {tock_time}",
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
 }
 
 impl<S: Interpolatable> ops::Add for Traj<S>
@@ -533,43 +1131,10 @@ where
 {
     type Output = Result<Traj<S>, NyxError>;
 
-    /// Add one trajectory to another, returns an error if the frames don't match
+    /// Add one trajectory to another, returns an error if the frames don't match.
+    /// A time gap between both trajectories is interpolated across, with a warning, cf. `join_with_policy`.
     fn add(self, other: &Traj<S>) -> Self::Output {
-        if self.first().frame() != other.first().frame() {
-            Err(NyxError::Trajectory {
-                source: TrajError::CreationError {
-                    msg: format!(
-                        "Frame mismatch in add operation: {} != {}",
-                        self.first().frame(),
-                        other.first().frame()
-                    ),
-                },
-            })
-        } else {
-            if self.last().epoch() < other.first().epoch() {
-                let gap = other.first().epoch() - self.last().epoch();
-                warn!(
-                    "Resulting merged trajectory will have a time-gap of {} starting at {}",
-                    gap,
-                    self.last().epoch()
-                );
-            }
-
-            let mut me = self.clone();
-            // Now start adding the other segments while correcting the index
-            for state in &other
-                .states
-                .iter()
-                .copied()
-                .filter(|s| s.epoch() > self.last().epoch())
-                .collect::<Vec<S>>()
-            {
-                me.states.push(*state);
-            }
-            me.finalize();
-
-            Ok(me)
-        }
+        self.join_with_policy(other, GapPolicy::WarnAndInterpolate)
     }
 }
 
@@ -37,27 +37,34 @@ where
     type Item = S;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.time_series.next() {
-            Some(next_epoch) => match self.traj.at(next_epoch) {
-                Ok(item) => Some(item),
-                Err(e) => {
-                    if next_epoch >= self.traj.first().epoch()
-                        && next_epoch <= self.traj.last().epoch()
-                    {
-                        let msg = format!(
-                            "{e} out of bounds in {}! Please submit bug report with exported traj",
-                            self.traj
-                        );
-                        if log_enabled!(log::Level::Error) {
-                            error!("{msg}");
-                        } else {
-                            eprintln!("{msg}");
-                        };
-                    }
-                    None
+        let mut next_epoch = self.time_series.next()?;
+        // Skip the epochs within the gaps of the trajectory
+        while self
+            .traj
+            .gaps
+            .iter()
+            .any(|(start, end)| *start < next_epoch && next_epoch < *end)
+        {
+            next_epoch = self.time_series.next()?;
+        }
+
+        match self.traj.at(next_epoch) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                if next_epoch >= self.traj.first().epoch() && next_epoch <= self.traj.last().epoch()
+                {
+                    let msg = format!(
+                        "{e} out of bounds in {}! Please submit bug report with exported traj",
+                        self.traj
+                    );
+                    if log_enabled!(log::Level::Error) {
+                        error!("{msg}");
+                    } else {
+                        eprintln!("{msg}");
+                    };
                 }
-            },
-            None => None,
+                None
+            }
         }
     }
 }
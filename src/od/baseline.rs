@@ -0,0 +1,288 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::ephemerides::EphemerisError;
+use anise::errors::{AlmanacError, AlmanacResult};
+use anise::prelude::{Almanac, Frame, Orbit};
+
+use super::msr::DeltaDor;
+use super::noise::StochasticNoise;
+use super::{GroundStation, ODAlmanacSnafu, ODError, ODTrajSnafu, TrackingDeviceSim};
+use crate::cosmic::SPEED_OF_LIGHT_KM_S;
+use crate::io::ConfigRepr;
+use crate::linalg::Vector3;
+use crate::mc::NyxRng;
+use crate::md::prelude::Traj;
+use crate::time::Epoch;
+use crate::Spacecraft;
+use crate::State;
+use nalgebra::OMatrix;
+use serde_derive::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::fmt;
+use std::sync::Arc;
+
+/// A baseline between two ground stations, used to generate Delta-DOR measurements of a spacecraft with respect to a reference quasar.
+///
+/// Both stations must see the spacecraft above their elevation mask for a measurement to be generated. The quasar direction is defined
+/// by its right ascension and declination in the J2000 orientation, which must be the orientation of the frame of the spacecraft.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Baseline {
+    pub name: String,
+    /// First station of the baseline
+    pub station_a: GroundStation,
+    /// Second station of the baseline, the baseline vector goes from the first station to this one
+    pub station_b: GroundStation,
+    /// Right ascension of the reference quasar, in degrees
+    pub quasar_ra_deg: f64,
+    /// Declination of the reference quasar, in degrees
+    pub quasar_dec_deg: f64,
+    /// Noise on the timestamp of the measurement
+    pub timestamp_noise_s: Option<StochasticNoise>,
+    /// Noise on the Delta-DOR angle, in radians
+    pub delta_dor_noise_rad: Option<StochasticNoise>,
+}
+
+impl Baseline {
+    /// Unit vector towards the quasar
+    pub fn quasar_unit(&self) -> Vector3<f64> {
+        let (ra, dec) = (
+            self.quasar_ra_deg.to_radians(),
+            self.quasar_dec_deg.to_radians(),
+        );
+        Vector3::new(dec.cos() * ra.cos(), dec.cos() * ra.sin(), dec.sin())
+    }
+
+    /// Returns the location of both stations in the provided frame.
+    pub fn stations_in(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        almanac: &Almanac,
+    ) -> AlmanacResult<(Orbit, Orbit)> {
+        let station_a =
+            almanac.transform_to(station_orbit(&self.station_a, epoch, almanac)?, frame, None)?;
+        let station_b =
+            almanac.transform_to(station_orbit(&self.station_b, epoch, almanac)?, frame, None)?;
+        Ok((station_a, station_b))
+    }
+
+    /// Computes the differential one-way range delay, in seconds, between the signal of the spacecraft and that of the quasar across this baseline.
+    /// This is the Delta-DOR observable prior to its conversion to an angle, i.e. `Delta-DOR (rad) = c * delay / |baseline|`.
+    pub fn differential_delay_s(&self, rx: Orbit, almanac: &Almanac) -> AlmanacResult<f64> {
+        let (station_a, station_b) = self.stations_in(rx.epoch, rx.frame, almanac)?;
+        let baseline_km = station_b.radius_km - station_a.radius_km;
+        let midpoint_km = (station_a.radius_km + station_b.radius_km) * 0.5;
+        let los_unit = (rx.radius_km - midpoint_km).normalize();
+        Ok(baseline_km.dot(&(los_unit - self.quasar_unit())) / SPEED_OF_LIGHT_KM_S)
+    }
+
+    /// Returns the timestamp noise and the Delta-DOR noise of this baseline at the provided epoch.
+    fn noises(&mut self, epoch: Epoch, rng: Option<&mut NyxRng>) -> Result<(f64, f64), ODError> {
+        match rng {
+            Some(rng) => {
+                let delta_dor_noise_rad = self
+                    .delta_dor_noise_rad
+                    .ok_or(ODError::NoiseNotConfigured { kind: "Delta-DOR" })?
+                    .sample(epoch, rng);
+
+                let timestamp_noise_s = match self.timestamp_noise_s.as_mut() {
+                    Some(timestamp_noise) => timestamp_noise.sample(epoch, rng),
+                    None => 0.0,
+                };
+
+                Ok((timestamp_noise_s, delta_dor_noise_rad))
+            }
+            None => Ok((0.0, 0.0)),
+        }
+    }
+}
+
+/// Returns the location of the station in its body fixed frame, propagating the conversion error of its geodetic coordinates.
+fn station_orbit(station: &GroundStation, epoch: Epoch, almanac: &Almanac) -> AlmanacResult<Orbit> {
+    station
+        .to_orbit(epoch, almanac)
+        .map_err(|source| AlmanacError::Ephemeris {
+            action: "computing the location of a baseline station",
+            source: Box::new(EphemerisError::EphemerisPhysics {
+                action: "converting the station geodetic coordinates",
+                source,
+            }),
+        })
+}
+
+impl ConfigRepr for Baseline {}
+
+impl TrackingDeviceSim<Spacecraft, DeltaDor> for Baseline {
+    /// Delta-DOR measurements are instantaneous: the integration time of the stations is ignored.
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut NyxRng>,
+        almanac: Arc<Almanac>,
+    ) -> Result<Option<DeltaDor>, ODError> {
+        self.measure_instantaneous(traj.at(epoch).context(ODTrajSnafu)?, rng, almanac)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the midpoint of the baseline
+    fn location(&self, epoch: Epoch, frame: Frame, almanac: Arc<Almanac>) -> AlmanacResult<Orbit> {
+        let (station_a, station_b) = self.stations_in(epoch, frame, &almanac)?;
+        let mut midpoint = station_a;
+        midpoint.radius_km = (station_a.radius_km + station_b.radius_km) * 0.5;
+        midpoint.velocity_km_s = (station_a.velocity_km_s + station_b.velocity_km_s) * 0.5;
+        Ok(midpoint)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut NyxRng>,
+        almanac: Arc<Almanac>,
+    ) -> Result<Option<DeltaDor>, ODError> {
+        for station in [&self.station_a, &self.station_b] {
+            let obstructing_body = if !station.frame.ephem_origin_match(rx.frame()) {
+                Some(rx.frame())
+            } else {
+                None
+            };
+
+            let aer = station
+                .azimuth_elevation_of(rx.orbit, obstructing_body, &almanac)
+                .context(ODAlmanacSnafu {
+                    action: "computing AER",
+                })?;
+
+            if aer.elevation_deg < station.elevation_mask_deg {
+                debug!(
+                    "{} {}: {} (el. mask {:.3} deg), object at {:.3} deg -- no measurement",
+                    self.name,
+                    rx.epoch(),
+                    station.name,
+                    station.elevation_mask_deg,
+                    aer.elevation_deg
+                );
+                return Ok(None);
+            }
+        }
+
+        let (station_a, station_b) =
+            self.stations_in(rx.epoch(), rx.frame(), &almanac)
+                .context(ODAlmanacSnafu {
+                    action: "computing baseline",
+                })?;
+
+        let midpoint_km = (station_a.radius_km + station_b.radius_km) * 0.5;
+        let los_unit = (rx.orbit.radius_km - midpoint_km).normalize();
+
+        let (timestamp_noise_s, delta_dor_noise_rad) = self.noises(rx.epoch(), rng)?;
+
+        Ok(Some(DeltaDor::new(
+            rx.epoch(),
+            los_unit,
+            self.quasar_unit(),
+            station_b.radius_km - station_a.radius_km,
+            timestamp_noise_s,
+            delta_dor_noise_rad,
+        )))
+    }
+
+    /// Returns the variance of the Delta-DOR noise, in rad^2.
+    fn measurement_covar(
+        &mut self,
+        epoch: Epoch,
+    ) -> Result<
+        OMatrix<
+            f64,
+            <DeltaDor as super::Measurement>::MeasurementSize,
+            <DeltaDor as super::Measurement>::MeasurementSize,
+        >,
+        ODError,
+    > {
+        let delta_dor_noise_rad2 = self
+            .delta_dor_noise_rad
+            .ok_or(ODError::NoiseNotConfigured { kind: "Delta-DOR" })?
+            .covariance(epoch);
+
+        Ok(OMatrix::<
+            f64,
+            <DeltaDor as super::Measurement>::MeasurementSize,
+            <DeltaDor as super::Measurement>::MeasurementSize,
+        >::from_element(delta_dor_noise_rad2))
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} baseline from {} to {} (quasar RA.: {:.4} deg    Dec.: {:.4} deg)",
+            self.name,
+            self.station_a.name,
+            self.station_b.name,
+            self.quasar_ra_deg,
+            self.quasar_dec_deg,
+        )
+    }
+}
+
+#[cfg(test)]
+mod baseline_ut {
+    use crate::io::ConfigRepr;
+    use crate::od::prelude::*;
+
+    #[test]
+    fn test_load_baseline() {
+        use std::env;
+        use std::path::PathBuf;
+
+        let test_data: PathBuf = [
+            env::var("CARGO_MANIFEST_DIR").unwrap(),
+            "data".to_string(),
+            "tests".to_string(),
+            "config".to_string(),
+            "delta_dor_baseline.yaml".to_string(),
+        ]
+        .iter()
+        .collect();
+
+        let baseline = Baseline::load(test_data).unwrap();
+        dbg!(&baseline);
+
+        assert_eq!(baseline.station_a.name, "Goldstone");
+        assert_eq!(baseline.station_b.name, "Canberra");
+        assert_eq!(
+            baseline
+                .delta_dor_noise_rad
+                .unwrap()
+                .white_noise
+                .unwrap()
+                .sigma,
+            2e-9
+        );
+        assert!((baseline.quasar_unit().norm() - 1.0).abs() < f64::EPSILON);
+
+        // Serialize back
+        let reser = serde_yaml::to_string(&baseline).unwrap();
+        assert_eq!(serde_yaml::from_str::<Baseline>(&reser).unwrap(), baseline);
+    }
+}
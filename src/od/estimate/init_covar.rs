@@ -0,0 +1,369 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::sync::Arc;
+
+use anise::almanac::Almanac;
+use na::{SMatrix, SVector};
+use snafu::ResultExt;
+
+use super::{Estimate, KfEstimate};
+use crate::linalg::Vector3;
+use crate::md::prelude::{OrbitalDynamics, Propagator, SpacecraftDynamics, Traj};
+use crate::od::{ODDynamicsSnafu, ODError, ODPropSnafu, ODTrajSnafu};
+use crate::time::Epoch;
+use crate::{Spacecraft, State};
+
+/// Specification of the initial covariance of a spacecraft estimate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InitialCovariance {
+    /// Full covariance of the spacecraft state, in the inertial frame of the nominal state.
+    Full(SMatrix<f64, 9, 9>),
+    /// Per-axis 1-sigma uncertainties in the RIC frame of the nominal state, in km and km/s.
+    RicSigmas {
+        position_km: Vector3<f64>,
+        velocity_km_s: Vector3<f64>,
+    },
+    /// Heuristic where the 1-sigma uncertainty of each inertial component is this fraction of the position and velocity magnitudes.
+    StateFraction(f64),
+}
+
+impl InitialCovariance {
+    /// Builds the inertial covariance of the provided nominal state, checking that its orbital part is positive definite.
+    pub fn to_covar(&self, nominal: &Spacecraft) -> Result<SMatrix<f64, 9, 9>, ODError> {
+        let covar = match *self {
+            Self::Full(covar) => covar,
+            Self::RicSigmas {
+                position_km,
+                velocity_km_s,
+            } => {
+                let ric_covar = SMatrix::<f64, 6, 6>::from_diagonal(&SVector::<f64, 6>::new(
+                    position_km.x.powi(2),
+                    position_km.y.powi(2),
+                    position_km.z.powi(2),
+                    velocity_km_s.x.powi(2),
+                    velocity_km_s.y.powi(2),
+                    velocity_km_s.z.powi(2),
+                ));
+
+                // Includes the time derivative of the rotation, so the velocity uncertainty accounts for the rotation of the RIC frame.
+                let dcm_ric2inertial = nominal
+                    .orbit
+                    .dcm_from_ric_to_inertial()
+                    .map_err(|e| ODError::InvalidCovariance {
+                        msg: format!("cannot compute RIC frame: {e}"),
+                    })?
+                    .state_dcm();
+
+                let mut covar = SMatrix::<f64, 9, 9>::zeros();
+                covar
+                    .fixed_view_mut::<6, 6>(0, 0)
+                    .copy_from(&(dcm_ric2inertial * ric_covar * dcm_ric2inertial.transpose()));
+                covar
+            }
+            Self::StateFraction(fraction) => {
+                let pos_var = (fraction * nominal.orbit.rmag_km()).powi(2);
+                let vel_var = (fraction * nominal.orbit.vmag_km_s()).powi(2);
+                SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+                    pos_var, pos_var, pos_var, vel_var, vel_var, vel_var, 0.0, 0.0, 0.0,
+                ]))
+            }
+        };
+
+        validate_covar(&covar)?;
+
+        Ok(covar)
+    }
+}
+
+/// How to map a covariance over a time gap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CovarianceMapping {
+    /// Propagates the nominal state with two-body dynamics and maps the covariance through the resulting STM.
+    Keplerian,
+    /// Uses the provided state at the new epoch, whose STM must map from the epoch of the estimate (e.g. propagated with `with_stm`).
+    Stm(Spacecraft),
+}
+
+impl KfEstimate<Spacecraft> {
+    /// Initializes a filter estimate from the state of the reference trajectory at the provided epoch and the covariance specification.
+    pub fn from_traj(
+        traj: &Traj<Spacecraft>,
+        epoch: Epoch,
+        covar: InitialCovariance,
+    ) -> Result<Self, ODError> {
+        let mut nominal = traj.at(epoch).context(ODTrajSnafu)?;
+        nominal.stm = None;
+
+        let covar = covar.to_covar(&nominal)?;
+
+        Ok(Self::from_covar(nominal, covar))
+    }
+
+    /// Inflates the covariance of this estimate by multiplying its 1-sigma uncertainties by the factor, and maps it to the new epoch.
+    ///
+    /// This is typically used to start a new orbit determination from the final estimate of a previous one.
+    /// The state deviation is added to the nominal state prior to mapping, so the returned estimate has no deviation.
+    pub fn inflated_to(
+        &self,
+        factor: f64,
+        epoch: Epoch,
+        mapping: CovarianceMapping,
+        almanac: Arc<Almanac>,
+    ) -> Result<Self, ODError> {
+        if factor <= 0.0 || !factor.is_finite() {
+            return Err(ODError::InvalidCovariance {
+                msg: format!("inflation factor must be strictly positive, got {factor}"),
+            });
+        }
+
+        let covar = self.covar * factor.powi(2);
+
+        let mut mapped = match mapping {
+            CovarianceMapping::Keplerian => {
+                Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+                    .with(self.state().with_stm(), almanac)
+                    .until_epoch(epoch)
+                    .context(ODPropSnafu)?
+            }
+            CovarianceMapping::Stm(state) => {
+                if state.epoch() != epoch {
+                    return Err(ODError::InvalidCovariance {
+                        msg: format!(
+                            "mapping state is at {} but covariance is requested at {epoch}",
+                            state.epoch()
+                        ),
+                    });
+                }
+                state
+            }
+        };
+
+        let stm = mapped.stm().context(ODDynamicsSnafu)?;
+        let covar = stm * covar * stm.transpose();
+        validate_covar(&covar)?;
+
+        mapped.stm = None;
+
+        Ok(Self::from_covar(mapped, covar))
+    }
+}
+
+/// Checks that the covariance is finite and symmetric, that its orbital part is positive definite, and that the other variances are not negative.
+fn validate_covar(covar: &SMatrix<f64, 9, 9>) -> Result<(), ODError> {
+    if covar.iter().any(|val| !val.is_finite()) {
+        return Err(ODError::InvalidCovariance {
+            msg: "covariance contains non-finite values".to_string(),
+        });
+    }
+
+    let scale = covar.amax().max(f64::MIN_POSITIVE);
+    if (covar - covar.transpose()).amax() > 1e-9 * scale {
+        return Err(ODError::InvalidCovariance {
+            msg: "covariance is not symmetric".to_string(),
+        });
+    }
+
+    if covar
+        .fixed_view::<6, 6>(0, 0)
+        .into_owned()
+        .cholesky()
+        .is_none()
+    {
+        return Err(ODError::InvalidCovariance {
+            msg: "orbital covariance is not positive definite".to_string(),
+        });
+    }
+
+    if (6..9).any(|i| covar[(i, i)] < 0.0) {
+        return Err(ODError::InvalidCovariance {
+            msg: "negative variance of non-orbital parameter".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod ut_init_covar {
+    use super::*;
+    use crate::md::StateParameter;
+    use crate::time::TimeUnits;
+    use crate::GMAT_EARTH_GM;
+    use anise::constants::frames::EME2000;
+    use anise::prelude::Orbit;
+
+    fn circular() -> Spacecraft {
+        let eme2k = EME2000.with_mu_km3_s2(GMAT_EARTH_GM);
+        let r_km = 7000.0;
+        let v_km_s = (GMAT_EARTH_GM / r_km).sqrt();
+        // On the +Y axis moving towards -X, so R = +Y, I = -X, C = +Z.
+        Spacecraft::from(Orbit::new(
+            0.0,
+            r_km,
+            0.0,
+            -v_km_s,
+            0.0,
+            0.0,
+            Epoch::from_gregorian_utc_at_noon(2024, 1, 1),
+            eme2k,
+        ))
+    }
+
+    #[test]
+    fn ric_sigmas_to_inertial() {
+        let nominal = circular();
+        let omega = nominal.orbit.vmag_km_s() / nominal.orbit.rmag_km();
+
+        let (sr, si, sc) = (0.1, 0.5, 0.2);
+        let (svr, svi, svc) = (1e-4, 3e-4, 2e-4);
+        let covar = InitialCovariance::RicSigmas {
+            position_km: Vector3::new(sr, si, sc),
+            velocity_km_s: Vector3::new(svr, svi, svc),
+        }
+        .to_covar(&nominal)
+        .unwrap();
+
+        // By hand: x = -δI, y = δR, z = δC, vx = -δvI - ω δR, vy = δvR - ω δI, vz = δvC
+        let mut expected = SMatrix::<f64, 9, 9>::zeros();
+        expected[(0, 0)] = si.powi(2);
+        expected[(1, 1)] = sr.powi(2);
+        expected[(2, 2)] = sc.powi(2);
+        expected[(3, 3)] = svi.powi(2) + (omega * sr).powi(2);
+        expected[(4, 4)] = svr.powi(2) + (omega * si).powi(2);
+        expected[(5, 5)] = svc.powi(2);
+        expected[(1, 3)] = -omega * sr.powi(2);
+        expected[(3, 1)] = expected[(1, 3)];
+        expected[(0, 4)] = omega * si.powi(2);
+        expected[(4, 0)] = expected[(0, 4)];
+
+        println!("{covar:.3e}\n{expected:.3e}");
+        assert!((covar - expected).amax() < 1e-10);
+
+        // Percentage of state
+        let covar = InitialCovariance::StateFraction(1e-3)
+            .to_covar(&nominal)
+            .unwrap();
+        assert!((covar[(0, 0)].sqrt() - 7.0).abs() < 1e-12);
+        assert!((covar[(3, 3)].sqrt() - 1e-3 * nominal.orbit.vmag_km_s()).abs() < 1e-15);
+
+        // Non positive definite covariances are rejected
+        assert!(InitialCovariance::Full(SMatrix::<f64, 9, 9>::zeros())
+            .to_covar(&nominal)
+            .is_err());
+        assert!(InitialCovariance::RicSigmas {
+            position_km: Vector3::new(0.1, 0.0, 0.1),
+            velocity_km_s: Vector3::new(1e-4, 1e-4, 1e-4),
+        }
+        .to_covar(&nominal)
+        .is_err());
+    }
+
+    #[test]
+    fn inflate_and_map() {
+        let almanac = Arc::new(Almanac::default());
+        let nominal = circular();
+
+        let estimate = KfEstimate::from_covar(
+            nominal,
+            InitialCovariance::RicSigmas {
+                position_km: Vector3::new(0.1, 0.5, 0.2),
+                velocity_km_s: Vector3::new(1e-4, 3e-4, 2e-4),
+            }
+            .to_covar(&nominal)
+            .unwrap(),
+        );
+
+        // Initialize from a trajectory
+        let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+            .with(nominal, almanac.clone())
+            .for_duration_with_traj(1.hours())
+            .unwrap();
+        let from_traj = KfEstimate::from_traj(
+            &traj,
+            nominal.epoch() + 30.minutes(),
+            InitialCovariance::StateFraction(1e-4),
+        )
+        .unwrap();
+        assert_eq!(from_traj.epoch(), nominal.epoch() + 30.minutes());
+        assert!(from_traj.nominal_state.stm.is_none());
+        assert!(KfEstimate::from_traj(
+            &traj,
+            nominal.epoch() + 2.hours(),
+            InitialCovariance::StateFraction(1e-4)
+        )
+        .is_err());
+
+        // Inflating without a time gap scales the sigmas.
+        let inflated = estimate
+            .inflated_to(
+                2.0,
+                nominal.epoch(),
+                CovarianceMapping::Keplerian,
+                almanac.clone(),
+            )
+            .unwrap();
+        assert!((inflated.covar - estimate.covar * 4.0).amax() < 1e-12);
+
+        // Map over a time gap: the SMA uncertainty is conserved by Keplerian motion.
+        let gap_epoch = nominal.epoch() + 3.hours();
+        let mapped = estimate
+            .inflated_to(
+                2.0,
+                gap_epoch,
+                CovarianceMapping::Keplerian,
+                almanac.clone(),
+            )
+            .unwrap();
+        assert_eq!(mapped.epoch(), gap_epoch);
+        let sma_sigma = inflated.sigma_for(StateParameter::SMA).unwrap();
+        let mapped_sma_sigma = mapped.sigma_for(StateParameter::SMA).unwrap();
+        println!("σ_SMA = {sma_sigma} km -> {mapped_sma_sigma} km");
+        assert!((sma_sigma - mapped_sma_sigma).abs() < 1e-6 * sma_sigma);
+        // But the in-track uncertainty grows.
+        assert!(
+            mapped.covar.fixed_view::<3, 3>(0, 0).trace()
+                > inflated.covar.fixed_view::<3, 3>(0, 0).trace()
+        );
+
+        // Mapping with a user provided STM is identical if the dynamics match.
+        let stm_state = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+            .with(nominal.with_stm(), almanac.clone())
+            .until_epoch(gap_epoch)
+            .unwrap();
+        let mapped_stm = estimate
+            .inflated_to(
+                2.0,
+                gap_epoch,
+                CovarianceMapping::Stm(stm_state),
+                almanac.clone(),
+            )
+            .unwrap();
+        assert!((mapped_stm.covar - mapped.covar).amax() < 1e-12);
+
+        // The mapping state must be at the requested epoch.
+        assert!(estimate
+            .inflated_to(
+                2.0,
+                gap_epoch + 1.seconds(),
+                CovarianceMapping::Stm(stm_state),
+                almanac
+            )
+            .is_err());
+    }
+}
@@ -20,15 +20,14 @@ use super::{Estimate, State};
 use crate::cosmic::AstroError;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, DimName, Matrix, OMatrix, OVector};
+use crate::mc::NyxRng;
 use crate::mc::{MultivariateNormal, StateDispersion};
 use crate::md::prelude::OrbitDual;
 use crate::md::StateParameter;
 use crate::Spacecraft;
 use na::SMatrix;
 use nalgebra::Const;
-use rand::SeedableRng;
 use rand_distr::Distribution;
-use rand_pcg::Pcg64Mcg;
 use std::cmp::PartialEq;
 use std::error::Error;
 use std::fmt;
@@ -111,8 +110,8 @@ impl KfEstimate<Spacecraft> {
         let generator = MultivariateNormal::new(nominal_state, dispersions)?;
 
         let mut rng = match seed {
-            Some(seed) => Pcg64Mcg::new(seed),
-            None => Pcg64Mcg::from_entropy(),
+            Some(seed) => NyxRng::from_seed_u128(seed),
+            None => NyxRng::from_entropy(),
         };
         let dispersed_state = generator.sample(&mut rng);
 
@@ -0,0 +1,554 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::Residual;
+use crate::io::watermark::pq_writer;
+use crate::io::{ArrowSnafu, ExportCfg, ParquetSnafu, StdIOSnafu};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DMatrix, DVector, DefaultAllocator, DimName};
+use crate::od::{ODError, ODIOSnafu, TooFewMeasurementsSnafu};
+use crate::time::{Duration, Epoch};
+use arrow::array::{Array, BooleanBuilder, Float64Builder, StringBuilder, UInt64Builder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use snafu::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::TAU;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Number of coefficients of the signature templates: bias, drift, and the cosine and sine of the once and twice per rev terms.
+const NUM_COEFFS: usize = 6;
+
+/// Signature of a dynamical model mismatch in a residual time series.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResidualSignature {
+    /// Constant offset of the residuals
+    Bias,
+    /// Linear trend of the residuals over the arc
+    Drift,
+    /// Sinusoid at the orbital period
+    OncePerRev,
+    /// Sinusoid at half of the orbital period
+    TwicePerRev,
+}
+
+impl ResidualSignature {
+    /// All of the signatures fitted to the residuals
+    pub const ALL: [Self; 4] = [Self::Bias, Self::Drift, Self::OncePerRev, Self::TwicePerRev];
+
+    /// Likely causes of this signature, as read by an analyst on a residual plot
+    pub fn likely_causes(&self) -> &'static str {
+        match self {
+            Self::Bias => "measurement or station bias, transponder delay, or station location error",
+            Self::Drift => "drag or GM error, clock drift, or another unmodeled secular acceleration",
+            Self::OncePerRev => "mismodeled solar radiation pressure or empirical accelerations, or an orbit shape (eccentricity) error",
+            Self::TwicePerRev => "mismodeled gravity field (oblateness), or attitude dependent SRP or thermal forces",
+        }
+    }
+}
+
+impl fmt::Display for ResidualSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bias => write!(f, "bias"),
+            Self::Drift => write!(f, "drift"),
+            Self::OncePerRev => write!(f, "once-per-rev"),
+            Self::TwicePerRev => write!(f, "twice-per-rev"),
+        }
+    }
+}
+
+/// Thresholds above which a signature fitted to the residuals is reported as significant.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MismatchCriteria {
+    /// Minimum ratio of the amplitude of a signature to its formal uncertainty
+    pub min_snr: f64,
+    /// Minimum amplitude of a signature as a fraction of the measurement noise standard deviation of the tracker, such that
+    /// signatures well below the noise (e.g. from integration errors) are not reported
+    pub min_noise_fraction: f64,
+}
+
+impl Default for MismatchCriteria {
+    fn default() -> Self {
+        Self {
+            min_snr: 3.0,
+            min_noise_fraction: 0.1,
+        }
+    }
+}
+
+/// Least squares fit of one signature to a residual time series.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SignatureFit {
+    pub signature: ResidualSignature,
+    /// Amplitude in the unit of the residuals: the value of the bias, the change over the whole arc for the drift, or the
+    /// semi-amplitude of the sinusoids
+    pub amplitude: f64,
+    /// Formal one sigma uncertainty of the amplitude, from the scatter of the residuals about the fit
+    pub sigma: f64,
+    /// Phase of the sinusoids in degrees, zero at the first residual of the series, unset for the bias and drift
+    pub phase_deg: Option<f64>,
+    /// Ratio of the absolute amplitude to its uncertainty
+    pub snr: f64,
+    /// Whether this signature meets the criteria of the analysis
+    pub significant: bool,
+}
+
+impl fmt::Display for SignatureFit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:.3e} ± {:.3e} (SNR {:.1})",
+            self.signature, self.amplitude, self.sigma, self.snr
+        )?;
+        if let Some(phase_deg) = self.phase_deg {
+            write!(f, " at phase {phase_deg:.1} deg")?;
+        }
+        if self.significant {
+            write!(f, " SIGNIFICANT")?;
+        }
+        Ok(())
+    }
+}
+
+/// Signatures fitted to the postfit residuals of one component of the measurements of one tracker.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResidualSeriesAnalysis {
+    pub tracker: String,
+    /// Name of the measurement component, e.g. `Range (km)`
+    pub component: String,
+    pub start: Epoch,
+    pub end: Epoch,
+    pub samples: usize,
+    /// Root mean square of the postfit residuals
+    pub rms: f64,
+    /// Root mean square of the postfit residuals once all of the signatures are removed
+    pub rms_after_fit: f64,
+    /// Standard deviation of the measurement noise of the tracker, as the root mean square of the noise of each residual
+    pub noise_sigma: f64,
+    /// Fits of each signature, in the order of `ResidualSignature::ALL`
+    pub fits: Vec<SignatureFit>,
+}
+
+impl ResidualSeriesAnalysis {
+    /// Signatures meeting the criteria of the analysis
+    pub fn significant(&self) -> impl Iterator<Item = &SignatureFit> {
+        self.fits.iter().filter(|fit| fit.significant)
+    }
+
+    /// Fit of the provided signature
+    pub fn fit(&self, signature: ResidualSignature) -> Option<&SignatureFit> {
+        self.fits.iter().find(|fit| fit.signature == signature)
+    }
+}
+
+impl fmt::Display for ResidualSeriesAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} {}: {} residuals from {} to {}, RMS {:.3e} ({:.3e} once fitted), noise σ {:.3e}",
+            self.tracker,
+            self.component,
+            self.samples,
+            self.start,
+            self.end,
+            self.rms,
+            self.rms_after_fit,
+            self.noise_sigma
+        )?;
+        for fit in &self.fits {
+            writeln!(f, "\t\t{fit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Detection of dynamical model mismatches from the signatures of the postfit residuals of an orbit determination.
+///
+/// The postfit residuals of each component of the measurements of each tracker are fitted in a least squares sense to a
+/// constant bias, a linear drift, and sinusoids at the orbital period and at half of it. The formal uncertainty of each
+/// signature is computed from the scatter of the residuals about the fit, and a signature is significant if it meets the
+/// [MismatchCriteria]. Each signature maps to its likely causes, cf. [ResidualSignature::likely_causes].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelMismatchReport {
+    pub orbital_period: Duration,
+    pub criteria: MismatchCriteria,
+    /// Analyses of each tracker and measurement component, sorted by tracker name
+    pub analyses: Vec<ResidualSeriesAnalysis>,
+}
+
+impl ModelMismatchReport {
+    /// Minimum number of residuals in a series for it to be analyzed
+    pub const MIN_SAMPLES: usize = 2 * NUM_COEFFS;
+
+    /// Analyzes the accepted postfit residuals of an orbit determination (e.g. `ODProcess::residuals`), where `components`
+    /// are the names of the components of the measurements.
+    ///
+    /// Series with fewer than `MIN_SAMPLES` residuals are skipped. Errors if no series can be analyzed.
+    pub fn from_residuals<M: DimName>(
+        residuals: &[Option<Residual<M>>],
+        components: &[String],
+        orbital_period: Duration,
+        criteria: MismatchCriteria,
+    ) -> Result<Self, ODError>
+    where
+        DefaultAllocator: Allocator<M>,
+    {
+        let mut per_tracker = BTreeMap::<String, Vec<&Residual<M>>>::new();
+        for residual in residuals.iter().flatten().filter(|resid| !resid.rejected) {
+            per_tracker
+                .entry(
+                    residual
+                        .tracker
+                        .clone()
+                        .unwrap_or_else(|| "unknown".to_string()),
+                )
+                .or_default()
+                .push(residual);
+        }
+
+        let period_s = orbital_period.to_seconds();
+
+        let mut analyses = Vec::new();
+        for (tracker, series) in per_tracker {
+            if series.len() < Self::MIN_SAMPLES {
+                warn!(
+                    "{tracker}: only {} postfit residuals, need {} to fit the model mismatch signatures",
+                    series.len(),
+                    Self::MIN_SAMPLES
+                );
+                continue;
+            }
+
+            let start = series.first().unwrap().epoch;
+            let end = series.last().unwrap().epoch;
+            let half_span_s = (end - start).to_seconds() / 2.0;
+            if half_span_s <= 0.0 {
+                warn!("{tracker}: all postfit residuals share the epoch {start}, cannot fit the model mismatch signatures");
+                continue;
+            }
+
+            // Design matrix of the templates, with the drift over a centered and normalized time to decouple it from the bias
+            let design = DMatrix::from_fn(series.len(), NUM_COEFFS, |i, j| {
+                let dt_s = (series[i].epoch - start).to_seconds();
+                let theta = TAU * dt_s / period_s;
+                match j {
+                    0 => 1.0,
+                    1 => (dt_s - half_span_s) / half_span_s,
+                    2 => theta.cos(),
+                    3 => theta.sin(),
+                    4 => (2.0 * theta).cos(),
+                    _ => (2.0 * theta).sin(),
+                }
+            });
+
+            let normal_inv = match (design.transpose() * &design).try_inverse() {
+                Some(normal_inv) => normal_inv,
+                None => {
+                    warn!("{tracker}: cannot separate the model mismatch signatures, residual arc too short");
+                    continue;
+                }
+            };
+
+            for (k, component) in components.iter().enumerate().take(M::dim()) {
+                let ys = DVector::from_iterator(
+                    series.len(),
+                    series.iter().map(|resid| resid.postfit[k]),
+                );
+                let coeffs = &normal_inv * design.transpose() * &ys;
+                let post = &ys - &design * &coeffs;
+
+                let dof = (series.len() - NUM_COEFFS) as f64;
+                let scatter = post.norm_squared() / dof;
+                let covar = &normal_inv * scatter;
+
+                let rms = (ys.norm_squared() / series.len() as f64).sqrt();
+                let rms_after_fit = (post.norm_squared() / series.len() as f64).sqrt();
+                // The tracker noise of the residuals is a standard deviation: average its variance
+                let noise_sigma = (series
+                    .iter()
+                    .map(|resid| resid.tracker_msr_noise[k].powi(2))
+                    .sum::<f64>()
+                    / series.len() as f64)
+                    .sqrt();
+
+                let fits = ResidualSignature::ALL
+                    .iter()
+                    .map(|signature| {
+                        let (amplitude, sigma, phase_deg) = match signature {
+                            ResidualSignature::Bias => (coeffs[0], covar[(0, 0)].sqrt(), None),
+                            // Change over the whole arc, since the normalized time spans from -1 to 1
+                            ResidualSignature::Drift => {
+                                (2.0 * coeffs[1], 2.0 * covar[(1, 1)].sqrt(), None)
+                            }
+                            ResidualSignature::OncePerRev => sinusoid(&coeffs, &covar, 2),
+                            ResidualSignature::TwicePerRev => sinusoid(&coeffs, &covar, 4),
+                        };
+
+                        let snr = if sigma > 0.0 {
+                            amplitude.abs() / sigma
+                        } else if amplitude != 0.0 {
+                            f64::INFINITY
+                        } else {
+                            0.0
+                        };
+
+                        SignatureFit {
+                            signature: *signature,
+                            amplitude,
+                            sigma,
+                            phase_deg,
+                            snr,
+                            significant: snr >= criteria.min_snr
+                                && amplitude.abs() >= criteria.min_noise_fraction * noise_sigma,
+                        }
+                    })
+                    .collect();
+
+                analyses.push(ResidualSeriesAnalysis {
+                    tracker: tracker.clone(),
+                    component: component.clone(),
+                    start,
+                    end,
+                    samples: series.len(),
+                    rms,
+                    rms_after_fit,
+                    noise_sigma,
+                    fits,
+                });
+            }
+        }
+
+        ensure!(
+            !analyses.is_empty(),
+            TooFewMeasurementsSnafu {
+                need: Self::MIN_SAMPLES,
+                action: "fitting model mismatch signatures to the residuals of any tracker",
+            }
+        );
+
+        let me = Self {
+            orbital_period,
+            criteria,
+            analyses,
+        };
+        info!("{me}");
+        Ok(me)
+    }
+
+    /// Whether any signature of any tracker is significant
+    pub fn mismatch_detected(&self) -> bool {
+        self.analyses
+            .iter()
+            .any(|analysis| analysis.significant().next().is_some())
+    }
+
+    /// Signatures which are significant for at least one tracker, in the order of `ResidualSignature::ALL`
+    pub fn significant_signatures(&self) -> Vec<ResidualSignature> {
+        ResidualSignature::ALL
+            .into_iter()
+            .filter(|signature| {
+                self.analyses.iter().any(|analysis| {
+                    analysis
+                        .fit(*signature)
+                        .map(|fit| fit.significant)
+                        .unwrap_or(false)
+                })
+            })
+            .collect()
+    }
+
+    /// Store the fit of every signature of every tracker and component in a parquet file
+    pub fn to_parquet<P: AsRef<Path>>(&self, path: P, cfg: ExportCfg) -> Result<PathBuf, ODError> {
+        let path_buf = cfg.actual_path(path);
+
+        let hdrs = vec![
+            Field::new("Tracker", DataType::Utf8, false),
+            Field::new("Component", DataType::Utf8, false),
+            Field::new("Start (UTC)", DataType::Utf8, false),
+            Field::new("End (UTC)", DataType::Utf8, false),
+            Field::new("Samples", DataType::UInt64, false),
+            Field::new("Signature", DataType::Utf8, false),
+            Field::new("Amplitude", DataType::Float64, false),
+            Field::new("Sigma", DataType::Float64, false),
+            Field::new("Phase (deg)", DataType::Float64, true),
+            Field::new("SNR", DataType::Float64, false),
+            Field::new("Noise sigma", DataType::Float64, false),
+            Field::new("Significant", DataType::Boolean, false),
+            Field::new("Likely causes", DataType::Utf8, false),
+        ];
+
+        let mut tracker_col = StringBuilder::new();
+        let mut component_col = StringBuilder::new();
+        let mut start_col = StringBuilder::new();
+        let mut end_col = StringBuilder::new();
+        let mut samples_col = UInt64Builder::new();
+        let mut signature_col = StringBuilder::new();
+        let mut amplitude_col = Float64Builder::new();
+        let mut sigma_col = Float64Builder::new();
+        let mut phase_col = Float64Builder::new();
+        let mut snr_col = Float64Builder::new();
+        let mut noise_col = Float64Builder::new();
+        let mut significant_col = BooleanBuilder::new();
+        let mut causes_col = StringBuilder::new();
+
+        for analysis in &self.analyses {
+            for fit in &analysis.fits {
+                tracker_col.append_value(&analysis.tracker);
+                component_col.append_value(&analysis.component);
+                start_col.append_value(analysis.start.to_isoformat());
+                end_col.append_value(analysis.end.to_isoformat());
+                samples_col.append_value(analysis.samples as u64);
+                signature_col.append_value(fit.signature.to_string());
+                amplitude_col.append_value(fit.amplitude);
+                sigma_col.append_value(fit.sigma);
+                phase_col.append_option(fit.phase_deg);
+                snr_col.append_value(fit.snr);
+                noise_col.append_value(analysis.noise_sigma);
+                significant_col.append_value(fit.significant);
+                causes_col.append_value(fit.signature.likely_causes());
+            }
+        }
+
+        let record: Vec<Arc<dyn Array>> = vec![
+            Arc::new(tracker_col.finish()),
+            Arc::new(component_col.finish()),
+            Arc::new(start_col.finish()),
+            Arc::new(end_col.finish()),
+            Arc::new(samples_col.finish()),
+            Arc::new(signature_col.finish()),
+            Arc::new(amplitude_col.finish()),
+            Arc::new(sigma_col.finish()),
+            Arc::new(phase_col.finish()),
+            Arc::new(snr_col.finish()),
+            Arc::new(noise_col.finish()),
+            Arc::new(significant_col.finish()),
+            Arc::new(causes_col.finish()),
+        ];
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "OD model mismatch signatures".to_string(),
+        );
+        metadata.insert(
+            "Orbital period".to_string(),
+            self.orbital_period.to_string(),
+        );
+        metadata.insert("Minimum SNR".to_string(), self.criteria.min_snr.to_string());
+        metadata.insert(
+            "Minimum noise fraction".to_string(),
+            self.criteria.min_noise_fraction.to_string(),
+        );
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata));
+
+        let schema = Arc::new(Schema::new(hdrs));
+
+        let file = File::create(&path_buf)
+            .context(StdIOSnafu {
+                action: "creating model mismatch report file",
+            })
+            .context(ODIOSnafu)?;
+
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)
+            .context(ParquetSnafu {
+                action: "exporting model mismatch report",
+            })
+            .context(ODIOSnafu)?;
+
+        let batch = RecordBatch::try_new(schema, record)
+            .context(ArrowSnafu {
+                action: "writing model mismatch report (building batch record)",
+            })
+            .context(ODIOSnafu)?;
+
+        writer
+            .write(&batch)
+            .context(ParquetSnafu {
+                action: "writing model mismatch report",
+            })
+            .context(ODIOSnafu)?;
+
+        writer
+            .close()
+            .context(ParquetSnafu {
+                action: "closing model mismatch report file",
+            })
+            .context(ODIOSnafu)?;
+
+        info!("Model mismatch report written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for ModelMismatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Model mismatch from postfit residuals (orbital period {}): {}",
+            self.orbital_period,
+            if self.mismatch_detected() {
+                "DETECTED"
+            } else {
+                "none significant"
+            }
+        )?;
+        for analysis in &self.analyses {
+            write!(f, "\t{analysis}")?;
+        }
+        let significant = self.significant_signatures();
+        if !significant.is_empty() {
+            writeln!(f, "\tLikely causes:")?;
+            for signature in significant {
+                writeln!(f, "\t\t{signature}: {}", signature.likely_causes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Semi-amplitude, its uncertainty, and phase in degrees of the sinusoid whose cosine coefficient is at `idx` and sine at `idx + 1`.
+fn sinusoid(coeffs: &DVector<f64>, covar: &DMatrix<f64>, idx: usize) -> (f64, f64, Option<f64>) {
+    let (c, s) = (coeffs[idx], coeffs[idx + 1]);
+    let amplitude = c.hypot(s);
+    let (var_c, var_s, cov_cs) = (
+        covar[(idx, idx)],
+        covar[(idx + 1, idx + 1)],
+        covar[(idx, idx + 1)],
+    );
+    let variance = if amplitude > 0.0 {
+        (c.powi(2) * var_c + s.powi(2) * var_s + 2.0 * c * s * cov_cs) / amplitude.powi(2)
+    } else {
+        (var_c + var_s) / 2.0
+    };
+    (
+        amplitude,
+        variance.max(0.0).sqrt(),
+        Some(s.atan2(c).to_degrees()),
+    )
+}
@@ -31,6 +31,19 @@ pub mod kfestimate;
 pub use kfestimate::KfEstimate;
 mod sc_uncertainty;
 pub use sc_uncertainty::SpacecraftUncertainty;
+mod init_covar;
+pub use init_covar::{CovarianceMapping, InitialCovariance};
+mod truth;
+pub use truth::{CovarianceConsistency, TruthComparison, TruthEpochComparison};
+mod mismatch;
+pub use mismatch::{
+    MismatchCriteria, ModelMismatchReport, ResidualSeriesAnalysis, ResidualSignature, SignatureFit,
+};
+mod requirements;
+pub use requirements::{
+    CovarianceRequirement, RequirementEpochs, RequirementEvaluation, RequirementResult,
+    RequirementsReport, UncertaintyKind, UncertaintyThreshold,
+};
 
 /// Stores an Estimate, as the result of a `time_update` or `measurement_update`.
 pub trait Estimate<T: State>
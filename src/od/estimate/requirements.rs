@@ -0,0 +1,543 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::Estimate;
+use crate::dynamics::guidance::LocalFrame;
+use crate::dynamics::SpacecraftDynamics;
+use crate::io::watermark::pq_writer;
+use crate::io::{ArrowSnafu, ExportCfg, ParquetSnafu, StdIOSnafu};
+use crate::linalg::{Matrix6, Vector3};
+use crate::od::{
+    ODDynamicsSnafu, ODError, ODIOSnafu, ODPhysicsSnafu, ODPropSnafu, TooFewMeasurementsSnafu,
+};
+use crate::propagators::Propagator;
+use crate::time::Epoch;
+use crate::{Spacecraft, State};
+use anise::prelude::Almanac;
+use arrow::array::{Array, BooleanBuilder, Float64Builder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use csv::WriterBuilder;
+use parquet::arrow::ArrowWriter;
+use snafu::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+/// Epochs at which a covariance requirement applies.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RequirementEpochs {
+    /// At a single epoch, e.g. the delivery epoch
+    At(Epoch),
+    /// At both bounds of this span and at every estimate within it
+    Span { start: Epoch, end: Epoch },
+}
+
+impl fmt::Display for RequirementEpochs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::At(epoch) => write!(f, "at {epoch}"),
+            Self::Span { start, end } => write!(f, "from {start} to {end}"),
+        }
+    }
+}
+
+/// Part of the covariance constrained by a requirement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UncertaintyKind {
+    /// Position uncertainty, in km
+    Position,
+    /// Velocity uncertainty, in km/s
+    Velocity,
+}
+
+impl UncertaintyKind {
+    /// Unit of the sigmas and thresholds
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Position => "km",
+            Self::Velocity => "km/s",
+        }
+    }
+
+    /// Index of the first component of this kind in the orbital covariance
+    fn offset(&self) -> usize {
+        match self {
+            Self::Position => 0,
+            Self::Velocity => 3,
+        }
+    }
+}
+
+/// Maximum scaled sigma allowed by a requirement, in km or km/s depending on the kind of requirement.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UncertaintyThreshold {
+    /// Maximum scaled sigma on each axis of the frame of the requirement
+    PerAxis([f64; 3]),
+    /// Maximum scaled root-sum-square of the sigmas of all three axes
+    Norm(f64),
+}
+
+impl fmt::Display for UncertaintyThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PerAxis([x, y, z]) => write!(f, "[{x}, {y}, {z}]"),
+            Self::Norm(norm) => write!(f, "{norm} (RSS)"),
+        }
+    }
+}
+
+/// Requirement on the uncertainty of an orbit determination solution, e.g. "3-sigma position uncertainty in RIC shall be below
+/// 100 m / 500 m / 100 m at the delivery epoch".
+#[derive(Clone, Debug, PartialEq, TypedBuilder)]
+#[builder(doc)]
+pub struct CovarianceRequirement {
+    /// Name of this requirement, as reported in the exports
+    #[builder(setter(into))]
+    pub name: String,
+    pub epochs: RequirementEpochs,
+    /// Frame in which the sigmas are computed
+    #[builder(default = LocalFrame::RIC)]
+    pub frame: LocalFrame,
+    #[builder(default = UncertaintyKind::Position)]
+    pub kind: UncertaintyKind,
+    pub threshold: UncertaintyThreshold,
+    /// The sigmas are multiplied by this factor before being compared to the threshold
+    #[builder(default = 3.0)]
+    pub sigma_multiple: f64,
+}
+
+impl fmt::Display for CovarianceRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}σ {:?} uncertainty ({:?}) ≤ {} {} {}",
+            self.name,
+            self.sigma_multiple,
+            self.kind,
+            self.frame,
+            self.threshold,
+            self.kind.unit(),
+            self.epochs
+        )
+    }
+}
+
+/// Uncertainty of the OD solution at an epoch where a requirement applies.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RequirementEvaluation {
+    pub epoch: Epoch,
+    /// Sigmas of each axis multiplied by the sigma multiple of the requirement, in km or km/s
+    pub scaled_sigmas: Vector3<f64>,
+    /// Root-sum-square of the scaled sigmas, in km or km/s
+    pub scaled_rss: f64,
+    /// Threshold minus scaled sigma of the governing axis (or of the RSS), in km or km/s, negative if the requirement is not met
+    pub margin: f64,
+    /// Margin in percent of the threshold of the governing axis; the governing axis is the one with the smallest percentage
+    pub margin_prct: f64,
+    /// Epoch of the estimate whose covariance was mapped with the STM to this epoch, if no estimate is exactly at this epoch
+    pub mapped_from: Option<Epoch>,
+}
+
+impl RequirementEvaluation {
+    /// Whether the requirement is met at this epoch
+    pub fn passed(&self) -> bool {
+        self.margin >= 0.0
+    }
+}
+
+/// Evaluation of a requirement at each epoch where it applies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequirementResult {
+    pub requirement: CovarianceRequirement,
+    /// Evaluations in chronological order
+    pub evaluations: Vec<RequirementEvaluation>,
+}
+
+impl RequirementResult {
+    /// Evaluation with the smallest margin, in percent of the threshold
+    pub fn worst(&self) -> &RequirementEvaluation {
+        self.evaluations
+            .iter()
+            .min_by(|a, b| a.margin_prct.total_cmp(&b.margin_prct))
+            .expect("requirement evaluated at no epoch")
+    }
+
+    /// Whether the requirement is met at every epoch where it applies
+    pub fn passed(&self) -> bool {
+        self.evaluations.iter().all(|eval| eval.passed())
+    }
+}
+
+impl fmt::Display for RequirementResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let worst = self.worst();
+        write!(
+            f,
+            "[{}] {}: worst margin of {:.6} {} ({:.2} %) at {}",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.requirement,
+            worst.margin,
+            self.requirement.kind.unit(),
+            worst.margin_prct,
+            worst.epoch
+        )
+    }
+}
+
+/// Pass/fail report of covariance requirements evaluated against the estimates of an orbit determination, e.g. for delivery
+/// documentation.
+///
+/// Requirements are evaluated at the covariance of the estimate at that epoch, if any. Otherwise, the covariance of the nearest
+/// estimate (before or after) is mapped to that epoch with the orbital STM of the provided dynamics, as `P' = Φ P Φ^T`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequirementsReport {
+    pub results: Vec<RequirementResult>,
+}
+
+impl RequirementsReport {
+    /// Evaluates the requirements against the provided estimates, sorted chronologically (e.g. those of an OD process).
+    ///
+    /// Errors if there are no estimates, or if the mapping of a covariance fails.
+    pub fn new<E: Estimate<Spacecraft>>(
+        requirements: &[CovarianceRequirement],
+        estimates: &[E],
+        dynamics: SpacecraftDynamics,
+        almanac: Arc<Almanac>,
+    ) -> Result<Self, ODError> {
+        ensure!(
+            !estimates.is_empty(),
+            TooFewMeasurementsSnafu {
+                need: 1_usize,
+                action: "evaluating covariance requirements",
+            }
+        );
+
+        let prop = Propagator::default(dynamics);
+        let (first, last) = (
+            estimates.first().unwrap().epoch(),
+            estimates.last().unwrap().epoch(),
+        );
+
+        let mut results = Vec::with_capacity(requirements.len());
+        for requirement in requirements {
+            let epochs = match requirement.epochs {
+                RequirementEpochs::At(epoch) => vec![epoch],
+                RequirementEpochs::Span { start, end } => {
+                    let mut epochs = vec![start];
+                    epochs.extend(
+                        estimates
+                            .iter()
+                            .map(|est| est.epoch())
+                            .filter(|epoch| *epoch > start && *epoch < end),
+                    );
+                    epochs.push(end);
+                    epochs.dedup();
+                    epochs
+                }
+            };
+
+            let mut evaluations = Vec::with_capacity(epochs.len());
+            for epoch in epochs {
+                if epoch < first || epoch > last {
+                    warn!(
+                        "{}: {epoch} outside of the estimates from {first} to {last}, the covariance is extrapolated",
+                        requirement.name
+                    );
+                }
+
+                // The last of the nearest estimates, i.e. the measurement update if it shares its epoch with the time update
+                let nearest = estimates
+                    .iter()
+                    .rev()
+                    .min_by_key(|est| (est.epoch() - epoch).abs())
+                    .unwrap();
+
+                let (orbit, covar, mapped_from) = if nearest.epoch() == epoch {
+                    let covar: Matrix6<f64> = nearest.covar().fixed_view::<6, 6>(0, 0).into_owned();
+                    (nearest.state().orbit, covar, None)
+                } else {
+                    let mapped = prop
+                        .with(nearest.state().with_stm(), almanac.clone())
+                        .until_epoch(epoch)
+                        .context(ODPropSnafu)?;
+                    let stm = mapped.orbit_stm().context(ODDynamicsSnafu)?;
+                    let covar = stm * nearest.covar().fixed_view::<6, 6>(0, 0) * stm.transpose();
+                    (mapped.orbit, covar, Some(nearest.epoch()))
+                };
+
+                let dcm = requirement
+                    .frame
+                    .dcm_to_inertial(orbit)
+                    .context(ODPhysicsSnafu)?
+                    .state_dcm();
+                let local_covar = dcm.transpose() * covar * dcm;
+                let offset = requirement.kind.offset();
+                let scaled_sigmas = Vector3::from_fn(|i, _| {
+                    local_covar[(offset + i, offset + i)].sqrt() * requirement.sigma_multiple
+                });
+                let scaled_rss = scaled_sigmas.norm();
+
+                let (margin, margin_prct) = match requirement.threshold {
+                    UncertaintyThreshold::PerAxis(thresholds) => (0..3)
+                        .map(|i| {
+                            let margin = thresholds[i] - scaled_sigmas[i];
+                            (margin, 100.0 * margin / thresholds[i])
+                        })
+                        .min_by(|a, b| a.1.total_cmp(&b.1))
+                        .unwrap(),
+                    UncertaintyThreshold::Norm(threshold) => {
+                        let margin = threshold - scaled_rss;
+                        (margin, 100.0 * margin / threshold)
+                    }
+                };
+
+                evaluations.push(RequirementEvaluation {
+                    epoch,
+                    scaled_sigmas,
+                    scaled_rss,
+                    margin,
+                    margin_prct,
+                    mapped_from,
+                });
+            }
+
+            results.push(RequirementResult {
+                requirement: requirement.clone(),
+                evaluations,
+            });
+        }
+
+        let me = Self { results };
+        info!("{me}");
+        Ok(me)
+    }
+
+    /// Whether all of the requirements are met
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed())
+    }
+
+    /// Store every evaluation of every requirement in a parquet file
+    pub fn to_parquet<P: AsRef<Path>>(&self, path: P, cfg: ExportCfg) -> Result<PathBuf, ODError> {
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = vec![Field::new("Requirement", DataType::Utf8, false)];
+        hdrs.extend(cfg.epoch_fields());
+        hdrs.push(Field::new("Unit", DataType::Utf8, false));
+        for name in [
+            "Scaled sigma 1",
+            "Scaled sigma 2",
+            "Scaled sigma 3",
+            "Scaled sigma RSS",
+            "Margin",
+            "Margin (%)",
+        ] {
+            hdrs.push(Field::new(name, DataType::Float64, false));
+        }
+        hdrs.push(Field::new("Mapped from", DataType::Utf8, true));
+        hdrs.push(Field::new("Pass", DataType::Boolean, false));
+
+        let evaluations = self
+            .results
+            .iter()
+            .flat_map(|result| {
+                result
+                    .evaluations
+                    .iter()
+                    .map(move |eval| (&result.requirement, eval))
+            })
+            .collect::<Vec<_>>();
+
+        let mut name_col = StringBuilder::new();
+        let mut unit_col = StringBuilder::new();
+        let mut mapped_col = StringBuilder::new();
+        let mut pass_col = BooleanBuilder::new();
+        for (requirement, eval) in &evaluations {
+            name_col.append_value(&requirement.name);
+            unit_col.append_value(requirement.kind.unit());
+            match eval.mapped_from {
+                Some(epoch) => mapped_col.append_value(epoch.to_string()),
+                None => mapped_col.append_null(),
+            }
+            pass_col.append_value(eval.passed());
+        }
+
+        let mut record: Vec<Arc<dyn Array>> = vec![Arc::new(name_col.finish())];
+        record.extend(cfg.epoch_columns(evaluations.iter().map(|(_, eval)| eval.epoch)));
+        record.push(Arc::new(unit_col.finish()));
+
+        let columns: [fn(&RequirementEvaluation) -> f64; 6] = [
+            |eval| eval.scaled_sigmas[0],
+            |eval| eval.scaled_sigmas[1],
+            |eval| eval.scaled_sigmas[2],
+            |eval| eval.scaled_rss,
+            |eval| eval.margin,
+            |eval| eval.margin_prct,
+        ];
+        for column in columns {
+            let mut data = Float64Builder::new();
+            for (_, eval) in &evaluations {
+                data.append_value(column(eval));
+            }
+            record.push(Arc::new(data.finish()));
+        }
+        record.push(Arc::new(mapped_col.finish()));
+        record.push(Arc::new(pass_col.finish()));
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "OD covariance requirements".to_string(),
+        );
+        metadata.insert(
+            "Verdict".to_string(),
+            if self.passed() { "PASS" } else { "FAIL" }.to_string(),
+        );
+        for result in &self.results {
+            metadata.insert(
+                format!("Requirement {}", result.requirement.name),
+                result.requirement.to_string(),
+            );
+        }
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata));
+
+        let schema = Arc::new(Schema::new(hdrs));
+
+        let file = File::create(&path_buf)
+            .context(StdIOSnafu {
+                action: "creating requirements report file",
+            })
+            .context(ODIOSnafu)?;
+
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)
+            .context(ParquetSnafu {
+                action: "exporting requirements report",
+            })
+            .context(ODIOSnafu)?;
+
+        let batch = RecordBatch::try_new(schema, record)
+            .context(ArrowSnafu {
+                action: "writing requirements report (building batch record)",
+            })
+            .context(ODIOSnafu)?;
+
+        writer
+            .write(&batch)
+            .context(ParquetSnafu {
+                action: "writing requirements report",
+            })
+            .context(ODIOSnafu)?;
+
+        writer
+            .close()
+            .context(ParquetSnafu {
+                action: "closing requirements report file",
+            })
+            .context(ODIOSnafu)?;
+
+        info!("Requirements report written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+
+    /// Exports the summary of this report to a CSV file, with one row per requirement at the epoch of its worst margin.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, ODError> {
+        let path_buf = path.as_ref().to_path_buf();
+
+        let write = || -> Result<(), csv::Error> {
+            let mut wtr = WriterBuilder::new().from_path(&path_buf)?;
+
+            wtr.write_record([
+                "Requirement",
+                "Kind",
+                "Frame",
+                "Sigma multiple",
+                "Threshold",
+                "Unit",
+                "Worst epoch (UTC)",
+                "Scaled sigma 1",
+                "Scaled sigma 2",
+                "Scaled sigma 3",
+                "Scaled sigma RSS",
+                "Margin",
+                "Margin (%)",
+                "Verdict",
+            ])?;
+
+            for result in &self.results {
+                let requirement = &result.requirement;
+                let worst = result.worst();
+                wtr.write_record([
+                    requirement.name.clone(),
+                    format!("{:?}", requirement.kind),
+                    format!("{:?}", requirement.frame),
+                    format!("{}", requirement.sigma_multiple),
+                    format!("{}", requirement.threshold),
+                    requirement.kind.unit().to_string(),
+                    worst.epoch.to_isoformat(),
+                    format!("{}", worst.scaled_sigmas[0]),
+                    format!("{}", worst.scaled_sigmas[1]),
+                    format!("{}", worst.scaled_sigmas[2]),
+                    format!("{}", worst.scaled_rss),
+                    format!("{}", worst.margin),
+                    format!("{}", worst.margin_prct),
+                    if result.passed() { "PASS" } else { "FAIL" }.to_string(),
+                ])?;
+            }
+
+            wtr.flush()?;
+            Ok(())
+        };
+
+        write()
+            .map_err(io::Error::from)
+            .context(StdIOSnafu {
+                action: "writing requirements report to CSV",
+            })
+            .context(ODIOSnafu)?;
+
+        info!("Requirements summary written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for RequirementsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Covariance requirements: {} ({} of {} met)",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.results.iter().filter(|result| result.passed()).count(),
+            self.results.len()
+        )?;
+        for result in &self.results {
+            writeln!(f, "\t{result}")?;
+        }
+        Ok(())
+    }
+}
@@ -16,8 +16,11 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::cosmic::Orbit;
+use crate::dynamics::guidance::LocalFrame;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName, OVector};
+use crate::linalg::{DMatrix, DVector, DefaultAllocator, DimName, OMatrix, OVector, Vector6};
+use anise::astro::PhysicsResult;
 use hifitime::Epoch;
 use std::fmt;
 
@@ -44,6 +47,13 @@ where
     pub rejected: bool,
     /// Name of the tracker that caused this residual
     pub tracker: Option<String>,
+    /// The scale factor applied to the measurement noise covariance of each component, one if the tracker was not weighted.
+    pub weight: OVector<f64, M>,
+    /// The prefit residual mapped into a position and velocity deviation (km and km/s) in the integration frame,
+    /// computed as the minimum norm solution of `H δx = r` from the orbital part of the sensitivity matrix `H`.
+    pub mapped_prefit: Option<Vector6<f64>>,
+    /// The postfit residual mapped into a position and velocity deviation (km and km/s) in the integration frame, unset if the measurement was rejected.
+    pub mapped_postfit: Option<Vector6<f64>>,
 }
 
 impl<M> Residual<M>
@@ -61,6 +71,9 @@ where
             ratio: 0.0,
             rejected: true,
             tracker: None,
+            weight: OVector::<f64, M>::repeat(1.0),
+            mapped_prefit: None,
+            mapped_postfit: None,
         }
     }
 
@@ -79,6 +92,9 @@ where
             tracker_msr_noise: tracker_msr_covar.map(|x| x.sqrt()),
             rejected: true,
             tracker: None,
+            weight: OVector::<f64, M>::repeat(1.0),
+            mapped_prefit: None,
+            mapped_postfit: None,
         }
     }
 
@@ -97,10 +113,54 @@ where
             tracker_msr_noise: tracker_msr_covar.map(|x| x.sqrt()),
             rejected: false,
             tracker: None,
+            weight: OVector::<f64, M>::repeat(1.0),
+            mapped_prefit: None,
+            mapped_postfit: None,
         }
     }
 }
 
+impl<M> Residual<M>
+where
+    M: DimName,
+    DefaultAllocator: Allocator<M>,
+{
+    /// Sets the mapped prefit and postfit residuals from the sensitivity matrix, whose first six columns must be the partials with respect to the position and velocity.
+    pub(crate) fn map_with_sensitivity<S: DimName>(&mut self, h_tilde: &OMatrix<f64, M, S>)
+    where
+        DefaultAllocator: Allocator<M, S>,
+    {
+        let h_orbit = DMatrix::from_fn(M::dim(), 6, |i, j| h_tilde[(i, j)]);
+        match h_orbit.pseudo_inverse(f64::EPSILON) {
+            Ok(h_pinv) => {
+                let map = |resid: &OVector<f64, M>| {
+                    Vector6::from_column_slice(
+                        (&h_pinv * DVector::from_column_slice(resid.as_slice())).as_slice(),
+                    )
+                };
+                self.mapped_prefit = Some(map(&self.prefit));
+                if !self.rejected {
+                    self.mapped_postfit = Some(map(&self.postfit));
+                }
+            }
+            Err(e) => warn!("cannot map residual at {}: {e}", self.epoch),
+        }
+    }
+
+    /// Returns the mapped prefit and postfit residuals rotated from the integration frame into the provided local frame of the nominal orbit.
+    pub fn mapped_in(
+        &self,
+        frame: LocalFrame,
+        nominal: Orbit,
+    ) -> PhysicsResult<(Option<Vector6<f64>>, Option<Vector6<f64>>)> {
+        let dcm_inertial2local = frame.dcm_to_inertial(nominal)?.transpose().state_dcm();
+        Ok((
+            self.mapped_prefit.map(|resid| dcm_inertial2local * resid),
+            self.mapped_postfit.map(|resid| dcm_inertial2local * resid),
+        ))
+    }
+}
+
 impl<M> fmt::Display for Residual<M>
 where
     M: DimName,
@@ -0,0 +1,327 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::Estimate;
+use crate::io::watermark::pq_writer;
+use crate::io::{ArrowSnafu, ExportCfg, ParquetSnafu, StdIOSnafu};
+use crate::linalg::Vector6;
+use crate::md::trajectory::Traj;
+use crate::od::{ODAlmanacSnafu, ODError, ODIOSnafu, ODTrajSnafu, TooFewMeasurementsSnafu};
+use crate::time::Epoch;
+use crate::{Spacecraft, State};
+use anise::prelude::Almanac;
+use arrow::array::{Array, Float64Builder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use snafu::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Names of the RIC components, position first and then velocity.
+const RIC_COMPONENTS: [&str; 6] = ["R", "I", "C", "VR", "VI", "VC"];
+
+/// Verdict of the consistency of the estimated covariance with the actual errors of the estimates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CovarianceConsistency {
+    /// The errors are statistically consistent with the formal sigmas
+    Consistent,
+    /// The covariance is too small for the actual errors: the filter is overconfident
+    Optimistic,
+    /// The covariance is much larger than the actual errors: the filter is underconfident
+    Pessimistic,
+}
+
+impl fmt::Display for CovarianceConsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Consistent => write!(f, "consistent"),
+            Self::Optimistic => write!(f, "optimistic (covariance too small)"),
+            Self::Pessimistic => write!(f, "pessimistic (covariance too large)"),
+        }
+    }
+}
+
+/// Error of an estimate with respect to the truth, expressed in the RIC frame of the estimate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TruthEpochComparison {
+    /// Epoch of the estimate
+    pub epoch: Epoch,
+    /// Estimate minus truth in the RIC frame, in km and km/s
+    pub ric_error: Vector6<f64>,
+    /// Formal 1-sigma of the estimate in the RIC frame, in km and km/s
+    pub ric_sigma: Vector6<f64>,
+}
+
+impl TruthEpochComparison {
+    /// Returns the error-to-formal-sigma ratio of each RIC component
+    pub fn sigma_ratios(&self) -> Vector6<f64> {
+        self.ric_error.component_div(&self.ric_sigma)
+    }
+}
+
+/// Statistical comparison of OD estimates with an external truth trajectory, e.g. a precise ephemeris.
+///
+/// The covariance is deemed optimistic if any RIC component is within its 3-sigma bounds less than 90% of the time, or if the
+/// RMS of its error-to-sigma ratios is above 2. It is deemed pessimistic if the RMS of the ratios is below 0.3 on all components.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruthComparison {
+    /// Per epoch comparisons, only at the epochs of the estimates within the span of the truth
+    pub epochs: Vec<TruthEpochComparison>,
+    /// RMS of the errors in the RIC frame, in km and km/s
+    pub rms_error: Vector6<f64>,
+    /// RMS of the error-to-formal-sigma ratios of each RIC component
+    pub rms_sigma_ratio: Vector6<f64>,
+    /// Percentage of epochs where the error of each RIC component is within 1 sigma
+    pub within_1sigma_prct: Vector6<f64>,
+    /// Percentage of epochs where the error of each RIC component is within 2 sigma
+    pub within_2sigma_prct: Vector6<f64>,
+    /// Percentage of epochs where the error of each RIC component is within 3 sigma
+    pub within_3sigma_prct: Vector6<f64>,
+    /// Covariance consistency verdict
+    pub verdict: CovarianceConsistency,
+}
+
+impl TruthComparison {
+    /// Compares the provided estimates (e.g. those of an OD process) with the truth trajectory.
+    ///
+    /// The truth is converted into the frame of each estimate if they differ, and estimates outside of the span of the
+    /// truth are ignored. Returns an error if none of the estimates overlap with the truth.
+    pub fn new<E: Estimate<Spacecraft>>(
+        estimates: &[E],
+        truth: &Traj<Spacecraft>,
+        almanac: Arc<Almanac>,
+    ) -> Result<Self, ODError> {
+        let (start, end) = (truth.first().epoch(), truth.last().epoch());
+
+        let mut epochs = Vec::with_capacity(estimates.len());
+        for estimate in estimates
+            .iter()
+            .filter(|est| (start..=end).contains(&est.epoch()))
+        {
+            let est_orbit = estimate.state().orbit();
+            let mut truth_orbit = truth.at(estimate.epoch()).context(ODTrajSnafu)?.orbit();
+            if truth_orbit.frame != est_orbit.frame {
+                truth_orbit = almanac
+                    .transform_to(truth_orbit, est_orbit.frame, None)
+                    .context(ODAlmanacSnafu {
+                        action: "converting the truth into the frame of the estimate",
+                    })?;
+            }
+
+            let dcm = est_orbit
+                .dcm_from_ric_to_inertial()
+                .map_err(|source| ODError::InvalidCovariance {
+                    msg: format!("cannot compute the RIC frame: {source}"),
+                })?
+                .state_dcm();
+
+            let error = est_orbit.to_cartesian_pos_vel() - truth_orbit.to_cartesian_pos_vel();
+            let ric_covar = dcm.transpose() * estimate.covar().fixed_view::<6, 6>(0, 0) * dcm;
+
+            epochs.push(TruthEpochComparison {
+                epoch: estimate.epoch(),
+                ric_error: dcm.transpose() * error,
+                ric_sigma: ric_covar.diagonal().map(|var| var.sqrt()),
+            });
+        }
+
+        ensure!(
+            !epochs.is_empty(),
+            TooFewMeasurementsSnafu {
+                need: 1_usize,
+                action: "comparing estimates to a truth trajectory",
+            }
+        );
+
+        if epochs.len() < estimates.len() {
+            warn!(
+                "{} estimates outside of the truth span from {start} to {end} ignored",
+                estimates.len() - epochs.len()
+            );
+        }
+
+        let num = epochs.len() as f64;
+        let mut rms_error = Vector6::zeros();
+        let mut rms_sigma_ratio = Vector6::zeros();
+        let mut within_prct = [Vector6::zeros(); 3];
+        for cmp in &epochs {
+            let ratios = cmp.sigma_ratios();
+            for i in 0..6 {
+                rms_error[i] += cmp.ric_error[i].powi(2) / num;
+                rms_sigma_ratio[i] += ratios[i].powi(2) / num;
+                for (n, prct) in within_prct.iter_mut().enumerate() {
+                    if ratios[i].abs() <= (n + 1) as f64 {
+                        prct[i] += 100.0 / num;
+                    }
+                }
+            }
+        }
+        let rms_error = rms_error.map(|v| v.sqrt());
+        let rms_sigma_ratio = rms_sigma_ratio.map(|v| v.sqrt());
+        let [within_1sigma_prct, within_2sigma_prct, within_3sigma_prct] = within_prct;
+
+        let verdict = if (0..6).any(|i| within_3sigma_prct[i] < 90.0 || rms_sigma_ratio[i] > 2.0) {
+            CovarianceConsistency::Optimistic
+        } else if rms_sigma_ratio.iter().all(|ratio| *ratio < 0.3) {
+            CovarianceConsistency::Pessimistic
+        } else {
+            CovarianceConsistency::Consistent
+        };
+
+        let me = Self {
+            epochs,
+            rms_error,
+            rms_sigma_ratio,
+            within_1sigma_prct,
+            within_2sigma_prct,
+            within_3sigma_prct,
+            verdict,
+        };
+        info!("{me}");
+        Ok(me)
+    }
+
+    /// Store the per epoch comparison in a parquet file
+    pub fn to_parquet<P: AsRef<Path>>(&self, path: P, cfg: ExportCfg) -> Result<PathBuf, ODError> {
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = cfg.epoch_fields();
+        for prefix in ["Error", "Sigma"] {
+            for (i, comp) in RIC_COMPONENTS.iter().enumerate() {
+                let unit = if i < 3 { "km" } else { "km/s" };
+                let mut meta = HashMap::new();
+                meta.insert("unit".to_string(), unit.to_string());
+                hdrs.push(
+                    Field::new(
+                        format!("{prefix} {comp} (RIC) ({unit})"),
+                        DataType::Float64,
+                        false,
+                    )
+                    .with_metadata(meta),
+                );
+            }
+        }
+        for comp in RIC_COMPONENTS {
+            let mut meta = HashMap::new();
+            meta.insert("unit".to_string(), "unitless".to_string());
+            hdrs.push(
+                Field::new(
+                    format!("Sigma ratio {comp} (RIC)"),
+                    DataType::Float64,
+                    false,
+                )
+                .with_metadata(meta),
+            );
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> =
+            cfg.epoch_columns(self.epochs.iter().map(|cmp| cmp.epoch));
+
+        let columns: [fn(&TruthEpochComparison) -> Vector6<f64>; 3] = [
+            |cmp| cmp.ric_error,
+            |cmp| cmp.ric_sigma,
+            |cmp| cmp.sigma_ratios(),
+        ];
+        for column in columns {
+            for i in 0..6 {
+                let mut data = Float64Builder::new();
+                for cmp in &self.epochs {
+                    data.append_value(column(cmp)[i]);
+                }
+                record.push(Arc::new(data.finish()));
+            }
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "OD estimates compared to truth".to_string(),
+        );
+        metadata.insert("Verdict".to_string(), self.verdict.to_string());
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata));
+
+        let file = File::create(&path_buf)
+            .context(StdIOSnafu {
+                action: "creating truth comparison file",
+            })
+            .context(ODIOSnafu)?;
+
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)
+            .context(ParquetSnafu {
+                action: "exporting truth comparison",
+            })
+            .context(ODIOSnafu)?;
+
+        let batch = RecordBatch::try_new(schema, record)
+            .context(ArrowSnafu {
+                action: "writing truth comparison (building batch record)",
+            })
+            .context(ODIOSnafu)?;
+
+        writer
+            .write(&batch)
+            .context(ParquetSnafu {
+                action: "writing truth comparison",
+            })
+            .context(ODIOSnafu)?;
+
+        writer
+            .close()
+            .context(ParquetSnafu {
+                action: "closing truth comparison file",
+            })
+            .context(ODIOSnafu)?;
+
+        info!("Truth comparison written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for TruthComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Comparison of {} estimates to truth: covariance is {}",
+            self.epochs.len(),
+            self.verdict
+        )?;
+        for (i, comp) in RIC_COMPONENTS.iter().enumerate() {
+            writeln!(
+                f,
+                "\t{comp:>2}: RMS error = {:.3e}\tRMS ratio = {:.3}\twithin 1σ/2σ/3σ = {:.1}% / {:.1}% / {:.1}%",
+                self.rms_error[i],
+                self.rms_sigma_ratio[i],
+                self.within_1sigma_prct[i],
+                self.within_2sigma_prct[i],
+                self.within_3sigma_prct[i]
+            )?;
+        }
+        Ok(())
+    }
+}
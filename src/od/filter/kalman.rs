@@ -21,10 +21,11 @@ use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector, U3};
 pub use crate::od::estimate::{Estimate, KfEstimate, Residual};
 use crate::od::process::ResidRejectCrit;
-pub use crate::od::snc::SNC;
+pub use crate::od::snc::{AdaptiveSnc, SNC};
 use crate::od::{Filter, ODDynamicsSnafu, ODError, State};
 pub use crate::time::{Epoch, Unit};
 use snafu::prelude::*;
+use std::collections::VecDeque;
 
 /// Defines both a Classical and an Extended Kalman filter (CKF and EKF)
 /// T: Type of state
@@ -60,6 +61,11 @@ where
     h_tilde: OMatrix<f64, M, <T as State>::Size>,
     h_tilde_updated: bool,
     prev_used_snc: usize,
+    /// Optional adaptive scaling of the process noise from the innovation statistics, disabled by default
+    pub adaptive_snc: Option<AdaptiveSnc>,
+    snc_scale: f64,
+    nis_window: VecDeque<f64>,
+    num_msr_processed: usize,
 }
 
 impl<T, A, M> KF<T, A, M>
@@ -100,6 +106,10 @@ where
             h_tilde: OMatrix::<f64, M, <T as State>::Size>::zeros(),
             h_tilde_updated: false,
             prev_used_snc: 0,
+            adaptive_snc: None,
+            snc_scale: 1.0,
+            nis_window: VecDeque::new(),
+            num_msr_processed: 0,
         }
     }
 
@@ -125,6 +135,54 @@ where
             h_tilde: OMatrix::<f64, M, <T as State>::Size>::zeros(),
             h_tilde_updated: false,
             prev_used_snc: 0,
+            adaptive_snc: None,
+            snc_scale: 1.0,
+            nis_window: VecDeque::new(),
+            num_msr_processed: 0,
+        }
+    }
+
+    /// Enables the adaptive scaling of the process noise of this filter with the provided configuration.
+    pub fn with_adaptive_snc(mut self, adaptive_snc: AdaptiveSnc) -> Self {
+        self.adaptive_snc = Some(adaptive_snc);
+        self
+    }
+
+    /// Returns the scale currently applied to the configured process noise (one unless the adaptive process noise changed it)
+    pub fn snc_scale(&self) -> f64 {
+        self.snc_scale
+    }
+
+    /// Records the normalized innovation squared of a fully processed measurement and adapts the scale of the process noise if needed.
+    /// This is only called once the measurement update is complete, so the process noise never changes during an update.
+    fn adapt_process_noise(&mut self, epoch: Epoch, nis: f64) {
+        let adaptive = match self.adaptive_snc {
+            Some(adaptive) => adaptive,
+            None => return,
+        };
+
+        self.num_msr_processed += 1;
+        if self.num_msr_processed <= adaptive.warmup {
+            return;
+        }
+
+        // Normalize by the measurement dimension, such that the expected value is one
+        self.nis_window.push_back(nis / (M::dim() as f64));
+        if self.nis_window.len() > adaptive.window {
+            self.nis_window.pop_front();
+        }
+        if self.nis_window.len() < adaptive.window {
+            return;
+        }
+
+        let mean_nis = self.nis_window.iter().sum::<f64>() / (self.nis_window.len() as f64);
+        if let Some(new_scale) = adaptive.adapt(self.snc_scale, mean_nis) {
+            info!(
+                "@{epoch} mean normalized innovation squared of {mean_nis:.3} over {} msr: process noise scale {:.3e} -> {new_scale:.3e}",
+                adaptive.window, self.snc_scale
+            );
+            self.snc_scale = new_scale;
+            self.nis_window.clear();
         }
     }
 }
@@ -155,6 +213,10 @@ where
             h_tilde: OMatrix::<f64, M, <T as State>::Size>::zeros(),
             h_tilde_updated: false,
             prev_used_snc: 0,
+            adaptive_snc: None,
+            snc_scale: 1.0,
+            nis_window: VecDeque::new(),
+            num_msr_processed: 0,
         }
     }
 }
@@ -240,8 +302,10 @@ where
                         gamma[(idx_k, idx_j)] = delta_t;
                     }
                 }
-                // Let's add the process noise
-                covar_bar += &gamma * snc_matrix * &gamma.transpose();
+                // Let's add the process noise, accounting for the correlation of the accelerations over the time update
+                let scale = self.snc_scale
+                    * snc.correlation_scale(nominal_state.epoch() - self.prev_estimate.epoch());
+                covar_bar += &gamma * (snc_matrix * scale) * &gamma.transpose();
                 // And break so we don't add any more process noise
                 break;
             }
@@ -292,24 +356,25 @@ where
         let h_tilde_t = &self.h_tilde.transpose();
         let h_p_ht = &self.h_tilde * covar_bar * h_tilde_t;
         // Account for state uncertainty in the measurement noise. Equation 4.10 of ODTK MathSpec.
+        // This is the innovation covariance H⋅P̄⋅H^T + R of the prefit residual.
         let r_k = &h_p_ht + measurement_covar;
 
         // Compute observation deviation (usually marked as y_i)
         let prefit = real_obs - computed_obs;
 
-        // Compute the prefit ratio for the automatic rejection
+        // Compute the normalized innovation squared, whose square root is the prefit ratio for the automatic rejection
         let r_k_inv = r_k.clone().try_inverse().ok_or(ODError::SingularNoiseRk)?;
-        let ratio_mat = prefit.transpose() * r_k_inv * &prefit;
-        let ratio = ratio_mat[0].sqrt();
+        let nis = (prefit.transpose() * r_k_inv * &prefit)[0];
+        let ratio = nis.sqrt();
 
         if let Some(resid_reject) = resid_rejection {
             if ratio > resid_reject.num_sigmas {
                 // Reject this whole measurement and perform only a time update
+                let mut res = Residual::rejected(epoch, prefit, ratio, r_k.diagonal());
+                res.map_with_sensitivity(&self.h_tilde);
                 let pred_est = self.time_update(nominal_state)?;
-                return Ok((
-                    pred_est,
-                    Residual::rejected(epoch, prefit, ratio, r_k.diagonal()),
-                ));
+                self.adapt_process_noise(epoch, nis);
+                return Ok((pred_est, res));
             }
         }
 
@@ -322,7 +387,7 @@ where
         let gain = covar_bar * h_tilde_t * &innovation_covar;
 
         // Compute the state estimate
-        let (state_hat, res) = if self.ekf {
+        let (state_hat, mut res) = if self.ekf {
             let state_hat = &gain * &prefit;
             let postfit = &prefit - (&self.h_tilde * state_hat);
             (
@@ -339,6 +404,8 @@ where
             )
         };
 
+        res.map_with_sensitivity(&self.h_tilde);
+
         // Compute covariance (Joseph update)
         let first_term = OMatrix::<f64, <T as State>::Size, <T as State>::Size>::identity()
             - &gain * &self.h_tilde;
@@ -361,6 +428,7 @@ where
         for snc in &mut self.process_noise {
             snc.prev_epoch = Some(self.prev_estimate.epoch());
         }
+        self.adapt_process_noise(epoch, nis);
         Ok((estimate, res))
     }
 
@@ -17,21 +17,23 @@
 */
 
 use anise::astro::{Aberration, AzElRange, PhysicsResult};
-use anise::errors::AlmanacResult;
+use anise::constants::celestial_objects::{EARTH, MOON};
+use anise::constants::usual_planetary_constants::MEAN_EARTH_ANGULAR_VELOCITY_DEG_S;
+use anise::errors::{AlmanacResult, MathError, PhysicsError};
 use anise::prelude::{Almanac, Frame, Orbit};
 
 use super::msr::RangeDoppler;
 use super::noise::StochasticNoise;
-use super::{ODAlmanacSnafu, ODError, ODTrajSnafu, TrackingDeviceSim};
+use super::{ODAlmanacSnafu, ODError, ODPhysicsSnafu, ODTrajSnafu, TrackingDeviceSim};
 use crate::errors::EventError;
 use crate::io::ConfigRepr;
+use crate::mc::NyxRng;
 use crate::md::prelude::{Interpolatable, Traj};
 use crate::md::EventEvaluator;
 use crate::time::Epoch;
 use crate::Spacecraft;
 use hifitime::{Duration, Unit};
-use nalgebra::{allocator::Allocator, DefaultAllocator, OMatrix};
-use rand_pcg::Pcg64Mcg;
+use nalgebra::{allocator::Allocator, DefaultAllocator, Matrix3, OMatrix, Vector3, Vector6};
 use serde_derive::{Deserialize, Serialize};
 use snafu::ResultExt;
 use std::fmt;
@@ -40,6 +42,9 @@ use std::sync::Arc;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
+/// Mean rotation rate of the Moon, i.e. one revolution per sidereal month of 27.321661 days.
+pub const MEAN_MOON_ANGULAR_VELOCITY_DEG_S: f64 = 360.0 / (27.321_661 * 86_400.0);
+
 /// GroundStation defines a two-way ranging and doppler station.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "python", pyclass)]
@@ -177,24 +182,136 @@ impl GroundStation {
         )
     }
 
-    /// Return this ground station as an orbit in its current frame
+    /// Mean rotation rate about its Z axis of the body on which this station lies, in degrees per second.
+    ///
+    /// Only the rates of the Earth and of the Moon are known: errors for a station on any other body, instead of ignoring its rotation.
+    pub fn angular_velocity_deg_s(&self) -> PhysicsResult<f64> {
+        match self.frame.ephemeris_id {
+            EARTH => Ok(MEAN_EARTH_ANGULAR_VELOCITY_DEG_S),
+            MOON => Ok(MEAN_MOON_ANGULAR_VELOCITY_DEG_S),
+            id => Err(PhysicsError::AppliedMath {
+                source: MathError::DomainError {
+                    value: id as f64,
+                    msg: "rotation rate unknown for the body of the ground station, only the Earth and the Moon are supported",
+                },
+            }),
+        }
+    }
+
+    /// Return this ground station as an orbit in its current frame.
+    ///
+    /// The station may lie on the Earth or the Moon (cf. `angular_velocity_deg_s`), provided that the body fixed frame and shape of
+    /// that body are loaded in the almanac (e.g. `IAU_MOON_FRAME`). The geodetic coordinates are converted with the radii and
+    /// flattening of that body.
     pub fn to_orbit(&self, epoch: Epoch, almanac: &Almanac) -> PhysicsResult<Orbit> {
-        use anise::constants::usual_planetary_constants::MEAN_EARTH_ANGULAR_VELOCITY_DEG_S;
         Orbit::try_latlongalt(
             self.latitude_deg,
             self.longitude_deg,
             self.height_km,
-            MEAN_EARTH_ANGULAR_VELOCITY_DEG_S,
+            self.angular_velocity_deg_s()?,
             epoch,
             almanac.frame_from_uid(self.frame).unwrap(),
         )
     }
 
+    /// Direction cosine matrix from the body fixed frame of this station to its topocentric South-East-Zenith (SEZ) frame.
+    pub fn dcm_to_sez(&self) -> Matrix3<f64> {
+        let (sin_lat, cos_lat) = self.latitude_deg.to_radians().sin_cos();
+        let (sin_long, cos_long) = self.longitude_deg.to_radians().sin_cos();
+        Matrix3::new(
+            sin_lat * cos_long,
+            sin_lat * sin_long,
+            -cos_lat,
+            -sin_long,
+            cos_long,
+            0.0,
+            cos_lat * cos_long,
+            cos_lat * sin_long,
+            sin_lat,
+        )
+    }
+
+    /// Returns a copy of this station moved by the provided offset in its body fixed frame, in km.
+    pub fn with_offset_km(
+        &self,
+        offset_km: Vector3<f64>,
+        almanac: &Almanac,
+    ) -> PhysicsResult<Self> {
+        // The location in the body fixed frame does not depend on the epoch.
+        let mut location = self.to_orbit(Epoch::from_tai_seconds(0.0), almanac)?;
+        location.radius_km += offset_km;
+
+        let mut me = self.clone();
+        me.latitude_deg = location.latitude_deg()?;
+        me.longitude_deg = location.longitude_deg();
+        me.height_km = location.height_km()?;
+        Ok(me)
+    }
+
+    /// Returns the position (km) and velocity (km/s) of the provided object relative to this ground station,
+    /// expressed in the topocentric South-East-Zenith (SEZ) frame of the station.
+    ///
+    /// The velocity is relative to the body fixed frame, in which the station is static.
+    pub fn sez_state_of(&self, rx: Orbit, almanac: &Almanac) -> Result<Vector6<f64>, ODError> {
+        let rx_fixed = almanac
+            .transform_to(rx, self.frame, None)
+            .context(ODAlmanacSnafu {
+                action: "transforming object into station frame",
+            })?;
+        let station = self.to_orbit(rx.epoch, almanac).context(ODPhysicsSnafu)?;
+
+        let dcm = self.dcm_to_sez();
+        let rho_km = dcm * (rx_fixed.radius_km - station.radius_km);
+        let rho_dot_km_s = dcm * rx_fixed.velocity_km_s;
+
+        Ok(Vector6::new(
+            rho_km.x,
+            rho_km.y,
+            rho_km.z,
+            rho_dot_km_s.x,
+            rho_dot_km_s.y,
+            rho_dot_km_s.z,
+        ))
+    }
+
+    /// Builds the orbit in the requested frame from a state relative to this ground station in its SEZ frame.
+    /// This is the inverse of `sez_state_of`.
+    pub fn orbit_from_sez(
+        &self,
+        sez: &Vector6<f64>,
+        epoch: Epoch,
+        frame: Frame,
+        almanac: &Almanac,
+    ) -> Result<Orbit, ODError> {
+        let station = self.to_orbit(epoch, almanac).context(ODPhysicsSnafu)?;
+
+        let dcm_t = self.dcm_to_sez().transpose();
+        let radius_km = station.radius_km + dcm_t * sez.fixed_rows::<3>(0);
+        let velocity_km_s = dcm_t * sez.fixed_rows::<3>(3);
+
+        let rx_fixed = Orbit::cartesian(
+            radius_km.x,
+            radius_km.y,
+            radius_km.z,
+            velocity_km_s.x,
+            velocity_km_s.y,
+            velocity_km_s.z,
+            epoch,
+            station.frame,
+        );
+
+        almanac
+            .transform_to(rx_fixed, frame, None)
+            .context(ODAlmanacSnafu {
+                action: "transforming SEZ state into requested frame",
+            })
+    }
+
     /// Returns the timestamp noise, range noise, and doppler noise for this ground station at the provided epoch.
     fn noises(
         &mut self,
         epoch: Epoch,
-        rng: Option<&mut Pcg64Mcg>,
+        rng: Option<&mut NyxRng>,
     ) -> Result<(f64, f64, f64), ODError> {
         let timestamp_noise_s;
         let range_noise_km;
@@ -240,7 +357,7 @@ impl TrackingDeviceSim<Spacecraft, RangeDoppler> for GroundStation {
         &mut self,
         epoch: Epoch,
         traj: &Traj<Spacecraft>,
-        rng: Option<&mut Pcg64Mcg>,
+        rng: Option<&mut NyxRng>,
         almanac: Arc<Almanac>,
     ) -> Result<Option<RangeDoppler>, ODError> {
         match self.integration_time {
@@ -302,7 +419,7 @@ impl TrackingDeviceSim<Spacecraft, RangeDoppler> for GroundStation {
     fn measure_instantaneous(
         &mut self,
         rx: Spacecraft,
-        rng: Option<&mut Pcg64Mcg>,
+        rng: Option<&mut NyxRng>,
         almanac: Arc<Almanac>,
     ) -> Result<Option<RangeDoppler>, ODError> {
         let obstructing_body = if !self.frame.ephem_origin_match(rx.frame()) {
@@ -0,0 +1,265 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::Almanac;
+use snafu::ResultExt;
+use std::fmt;
+use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+use super::estimate::Estimate;
+use super::{ODDynamicsSnafu, ODError, ODPropSnafu};
+use crate::dynamics::guidance::{ManeuverPlan, ManeuverWindow};
+use crate::dynamics::SpacecraftDynamics;
+use crate::io::ConfigError;
+use crate::linalg::{Matrix3, Matrix6, Vector3, Vector6};
+use crate::propagators::Propagator;
+use crate::time::Epoch;
+use crate::{Spacecraft, State};
+
+/// Reconstruction (or calibration) of an executed maneuver from the orbit determination solutions before and after it.
+///
+/// The pre-burn estimate is propagated through the planned maneuver window and compared to the post-burn estimate. The velocity
+/// correction at the end of the window which reconciles both solutions is solved for with a Gauss-Newton iteration, weighted by their
+/// covariances. The correction is modeled as an impulse at the end of the window, which neglects the position change due to the
+/// execution error during the window: this assumes that the burn is short compared to the orbital period.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(doc)]
+pub struct ManeuverReconstructor {
+    #[builder(default = 10)]
+    pub max_iterations: usize,
+    /// The solution has converged when the norm of the correction of an iteration is below this tolerance, in km/s
+    #[builder(default = 1e-10)]
+    pub tolerance_km_s: f64,
+}
+
+/// Achieved delta-v of a maneuver compared to the planned one, with the formal uncertainty of the reconstruction.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ManeuverReconstruction {
+    /// End of the maneuver window, where the correction is applied
+    pub epoch: Epoch,
+    /// Planned delta-v, computed as the velocity difference at the end of the window between the planned burn and a coast, in km/s
+    pub planned_dv_km_s: Vector3<f64>,
+    /// Achieved delta-v, in km/s
+    pub achieved_dv_km_s: Vector3<f64>,
+    /// Covariance of the achieved delta-v, in km^2/s^2
+    pub dv_covar_km2_s2: Matrix3<f64>,
+    /// Position (km) and velocity (km/s) difference between the post-burn estimate and the reconstructed trajectory
+    pub postfit_residual: Vector6<f64>,
+    /// Number of Gauss-Newton iterations
+    pub iterations: usize,
+}
+
+impl ManeuverReconstruction {
+    /// Magnitude of the achieved delta-v divided by the planned one, e.g. 1.02 for a 2% hot burn
+    pub fn efficiency(&self) -> f64 {
+        self.achieved_dv_km_s.norm() / self.planned_dv_km_s.norm()
+    }
+
+    /// Formal one sigma uncertainty of the magnitude of the achieved delta-v, in km/s
+    pub fn magnitude_sigma_km_s(&self) -> f64 {
+        let unit = self.achieved_dv_km_s.normalize();
+        (unit.transpose() * self.dv_covar_km2_s2 * unit)[0].sqrt()
+    }
+
+    /// Formal one sigma uncertainty of the efficiency
+    pub fn efficiency_sigma(&self) -> f64 {
+        self.magnitude_sigma_km_s() / self.planned_dv_km_s.norm()
+    }
+
+    /// Angle between the planned and achieved delta-v vectors, in degrees
+    pub fn pointing_error_deg(&self) -> f64 {
+        self.planned_dv_km_s
+            .cross(&self.achieved_dv_km_s)
+            .norm()
+            .atan2(self.planned_dv_km_s.dot(&self.achieved_dv_km_s))
+            .to_degrees()
+    }
+
+    /// Formal one sigma uncertainty of the pointing error, from the uncertainty of the achieved delta-v perpendicular to it, in degrees
+    pub fn pointing_sigma_deg(&self) -> f64 {
+        let unit = self.achieved_dv_km_s.normalize();
+        let perp = Matrix3::identity() - unit * unit.transpose();
+        ((perp * self.dv_covar_km2_s2 * perp).trace().sqrt() / self.achieved_dv_km_s.norm())
+            .to_degrees()
+    }
+}
+
+impl fmt::Display for ManeuverReconstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "maneuver ending {}: achieved {:.3} ± {:.3} mm/s for {:.3} mm/s planned (efficiency {:.4} ± {:.4}), pointing error {:.3} ± {:.3} deg",
+            self.epoch,
+            self.achieved_dv_km_s.norm() * 1e6,
+            self.magnitude_sigma_km_s() * 1e6,
+            self.planned_dv_km_s.norm() * 1e6,
+            self.efficiency(),
+            self.efficiency_sigma(),
+            self.pointing_error_deg(),
+            self.pointing_sigma_deg()
+        )
+    }
+}
+
+impl ManeuverReconstructor {
+    /// Reconstructs the maneuver of the provided window from the estimates before its start and after its end.
+    ///
+    /// The dynamics are those of the coast arcs, and the guidance law of the window is used to propagate the planned burn. Both estimates
+    /// must be in the same frame. Errors if the estimates do not bracket the window, if the covariances are singular, or if the solution
+    /// does not converge.
+    pub fn reconstruct<E: Estimate<Spacecraft>>(
+        &self,
+        pre_burn: &E,
+        post_burn: &E,
+        window: &ManeuverWindow,
+        dynamics: SpacecraftDynamics,
+        almanac: Arc<Almanac>,
+    ) -> Result<ManeuverReconstruction, ODError> {
+        if pre_burn.epoch() > window.start || post_burn.epoch() < window.end {
+            return Err(ODError::ODConfigError {
+                source: ConfigError::InvalidConfig {
+                    msg: format!(
+                        "estimates at {} and {} do not bracket the maneuver window {window}",
+                        pre_burn.epoch(),
+                        post_burn.epoch()
+                    ),
+                },
+            });
+        }
+
+        let pre_state = pre_burn.state().with_stm();
+        let post_state = post_burn.state();
+        if pre_state.orbit.frame != post_state.orbit.frame {
+            return Err(ODError::ODConfigError {
+                source: ConfigError::InvalidConfig {
+                    msg: format!(
+                        "pre-burn estimate in {} but post-burn estimate in {}",
+                        pre_state.orbit.frame, post_state.orbit.frame
+                    ),
+                },
+            });
+        }
+
+        let plan = ManeuverPlan::new(vec![(window.start, window.end, window.law.clone())])
+            .map_err(|e| ODError::ODConfigError {
+                source: ConfigError::InvalidConfig {
+                    msg: format!("{e}"),
+                },
+            })?;
+
+        let mut coast_dynamics = dynamics;
+        coast_dynamics.guid_law = None;
+        let burn_dynamics = coast_dynamics.with_guidance_law(plan);
+        let coast = Propagator::default(coast_dynamics);
+        let burn = Propagator::default(burn_dynamics);
+
+        // The STM through the window is that of the coast, which maps the pre-burn covariance
+        let coast_end = coast
+            .with(pre_state, almanac.clone())
+            .until_epoch(window.end)
+            .context(ODPropSnafu)?;
+        let phi_pre = coast_end.orbit_stm().context(ODDynamicsSnafu)?;
+
+        // Propagate the planned burn, stepping onto the start of the window such that it starts on time
+        let mut state = pre_state;
+        state.stm = None;
+        let start_state = burn
+            .with(state, almanac.clone())
+            .until_epoch(window.start)
+            .context(ODPropSnafu)?;
+        let planned_end = burn
+            .with(start_state, almanac.clone())
+            .until_epoch(window.end)
+            .context(ODPropSnafu)?;
+        let coasted_end = coast
+            .with(start_state, almanac.clone())
+            .until_epoch(window.end)
+            .context(ODPropSnafu)?;
+        let planned_dv_km_s = planned_end.orbit.velocity_km_s - coasted_end.orbit.velocity_km_s;
+
+        let post_covar: Matrix6<f64> = post_burn.covar().fixed_view::<6, 6>(0, 0).into_owned();
+        let pre_covar: Matrix6<f64> = pre_burn.covar().fixed_view::<6, 6>(0, 0).into_owned();
+
+        let mut correction_km_s = Vector3::zeros();
+        let mut dv_covar_km2_s2 = Matrix3::zeros();
+        let mut postfit_residual = Vector6::zeros();
+        let mut iterations = 0;
+        let mut converged = false;
+
+        while iterations < self.max_iterations {
+            iterations += 1;
+
+            let mut corrected = planned_end;
+            corrected.orbit.velocity_km_s += correction_km_s;
+            let predicted = coast
+                .with(corrected.with_stm(), almanac.clone())
+                .until_epoch(post_state.epoch())
+                .context(ODPropSnafu)?;
+            let phi_post = predicted.orbit_stm().context(ODDynamicsSnafu)?;
+
+            let residual =
+                post_state.orbit.to_cartesian_pos_vel() - predicted.orbit.to_cartesian_pos_vel();
+            let sensitivity = phi_post.fixed_view::<6, 3>(0, 3).into_owned();
+            let phi = phi_post * phi_pre;
+            let weight = (post_covar + phi * pre_covar * phi.transpose())
+                .try_inverse()
+                .ok_or_else(|| ODError::InvalidCovariance {
+                    msg: "combined covariance of the estimates is singular".to_string(),
+                })?;
+
+            let info = sensitivity.transpose() * weight * sensitivity;
+            dv_covar_km2_s2 = info
+                .try_inverse()
+                .ok_or_else(|| ODError::InvalidCovariance {
+                    msg: "delta-v information matrix is singular".to_string(),
+                })?;
+            let delta = dv_covar_km2_s2 * sensitivity.transpose() * weight * residual;
+            correction_km_s += delta;
+            postfit_residual = residual - sensitivity * delta;
+
+            debug!(
+                "maneuver reconstruction iteration #{iterations}: correction of {:.3} mm/s",
+                delta.norm() * 1e6
+            );
+
+            if delta.norm() < self.tolerance_km_s {
+                converged = true;
+                break;
+            }
+        }
+
+        if !converged {
+            return Err(ODError::Diverged {
+                loops: self.max_iterations,
+            });
+        }
+
+        let recon = ManeuverReconstruction {
+            epoch: window.end,
+            planned_dv_km_s,
+            achieved_dv_km_s: planned_dv_km_s + correction_km_s,
+            dv_covar_km2_s2,
+            postfit_residual,
+            iterations,
+        };
+        info!("{recon}");
+
+        Ok(recon)
+    }
+}
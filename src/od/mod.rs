@@ -27,7 +27,7 @@ use crate::time::Epoch;
 use crate::Orbit;
 pub use crate::{State, TimeTagged};
 use anise::almanac::planetary::PlanetaryDataError;
-use anise::errors::AlmanacError;
+use anise::errors::{AlmanacError, PhysicsError};
 use hifitime::Duration;
 use snafu::prelude::Snafu;
 use std::sync::Arc;
@@ -39,6 +39,10 @@ pub use filter::Filter;
 mod ground_station;
 pub use ground_station::GroundStation;
 
+/// Provides a Delta-DOR baseline between two ground stations.
+mod baseline;
+pub use baseline::Baseline;
+
 /// Provides Estimate handling functionalities.
 pub mod estimate;
 
@@ -57,9 +61,18 @@ pub mod process;
 use arrow::datatypes::Field;
 pub use simulator::TrackingDeviceSim;
 
+/// Reconstructs executed maneuvers from the orbit determination solutions before and after them
+pub mod maneuver_recon;
+
 /// Provides all state noise compensation functionality
 pub mod snc;
 
+/// Estimates the location of ground stations from the tracking of a well known spacecraft
+pub mod station_location;
+
+/// Estimates the time-tag bias of tracking devices from the tracking of a well known spacecraft
+pub mod time_bias;
+
 /// A helper type for spacecraft orbit determination.
 pub type SpacecraftODProcess<'a> = self::process::ODProcess<
     'a,
@@ -75,12 +88,15 @@ pub mod prelude {
     pub use super::estimate::*;
     pub use super::filter::kalman::*;
     pub use super::ground_station::*;
+    pub use super::maneuver_recon::*;
     pub use super::msr::*;
     pub use super::noise::{GaussMarkov, StochasticNoise, WhiteNoise};
     pub use super::process::*;
     pub use super::simulator::TrackingArcSim;
     pub use super::simulator::*;
     pub use super::snc::*;
+    pub use super::station_location::*;
+    pub use super::time_bias::*;
     pub use super::*;
 
     pub use crate::time::{Duration, Epoch, TimeUnits, Unit};
@@ -104,6 +120,12 @@ pub trait Measurement: Copy + TimeTagged {
     fn observation(&self) -> OVector<f64, Self::MeasurementSize>
     where
         DefaultAllocator: Allocator<Self::MeasurementSize>;
+
+    /// Returns whether the measurement sensitivity must be evaluated on the computed measurement instead of the real one.
+    /// This is required when the sensitivity relies on tracking geometry which is not stored with the observation, e.g. the baseline direction of a Delta-DOR.
+    fn sensitivity_from_computed() -> bool {
+        false
+    }
 }
 
 /// The Estimate trait defines the interface that is the opposite of a `SolveFor`.
@@ -214,4 +236,8 @@ pub enum ODError {
     },
     #[snafu(display("not enough residuals to {action}"))]
     ODNoResiduals { action: &'static str },
+    #[snafu(display("invalid covariance: {msg}"))]
+    InvalidCovariance { msg: String },
+    #[snafu(display("OD failed because {source}"))]
+    ODPhysicsError { source: PhysicsError },
 }
@@ -36,7 +36,6 @@ use arrow::array::{Array, Float64Builder, StringBuilder};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use hifitime::prelude::{Duration, Epoch, Unit};
-use hifitime::TimeScale;
 use parquet::arrow::ArrowWriter;
 
 /// Tracking arc contains the tracking data generated by the tracking devices defined in this structure.
@@ -100,10 +99,8 @@ where
         }
 
         // Build the schema
-        let mut hdrs = vec![
-            Field::new("Epoch (UTC)", DataType::Utf8, false),
-            Field::new("Tracking device", DataType::Utf8, false),
-        ];
+        let mut hdrs = cfg.epoch_fields();
+        hdrs.push(Field::new("Tracking device", DataType::Utf8, false));
 
         let mut msr_fields = Msr::fields();
 
@@ -138,11 +135,7 @@ where
         // Build all of the records
 
         // Epochs
-        let mut utc_epoch = StringBuilder::new();
-        for m in &measurements {
-            utc_epoch.append_value(m.1.epoch().to_time_scale(TimeScale::UTC).to_isoformat());
-        }
-        record.push(Arc::new(utc_epoch.finish()));
+        record.extend(cfg.epoch_columns(measurements.iter().map(|m| m.1.epoch())));
 
         // Device names
         let mut device_names = StringBuilder::new();
@@ -196,6 +189,14 @@ where
         set
     }
 
+    /// Returns the epochs of the measurements of this arc, e.g. to pin them in the propagator with `PropInstance::pin_epochs`.
+    pub fn epochs(&self) -> Vec<Epoch> {
+        self.measurements
+            .iter()
+            .map(|(_name, msr)| msr.epoch())
+            .collect()
+    }
+
     /// Returns the minimum duration between two subsequent measurements.
     /// This is important to correctly set up the propagator and not miss any measurement.
     pub fn min_duration_sep(&self) -> Option<Duration> {
@@ -276,6 +277,46 @@ where
         }
     }
 
+    /// Returns the componentwise difference between the measurements of this arc and those of the other arc, e.g. real minus simulated observations.
+    ///
+    /// Each measurement of this arc is matched to the measurement of the same device in the other arc with the closest epoch,
+    /// provided that epochs are within the tolerance. Measurements without a match are skipped.
+    pub fn residuals_against(&self, other: &Self, tol: Duration) -> Vec<(String, Epoch, Vec<f64>)> {
+        // Group the other measurements by device, they are chronological.
+        let mut others: HashMap<&String, Vec<&Msr>> = HashMap::new();
+        for (name, msr) in &other.measurements {
+            others.entry(name).or_default().push(msr);
+        }
+        for msrs in others.values_mut() {
+            msrs.sort_by_key(|msr| msr.epoch());
+        }
+
+        let mut residuals = Vec::new();
+        for (name, msr) in &self.measurements {
+            let Some(candidates) = others.get(name) else {
+                continue;
+            };
+
+            let epoch = msr.epoch();
+            let idx = candidates.partition_point(|other| other.epoch() < epoch);
+            // The closest measurement is either right before or right after this epoch.
+            let closest = [idx.checked_sub(1), Some(idx)]
+                .into_iter()
+                .flatten()
+                .filter_map(|ii| candidates.get(ii))
+                .min_by_key(|other| (other.epoch() - epoch).abs());
+
+            if let Some(matched) = closest {
+                if (matched.epoch() - epoch).abs() <= tol {
+                    let diff = msr.observation() - matched.observation();
+                    residuals.push((name.clone(), epoch, diff.iter().copied().collect()));
+                }
+            }
+        }
+
+        residuals
+    }
+
     /// If this tracking arc has devices that can be used to generate simulated measurements,
     /// then this function can be used to rebuild said measurement devices
     pub fn set_devices<MsrIn, D>(
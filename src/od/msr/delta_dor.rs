@@ -0,0 +1,139 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::Orbit;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, OMatrix, OVector, Vector1, Vector3, U1};
+use crate::od::{EstimateFrom, Measurement};
+use crate::{Spacecraft, TimeTagged};
+use arrow::datatypes::{DataType, Field};
+use hifitime::{Epoch, Unit};
+use std::collections::HashMap;
+
+/// A Delta-DOR (delta differential one-way range) measurement, expressed as an angle in radians.
+///
+/// The differential one-way range is the difference in arrival time of the spacecraft signal at both stations of a baseline.
+/// The same delay is measured on a quasar near the spacecraft in the sky, and the difference of both delays (i.e. the Delta-DOR)
+/// is scaled by the speed of light and the baseline length to yield an angle: the projection onto the baseline of the difference
+/// between the direction of the spacecraft and the direction of the quasar. Hence, this measurement constrains the position of the
+/// spacecraft in the plane of the sky, along the projection of the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeltaDor {
+    /// Epoch of the observation
+    pub epoch: Epoch,
+    /// Observation vector in radians
+    pub obs: Vector1<f64>,
+    /// Unit vector of the baseline in the inertial frame of the spacecraft at the time of the observation.
+    /// This is not part of the observation and is zero if the measurement was loaded from a file.
+    pub baseline_unit: Vector3<f64>,
+}
+
+impl DeltaDor {
+    /// Initialize a new Delta-DOR measurement from the line of sight of the spacecraft from the baseline midpoint, the direction to the quasar,
+    /// and the baseline vector from the first station to the second one, all in the same inertial frame. The noises are in radians and seconds.
+    pub fn new(
+        epoch: Epoch,
+        los_unit: Vector3<f64>,
+        quasar_unit: Vector3<f64>,
+        baseline_km: Vector3<f64>,
+        timestamp_noise_s: f64,
+        delta_dor_noise_rad: f64,
+    ) -> Self {
+        let baseline_unit = baseline_km.normalize();
+        Self {
+            epoch: epoch + timestamp_noise_s * Unit::Second,
+            obs: Vector1::new(baseline_unit.dot(&(los_unit - quasar_unit)) + delta_dor_noise_rad),
+            baseline_unit,
+        }
+    }
+
+    /// Returns the Delta-DOR angle in radians
+    pub fn delta_dor_rad(&self) -> f64 {
+        self.obs[0]
+    }
+}
+
+impl TimeTagged for DeltaDor {
+    fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.epoch = epoch
+    }
+}
+
+impl Measurement for DeltaDor {
+    type MeasurementSize = U1;
+
+    /// Returns this measurement as a vector of the Delta-DOR angle
+    ///
+    /// **Units:** rad
+    fn observation(&self) -> Vector1<f64> {
+        self.obs
+    }
+
+    fn fields() -> Vec<Field> {
+        let mut meta = HashMap::new();
+        meta.insert("unit".to_string(), "rad".to_string());
+        vec![Field::new("Delta-DOR (rad)", DataType::Float64, false).with_metadata(meta)]
+    }
+
+    fn from_observation(epoch: Epoch, obs: OVector<f64, Self::MeasurementSize>) -> Self {
+        Self {
+            epoch,
+            obs,
+            baseline_unit: Vector3::zeros(),
+        }
+    }
+
+    /// The baseline direction is not part of the observation, so the sensitivity uses that of the computed measurement.
+    fn sensitivity_from_computed() -> bool {
+        true
+    }
+}
+
+impl EstimateFrom<Spacecraft, DeltaDor> for Spacecraft {
+    fn extract(from: Spacecraft) -> Self {
+        from
+    }
+
+    /// The sensitivity is only with respect to the components of the position in the plane of the sky, i.e. orthogonal to the line of sight.
+    /// The transmitter must be the midpoint of the baseline.
+    fn sensitivity(
+        msr: &DeltaDor,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<<DeltaDor as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let rho = receiver.orbit.radius_km - transmitter.radius_km;
+        let los_unit = rho / rho.norm();
+        let baseline_unit = msr.baseline_unit;
+        // Derivative of the unit line of sight projected onto the baseline
+        let partial = (baseline_unit - baseline_unit.dot(&los_unit) * los_unit) / rho.norm();
+
+        let mut h_tilde =
+            OMatrix::<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>::zeros();
+        for i in 0..3 {
+            h_tilde[(0, i)] = partial[i];
+        }
+        h_tilde
+    }
+}
@@ -17,11 +17,13 @@
 */
 
 mod arc;
+mod delta_dor;
 mod range;
 mod range_doppler;
 mod rangerate;
 
 pub use arc::TrackingArc;
+pub use delta_dor::DeltaDor;
 pub use range::RangeMsr;
 pub use range_doppler::RangeDoppler;
 pub use rangerate::RangeRate;
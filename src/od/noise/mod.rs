@@ -17,13 +17,13 @@
 */
 
 use crate::io::watermark::pq_writer;
+use crate::mc::NyxRng;
 use arrow::array::{ArrayRef, Float64Array, UInt32Array};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use hifitime::{Epoch, TimeSeries, TimeUnits};
 use parquet::arrow::ArrowWriter;
-use rand::{Rng, SeedableRng};
-use rand_pcg::Pcg64Mcg;
+use rand::Rng;
 use serde_derive::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs::File;
@@ -31,9 +31,11 @@ use std::path::Path;
 use std::sync::Arc;
 
 pub mod gauss_markov;
+pub mod random_walk;
 pub mod white;
 
 pub use gauss_markov::GaussMarkov;
+pub use random_walk::RandomWalk;
 pub use white::WhiteNoise;
 
 /// Trait for any kind of stochastic modeling, developing primarily for synthetic orbit determination measurements.
@@ -47,11 +49,14 @@ pub trait Stochastics {
 
 /// Stochastic noise modeling used primarily for synthetic orbit determination measurements.
 ///
-/// This implementation distinguishes between the white noise model and the bias model. It also includes a constant offset.
+/// This implementation distinguishes between the white noise model, the bias model, and the random walk (drift) model. It also includes a constant offset.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct StochasticNoise {
     pub white_noise: Option<WhiteNoise>,
     pub bias: Option<GaussMarkov>,
+    /// Random walk added across the arc, producing correlated errors (e.g. a clock drift).
+    #[serde(default)]
+    pub random_walk: Option<RandomWalk>,
 }
 
 impl StochasticNoise {
@@ -59,6 +64,7 @@ impl StochasticNoise {
     pub const ZERO: Self = Self {
         white_noise: None,
         bias: None,
+        random_walk: None,
     };
 
     /// The minimum stochastic noise process with a zero mean white noise of 1e-6.
@@ -68,6 +74,7 @@ impl StochasticNoise {
             sigma: 1e-6,
         }),
         bias: None,
+        random_walk: None,
     };
 
     /// Default stochastic process of the Deep Space Network, as per DESCANSO Chapter 3, Table 3-3.
@@ -79,6 +86,7 @@ impl StochasticNoise {
                 ..Default::default()
             }),
             bias: Some(GaussMarkov::default_range_km()),
+            random_walk: None,
         }
     }
 
@@ -90,9 +98,26 @@ impl StochasticNoise {
                 ..Default::default()
             }),
             bias: Some(GaussMarkov::default_doppler_km_s()),
+            random_walk: None,
         }
     }
 
+    /// Returns a copy of these stochastics whose covariance is scaled by the provided factor, e.g. to weight or deweight a tracking device.
+    pub fn scaled(&self, covariance_scale: f64) -> Self {
+        let sigma_scale = covariance_scale.sqrt();
+        let mut me = *self;
+        if let Some(wn) = &mut me.white_noise {
+            wn.sigma *= sigma_scale;
+        }
+        if let Some(gm) = &mut me.bias {
+            gm.process_noise *= sigma_scale;
+        }
+        if let Some(rw) = &mut me.random_walk {
+            rw.process_noise *= sigma_scale;
+        }
+        me
+    }
+
     /// Sample these stochastics
     pub fn sample<R: Rng>(&mut self, epoch: Epoch, rng: &mut R) -> f64 {
         let mut sample = 0.0;
@@ -102,6 +127,9 @@ impl StochasticNoise {
         if let Some(gm) = &mut self.bias {
             sample += gm.sample(epoch, rng);
         }
+        if let Some(rw) = &mut self.random_walk {
+            sample += rw.sample(epoch, rng);
+        }
         sample
     }
 
@@ -114,6 +142,9 @@ impl StochasticNoise {
         if let Some(gm) = &self.bias {
             variance += gm.covariance(epoch);
         }
+        if let Some(rw) = &self.random_walk {
+            variance += rw.covariance(epoch);
+        }
         variance
     }
 
@@ -139,7 +170,7 @@ impl StochasticNoise {
         let mut samples = Vec::with_capacity(capacity);
 
         for run in 0..num_runs {
-            let mut rng = Pcg64Mcg::from_entropy();
+            let mut rng = NyxRng::from_entropy();
 
             let mut mdl = self;
             for epoch in TimeSeries::inclusive(start, end, step) {
@@ -211,7 +242,9 @@ pub struct StochasticState {
 mod ut_stochastics {
     use std::path::PathBuf;
 
-    use super::{white::WhiteNoise, StochasticNoise};
+    use hifitime::TimeUnits;
+
+    use super::{white::WhiteNoise, RandomWalk, StochasticNoise};
 
     #[test]
     fn test_simulate_zero() {
@@ -268,6 +301,28 @@ mod ut_stochastics {
             .unwrap();
     }
 
+    #[test]
+    fn test_simulate_random_walk() {
+        let path: PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "output_data",
+            "stochastics_random_walk.parquet",
+        ]
+        .iter()
+        .collect();
+
+        let noise = StochasticNoise {
+            random_walk: Some(RandomWalk::from_drift(1e-3, 1.hours()).unwrap()),
+            ..Default::default()
+        };
+
+        let rslts = noise
+            .simulate(path, None, Some("kilometer".to_string()))
+            .unwrap();
+        // The variance of a random walk grows with time.
+        assert!(rslts.last().unwrap().variance > rslts[1].variance);
+    }
+
     #[test]
     fn test_simulate_dsn_range_gm_only() {
         let path: PathBuf = [
@@ -0,0 +1,220 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::io::ConfigError;
+use hifitime::{Duration, Epoch};
+use rand::Rng;
+use rand_distr::Normal;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+
+use super::Stochastics;
+
+/// A random walk process, i.e. integrated white noise, for modeling the drift of clocks or of biases.
+///
+/// Each sample is the previous sample plus a zero mean Normal increment whose variance grows linearly with the time since the previous sample:
+///
+/// b(t_k) = b(t_{k-1}) + w_k, where w_k ~ 𝓝(0, q² (t_k - t_{k-1}))
+///
+/// Hence, the errors generated by this process are correlated over the whole tracking arc, and the variance of the process grows linearly with time.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "RandomWalkSerde")]
+pub struct RandomWalk {
+    /// Standard deviation of the increment of the process after one second, i.e. in units per square root of second.
+    pub process_noise: f64,
+    /// Epoch of the first realization, used to compute the variance of the process.
+    #[serde(skip)]
+    pub init_epoch: Option<Epoch>,
+    /// Epoch of the previous realization, used to compute the time delta for the increment.
+    #[serde(skip)]
+    pub prev_epoch: Option<Epoch>,
+    /// Sample of previous realization
+    #[serde(skip)]
+    pub prev_sample: f64,
+}
+
+impl fmt::Display for RandomWalk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Random walk process with q = {} /√s", self.process_noise)
+    }
+}
+
+/// Serialized representation of a random walk, validated on deserialization.
+#[derive(Deserialize)]
+struct RandomWalkSerde {
+    process_noise: f64,
+}
+
+impl TryFrom<RandomWalkSerde> for RandomWalk {
+    type Error = ConfigError;
+
+    fn try_from(rw: RandomWalkSerde) -> Result<Self, Self::Error> {
+        Self::new(rw.process_noise)
+    }
+}
+
+impl RandomWalk {
+    /// Create a new random walk process from its process noise, in units per square root of second.
+    /// Errors if the process noise is negative or not finite.
+    pub fn new(process_noise: f64) -> Result<Self, ConfigError> {
+        if !process_noise.is_finite() || process_noise < 0.0 {
+            return Err(ConfigError::InvalidConfig {
+                msg: format!(
+                    "random walk process noise must be finite and non-negative but got {process_noise}"
+                ),
+            });
+        }
+
+        Ok(Self {
+            process_noise,
+            ..Default::default()
+        })
+    }
+
+    /// Create a new random walk process whose standard deviation reaches `sigma` after the provided duration.
+    /// Errors if the duration is not positive, or if `sigma` is negative or not finite.
+    pub fn from_drift(sigma: f64, duration: Duration) -> Result<Self, ConfigError> {
+        if duration <= Duration::ZERO {
+            return Err(ConfigError::InvalidConfig {
+                msg: format!("random walk drift duration must be positive but got {duration}"),
+            });
+        }
+        Self::new(sigma / duration.to_seconds().sqrt())
+    }
+}
+
+impl Stochastics for RandomWalk {
+    /// The variance of a random walk grows linearly since its first realization.
+    fn covariance(&self, epoch: Epoch) -> f64 {
+        match self.init_epoch {
+            Some(init_epoch) => {
+                self.process_noise.powi(2) * (epoch - init_epoch).abs().to_seconds()
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Return the next sample of the random walk.
+    fn sample<R: Rng>(&mut self, epoch: Epoch, rng: &mut R) -> f64 {
+        // Compute the delta time in seconds between the previous epoch and the sample epoch.
+        let dt_s = (match self.prev_epoch {
+            None => Duration::ZERO,
+            Some(prev_epoch) => epoch - prev_epoch,
+        })
+        .abs()
+        .to_seconds();
+        self.prev_epoch = Some(epoch);
+        if self.init_epoch.is_none() {
+            self.init_epoch = Some(epoch);
+        }
+
+        if dt_s > 0.0 {
+            let increment_sigma = self.process_noise * dt_s.sqrt();
+            self.prev_sample += rng.sample(Normal::new(0.0, increment_sigma).unwrap());
+        }
+
+        self.prev_sample
+    }
+}
+
+#[cfg(test)]
+mod ut_rw {
+    use crate::mc::NyxRng;
+    use hifitime::{Epoch, TimeUnits};
+
+    use super::{RandomWalk, Stochastics};
+    use crate::od::noise::WhiteNoise;
+
+    /// Normalized autocorrelation of the samples at the provided lag
+    fn autocorrelation(samples: &[f64], lag: usize) -> f64 {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let var = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>();
+        samples
+            .iter()
+            .zip(samples.iter().skip(lag))
+            .map(|(x0, x1)| (x0 - mean) * (x1 - mean))
+            .sum::<f64>()
+            / var
+    }
+
+    #[test]
+    fn random_walk_autocorrelation() {
+        let sigma = 1e-3;
+        let mut rw = RandomWalk::new(sigma).unwrap();
+        let mut wn = WhiteNoise::constant_white_noise(sigma);
+
+        let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+        let mut rng = NyxRng::from_seed(1000);
+
+        let mut rw_samples = Vec::with_capacity(2000);
+        let mut wn_samples = Vec::with_capacity(2000);
+        for minute in 0..2000_i64 {
+            rw_samples.push(rw.sample(epoch + minute.minutes(), &mut rng));
+            wn_samples.push(wn.sample(epoch + minute.minutes(), &mut rng));
+        }
+
+        // The first sample of a random walk is zero.
+        assert_eq!(rw_samples[0], 0.0);
+
+        for lag in [1, 10, 50] {
+            let rw_corr = autocorrelation(&rw_samples, lag);
+            let wn_corr = autocorrelation(&wn_samples, lag);
+            println!("lag {lag}: random walk {rw_corr:.3}\twhite noise {wn_corr:.3}");
+            assert!(
+                rw_corr > 0.7,
+                "random walk should be correlated at lag {lag}"
+            );
+            assert!(
+                wn_corr.abs() < 0.1,
+                "white noise should not be correlated at lag {lag}"
+            );
+        }
+
+        // The variance grows linearly with time
+        let end = epoch + 1999.minutes();
+        assert!((rw.covariance(end) - sigma.powi(2) * 1999.0 * 60.0).abs() < 1e-12);
+        // And the increment variance matches the process noise, within 10%.
+        let increments = rw_samples
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect::<Vec<f64>>();
+        let incr_var =
+            increments.iter().map(|x| x.powi(2)).sum::<f64>() / (increments.len() as f64);
+        let expected_var = sigma.powi(2) * 60.0;
+        assert!(
+            (incr_var - expected_var).abs() / expected_var < 0.1,
+            "increment variance {incr_var:.3e} != expected {expected_var:.3e}"
+        );
+    }
+
+    #[test]
+    fn random_walk_validation() {
+        assert!(RandomWalk::new(0.0).is_ok());
+        assert!(RandomWalk::new(-1e-3).is_err());
+        assert!(RandomWalk::new(f64::NAN).is_err());
+        assert!(RandomWalk::new(f64::INFINITY).is_err());
+        assert!(RandomWalk::from_drift(1e-3, 0.hours()).is_err());
+        assert!(RandomWalk::from_drift(-1e-3, 1.hours()).is_err());
+
+        // Deserialization is validated as well
+        let rw: RandomWalk = serde_yaml::from_str("process_noise: 1.0e-3").unwrap();
+        assert_eq!(rw, RandomWalk::new(1e-3).unwrap());
+        assert!(serde_yaml::from_str::<RandomWalk>("process_noise: -1.0e-3").is_err());
+    }
+}
@@ -80,9 +80,9 @@ impl Stochastics for WhiteNoise {
 #[cfg(test)]
 mod ut_wn {
     use hifitime::{Epoch, TimeUnits};
-    use rand_pcg::Pcg64Mcg;
 
     use super::{Stochastics, WhiteNoise};
+    use crate::mc::NyxRng;
 
     #[test]
     fn white_noise_test() {
@@ -96,7 +96,7 @@ mod ut_wn {
 
         let epoch = Epoch::now().unwrap();
 
-        let mut rng = Pcg64Mcg::new(1000);
+        let mut rng = NyxRng::from_seed(1000);
         let mut cnt_above_3sigma = 0;
         let mut cnt_below_3sigma = 0;
         let mut larger_cnt_above_3sigma = 0;
@@ -56,6 +56,53 @@ impl Default for SmoothingArc {
     }
 }
 
+/// Defines which estimates are stored by the OD process: all of the measurements are processed regardless.
+///
+/// Storing fewer estimates reduces the memory usage of long arcs, but prevents smoothing the solution.
+/// The last estimate of each call to `process` or `predict_until` is always stored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EstimateCadence {
+    /// Store every estimate, from both the measurement updates and the time updates
+    All,
+    /// Only store the estimate of every N-th measurement update (and none of the time updates)
+    Measurements(usize),
+    /// Only store the first estimate at least this duration after the previously stored estimate
+    Interval(Duration),
+}
+
+impl EstimateCadence {
+    /// Returns whether the estimate at the provided epoch must be stored, given the number of measurement updates so far (if this estimate is a measurement update)
+    /// and the epoch of the previously stored estimate.
+    pub(crate) fn stores(
+        &self,
+        epoch: Epoch,
+        msr_cnt: Option<usize>,
+        prev_stored: Option<Epoch>,
+    ) -> bool {
+        match *self {
+            Self::All => true,
+            Self::Measurements(every) => msr_cnt.is_some_and(|cnt| cnt % every.max(1) == 0),
+            Self::Interval(interval) => prev_stored.map_or(true, |prev| epoch - prev >= interval),
+        }
+    }
+}
+
+impl fmt::Display for EstimateCadence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EstimateCadence::All => write!(f, "all estimates"),
+            EstimateCadence::Measurements(n) => write!(f, "every {n} measurements"),
+            EstimateCadence::Interval(i) => write!(f, "every {i}"),
+        }
+    }
+}
+
+impl Default for EstimateCadence {
+    fn default() -> Self {
+        Self::All
+    }
+}
+
 /// Defines a filter iteration configuration. Allows iterating on an OD solution until convergence criteria is met.
 /// The root mean squared of the prefit residuals ratios is used to assess convergence between iterations.
 #[derive(Clone, Copy, Debug, TypedBuilder)]
@@ -17,19 +17,19 @@
 */
 
 use crate::io::watermark::pq_writer;
-use crate::io::{ArrowSnafu, ExportCfg, ParquetSnafu, StdIOSnafu};
+use crate::io::{ArrowSnafu, ConfigError, ExportCfg, ParquetSnafu, StdIOSnafu};
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, DimName};
-use crate::md::trajectory::Interpolatable;
+use crate::md::trajectory::{Interpolatable, TrajError};
 use crate::md::StateParameter;
 use crate::od::estimate::*;
+use crate::time::TimeSeries;
 use crate::State;
 use crate::{od::*, Spacecraft};
 use arrow::array::{Array, BooleanBuilder, Float64Builder, StringBuilder};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use filter::kalman::KF;
-use hifitime::TimeScale;
 use na::Const;
 use parquet::arrow::ArrowWriter;
 use snafu::prelude::*;
@@ -40,7 +40,10 @@ use std::path::{Path, PathBuf};
 
 use super::ODProcess;
 
-impl<'a, D: Dynamics, Msr: Measurement, A: DimName>
+/// Key of the parquet metadata entry storing the number of non-finite covariance, sigma and RIC sigma entries of an OD export.
+pub const NON_FINITE_COVAR_KEY: &str = "Non-finite covariance entries";
+
+impl<'a, D: Dynamics<StateType = Spacecraft>, Msr: Measurement, A: DimName>
     ODProcess<'a, D, Msr, A, Spacecraft, KF<Spacecraft, A, Msr::MeasurementSize>>
 where
     D::StateType:
@@ -70,7 +73,49 @@ where
         + Allocator<A, <Spacecraft as State>::Size>,
     Spacecraft: EstimateFrom<D::StateType, Msr>,
 {
-    /// Store the estimates and residuals in a parquet file
+    /// Returns the estimate at the provided epoch, which must be within the span of the estimates, e.g. to generate products
+    /// at round epochs instead of at the measurement epochs.
+    ///
+    /// If the latest estimate at or before this epoch is exactly at this epoch, it is returned as is. Otherwise, its nominal
+    /// state is propagated to this epoch along with the variational equations, and its state deviation and covariance are
+    /// mapped with the resulting STM, i.e. `P = Φ P_k Φ^T`. The covariance is never interpolated element-wise, so it remains
+    /// positive definite. Note that the process noise of the filter, if any, is not added over this sub-interval.
+    pub fn estimate_at(&self, epoch: Epoch) -> Result<KfEstimate<Spacecraft>, ODError> {
+        let idx = self.estimates.partition_point(|est| est.epoch() <= epoch);
+        if idx == 0 || epoch > self.estimates[self.estimates.len() - 1].epoch() {
+            return Err(ODError::ODTrajError {
+                source: TrajError::NoInterpolationData { epoch },
+            });
+        }
+
+        let prev = &self.estimates[idx - 1];
+        if prev.epoch() == epoch {
+            return Ok(*prev);
+        }
+
+        let nominal_state = self
+            .prop
+            .prop
+            .with(prev.nominal_state.with_stm(), self.almanac.clone())
+            .until_epoch(epoch)
+            .context(ODPropSnafu)?;
+        let stm = nominal_state.stm().context(ODDynamicsSnafu)?;
+        let covar = stm * prev.covar * stm.transpose();
+
+        Ok(KfEstimate {
+            nominal_state,
+            state_deviation: stm * prev.state_deviation,
+            covar,
+            covar_bar: covar,
+            predicted: true,
+            stm,
+        })
+    }
+
+    /// Store the estimates and residuals in a parquet file.
+    ///
+    /// If the export configuration has a `step`, the estimates are exported at that cadence from the start epoch (cf.
+    /// `estimate_at`), with the residuals of the actual estimates at these epochs, if any.
     pub fn to_parquet<P: AsRef<Path>>(&self, path: P, cfg: ExportCfg) -> Result<PathBuf, ODError> {
         ensure!(
             !self.estimates.is_empty(),
@@ -91,31 +136,30 @@ where
         let tick = Epoch::now().unwrap();
         info!("Exporting orbit determination result to parquet file...");
 
-        if cfg.step.is_some() {
-            warn!("The `step` parameter in the export is not supported for orbit determination exports.");
-        }
-
         // Grab the path here before we move stuff.
         let path_buf = cfg.actual_path(path);
 
         // Build the schema
-        let mut hdrs = vec![Field::new("Epoch (UTC)", DataType::Utf8, false)];
+        let mut hdrs = cfg.epoch_fields();
 
         let frame = self.estimates[0].state().frame();
 
-        let more_meta = Some(vec![(
-            "Frame".to_string(),
-            serde_dhall::serialize(&frame)
-                .to_string()
-                .map_err(|e| ODError::ODIOError {
-                    source: InputOutputError::SerializeDhall {
-                        what: format!("frame `{frame}`"),
-                        err: e.to_string(),
-                    },
-                })?,
-        )]);
-
-        let mut fields = match cfg.fields {
+        let more_meta = Some(vec![
+            (
+                "Frame".to_string(),
+                serde_dhall::serialize(&frame)
+                    .to_string()
+                    .map_err(|e| ODError::ODIOError {
+                        source: InputOutputError::SerializeDhall {
+                            what: format!("frame `{frame}`"),
+                            err: e.to_string(),
+                        },
+                    })?,
+            ),
+            ("frame".to_string(), frame.to_string()),
+        ]);
+
+        let mut fields = match cfg.fields.clone() {
             Some(fields) => fields,
             None => Spacecraft::export_params(),
         };
@@ -144,8 +188,9 @@ where
             ) && self.estimates[0].sigma_for(*param).is_ok()
         });
 
+        // The uncertainties are nullable, as are all covariance entries, in case they are not finite
         for field in &sigma_fields {
-            hdrs.push(field.to_cov_field(more_meta.clone()));
+            hdrs.push(field.to_cov_field(more_meta.clone()).with_nullable(true));
         }
 
         let state_items = ["X", "Y", "Z", "Vx", "Vy", "Vz", "Cr", "Cd", "Mass"];
@@ -186,16 +231,24 @@ where
 
         let est_size = <Spacecraft as State>::Size::dim();
 
+        // Builds a nullable field annotated with its unit and frame
+        let annotated_field = |name: String, unit: &str, frame: String| {
+            let mut meta = HashMap::new();
+            meta.insert("unit".to_string(), unit.to_string());
+            meta.insert("frame".to_string(), frame);
+            Field::new(name, DataType::Float64, true).with_metadata(meta)
+        };
+
         let mut idx = 0;
         for i in 0..state_items.len() {
             for j in i..state_items.len() {
-                hdrs.push(Field::new(
+                hdrs.push(annotated_field(
                     format!(
                         "Covariance {}*{} ({frame:x}) ({})",
                         state_items[i], state_items[j], cov_units[idx]
                     ),
-                    DataType::Float64,
-                    false,
+                    cov_units[idx],
+                    frame.to_string(),
                 ));
                 idx += 1;
             }
@@ -203,19 +256,19 @@ where
 
         // Add the uncertainty in the integration frame
         for (i, coord) in state_items.iter().enumerate() {
-            hdrs.push(Field::new(
+            hdrs.push(annotated_field(
                 format!("Sigma {coord} ({frame:x}) ({})", state_units[i]),
-                DataType::Float64,
-                false,
+                state_units[i],
+                frame.to_string(),
             ));
         }
 
         // Add the position and velocity uncertainty in the RIC frame
         for (i, coord) in state_items.iter().enumerate().take(6) {
-            hdrs.push(Field::new(
+            hdrs.push(annotated_field(
                 format!("Sigma {coord} (RIC) ({})", state_units[i]),
-                DataType::Float64,
-                false,
+                state_units[i],
+                "RIC".to_string(),
             ));
         }
 
@@ -242,11 +295,41 @@ where
                     .with_name(format!("Measurement noise: {}", f.name())),
             );
         }
+        for f in Msr::fields() {
+            let mut meta = HashMap::new();
+            meta.insert("unit".to_string(), "unitless".to_string());
+            msr_fields.push(
+                Field::new(
+                    format!("Measurement weight: {}", f.name()),
+                    DataType::Float64,
+                    true,
+                )
+                .with_metadata(meta),
+            );
+        }
 
-        msr_fields.push(Field::new("Residual ratio", DataType::Float64, true));
+        let mut meta = HashMap::new();
+        meta.insert("unit".to_string(), "unitless".to_string());
+        msr_fields.push(Field::new("Residual ratio", DataType::Float64, true).with_metadata(meta));
         msr_fields.push(Field::new("Residual Rejected", DataType::Boolean, true));
         msr_fields.push(Field::new("Tracker", DataType::Utf8, true));
 
+        // Add the residuals mapped into position and velocity deviations
+        if let Some(resid_frame) = cfg.residual_frame {
+            for kind in ["Prefit", "Postfit"] {
+                for (i, coord) in state_items.iter().enumerate().take(6) {
+                    msr_fields.push(annotated_field(
+                        format!(
+                            "{kind} mapped residual: {coord} ({resid_frame:?}) ({})",
+                            state_units[i]
+                        ),
+                        state_units[i],
+                        format!("{resid_frame:?}"),
+                    ));
+                }
+            }
+        }
+
         hdrs.append(&mut msr_fields);
 
         // Build the schema
@@ -268,10 +351,27 @@ where
                     Vec::with_capacity(self.residuals.len());
                 let mut estimates = Vec::with_capacity(self.estimates.len());
 
-                for (estimate, residual) in self.estimates.iter().zip(self.residuals.iter()) {
-                    if estimate.epoch() >= start && estimate.epoch() <= end {
-                        estimates.push(*estimate);
-                        residuals.push(residual.clone());
+                match cfg.step {
+                    Some(step) => {
+                        for epoch in TimeSeries::inclusive(start, end, step) {
+                            estimates.push(self.estimate_at(epoch)?);
+                            // Only the actual estimates have residuals
+                            let idx = self.estimates.partition_point(|est| est.epoch() <= epoch);
+                            residuals.push(if self.estimates[idx - 1].epoch() == epoch {
+                                self.residuals[idx - 1].clone()
+                            } else {
+                                None
+                            });
+                        }
+                    }
+                    None => {
+                        for (estimate, residual) in self.estimates.iter().zip(self.residuals.iter())
+                        {
+                            if estimate.epoch() >= start && estimate.epoch() <= end {
+                                estimates.push(*estimate);
+                                residuals.push(residual.clone());
+                            }
+                        }
                     }
                 }
 
@@ -283,11 +383,7 @@ where
         // Build all of the records
 
         // Epochs
-        let mut utc_epoch = StringBuilder::new();
-        for s in &estimates {
-            utc_epoch.append_value(s.epoch().to_time_scale(TimeScale::UTC).to_isoformat());
-        }
-        record.push(Arc::new(utc_epoch.finish()));
+        record.extend(cfg.epoch_columns(estimates.iter().map(|s| s.epoch())));
 
         // Add all of the fields
         for field in fields {
@@ -298,11 +394,14 @@ where
             record.push(Arc::new(data.finish()));
         }
 
+        // Track the non-finite covariance entries, which are a sign of filter divergence.
+        let mut non_finite = NonFiniteCovar::new(cfg.covar_sentinel);
+
         // Add all of the 1-sigma uncertainties
         for field in sigma_fields {
             let mut data = Float64Builder::new();
             for s in &estimates {
-                data.append_value(s.sigma_for(field).unwrap());
+                non_finite.append(&mut data, s.sigma_for(field).unwrap(), s.epoch());
             }
             record.push(Arc::new(data.finish()));
         }
@@ -312,7 +411,7 @@ where
             for j in i..est_size {
                 let mut data = Float64Builder::new();
                 for s in &estimates {
-                    data.append_value(s.covar()[(i, j)]);
+                    non_finite.append(&mut data, s.covar()[(i, j)], s.epoch());
                 }
                 record.push(Arc::new(data.finish()));
             }
@@ -322,7 +421,7 @@ where
         for i in 0..est_size {
             let mut data = Float64Builder::new();
             for s in &estimates {
-                data.append_value(s.covar()[(i, i)].sqrt());
+                non_finite.append(&mut data, s.covar()[(i, i)].sqrt(), s.epoch());
             }
             record.push(Arc::new(data.finish()));
         }
@@ -350,12 +449,14 @@ where
         // Now store the RIC covariance data.
         for i in 0..6 {
             let mut data = Float64Builder::new();
-            for cov in ric_covariances.iter().take(estimates.len()) {
-                data.append_value(cov[(i, i)].sqrt());
+            for (cov, s) in ric_covariances.iter().zip(&estimates) {
+                non_finite.append(&mut data, cov[(i, i)].sqrt(), s.epoch());
             }
             record.push(Arc::new(data.finish()));
         }
 
+        non_finite.warn();
+
         // Finally, add the residuals.
         // Prefits
         for i in 0..Msr::MeasurementSize::dim() {
@@ -393,6 +494,18 @@ where
             }
             record.push(Arc::new(data.finish()));
         }
+        // Measurement weight
+        for i in 0..Msr::MeasurementSize::dim() {
+            let mut data = Float64Builder::new();
+            for resid_opt in &residuals {
+                if let Some(resid) = resid_opt {
+                    data.append_value(resid.weight[i]);
+                } else {
+                    data.append_null();
+                }
+            }
+            record.push(Arc::new(data.finish()));
+        }
         // Residual ratio (unique entry regardless of the size)
         let mut data = Float64Builder::new();
         for resid_opt in &residuals {
@@ -431,6 +544,34 @@ where
         }
         record.push(Arc::new(data.finish()));
 
+        // Mapped residuals in the requested frame
+        if let Some(resid_frame) = cfg.residual_frame {
+            let mut mapped = Vec::with_capacity(residuals.len());
+            for (estimate, resid_opt) in estimates.iter().zip(&residuals) {
+                mapped.push(match resid_opt {
+                    Some(resid) => resid
+                        .mapped_in(resid_frame, estimate.state().orbit())
+                        .map_err(|e| ODError::ODConfigError {
+                            source: ConfigError::InvalidConfig {
+                                msg: format!("cannot rotate residual into {resid_frame:?}: {e}"),
+                            },
+                        })?,
+                    None => (None, None),
+                });
+            }
+
+            for postfit in [false, true] {
+                for i in 0..6 {
+                    let mut data = Float64Builder::new();
+                    for (prefit_vec, postfit_vec) in &mapped {
+                        let vec = if postfit { postfit_vec } else { prefit_vec };
+                        data.append_option(vec.map(|v| v[i]));
+                    }
+                    record.push(Arc::new(data.finish()));
+                }
+            }
+        }
+
         info!("Serialized {} estimates and residuals", estimates.len());
 
         // Serialize all of the devices and add that to the parquet file too.
@@ -439,6 +580,10 @@ where
             "Purpose".to_string(),
             "Orbit determination results".to_string(),
         );
+        metadata.insert(
+            NON_FINITE_COVAR_KEY.to_string(),
+            format!("{}", non_finite.count),
+        );
         if let Some(add_meta) = cfg.metadata {
             for (k, v) in add_meta {
                 metadata.insert(k, v);
@@ -488,3 +633,134 @@ where
         Ok(path_buf)
     }
 }
+
+/// Appends the covariance value to the builder, replacing it with the sentinel (or null if unset) if it isn't finite.
+/// Returns true if the value was not finite.
+fn append_covar_value(data: &mut Float64Builder, value: f64, sentinel: Option<f64>) -> bool {
+    if value.is_finite() {
+        data.append_value(value);
+        false
+    } else {
+        match sentinel {
+            Some(sentinel) => data.append_value(sentinel),
+            None => data.append_null(),
+        }
+        true
+    }
+}
+
+/// Counts the non-finite covariance, sigma and RIC sigma entries of an export, which are a sign of filter divergence.
+struct NonFiniteCovar {
+    sentinel: Option<f64>,
+    count: usize,
+    first_epoch: Option<Epoch>,
+}
+
+impl NonFiniteCovar {
+    fn new(sentinel: Option<f64>) -> Self {
+        Self {
+            sentinel,
+            count: 0,
+            first_epoch: None,
+        }
+    }
+
+    /// Appends the value to the builder with `append_covar_value`, counting it if it isn't finite.
+    fn append(&mut self, data: &mut Float64Builder, value: f64, epoch: Epoch) {
+        if append_covar_value(data, value, self.sentinel) {
+            self.count += 1;
+            if self.first_epoch.map_or(true, |first| epoch < first) {
+                self.first_epoch = Some(epoch);
+            }
+        }
+    }
+
+    /// Returns the warning to log if any entry was not finite.
+    fn warning(&self) -> Option<String> {
+        self.first_epoch.map(|first_epoch| {
+            format!(
+                "{} non-finite covariance entries exported as {}, first one on {first_epoch}: the filter may have diverged",
+                self.count,
+                match self.sentinel {
+                    Some(sentinel) => format!("{sentinel}"),
+                    None => "null".to_string(),
+                }
+            )
+        })
+    }
+
+    /// Logs a warning if any entry was not finite.
+    fn warn(&self) {
+        if let Some(msg) = self.warning() {
+            warn!("{msg}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod ut_od_export {
+    use super::{append_covar_value, NonFiniteCovar};
+    use crate::linalg::Matrix6;
+    use crate::time::{Epoch, Unit};
+    use arrow::array::{Array, Float64Builder};
+
+    #[test]
+    fn nan_covar_sentinel() {
+        // Build a covariance where the filter diverged on one element
+        let mut covar = Matrix6::<f64>::identity();
+        covar[(2, 4)] = f64::NAN;
+        covar[(4, 2)] = f64::NAN;
+
+        let mut data = Float64Builder::new();
+        let mut flagged = 0;
+        for i in 0..6 {
+            for j in i..6 {
+                if append_covar_value(&mut data, covar[(i, j)], Some(-999.0)) {
+                    flagged += 1;
+                }
+            }
+        }
+        let array = data.finish();
+        assert_eq!(flagged, 1, "only one upper triangular element is NaN");
+        assert_eq!(array.null_count(), 0);
+        // Index of (2, 4) in the upper triangular ordering
+        assert_eq!(array.value(6 + 5 + 2), -999.0);
+        assert_eq!(array.value(0), 1.0);
+
+        // Without a sentinel, the value is exported as null
+        let mut data = Float64Builder::new();
+        assert!(append_covar_value(&mut data, f64::INFINITY, None));
+        assert!(!append_covar_value(&mut data, 1.0, None));
+        let array = data.finish();
+        assert!(array.is_null(0));
+        assert!(!array.is_null(1));
+    }
+
+    #[test]
+    fn non_finite_covar_warning() {
+        let first = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+        let mut non_finite = NonFiniteCovar::new(Some(-999.0));
+        let mut data = Float64Builder::new();
+        non_finite.append(&mut data, 1.0, first);
+        // No warning while all of the entries are finite
+        assert!(non_finite.warning().is_none());
+
+        non_finite.append(&mut data, f64::NAN, first + 2 * Unit::Minute);
+        non_finite.append(&mut data, f64::INFINITY, first + 1 * Unit::Minute);
+        assert_eq!(non_finite.count, 2);
+        assert_eq!(non_finite.first_epoch, Some(first + 1 * Unit::Minute));
+
+        let msg = non_finite.warning().unwrap();
+        assert!(msg.starts_with("2 non-finite covariance entries exported as -999"));
+        assert!(msg.contains(&format!("{}", first + 1 * Unit::Minute)));
+
+        // Without a sentinel, the warning reports null entries
+        let mut non_finite = NonFiniteCovar::new(None);
+        non_finite.append(&mut data, f64::NAN, first);
+        assert!(non_finite
+            .warning()
+            .unwrap()
+            .starts_with("1 non-finite covariance entries exported as null"));
+    }
+}
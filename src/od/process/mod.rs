@@ -16,9 +16,10 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::io::ConfigError;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, DimName};
-use crate::md::trajectory::{Interpolatable, Traj};
+use crate::md::trajectory::{InterpMethod, Interpolatable, Traj};
 pub use crate::od::estimate::*;
 pub use crate::od::ground_station::*;
 pub use crate::od::snc::*;
@@ -28,7 +29,7 @@ pub use crate::time::{Duration, Unit};
 use anise::prelude::Almanac;
 use snafu::prelude::*;
 mod conf;
-pub use conf::{IterationConf, SmoothingArc};
+pub use conf::{EstimateCadence, IterationConf, SmoothingArc};
 mod trigger;
 pub use trigger::EkfTrigger;
 mod rejectcrit;
@@ -38,6 +39,12 @@ use std::collections::BTreeMap;
 use std::marker::PhantomData;
 use std::ops::Add;
 mod export;
+pub use export::NON_FINITE_COVAR_KEY;
+mod setup;
+pub use setup::{run_from_setup, EkfTriggerSerde, OdSetup, OdSetupSerde, SncSerde, SolveForSerde};
+mod weighting;
+use weighting::weighted_covar;
+pub use weighting::{DeviceWeight, MeasurementWeights, WeightOverride};
 
 /// An orbit determination process. Note that everything passed to this structure is moved.
 #[allow(clippy::upper_case_acronyms)]
@@ -81,6 +88,10 @@ pub struct ODProcess<
     pub ekf_trigger: Option<EkfTrigger>,
     /// Residual rejection criteria allows preventing bad measurements from affecting the estimation.
     pub resid_crit: Option<ResidRejectCrit>,
+    /// Per device weighting of the measurement noise covariance, if any.
+    pub weights: Option<MeasurementWeights>,
+    /// Cadence at which the estimates (and their residuals) are stored, defaults to all of them.
+    pub cadence: EstimateCadence,
     pub almanac: Arc<Almanac>,
     init_state: D::StateType,
     _marker: PhantomData<A>,
@@ -134,6 +145,8 @@ where
             residuals: Vec::with_capacity(10_000),
             ekf_trigger,
             resid_crit,
+            weights: None,
+            cadence: EstimateCadence::All,
             almanac,
             init_state,
             _marker: PhantomData::<A>,
@@ -156,17 +169,60 @@ where
             residuals: Vec::with_capacity(10_000),
             ekf_trigger: Some(trigger),
             resid_crit,
+            weights: None,
+            cadence: EstimateCadence::All,
             almanac,
             init_state,
             _marker: PhantomData::<A>,
         }
     }
 
+    /// Sets the per device weighting of the measurement noise covariance, applied to each measurement before its measurement update.
+    pub fn with_weights(mut self, weights: MeasurementWeights) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Sets the cadence at which the estimates are stored, all of the measurements are processed regardless.
+    pub fn with_cadence(mut self, cadence: EstimateCadence) -> Self {
+        self.cadence = cadence;
+        self
+    }
+
+    /// Stores the provided estimate and its residual if required by the cadence, or returns them otherwise.
+    #[allow(clippy::type_complexity)]
+    fn store(
+        &mut self,
+        estimate: K::Estimate,
+        residual: Option<Residual<Msr::MeasurementSize>>,
+        msr_cnt: Option<usize>,
+    ) -> Option<(K::Estimate, Option<Residual<Msr::MeasurementSize>>)> {
+        let prev_stored = self.estimates.last().map(|est| est.epoch());
+        if self.cadence.stores(estimate.epoch(), msr_cnt, prev_stored) {
+            self.estimates.push(estimate);
+            self.residuals.push(residual);
+            None
+        } else {
+            Some((estimate, residual))
+        }
+    }
+
     /// Allows to smooth the provided estimates. Returns the smoothed estimates or an error.
     ///
     /// Estimates must be ordered in chronological order. This function will smooth the
     /// estimates from the last in the list to the first one.
     pub fn smooth(&self, condition: SmoothingArc) -> Result<Vec<K::Estimate>, ODError> {
+        if self.cadence != EstimateCadence::All {
+            return Err(ODError::ODConfigError {
+                source: ConfigError::InvalidConfig {
+                    msg: format!(
+                        "smoothing requires all of the estimates but only {} are stored",
+                        self.cadence
+                    ),
+                },
+            });
+        }
+
         let l = self.estimates.len() - 1;
 
         info!("Smoothing {} estimates until {}", l + 1, condition);
@@ -272,6 +328,21 @@ where
         (sum / (self.residuals.len() as f64)).sqrt()
     }
 
+    /// Fits the signatures of dynamical model mismatches (bias, drift, once and twice per rev) to the postfit residuals of
+    /// each tracker, cf. [ModelMismatchReport]. The orbital period is typically that of the initial orbit.
+    pub fn model_mismatch(
+        &self,
+        orbital_period: Duration,
+        criteria: MismatchCriteria,
+    ) -> Result<ModelMismatchReport, ODError> {
+        let components = Msr::fields()
+            .iter()
+            .map(|field| field.name().to_string())
+            .collect::<Vec<_>>();
+
+        ModelMismatchReport::from_residuals(&self.residuals, &components, orbital_period, criteria)
+    }
+
     /// Allows iterating on the filter solution. Requires specifying a smoothing condition to know where to stop the smoothing.
     pub fn iterate<Dev>(
         &mut self,
@@ -483,6 +554,11 @@ where
             StepSizeSnafu { step: max_step }
         );
 
+        if let Some(weights) = &self.weights {
+            weights.validate().context(ODConfigSnafu)?;
+            weights.warn_unknown(devices.keys());
+        }
+
         // Start by propagating the estimator (on the same thread).
         let num_msrs = measurements.len();
 
@@ -504,6 +580,9 @@ where
         let mut traj: Traj<S> = Traj::new();
 
         let mut msr_accepted_cnt: usize = 0;
+        // Number of measurement updates, and the latest estimate if it was not stored because of the cadence
+        let mut msr_update_cnt: usize = 0;
+        let mut unstored = None;
         let tick = Epoch::now().unwrap();
 
         for (msr_cnt, (device_name, msr)) in measurements.iter().enumerate() {
@@ -575,21 +654,37 @@ where
                                     }
                                 }
 
-                                let h_tilde = S::sensitivity(msr, nominal_state, device_loc);
+                                let h_msr = if Msr::sensitivity_from_computed() {
+                                    &computed_meas
+                                } else {
+                                    msr
+                                };
+                                let h_tilde = S::sensitivity(h_msr, nominal_state, device_loc);
 
                                 self.kf.update_h_tilde(h_tilde);
 
+                                // Apply the weight of this device, if any
+                                let weight = match &self.weights {
+                                    Some(weights) => weights
+                                        .scale_at::<Msr::MeasurementSize>(device_name, epoch)
+                                        .context(ODConfigSnafu)?,
+                                    None => OVector::<f64, Msr::MeasurementSize>::repeat(1.0),
+                                };
+                                let r_k =
+                                    weighted_covar(&device.measurement_covar(epoch)?, &weight);
+
                                 match self.kf.measurement_update(
                                     nominal_state,
                                     &msr.observation(),
                                     &computed_meas.observation(),
-                                    device.measurement_covar(epoch)?,
+                                    r_k,
                                     self.resid_crit,
                                 ) {
                                     Ok((estimate, mut residual)) => {
                                         debug!("processed msr #{msr_cnt} @ {epoch}");
 
                                         residual.tracker = Some(device.name());
+                                        residual.weight = weight;
 
                                         if !residual.rejected {
                                             msr_accepted_cnt += 1;
@@ -619,8 +714,12 @@ where
 
                                         self.prop.state.reset_stm();
 
-                                        self.estimates.push(estimate);
-                                        self.residuals.push(Some(residual));
+                                        msr_update_cnt += 1;
+                                        unstored = self.store(
+                                            estimate,
+                                            Some(residual),
+                                            Some(msr_update_cnt),
+                                        );
                                     }
                                     Err(e) => return Err(e),
                                 }
@@ -656,9 +755,8 @@ where
                         Ok(est) => {
                             // State deviation is always zero for an EKF time update
                             // therefore we don't do anything different for an extended filter
-                            self.estimates.push(est);
-                            // We push None so that the residuals and estimates are aligned
-                            self.residuals.push(None);
+                            // We store None so that the residuals and estimates are aligned
+                            unstored = self.store(est, None, None);
                         }
                         Err(e) => return Err(e),
                     }
@@ -667,6 +765,12 @@ where
             }
         }
 
+        // Always store the last estimate
+        if let Some((estimate, residual)) = unstored {
+            self.estimates.push(estimate);
+            self.residuals.push(residual);
+        }
+
         // Always report the 100% mark
         if !reported[10] {
             let tock_time = Epoch::now().unwrap() - tick;
@@ -684,6 +788,7 @@ where
         let prop_time = end_epoch - self.kf.previous_estimate().epoch();
         info!("Mapping covariance for {prop_time} with {step} step");
 
+        let mut unstored = None;
         loop {
             let mut epoch = self.prop.state.epoch();
             if epoch + self.prop.details.step > end_epoch {
@@ -705,8 +810,7 @@ where
                 Ok(est) => {
                     // State deviation is always zero for an EKF time update
                     // therefore we don't do anything different for an extended filter
-                    self.estimates.push(est);
-                    self.residuals.push(None);
+                    unstored = self.store(est, None, None);
                 }
                 Err(e) => return Err(e),
             }
@@ -716,6 +820,12 @@ where
             }
         }
 
+        // Always store the last estimate
+        if let Some((estimate, residual)) = unstored {
+            self.estimates.push(estimate);
+            self.residuals.push(residual);
+        }
+
         Ok(())
     }
 
@@ -743,6 +853,11 @@ where
                     .map(|est| est.nominal_state())
                     .collect(),
                 name: None,
+                inertial_interp: None,
+                gaps: Vec::new(),
+                annotations: Vec::new(),
+                interpolation: InterpMethod::default(),
+                max_states: None,
             })
         }
     }
@@ -794,6 +909,8 @@ where
             residuals: Vec::with_capacity(10_000),
             resid_crit,
             ekf_trigger: None,
+            weights: None,
+            cadence: EstimateCadence::All,
             init_state,
             almanac,
             _marker: PhantomData::<A>,
@@ -0,0 +1,391 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{DeviceWeight, EkfTrigger, MeasurementWeights, ODProcess, ResidRejectCrit};
+use crate::dynamics::{DynamicsPreset, PresetOptions, SpacecraftDynamics};
+use crate::io::tracking_data::DynamicTrackingArc;
+use crate::io::{
+    duration_from_str, duration_to_str, ConfigError, ConfigRepr, ExportCfg, ReadSnafu,
+};
+use crate::linalg::{Const, SMatrix};
+use crate::md::StateParameter;
+use crate::od::filter::kalman::KF;
+use crate::od::msr::{RangeDoppler, TrackingArc};
+use crate::od::snc::SNC3;
+use crate::od::{
+    GroundStation, KfEstimate, ODConfigSnafu, ODError, ODIOSnafu, SpacecraftODProcess,
+};
+use crate::propagators::{IntegratorOptions, Propagator};
+use crate::time::Duration;
+use crate::Spacecraft;
+use anise::almanac::Almanac;
+use flate2::Crc;
+use serde_derive::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Serializable description of everything needed to reproduce an orbit determination run of a spacecraft from range and Doppler tracking.
+///
+/// The tracking arc itself is only referenced by path, and its CRC32 checksum is verified when the setup is built so that a modified arc is caught.
+/// Relative paths are relative to the working directory, or to the directory of the setup file when using [run_from_setup].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OdSetupSerde {
+    /// Path to the tracking arc parquet file
+    pub arc_path: String,
+    /// CRC32 checksum of the tracking arc file, cf. [OdSetupSerde::arc_checksum]
+    pub arc_crc32: u32,
+    /// Nominal initial state of the filter
+    pub initial_state: Spacecraft,
+    /// Estimated parameters and their a priori one sigma uncertainty. All of the orbital Cartesian components are required.
+    pub solve_for: Vec<SolveForSerde>,
+    /// Dynamics of the filter
+    pub dynamics: DynamicsPreset,
+    /// Degree of the harmonics of the dynamics preset, defaults to that of the preset
+    #[serde(default)]
+    pub harmonics_degree: Option<usize>,
+    /// Order of the harmonics of the dynamics preset, defaults to the degree
+    #[serde(default)]
+    pub harmonics_order: Option<usize>,
+    /// Integrator options, defaults to those of the default propagator
+    #[serde(default)]
+    pub integrator: Option<IntegratorOptions>,
+    /// Configuration of each tracking device, indexed by the name used in the tracking arc
+    pub devices: BTreeMap<String, GroundStation>,
+    /// Scale factor on the measurement noise covariance of the named devices, e.g. 4.0 doubles their noise sigma
+    #[serde(default)]
+    pub measurement_weights: BTreeMap<String, f64>,
+    /// Measurement editing criteria, if any
+    #[serde(default)]
+    pub resid_crit: Option<ResidRejectCrit>,
+    /// State noise compensation, if any
+    #[serde(default)]
+    pub snc: Option<SncSerde>,
+    /// Switch from a classical to an extended Kalman filter, if set
+    #[serde(default)]
+    pub ekf: Option<EkfTriggerSerde>,
+    /// Path to the parquet file where the estimates and residuals are written after a run, if set
+    #[serde(default)]
+    pub estimates_path: Option<String>,
+}
+
+/// An estimated parameter and its a priori one sigma uncertainty, in the unit of that parameter.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SolveForSerde {
+    pub param: StateParameter,
+    pub sigma: f64,
+}
+
+/// State noise compensation on the three components of the acceleration.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SncSerde {
+    /// Time between measurements after which the process noise is no longer applied
+    #[serde(
+        serialize_with = "duration_to_str",
+        deserialize_with = "duration_from_str"
+    )]
+    pub disable_time: Duration,
+    /// Diagonal of the process noise, in km^2/s^4
+    pub diagonal: [f64; 3],
+}
+
+/// Configuration of the switch to an extended Kalman filter, cf. [EkfTrigger].
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EkfTriggerSerde {
+    pub num_msrs: usize,
+    #[serde(
+        serialize_with = "duration_to_str",
+        deserialize_with = "duration_from_str"
+    )]
+    pub disable_time: Duration,
+}
+
+impl ConfigRepr for OdSetupSerde {}
+
+impl OdSetupSerde {
+    /// Returns the CRC32 checksum of the file at the provided path, used to reference the tracking arc.
+    pub fn arc_checksum<P: AsRef<Path>>(path: P) -> Result<u32, ConfigError> {
+        let bytes = std::fs::read(path).context(ReadSnafu)?;
+        let mut crc = Crc::new();
+        crc.update(&bytes);
+        Ok(crc.sum())
+    }
+
+    /// Returns a copy of this setup where all relative paths are made relative to the provided directory.
+    pub fn relative_to<P: AsRef<Path>>(&self, dir: P) -> Self {
+        let rebase = |path: &String| -> String {
+            let as_path = Path::new(path);
+            if as_path.is_relative() {
+                dir.as_ref().join(as_path).to_string_lossy().to_string()
+            } else {
+                path.clone()
+            }
+        };
+
+        let mut me = self.clone();
+        me.arc_path = rebase(&self.arc_path);
+        me.estimates_path = self.estimates_path.as_ref().map(rebase);
+        me
+    }
+
+    /// Validates this setup and reconstructs the filter, its dynamics, the devices, and the tracking arc, ready to run.
+    ///
+    /// Errors if the tracking arc does not match its checksum, if a solve-for parameter is not supported by the dynamics,
+    /// or if the devices or their weights are inconsistent with the tracking arc.
+    pub fn build(&self, almanac: Arc<Almanac>) -> Result<OdSetup, ODError> {
+        let crc32 = Self::arc_checksum(&self.arc_path).context(ODConfigSnafu)?;
+        if crc32 != self.arc_crc32 {
+            return Err(invalid(format!(
+                "tracking arc {} has checksum {crc32} but the setup expects {}",
+                self.arc_path, self.arc_crc32
+            )));
+        }
+
+        let arc = DynamicTrackingArc::from_parquet(&self.arc_path)
+            .map_err(|e| invalid(format!("loading tracking arc {}: {e}", self.arc_path)))?
+            .to_tracking_arc::<RangeDoppler>()
+            .context(ODIOSnafu)?;
+
+        // Devices must cover the arc, and weights may only reference devices of the arc.
+        let arc_devices = arc.device_names();
+        for name in &arc_devices {
+            if !self.devices.contains_key(*name) {
+                return Err(invalid(format!(
+                    "device `{name}` of the tracking arc is not configured"
+                )));
+            }
+        }
+
+        let mut devices = BTreeMap::new();
+        for (name, device) in &self.devices {
+            if &device.name != name {
+                return Err(invalid(format!(
+                    "device configured as `{name}` is named `{}`",
+                    device.name
+                )));
+            }
+            if !arc_devices.contains(&name) {
+                warn!("no measurements from {name} in the tracking arc");
+            }
+            devices.insert(name.clone(), device.clone());
+        }
+
+        let mut weights = MeasurementWeights::default();
+        for (name, scale) in &self.measurement_weights {
+            if !arc_devices.contains(&name) {
+                return Err(invalid(format!(
+                    "weighted device `{name}` is not in the tracking arc (available: {arc_devices:?})"
+                )));
+            }
+            if *scale <= 0.0 || !scale.is_finite() {
+                return Err(invalid(format!(
+                    "weight of `{name}` must be strictly positive, got {scale}"
+                )));
+            }
+            weights = weights.with_device(name, DeviceWeight::new(*scale));
+        }
+
+        let options = PresetOptions {
+            harmonics_degree: self.harmonics_degree,
+            harmonics_order: self.harmonics_order,
+            ..Default::default()
+        };
+        let dynamics = self
+            .dynamics
+            .build(almanac.clone(), options)
+            .map_err(|e| invalid(format!("building {} dynamics: {e}", self.dynamics)))?;
+
+        let covar = self.apriori_covar(&dynamics)?;
+        let prop = match self.integrator {
+            Some(opts) => Propagator::rk89(dynamics, opts),
+            None => Propagator::default(dynamics),
+        };
+
+        let initial_estimate = KfEstimate::from_covar(self.initial_state, covar);
+        let kf = match self.snc {
+            Some(snc) => KF::new(
+                initial_estimate,
+                SNC3::from_diagonal(snc.disable_time, &snc.diagonal),
+            ),
+            None => KF::no_snc(initial_estimate),
+        };
+
+        Ok(OdSetup {
+            prop,
+            kf,
+            arc,
+            devices,
+            weights,
+            initial_state: self.initial_state,
+            resid_crit: self.resid_crit,
+            ekf: self.ekf,
+            estimates_path: self.estimates_path.as_ref().map(PathBuf::from),
+            almanac,
+        })
+    }
+
+    /// Builds the a priori covariance from the solve-for list, checking that each parameter is supported by the dynamics.
+    fn apriori_covar(&self, dynamics: &SpacecraftDynamics) -> Result<SMatrix<f64, 9, 9>, ODError> {
+        let mut covar = SMatrix::<f64, 9, 9>::zeros();
+        let mut solved = [false; 9];
+
+        for solve_for in &self.solve_for {
+            let idx = match solve_for.param {
+                StateParameter::X => 0,
+                StateParameter::Y => 1,
+                StateParameter::Z => 2,
+                StateParameter::VX => 3,
+                StateParameter::VY => 4,
+                StateParameter::VZ => 5,
+                StateParameter::Cr | StateParameter::Cd => {
+                    let idx = if solve_for.param == StateParameter::Cr {
+                        6
+                    } else {
+                        7
+                    };
+                    if !dynamics
+                        .force_models
+                        .iter()
+                        .any(|model| model.estimation_index() == Some(idx))
+                    {
+                        return Err(invalid(format!(
+                            "{} cannot be solved for because the {} dynamics do not estimate it",
+                            solve_for.param, self.dynamics
+                        )));
+                    }
+                    idx
+                }
+                param => {
+                    return Err(invalid(format!("solving for {param} is not supported")));
+                }
+            };
+
+            if solved[idx] {
+                return Err(invalid(format!(
+                    "{} is listed more than once in the solve-for parameters",
+                    solve_for.param
+                )));
+            }
+            if solve_for.sigma <= 0.0 || !solve_for.sigma.is_finite() {
+                return Err(invalid(format!(
+                    "a priori sigma of {} must be strictly positive, got {}",
+                    solve_for.param, solve_for.sigma
+                )));
+            }
+            solved[idx] = true;
+            covar[(idx, idx)] = solve_for.sigma.powi(2);
+        }
+
+        if solved[..6].iter().any(|is_solved| !is_solved) {
+            return Err(invalid(
+                "all of X, Y, Z, VX, VY, VZ must be solved for".to_string(),
+            ));
+        }
+
+        Ok(covar)
+    }
+}
+
+/// An orbit determination run reconstructed from an [OdSetupSerde], ready to run.
+pub struct OdSetup {
+    pub prop: Propagator<SpacecraftDynamics>,
+    pub kf: KF<Spacecraft, Const<3>, Const<2>>,
+    pub arc: TrackingArc<RangeDoppler>,
+    pub devices: BTreeMap<String, GroundStation>,
+    /// Weights of the measurements of each device
+    pub weights: MeasurementWeights,
+    pub initial_state: Spacecraft,
+    pub resid_crit: Option<ResidRejectCrit>,
+    pub ekf: Option<EkfTriggerSerde>,
+    pub estimates_path: Option<PathBuf>,
+    pub almanac: Arc<Almanac>,
+}
+
+impl OdSetup {
+    /// Initializes the orbit determination process of this setup, before any measurement is processed.
+    pub fn odp(&self) -> SpacecraftODProcess<'_> {
+        let prop_est = self
+            .prop
+            .with(self.initial_state.with_stm(), self.almanac.clone());
+
+        let odp = match self.ekf {
+            Some(ekf) => ODProcess::ekf(
+                prop_est,
+                self.kf.clone(),
+                EkfTrigger::new(ekf.num_msrs, ekf.disable_time),
+                self.resid_crit,
+                self.almanac.clone(),
+            ),
+            None => ODProcess::ckf(
+                prop_est,
+                self.kf.clone(),
+                self.resid_crit,
+                self.almanac.clone(),
+            ),
+        };
+
+        odp.with_weights(self.weights.clone())
+    }
+
+    /// Processes the whole tracking arc, writes the estimates if an output path is set, and returns the estimates.
+    pub fn run(&self) -> Result<Vec<KfEstimate<Spacecraft>>, ODError> {
+        let mut odp = self.odp();
+
+        let step_size = self
+            .arc
+            .min_duration_sep()
+            .ok_or(ODError::TooFewMeasurements {
+                action: "determining the minimum step size",
+                need: 2,
+            })?;
+
+        let mut devices = self.devices.clone();
+        odp.process(&self.arc.measurements, &mut devices, step_size)?;
+
+        if let Some(path) = &self.estimates_path {
+            let path = odp.to_parquet(path, ExportCfg::default())?;
+            info!("OD estimates written to {}", path.display());
+        }
+
+        Ok(odp.estimates)
+    }
+}
+
+/// Loads the OD setup YAML file at the provided path, validates it, and runs it end-to-end, returning the estimates.
+///
+/// Relative paths in the setup are relative to the directory of the setup file.
+pub fn run_from_setup<P: AsRef<Path>>(
+    path: P,
+    almanac: Arc<Almanac>,
+) -> Result<Vec<KfEstimate<Spacecraft>>, ODError> {
+    let setup = OdSetupSerde::load(&path).context(ODConfigSnafu)?;
+    let dir = path
+        .as_ref()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    setup.relative_to(dir).build(almanac)?.run()
+}
+
+fn invalid(msg: String) -> ODError {
+    ODError::ODConfigError {
+        source: ConfigError::InvalidConfig { msg },
+    }
+}
@@ -0,0 +1,217 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::io::{epoch_from_str, epoch_to_str, ConfigError, ConfigRepr};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector};
+use crate::time::Epoch;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A time-windowed override of the weight of a device, e.g. to deweight a station during antenna maintenance.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeightOverride {
+    /// Start epoch of this override (included)
+    #[serde(serialize_with = "epoch_to_str", deserialize_with = "epoch_from_str")]
+    pub start: Epoch,
+    /// End epoch of this override (excluded)
+    #[serde(serialize_with = "epoch_to_str", deserialize_with = "epoch_from_str")]
+    pub end: Epoch,
+    /// Scale factor on the measurement noise covariance, applied on top of the weight of the device
+    pub scale: f64,
+}
+
+impl WeightOverride {
+    /// Returns whether the provided epoch is within this override (the end is excluded)
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        (self.start..self.end).contains(&epoch)
+    }
+}
+
+/// Weight of the measurements of a device, as scale factors on its measurement noise covariance R.
+///
+/// A scale greater than one deweights the device: a scale of 4.0 doubles the noise sigma of its measurements.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceWeight {
+    /// Scale factor on all of the components of the measurement
+    #[serde(default = "unit_scale")]
+    pub scale: f64,
+    /// Additional scale factor on each component of the measurement, e.g. `[1.0, 10.0]` only deweights the Doppler of range and Doppler measurements
+    #[serde(default)]
+    pub components: Option<Vec<f64>>,
+    /// Time-windowed overrides, all of the overrides active at a given epoch apply
+    #[serde(default)]
+    pub overrides: Vec<WeightOverride>,
+}
+
+fn unit_scale() -> f64 {
+    1.0
+}
+
+impl Default for DeviceWeight {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl DeviceWeight {
+    /// Initializes a new device weight applying the same scale to all of the components of its measurements.
+    pub fn new(scale: f64) -> Self {
+        Self {
+            scale,
+            components: None,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Sets the additional scale factor of each component of the measurements of this device.
+    pub fn with_components(mut self, components: Vec<f64>) -> Self {
+        self.components = Some(components);
+        self
+    }
+
+    /// Adds a time-windowed override of this device weight, e.g. to deweight it during known problem periods.
+    pub fn with_override(mut self, start: Epoch, end: Epoch, scale: f64) -> Self {
+        self.overrides.push(WeightOverride { start, end, scale });
+        self
+    }
+
+    /// Returns the scale factor of each of the `dim` components of the measurements at the provided epoch.
+    pub fn scales_at(&self, epoch: Epoch, dim: usize) -> Result<Vec<f64>, ConfigError> {
+        let mut scale = self.scale;
+        for window in self
+            .overrides
+            .iter()
+            .filter(|window| window.contains(epoch))
+        {
+            scale *= window.scale;
+        }
+
+        match &self.components {
+            Some(components) => {
+                if components.len() != dim {
+                    return Err(ConfigError::InvalidConfig {
+                        msg: format!(
+                            "{} component weights provided for measurements of {dim} components",
+                            components.len()
+                        ),
+                    });
+                }
+                Ok(components.iter().map(|comp| comp * scale).collect())
+            }
+            None => Ok(vec![scale; dim]),
+        }
+    }
+
+    /// Checks that all of the scales are strictly positive and finite and that the overrides are chronological.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let check = |what: String, scale: f64| {
+            if scale > 0.0 && scale.is_finite() {
+                Ok(())
+            } else {
+                Err(ConfigError::InvalidConfig {
+                    msg: format!("{what} must be strictly positive, got {scale}"),
+                })
+            }
+        };
+
+        check("weight scale".to_string(), self.scale)?;
+        for (ii, comp) in self.components.iter().flatten().enumerate() {
+            check(format!("weight of component #{ii}"), *comp)?;
+        }
+        for (ii, window) in self.overrides.iter().enumerate() {
+            check(format!("weight override #{ii}"), window.scale)?;
+            if window.end <= window.start {
+                return Err(ConfigError::InvalidConfig {
+                    msg: format!(
+                        "weight override #{ii} ends ({}) before it starts ({})",
+                        window.end, window.start
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Weighting of the measurements of each device, applied by the orbit determination process on the measurement noise covariance
+/// of each measurement before the measurement update. Devices which are not listed are not weighted.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeasurementWeights {
+    /// Weight of each device, indexed by its name
+    pub devices: BTreeMap<String, DeviceWeight>,
+}
+
+impl MeasurementWeights {
+    /// Sets the weight of the provided device.
+    pub fn with_device(mut self, name: &str, weight: DeviceWeight) -> Self {
+        self.devices.insert(name.to_string(), weight);
+        self
+    }
+
+    /// Checks the weight of each device.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (name, weight) in &self.devices {
+            weight.validate().map_err(|e| ConfigError::InvalidConfig {
+                msg: format!("{name}: {e}"),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Logs a warning for each weighted device which is not in the provided list of known devices.
+    pub fn warn_unknown<'a, I: Iterator<Item = &'a String>>(&self, known: I) {
+        let known = known.collect::<Vec<&String>>();
+        for name in self.devices.keys() {
+            if !known.contains(&name) {
+                warn!("measurement weights reference unknown device `{name}` (known devices: {known:?})");
+            }
+        }
+    }
+
+    /// Returns the scale factor on each component of the measurement noise covariance of the provided device at the provided epoch.
+    pub fn scale_at<M: DimName>(
+        &self,
+        device: &str,
+        epoch: Epoch,
+    ) -> Result<OVector<f64, M>, ConfigError>
+    where
+        DefaultAllocator: Allocator<M>,
+    {
+        match self.devices.get(device) {
+            Some(weight) => Ok(OVector::<f64, M>::from_vec(
+                weight.scales_at(epoch, M::dim())?,
+            )),
+            None => Ok(OVector::<f64, M>::repeat(1.0)),
+        }
+    }
+}
+
+impl ConfigRepr for MeasurementWeights {}
+
+/// Scales the provided measurement noise covariance by the provided component scales, i.e. each variance is multiplied by its scale
+/// and each covariance by the square root of the product of the scales of both components, which keeps the correlations unchanged.
+pub(crate) fn weighted_covar<M: DimName>(
+    covar: &OMatrix<f64, M, M>,
+    scale: &OVector<f64, M>,
+) -> OMatrix<f64, M, M>
+where
+    DefaultAllocator: Allocator<M> + Allocator<M, M>,
+{
+    OMatrix::<f64, M, M>::from_fn(|i, j| covar[(i, j)] * (scale[i] * scale[j]).sqrt())
+}
@@ -16,11 +16,10 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::mc::NyxRng;
 use anise::almanac::Almanac;
 use hifitime::{Duration, Epoch, TimeSeries, TimeUnits};
 use num::integer::gcd;
-use rand::SeedableRng;
-use rand_pcg::Pcg64Mcg;
 
 use crate::dynamics::NyxError;
 use crate::io::ConfigError;
@@ -61,7 +60,7 @@ where
     /// Configuration of each device
     pub configs: BTreeMap<String, TrkConfig>,
     /// Random number generator used for this tracking arc, ensures repeatability
-    rng: Pcg64Mcg,
+    rng: NyxRng,
     /// Greatest common denominator time series that allows this arc to meet all of the conditions.
     time_series: TimeSeries,
     _msr_in: PhantomData<MsrIn>,
@@ -86,7 +85,7 @@ where
         devices: Vec<D>,
         trajectory: Traj<MsrIn>,
         configs: BTreeMap<String, TrkConfig>,
-        rng: Pcg64Mcg,
+        rng: NyxRng,
     ) -> Result<Self, ConfigError> {
         // Check that each device has an associated configurations.
         // We don't care if there are more configurations than chosen devices.
@@ -149,7 +148,7 @@ where
         configs: BTreeMap<String, TrkConfig>,
         seed: u64,
     ) -> Result<Self, ConfigError> {
-        let rng = Pcg64Mcg::new(seed as u128);
+        let rng = NyxRng::from_seed(seed);
 
         Self::with_rng(devices, trajectory, configs, rng)
     }
@@ -160,7 +159,7 @@ where
         trajectory: Traj<MsrIn>,
         configs: BTreeMap<String, TrkConfig>,
     ) -> Result<Self, ConfigError> {
-        let rng = Pcg64Mcg::from_entropy();
+        let rng = NyxRng::from_entropy();
 
         Self::with_rng(devices, trajectory, configs, rng)
     }
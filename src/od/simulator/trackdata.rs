@@ -18,10 +18,10 @@
 
 use std::sync::Arc;
 
+use crate::mc::NyxRng;
 use anise::almanac::Almanac;
 use anise::errors::AlmanacResult;
 use hifitime::Epoch;
-use rand_pcg::Pcg64Mcg;
 
 use crate::io::ConfigRepr;
 use crate::linalg::allocator::Allocator;
@@ -50,7 +50,7 @@ where
     /// If the random number generator is provided, it shall be used to add noise to the measurement.
     ///
     /// # Choice of the random number generator
-    /// The Pcg64Mcg is chosen because it is fast, space efficient, and has a good statistical distribution.
+    /// The NyxRng is used by all stochastic features so that a seeded simulation is reproducible on all platforms, cf. [NyxRng].
     ///
     /// # Errors
     /// + A specific measurement is requested but the noise on that measurement type is not configured.
@@ -59,7 +59,7 @@ where
         &mut self,
         epoch: Epoch,
         traj: &Traj<MsrIn>,
-        rng: Option<&mut Pcg64Mcg>,
+        rng: Option<&mut NyxRng>,
         almanac: Arc<Almanac>,
     ) -> Result<Option<Msr>, ODError>;
 
@@ -70,7 +70,7 @@ where
     fn measure_instantaneous(
         &mut self,
         rx: MsrIn,
-        rng: Option<&mut Pcg64Mcg>,
+        rng: Option<&mut NyxRng>,
         almanac: Arc<Almanac>,
     ) -> Result<Option<Msr>, ODError>;
 
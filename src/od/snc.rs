@@ -19,9 +19,10 @@
 use crate::cosmic::Frame;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector, U3, U6};
-use crate::time::{Duration, Epoch};
+use crate::time::{Duration, Epoch, Unit};
 
 use std::fmt;
+use typed_builder::TypedBuilder;
 
 #[allow(clippy::upper_case_acronyms)]
 pub type SNC3 = SNC<U3>;
@@ -42,6 +43,8 @@ where
     pub disable_time: Duration,
     // Stores the initial epoch when the SNC is requested, needed for decay. Kalman filter will edit this automatically.
     pub init_epoch: Option<Epoch>,
+    /// Correlation time constant of the unmodeled accelerations (first order Gauss-Markov), or None if they are uncorrelated between time updates
+    pub time_constant: Option<Duration>,
     diag: OVector<f64, A>,
     decay_diag: Option<Vec<f64>>,
     // Stores the previous epoch of the SNC request, needed for disable time
@@ -66,8 +69,13 @@ where
         }
         write!(
             f,
-            "SNC: diag({}) {}",
+            "SNC: diag({}){} {}",
             fmt_cov.join(", "),
+            if let Some(tau) = self.time_constant {
+                format!(" with time constant {tau}")
+            } else {
+                "".to_string()
+            },
             if let Some(start) = self.start_time {
                 format!("starting at {start}")
             } else {
@@ -109,6 +117,7 @@ where
             disable_time,
             start_time: None,
             frame: None,
+            time_constant: None,
             decay_diag: None,
             init_epoch: None,
             prev_epoch: None,
@@ -140,6 +149,26 @@ where
         me
     }
 
+    /// Returns the factor by which the SNC matrix is scaled for a time update of the provided duration.
+    ///
+    /// The Gamma matrix of the Kalman filter assumes that the unmodeled acceleration is constant over the time update. If these
+    /// accelerations are exponentially correlated with a time constant τ, the variance of their mean over Δt is
+    /// σ² × 2τ/Δt × (1 - τ/Δt × (1 - exp(-Δt/τ))): it tends to σ² for short updates and decreases as 2τ/Δt for long updates.
+    /// Without a time constant, this factor is one.
+    pub fn correlation_scale(&self, delta_t: Duration) -> f64 {
+        match self.time_constant {
+            Some(tau) => {
+                let ratio = delta_t.abs().to_seconds() / tau.to_seconds();
+                if ratio < 1e-6 {
+                    1.0
+                } else {
+                    2.0 / ratio * (1.0 - (1.0 - (-ratio).exp()) / ratio)
+                }
+            }
+            None => 1.0,
+        }
+    }
+
     /// Returns the SNC matrix (_not_ incl. Gamma matrix approximation) at the provided Epoch.
     /// May be None if:
     ///  1. Start time of this matrix is _after_ epoch
@@ -187,6 +216,102 @@ where
     }
 }
 
+/// Configuration of a state noise compensation model, i.e. the process noise added to the covariance at each time update
+/// of the Kalman filter to account for unmodeled accelerations.
+///
+/// The unmodeled accelerations are isotropic with the provided standard deviation. If a time constant is provided, they are
+/// modeled as exponentially correlated (first order Gauss-Markov) accelerations instead of being uncorrelated between time updates.
+#[derive(Copy, Clone, Debug, PartialEq, TypedBuilder)]
+pub struct ProcessNoiseConfig {
+    /// Standard deviation of the unmodeled accelerations, in km/s^2
+    pub accel_sigma_km_s2: f64,
+    /// Correlation time constant of the unmodeled accelerations
+    #[builder(default, setter(strip_option))]
+    pub time_constant: Option<Duration>,
+    /// The process noise is only added if the time between two time updates is less than this duration
+    #[builder(default = 2 * Unit::Minute)]
+    pub disable_time: Duration,
+    /// Epoch from which the process noise is applied, if not from the start
+    #[builder(default, setter(strip_option))]
+    pub start_time: Option<Epoch>,
+}
+
+impl ProcessNoiseConfig {
+    /// Builds the state noise compensation of this configuration, with an acceleration variance on each of its axes.
+    pub fn to_snc<A: DimName>(&self) -> SNC<A>
+    where
+        DefaultAllocator: Allocator<A> + Allocator<A, A>,
+    {
+        let mut snc = SNC::from_diagonal(
+            self.disable_time,
+            &vec![self.accel_sigma_km_s2.powi(2); A::dim()],
+        );
+        snc.start_time = self.start_time;
+        snc.time_constant = self.time_constant;
+        snc
+    }
+}
+
+/// Configuration of the adaptive scaling of the process noise based on the innovation statistics of the filter.
+///
+/// The normalized innovation squared (NIS) of each measurement is `y^T⋅(H⋅P̄⋅H^T + R)^-1⋅y`, where `y` is the prefit residual,
+/// `H⋅P̄⋅H^T` the predicted covariance mapped into the measurement space, and `R` the measurement noise. Divided by the measurement
+/// dimension, it has an expected value of one for a consistent filter.
+/// Once the warmup is over, the filter averages it over a sliding window of measurements: if the mean is above the upper bound,
+/// the filter is overconfident and the process noise is scaled up by the scale factor; if it is below the lower bound, the process
+/// noise is scaled down. The window is restarted after each adaptation so that the next decision only uses innovations computed
+/// with the new process noise.
+#[derive(Copy, Clone, Debug, PartialEq, TypedBuilder)]
+pub struct AdaptiveSnc {
+    /// Number of measurements in the sliding window of innovations
+    #[builder(default = 20)]
+    pub window: usize,
+    /// Number of measurements processed before any adaptation, to let the filter converge
+    #[builder(default = 20)]
+    pub warmup: usize,
+    /// Mean NIS per measurement component above which the process noise is scaled up
+    #[builder(default = 2.0)]
+    pub upper_ratio: f64,
+    /// Mean NIS per measurement component below which the process noise is scaled down
+    #[builder(default = 0.5)]
+    pub lower_ratio: f64,
+    /// Factor by which the process noise is multiplied or divided at each adaptation
+    #[builder(default = 4.0)]
+    pub scale_factor: f64,
+    /// Minimum scale applied to the configured process noise
+    #[builder(default = 0.1)]
+    pub min_scale: f64,
+    /// Maximum scale applied to the configured process noise
+    #[builder(default = 1e4)]
+    pub max_scale: f64,
+}
+
+impl AdaptiveSnc {
+    /// Returns the new scale of the process noise given the current scale and the mean NIS per measurement component, if it changes.
+    pub fn adapt(&self, scale: f64, mean_nis: f64) -> Option<f64> {
+        let new_scale = if mean_nis > self.upper_ratio {
+            scale * self.scale_factor
+        } else if mean_nis < self.lower_ratio {
+            scale / self.scale_factor
+        } else {
+            return None;
+        }
+        .clamp(self.min_scale, self.max_scale);
+
+        if new_scale == scale {
+            None
+        } else {
+            Some(new_scale)
+        }
+    }
+}
+
+impl Default for AdaptiveSnc {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
 #[test]
 fn test_snc_init() {
     use crate::time::Unit;
@@ -204,3 +329,45 @@ fn test_snc_init() {
     );
     println!("{}", snc_std);
 }
+
+#[test]
+fn test_process_noise_config() {
+    use crate::time::TimeUnits;
+    let white = ProcessNoiseConfig::builder()
+        .accel_sigma_km_s2(1e-9)
+        .build()
+        .to_snc::<U3>();
+    let snc = white.to_matrix(Epoch::from_et_seconds(0.0)).unwrap();
+    assert!((snc - OMatrix::<f64, U3, U3>::identity() * 1e-18).norm() < 1e-30);
+    assert_eq!(white.correlation_scale(1.hours()), 1.0);
+
+    let correlated = ProcessNoiseConfig::builder()
+        .accel_sigma_km_s2(1e-9)
+        .time_constant(10.minutes())
+        .build()
+        .to_snc::<U6>();
+    println!("{correlated}");
+    // Updates much shorter than the time constant see the full variance
+    assert!((correlated.correlation_scale(1.seconds()) - 1.0).abs() < 1e-3);
+    assert_eq!(correlated.correlation_scale(0.seconds()), 1.0);
+    // Updates much longer than the time constant average the accelerations out
+    let long = correlated.correlation_scale(100.hours());
+    assert!((long - 2.0 * 600.0 / 360_000.0).abs() < 1e-5);
+    // And the scale decreases with the duration of the update
+    assert!(correlated.correlation_scale(5.minutes()) > correlated.correlation_scale(20.minutes()));
+}
+
+#[test]
+fn test_adaptive_snc() {
+    let adaptive = AdaptiveSnc::builder().max_scale(10.0).build();
+    // Consistent innovations do not change the scale
+    assert_eq!(adaptive.adapt(1.0, 1.0), None);
+    // Overconfident filter
+    assert_eq!(adaptive.adapt(1.0, 5.0), Some(4.0));
+    // Clamped to the bounds
+    assert_eq!(adaptive.adapt(4.0, 5.0), Some(10.0));
+    assert_eq!(adaptive.adapt(10.0, 5.0), None);
+    // Underconfident filter
+    assert_eq!(adaptive.adapt(1.0, 0.1), Some(0.25));
+    assert_eq!(adaptive.adapt(0.25, 0.1), Some(0.1));
+}
@@ -0,0 +1,345 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::Almanac;
+use serde_derive::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::fmt;
+use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+use super::msr::{RangeDoppler, TrackingArc};
+use super::{
+    GroundStation, ODAlmanacSnafu, ODError, ODPhysicsSnafu, ODTrajSnafu, TrackingDeviceSim,
+};
+use crate::linalg::{DMatrix, DVector, Matrix3, Vector2, Vector3};
+use crate::md::prelude::Traj;
+use crate::Spacecraft;
+
+/// A component of the location of a ground station which may be estimated, either along an axis of its body fixed frame
+/// or along a local direction of the station.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StationComponent {
+    X,
+    Y,
+    Z,
+    East,
+    North,
+    Up,
+}
+
+impl StationComponent {
+    /// Unit vector of this component in the body fixed frame of the station
+    pub fn direction(&self, station: &GroundStation) -> Vector3<f64> {
+        let sez = station.dcm_to_sez();
+        match self {
+            Self::X => Vector3::x(),
+            Self::Y => Vector3::y(),
+            Self::Z => Vector3::z(),
+            Self::East => sez.row(1).transpose(),
+            Self::North => -sez.row(0).transpose(),
+            Self::Up => sez.row(2).transpose(),
+        }
+    }
+}
+
+impl fmt::Display for StationComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// The location of a ground station to estimate. The components which are not listed are pinned to their a priori value,
+/// e.g. only listing `Up` estimates the height of the station.
+#[derive(Clone, Debug, PartialEq, TypedBuilder)]
+#[builder(doc)]
+pub struct StationSolveFor {
+    /// A priori station, whose name must match that of its measurements
+    pub station: GroundStation,
+    /// Estimated components, defaults to the three axes of the body fixed frame
+    #[builder(default = vec![StationComponent::X, StationComponent::Y, StationComponent::Z])]
+    pub components: Vec<StationComponent>,
+    /// A priori one sigma uncertainty of each component, in km
+    pub sigma_km: f64,
+}
+
+/// Weighted least squares estimation of the location of ground stations from range and Doppler measurements of a spacecraft
+/// whose trajectory is well known, e.g. in a calibration campaign.
+///
+/// The trajectory is not estimated: the solve-for vector only stacks the corrections of the estimated components of each station,
+/// starting from their a priori location, and is solved with a Gauss-Newton iteration. Measurements of other stations are ignored.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(doc)]
+pub struct StationLocationSolver {
+    pub solve_for: Vec<StationSolveFor>,
+    #[builder(default = 10)]
+    pub max_iterations: usize,
+    /// The solution has converged when the norm of the correction of an iteration is below this tolerance, in km
+    #[builder(default = 1e-7)]
+    pub tolerance_km: f64,
+}
+
+/// The estimated location of a ground station, with the formal uncertainty of the estimate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StationLocationEstimate {
+    /// Station at its a priori location
+    pub apriori: GroundStation,
+    /// Station at its estimated location
+    pub estimated: GroundStation,
+    pub components: Vec<StationComponent>,
+    /// Estimated correction of each component, in km
+    pub corrections_km: Vec<f64>,
+    /// Formal one sigma uncertainty of each correction, in km
+    pub sigmas_km: Vec<f64>,
+    /// Estimated correction in the body fixed frame, in km
+    pub offset_km: Vector3<f64>,
+    /// Covariance of the correction in the body fixed frame, in km^2 (singular if any component is pinned)
+    pub offset_covar_km2: Matrix3<f64>,
+    /// Number of measurements of this station used in the estimation
+    pub num_msrs: usize,
+    /// RMS of the range (km) and Doppler (km/s) residuals of the last iteration
+    pub residual_rms: Vector2<f64>,
+}
+
+impl fmt::Display for StationLocationEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({} msrs):", self.apriori.name, self.num_msrs)?;
+        for ((component, correction_km), sigma_km) in self
+            .components
+            .iter()
+            .zip(&self.corrections_km)
+            .zip(&self.sigmas_km)
+        {
+            write!(
+                f,
+                " {component} {:.3} ± {:.3} m",
+                correction_km * 1e3,
+                sigma_km * 1e3
+            )?;
+        }
+        write!(
+            f,
+            " (residual RMS: {:.3} m, {:.3} mm/s)",
+            self.residual_rms[0] * 1e3,
+            self.residual_rms[1] * 1e6
+        )
+    }
+}
+
+impl StationLocationSolver {
+    /// Estimates the location of the stations from the measurements of the arc, given the trajectory of the tracked spacecraft.
+    ///
+    /// Errors if the measurement noise of a station is not configured, or if the solution does not converge.
+    pub fn solve(
+        &self,
+        arc: &TrackingArc<RangeDoppler>,
+        traj: &Traj<Spacecraft>,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<StationLocationEstimate>, ODError> {
+        // Index of the first component of each station in the solve-for vector
+        let mut starts = Vec::with_capacity(self.solve_for.len());
+        let mut num_params = 0;
+        for solve_for in &self.solve_for {
+            starts.push(num_params);
+            num_params += solve_for.components.len();
+        }
+
+        let directions = self
+            .solve_for
+            .iter()
+            .map(|solve_for| {
+                solve_for
+                    .components
+                    .iter()
+                    .map(|component| component.direction(&solve_for.station))
+                    .collect::<Vec<Vector3<f64>>>()
+            })
+            .collect::<Vec<_>>();
+
+        let apriori_info = DMatrix::from_diagonal(&DVector::from_iterator(
+            num_params,
+            self.solve_for.iter().flat_map(|solve_for| {
+                solve_for
+                    .components
+                    .iter()
+                    .map(|_| solve_for.sigma_km.powi(-2))
+            }),
+        ));
+
+        let offset_of = |x: &DVector<f64>, k: usize| -> Vector3<f64> {
+            directions[k]
+                .iter()
+                .enumerate()
+                .map(|(i, direction)| x[starts[k] + i] * direction)
+                .sum()
+        };
+
+        let mut x = DVector::<f64>::zeros(num_params);
+        let mut covar = apriori_info.clone();
+        let mut num_msrs = vec![0; self.solve_for.len()];
+        let mut sum_sq_resid = vec![Vector2::zeros(); self.solve_for.len()];
+        let mut converged = false;
+
+        for iteration in 0..self.max_iterations {
+            let mut stations = Vec::with_capacity(self.solve_for.len());
+            for (k, solve_for) in self.solve_for.iter().enumerate() {
+                stations.push(
+                    solve_for
+                        .station
+                        .with_offset_km(offset_of(&x, k), &almanac)
+                        .context(ODPhysicsSnafu)?,
+                );
+            }
+
+            // The a priori pulls the corrections back to zero
+            let mut info = apriori_info.clone();
+            let mut rhs = -(&apriori_info * &x);
+            num_msrs.fill(0);
+            sum_sq_resid.fill(Vector2::zeros());
+
+            for (name, msr) in &arc.measurements {
+                let Some(k) = self
+                    .solve_for
+                    .iter()
+                    .position(|solve_for| &solve_for.station.name == name)
+                else {
+                    continue;
+                };
+                let station = &mut stations[k];
+
+                let Some(computed) = station.measure(msr.epoch, traj, None, almanac.clone())?
+                else {
+                    // Below the elevation mask of the current location of the station
+                    continue;
+                };
+                let resid = msr.obs - computed.obs;
+
+                let rx = almanac
+                    .transform_to(
+                        traj.at(msr.epoch).context(ODTrajSnafu)?.orbit,
+                        station.frame,
+                        None,
+                    )
+                    .context(ODAlmanacSnafu {
+                        action: "transforming receiver into station frame",
+                    })?;
+                let tx = station
+                    .to_orbit(msr.epoch, &almanac)
+                    .context(ODPhysicsSnafu)?;
+
+                let rho_km = rx.radius_km - tx.radius_km;
+                let range_km = rho_km.norm();
+                let rho_hat = rho_km / range_km;
+                let v_rel_km_s = rx.velocity_km_s - tx.velocity_km_s;
+                let range_rate_km_s = rho_hat.dot(&v_rel_km_s);
+
+                // Partials of the range and Doppler with respect to the location of the station in the body fixed frame
+                let h_range = -rho_hat;
+                // The velocity of the station (cf. `GroundStation::to_orbit`) is the rotation of its body about the Z axis.
+                let omega_deg_s = station.angular_velocity_deg_s().context(ODPhysicsSnafu)?;
+                let omega_rad_s = Vector3::new(0.0, 0.0, omega_deg_s.to_radians());
+                let h_doppler = -(v_rel_km_s - range_rate_km_s * rho_hat) / range_km
+                    - rho_hat.cross(&omega_rad_s);
+
+                let msr_covar = station.measurement_covar(msr.epoch)?;
+
+                for (row, h, weight) in [
+                    (0, h_range, 1.0 / msr_covar[(0, 0)]),
+                    (1, h_doppler, 1.0 / msr_covar[(1, 1)]),
+                ] {
+                    let start = starts[k];
+                    for (i, dir_i) in directions[k].iter().enumerate() {
+                        let h_i = h.dot(dir_i);
+                        rhs[start + i] += weight * h_i * resid[row];
+                        for (j, dir_j) in directions[k].iter().enumerate() {
+                            info[(start + i, start + j)] += weight * h_i * h.dot(dir_j);
+                        }
+                    }
+                }
+
+                num_msrs[k] += 1;
+                sum_sq_resid[k] += resid.component_mul(&resid);
+            }
+
+            covar = info
+                .try_inverse()
+                .ok_or_else(|| ODError::InvalidCovariance {
+                    msg: "information matrix of the station locations is singular".to_string(),
+                })?;
+            let dx = &covar * rhs;
+            x += &dx;
+
+            debug!(
+                "station location iteration #{iteration}: |dx| = {:.3e} m",
+                dx.norm() * 1e3
+            );
+
+            if dx.norm() < self.tolerance_km {
+                converged = true;
+                break;
+            }
+        }
+
+        if !converged {
+            return Err(ODError::Diverged {
+                loops: self.max_iterations,
+            });
+        }
+
+        let mut estimates = Vec::with_capacity(self.solve_for.len());
+        for (k, solve_for) in self.solve_for.iter().enumerate() {
+            let start = starts[k];
+            let num_components = solve_for.components.len();
+
+            let mut t_mat = DMatrix::<f64>::zeros(3, num_components);
+            for (i, direction) in directions[k].iter().enumerate() {
+                t_mat.set_column(i, direction);
+            }
+            let station_covar = covar
+                .view((start, start), (num_components, num_components))
+                .into_owned();
+            let offset_covar = &t_mat * station_covar * t_mat.transpose();
+
+            let offset_km = offset_of(&x, k);
+            let residual_rms = if num_msrs[k] > 0 {
+                (sum_sq_resid[k] / num_msrs[k] as f64).map(f64::sqrt)
+            } else {
+                Vector2::zeros()
+            };
+
+            estimates.push(StationLocationEstimate {
+                apriori: solve_for.station.clone(),
+                estimated: solve_for
+                    .station
+                    .with_offset_km(offset_km, &almanac)
+                    .context(ODPhysicsSnafu)?,
+                components: solve_for.components.clone(),
+                corrections_km: x.rows(start, num_components).iter().copied().collect(),
+                sigmas_km: (0..num_components)
+                    .map(|i| covar[(start + i, start + i)].sqrt())
+                    .collect(),
+                offset_km,
+                offset_covar_km2: Matrix3::from_fn(|i, j| offset_covar[(i, j)]),
+                num_msrs: num_msrs[k],
+                residual_rms,
+            });
+        }
+
+        Ok(estimates)
+    }
+}
@@ -0,0 +1,225 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anise::prelude::Almanac;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use typed_builder::TypedBuilder;
+
+use super::msr::TrackingArc;
+use super::{Measurement, ODError, TrackingDeviceSim};
+use crate::io::ConfigError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, OVector};
+use crate::md::prelude::Traj;
+use crate::time::{Duration, Epoch, Unit};
+use crate::{Spacecraft, State, TimeTagged};
+
+/// Weighted least squares estimation of the time-tag bias of tracking devices, from their measurements of a spacecraft whose
+/// trajectory is well known, e.g. a converged OD solution.
+///
+/// The time-tag bias is the offset between the epoch tagged on a measurement and the epoch at which it was actually realized:
+/// the predicted observation of a measurement tagged at `t` is computed at `t - bias`. The partial of that observation with
+/// respect to the bias is the opposite of the rate of the observation, computed with a central difference. The bias of each
+/// device is solved independently with a Gauss-Newton iteration, and measurements of other devices are ignored.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(doc)]
+pub struct TimeTagBiasSolver {
+    /// Names of the devices whose time-tag bias is estimated
+    pub devices: Vec<String>,
+    /// A priori one sigma uncertainty of each bias, in seconds
+    #[builder(default = 1.0)]
+    pub sigma_s: f64,
+    /// Step of the central difference computing the rate of the observations
+    #[builder(default = Unit::Second * 1)]
+    pub rate_step: Duration,
+    #[builder(default = 10)]
+    pub max_iterations: usize,
+    /// The solution has converged when the correction of an iteration is below this tolerance, in seconds
+    #[builder(default = 1e-7)]
+    pub tolerance_s: f64,
+}
+
+/// The estimated time-tag bias of a tracking device, with the formal uncertainty of the estimate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeTagBiasEstimate {
+    pub device: String,
+    /// Estimated bias, in seconds: the measurements are tagged this late
+    pub bias_s: f64,
+    /// Formal one sigma uncertainty of the bias, in seconds
+    pub sigma_s: f64,
+    /// Number of measurements of this device used in the estimation
+    pub num_msrs: usize,
+    /// RMS of the residuals of the last iteration normalized by the measurement noise, which should be close to one
+    pub normalized_residual_rms: f64,
+}
+
+impl TimeTagBiasEstimate {
+    /// Removes this bias from the time tags of the measurements of this device in the provided arc.
+    pub fn correct<Msr>(&self, arc: &mut TrackingArc<Msr>)
+    where
+        Msr: Measurement,
+        DefaultAllocator: Allocator<Msr::MeasurementSize>,
+    {
+        for (name, msr) in arc.measurements.iter_mut() {
+            if name == &self.device {
+                msr.set_epoch(msr.epoch() - self.bias_s * Unit::Second);
+            }
+        }
+    }
+}
+
+impl fmt::Display for TimeTagBiasEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} msrs): time-tag bias of {:.6} ± {:.6} ms (normalized residual RMS: {:.3})",
+            self.device,
+            self.num_msrs,
+            self.bias_s * 1e3,
+            self.sigma_s * 1e3,
+            self.normalized_residual_rms
+        )
+    }
+}
+
+impl TimeTagBiasSolver {
+    /// Estimates the time-tag bias of the devices from the measurements of the arc, given the trajectory of the tracked spacecraft.
+    ///
+    /// Measurements whose central difference would fall outside of the trajectory are ignored. Errors if a requested device is
+    /// not in the provided devices, if its measurement noise is singular, or if the solution does not converge.
+    pub fn solve<Msr, D>(
+        &self,
+        arc: &TrackingArc<Msr>,
+        devices: &mut BTreeMap<String, D>,
+        traj: &Traj<Spacecraft>,
+        almanac: Arc<Almanac>,
+    ) -> Result<Vec<TimeTagBiasEstimate>, ODError>
+    where
+        Msr: Measurement,
+        D: TrackingDeviceSim<Spacecraft, Msr>,
+        DefaultAllocator:
+            Allocator<Msr::MeasurementSize> + Allocator<Msr::MeasurementSize, Msr::MeasurementSize>,
+    {
+        let (start, end) = (
+            traj.first().epoch() + self.rate_step,
+            traj.last().epoch() - self.rate_step,
+        );
+        let step_s = self.rate_step.to_seconds();
+        let apriori_info = self.sigma_s.powi(-2);
+
+        let mut estimates = Vec::with_capacity(self.devices.len());
+        for name in &self.devices {
+            let device = devices
+                .get_mut(name)
+                .ok_or_else(|| ODError::ODConfigError {
+                    source: ConfigError::InvalidConfig {
+                        msg: format!("no device named {name} to estimate its time-tag bias"),
+                    },
+                })?;
+
+            let mut bias_s = 0.0;
+            let mut covar = 1.0 / apriori_info;
+            let mut num_msrs = 0;
+            let mut num_obs = 0;
+            let mut sum_sq_resid = 0.0;
+            let mut converged = false;
+
+            for iteration in 0..self.max_iterations {
+                // The a priori pulls the bias back to zero
+                let mut info = apriori_info;
+                let mut rhs = -apriori_info * bias_s;
+                num_msrs = 0;
+                num_obs = 0;
+                sum_sq_resid = 0.0;
+
+                for (_, msr) in arc.measurements.iter().filter(|(dev, _)| dev == name) {
+                    let epoch: Epoch = msr.epoch() - bias_s * Unit::Second;
+                    if epoch < start || epoch > end {
+                        continue;
+                    }
+
+                    let (Some(computed), Some(before), Some(after)) = (
+                        device.measure(epoch, traj, None, almanac.clone())?,
+                        device.measure(epoch - self.rate_step, traj, None, almanac.clone())?,
+                        device.measure(epoch + self.rate_step, traj, None, almanac.clone())?,
+                    ) else {
+                        // Not visible from the device
+                        continue;
+                    };
+
+                    let resid: OVector<f64, Msr::MeasurementSize> =
+                        msr.observation() - computed.observation();
+                    let rate: OVector<f64, Msr::MeasurementSize> =
+                        (after.observation() - before.observation()) / (2.0 * step_s);
+                    let h = -rate;
+
+                    let weight = device
+                        .measurement_covar(msr.epoch())?
+                        .try_inverse()
+                        .ok_or(ODError::SingularNoiseRk)?;
+
+                    info += h.dot(&(&weight * &h));
+                    rhs += h.dot(&(&weight * &resid));
+
+                    num_msrs += 1;
+                    num_obs += resid.len();
+                    sum_sq_resid += resid.dot(&(&weight * &resid));
+                }
+
+                covar = 1.0 / info;
+                let delta_s = covar * rhs;
+                bias_s += delta_s;
+
+                debug!(
+                    "{name} time-tag bias iteration #{iteration}: {:.6} ms (correction of {:.3e} ms)",
+                    bias_s * 1e3,
+                    delta_s * 1e3
+                );
+
+                if delta_s.abs() < self.tolerance_s {
+                    converged = true;
+                    break;
+                }
+            }
+
+            if !converged {
+                return Err(ODError::Diverged {
+                    loops: self.max_iterations,
+                });
+            }
+
+            let estimate = TimeTagBiasEstimate {
+                device: name.clone(),
+                bias_s,
+                sigma_s: covar.sqrt(),
+                num_msrs,
+                normalized_residual_rms: if num_obs > 0 {
+                    (sum_sq_resid / num_obs as f64).sqrt()
+                } else {
+                    0.0
+                },
+            };
+            info!("{estimate}");
+            estimates.push(estimate);
+        }
+
+        Ok(estimates)
+    }
+}
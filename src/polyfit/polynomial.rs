@@ -21,6 +21,7 @@
 use std::fmt;
 use std::ops;
 
+use crate::linalg::{DMatrix, DVector};
 use crate::NyxError;
 
 /// Polynomial is a statically allocated polynomial.
@@ -38,6 +39,34 @@ impl<const SIZE: usize> Polynomial<SIZE> {
         }
     }
 
+    /// Least squares fit of this polynomial to the provided samples.
+    ///
+    /// Requires at least as many samples as coefficients.
+    pub fn fit(xs: &[f64], ys: &[f64]) -> Result<Self, NyxError> {
+        if xs.len() != ys.len() || xs.len() < SIZE {
+            return Err(NyxError::MathDomain {
+                msg: format!(
+                    "fitting {SIZE} coefficients requires at least as many samples, got {} abscissas and {} ordinates",
+                    xs.len(),
+                    ys.len()
+                ),
+            });
+        }
+
+        // Vandermonde matrix, with the columns ordered by power
+        let vander = DMatrix::from_fn(xs.len(), SIZE, |i, j| xs[i].powi(j as i32));
+        let coeffs = vander
+            .svd(true, true)
+            .solve(&DVector::from_column_slice(ys), f64::EPSILON)
+            .map_err(|msg| NyxError::MathDomain {
+                msg: msg.to_string(),
+            })?;
+
+        let mut me = Self::zeros();
+        me.coefficients.copy_from_slice(coeffs.as_slice());
+        Ok(me)
+    }
+
     /// Get the order of the polynomial
     pub const fn order(&self) -> usize {
         SIZE - 1
@@ -429,6 +458,20 @@ fn poly_quadratic() {
     }
 }
 
+#[test]
+fn poly_fit() {
+    let expected = Polynomial::from_most_significant([0.5, -2.0, 10.0]);
+    let xs = (-10..=10).map(f64::from).collect::<Vec<f64>>();
+    let ys = xs.iter().map(|x| expected.eval(*x)).collect::<Vec<f64>>();
+
+    let fitted = Polynomial::<3>::fit(&xs, &ys).unwrap();
+    for (fit, exp) in fitted.coefficients.iter().zip(expected.coefficients) {
+        assert!((fit - exp).abs() < 1e-10, "{fitted} != {expected}");
+    }
+
+    assert!(Polynomial::<3>::fit(&xs[..2], &ys[..2]).is_err());
+}
+
 #[test]
 fn poly_print() {
     let p = Polynomial {
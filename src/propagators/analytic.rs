@@ -0,0 +1,338 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+use anise::almanac::Almanac;
+
+use super::PropagationError;
+use crate::cosmic::Spacecraft;
+use crate::io::ConfigError;
+use crate::linalg::Vector3;
+use crate::md::trajectory::Traj;
+use crate::time::{Duration, Epoch};
+use crate::State;
+use nalgebra::{Rotation3, Unit};
+
+/// Maximum number of Newton iterations on the universal anomaly
+const MAX_ITERATIONS: usize = 50;
+
+/// A closed-form propagator for fast previews and coarse searches, _not_ for precise analyses.
+///
+/// The orbit is propagated in two-body dynamics with universal variables, so all conic types are supported.
+/// If J2 is set, the first order secular drift of the RAAN, the argument of periapsis, and the mean anomaly
+/// is applied on top of the two-body solution for closed orbits. The osculating elements of the initial state
+/// are used as mean elements, so the short periodic terms of J2 are ignored. The node drift is computed about
+/// the Z axis of the frame of the state, which should therefore be aligned with the pole of the central body
+/// (e.g. EME2000 for the Earth).
+///
+/// The trajectories it builds are named after this propagator such that they are identified as analytic, including in their exports.
+///
+/// Limitations: this is a standalone propagator, not a [super::Propagator] with some dynamics, so it cannot be used where
+/// a `Propagator` is expected (e.g. orbit determination, targeters, Monte Carlo runs). It ignores the force models, guidance,
+/// and STM of the spacecraft, and provides no event search during propagation: use `for_duration_with_traj` and search
+/// the resulting trajectory instead.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AnalyticPropagator {
+    /// Unnormalized J2 of the central body, set to zero for pure two-body propagation
+    pub j2: f64,
+    /// Output cadence of the trajectories
+    pub step: Duration,
+}
+
+impl AnalyticPropagator {
+    /// Unnormalized Earth J2 of the JGM3 model
+    pub const EARTH_J2_JGM3: f64 = 1.082_626_690_597_816_5e-3;
+
+    /// Two-body analytic propagator with trajectory states every `step`.
+    pub fn two_body(step: Duration) -> Self {
+        Self { j2: 0.0, step }
+    }
+
+    /// Two-body analytic propagator with the secular drift due to the provided unnormalized J2, with trajectory states every `step`.
+    pub fn two_body_j2(j2: f64, step: Duration) -> Self {
+        Self { j2, step }
+    }
+
+    /// Two-body analytic propagator with the secular drift due to the J2 of the Earth, with trajectory states every `step`.
+    pub fn earth_j2(step: Duration) -> Self {
+        Self::two_body_j2(Self::EARTH_J2_JGM3, step)
+    }
+
+    /// Returns an instance of this propagator for the provided state.
+    /// The almanac is not used by the propagation itself but kept to match the numerical propagator API.
+    pub fn with(&self, state: Spacecraft, almanac: Arc<Almanac>) -> AnalyticInstance {
+        AnalyticInstance {
+            state,
+            prop: *self,
+            almanac,
+        }
+    }
+
+    /// Returns the state of the provided spacecraft propagated by `dt`, which may be negative.
+    pub fn propagate(
+        &self,
+        state: Spacecraft,
+        dt: Duration,
+    ) -> Result<Spacecraft, PropagationError> {
+        let orbit = state.orbit;
+        let epoch = orbit.epoch + dt;
+        let dt_s = dt.to_seconds();
+
+        let mu_km3_s2 = orbit
+            .frame
+            .mu_km3_s2()
+            .map_err(|e| analytic_config_error(format!("{e}")))?;
+
+        let rmag_km = orbit.rmag_km();
+        let vmag_km_s = orbit.vmag_km_s();
+        // Inverse of the semi-major axis
+        let alpha = 2.0 / rmag_km - vmag_km_s.powi(2) / mu_km3_s2;
+
+        let (mut kepler_dt_s, mut d_raan_rad, mut d_aop_rad) = (dt_s, 0.0, 0.0);
+
+        if alpha > 0.0 {
+            let sma_km = 1.0 / alpha;
+            let n_rad_s = (mu_km3_s2 * alpha.powi(3)).sqrt();
+
+            if self.j2.abs() > 0.0 {
+                let eq_radius_km = orbit
+                    .frame
+                    .mean_equatorial_radius_km()
+                    .map_err(|e| analytic_config_error(format!("{e}")))?;
+
+                let hvec = orbit.radius_km.cross(&orbit.velocity_km_s);
+                let ecc = ((vmag_km_s.powi(2) - mu_km3_s2 / rmag_km) * orbit.radius_km
+                    - orbit.radius_km.dot(&orbit.velocity_km_s) * orbit.velocity_km_s)
+                    .norm()
+                    / mu_km3_s2;
+                let cos_inc = hvec.z / hvec.norm();
+                let k = self.j2 * (eq_radius_km / (sma_km * (1.0 - ecc.powi(2)))).powi(2);
+
+                d_raan_rad = -1.5 * n_rad_s * k * cos_inc * dt_s;
+                d_aop_rad = 0.75 * n_rad_s * k * (5.0 * cos_inc.powi(2) - 1.0) * dt_s;
+                let d_ma_rad = 0.75
+                    * n_rad_s
+                    * k
+                    * (1.0 - ecc.powi(2)).sqrt()
+                    * (3.0 * cos_inc.powi(2) - 1.0)
+                    * dt_s;
+                // The mean anomaly drift is equivalent to propagating the two-body orbit for a bit longer
+                kepler_dt_s += d_ma_rad / n_rad_s;
+            }
+        }
+
+        let (mut radius_km, mut velocity_km_s) =
+            kepler_universal(orbit.radius_km, orbit.velocity_km_s, kepler_dt_s, mu_km3_s2)
+                .ok_or_else(|| {
+                    analytic_config_error(format!(
+                        "universal anomaly did not converge after {MAX_ITERATIONS} iterations when propagating to {epoch}"
+                    ))
+                })?;
+
+        if d_aop_rad != 0.0 || d_raan_rad != 0.0 {
+            // The apsidal motion is a rotation about the angular momentum, and the node regression a rotation about the pole
+            let hvec = orbit.radius_km.cross(&orbit.velocity_km_s);
+            let apsidal = Rotation3::from_axis_angle(&Unit::new_normalize(hvec), d_aop_rad);
+            let nodal = Rotation3::from_axis_angle(&Vector3::z_axis(), d_raan_rad);
+            radius_km = nodal * (apsidal * radius_km);
+            velocity_km_s = nodal * (apsidal * velocity_km_s);
+        }
+
+        let mut next = state;
+        next.orbit.radius_km = radius_km;
+        next.orbit.velocity_km_s = velocity_km_s;
+        next.orbit.epoch = epoch;
+        Ok(next)
+    }
+}
+
+impl fmt::Display for AnalyticPropagator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.j2.abs() > 0.0 {
+            write!(f, "analytic two-body + J2 ({:e})", self.j2)
+        } else {
+            write!(f, "analytic two-body")
+        }
+    }
+}
+
+/// A propagator instance of the analytic propagator, with the same propagation methods as the numerical propagator instance.
+pub struct AnalyticInstance {
+    /// Current state of this instance
+    pub state: Spacecraft,
+    pub prop: AnalyticPropagator,
+    pub almanac: Arc<Almanac>,
+}
+
+impl AnalyticInstance {
+    /// Propagates for the provided duration, which may be negative, and returns the end state.
+    pub fn for_duration(&mut self, duration: Duration) -> Result<Spacecraft, PropagationError> {
+        self.state = self.prop.propagate(self.state, duration)?;
+        Ok(self.state)
+    }
+
+    /// Propagates until the provided epoch, which may be before the current epoch, and returns the end state.
+    pub fn until_epoch(&mut self, end_time: Epoch) -> Result<Spacecraft, PropagationError> {
+        let duration = end_time - self.state.epoch();
+        self.for_duration(duration)
+    }
+
+    /// Propagates for the provided duration and returns the end state and the trajectory, with states every step of the propagator and at the end epoch.
+    pub fn for_duration_with_traj(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(Spacecraft, Traj<Spacecraft>), PropagationError> {
+        let start_state = self.state;
+        let step = if duration.is_negative() {
+            -self.prop.step.abs()
+        } else {
+            self.prop.step.abs()
+        };
+
+        let mut traj = Traj::new();
+        traj.name = Some(format!("{}", self.prop));
+        traj.states.push(start_state);
+
+        if step != Duration::ZERO {
+            // Each state is computed from the start state, so errors do not accumulate.
+            let mut offset = step;
+            while offset.abs() < duration.abs() {
+                traj.states.push(self.prop.propagate(start_state, offset)?);
+                offset += step;
+            }
+        }
+
+        let end_state = self.for_duration(duration)?;
+        traj.states.push(end_state);
+
+        traj.finalize();
+
+        Ok((end_state, traj))
+    }
+
+    /// Propagates until the provided epoch and returns the end state and the trajectory.
+    pub fn until_epoch_with_traj(
+        &mut self,
+        end_time: Epoch,
+    ) -> Result<(Spacecraft, Traj<Spacecraft>), PropagationError> {
+        let duration = end_time - self.state.epoch();
+        self.for_duration_with_traj(duration)
+    }
+}
+
+fn analytic_config_error(msg: String) -> PropagationError {
+    PropagationError::PropConfigError {
+        source: ConfigError::InvalidConfig { msg },
+    }
+}
+
+/// Stumpff functions c2 and c3 of the provided psi
+fn stumpff(psi: f64) -> (f64, f64) {
+    if psi > 1e-6 {
+        let sqrt_psi = psi.sqrt();
+        (
+            (1.0 - sqrt_psi.cos()) / psi,
+            (sqrt_psi - sqrt_psi.sin()) / (sqrt_psi * psi),
+        )
+    } else if psi < -1e-6 {
+        let sqrt_psi = (-psi).sqrt();
+        (
+            (1.0 - sqrt_psi.cosh()) / psi,
+            (sqrt_psi.sinh() - sqrt_psi) / (sqrt_psi * -psi),
+        )
+    } else {
+        (0.5, 1.0 / 6.0)
+    }
+}
+
+/// Solves Kepler's problem with universal variables (Vallado, algorithm 8), returning the position and velocity after `dt_s` seconds,
+/// or None if the universal anomaly did not converge. All conic types are supported, and closed orbits are only propagated over
+/// the fraction of their period to keep the universal anomaly small.
+pub(crate) fn kepler_universal(
+    r0: Vector3<f64>,
+    v0: Vector3<f64>,
+    dt_s: f64,
+    mu_km3_s2: f64,
+) -> Option<(Vector3<f64>, Vector3<f64>)> {
+    if dt_s.abs() < f64::EPSILON {
+        return Some((r0, v0));
+    }
+
+    let sqrt_mu = mu_km3_s2.sqrt();
+    let r0mag = r0.norm();
+    let v0mag = v0.norm();
+    let rdotv = r0.dot(&v0);
+    let alpha = 2.0 / r0mag - v0mag.powi(2) / mu_km3_s2;
+
+    let mut dt_s = dt_s;
+    if alpha > 0.0 {
+        let period_s = TAU / (mu_km3_s2 * alpha.powi(3)).sqrt();
+        dt_s -= period_s * (dt_s / period_s).trunc();
+    }
+
+    // Initial guess of the universal anomaly
+    let mut chi = if alpha > 1e-6 {
+        sqrt_mu * dt_s * alpha
+    } else if alpha < -1e-6 {
+        let sma = 1.0 / alpha;
+        dt_s.signum()
+            * (-sma).sqrt()
+            * ((-2.0 * mu_km3_s2 * alpha * dt_s)
+                / (rdotv + dt_s.signum() * (-mu_km3_s2 * sma).sqrt() * (1.0 - r0mag * alpha)))
+                .ln()
+    } else {
+        // Parabolic orbit, use Barker's equation
+        let p = r0.cross(&v0).norm_squared() / mu_km3_s2;
+        let s = 0.5 * (1.0 / (3.0 * (mu_km3_s2 / p.powi(3)).sqrt() * dt_s)).atan();
+        let w = s.tan().cbrt().atan();
+        p.sqrt() * 2.0 / (2.0 * w).tan()
+    };
+
+    for _ in 0..MAX_ITERATIONS {
+        let psi = chi.powi(2) * alpha;
+        let (c2, c3) = stumpff(psi);
+        let r =
+            chi.powi(2) * c2 + rdotv / sqrt_mu * chi * (1.0 - psi * c3) + r0mag * (1.0 - psi * c2);
+        let delta = (sqrt_mu * dt_s
+            - chi.powi(3) * c3
+            - rdotv / sqrt_mu * chi.powi(2) * c2
+            - r0mag * chi * (1.0 - psi * c3))
+            / r;
+        chi += delta;
+
+        if delta.abs() < 1e-12 * chi.abs().max(1.0) {
+            let psi = chi.powi(2) * alpha;
+            let (c2, c3) = stumpff(psi);
+            let r = chi.powi(2) * c2
+                + rdotv / sqrt_mu * chi * (1.0 - psi * c3)
+                + r0mag * (1.0 - psi * c2);
+
+            let f = 1.0 - chi.powi(2) / r0mag * c2;
+            let g = dt_s - chi.powi(3) / sqrt_mu * c3;
+            let fdot = sqrt_mu / (r * r0mag) * chi * (psi * c3 - 1.0);
+            let gdot = 1.0 - chi.powi(2) / r * c2;
+
+            return Some((f * r0 + g * v0, fdot * r0 + gdot * v0));
+        }
+    }
+
+    None
+}
@@ -16,22 +16,38 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::{DynamicsSnafu, IntegrationDetails, PropagationError, Propagator};
-use crate::dynamics::{Dynamics, DynamicsAlmanacSnafu};
+use super::{
+    DynamicsSnafu, IntegrationDetails, InvalidStateCheck, PropStats, PropagationError, Propagator,
+    StepRecord, StmKind,
+};
+use crate::cosmic::Spacecraft;
+use crate::dynamics::{Dynamics, DynamicsAlmanacSnafu, DynamicsError};
+use crate::errors::EventError;
+use crate::io::watermark::pq_writer;
+use crate::io::ExportCfg;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, OVector};
-use crate::md::trajectory::{Interpolatable, Traj};
-use crate::md::EventEvaluator;
+use crate::linalg::{DefaultAllocator, OMatrix, OVector};
+use crate::md::trajectory::{AnomalySampler, Interpolatable, Traj, INTERPOLATION_SAMPLES};
+use crate::md::{EventEvaluator, StateParameter};
 use crate::propagators::TrajectoryEventSnafu;
 use crate::time::{Duration, Epoch, Unit};
 use crate::State;
 use anise::almanac::Almanac;
+use arrow::array::{Array, BooleanBuilder, Float64Builder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
 use rayon::iter::ParallelBridge;
 use rayon::prelude::ParallelIterator;
 use snafu::ResultExt;
+use std::collections::HashMap;
+use std::error::Error;
 use std::f64;
+use std::fs::File;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Sender};
 use std::sync::Arc;
+use std::thread;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
@@ -52,9 +68,20 @@ where
     pub details: IntegrationDetails,
     /// Should progress reports be logged
     pub log_progress: bool,
+    /// Accepted steps of this instance, only populated if `record_steps` is set in the integrator options
+    pub step_history: Vec<StepRecord>,
+    /// Rejected step attempts of this instance, only populated if `record_steps` is set in the integrator options
+    pub rejected_steps: Vec<StepRecord>,
+    /// Integrator statistics accumulated over all propagations of this instance
+    pub stats: PropStats,
+    /// Sorted epochs which the integrator lands on exactly instead of stepping across them, cf. `pin_epochs`
+    pub pinned_epochs: Vec<Epoch>,
     pub(crate) almanac: Arc<Almanac>,
     pub(crate) step_size: Duration, // Stores the adapted step for the _next_ call
     pub(crate) fixed_step: bool,
+    /// STM of the latest integration step, if the STM of the state is enabled
+    pub(crate) step_stm:
+        Option<OMatrix<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>>,
     // Allows us to do pre-allocation of the ki vectors
     pub(crate) k: Vec<OVector<f64, <D::StateType as State>::VecLength>>,
 }
@@ -77,17 +104,70 @@ where
         self
     }
 
+    /// Pins the provided epochs, e.g. the measurement epochs of a tracking arc or the start and end of the maneuvers of a plan.
+    ///
+    /// Whenever an integration step would cross a pinned epoch, it is shortened to land exactly on that epoch, in either
+    /// propagation direction. Since the shortened step is smaller than the step proposed by the step size controller, it
+    /// is accepted as is, and the adaptive step size is restored for the following step.
+    pub fn pin_epochs<I: IntoIterator<Item = Epoch>>(&mut self, epochs: I) {
+        self.pinned_epochs.extend(epochs);
+        self.pinned_epochs.sort();
+        self.pinned_epochs.dedup();
+    }
+
+    /// Returns this instance with the provided epochs pinned, cf. `pin_epochs`.
+    pub fn with_pinned_epochs<I: IntoIterator<Item = Epoch>>(mut self, epochs: I) -> Self {
+        self.pin_epochs(epochs);
+        self
+    }
+
+    /// Returns the first pinned epoch strictly within the next step from `epoch`, which never goes past `stop_time`.
+    fn next_pinned_epoch(&self, epoch: Epoch, stop_time: Epoch) -> Option<Epoch> {
+        if self.step_size.is_negative() {
+            let step_end = (epoch + self.step_size).max(stop_time);
+            // Pinned epochs are sorted, so the last one before this epoch precedes the first one at or after it
+            let idx = self.pinned_epochs.partition_point(|pinned| *pinned < epoch);
+            idx.checked_sub(1)
+                .map(|prev| self.pinned_epochs[prev])
+                .filter(|pinned| *pinned > step_end)
+        } else {
+            let step_end = (epoch + self.step_size).min(stop_time);
+            let idx = self
+                .pinned_epochs
+                .partition_point(|pinned| *pinned <= epoch);
+            self.pinned_epochs
+                .get(idx)
+                .copied()
+                .filter(|pinned| *pinned < step_end)
+        }
+    }
+
     /// Allows setting the step size of the propagator
     pub fn set_step(&mut self, step_size: Duration, fixed: bool) {
         self.step_size = step_size;
         self.fixed_step = fixed;
     }
 
-    #[allow(clippy::erasing_op)]
     fn for_duration_channel_option(
         &mut self,
         duration: Duration,
         maybe_tx_chan: Option<Sender<D::StateType>>,
+    ) -> Result<D::StateType, PropagationError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let tick = Instant::now();
+        let rslt = self.for_duration_channel_inner(duration, maybe_tx_chan);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.stats.wall_time += tick.elapsed().into();
+        }
+        rslt
+    }
+
+    #[allow(clippy::erasing_op)]
+    fn for_duration_channel_inner(
+        &mut self,
+        duration: Duration,
+        maybe_tx_chan: Option<Sender<D::StateType>>,
     ) -> Result<D::StateType, PropagationError> {
         if duration == 0 * Unit::Second {
             return Ok(self.state);
@@ -144,6 +224,24 @@ where
 
         loop {
             let epoch = self.state.epoch();
+            if let Some(pinned_epoch) = self.next_pinned_epoch(epoch, stop_time) {
+                // Shorten this step to land exactly on the pinned epoch, and resume with the adapted step size afterward
+                let prev_step_size = self.step_size;
+                let prev_step_kind = self.fixed_step;
+                self.set_step(pinned_epoch - epoch, true);
+
+                self.single_step()?;
+
+                self.set_step(prev_step_size, prev_step_kind);
+
+                // Publish to channel if provided
+                if let Some(ref chan) = maybe_tx_chan {
+                    if let Err(e) = chan.send(self.state) {
+                        warn!("{} when sending on channel", e)
+                    }
+                }
+                continue;
+            }
             if (!backprop && epoch + self.step_size > stop_time)
                 || (backprop && epoch + self.step_size <= stop_time)
             {
@@ -302,6 +400,35 @@ where
         Ok((end_state, traj))
     }
 
+    /// Propagates the provided Dynamics for the provided duration and returns the trajectory accumulated until the end of the propagation or until it failed,
+    /// e.g. because the state became invalid, along with the error if any.
+    ///
+    /// On failure, the trajectory ends at the last valid state, which is also the state of this instance.
+    pub fn for_duration_with_partial_traj(
+        &mut self,
+        duration: Duration,
+    ) -> (Traj<D::StateType>, Option<PropagationError>)
+    where
+        <DefaultAllocator as Allocator<<D::StateType as State>::VecLength>>::Buffer<f64>: Send,
+        D::StateType: Interpolatable,
+    {
+        let mut traj = Traj::new();
+        let start_state = self.state;
+
+        let (rslt, rx) = {
+            // The sender is dropped when the propagation returns, successfully or not.
+            let (tx, rx) = channel();
+            (self.for_duration_with_channel(duration, tx), rx)
+        };
+
+        traj.states = rx.into_iter().par_bridge().collect();
+        traj.states.push(start_state);
+
+        traj.finalize();
+
+        (traj, rslt.err())
+    }
+
     /// Propagates the provided Dynamics until the provided epoch and generate the trajectory of these dynamics on its own thread.
     /// Returns the end state and the trajectory.
     /// Known bug #190: Cannot generate a valid trajectory when propagating backward
@@ -361,17 +488,97 @@ where
 
     /// Take a single propagator step and emit the result on the TX channel (if enabled)
     pub fn single_step(&mut self) -> Result<(), PropagationError> {
-        let (t, state_vec) = self.derive()?;
+        let prev_state = self.state;
+        // If the STM is enabled, only integrate the STM of this step, and accumulate it once the step is accepted.
+        let prev_stm = self.state.stm().ok();
+        if prev_stm.is_some() {
+            self.state.reset_stm();
+        }
+        let (t, state_vec) = match self.derive() {
+            Ok(step) => step,
+            Err(e) => {
+                if let Some(prev_stm) = prev_stm {
+                    self.state.set_stm(prev_stm);
+                }
+                return Err(e);
+            }
+        };
         self.state.set(self.state.epoch() + t, &state_vec);
+        if let Some(prev_stm) = prev_stm {
+            // Φ(t_{k+1}, t_0) = Φ(t_{k+1}, t_k) Φ(t_k, t_0)
+            let step_stm = self.state.stm().context(DynamicsSnafu)?;
+            self.state.set_stm(&step_stm * prev_stm);
+            self.step_stm = Some(step_stm);
+        }
         self.state = self
             .prop
             .dynamics
             .finally(self.state, self.almanac.clone())
             .context(DynamicsSnafu)?;
 
+        if let Some(check) = self.invalid_state_check() {
+            let epoch = self.state.epoch();
+            // Reset to the last valid state
+            self.state = prev_state;
+            return Err(PropagationError::InvalidState {
+                check,
+                epoch,
+                last_valid: prev_state.orbit(),
+            });
+        }
+
+        self.stats.accept(t);
+        if self.prop.opts.record_steps {
+            self.step_history.push(StepRecord {
+                epoch: prev_state.epoch(),
+                step: t,
+                error: if self.fixed_step {
+                    0.0
+                } else {
+                    self.details.error
+                },
+                accepted: true,
+            });
+        }
+
         Ok(())
     }
 
+    /// Returns the first validity check which the current state fails, if any.
+    fn invalid_state_check(&self) -> Option<InvalidStateCheck> {
+        let orbit = self.state.orbit();
+        if orbit
+            .radius_km
+            .iter()
+            .chain(orbit.velocity_km_s.iter())
+            .any(|x| !x.is_finite())
+        {
+            return Some(InvalidStateCheck::NonFinite);
+        }
+
+        if let Some(min_altitude_km) = self.prop.opts.min_altitude_km {
+            // The radius check is skipped if the shape of the frame is not set
+            if let Ok(r_eq_km) = orbit.frame.mean_equatorial_radius_km() {
+                let min_rmag_km = r_eq_km + min_altitude_km;
+                if orbit.rmag_km() < min_rmag_km {
+                    return Some(InvalidStateCheck::BelowMinRadius {
+                        rmag_km: orbit.rmag_km(),
+                        min_rmag_km,
+                    });
+                }
+            }
+        }
+
+        // Only states with a mass, like a spacecraft, are checked for negative mass
+        if let Ok(mass_kg) = self.state.value(StateParameter::FuelMass) {
+            if mass_kg < 0.0 {
+                return Some(InvalidStateCheck::NegativeMass { mass_kg });
+            }
+        }
+
+        None
+    }
+
     /// This method integrates whichever function is provided as `d_xdt`. Everything passed to this function is in **seconds**.
     ///
     /// This function returns the step sized used (as a Duration) and the new state as y_{n+1} = y_n + \frac{dy_n}{dt}.
@@ -419,6 +626,7 @@ where
                     .context(DynamicsSnafu)?;
                 self.k[i + 1] = ki;
             }
+            self.stats.function_evals += self.prop.method.stages();
             // Compute the next state and the error
             let mut next_state = state_vec.clone();
             // State error estimation from https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods#Adaptive_Runge%E2%80%93Kutta_methods
@@ -477,6 +685,15 @@ where
                     // Error is too high and we aren't using the smallest step, and we haven't hit the max number of attempts.
                     // So let's adapt the step size.
                     self.details.attempts += 1;
+                    self.stats.rejected_steps += 1;
+                    if self.prop.opts.record_steps {
+                        self.rejected_steps.push(StepRecord {
+                            epoch: state_ctx.epoch(),
+                            step: step_size * Unit::Second,
+                            error: self.details.error,
+                            accepted: false,
+                        });
+                    }
                     let proposed_step = 0.9
                         * step_size
                         * (self.prop.opts.tolerance / self.details.error)
@@ -496,4 +713,154 @@ where
     pub fn latest_details(&self) -> IntegrationDetails {
         self.details
     }
+
+    /// Returns the requested STM, either accumulated from the start of the propagation (same as the STM of the state) or over the latest step only.
+    ///
+    /// Errors if the STM of the propagated state is not enabled, or if no step was taken yet for the step STM.
+    pub fn stm(
+        &self,
+        kind: StmKind,
+    ) -> Result<
+        OMatrix<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+        DynamicsError,
+    > {
+        match kind {
+            StmKind::Traj => self.state.stm(),
+            StmKind::Step => self
+                .step_stm
+                .clone()
+                .ok_or(DynamicsError::StateTransitionMatrixUnset),
+        }
+    }
+
+    /// Returns the accepted steps recorded so far, empty unless `record_steps` is set in the integrator options.
+    pub fn step_history(&self) -> &[StepRecord] {
+        &self.step_history
+    }
+
+    /// Returns the integrator statistics accumulated over all propagations of this instance.
+    pub fn stats(&self) -> PropStats {
+        self.stats
+    }
+
+    /// Returns all of the recorded step attempts, accepted or rejected, in chronological order.
+    /// Rejected attempts are listed before the accepted step starting at the same epoch.
+    pub fn step_log(&self) -> Vec<StepRecord> {
+        let mut log = Vec::with_capacity(self.step_history.len() + self.rejected_steps.len());
+        log.extend_from_slice(&self.step_history);
+        log.extend_from_slice(&self.rejected_steps);
+        log.sort_by(|a, b| a.epoch.cmp(&b.epoch).then(a.accepted.cmp(&b.accepted)));
+        log
+    }
+
+    /// Exports the step log (cf. `step_log`) to a parquet file with one row per step attempt.
+    pub fn step_log_to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let log = self.step_log();
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = cfg.epoch_fields();
+        let mut meta = HashMap::new();
+        meta.insert("unit".to_string(), "s".to_string());
+        hdrs.push(Field::new("Step (s)", DataType::Float64, false).with_metadata(meta));
+        hdrs.push(Field::new("Error estimate", DataType::Float64, false));
+        hdrs.push(Field::new("Accepted", DataType::Boolean, false));
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+        record.extend(cfg.epoch_columns(log.iter().map(|rec| rec.epoch)));
+
+        let mut steps = Float64Builder::new();
+        let mut errors = Float64Builder::new();
+        let mut accepted = BooleanBuilder::new();
+        for rec in &log {
+            steps.append_value(rec.step.to_seconds());
+            errors.append_value(rec.error);
+            accepted.append_value(rec.accepted);
+        }
+        record.push(Arc::new(steps.finish()));
+        record.push(Arc::new(errors.finish()));
+        record.push(Arc::new(accepted.finish()));
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "Integrator step log".to_string());
+        metadata.insert("Method".to_string(), format!("{:?}", self.prop.method));
+        metadata.insert("Statistics".to_string(), format!("{}", self.stats));
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata));
+
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!("Step log written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl<D: Dynamics<StateType = Spacecraft>> PropInstance<'_, D> {
+    /// Propagates for the provided duration, and returns the end state and the states at equally spaced anomalies along the way.
+    /// Refer to `Traj::every_anomaly` for the anchor and the spacing of these states.
+    ///
+    /// Unlike sampling the trajectory of `for_duration_with_traj`, only a rolling window of the propagated states is kept in memory, which makes
+    /// this suitable for multi-year propagations. This only supports forward propagation.
+    pub fn for_duration_every_anomaly(
+        &mut self,
+        duration: Duration,
+        states_per_rev: usize,
+        anchor: StateParameter,
+    ) -> Result<(Spacecraft, Vec<Spacecraft>), PropagationError> {
+        let sampler = AnomalySampler::new(states_per_rev, anchor).context(TrajectoryEventSnafu)?;
+        let almanac = self.almanac.clone();
+        let start_state = self.state;
+
+        let (tx, rx) = channel::<Spacecraft>();
+        thread::scope(|scope| {
+            let sampling = scope.spawn(move || -> Result<Vec<Spacecraft>, EventError> {
+                let mut samples = Vec::new();
+                if sampler.is_sample(&start_state)? {
+                    samples.push(start_state);
+                }
+
+                // Only keep the states needed to interpolate between the last two states
+                let mut window = Traj::new();
+                window.states.push(start_state);
+                for state in rx {
+                    let prev = *window.last();
+                    window.states.push(state);
+                    if window.states.len() > 2 * INTERPOLATION_SAMPLES {
+                        let excess = window.states.len() - INTERPOLATION_SAMPLES;
+                        window.states.drain(..excess);
+                    }
+                    samples.extend(sampler.samples_between(
+                        &window,
+                        &prev,
+                        &state,
+                        almanac.clone(),
+                    )?);
+                }
+
+                Ok(samples)
+            });
+
+            let end_state = self.for_duration_with_channel(duration, tx)?;
+            let samples = sampling
+                .join()
+                .expect("anomaly sampling thread panicked")
+                .context(TrajectoryEventSnafu)?;
+
+            Ok((end_state, samples))
+        })
+    }
 }
@@ -32,8 +32,18 @@ mod rk_methods;
 pub use rk_methods::*;
 mod options;
 pub use options::*;
+mod analytic;
+pub use analytic::*;
+mod timeline;
+pub use timeline::*;
 
-use crate::{dynamics::DynamicsError, errors::EventError, io::ConfigError, time::Duration};
+use crate::{
+    cosmic::Orbit,
+    dynamics::DynamicsError,
+    errors::EventError,
+    io::ConfigError,
+    time::{Duration, Epoch},
+};
 
 /// Stores the details of the previous integration step of a given propagator. Access as `my_prop.clone().latest_details()`.
 #[derive(Copy, Clone, Debug)]
@@ -56,6 +66,142 @@ impl fmt::Display for IntegrationDetails {
     }
 }
 
+/// Kind of state transition matrix (STM) available from a propagator instance when the STM of the propagated state is enabled.
+///
+/// Both are maintained at each integration step with the convention `Φ(t_{k+1}, t_0) = Φ(t_{k+1}, t_k) Φ(t_k, t_0)`,
+/// i.e. the step STM multiplies the accumulated STM on the left.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StmKind {
+    /// STM from the start of the propagation to the current epoch, `Φ(t, t_0)`, which is also the STM of the propagated state and of the states of its trajectory
+    #[default]
+    Traj,
+    /// STM over the latest integration step only, `Φ(t_{k+1}, t_k)`, e.g. for the time update of a filter
+    Step,
+}
+
+/// Record of an integration step attempt, stored when `record_steps` is set in the integrator options.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StepRecord {
+    /// epoch at the start of the step
+    pub epoch: Epoch,
+    /// step size used, negative when propagating backward
+    pub step: Duration,
+    /// error estimate of this step, zero for fixed steps (including the final step to the stop epoch)
+    pub error: f64,
+    /// whether this step was accepted, or rejected because its error estimate exceeded the tolerance
+    pub accepted: bool,
+}
+
+impl fmt::Display for StepRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: step {}, error {:.3e}{}",
+            self.epoch,
+            self.step,
+            self.error,
+            if self.accepted { "" } else { " (rejected)" }
+        )
+    }
+}
+
+/// Statistics of the integrator, accumulated over all of the propagations of a propagator instance. Access as `my_prop.stats()`.
+///
+/// Unlike the step history, these are always collected since they only amount to a few counters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PropStats {
+    /// number of accepted steps, including the final step to the stop epoch
+    pub accepted_steps: usize,
+    /// number of step attempts rejected because their error estimate exceeded the tolerance
+    pub rejected_steps: usize,
+    /// number of evaluations of the equations of motion, i.e. the number of stages of the method per step attempt
+    pub function_evals: usize,
+    /// smallest accepted step magnitude, if any step was accepted
+    pub min_step: Option<Duration>,
+    /// largest accepted step magnitude, if any step was accepted
+    pub max_step: Option<Duration>,
+    /// sum of the accepted step magnitudes
+    pub total_step: Duration,
+    /// wall clock time spent propagating (always zero on wasm32)
+    pub wall_time: Duration,
+}
+
+impl PropStats {
+    /// Mean accepted step magnitude, if any step was accepted
+    pub fn mean_step(&self) -> Option<Duration> {
+        if self.accepted_steps == 0 {
+            None
+        } else {
+            Some(self.total_step * (1.0 / self.accepted_steps as f64))
+        }
+    }
+
+    /// Accounts for a newly accepted step of the provided size
+    pub(crate) fn accept(&mut self, step: Duration) {
+        let step = step.abs();
+        self.accepted_steps += 1;
+        self.total_step += step;
+        self.min_step = Some(self.min_step.map_or(step, |min| min.min(step)));
+        self.max_step = Some(self.max_step.map_or(step, |max| max.max(step)));
+    }
+}
+
+impl Default for PropStats {
+    fn default() -> Self {
+        Self {
+            accepted_steps: 0,
+            rejected_steps: 0,
+            function_evals: 0,
+            min_step: None,
+            max_step: None,
+            total_step: Duration::ZERO,
+            wall_time: Duration::ZERO,
+        }
+    }
+}
+
+impl fmt::Display for PropStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} accepted steps, {} rejected, {} function evaluations",
+            self.accepted_steps, self.rejected_steps, self.function_evals
+        )?;
+        if let (Some(min), Some(max), Some(mean)) = (self.min_step, self.max_step, self.mean_step())
+        {
+            write!(f, ", step min {min} max {max} mean {mean}")?;
+        }
+        write!(f, ", in {}", self.wall_time)
+    }
+}
+
+/// Validity check of the propagated state which failed after an integration step.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InvalidStateCheck {
+    /// The position or the velocity is not finite (NaN or infinite)
+    NonFinite,
+    /// The radius is below the mean equatorial radius of the frame plus the `min_altitude_km` of the integrator options
+    BelowMinRadius { rmag_km: f64, min_rmag_km: f64 },
+    /// The fuel mass is negative
+    NegativeMass { mass_kg: f64 },
+}
+
+impl fmt::Display for InvalidStateCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NonFinite => write!(f, "non finite position or velocity"),
+            Self::BelowMinRadius {
+                rmag_km,
+                min_rmag_km,
+            } => write!(
+                f,
+                "radius of {rmag_km:.3} km below the minimum of {min_rmag_km:.3} km"
+            ),
+            Self::NegativeMass { mass_kg } => write!(f, "negative fuel mass of {mass_kg} kg"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Snafu)]
 pub enum PropagationError {
     #[snafu(display("encountered a dynamics error {source}"))]
@@ -66,4 +212,11 @@ pub enum PropagationError {
     NthEventError { nth: usize, found: usize },
     #[snafu(display("propagation failed because {source}"))]
     PropConfigError { source: ConfigError },
+    /// The propagator instance is reset to the last valid state, whose orbit is also provided here.
+    #[snafu(display("invalid state at {epoch}: {check} (last valid state {last_valid})"))]
+    InvalidState {
+        check: InvalidStateCheck,
+        epoch: Epoch,
+        last_valid: Orbit,
+    },
 }
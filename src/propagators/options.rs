@@ -52,6 +52,14 @@ pub struct IntegratorOptions {
     /// Note, when setting this, it's recommended to call `strip` on the Frame.
     #[builder(default, setter(strip_option))]
     pub integration_frame: Option<Frame>,
+    /// If set, the epoch, step size, and error estimate of each step attempt are recorded by the propagator instance, cf. `PropInstance::step_history` and `PropInstance::step_log`.
+    #[builder(default = false)]
+    #[serde(default)]
+    pub record_steps: bool,
+    /// If set, the propagation stops with an error as soon as the radius of the state falls below the mean equatorial radius of its frame plus this altitude (may be negative).
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub min_altitude_km: Option<f64>,
 }
 
 impl IntegratorOptions {
@@ -72,6 +80,8 @@ impl IntegratorOptions {
             fixed_step: false,
             error_ctrl,
             integration_frame: None,
+            record_steps: false,
+            min_altitude_km: None,
         }
     }
 
@@ -101,6 +111,8 @@ impl IntegratorOptions {
             attempts: 0,
             error_ctrl: ErrorControl::RSSCartesianStep,
             integration_frame: None,
+            record_steps: false,
+            min_altitude_km: None,
         }
     }
 
@@ -172,6 +184,8 @@ impl Default for IntegratorOptions {
             fixed_step: false,
             error_ctrl: ErrorControl::RSSCartesianStep,
             integration_frame: None,
+            record_steps: false,
+            min_altitude_km: None,
         }
     }
 }
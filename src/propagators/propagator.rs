@@ -20,7 +20,7 @@ use std::sync::Arc;
 
 use anise::almanac::Almanac;
 
-use super::{IntegrationDetails, IntegratorMethod, IntegratorOptions, PropInstance};
+use super::{IntegrationDetails, IntegratorMethod, IntegratorOptions, PropInstance, PropStats};
 use crate::dynamics::Dynamics;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, OVector};
@@ -100,9 +100,14 @@ where
                 attempts: 1,
             },
             log_progress: true,
+            step_history: Vec::new(),
+            rejected_steps: Vec::new(),
+            stats: PropStats::default(),
+            pinned_epochs: Vec::new(),
             almanac,
             step_size: self.opts.init_step,
             fixed_step: self.opts.fixed_step,
+            step_stm: None,
             k,
         }
     }
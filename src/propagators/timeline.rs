@@ -0,0 +1,352 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{PropInstance, PropagationError};
+use crate::cosmic::{GuidanceMode, Spacecraft};
+use crate::dynamics::Dynamics;
+use crate::io::{epoch_from_str, epoch_to_str, ConfigError, ConfigRepr};
+use crate::md::trajectory::Traj;
+use crate::time::{Duration, Epoch};
+use crate::{NyxError, State};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A change of the spacecraft configuration defined by a closure, e.g. to model a configuration change not covered by the other variants.
+/// Custom changes cannot be loaded from nor serialized into YAML.
+#[derive(Clone)]
+pub struct CustomChange {
+    /// Name of this change, used in the logs and the trajectory annotations
+    pub name: String,
+    /// Function applying this change to the spacecraft
+    pub apply: Arc<dyn Fn(&mut Spacecraft) + Send + Sync>,
+}
+
+impl fmt::Debug for CustomChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CustomChange {{ name: {:?} }}", self.name)
+    }
+}
+
+/// Discrete change of the spacecraft configuration, applied instantaneously.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ConfigChangeKind {
+    /// Adds this mass to the dry mass, negative to jettison a component (in kg)
+    DryMassDelta { delta_kg: f64 },
+    /// Sets the drag area (in m^2), e.g. after deploying the solar arrays
+    DragArea { area_m2: f64 },
+    /// Sets the solar radiation pressure area (in m^2)
+    SrpArea { area_m2: f64 },
+    /// Sets the coefficient of reflectivity
+    Cr { cr: f64 },
+    /// Sets the coefficient of drag
+    Cd { cd: f64 },
+    /// Sets the guidance mode, e.g. to inhibit the guidance law during a safe-mode window
+    GuidanceMode { mode: GuidanceMode },
+    /// Applies a custom change
+    #[serde(skip)]
+    Custom(CustomChange),
+}
+
+impl ConfigChangeKind {
+    /// Applies this change to the provided spacecraft.
+    pub fn apply(&self, sc: &mut Spacecraft) {
+        match self {
+            Self::DryMassDelta { delta_kg } => sc.dry_mass_kg += delta_kg,
+            Self::DragArea { area_m2 } => sc.drag.area_m2 = *area_m2,
+            Self::SrpArea { area_m2 } => sc.srp.area_m2 = *area_m2,
+            Self::Cr { cr } => sc.srp.cr = *cr,
+            Self::Cd { cd } => sc.drag.cd = *cd,
+            Self::GuidanceMode { mode } => sc.mut_mode(*mode),
+            Self::Custom(custom) => (custom.apply)(sc),
+        }
+    }
+}
+
+impl fmt::Display for ConfigChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DryMassDelta { delta_kg } => write!(f, "dry mass change of {delta_kg} kg"),
+            Self::DragArea { area_m2 } => write!(f, "drag area set to {area_m2} m^2"),
+            Self::SrpArea { area_m2 } => write!(f, "SRP area set to {area_m2} m^2"),
+            Self::Cr { cr } => write!(f, "Cr set to {cr}"),
+            Self::Cd { cd } => write!(f, "Cd set to {cd}"),
+            Self::GuidanceMode { mode } => write!(f, "guidance mode set to {mode:?}"),
+            Self::Custom(custom) => write!(f, "{}", custom.name),
+        }
+    }
+}
+
+/// A configuration change of the spacecraft scheduled at a specific epoch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfigChange {
+    /// Epoch at which this change is applied
+    #[serde(serialize_with = "epoch_to_str", deserialize_with = "epoch_from_str")]
+    pub epoch: Epoch,
+    /// Change to apply
+    pub change: ConfigChangeKind,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.epoch, self.change)
+    }
+}
+
+/// A chronological timeline of configuration changes of the spacecraft, like jettisoning an adapter, deploying the solar arrays,
+/// or switching to a safe mode, which are not triggered by the guidance law.
+///
+/// The propagator steps exactly to the epoch of each change, applies it, and continues, cf. `PropInstance::for_duration_with_timeline`.
+/// Timelines may be loaded from YAML, in which case `validate` must be called prior to use. The changes of the dry mass are
+/// checked against the initial spacecraft when propagating, cf. `validate_for`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Timeline {
+    /// Changes of this timeline, in chronological order
+    pub changes: Vec<ConfigChange>,
+}
+
+impl Timeline {
+    /// Builds a timeline from the provided changes, which are sorted and validated.
+    pub fn new(mut changes: Vec<ConfigChange>) -> Result<Self, NyxError> {
+        // Stable sort: changes at the same epoch are applied in the order provided
+        changes.sort_by_key(|change| change.epoch);
+        let me = Self { changes };
+        me.validate()?;
+        Ok(me)
+    }
+
+    /// Builds a timeline from changes defined as offsets from the reference epoch.
+    pub fn from_elapsed(
+        reference: Epoch,
+        changes: &[(Duration, ConfigChangeKind)],
+    ) -> Result<Self, NyxError> {
+        Self::new(
+            changes
+                .iter()
+                .map(|(offset, change)| ConfigChange {
+                    epoch: reference + *offset,
+                    change: change.clone(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Checks that the changes are chronological and that the areas and coefficients they set are physical.
+    pub fn validate(&self) -> Result<(), NyxError> {
+        for (ii, pair) in self.changes.windows(2).enumerate() {
+            if pair[1].epoch < pair[0].epoch {
+                return Err(NyxError::CustomError {
+                    msg: format!(
+                        "configuration change #{} at {} is before #{ii} at {}",
+                        ii + 1,
+                        pair[1].epoch,
+                        pair[0].epoch
+                    ),
+                });
+            }
+        }
+        for (ii, change) in self.changes.iter().enumerate() {
+            let invalid = match change.change {
+                ConfigChangeKind::DragArea { area_m2 } | ConfigChangeKind::SrpArea { area_m2 } => {
+                    area_m2 < 0.0
+                }
+                ConfigChangeKind::Cr { cr } => !(0.0..=2.0).contains(&cr),
+                ConfigChangeKind::Cd { cd } => cd < 0.0,
+                _ => false,
+            };
+            if invalid {
+                return Err(NyxError::CustomError {
+                    msg: format!("configuration change #{ii} is invalid: {change}"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates this timeline and checks that applying its changes in order to the provided spacecraft never drives
+    /// its dry mass negative.
+    pub fn validate_for(&self, sc: &Spacecraft) -> Result<(), NyxError> {
+        self.validate()?;
+        let mut sc = *sc;
+        for (ii, change) in self.changes.iter().enumerate() {
+            change.change.apply(&mut sc);
+            if sc.dry_mass_kg < 0.0 {
+                return Err(NyxError::CustomError {
+                    msg: format!(
+                        "configuration change #{ii} ({change}) drives the dry mass negative: {} kg",
+                        sc.dry_mass_kg
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the changes of this timeline between the provided epochs, both included.
+    pub fn changes_within(&self, start: Epoch, end: Epoch) -> impl Iterator<Item = &ConfigChange> {
+        self.changes
+            .iter()
+            .filter(move |change| (start..=end).contains(&change.epoch))
+    }
+}
+
+impl ConfigRepr for Timeline {}
+
+impl fmt::Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Timeline with {} changes", self.changes.len())
+    }
+}
+
+impl<D: Dynamics<StateType = Spacecraft>> PropInstance<'_, D> {
+    /// Propagates for the provided duration while applying the configuration changes of the timeline exactly at their epochs,
+    /// and returns the end state and the trajectory. Each applied change is recorded in the annotations of the trajectory.
+    ///
+    /// At the epoch of a change, the trajectory stores the state after the change. Changes outside of the propagation span are
+    /// ignored with a warning. This only supports forward propagation.
+    ///
+    /// Returns an error if the timeline is invalid for the current state, e.g. if its changes drive the dry mass negative.
+    pub fn for_duration_with_timeline(
+        &mut self,
+        duration: Duration,
+        timeline: &Timeline,
+    ) -> Result<(Spacecraft, Traj<Spacecraft>), PropagationError> {
+        timeline
+            .validate_for(&self.state)
+            .map_err(|e| PropagationError::PropConfigError {
+                source: ConfigError::InvalidConfig {
+                    msg: format!("{e}"),
+                },
+            })?;
+
+        let start = self.state.epoch();
+        let end = start + duration;
+
+        for change in &timeline.changes {
+            if !(start..=end).contains(&change.epoch) {
+                warn!("ignoring configuration change {change} outside of the propagation span {start} -- {end}");
+            }
+        }
+
+        let mut traj = Traj::new();
+        traj.states.push(self.state);
+
+        for change in timeline.changes_within(start, end) {
+            let (_, segment) = self.until_epoch_with_traj(change.epoch)?;
+            // Replace the state before the change by the state after it
+            traj.states.extend(segment.states.into_iter().skip(1));
+            traj.states.pop();
+
+            change.change.apply(&mut self.state);
+            if self.log_progress {
+                info!("applied configuration change {change}");
+            }
+            traj.states.push(self.state);
+            traj.annotations
+                .push((change.epoch, format!("{}", change.change)));
+        }
+
+        let (end_state, segment) = self.until_epoch_with_traj(end)?;
+        traj.states.extend(segment.states.into_iter().skip(1));
+        traj.finalize();
+
+        Ok((end_state, traj))
+    }
+}
+
+#[cfg(test)]
+mod ut_timeline {
+    use super::*;
+    use crate::time::TimeUnits;
+
+    #[test]
+    fn validation_and_serde() {
+        let start = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+
+        let timeline = Timeline::from_elapsed(
+            start,
+            &[
+                (2.hours(), ConfigChangeKind::DragArea { area_m2: 12.5 }),
+                (
+                    1.hours(),
+                    ConfigChangeKind::DryMassDelta { delta_kg: -50.0 },
+                ),
+                (
+                    3.hours(),
+                    ConfigChangeKind::GuidanceMode {
+                        mode: GuidanceMode::Inhibit,
+                    },
+                ),
+            ],
+        )
+        .unwrap();
+
+        // Changes are sorted
+        assert_eq!(timeline.changes[0].epoch, start + 1.hours());
+        assert_eq!(timeline.changes_within(start, start + 2.hours()).count(), 2);
+
+        let yaml = serde_yaml::to_string(&timeline).unwrap();
+        let loaded: Timeline = serde_yaml::from_str(&yaml).unwrap();
+        loaded.validate().unwrap();
+        assert_eq!(serde_yaml::to_string(&loaded).unwrap(), yaml);
+
+        let mut sc = Spacecraft {
+            dry_mass_kg: 1000.0,
+            ..Default::default()
+        };
+        for change in &loaded.changes {
+            change.change.apply(&mut sc);
+        }
+        assert_eq!(sc.dry_mass_kg, 950.0);
+        assert_eq!(sc.drag.area_m2, 12.5);
+        assert_eq!(sc.mode(), GuidanceMode::Inhibit);
+
+        // The dry mass changes are checked cumulatively against the initial spacecraft
+        let sc = Spacecraft {
+            dry_mass_kg: 1000.0,
+            ..Default::default()
+        };
+        loaded.validate_for(&sc).unwrap();
+        let jettisons = Timeline::from_elapsed(
+            start,
+            &[
+                (
+                    1.hours(),
+                    ConfigChangeKind::DryMassDelta { delta_kg: -600.0 },
+                ),
+                (
+                    2.hours(),
+                    ConfigChangeKind::DryMassDelta { delta_kg: -500.0 },
+                ),
+            ],
+        )
+        .unwrap();
+        assert!(jettisons.validate_for(&sc).is_err());
+
+        // Unphysical coefficients are rejected
+        assert!(
+            Timeline::from_elapsed(start, &[(1.hours(), ConfigChangeKind::Cr { cr: 2.5 })])
+                .is_err()
+        );
+        assert!(Timeline::from_elapsed(
+            start,
+            &[(1.hours(), ConfigChangeKind::SrpArea { area_m2: -1.0 })]
+        )
+        .is_err());
+    }
+}
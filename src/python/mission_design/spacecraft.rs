@@ -60,6 +60,7 @@ impl Spacecraft {
                 dry_mass_kg: dry_mass_kg.unwrap(),
                 fuel_mass_kg: fuel_mass_kg.unwrap_or(0.0),
                 thruster,
+                thruster_limits: None,
                 mode: mode.unwrap_or(GuidanceMode::Coast),
                 stm: None,
                 srp: srp.unwrap_or_else(|| SrpConfig::default()),
@@ -17,13 +17,12 @@
 */
 
 use crate::mc::GaussianGenerator;
+use crate::mc::NyxRng;
 use crate::md::StateParameter;
 use crate::Orbit;
 use crate::{NyxError, Spacecraft};
 use pyo3::{prelude::*, py_run};
-use rand::SeedableRng;
 use rand_distr::Distribution;
-use rand_pcg::Pcg64Mcg;
 
 /// Monte Carlo
 pub(crate) fn register_mc(py: Python<'_>, parent_module: &PyModule) -> PyResult<()> {
@@ -66,8 +65,8 @@ fn generate_orbits(
     };
 
     let rng = match seed {
-        Some(seed) => Pcg64Mcg::new(seed.into()),
-        None => Pcg64Mcg::from_entropy(),
+        Some(seed) => NyxRng::from_seed(seed),
+        None => NyxRng::from_entropy(),
     };
 
     // Generate multithreaded
@@ -102,8 +101,8 @@ fn generate_spacecraft(
     };
 
     let rng = match seed {
-        Some(seed) => Pcg64Mcg::new(seed.into()),
-        None => Pcg64Mcg::from_entropy(),
+        Some(seed) => NyxRng::from_seed(seed),
+        None => NyxRng::from_entropy(),
     };
 
     // Generate multithreaded
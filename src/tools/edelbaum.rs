@@ -0,0 +1,293 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Edelbaum's analytic estimates of low-thrust transfers between circular orbits, for quick sizing before any low-thrust optimization.
+//!
+//! The thrust is continuous with a yaw angle out of the orbital plane which is constant over each revolution, and changes from
+//! one revolution to the next according to Edelbaum's steering law, to change the radius and the inclination simultaneously.
+//! The theory assumes that the orbit remains quasi-circular and that the acceleration is small compared to the local gravity.
+//! Its delta-v is typically within 5-10% of that of an optimized or closed-loop (e.g. Q-law) low-thrust transfer.
+//!
+//! Reference: T. N. Edelbaum, "Propulsion Requirements for Controllable Satellites", ARS Journal, 1961, and J. A. Kechichian,
+//! "Reformulation of Edelbaum's Low-Thrust Transfer Problem Using Optimal Control Theory", JGCD, 1997.
+
+use crate::cosmic::{Orbit, STD_GRAVITY};
+use crate::errors::{FromPhysicsSnafu, NyxError};
+use crate::time::{Duration, Unit};
+use snafu::ResultExt;
+use std::f64::consts::FRAC_PI_2;
+use std::fmt;
+
+/// Maximum eccentricity of the orbits of an Edelbaum transfer, which must be circular.
+pub const EDELBAUM_MAX_ECC: f64 = 0.01;
+
+/// Low-thrust transfer between two circular orbits of different radii and inclinations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EdelbaumTransfer {
+    /// Gravitational parameter of the central body
+    pub mu_km3_s2: f64,
+    pub initial_radius_km: f64,
+    pub final_radius_km: f64,
+    /// Change of the orbital plane, in degrees
+    pub inclination_change_deg: f64,
+}
+
+/// Delta-v, duration, and propellant of an Edelbaum transfer for a given thruster and initial mass.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EdelbaumReport {
+    pub transfer: EdelbaumTransfer,
+    pub delta_v_km_s: f64,
+    /// Initial yaw angle of the thrust out of the orbital plane, in degrees
+    pub initial_yaw_deg: f64,
+    /// Constant thrust, in Newtons
+    pub thrust_N: f64,
+    pub isp_s: f64,
+    pub initial_mass_kg: f64,
+    pub propellant_mass_kg: f64,
+    /// Duration of the transfer, thrusting continuously
+    pub transfer_time: Duration,
+}
+
+impl EdelbaumReport {
+    /// Mass at the end of the transfer
+    pub fn final_mass_kg(&self) -> f64 {
+        self.initial_mass_kg - self.propellant_mass_kg
+    }
+}
+
+impl fmt::Display for EdelbaumReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Edelbaum transfer from {:.3} km to {:.3} km with {:.3} deg of plane change: Δv = {:.6} km/s (initial yaw {:.3} deg), {:.3} kg of propellant from {:.3} kg in {} with {:.6} N at {:.1} s of Isp",
+            self.transfer.initial_radius_km,
+            self.transfer.final_radius_km,
+            self.transfer.inclination_change_deg,
+            self.delta_v_km_s,
+            self.initial_yaw_deg,
+            self.propellant_mass_kg,
+            self.initial_mass_kg,
+            self.transfer_time,
+            self.thrust_N,
+            self.isp_s
+        )
+    }
+}
+
+impl EdelbaumTransfer {
+    /// Initializes a new transfer, checking that the radii are positive and that the plane change is within [0; 180] degrees.
+    pub fn new(
+        mu_km3_s2: f64,
+        initial_radius_km: f64,
+        final_radius_km: f64,
+        inclination_change_deg: f64,
+    ) -> Result<Self, NyxError> {
+        if mu_km3_s2 <= 0.0 || initial_radius_km <= 0.0 || final_radius_km <= 0.0 {
+            return Err(NyxError::CustomError {
+                msg: format!(
+                    "Edelbaum transfer requires positive gravitational parameter and radii, got {mu_km3_s2} km^3/s^2, {initial_radius_km} km and {final_radius_km} km"
+                ),
+            });
+        }
+        if !(0.0..=180.0).contains(&inclination_change_deg) {
+            return Err(NyxError::CustomError {
+                msg: format!(
+                    "Edelbaum transfer requires a plane change within [0; 180] deg, got {inclination_change_deg} deg"
+                ),
+            });
+        }
+
+        Ok(Self {
+            mu_km3_s2,
+            initial_radius_km,
+            final_radius_km,
+            inclination_change_deg,
+        })
+    }
+
+    /// Initializes the transfer between two circular orbits around the same body, whose plane change is the angle between
+    /// their orbital planes (i.e. it includes any change of right ascension of the ascending node).
+    ///
+    /// Errors if either eccentricity is above [EDELBAUM_MAX_ECC] or if the orbits are not around the same body.
+    pub fn from_orbits(initial: Orbit, target: Orbit) -> Result<Self, NyxError> {
+        if !initial.frame.ephem_origin_match(target.frame) {
+            return Err(NyxError::CustomError {
+                msg: format!(
+                    "Edelbaum transfer requires orbits around the same body, got {} and {}",
+                    initial.frame, target.frame
+                ),
+            });
+        }
+
+        for orbit in [initial, target] {
+            let ecc = orbit.ecc().context(FromPhysicsSnafu {
+                action: "computing eccentricity for Edelbaum transfer",
+            })?;
+            if ecc > EDELBAUM_MAX_ECC {
+                return Err(NyxError::CustomError {
+                    msg: format!(
+                        "Edelbaum transfer requires circular orbits, got an eccentricity of {ecc:.6} at {}",
+                        orbit.epoch
+                    ),
+                });
+            }
+        }
+
+        let mu_km3_s2 = initial.frame.mu_km3_s2().context(FromPhysicsSnafu {
+            action: "fetching gravitational parameter for Edelbaum transfer",
+        })?;
+
+        let h_initial = initial.radius_km.cross(&initial.velocity_km_s);
+        let h_target = target.radius_km.cross(&target.velocity_km_s);
+        let inclination_change_deg = h_initial.angle(&h_target).to_degrees();
+
+        Self::new(
+            mu_km3_s2,
+            initial.rmag_km(),
+            target.rmag_km(),
+            inclination_change_deg,
+        )
+    }
+
+    /// Circular velocity of the initial orbit
+    pub fn initial_velocity_km_s(&self) -> f64 {
+        (self.mu_km3_s2 / self.initial_radius_km).sqrt()
+    }
+
+    /// Circular velocity of the final orbit
+    pub fn final_velocity_km_s(&self) -> f64 {
+        (self.mu_km3_s2 / self.final_radius_km).sqrt()
+    }
+
+    /// Total delta-v of the transfer: `Δv² = v0² - 2 v0 v1 cos(π/2 Δi) + v1²`
+    pub fn delta_v_km_s(&self) -> f64 {
+        let (v0, v1) = (self.initial_velocity_km_s(), self.final_velocity_km_s());
+        let half_di_rad = FRAC_PI_2 * self.inclination_change_deg.to_radians();
+        (v0.powi(2) - 2.0 * v0 * v1 * half_di_rad.cos() + v1.powi(2)).sqrt()
+    }
+
+    /// Initial yaw angle of the thrust out of the orbital plane, in radians, with `tan β0 = sin(π/2 Δi) / (v0/v1 - cos(π/2 Δi))`.
+    /// It is zero for a coplanar raise, and 180 degrees for a coplanar descent.
+    fn initial_yaw_rad(&self) -> f64 {
+        let half_di_rad = FRAC_PI_2 * self.inclination_change_deg.to_radians();
+        half_di_rad
+            .sin()
+            .atan2(self.initial_velocity_km_s() / self.final_velocity_km_s() - half_di_rad.cos())
+    }
+
+    /// Initial yaw angle of the thrust out of the orbital plane, in degrees
+    pub fn initial_yaw_deg(&self) -> f64 {
+        self.initial_yaw_rad().to_degrees()
+    }
+
+    /// Yaw angle of the thrust out of the orbital plane once the provided delta-v has been spent, in degrees, per Edelbaum's
+    /// steering law `tan β = v0 sin β0 / (v0 cos β0 - Δv)`.
+    pub fn yaw_deg(&self, dv_spent_km_s: f64) -> f64 {
+        let (v0, beta0) = (self.initial_velocity_km_s(), self.initial_yaw_rad());
+        (v0 * beta0.sin())
+            .atan2(v0 * beta0.cos() - dv_spent_km_s)
+            .to_degrees()
+    }
+
+    /// Circular velocity once the provided delta-v has been spent, `v² = v0² - 2 v0 Δv cos β0 + Δv²`
+    pub fn velocity_km_s(&self, dv_spent_km_s: f64) -> f64 {
+        let (v0, beta0) = (self.initial_velocity_km_s(), self.initial_yaw_rad());
+        (v0.powi(2) - 2.0 * v0 * dv_spent_km_s * beta0.cos() + dv_spent_km_s.powi(2)).sqrt()
+    }
+
+    /// Plane change achieved once the provided delta-v has been spent, in degrees
+    pub fn inclination_changed_deg(&self, dv_spent_km_s: f64) -> f64 {
+        let (v0, beta0) = (self.initial_velocity_km_s(), self.initial_yaw_rad());
+        let angle_rad = (dv_spent_km_s - v0 * beta0.cos()).atan2(v0 * beta0.sin());
+        (2.0 / std::f64::consts::PI * (angle_rad + FRAC_PI_2 - beta0)).to_degrees()
+    }
+
+    /// Computes the duration and propellant of this transfer thrusting continuously with the provided constant thrust and Isp.
+    #[allow(non_snake_case)]
+    pub fn with_thrust(
+        &self,
+        thrust_N: f64,
+        isp_s: f64,
+        initial_mass_kg: f64,
+    ) -> Result<EdelbaumReport, NyxError> {
+        if thrust_N <= 0.0 {
+            return Err(NyxError::CustomError {
+                msg: format!("Edelbaum transfer requires a positive thrust, got {thrust_N} N"),
+            });
+        }
+        let propellant_mass_kg = self.propellant_mass_kg(isp_s, initial_mass_kg)?;
+        let mass_flow_kg_s = thrust_N / (isp_s * STD_GRAVITY);
+
+        Ok(EdelbaumReport {
+            transfer: *self,
+            delta_v_km_s: self.delta_v_km_s(),
+            initial_yaw_deg: self.initial_yaw_deg(),
+            thrust_N,
+            isp_s,
+            initial_mass_kg,
+            propellant_mass_kg,
+            transfer_time: (propellant_mass_kg / mass_flow_kg_s) * Unit::Second,
+        })
+    }
+
+    /// Inverse mode: computes the constant thrust needed to complete this transfer in the provided duration with the provided Isp.
+    ///
+    /// The propellant mass only depends on the delta-v and the Isp, so the thrust is the mass flow that burns it in that duration.
+    #[allow(non_snake_case)]
+    pub fn required_thrust(
+        &self,
+        transfer_time: Duration,
+        isp_s: f64,
+        initial_mass_kg: f64,
+    ) -> Result<EdelbaumReport, NyxError> {
+        if transfer_time <= Duration::ZERO {
+            return Err(NyxError::CustomError {
+                msg: format!(
+                    "Edelbaum transfer requires a positive transfer time, got {transfer_time}"
+                ),
+            });
+        }
+        let propellant_mass_kg = self.propellant_mass_kg(isp_s, initial_mass_kg)?;
+        let thrust_N = propellant_mass_kg / transfer_time.to_seconds() * isp_s * STD_GRAVITY;
+
+        Ok(EdelbaumReport {
+            transfer: *self,
+            delta_v_km_s: self.delta_v_km_s(),
+            initial_yaw_deg: self.initial_yaw_deg(),
+            thrust_N,
+            isp_s,
+            initial_mass_kg,
+            propellant_mass_kg,
+            transfer_time,
+        })
+    }
+
+    /// Propellant mass from the rocket equation
+    fn propellant_mass_kg(&self, isp_s: f64, initial_mass_kg: f64) -> Result<f64, NyxError> {
+        if isp_s <= 0.0 || initial_mass_kg <= 0.0 {
+            return Err(NyxError::CustomError {
+                msg: format!(
+                    "Edelbaum transfer requires a positive Isp and initial mass, got {isp_s} s and {initial_mass_kg} kg"
+                ),
+            });
+        }
+        let exhaust_velocity_km_s = isp_s * STD_GRAVITY * 1e-3;
+        Ok(initial_mass_kg * (1.0 - (-self.delta_v_km_s() / exhaust_velocity_km_s).exp()))
+    }
+}
@@ -0,0 +1,391 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2018-onwards Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Patched-conics sketching of interplanetary trajectories.
+//!
+//! Each leg between two encounters is a heliocentric (or more generally, central body centered) Lambert arc.
+//! At every intermediate encounter, the incoming and outgoing hyperbolic excess velocities are compared to
+//! determine the turn angle of the flyby, the periapsis radius it requires, and the Δv needed at periapsis
+//! if the excess velocity magnitudes do not match (powered flyby).
+
+use super::lambert::{standard, TransferKind};
+use crate::cosmic::{Frame, Orbit, Spacecraft};
+use crate::errors::{FromAlmanacSnafu, FromPhysicsSnafu, NyxError};
+use crate::linalg::Vector3;
+use crate::md::trajectory::Traj;
+use crate::time::{Duration, Epoch, TimeSeries};
+use anise::almanac::Almanac;
+use snafu::ResultExt;
+use std::fmt;
+use typed_builder::TypedBuilder;
+
+/// A body encountered along an itinerary, i.e. the departure body, a flyby body, or the arrival body.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Encounter {
+    /// Frame of the encountered body, its gravitational parameter must be set.
+    pub frame: Frame,
+    /// Epoch of the encounter.
+    pub epoch: Epoch,
+    /// Minimum safe periapsis radius of a flyby, defaults to the mean equatorial radius of the frame.
+    pub min_periapsis_radius_km: Option<f64>,
+}
+
+impl Encounter {
+    pub fn new(frame: Frame, epoch: Epoch) -> Self {
+        Self {
+            frame,
+            epoch,
+            min_periapsis_radius_km: None,
+        }
+    }
+
+    /// Sets the minimum safe periapsis radius of a flyby of this body.
+    pub fn with_min_periapsis_radius_km(mut self, radius_km: f64) -> Self {
+        self.min_periapsis_radius_km = Some(radius_km);
+        self
+    }
+
+    fn mu_km3_s2(&self) -> Result<f64, NyxError> {
+        self.frame.mu_km3_s2().context(FromPhysicsSnafu {
+            action: "fetching gravitational parameter of encountered body",
+        })
+    }
+}
+
+/// A sequence of encounters to connect with Lambert arcs around a central body (typically the Sun).
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(doc)]
+pub struct Itinerary {
+    /// Central body frame of the Lambert arcs, its gravitational parameter must be set.
+    pub central: Frame,
+    /// Departure body, flyby bodies, and arrival body, in chronological order.
+    pub encounters: Vec<Encounter>,
+    /// Radius of the circular parking orbit at departure. If unset, the departure Δv is the hyperbolic excess velocity.
+    #[builder(default, setter(strip_option))]
+    pub departure_parking_radius_km: Option<f64>,
+    /// Periapsis radius of the capture orbit at arrival. If unset, the arrival Δv is the hyperbolic excess velocity.
+    #[builder(default, setter(strip_option))]
+    pub arrival_periapsis_radius_km: Option<f64>,
+    /// Eccentricity of the capture orbit at arrival, defaults to a circular orbit.
+    #[builder(default)]
+    pub arrival_eccentricity: f64,
+}
+
+/// A Lambert arc between two successive encounters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Leg {
+    /// State on the transfer arc at the departure encounter, in the central frame.
+    pub departure: Orbit,
+    /// State on the transfer arc at the arrival encounter, in the central frame.
+    pub arrival: Orbit,
+    /// Whether the arc is traveled the long way, i.e. the transfer angle is greater than 180 degrees.
+    pub long_way: bool,
+}
+
+impl Leg {
+    /// Time of flight of this leg.
+    pub fn tof(&self) -> Duration {
+        self.arrival.epoch - self.departure.epoch
+    }
+
+    /// Samples this conic arc every step into a trajectory, including its arrival state.
+    pub fn to_traj(&self, step: Duration) -> Result<Traj<Spacecraft>, NyxError> {
+        let mut traj = Traj::new();
+        for epoch in TimeSeries::inclusive(self.departure.epoch, self.arrival.epoch, step) {
+            let orbit = self.departure.at_epoch(epoch).context(FromPhysicsSnafu {
+                action: "sampling itinerary leg",
+            })?;
+            traj.states.push(Spacecraft::from(orbit));
+        }
+        traj.states.push(Spacecraft::from(self.arrival));
+        traj.finalize();
+        Ok(traj)
+    }
+}
+
+/// Geometry of a gravity assist at an intermediate encounter.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Flyby {
+    /// Angle between the incoming and outgoing hyperbolic excess velocities, in degrees.
+    pub turn_angle_deg: f64,
+    /// Periapsis radius required to achieve this turn angle, computed with the mean of the excess velocity magnitudes.
+    pub periapsis_radius_km: f64,
+    /// Minimum safe periapsis radius of this flyby.
+    pub min_periapsis_radius_km: f64,
+    /// Δv at periapsis to match the magnitudes of the incoming and outgoing excess velocities.
+    pub powered_dv_km_s: f64,
+}
+
+impl Flyby {
+    /// A flyby is feasible if its periapsis radius is above the minimum safe radius.
+    pub fn is_feasible(&self) -> bool {
+        self.periapsis_radius_km >= self.min_periapsis_radius_km
+    }
+}
+
+/// Hyperbolic excess velocities at an encounter, and the flyby geometry for intermediate encounters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EncounterReport {
+    pub encounter: Encounter,
+    /// Incoming hyperbolic excess velocity (unset for the departure body), in km/s.
+    pub v_inf_in: Option<Vector3<f64>>,
+    /// Outgoing hyperbolic excess velocity (unset for the arrival body), in km/s.
+    pub v_inf_out: Option<Vector3<f64>>,
+    /// Flyby geometry, only set for intermediate encounters.
+    pub flyby: Option<Flyby>,
+}
+
+/// Summary of a patched-conics itinerary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItineraryReport {
+    pub legs: Vec<Leg>,
+    pub encounters: Vec<EncounterReport>,
+    /// Characteristic energy at departure, in km^2/s^2.
+    pub departure_c3_km2_s2: f64,
+    /// Injection Δv at departure, in km/s.
+    pub departure_dv_km_s: f64,
+    /// Sum of the powered flyby Δv, i.e. the mismatch between successive legs, in km/s.
+    pub flyby_dv_km_s: f64,
+    /// Insertion Δv at arrival, in km/s.
+    pub arrival_dv_km_s: f64,
+}
+
+impl ItineraryReport {
+    /// Total Δv of this itinerary, in km/s.
+    pub fn total_dv_km_s(&self) -> f64 {
+        self.departure_dv_km_s + self.flyby_dv_km_s + self.arrival_dv_km_s
+    }
+
+    /// Returns the encounters whose flyby requires a periapsis radius below the minimum safe radius.
+    pub fn infeasible_flybys(&self) -> Vec<&EncounterReport> {
+        self.encounters
+            .iter()
+            .filter(|enc| enc.flyby.is_some_and(|flyby| !flyby.is_feasible()))
+            .collect()
+    }
+
+    /// Returns true if all flybys are feasible.
+    pub fn is_feasible(&self) -> bool {
+        self.infeasible_flybys().is_empty()
+    }
+
+    /// Samples each leg every step, returning one trajectory per leg.
+    pub fn to_trajs(&self, step: Duration) -> Result<Vec<Traj<Spacecraft>>, NyxError> {
+        self.legs.iter().map(|leg| leg.to_traj(step)).collect()
+    }
+}
+
+impl fmt::Display for ItineraryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Itinerary of {} legs: total Δv = {:.3} km/s (departure C3 = {:.3} km^2/s^2, departure Δv = {:.3} km/s, flyby Δv = {:.3} km/s, arrival Δv = {:.3} km/s)",
+            self.legs.len(),
+            self.total_dv_km_s(),
+            self.departure_c3_km2_s2,
+            self.departure_dv_km_s,
+            self.flyby_dv_km_s,
+            self.arrival_dv_km_s
+        )?;
+        for enc in &self.encounters {
+            write!(f, "\t{} @ {}", enc.encounter.frame, enc.encounter.epoch)?;
+            if let Some(v_inf) = enc.v_inf_in {
+                write!(f, "\tv_inf in = {:.3} km/s", v_inf.norm())?;
+            }
+            if let Some(v_inf) = enc.v_inf_out {
+                write!(f, "\tv_inf out = {:.3} km/s", v_inf.norm())?;
+            }
+            if let Some(flyby) = enc.flyby {
+                write!(
+                    f,
+                    "\tturn = {:.3} deg\trp = {:.1} km (min {:.1} km){}\tpowered Δv = {:.3} km/s",
+                    flyby.turn_angle_deg,
+                    flyby.periapsis_radius_km,
+                    flyby.min_periapsis_radius_km,
+                    if flyby.is_feasible() {
+                        ""
+                    } else {
+                        " INFEASIBLE"
+                    },
+                    flyby.powered_dv_km_s
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Itinerary {
+    /// Solves the Lambert arc of each leg and computes the excess velocities, flyby geometries, and Δv budget.
+    pub fn solve(&self, almanac: &Almanac) -> Result<ItineraryReport, NyxError> {
+        if self.encounters.len() < 2 {
+            return Err(NyxError::CustomError {
+                msg: "an itinerary requires at least two encounters".to_string(),
+            });
+        }
+
+        let gm = self.central.mu_km3_s2().context(FromPhysicsSnafu {
+            action: "fetching gravitational parameter of central body",
+        })?;
+
+        // Compute the state of each body in the central frame.
+        let mut bodies = Vec::with_capacity(self.encounters.len());
+        for enc in &self.encounters {
+            let mut body = almanac
+                .transform(enc.frame, self.central, enc.epoch, None)
+                .context(FromAlmanacSnafu {
+                    action: "computing state of encountered body",
+                })?;
+            body.frame = self.central;
+            bodies.push(body);
+        }
+
+        let mut legs = Vec::with_capacity(bodies.len() - 1);
+        for (ii, pair) in bodies.windows(2).enumerate() {
+            let (from, to) = (pair[0], pair[1]);
+            let tof_s = (to.epoch - from.epoch).to_seconds();
+            if tof_s <= 0.0 {
+                return Err(NyxError::CustomError {
+                    msg: format!("encounter #{} is not after encounter #{ii}", ii + 1),
+                });
+            }
+
+            // Prograde transfers travel in the direction of motion of the departure body.
+            let hvec = from.hvec().context(FromPhysicsSnafu {
+                action: "computing orbital momentum of departure body",
+            })?;
+            let long_way = from.radius_km.cross(&to.radius_km).dot(&hvec) < 0.0;
+            let kind = if long_way {
+                TransferKind::LongWay
+            } else {
+                TransferKind::ShortWay
+            };
+
+            let sol = standard(from.radius_km, to.radius_km, tof_s, gm, kind)?;
+
+            let mut departure = from;
+            departure.velocity_km_s = sol.v_init;
+            let mut arrival = to;
+            arrival.velocity_km_s = sol.v_final;
+
+            legs.push(Leg {
+                departure,
+                arrival,
+                long_way,
+            });
+        }
+
+        let mut encounters = Vec::with_capacity(bodies.len());
+        let mut flyby_dv_km_s = 0.0;
+        for (ii, (enc, body)) in self.encounters.iter().zip(&bodies).enumerate() {
+            let v_inf_in = ii
+                .checked_sub(1)
+                .map(|prev| legs[prev].arrival.velocity_km_s - body.velocity_km_s);
+            let v_inf_out = legs
+                .get(ii)
+                .map(|leg| leg.departure.velocity_km_s - body.velocity_km_s);
+
+            let flyby = match (v_inf_in, v_inf_out) {
+                (Some(v_in), Some(v_out)) => {
+                    let mu = enc.mu_km3_s2()?;
+                    let min_periapsis_radius_km = match enc.min_periapsis_radius_km {
+                        Some(radius_km) => radius_km,
+                        None => {
+                            enc.frame
+                                .mean_equatorial_radius_km()
+                                .context(FromPhysicsSnafu {
+                                    action: "fetching minimum flyby radius",
+                                })?
+                        }
+                    };
+
+                    let turn_angle = (v_in.dot(&v_out) / (v_in.norm() * v_out.norm()))
+                        .clamp(-1.0, 1.0)
+                        .acos();
+                    let v_inf = 0.5 * (v_in.norm() + v_out.norm());
+                    // From sin(δ/2) = 1 / (1 + rp v_inf^2 / μ)
+                    let periapsis_radius_km = if turn_angle > f64::EPSILON {
+                        mu / v_inf.powi(2) * (1.0 / (0.5 * turn_angle).sin() - 1.0)
+                    } else {
+                        f64::INFINITY
+                    };
+
+                    // The periapsis speeds must be matched with a maneuver if the excess velocities differ.
+                    let rp = periapsis_radius_km.max(min_periapsis_radius_km);
+                    let powered_dv_km_s = ((v_out.norm_squared() + 2.0 * mu / rp).sqrt()
+                        - (v_in.norm_squared() + 2.0 * mu / rp).sqrt())
+                    .abs();
+                    flyby_dv_km_s += powered_dv_km_s;
+
+                    let flyby = Flyby {
+                        turn_angle_deg: turn_angle.to_degrees(),
+                        periapsis_radius_km,
+                        min_periapsis_radius_km,
+                        powered_dv_km_s,
+                    };
+
+                    if !flyby.is_feasible() {
+                        warn!(
+                            "flyby of {} @ {} requires rp = {:.1} km < {:.1} km",
+                            enc.frame, enc.epoch, periapsis_radius_km, min_periapsis_radius_km
+                        );
+                    }
+
+                    Some(flyby)
+                }
+                _ => None,
+            };
+
+            encounters.push(EncounterReport {
+                encounter: *enc,
+                v_inf_in,
+                v_inf_out,
+                flyby,
+            });
+        }
+
+        let v_inf_dep = encounters[0].v_inf_out.unwrap().norm();
+        let departure_dv_km_s = match self.departure_parking_radius_km {
+            Some(radius_km) => {
+                let mu = self.encounters[0].mu_km3_s2()?;
+                (v_inf_dep.powi(2) + 2.0 * mu / radius_km).sqrt() - (mu / radius_km).sqrt()
+            }
+            None => v_inf_dep,
+        };
+
+        let v_inf_arr = encounters.last().unwrap().v_inf_in.unwrap().norm();
+        let arrival_dv_km_s = match self.arrival_periapsis_radius_km {
+            Some(radius_km) => {
+                let mu = self.encounters.last().unwrap().mu_km3_s2()?;
+                (v_inf_arr.powi(2) + 2.0 * mu / radius_km).sqrt()
+                    - (mu * (1.0 + self.arrival_eccentricity) / radius_km).sqrt()
+            }
+            None => v_inf_arr,
+        };
+
+        Ok(ItineraryReport {
+            legs,
+            encounters,
+            departure_c3_km2_s2: v_inf_dep.powi(2),
+            departure_dv_km_s,
+            flyby_dv_km_s,
+            arrival_dv_km_s,
+        })
+    }
+}
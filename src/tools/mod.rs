@@ -16,4 +16,6 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod edelbaum;
+pub mod itinerary;
 pub mod lambert;
@@ -0,0 +1,144 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{arrival_periapsis, departure_injection, AstroError, BPlane, Orbit};
+use nyx::linalg::Vector3;
+use nyx::propagators::AnalyticPropagator;
+use nyx::time::{Epoch, Unit};
+use nyx::utils::between_pm_180;
+use nyx::Spacecraft;
+
+use std::sync::Arc;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Returns the right ascension and declination of the provided vector, in degrees
+fn radec_deg(vec: &Vector3<f64>) -> (f64, f64) {
+    (
+        vec.y.atan2(vec.x).to_degrees(),
+        (vec.z / vec.norm()).asin().to_degrees(),
+    )
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn departure_asymptote_curtis(almanac: Arc<Almanac>) {
+    // Curtis, 3rd Ed., Example 8.4: departure to Mars from a 300 km circular parking orbit with v∞ = 2.943 km/s,
+    // requiring an injection Δv of 3.590 km/s onto a hyperbola whose periapsis is 29.16 deg from the asymptote's normal.
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(398_600.0);
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2026, 11, 1);
+    let parking_alt_km = 6678.0 - eme2k.mean_equatorial_radius_km().unwrap();
+    let c3_km2_s2 = 2.943_f64.powi(2);
+    let (rla_deg, dla_deg, inc_deg) = (140.0, 20.0, 28.5);
+
+    let solutions = departure_injection(
+        c3_km2_s2,
+        rla_deg,
+        dla_deg,
+        inc_deg,
+        parking_alt_km,
+        epoch,
+        eme2k,
+    )
+    .unwrap();
+
+    for departure in solutions {
+        println!("{departure}");
+        assert!((departure.delta_v_km_s - 3.590).abs() < 1e-3);
+        assert!((departure.parking.rmag_km() - 6678.0).abs() < 1e-9);
+        assert!((departure.parking.vmag_km_s() - 7.726).abs() < 1e-3);
+        assert!((departure.parking.inc_deg().unwrap() - inc_deg).abs() < 1e-9);
+        assert!(departure.parking.ecc().unwrap() < 1e-12);
+        assert!((departure.hyperbola.inc_deg().unwrap() - inc_deg).abs() < 1e-9);
+        assert!((departure.hyperbola.c3_km2_s2().unwrap() - c3_km2_s2).abs() < 1e-9);
+        assert!((departure.hyperbola.ta_deg().unwrap()).abs() < 1e-6);
+        // Curtis' β is the angle between the periapsis and the hyperbola's center-to-asymptote direction
+        let beta_deg = (1.0 / departure.hyperbola.ecc().unwrap())
+            .acos()
+            .to_degrees();
+        assert!((beta_deg - 29.16).abs() < 1e-2);
+        assert!(
+            between_pm_180(departure.aol_deg - departure.parking.aol_deg().unwrap()).abs() < 1e-6
+        );
+
+        // Far from the Earth, the velocity aligns with the requested asymptote
+        let far = AnalyticPropagator::two_body(1 * Unit::Day)
+            .with(Spacecraft::from(departure.hyperbola), almanac.clone())
+            .for_duration(1000 * Unit::Day)
+            .unwrap();
+        let (ra_deg, dec_deg) = radec_deg(&far.orbit.velocity_km_s);
+        assert!(between_pm_180(ra_deg - rla_deg).abs() < 5e-2);
+        assert!((dec_deg - dla_deg).abs() < 5e-2);
+        assert!((far.orbit.vmag_km_s() - 2.943).abs() < 1e-3);
+    }
+
+    // Both solutions inject at different nodes of the parking orbit
+    assert!(
+        (solutions[0].parking.raan_deg().unwrap() - solutions[1].parking.raan_deg().unwrap()).abs()
+            > 1.0
+    );
+
+    // The declination exceeds the inclination: a plane change would be needed
+    match departure_injection(
+        c3_km2_s2,
+        rla_deg,
+        40.0,
+        inc_deg,
+        parking_alt_km,
+        epoch,
+        eme2k,
+    ) {
+        Err(AstroError::InfeasibleAsymptote {
+            min_inc_deg,
+            max_inc_deg,
+            ..
+        }) => {
+            assert!((min_inc_deg - 40.0).abs() < 1e-12);
+            assert!((max_inc_deg - 140.0).abs() < 1e-12);
+        }
+        other => panic!("expected an infeasible asymptote, got {other:?}"),
+    }
+
+    assert!(
+        departure_injection(0.0, rla_deg, dla_deg, inc_deg, parking_alt_km, epoch, eme2k).is_err()
+    );
+}
+
+#[rstest]
+fn arrival_asymptote_b_plane(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2026, 11, 1);
+    let v_inf_km_s = Vector3::new(-1.2, 2.5, 0.8);
+
+    for b_plane_angle_deg in [0.0, 35.0, 120.0, -60.0] {
+        let arrival =
+            arrival_periapsis(v_inf_km_s, 500.0, b_plane_angle_deg, epoch, eme2k).unwrap();
+        println!("{arrival}");
+
+        let orbit: Orbit = arrival.hyperbola;
+        assert!((orbit.periapsis_km().unwrap() - orbit.rmag_km()).abs() < 1e-6);
+        assert!(
+            (orbit.rmag_km() - eme2k.mean_equatorial_radius_km().unwrap() - 500.0).abs() < 1e-9
+        );
+        assert!((orbit.c3_km2_s2().unwrap() - v_inf_km_s.norm_squared()).abs() < 1e-9);
+
+        // The B-Plane of the resulting hyperbola matches the design
+        let b_plane = BPlane::new(orbit).unwrap();
+        assert!((b_plane.b_dot_t() - arrival.b_t_km).abs() < 1e-6);
+        assert!((b_plane.b_dot_r() - arrival.b_r_km).abs() < 1e-6);
+        assert!(between_pm_180(b_plane.angle() - b_plane_angle_deg).abs() < 1e-6);
+
+        // And its incoming asymptote is the provided v∞
+        let s_hat = b_plane.inertial_to_bplane().row(0).transpose();
+        assert!((s_hat - v_inf_km_s.normalize()).norm() < 1e-9);
+    }
+}
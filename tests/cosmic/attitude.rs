@@ -0,0 +1,171 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::attitude::{AttitudeEphemeris, AttitudeError, Plate, PlateModel};
+use nyx::cosmic::fov::AttitudeLaw;
+use nyx::cosmic::Orbit;
+use nyx::linalg::Vector3;
+use nyx::time::{Epoch, Unit};
+
+use anise::constants::frames::EARTH_J2000;
+use anise::prelude::Almanac;
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Almanac {
+    use crate::test_almanac;
+    test_almanac()
+}
+
+/// Body rate about the Z axis of EME2000, in degrees per second
+const RATE_DEG_S: f64 = 0.5;
+
+/// Builds an AEM of a body spinning at a constant rate about the Z axis, sampled every minute for ten minutes.
+/// The `conjugate` flag writes the quaternion of the opposite rotation, `scalar_first` selects the QUATERNION_TYPE.
+fn spinning_aem(
+    frame_a: &str,
+    frame_b: &str,
+    dir: &str,
+    conjugate: bool,
+    scalar_first: bool,
+) -> String {
+    let mut aem = format!(
+        "CCSDS_AEM_VERS = 1.0
+CREATION_DATE = 2024-01-01T00:00:00
+ORIGINATOR = NYX
+COMMENT Synthetic constant rate spin about Z
+META_START
+OBJECT_NAME = SPINNER
+OBJECT_ID = 2024-001A
+REF_FRAME_A = {frame_a}
+REF_FRAME_B = {frame_b}
+ATTITUDE_DIR = {dir}
+TIME_SYSTEM = UTC
+START_TIME = 2024-01-01T00:00:00.000
+STOP_TIME = 2024-01-01T00:10:00.000
+ATTITUDE_TYPE = QUATERNION
+QUATERNION_TYPE = {}
+META_STOP
+DATA_START
+",
+        if scalar_first { "FIRST" } else { "LAST" }
+    );
+    for minute in 0..=10 {
+        let half_angle = (RATE_DEG_S * 60.0 * f64::from(minute)).to_radians() / 2.0;
+        let qc = half_angle.cos();
+        let q3 = if conjugate {
+            -half_angle.sin()
+        } else {
+            half_angle.sin()
+        };
+        let line = if scalar_first {
+            format!("2024-01-01T00:{minute:02}:00.000 {qc:.15} 0.0 0.0 {q3:.15}\n")
+        } else {
+            format!("2024-01-01T00:{minute:02}:00.000 0.0 0.0 {q3:.15} {qc:.15}\n")
+        };
+        aem.push_str(&line);
+    }
+    aem.push_str("DATA_STOP\n");
+    aem
+}
+
+#[rstest]
+fn aem_constant_rate_slerp(almanac: Almanac) {
+    let start = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+
+    let ephem =
+        AttitudeEphemeris::from_aem_str(&spinning_aem("EME2000", "SC_BODY_1", "A2B", false, false))
+            .unwrap();
+    assert_eq!(ephem.object_name, Some("SPINNER".to_string()));
+    assert_eq!(ephem.start(), start);
+    assert_eq!(ephem.end(), start + 10 * Unit::Minute);
+    assert_eq!(ephem.samples().len(), 11);
+
+    // The same attitude, with the body frame first or the rotation reversed, and with the scalar first.
+    let equivalents = [
+        spinning_aem("SC_BODY_1", "EME2000", "A2B", true, false),
+        spinning_aem("SC_BODY_1", "EME2000", "B2A", false, true),
+        spinning_aem("EME2000", "SC_BODY_1", "B2A", true, true),
+    ]
+    .map(|aem| AttitudeEphemeris::from_aem_str(&aem).unwrap());
+
+    for seconds in [0.0, 15.0, 45.0, 95.5, 333.3, 599.0, 600.0] {
+        let epoch = start + seconds * Unit::Second;
+        let angle = (RATE_DEG_S * seconds).to_radians();
+
+        // SLERP is exact for a constant rate about a fixed axis.
+        let dcm = ephem.dcm_to_inertial(epoch).unwrap();
+        let x_body = dcm * Vector3::x();
+        let expected = Vector3::new(angle.cos(), angle.sin(), 0.0);
+        assert!(
+            (x_body - expected).norm() < 1e-12,
+            "t = {seconds} s: got {x_body} expected {expected}"
+        );
+        assert!((dcm * Vector3::z() - Vector3::z()).norm() < 1e-12);
+
+        for other in &equivalents {
+            let other_dcm = other.dcm_to_inertial(epoch).unwrap();
+            assert!((other_dcm - dcm).norm() < 1e-12, "t = {seconds} s");
+        }
+    }
+
+    // Outside of the coverage, the error reports the covered span.
+    for epoch in [start - 1 * Unit::Second, start + 11 * Unit::Minute] {
+        assert_eq!(
+            ephem.quaternion_at(epoch),
+            Err(AttitudeError::OutOfCoverage {
+                epoch,
+                start,
+                end: start + 10 * Unit::Minute,
+            })
+        );
+    }
+
+    // A single plate along the body X axis, seen from the X axis of EME2000: its projected area follows the spin.
+    let plates = PlateModel {
+        plates: vec![Plate {
+            normal_body: Vector3::x(),
+            area_m2: 2.0,
+        }],
+    };
+    for (seconds, expected_m2) in [
+        (0.0, 2.0),
+        (120.0, 1.0),
+        (180.0, 0.0),
+        (300.0, 0.0),
+        (600.0, 1.0),
+    ] {
+        let area_m2 = plates
+            .projected_area_at_m2(&ephem, start + seconds * Unit::Second, &Vector3::x())
+            .unwrap();
+        assert!(
+            (area_m2 - expected_m2).abs() < 1e-9,
+            "t = {seconds} s: got {area_m2} m^2 expected {expected_m2} m^2"
+        );
+    }
+
+    // The attitude law of the field of view events uses the ephemeris.
+    let epoch = start + 95.5 * Unit::Second;
+    let orbit = Orbit::keplerian(7000.0, 0.001, 30.0, 10.0, 20.0, 30.0, epoch, EARTH_J2000);
+    let law = AttitudeLaw::Ephemeris(Arc::new(ephem.clone()));
+    assert_eq!(
+        law.dcm_to_inertial(&orbit, &almanac).unwrap(),
+        ephem.dcm_to_inertial(epoch).unwrap()
+    );
+}
+
+#[test]
+fn aem_invalid() {
+    // Neither frame is inertial
+    assert!(AttitudeEphemeris::from_aem_str(&spinning_aem(
+        "SC_BODY_1",
+        "INSTRUMENT_1",
+        "A2B",
+        false,
+        false
+    ))
+    .is_err());
+
+    // No data
+    assert!(AttitudeEphemeris::from_aem_str("CCSDS_AEM_VERS = 1.0\n").is_err());
+}
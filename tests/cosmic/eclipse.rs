@@ -3,12 +3,16 @@ extern crate nyx_space as nyx;
 use anise::astro::Occultation;
 use anise::constants::celestial_objects::{JUPITER_BARYCENTER, SUN};
 use anise::constants::frames::SUN_J2000;
-use nyx::cosmic::eclipse::EclipseLocator;
-use nyx::cosmic::Orbit;
+use nyx::cosmic::eclipse::{EclipseEvent, EclipseLocator};
+use nyx::cosmic::{Orbit, Spacecraft};
 use nyx::dynamics::orbital::OrbitalDynamics;
 use nyx::dynamics::SpacecraftDynamics;
+use nyx::md::prelude::Traj;
+use nyx::md::{EventDetails, EventEdge, EventError, EventEvaluator, EventPrediction};
 use nyx::propagators::{IntegratorOptions, Propagator};
-use nyx::time::{Epoch, Unit};
+use nyx::time::{Duration, Epoch, Unit};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{mpsc, Arc};
 use std::thread;
 
@@ -122,3 +126,143 @@ fn geo_sun_earth_eclipses(almanac: Arc<Almanac>) {
 
     assert_eq!(cnt_changes, 14, "wrong number of eclipse state changes");
 }
+
+/// Eclipse event which counts its evaluations, i.e. the interpolations of the trajectory during the search.
+struct CountedEclipseEvent {
+    inner: EclipseEvent,
+    evals: AtomicUsize,
+}
+
+impl fmt::Display for CountedEclipseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "counted {}", self.inner)
+    }
+}
+
+impl EventEvaluator<Spacecraft> for CountedEclipseEvent {
+    fn eval(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Result<f64, EventError> {
+        self.evals.fetch_add(1, Ordering::Relaxed);
+        self.inner.eval(state, almanac)
+    }
+
+    fn eval_string(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Result<String, EventError> {
+        self.inner.eval_string(state, almanac)
+    }
+
+    fn epoch_precision(&self) -> Duration {
+        self.inner.epoch_precision()
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.inner.value_precision()
+    }
+
+    fn predict(&self, state: &Spacecraft, almanac: Arc<Almanac>) -> Option<EventPrediction> {
+        self.inner.predict(state, almanac)
+    }
+}
+
+#[rstest]
+fn leo_predicted_eclipses(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let leo = Orbit::keplerian(7000.0, 0.001, 28.5, 35.0, 45.0, 0.0, start_time, eme2k);
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(leo.into(), almanac.clone())
+        .for_duration_with_traj(7 * Unit::Day)
+        .unwrap();
+
+    let e_loc = EclipseLocator {
+        light_source: almanac.frame_from_uid(SUN_J2000).unwrap(),
+        shadow_bodies: vec![eme2k],
+    };
+
+    let predicted = CountedEclipseEvent {
+        inner: e_loc.to_eclipse_event(0.5),
+        evals: AtomicUsize::new(0),
+    };
+    let generic = CountedEclipseEvent {
+        inner: e_loc.to_eclipse_event(0.5).with_max_ecc(0.0),
+        evals: AtomicUsize::new(0),
+    };
+
+    let events = traj.find(&predicted, almanac.clone()).unwrap();
+
+    // The generic search only finds one event per percent of the trajectory, so search each day separately.
+    let mut ref_events: Vec<EventDetails<Spacecraft>> = Vec::new();
+    let days = 7;
+    let num_states = traj.states.len();
+    for day in 0..days {
+        // Overlap by one state so that no crossing falls between two days
+        let day_traj = Traj {
+            name: None,
+            states: traj.states
+                [day * num_states / days..((day + 1) * num_states / days + 1).min(num_states)]
+                .to_vec(),
+            ..Default::default()
+        };
+        for event in day_traj.find(&generic, almanac.clone()).unwrap() {
+            if !ref_events
+                .iter()
+                .any(|prev| (event.state.epoch() - prev.state.epoch()).abs() < 1 * Unit::Second)
+            {
+                ref_events.push(event);
+            }
+        }
+    }
+
+    let predicted_evals = predicted.evals.load(Ordering::Relaxed);
+    let generic_evals = generic.evals.load(Ordering::Relaxed);
+    // About one entry and one exit per revolution
+    assert!(events.len() > 150, "too few eclipse edges found");
+    assert_eq!(events.len(), ref_events.len(), "different number of edges");
+    for (event, ref_event) in events.iter().zip(&ref_events) {
+        assert_eq!(event.edge, ref_event.edge);
+        assert!(
+            (event.state.epoch() - ref_event.state.epoch()).abs() < 1 * Unit::Second,
+            "{} != {}",
+            event.state.epoch(),
+            ref_event.state.epoch()
+        );
+    }
+
+    assert!(
+        predicted_evals * 3 < generic_evals,
+        "predicted search not significantly cheaper: {predicted_evals} vs {generic_evals} evaluations"
+    );
+
+    // The arcs are the eclipses, delimited by the same edges
+    let arcs = traj.find_arcs(&predicted, almanac.clone()).unwrap();
+    let rises = ref_events
+        .iter()
+        .filter(|event| event.edge == EventEdge::Rising)
+        .count();
+    assert!(arcs.len() == rises || arcs.len() == rises + 1);
+    for arc in &arcs {
+        assert!(
+            arc.rise.state.epoch() == traj.first().epoch()
+                || ref_events
+                    .iter()
+                    .any(|event| (event.state.epoch() - arc.rise.state.epoch()).abs()
+                        < 1 * Unit::Second),
+            "unexpected eclipse entry at {}",
+            arc.rise.state.epoch()
+        );
+        assert!(
+            arc.fall.state.epoch() == traj.last().epoch()
+                || ref_events
+                    .iter()
+                    .any(|event| (event.state.epoch() - arc.fall.state.epoch()).abs()
+                        < 1 * Unit::Second),
+            "unexpected eclipse exit at {}",
+            arc.fall.state.epoch()
+        );
+        // Confirm that the middle of each arc is in shadow
+        let mid = traj
+            .at(arc.rise.state.epoch() + (arc.fall.state.epoch() - arc.rise.state.epoch()) * 0.5)
+            .unwrap();
+        assert!(e_loc.compute(mid.orbit, almanac.clone()).unwrap().factor() > 0.5);
+    }
+}
@@ -0,0 +1,123 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::{EARTH_J2000, MOON_J2000, SUN_J2000};
+use anise::prelude::Almanac;
+use nyx::cosmic::fov::{AttitudeLaw, FovAccessEvent, FovTarget};
+use nyx::cosmic::{Orbit, Spacecraft};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::linalg::Vector3;
+use nyx::md::EventEvaluator;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+use nyx::State;
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn nadir_instrument_moon_access(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 3, 1);
+
+    // Circular LEO whose plane contains the direction of the Moon, starting on the Moon side
+    let moon_hat = almanac
+        .transform(MOON_J2000, eme2k, epoch, None)
+        .unwrap()
+        .radius_km
+        .normalize();
+    let r_km = 7000.0;
+    let v_km_s = (eme2k.mu_km3_s2().unwrap() / r_km).sqrt();
+    let v_hat = Vector3::z().cross(&moon_hat).normalize();
+    let leo = Orbit::new(
+        r_km * moon_hat.x,
+        r_km * moon_hat.y,
+        r_km * moon_hat.z,
+        v_km_s * v_hat.x,
+        v_km_s * v_hat.y,
+        v_km_s * v_hat.z,
+        epoch,
+        eme2k,
+    );
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(Spacecraft::from(leo), almanac.clone())
+        .for_duration_with_traj(12 * Unit::Hour)
+        .unwrap();
+
+    let half_angle_deg = 60.0;
+    let event = FovAccessEvent::new(
+        AttitudeLaw::Nadir,
+        Vector3::z(),
+        half_angle_deg,
+        FovTarget::Body(MOON_J2000),
+    );
+
+    // Angle between the nadir and the Moon, and whether the Moon is below the local horizon plane
+    let moon_geometry = |sc: &Spacecraft| -> (f64, bool) {
+        let moon = almanac
+            .transform(MOON_J2000, eme2k, sc.epoch(), None)
+            .unwrap()
+            .radius_km
+            - sc.orbit.radius_km;
+        let nadir = -sc.orbit.radius_km;
+        let angle_deg = nadir.angle(&moon).to_degrees();
+        (angle_deg, moon.dot(&sc.orbit.radius_km) < 0.0)
+    };
+
+    let arcs = traj.find_arcs(&event, almanac.clone()).unwrap();
+    println!("{} windows", arcs.len());
+    // Starting on the Moon side, the Moon is visible once per revolution on the far side
+    assert!(arcs.len() >= 7, "expected a window per revolution");
+
+    for arc in &arcs {
+        let duration = arc.fall.state.epoch() - arc.rise.state.epoch();
+        println!("{} for {duration}", arc.rise.state.epoch());
+        // Within the window, the Moon is below the local horizon plane, within the field of view
+        for sc in traj.every_between(
+            30 * Unit::Second,
+            arc.rise.state.epoch() + 1 * Unit::Second,
+            arc.fall.state.epoch() - 1 * Unit::Second,
+        ) {
+            let (angle_deg, below_horizon) = moon_geometry(&sc);
+            assert!(below_horizon, "Moon above the horizon @ {}", sc.epoch());
+            assert!(angle_deg < half_angle_deg + 1e-2);
+        }
+    }
+
+    // Outside of the windows, the Moon is not in the field of view
+    for sc in traj.every(1 * Unit::Minute) {
+        let in_window = arcs.iter().any(|arc| {
+            arc.rise.state.epoch() <= sc.epoch() && sc.epoch() <= arc.fall.state.epoch()
+        });
+        let (angle_deg, _) = moon_geometry(&sc);
+        if !in_window {
+            assert!(
+                angle_deg > half_angle_deg - 1e-2,
+                "missed window @ {}",
+                sc.epoch()
+            );
+        }
+        // The single constraint is the field of view
+        let eval = event.eval(&sc, almanac.clone()).unwrap();
+        assert!((eval - (half_angle_deg - angle_deg)).abs() < 1e-6);
+    }
+
+    // A keep-out cone around the Sun can only shorten the windows
+    let constrained = event
+        .clone()
+        .with_keep_out(FovTarget::Body(SUN_J2000), 45.0)
+        .with_limb_avoidance(0.0);
+    for sc in traj.every(1 * Unit::Minute) {
+        let margins = constrained.margins(&sc, almanac.clone()).unwrap();
+        assert_eq!(margins.len(), 3);
+        let eval = constrained.eval(&sc, almanac.clone()).unwrap();
+        assert!(eval <= event.eval(&sc, almanac.clone()).unwrap());
+        assert_eq!(eval, margins.iter().copied().fold(f64::INFINITY, f64::min));
+    }
+}
@@ -1,3 +1,10 @@
+mod asymptote;
+mod attitude;
 mod bplane;
 mod eclipse;
+mod fov;
+mod orbit;
 mod orbit_dual;
+mod rotating;
+mod synodic;
+mod tle;
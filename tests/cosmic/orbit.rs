@@ -0,0 +1,441 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Orbit, OrbitDual, OrbitExt, Spacecraft};
+use nyx::dynamics::guidance::LocalFrame;
+use nyx::linalg::{Matrix3, Vector3};
+use nyx::md::StateParameter;
+use nyx::time::Epoch;
+use nyx::utils::between_pm_180;
+use nyx::State;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Almanac {
+    use crate::test_almanac;
+    test_almanac()
+}
+
+#[rstest]
+fn periapsis_apoapsis_altitude(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let r_eq_km = eme2k.mean_equatorial_radius_km().unwrap();
+
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+    // Equatorial LEO
+    let orbit = Orbit::keplerian(r_eq_km + 500.0, 0.01, 0.0, 0.0, 0.0, 0.0, epoch, eme2k);
+
+    let rp_km = orbit.periapsis_km().unwrap();
+    let ra_km = orbit.apoapsis_km().unwrap();
+
+    assert!((orbit.periapsis_altitude_km().unwrap() - (rp_km - r_eq_km)).abs() < 1e-12);
+    assert!((orbit.apoapsis_altitude_km().unwrap() - (ra_km - r_eq_km)).abs() < 1e-12);
+
+    // At periapsis of an equatorial orbit, the periapsis altitude is the distance above the equator.
+    assert!((orbit.periapsis_altitude_km().unwrap() - (orbit.rmag_km() - r_eq_km)).abs() < 1e-9);
+
+    // Check that these are available as state parameters.
+    let sc = Spacecraft::from(orbit);
+    assert_eq!(
+        sc.value(StateParameter::PeriapsisAltitude).unwrap(),
+        orbit.periapsis_altitude_km().unwrap()
+    );
+    assert_eq!(
+        sc.value(StateParameter::ApoapsisAltitude).unwrap(),
+        orbit.apoapsis_altitude_km().unwrap()
+    );
+
+    // The altitude is also available with its partials.
+    let dual = OrbitDual::from(orbit);
+    let alt_partial = dual.partial_for(StateParameter::PeriapsisAltitude).unwrap();
+    assert!((alt_partial.real() - orbit.periapsis_altitude_km().unwrap()).abs() < 1e-9);
+}
+
+#[rstest]
+fn from_radec_round_trip(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+
+    let (ra_deg, dec_deg, range_km) = (123.4, -23.5, 38_000.0);
+    let (ra_dot_deg_s, dec_dot_deg_s, range_rate_km_s) = (1e-3, -2e-4, 0.75);
+
+    let orbit = Orbit::from_radec(
+        ra_deg,
+        dec_deg,
+        range_km,
+        ra_dot_deg_s,
+        dec_dot_deg_s,
+        range_rate_km_s,
+        epoch,
+        eme2k,
+    );
+
+    assert_eq!(orbit.epoch, epoch);
+    assert!((orbit.right_ascension_deg() - ra_deg).abs() < 1e-10);
+    assert!((orbit.declination_deg() - dec_deg).abs() < 1e-10);
+    assert!((orbit.rmag_km() - range_km).abs() < 1e-8);
+    let range_rate = orbit.radius_km.dot(&orbit.velocity_km_s) / orbit.rmag_km();
+    assert!((range_rate - range_rate_km_s).abs() < 1e-12);
+
+    // The angular rates match a finite difference of the straight line motion.
+    let dt_s = 1e-3;
+    let mut later = orbit;
+    later.radius_km += orbit.velocity_km_s * dt_s;
+    let ra_dot_fd = (later.right_ascension_deg() - orbit.right_ascension_deg()) / dt_s;
+    let dec_dot_fd = (later.declination_deg() - orbit.declination_deg()) / dt_s;
+    assert!((ra_dot_fd - ra_dot_deg_s).abs() < 1e-8);
+    assert!((dec_dot_fd - dec_dot_deg_s).abs() < 1e-8);
+
+    // Round trip from an arbitrary orbit.
+    let orig = Orbit::keplerian(8_000.0, 0.1, 28.5, 45.0, 30.0, 60.0, epoch, eme2k);
+    let rho = orig.radius_km;
+    let (x, y, z) = (rho.x, rho.y, rho.z);
+    let rxy2 = x * x + y * y;
+    let ra_dot = (x * orig.velocity_km_s.y - y * orig.velocity_km_s.x) / rxy2;
+    let dec_dot = (orig.velocity_km_s.z * rxy2
+        - z * (x * orig.velocity_km_s.x + y * orig.velocity_km_s.y))
+        / (orig.rmag_km().powi(2) * rxy2.sqrt());
+    let rebuilt = Orbit::from_radec(
+        orig.right_ascension_deg(),
+        orig.declination_deg(),
+        orig.rmag_km(),
+        ra_dot.to_degrees(),
+        dec_dot.to_degrees(),
+        rho.dot(&orig.velocity_km_s) / orig.rmag_km(),
+        epoch,
+        eme2k,
+    );
+    assert!((rebuilt.radius_km - orig.radius_km).norm() < 1e-8);
+    assert!((rebuilt.velocity_km_s - orig.velocity_km_s).norm() < 1e-11);
+}
+
+#[rstest]
+fn vis_viva(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+
+    // Start away from periapsis
+    let orbit = Orbit::keplerian(8_000.0, 0.2, 28.5, 10.0, 20.0, 75.0, epoch, eme2k);
+
+    let energy = orbit.specific_energy().unwrap();
+    assert!((energy - orbit.energy_km2_s2().unwrap()).abs() < 1e-12);
+    assert!((energy + eme2k.mu_km3_s2().unwrap() / (2.0 * orbit.sma_km().unwrap())).abs() < 1e-9);
+
+    // Speed at the current radius matches the state
+    assert!((orbit.velocity_at_radius(orbit.rmag_km()).unwrap() - orbit.vmag_km_s()).abs() < 1e-12);
+
+    // Speed at perigee matches the Cartesian state at perigee
+    let at_perigee = Orbit::keplerian(8_000.0, 0.2, 28.5, 10.0, 20.0, 0.0, epoch, eme2k);
+    let rp_km = orbit.periapsis_km().unwrap();
+    assert!((at_perigee.rmag_km() - rp_km).abs() < 1e-9);
+    assert!((orbit.velocity_at_radius(rp_km).unwrap() - at_perigee.vmag_km_s()).abs() < 1e-10);
+
+    // Beyond apoapsis or invalid radii are rejected
+    assert!(orbit
+        .velocity_at_radius(orbit.apoapsis_km().unwrap() + 1.0)
+        .is_err());
+    assert!(orbit.velocity_at_radius(0.0).is_err());
+}
+
+#[rstest]
+fn angle_to_and_phase_angle(almanac: Almanac) {
+    use anise::constants::frames::{MOON_J2000, SUN_J2000};
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 6, 21);
+
+    let chaser = Orbit::keplerian(7_000.0, 0.0, 51.6, 30.0, 0.0, 10.0, epoch, eme2k);
+    let target = Orbit::keplerian(7_000.0, 0.0, 51.6, 30.0, 0.0, 100.0, epoch, eme2k);
+    assert!((chaser.angle_to(&target).unwrap() - 90.0).abs() < 1e-9);
+    assert!((target.angle_to(&chaser).unwrap() - 90.0).abs() < 1e-9);
+    assert!(chaser.angle_to(&chaser).unwrap().abs() < 1e-9);
+
+    let moon = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let lunar = Orbit::keplerian(2_000.0, 0.0, 10.0, 0.0, 0.0, 0.0, epoch, moon);
+    assert!(chaser.angle_to(&lunar).is_err());
+
+    // Place the spacecraft above the sub-solar and anti-solar points
+    let sun = almanac
+        .transform(SUN_J2000, EARTH_J2000, epoch, None)
+        .unwrap();
+    let sun_dir = sun.radius_km / sun.rmag_km();
+    let above_sun = Orbit::new(
+        7_000.0 * sun_dir.x,
+        7_000.0 * sun_dir.y,
+        7_000.0 * sun_dir.z,
+        0.0,
+        0.0,
+        7.5,
+        epoch,
+        eme2k,
+    );
+    assert!(above_sun.phase_angle_deg(&almanac).unwrap() < 1e-6);
+
+    let eclipsed = Orbit::new(
+        -7_000.0 * sun_dir.x,
+        -7_000.0 * sun_dir.y,
+        -7_000.0 * sun_dir.z,
+        0.0,
+        0.0,
+        7.5,
+        epoch,
+        eme2k,
+    );
+    assert!((eclipsed.phase_angle_deg(&almanac).unwrap() - 180.0).abs() < 1e-6);
+
+    // The daylight side is fully lit, and the spacecraft behind the Earth is in its umbra
+    assert_eq!(above_sun.illumination_fraction(&almanac).unwrap(), 1.0);
+    assert_eq!(eclipsed.illumination_fraction(&almanac).unwrap(), 0.0);
+}
+
+#[rstest]
+fn equinoctial_continuity(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 6, 21);
+
+    // Sweep the eccentricity vector through zero along a fixed line at a constant argument of latitude:
+    // the classical AoP jumps from 0 to 180 degrees while the equinoctial elements vary smoothly.
+    let (raan_deg, inc_deg, aol_deg) = (30.0, 51.6, 45.0);
+    let mut prev: Option<(Orbit, f64)> = None;
+    for step in -10..10 {
+        let signed_ecc = (step as f64 + 0.5) * 1e-5;
+        let aop_deg = if signed_ecc > 0.0 { 0.0 } else { 180.0 };
+        let orbit = Orbit::keplerian(
+            7_000.0,
+            signed_ecc.abs(),
+            inc_deg,
+            raan_deg,
+            aop_deg,
+            aol_deg - aop_deg,
+            epoch,
+            eme2k,
+        );
+
+        let eq = orbit.equinoctial().unwrap();
+        assert!((eq.sma_km - 7_000.0).abs() < 1e-6);
+        assert!((eq.ecc() - signed_ecc.abs()).abs() < 1e-9);
+        assert!((eq.inc_deg() - inc_deg).abs() < 1e-9);
+        assert!((eq.k - signed_ecc * raan_deg.to_radians().cos()).abs() < 1e-9);
+        assert!((eq.h - signed_ecc * raan_deg.to_radians().sin()).abs() < 1e-9);
+
+        // The dual representation matches, and so does the spacecraft parameter used for events and targeting
+        let dual = OrbitDual::from(orbit);
+        assert!((dual.equinoctial_h().unwrap().real() - eq.h).abs() < 1e-12);
+        assert!((dual.equinoctial_k().unwrap().real() - eq.k).abs() < 1e-12);
+        assert!((dual.equinoctial_p().real() - eq.p).abs() < 1e-12);
+        assert!((dual.equinoctial_q().real() - eq.q).abs() < 1e-12);
+        let sc = Spacecraft::builder().orbit(orbit).build();
+        assert!((sc.value(StateParameter::MeanLongitude).unwrap() - eq.lambda_deg).abs() < 1e-9);
+
+        if let Some((prev_orbit, prev_lambda_deg)) = prev {
+            let prev_eq = prev_orbit.equinoctial().unwrap();
+            assert!((eq.h - prev_eq.h).abs() < 1.1e-5);
+            assert!((eq.k - prev_eq.k).abs() < 1.1e-5);
+            assert!((eq.p - prev_eq.p).abs() < 1e-12);
+            assert!((eq.q - prev_eq.q).abs() < 1e-12);
+            assert!((eq.lambda_deg - prev_lambda_deg).abs() < 1e-3);
+
+            let aop_jump_deg =
+                between_pm_180(orbit.aop_deg().unwrap() - prev_orbit.aop_deg().unwrap()).abs();
+            if step == 0 {
+                assert!((aop_jump_deg - 180.0).abs() < 1e-6);
+            } else {
+                assert!(aop_jump_deg < 1e-6);
+            }
+        }
+        prev = Some((orbit, eq.lambda_deg));
+    }
+
+    // An equatorial orbit has no ascending node: the dual RAAN and AoP default to zero unless routed through the equinoctial elements
+    let mu_km3_s2 = eme2k.mu_km3_s2().unwrap();
+    let (sin_lon, cos_lon) = 30.0_f64.to_radians().sin_cos();
+    let vp_km_s = 1.01 * (mu_km3_s2 / 7_000.0).sqrt();
+    let equatorial = Orbit::new(
+        7_000.0 * cos_lon,
+        7_000.0 * sin_lon,
+        0.0,
+        -vp_km_s * sin_lon,
+        vp_km_s * cos_lon,
+        0.0,
+        epoch,
+        eme2k,
+    );
+    let dual = OrbitDual::from(equatorial);
+    assert_eq!(dual.raan_deg().real(), 0.0);
+    assert_eq!(dual.aop_deg().unwrap().real(), 0.0);
+
+    let fallback = dual.with_equinoctial_fallback();
+    assert!(fallback.raan_deg().real().abs() < 1e-12);
+    assert!((fallback.aop_deg().unwrap().real() - 30.0).abs() < 1e-9);
+    assert!((equatorial.equinoctial().unwrap().lonper_deg() - 30.0).abs() < 1e-9);
+}
+
+#[rstest]
+fn rotate_position_velocity(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 51.6, 30.0, 45.0, 60.0, epoch, eme2k);
+
+    // Rotating by identity is a no-op
+    let same = orbit.rotate_position_velocity(Matrix3::identity());
+    assert_eq!(same, orbit);
+
+    // Rotations about Z and X, which compose like the matrices
+    let (sin_a, cos_a) = 0.3_f64.sin_cos();
+    let (sin_b, cos_b) = (-1.2_f64).sin_cos();
+    let rot_z = Matrix3::new(cos_a, sin_a, 0.0, -sin_a, cos_a, 0.0, 0.0, 0.0, 1.0);
+    let rot_x = Matrix3::new(1.0, 0.0, 0.0, 0.0, cos_b, sin_b, 0.0, -sin_b, cos_b);
+
+    let twice = orbit
+        .rotate_position_velocity(rot_z)
+        .rotate_position_velocity(rot_x);
+    let composed = orbit.rotate_position_velocity(rot_x * rot_z);
+    assert!((twice.radius_km - composed.radius_km).norm() < 1e-9);
+    assert!((twice.velocity_km_s - composed.velocity_km_s).norm() < 1e-12);
+    assert_eq!(twice.epoch, orbit.epoch);
+    assert_eq!(twice.frame, orbit.frame);
+
+    // Rotations preserve the magnitudes, and the inverse rotation goes back to the original state
+    assert!((twice.rmag_km() - orbit.rmag_km()).abs() < 1e-9);
+    assert!((twice.vmag_km_s() - orbit.vmag_km_s()).abs() < 1e-12);
+    let back = twice.rotate_position_velocity((rot_x * rot_z).transpose());
+    assert!((back.radius_km - orbit.radius_km).norm() < 1e-9);
+    assert!((back.velocity_km_s - orbit.velocity_km_s).norm() < 1e-12);
+
+    // In its own RIC frame, the position is along the radial direction
+    let dcm = orbit.dcm_from_ric_to_inertial().unwrap().rot_mat;
+    let ric = orbit.rotate_position_velocity(dcm.transpose());
+    assert!((ric.radius_km - Vector3::new(orbit.rmag_km(), 0.0, 0.0)).norm() < 1e-9);
+    assert!(ric.velocity_km_s.z.abs() < 1e-12);
+}
+
+#[rstest]
+fn keplerian_mean_anomaly(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+
+    // For a circular orbit, the mean and true anomalies are identical
+    let circ_mean =
+        Orbit::keplerian_mean(7000.0, 0.0, 28.5, 20.0, 30.0, 45.0, epoch, eme2k).unwrap();
+    let circ_true = Orbit::keplerian(7000.0, 0.0, 28.5, 20.0, 30.0, 45.0, epoch, eme2k);
+    assert!((circ_mean.radius_km - circ_true.radius_km).norm() < 1e-9);
+    assert!((circ_mean.velocity_km_s - circ_true.velocity_km_s).norm() < 1e-12);
+
+    // Solving E - 0.5 sin(E) = 60 deg by hand gives E = 88.63981756790234 deg, i.e. a true anomaly of 118.81500092699669 deg.
+    let ecc_mean =
+        Orbit::keplerian_mean(10000.0, 0.5, 51.6, 30.0, 45.0, 60.0, epoch, eme2k).unwrap();
+    let ecc_true = Orbit::keplerian(
+        10000.0,
+        0.5,
+        51.6,
+        30.0,
+        45.0,
+        118.81500092699669,
+        epoch,
+        eme2k,
+    );
+    assert!((ecc_mean.radius_km - ecc_true.radius_km).norm() < 1e-8);
+    assert!((ecc_mean.velocity_km_s - ecc_true.velocity_km_s).norm() < 1e-11);
+    assert!((ecc_mean.ma_deg().unwrap() - 60.0).abs() < 1e-9);
+    assert!((ecc_mean.ea_deg().unwrap() - 88.63981756790234).abs() < 1e-9);
+
+    // Mean anomalies beyond a revolution and highly eccentric orbits
+    for ma_deg in [-170.0, 0.0, 179.0, 359.0, 725.0] {
+        let orbit =
+            Orbit::keplerian_mean(42000.0, 0.95, 10.0, 0.0, 0.0, ma_deg, epoch, eme2k).unwrap();
+        assert!(between_pm_180(orbit.ma_deg().unwrap() - ma_deg).abs() < 1e-8);
+    }
+
+    // Hyperbolic orbits use the hyperbolic anomaly
+    let hyp = Orbit::keplerian_mean(-20000.0, 1.5, 10.0, 0.0, 0.0, 60.0, epoch, eme2k).unwrap();
+    assert!((hyp.ta_deg().unwrap() - 100.05553140485817).abs() < 1e-9);
+
+    // Parabolic orbits are not supported
+    assert!(Orbit::keplerian_mean(7000.0, 1.0, 10.0, 0.0, 0.0, 60.0, epoch, eme2k).is_err());
+}
+
+#[rstest]
+fn local_frame_conversions(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+    let orbit = Orbit::keplerian(8000.0, 0.1, 51.6, 30.0, 45.0, 60.0, epoch, eme2k);
+
+    // Inertial is the identity
+    let inertial = LocalFrame::Inertial.to_local(orbit).unwrap();
+    assert!((inertial.radius_km - orbit.radius_km).norm() < 1e-12);
+    assert!((inertial.velocity_km_s - orbit.velocity_km_s).norm() < 1e-12);
+
+    // RIC matches the rotation with the RIC DCM of the orbit
+    let ric = LocalFrame::RIC.to_local(orbit).unwrap();
+    let direct = (orbit.dcm_from_ric_to_inertial().unwrap().transpose() * orbit).unwrap();
+    assert!((ric.radius_km - direct.radius_km).norm() < 1e-12);
+    assert!((ric.velocity_km_s - direct.velocity_km_s).norm() < 1e-12);
+    assert!((ric.radius_km - Vector3::new(orbit.rmag_km(), 0.0, 0.0)).norm() < 1e-9);
+
+    // In VNC, the position has no component along the orbit normal, and in RCN it lies along the radial direction.
+    let vnc = LocalFrame::VNC.to_local(orbit).unwrap();
+    assert!(vnc.radius_km.y.abs() < 1e-9);
+    assert!((vnc.rmag_km() - orbit.rmag_km()).abs() < 1e-9);
+    let rcn = LocalFrame::RCN.to_local(orbit).unwrap();
+    assert!((rcn.radius_km - Vector3::new(orbit.rmag_km(), 0.0, 0.0)).norm() < 1e-9);
+}
+
+#[rstest]
+fn position_velocity_vector(almanac: Almanac) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+    let orbit = Orbit::keplerian(8000.0, 0.2, 28.5, 10.0, 20.0, 45.0, epoch, eme2k);
+
+    let pos_vel = orbit.position_velocity_vector();
+    for i in 0..3 {
+        assert_eq!(pos_vel[i], orbit.radius_km[i]);
+        assert_eq!(pos_vel[i + 3], orbit.velocity_km_s[i]);
+    }
+
+    let rebuilt = Orbit::from_position_velocity(pos_vel, epoch, eme2k);
+    assert_eq!(rebuilt, orbit);
+    assert_eq!(rebuilt.position_velocity_vector(), pos_vel);
+}
+
+#[rstest]
+fn orbit_approx_eq(almanac: Almanac) {
+    use anise::constants::celestial_objects::EARTH;
+    use anise::constants::frames::MOON_J2000;
+    use anise::constants::orientations::ECLIPJ2000;
+    use anise::prelude::Frame;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+
+    let orbit = Orbit::keplerian(7_000.0, 0.01, 51.6, 30.0, 45.0, 10.0, epoch, eme2k);
+    assert!(orbit.approx_eq(&orbit, 0.0, 0.0));
+
+    // One meter away along the Y axis
+    let mut moved = orbit;
+    moved.radius_km.y += 1e-3;
+    assert!(orbit.approx_eq(&moved, 1.1e-3, 1e-9));
+    assert!(!orbit.approx_eq(&moved, 0.9e-3, 1e-9));
+    assert!(moved.approx_eq(&orbit, 1.1e-3, 1e-9));
+
+    // The velocity tolerance applies independently of the position tolerance
+    let mut faster = orbit;
+    faster.velocity_km_s.z -= 1e-6;
+    assert!(orbit.approx_eq(&faster, 1e-9, 1.1e-6));
+    assert!(!orbit.approx_eq(&faster, 1.0, 0.9e-6));
+
+    // Same components in another frame, with a different center or a different orientation, are not equal
+    let moon = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let mut lunar = orbit;
+    lunar.frame = moon;
+    assert!(!orbit.approx_eq(&lunar, 1.0, 1.0));
+
+    let mut ecliptic = orbit;
+    ecliptic.frame = Frame::new(EARTH, ECLIPJ2000);
+    assert!(!orbit.approx_eq(&ecliptic, 1.0, 1.0));
+
+    // But the frame without its gravitational parameter is the same frame
+    let mut no_gm = orbit;
+    no_gm.frame = EARTH_J2000;
+    assert!(orbit.approx_eq(&no_gm, 0.0, 0.0));
+}
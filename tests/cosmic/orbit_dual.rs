@@ -53,6 +53,10 @@ fn orbit_dual_test(almanac: Almanac) {
         StateParameter::EccentricAnomaly,
         StateParameter::Eccentricity,
         StateParameter::Energy,
+        StateParameter::EquinoctialH,
+        StateParameter::EquinoctialK,
+        StateParameter::EquinoctialP,
+        StateParameter::EquinoctialQ,
         StateParameter::FlightPathAngle,
         StateParameter::Height,
         StateParameter::Latitude,
@@ -63,6 +67,7 @@ fn orbit_dual_test(almanac: Almanac) {
         StateParameter::HZ,
         StateParameter::Inclination,
         StateParameter::MeanAnomaly,
+        StateParameter::MeanLongitude,
         StateParameter::Periapsis,
         StateParameter::RightAscension,
         StateParameter::RAAN,
@@ -129,3 +134,181 @@ fn orbit_dual_test(almanac: Almanac) {
         );
     }
 }
+
+#[rstest]
+fn orbit_dual_check_partials(almanac: Almanac) {
+    use nyx::cosmic::check_partials;
+    use nyx::md::StateParameter;
+
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(GMAT_EARTH_GM);
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2021, 3, 4);
+    let orbit = Orbit::keplerian(12_000.0, 0.25, 35.0, 48.0, 112.0, 63.0, dt, eme2k);
+
+    let params = [
+        StateParameter::AoL,
+        StateParameter::AoP,
+        StateParameter::Apoapsis,
+        StateParameter::ApoapsisAltitude,
+        StateParameter::ApoapsisRadius,
+        StateParameter::C3,
+        StateParameter::Declination,
+        StateParameter::EccentricAnomaly,
+        StateParameter::Eccentricity,
+        StateParameter::Energy,
+        StateParameter::EquinoctialH,
+        StateParameter::EquinoctialK,
+        StateParameter::EquinoctialP,
+        StateParameter::EquinoctialQ,
+        StateParameter::FlightPathAngle,
+        StateParameter::Height,
+        StateParameter::Latitude,
+        StateParameter::Longitude,
+        StateParameter::Hmag,
+        StateParameter::HX,
+        StateParameter::HY,
+        StateParameter::HZ,
+        StateParameter::Inclination,
+        StateParameter::MeanAnomaly,
+        StateParameter::MeanLongitude,
+        StateParameter::Periapsis,
+        StateParameter::PeriapsisAltitude,
+        StateParameter::PeriapsisRadius,
+        StateParameter::RightAscension,
+        StateParameter::RAAN,
+        StateParameter::RangeRate,
+        StateParameter::Rmag,
+        StateParameter::SemiMinorAxis,
+        StateParameter::SemiParameter,
+        StateParameter::SMA,
+        StateParameter::TrueAnomaly,
+        StateParameter::TrueLongitude,
+        StateParameter::Vmag,
+        StateParameter::X,
+        StateParameter::Y,
+        StateParameter::Z,
+        StateParameter::VX,
+        StateParameter::VY,
+        StateParameter::VZ,
+    ];
+
+    for param in params {
+        let err = check_partials(orbit, param, 1e-5).unwrap();
+        println!("{param:?}\t{err:.3e}");
+        assert!(err < 1e-5, "{param:?} partials error of {err:.3e}");
+    }
+
+    // Hyperbolic anomaly is not defined for an elliptical orbit
+    assert!(check_partials(orbit, StateParameter::HyperbolicAnomaly, 1e-5).is_err());
+}
+
+#[rstest]
+fn orbit_dual_radius_rate(almanac: Almanac) {
+    use nyx::cosmic::{check_partials, OrbitDual, OrbitExt};
+    use nyx::md::StateParameter;
+    use nyx::time::Unit;
+
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(GMAT_EARTH_GM);
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2021, 3, 4);
+
+    // The radius rate is zero at the apsides, positive from periapsis to apoapsis and negative afterwards.
+    for (ta_deg, sign) in [(0.0, 0.0), (63.0, 1.0), (180.0, 0.0), (250.0, -1.0)] {
+        let orbit = Orbit::keplerian(12_000.0, 0.25, 35.0, 48.0, 112.0, ta_deg, dt, eme2k);
+        let rdot_km_s = orbit.radius_rate_km_s();
+        let dual = OrbitDual::from(orbit)
+            .partial_for(StateParameter::RangeRate)
+            .unwrap();
+        assert_eq!(dual.real(), rdot_km_s);
+
+        if sign == 0.0 {
+            assert!(rdot_km_s.abs() < 1e-9, "{rdot_km_s} km/s at TA = {ta_deg}");
+        } else {
+            assert_eq!(rdot_km_s.signum(), sign);
+        }
+
+        // It is the time derivative of the radius magnitude
+        let step = 1 * Unit::Second;
+        let fwd = orbit.at_epoch(dt + step).unwrap().rmag_km();
+        let bwd = orbit.at_epoch(dt - step).unwrap().rmag_km();
+        let numerical = (fwd - bwd) / (2.0 * step.to_seconds());
+        assert!((numerical - rdot_km_s).abs() < 1e-6);
+
+        // And its partials match the finite differences on this eccentric orbit
+        let err = check_partials(orbit, StateParameter::RangeRate, 1e-5).unwrap();
+        assert!(err < 1e-6, "range rate partials error of {err:.3e}");
+    }
+}
+
+#[rstest]
+fn orbit_dual_retrograde_equatorial(almanac: Almanac) {
+    use nyx::cosmic::{check_partials, OrbitDual};
+    use nyx::md::StateParameter;
+
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(GMAT_EARTH_GM);
+    let dt = Epoch::from_gregorian_tai_at_midnight(2021, 3, 4);
+
+    // Exactly retrograde equatorial, at periapsis 30 degrees from the X axis and moving clockwise
+    let (sin_30, cos_30) = 30.0_f64.to_radians().sin_cos();
+    let equatorial = Orbit::cartesian(
+        7000.0 * cos_30,
+        7000.0 * sin_30,
+        0.0,
+        7.6 * sin_30,
+        -7.6 * cos_30,
+        0.0,
+        dt,
+        eme2k,
+    );
+    let dual = OrbitDual::from(equatorial);
+    let inc = dual.inc_deg();
+    assert_eq!(inc.real(), 180.0);
+    for partial in [
+        inc.wtr_x(),
+        inc.wtr_y(),
+        inc.wtr_z(),
+        inc.wtr_vx(),
+        inc.wtr_vy(),
+        inc.wtr_vz(),
+    ] {
+        assert!(partial.is_finite());
+    }
+    let err = check_partials(equatorial, StateParameter::Inclination, 1e-5).unwrap();
+    assert!(err < 1e-5, "inclination partials error of {err:.3e}");
+
+    // The RAAN is zero and the AoP is measured clockwise from the X axis
+    let fallback = dual.with_equinoctial_fallback();
+    assert_eq!(fallback.raan_deg().real(), 0.0);
+    let aop = fallback.aop_deg().unwrap();
+    assert!((aop.real() - 330.0).abs() < 1e-6, "AoP = {}", aop.real());
+    assert!(aop.wtr_x().is_finite() && aop.wtr_vy().is_finite());
+
+    // Nearly retrograde equatorial orbits, where acos(hz / hmag) loses precision
+    for inc_deg in [175.0, 179.9] {
+        let orbit = Orbit::keplerian(7000.0, 0.01, inc_deg, 48.0, 112.0, 63.0, dt, eme2k);
+        let inc = OrbitDual::from(orbit).inc_deg();
+        assert!(
+            (inc.real() - inc_deg).abs() < 1e-9,
+            "{} != {inc_deg}",
+            inc.real()
+        );
+        for param in [
+            StateParameter::Inclination,
+            StateParameter::RAAN,
+            StateParameter::AoP,
+        ] {
+            let err = check_partials(orbit, param, 1e-5).unwrap();
+            println!("inc = {inc_deg} deg\t{param:?}\t{err:.3e}");
+            assert!(err < 1e-5, "{param:?} partials error of {err:.3e}");
+        }
+    }
+}
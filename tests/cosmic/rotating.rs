@@ -0,0 +1,81 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::{EARTH_J2000, SUN_J2000};
+use anise::prelude::Almanac;
+use nyx::cosmic::rotating::{RotatingFrame, RotatingOrigin};
+use nyx::cosmic::Orbit;
+use nyx::dynamics::{Cr3bp, LibrationPoint};
+use nyx::time::Epoch;
+
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Almanac {
+    use crate::test_almanac;
+    test_almanac()
+}
+
+#[rstest]
+fn sun_earth_l2_rotating_frame(almanac: Almanac) {
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 6, 1);
+    let sun_j2k = almanac.frame_from_uid(SUN_J2000).unwrap();
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let sel2 = RotatingFrame::sun_earth_l2();
+    println!("{sel2}");
+
+    // L2 from the quintic solution, co-rotating and pulsating with the Earth
+    let earth = almanac
+        .transform(EARTH_J2000, SUN_J2000, epoch, None)
+        .unwrap();
+    let system = Cr3bp::new(
+        sun_j2k.mu_km3_s2().unwrap(),
+        eme2k.mu_km3_s2().unwrap(),
+        earth.radius_km.norm(),
+    );
+    let scale = system.lagrange_point(LibrationPoint::L2).x + system.mu;
+    let l2_distance_km = (scale - 1.0) * earth.radius_km.norm();
+    assert!(
+        (1.4e6..1.6e6).contains(&l2_distance_km),
+        "L2 is {l2_distance_km} km from the Earth"
+    );
+
+    let radius_km = scale * earth.radius_km;
+    let velocity_km_s = scale * earth.velocity_km_s;
+    let at_l2 = Orbit::new(
+        radius_km.x,
+        radius_km.y,
+        radius_km.z,
+        velocity_km_s.x,
+        velocity_km_s.y,
+        velocity_km_s.z,
+        epoch,
+        sun_j2k,
+    );
+
+    let state = sel2.to_rotating(at_l2, &almanac).unwrap();
+    println!("spacecraft at L2 in SEL2: {state}");
+    assert!(state.fixed_rows::<3>(0).norm() < 1e-3);
+    assert!(state.fixed_rows::<3>(3).norm() < 1e-9);
+
+    // The Earth lies on the negative X axis of the SEL2 frame
+    let earth_sel2 = sel2.to_rotating(earth, &almanac).unwrap();
+    assert!((earth_sel2[0] + l2_distance_km).abs() < 1e-3);
+    assert!(earth_sel2[1].abs() < 1e-3 && earth_sel2[2].abs() < 1e-3);
+
+    // Round trip of any orbit, here in EME2000
+    let leo = Orbit::keplerian(7000.0, 0.01, 51.6, 20.0, 30.0, 40.0, epoch, eme2k);
+    let rotating = sel2.to_rotating(leo, &almanac).unwrap();
+    let back = sel2
+        .from_rotating(&rotating, epoch, eme2k, &almanac)
+        .unwrap();
+    assert!((back.radius_km - leo.radius_km).norm() < 1e-6);
+    assert!((back.velocity_km_s - leo.velocity_km_s).norm() < 1e-9);
+
+    // The barycentric frame is offset from SEL2 by the position of L2 along the X axis
+    let barycentric = RotatingFrame::new(SUN_J2000, EARTH_J2000, RotatingOrigin::Barycenter);
+    let leo_bary = barycentric.to_rotating(leo, &almanac).unwrap();
+    let offset_km = (scale - system.mu) * earth.radius_km.norm();
+    assert!((leo_bary[0] - rotating[0] - offset_km).abs() < 1e-3);
+    assert!((leo_bary[1] - rotating[1]).abs() < 1e-6);
+}
@@ -0,0 +1,76 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::{EARTH_J2000, MARS_BARYCENTER_J2000};
+use anise::prelude::Almanac;
+use nyx::cosmic::synodic::{solar_alignments, synodic_period, SolarAlignmentKind};
+use nyx::time::{Epoch, Unit};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn earth_mars_synodic_period(almanac: Arc<Almanac>) {
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2021, 1, 1);
+    let period = synodic_period(EARTH_J2000, MARS_BARYCENTER_J2000, epoch, almanac).unwrap();
+    println!("Earth-Mars synodic period: {period}");
+    // The mean synodic period is 779.94 days, but the osculating periods vary with the eccentricity of Mars and the Moon's pull on the Earth.
+    assert!((period.to_unit(Unit::Day) - 779.94).abs() < 10.0);
+}
+
+#[rstest]
+fn mars_solar_conjunctions(almanac: Arc<Almanac>) {
+    let start = Epoch::from_gregorian_utc_at_midnight(2019, 1, 1);
+    let end = Epoch::from_gregorian_utc_at_midnight(2024, 6, 1);
+
+    let alignments = solar_alignments(
+        EARTH_J2000,
+        MARS_BARYCENTER_J2000,
+        start,
+        end,
+        Unit::Day * 1,
+        5.0,
+        almanac,
+    )
+    .unwrap();
+
+    for alignment in &alignments {
+        println!("{alignment}");
+    }
+
+    let conjunctions = alignments
+        .iter()
+        .filter(|alignment| alignment.kind == SolarAlignmentKind::Conjunction)
+        .collect::<Vec<_>>();
+    let oppositions = alignments
+        .iter()
+        .filter(|alignment| alignment.kind == SolarAlignmentKind::Opposition)
+        .collect::<Vec<_>>();
+
+    // Published dates of the Mars solar conjunctions
+    let expected = [
+        Epoch::from_gregorian_utc_at_noon(2019, 9, 2),
+        Epoch::from_gregorian_utc_at_noon(2021, 10, 8),
+        Epoch::from_gregorian_utc_at_noon(2023, 11, 18),
+    ];
+    assert_eq!(conjunctions.len(), expected.len());
+    for (conjunction, expected) in conjunctions.iter().zip(expected) {
+        assert!(
+            (conjunction.epoch - expected).abs() < Unit::Day * 1,
+            "{conjunction} is not within a day of {expected}"
+        );
+        assert!(conjunction.angle_deg < 5.0);
+    }
+
+    // Oppositions of 2020 and 2022, between the conjunctions
+    assert_eq!(oppositions.len(), 2);
+    for (ii, opposition) in oppositions.iter().enumerate() {
+        assert!(opposition.epoch > conjunctions[ii].epoch);
+        assert!(opposition.epoch < conjunctions[ii + 1].epoch);
+        assert!(opposition.angle_deg > 175.0);
+    }
+}
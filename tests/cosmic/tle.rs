@@ -0,0 +1,88 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Orbit, OrbitExt};
+use nyx::io::tle::{teme_to_eme2000, Tle};
+use nyx::linalg::Vector3;
+use nyx::time::{Epoch, Unit};
+
+use anise::prelude::Almanac;
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Almanac {
+    use crate::test_almanac;
+    test_almanac()
+}
+
+// Vanguard 1, first test case of Vallado's "Revisiting Spacetrack Report #3" verification set
+const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+#[test]
+fn tle_sgp4_vallado() {
+    let tle = Tle::from_lines(LINE1, LINE2).unwrap();
+    println!("{tle}");
+
+    assert_eq!(tle.norad_id, 5);
+    let expected_epoch =
+        Epoch::from_gregorian_utc_at_midnight(2000, 1, 1) + 178.78495062 * Unit::Day;
+    assert!((tle.epoch - expected_epoch).abs() < 1 * Unit::Millisecond);
+
+    // Reference TEME states of the verification output (tcppver.out)
+    for (minutes, r_km, v_km_s) in [
+        (
+            0.0,
+            Vector3::new(7022.46529266, -1400.08296755, 0.03995155),
+            Vector3::new(1.893841015, 6.405893759, 4.534807250),
+        ),
+        (
+            360.0,
+            Vector3::new(-7154.03120202, -3783.17682504, -3536.19412294),
+            Vector3::new(4.741887409, -4.151817765, -2.093935425),
+        ),
+    ] {
+        let (radius_km, velocity_km_s) = tle.teme_at(tle.epoch + minutes * Unit::Minute).unwrap();
+        println!("{minutes} min: {radius_km} {velocity_km_s}");
+        assert!(
+            (radius_km - r_km).norm() < 1e-3,
+            "position error at {minutes} min"
+        );
+        assert!(
+            (velocity_km_s - v_km_s).norm() < 1e-6,
+            "velocity error at {minutes} min"
+        );
+    }
+
+    // Invalid checksum of the first line
+    assert!(Tle::from_lines(&LINE1.replace("4753", "4754"), LINE2).is_err());
+}
+
+#[test]
+fn teme_to_eme2000_vallado() {
+    // Example of Vallado's "Revisiting Spacetrack Report #3", section on the TEME frame
+    let epoch = Epoch::from_gregorian_utc(2004, 4, 6, 7, 51, 28, 386_009_000);
+    let r_teme_km = Vector3::new(5094.18016210, 6127.64465950, 6380.34453270);
+    let r_j2000_km = Vector3::new(5102.5096, 6123.01152, 6378.1363);
+
+    let r_km = teme_to_eme2000(epoch) * r_teme_km;
+    println!("{r_km}");
+    // The reference ignores the small corrections to the nutation, and the series is truncated here.
+    assert!((r_km - r_j2000_km).norm() < 2e-3);
+}
+
+#[rstest]
+fn orbit_from_tle(almanac: Almanac) {
+    let orbit = Orbit::from_tle(LINE1, LINE2, &almanac).unwrap();
+    println!("{orbit:x}");
+
+    let tle = Tle::from_lines(LINE1, LINE2).unwrap();
+    assert_eq!(orbit.epoch, tle.epoch);
+
+    // The rotation into EME2000 preserves the norms of the TEME state
+    let (radius_km, velocity_km_s) = tle.teme_at(tle.epoch).unwrap();
+    assert!((orbit.rmag_km() - radius_km.norm()).abs() < 1e-9);
+    assert!((orbit.vmag_km_s() - velocity_km_s.norm()).abs() < 1e-12);
+    // and mostly preserves the osculating elements, except for the slow precession of the equinox since 2000.
+    assert!((orbit.ecc().unwrap() - 0.1859667).abs() < 1e-2);
+    assert!((orbit.inc_deg().unwrap() - 34.2682).abs() < 0.1);
+}
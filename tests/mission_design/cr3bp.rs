@@ -0,0 +1,134 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::celestial_objects::{MOON, SUN};
+use anise::constants::frames::MOON_J2000;
+use anise::prelude::Almanac;
+use nyx::dynamics::{Cr3bp, LibrationPoint, OrbitalDynamics, SpacecraftDynamics};
+use nyx::md::prelude::*;
+use std::f64::consts::PI;
+
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn cr3bp_earth_moon_lagrange_points(almanac: Arc<Almanac>) {
+    let system = Cr3bp::earth_moon(&almanac).unwrap();
+    println!("{system}");
+    assert!((system.mu - 0.012150585609624).abs() < 1e-12);
+
+    let points = system.lagrange_points();
+    // Published Earth-Moon values for μ = 0.01215058
+    assert!((points[0].x - 0.836915).abs() < 1e-6, "L1 {}", points[0].x);
+    assert!((points[1].x - 1.155682).abs() < 1e-6, "L2 {}", points[1].x);
+    assert!((points[2].x + 1.005063).abs() < 1e-6, "L3 {}", points[2].x);
+    assert!((points[3].y - 0.75_f64.sqrt()).abs() < f64::EPSILON);
+    assert!((points[4].y + 0.75_f64.sqrt()).abs() < f64::EPSILON);
+}
+
+#[rstest]
+fn cr3bp_earth_moon_periodic_orbits(almanac: Arc<Almanac>) {
+    let system = Cr3bp::earth_moon(&almanac).unwrap();
+
+    // A small Lyapunov orbit has the period of the linearized in-plane motion about L1.
+    let lyapunov = system
+        .planar_lyapunov(LibrationPoint::L1, 40.0, almanac.clone())
+        .unwrap();
+    println!("{lyapunov}");
+    assert!((lyapunov.period - 2.6916).abs() < 1e-4);
+
+    let x_l1 = system.lagrange_point(LibrationPoint::L1).x;
+    let c2 = system.mu / (x_l1 - 1.0 + system.mu).abs().powi(3)
+        + (1.0 - system.mu) / (x_l1 + system.mu).abs().powi(3);
+    let lambda = ((2.0 - c2 + (9.0 * c2 * c2 - 8.0 * c2).sqrt()) / 2.0).sqrt();
+    assert!((lyapunov.period - 2.0 * PI / lambda).abs() < 1e-4);
+
+    // Larger Lyapunov orbits are reached by continuation.
+    let large_lyapunov = system
+        .planar_lyapunov(LibrationPoint::L1, 0.02 * system.lstar_km, almanac.clone())
+        .unwrap();
+    println!("{large_lyapunov}");
+    assert!((large_lyapunov.period - 2.8272).abs() < 1e-3);
+
+    // Halo orbits of 10,000 km out-of-plane amplitude
+    let l1_halo = system
+        .halo(LibrationPoint::L1, 10_000.0, true, almanac.clone())
+        .unwrap();
+    println!("{l1_halo}");
+    assert!((l1_halo.state[0] - 0.823410).abs() < 1e-5);
+    assert!((l1_halo.state[2] - 0.027914).abs() < 1e-5);
+    assert!((l1_halo.period - 2.74818).abs() < 1e-4);
+    assert!(l1_halo.stability_index(almanac.clone()).unwrap() > 1.0);
+
+    let l2_halo = system
+        .halo(LibrationPoint::L2, 10_000.0, true, almanac.clone())
+        .unwrap();
+    println!("{l2_halo}");
+    assert!((l2_halo.state[0] - 1.116626).abs() < 1e-5);
+    assert!((l2_halo.period - 3.40724).abs() < 1e-4);
+
+    // The Jacobi constant is conserved along the orbit.
+    let final_sc = Propagator::rk89(
+        system.dynamics(),
+        IntegratorOptions::with_max_step(0.05 * Unit::Second),
+    )
+    .with(
+        system.spacecraft(&l1_halo.state, Epoch::from_tdb_seconds(0.0)),
+        almanac.clone(),
+    )
+    .for_duration(l1_halo.period * Unit::Second)
+    .unwrap();
+    let final_state = final_sc.orbit.to_cartesian_pos_vel();
+    assert!((system.jacobi_constant(&final_state) - l1_halo.jacobi_constant()).abs() < 1e-9);
+    assert!((final_state - l1_halo.state).norm() < 1e-5);
+
+    assert!(system
+        .halo(LibrationPoint::L3, 10_000.0, true, almanac)
+        .is_err());
+}
+
+#[rstest]
+fn cr3bp_halo_to_ephemeris(almanac: Arc<Almanac>) {
+    let system = Cr3bp::earth_moon(&almanac).unwrap();
+    let halo = system
+        .halo(LibrationPoint::L1, 10_000.0, true, almanac.clone())
+        .unwrap();
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 3, 1);
+    let nodes = halo.to_ephemeris(epoch, 8, almanac.clone()).unwrap();
+    assert_eq!(nodes.len(), 9);
+
+    // The first node is close to the direct conversion of the CR3BP state.
+    let direct = system
+        .to_ephemeris(&halo.state, epoch, almanac.clone())
+        .unwrap();
+    println!("direct: {direct}\ncorrected: {}", nodes[0]);
+
+    // Propagate the first node for a full revolution in the point mass model.
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::point_masses(vec![MOON, SUN]));
+    let duration = nodes[8].epoch - nodes[0].epoch;
+    let (final_sc, traj) = Propagator::default(dynamics)
+        .with(nodes[0].into(), almanac.clone())
+        .for_duration_with_traj(duration)
+        .unwrap();
+
+    // The corrected trajectory is continuous, so it reaches the last node.
+    let final_err_km = (final_sc.orbit.radius_km - nodes[8].radius_km).norm();
+    println!("final error: {final_err_km:.3} km");
+    assert!(final_err_km < 10.0);
+
+    // And stays in the L1 region for the whole revolution: the L1 point is about 58,000 km from the Moon.
+    for state in traj.every(6 * Unit::Hour) {
+        let wrt_moon = almanac.transform_to(state.orbit, MOON_J2000, None).unwrap();
+        assert!(
+            (20_000.0..120_000.0).contains(&wrt_moon.rmag_km()),
+            "{} km from the Moon at {}",
+            wrt_moon.rmag_km(),
+            state.epoch()
+        );
+    }
+}
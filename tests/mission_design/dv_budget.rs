@@ -0,0 +1,161 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::EARTH_J2000;
+use anise::prelude::Almanac;
+use nyx::cosmic::{GuidanceMode, Orbit, Spacecraft, STD_GRAVITY};
+use nyx::dynamics::guidance::Thruster;
+use nyx::linalg::{Vector1, Vector3};
+use nyx::md::objective::Objective;
+use nyx::md::opti::solution::TargeterSolution;
+use nyx::md::prelude::*;
+use nyx::md::{DvBudget, Vary};
+use nyx::time::TimeScale;
+use std::path::PathBuf;
+
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Coasting one day mass timeline of a spacecraft with 60 kg of fuel
+fn coast_timeline(almanac: Arc<Almanac>) -> Traj<Spacecraft> {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, start_time, eme2k);
+    let thruster = Thruster {
+        thrust_N: 10.0,
+        isp_s: 300.0,
+    };
+    let sc = Spacecraft::from_thruster(orbit, 500.0, 60.0, thruster, GuidanceMode::Coast);
+
+    let mut traj = Traj::new();
+    for minutes in (0..=24 * 60).step_by(10) {
+        let epoch = start_time + (minutes as f64) * Unit::Minute;
+        traj.states
+            .push(sc.with_orbit(orbit.at_epoch(epoch).unwrap()));
+    }
+    traj.finalize();
+    traj
+}
+
+#[rstest]
+fn dv_budget_three_maneuvers(almanac: Arc<Almanac>) {
+    let timeline = coast_timeline(almanac);
+    let start_time = timeline.first().epoch();
+    let ve_km_s = 300.0 * STD_GRAVITY * 1e-3;
+
+    // The first maneuver is an impulsive targeter solution of 50 m/s.
+    let corrected_state = timeline.at(start_time + 2 * Unit::Hour).unwrap();
+    let solution = TargeterSolution::<3, 1> {
+        corrected_state,
+        achieved_state: corrected_state,
+        correction: Vector3::new(0.03, 0.0, -0.04),
+        variables: [
+            Vary::VelocityX.into(),
+            Vary::VelocityY.into(),
+            Vary::VelocityZ.into(),
+        ],
+        achieved_errors: Vector1::zeros(),
+        achieved_values: Vector1::new(7100.0),
+        achieved_objectives: [Objective::new(StateParameter::SMA, 7100.0)],
+        iterations: 3,
+        computation_dur: std::time::Duration::from_millis(10),
+    };
+
+    let mut budget = DvBudget::new()
+        .with_margin("Orbit raising", 5.0)
+        .with_margin("Station keeping", 10.0);
+
+    budget
+        .add_targeter_solution(&solution, "Orbit raising", "Apogee raise")
+        .unwrap();
+    // Added out of order on purpose: the report is chronological.
+    budget.add(
+        start_time + 20 * Unit::Hour,
+        "Disposal",
+        "Deorbit burn",
+        0.3,
+    );
+    budget.add(
+        start_time + 8 * Unit::Hour,
+        "Station keeping",
+        "Drag make up",
+        0.02,
+    );
+
+    let report = budget.report(&timeline).unwrap();
+    println!("{report}");
+
+    assert_eq!(report.rows.len(), 3);
+    assert!((report.rows[0].maneuver.dv_km_s - 0.05).abs() < 1e-12);
+    assert_eq!(report.rows[1].maneuver.category, "Station keeping");
+    assert_eq!(report.rows[2].maneuver.purpose, "Deorbit burn");
+
+    // Check the fuel of each maneuver with the rocket equation, the mass decreasing with the fuel of the budget
+    // since the timeline is coasting only.
+    let mut mass_kg = 560.0;
+    let mut cumulative_fuel_kg = 0.0;
+    for (row, (dv_km_s, margin_prct)) in
+        report
+            .rows
+            .iter()
+            .zip([(0.05, 5.0), (0.02, 10.0), (0.3, 0.0)])
+    {
+        let fuel_kg = mass_kg * (1.0 - (-dv_km_s * (1.0 + margin_prct / 100.0) / ve_km_s).exp());
+        cumulative_fuel_kg += fuel_kg;
+        assert_eq!(row.margin_prct, margin_prct);
+        assert!((row.mass_kg - mass_kg).abs() < 1e-9);
+        assert!((row.fuel_kg - fuel_kg).abs() < 1e-9);
+        assert!((row.cumulative_fuel_kg - cumulative_fuel_kg).abs() < 1e-9);
+        mass_kg -= fuel_kg;
+    }
+
+    // The deorbit burn needs more fuel than loaded.
+    assert!(report.remaining_fuel_kg() < 0.0);
+    assert_eq!(report.exceeded_at, Some(start_time + 20 * Unit::Hour));
+
+    let subtotals = report.subtotals();
+    assert_eq!(subtotals.len(), 3);
+    assert_eq!(subtotals["Station keeping"].count, 1);
+    assert!((subtotals["Orbit raising"].dv_with_margin_km_s - 0.0525).abs() < 1e-12);
+    assert!((report.total().dv_km_s - 0.37).abs() < 1e-12);
+    assert!((report.total().fuel_kg - cumulative_fuel_kg).abs() < 1e-9);
+
+    // A smaller deorbit burn fits in the loaded fuel.
+    budget.maneuvers[1].dv_km_s = 0.15;
+    let report = budget.report(&timeline).unwrap();
+    assert!(report.exceeded_at.is_none());
+    assert!(report.remaining_fuel_kg() > 0.0);
+
+    let csv_path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "dv_budget.csv"]
+        .iter()
+        .collect();
+    report.to_csv(&csv_path).unwrap();
+    let csv = std::fs::read_to_string(csv_path).unwrap();
+    // Header, three maneuvers, three subtotals and the total
+    assert_eq!(csv.lines().count(), 8);
+    assert!(csv.lines().last().unwrap().contains("Total"));
+    // The epochs are exported in UTC, as stated in the header
+    assert!(csv.lines().next().unwrap().starts_with("Epoch (UTC),"));
+    assert!(csv.lines().nth(1).unwrap().starts_with(&format!(
+        "{},",
+        report.rows[0]
+            .maneuver
+            .epoch
+            .to_time_scale(TimeScale::UTC)
+            .to_isoformat()
+    )));
+
+    let pq_path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "dv_budget.parquet",
+    ]
+    .iter()
+    .collect();
+    report.to_parquet(pq_path, ExportCfg::default()).unwrap();
+}
@@ -1,12 +1,21 @@
 extern crate nyx_space as nyx;
 
-use anise::constants::frames::MOON_J2000;
-use nyx::cosmic::{Orbit, Spacecraft};
-use nyx::dynamics::{Drag, OrbitalDynamics, SolarPressure, SpacecraftDynamics};
-use nyx::linalg::Vector6;
+use anise::constants::celestial_objects::{
+    EARTH, JUPITER_BARYCENTER, MARS_BARYCENTER, MERCURY, MOON, NEPTUNE_BARYCENTER,
+    SATURN_BARYCENTER, SUN, URANUS_BARYCENTER, VENUS,
+};
+use anise::constants::frames::{IAU_EARTH_FRAME, IAU_MOON_FRAME, MOON_J2000, SUN_J2000};
+use nyx::cosmic::{Orbit, Spacecraft, AU};
+use nyx::dynamics::{
+    moon_pa_frame, AtmDensity, Drag, DynamicsPreset, ForceModel, Harmonics, OrbitalDynamics,
+    PointMasses, PresetOptions, SolarPressure, SpacecraftDynamics,
+};
+use nyx::io::gravity::HarmonicsMem;
+use nyx::linalg::{Vector3, Vector6};
 use nyx::propagators::Propagator;
-use nyx::time::{Epoch, Unit};
+use nyx::time::{Duration, Epoch, Unit};
 use nyx::utils::rss_orbit_vec_errors;
+use nyx::State;
 
 use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
 use nyx_space::md::prelude::Interpolatable;
@@ -125,6 +134,59 @@ fn srp_earth_leo(almanac: Arc<Almanac>) {
     assert!(err_v < 8e-6, "velocity error too large for SRP");
 }
 
+#[rstest]
+fn srp_earth_leo_umbra(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(GMAT_EARTH_GM);
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+
+    let orbit = Orbit::keplerian(7_000.0, 0.0, 0.0, 0.0, 0.0, 0.0, dt, eme2k);
+
+    let srp = SolarPressure::default(eme2k, almanac.clone()).unwrap();
+
+    let sc_dyn = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), srp.clone());
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 16.0);
+
+    let setup = Propagator::default(sc_dyn);
+    let (_, traj) = setup
+        .with(sc, almanac.clone())
+        .for_duration_with_traj(orbit.period().unwrap())
+        .unwrap();
+
+    // The SRP acceleration must be zero in the umbra and non-zero in full sunlight.
+    let mut umbra_cnt = 0;
+    let mut sunlit_cnt = 0;
+    for state in traj.every(30 * Unit::Second) {
+        let occultation = srp.e_loc.compute(state.orbit, almanac.clone()).unwrap();
+        let srp_acc = srp.eom(&state, almanac.clone()).unwrap() / state.mass_kg();
+        if occultation.percentage >= 100.0 {
+            umbra_cnt += 1;
+            assert_eq!(
+                srp_acc.norm(),
+                0.0,
+                "SRP not zero in umbra on {}",
+                state.epoch()
+            );
+        } else if occultation.percentage <= 0.0 {
+            sunlit_cnt += 1;
+            assert!(
+                srp_acc.norm() > 0.0,
+                "SRP zero in sunlight on {}",
+                state.epoch()
+            );
+        }
+    }
+
+    println!("{umbra_cnt} samples in umbra, {sunlit_cnt} in sunlight");
+    assert!(umbra_cnt > 0, "LEO never in umbra");
+    assert!(sunlit_cnt > 0, "LEO never in sunlight");
+}
+
 #[rstest]
 fn srp_earth_meo_ecc_inc(almanac: Arc<Almanac>) {
     use std::env::var as envvar;
@@ -288,6 +350,170 @@ fn exp_drag_earth(almanac: Arc<Almanac>) {
     println!("{}", final_state.orbit);
 }
 
+#[rstest]
+fn drag_and_srp_accel(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+    let orbit = Orbit::keplerian(6800.0, 1e-3, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+
+    // Compute the drag in the integration frame so that it exactly opposes the inertial velocity
+    let drag = Drag {
+        density: AtmDensity::Constant(1e-12),
+        drag_frame: eme2k,
+        estimate: false,
+        plates: None,
+    };
+    let srp = SolarPressure::default(eme2k, almanac.clone()).unwrap();
+
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 2.0).with_drag(3.0, 2.2);
+
+    let (drag_accel, srp_accel) = sc
+        .drag_and_srp_accel(&drag, srp.as_ref(), almanac.clone())
+        .unwrap();
+    println!("drag: {drag_accel} km/s^2\nSRP: {srp_accel} km/s^2");
+
+    // The drag opposes the velocity vector
+    let cos_angle = drag_accel.dot(&orbit.velocity_km_s) / (drag_accel.norm() * orbit.vmag_km_s());
+    assert!((cos_angle + 1.0).abs() < 1e-12, "cos = {cos_angle}");
+
+    // These are the accelerations of the force models used in the propagation
+    assert_eq!(
+        drag_accel,
+        drag.eom(&sc, almanac.clone()).unwrap() / sc.mass_kg()
+    );
+    assert_eq!(
+        srp_accel,
+        srp.eom(&sc, almanac.clone()).unwrap() / sc.mass_kg()
+    );
+
+    // The drag acceleration scales with the product of the drag coefficient and the drag area
+    for (area_m2, cd) in [(6.0, 2.2), (3.0, 4.4), (1.5, 1.1)] {
+        let scaled_sc = sc.with_drag(area_m2, cd);
+        let (scaled_drag_accel, scaled_srp_accel) = scaled_sc
+            .drag_and_srp_accel(&drag, srp.as_ref(), almanac.clone())
+            .unwrap();
+
+        let expected_ratio = (area_m2 * cd) / (3.0 * 2.2);
+        assert!(
+            (scaled_drag_accel - drag_accel * expected_ratio).norm()
+                < 1e-12 * drag_accel.norm() * expected_ratio
+        );
+        // The SRP does not depend on the drag parameters
+        assert_eq!(scaled_srp_accel, srp_accel);
+    }
+}
+
+#[rstest]
+fn plates_drag_and_srp_accel(almanac: Arc<Almanac>) {
+    use anise::constants::orientations::J2000;
+    use nalgebra::UnitQuaternion;
+    use nyx::cosmic::attitude::{AttitudeEphemeris, OrientedPlates, Plate, PlateModel};
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+    let orbit = Orbit::keplerian(6800.0, 1e-3, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 2.0).with_drag(3.0, 2.2);
+
+    // Box without Z faces: 1 m^2 on the X faces and 4 m^2 on the Y faces
+    let plates = PlateModel {
+        plates: [
+            (Vector3::x(), 1.0),
+            (-Vector3::x(), 1.0),
+            (Vector3::y(), 4.0),
+            (-Vector3::y(), 4.0),
+        ]
+        .iter()
+        .map(|(normal_body, area_m2)| Plate {
+            normal_body: *normal_body,
+            area_m2: *area_m2,
+        })
+        .collect(),
+    };
+
+    let sun_dir = -almanac
+        .transform_to(orbit, SUN_J2000, None)
+        .unwrap()
+        .radius_km
+        .normalize();
+    let vel_dir = orbit.velocity_km_s.normalize();
+
+    // Body frame aligned with the inertial frame, then rotated by 90 degrees about Z so that the X and Y faces swap
+    let attitudes = [
+        UnitQuaternion::identity(),
+        UnitQuaternion::from_axis_angle(&Vector3::z_axis(), std::f64::consts::FRAC_PI_2),
+    ];
+    let box_area_m2 = |dir: &Vector3<f64>, swapped: bool| {
+        let (x_area, y_area) = if swapped { (4.0, 1.0) } else { (1.0, 4.0) };
+        x_area * dir[0].abs() + y_area * dir[1].abs()
+    };
+
+    let mut accels = Vec::new();
+    for (idx, quaternion) in attitudes.iter().enumerate() {
+        let attitude = Arc::new(
+            AttitudeEphemeris::new(
+                vec![
+                    (dt - 1 * Unit::Hour, *quaternion),
+                    (dt + 1 * Unit::Hour, *quaternion),
+                ],
+                J2000,
+            )
+            .unwrap(),
+        );
+        let oriented = OrientedPlates {
+            plates: plates.clone(),
+            attitude,
+        };
+        let drag = Drag {
+            density: AtmDensity::Constant(1e-12),
+            drag_frame: eme2k,
+            estimate: false,
+            plates: None,
+        }
+        .with_plates(oriented.clone());
+        // No shadow body, so that the SRP is never zero
+        let srp = SolarPressure::default_raw(vec![], almanac.clone())
+            .unwrap()
+            .with_plates(oriented);
+
+        let drag_accel = drag.eom(&sc, almanac.clone()).unwrap();
+        let srp_accel = srp.eom(&sc, almanac.clone()).unwrap();
+
+        // Identical to the accelerations with the projected areas set as the spacecraft areas
+        let swapped = idx == 1;
+        let projected_sc = sc
+            .with_drag_area(box_area_m2(&vel_dir, swapped))
+            .with_srp_area(box_area_m2(&sun_dir, swapped));
+        let drag_expected = Drag {
+            density: AtmDensity::Constant(1e-12),
+            drag_frame: eme2k,
+            estimate: false,
+            plates: None,
+        }
+        .eom(&projected_sc, almanac.clone())
+        .unwrap();
+        let srp_expected = SolarPressure::default_raw(vec![], almanac.clone())
+            .unwrap()
+            .eom(&projected_sc, almanac.clone())
+            .unwrap();
+        assert!((drag_accel - drag_expected).norm() < 1e-12 * drag_expected.norm());
+        assert!((srp_accel - srp_expected).norm() < 1e-12 * srp_expected.norm());
+
+        accels.push((drag_accel, srp_accel));
+    }
+
+    // The accelerations depend on the attitude
+    let (drag_ratio, srp_ratio) = (
+        accels[1].0.norm() / accels[0].0.norm(),
+        accels[1].1.norm() / accels[0].1.norm(),
+    );
+    assert!((drag_ratio - box_area_m2(&vel_dir, true) / box_area_m2(&vel_dir, false)).abs() < 1e-9);
+    assert!((srp_ratio - box_area_m2(&sun_dir, true) / box_area_m2(&sun_dir, false)).abs() < 1e-9);
+    assert!((drag_ratio - 1.0).abs() > 1e-3);
+    assert!((srp_ratio - 1.0).abs() > 1e-3);
+}
+
 #[rstest]
 fn std_atm_drag_earth(almanac: Arc<Almanac>) {
     let eme2k = almanac
@@ -383,3 +609,353 @@ fn std_atm_drag_earth_low(almanac: Arc<Almanac>) {
 
     */
 }
+
+/// Propagates the spacecraft with the preset and with the reference force model stack, and checks that both end states match.
+fn check_preset(
+    name: &str,
+    reference: SpacecraftDynamics,
+    options: PresetOptions,
+    sc: Spacecraft,
+    prop_time: Duration,
+    almanac: Arc<Almanac>,
+) -> Spacecraft {
+    let preset = SpacecraftDynamics::preset(name, almanac.clone(), options).unwrap();
+    println!("{name}: {preset}");
+
+    let preset_state = Propagator::default(preset)
+        .with(sc, almanac.clone())
+        .for_duration(prop_time)
+        .unwrap();
+
+    let ref_state = Propagator::default(reference)
+        .with(sc, almanac)
+        .for_duration(prop_time)
+        .unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &preset_state.orbit.to_cartesian_pos_vel(),
+        &ref_state.orbit.to_cartesian_pos_vel(),
+    );
+    println!(
+        "{name} preset vs reference: {:.3e} km\t{:.3e} km/s",
+        err_r, err_v
+    );
+    assert!(err_r < 1e-9, "{name} preset does not match the reference");
+    assert!(err_v < 1e-12, "{name} preset does not match the reference");
+
+    preset_state
+}
+
+#[rstest]
+fn preset_leo_precise(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(6778.0, 0.001, 51.6, 30.0, 45.0, 10.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 2.0).with_drag(2.0, 2.2);
+
+    let reference = SpacecraftDynamics::from_models(
+        OrbitalDynamics::new(vec![
+            PointMasses::new(vec![MOON, SUN]),
+            Harmonics::from_stor(
+                iau_earth,
+                HarmonicsMem::from_cof("data/JGM3.cof.gz", 70, 70, true).unwrap(),
+            ),
+        ]),
+        vec![
+            Drag::std_atm1976(almanac.clone()).unwrap(),
+            SolarPressure::new(vec![eme2k, moon_j2k], almanac.clone()).unwrap(),
+        ],
+    );
+
+    let end_state = check_preset(
+        "LEO_PRECISE",
+        reference,
+        PresetOptions::default(),
+        sc,
+        6 * Unit::Hour,
+        almanac.clone(),
+    );
+
+    // Overriding the degree and order of the harmonics changes the dynamics.
+    let low_fidelity = SpacecraftDynamics::preset(
+        "leo_precise",
+        almanac.clone(),
+        PresetOptions::builder()
+            .harmonics_degree(2)
+            .harmonics_order(0)
+            .build(),
+    )
+    .unwrap();
+    let low_fidelity_state = Propagator::default(low_fidelity)
+        .with(sc, almanac)
+        .for_duration(6 * Unit::Hour)
+        .unwrap();
+
+    let (err_r, _) = rss_orbit_vec_errors(
+        &end_state.orbit.to_cartesian_pos_vel(),
+        &low_fidelity_state.orbit.to_cartesian_pos_vel(),
+    );
+    println!("70x70 vs J2 only: {:.3} km", err_r);
+    assert!(err_r > 1e-3);
+
+    // Independently of the force models, the node regresses at the secular J2 rate of JGM3.
+    // The tolerance covers the short period J2 terms, the drag and the third body perturbations.
+    let j2 = 1.082_636_0e-3;
+    let eq_radius_km = 6378.1363;
+    let sma_km = orbit.sma_km().unwrap();
+    let semi_latus_km = sma_km * (1.0 - orbit.ecc().unwrap().powi(2));
+    let mean_motion_rad_s = (orbit.frame.mu_km3_s2().unwrap() / sma_km.powi(3)).sqrt();
+    let raan_rate_deg_s = (-1.5
+        * mean_motion_rad_s
+        * j2
+        * (eq_radius_km / semi_latus_km).powi(2)
+        * orbit.inc_deg().unwrap().to_radians().cos())
+    .to_degrees();
+    let expected_draan_deg = raan_rate_deg_s * (6 * Unit::Hour).to_seconds();
+    let draan_deg = end_state.orbit.raan_deg().unwrap() - orbit.raan_deg().unwrap();
+    println!("RAAN change: {draan_deg:.6} deg (J2 secular: {expected_draan_deg:.6} deg)");
+    assert!(
+        (draan_deg - expected_draan_deg).abs() < 0.1 * expected_draan_deg.abs(),
+        "LEO_PRECISE nodal regression does not match the J2 secular rate"
+    );
+}
+
+#[rstest]
+fn preset_geo(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(42164.0, 1e-4, 0.05, 0.0, 0.0, 0.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 2000.0, 30.0);
+
+    let reference = SpacecraftDynamics::from_models(
+        OrbitalDynamics::new(vec![
+            PointMasses::new(vec![MOON, SUN]),
+            Harmonics::from_stor(
+                iau_earth,
+                HarmonicsMem::from_cof("data/JGM3.cof.gz", 8, 8, true).unwrap(),
+            ),
+        ]),
+        vec![SolarPressure::new(vec![eme2k, moon_j2k], almanac.clone()).unwrap()],
+    );
+
+    check_preset(
+        "GEO",
+        reference,
+        PresetOptions::default(),
+        sc,
+        3 * Unit::Day,
+        almanac,
+    );
+}
+
+#[rstest]
+fn preset_cislunar(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let iau_moon = almanac.frame_from_uid(IAU_MOON_FRAME).unwrap();
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    // Highly elliptical orbit reaching towards the Moon
+    let orbit = Orbit::keplerian(200_000.0, 0.95, 28.5, 0.0, 0.0, 0.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 1000.0, 10.0);
+
+    let reference = SpacecraftDynamics::from_models(
+        OrbitalDynamics::new(vec![
+            PointMasses::new(vec![MOON, SUN]),
+            Harmonics::from_stor(
+                iau_moon,
+                HarmonicsMem::from_shadr("data/Luna_jggrx_1500e_sha.tab.gz", 20, 20, true).unwrap(),
+            ),
+        ]),
+        vec![SolarPressure::new(vec![eme2k, moon_j2k], almanac.clone()).unwrap()],
+    );
+
+    check_preset(
+        "CISLUNAR",
+        reference,
+        PresetOptions::default(),
+        sc,
+        5 * Unit::Day,
+        almanac,
+    );
+}
+
+/// Almanac with the orientation of the Moon principal axes frame from the DE440 libration angles.
+fn moon_pa_almanac() -> Arc<Almanac> {
+    use anise::almanac::metaload::MetaFile;
+
+    let mut moon_pa = MetaFile {
+        uri: "http://public-data.nyxspace.com/anise/moon_pa_de440_200625.bpc".to_string(),
+        crc32: None,
+    };
+    moon_pa.process(true).unwrap();
+
+    Arc::new(crate::test_almanac().load(&moon_pa.uri).unwrap())
+}
+
+#[test]
+fn preset_llo() {
+    let almanac = moon_pa_almanac();
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let moon_pa = moon_pa_frame(&almanac).unwrap();
+    let iau_moon = almanac.frame_from_uid(IAU_MOON_FRAME).unwrap();
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    // 100 km polar lunar orbit
+    let orbit = Orbit::keplerian(
+        moon_j2k.mean_equatorial_radius_km().unwrap() + 100.0,
+        0.001,
+        90.0,
+        0.0,
+        0.0,
+        0.0,
+        dt,
+        moon_j2k,
+    );
+    let sc = Spacecraft::from_srp_defaults(orbit, 1000.0, 10.0);
+
+    let options = PresetOptions::builder().harmonics_degree(30).build();
+    let build_reference = |frame| {
+        SpacecraftDynamics::from_models(
+            OrbitalDynamics::new(vec![
+                PointMasses::new(vec![EARTH, SUN]),
+                Harmonics::from_stor(
+                    frame,
+                    HarmonicsMem::from_shadr("data/Luna_jggrx_1500e_sha.tab.gz", 30, 30, true)
+                        .unwrap(),
+                ),
+            ]),
+            vec![SolarPressure::new(vec![moon_j2k, eme2k], almanac.clone()).unwrap()],
+        )
+    };
+
+    let prop_time = 7 * Unit::Day;
+    let llo_state = check_preset(
+        "LLO",
+        build_reference(moon_pa),
+        options.clone(),
+        sc,
+        prop_time,
+        almanac.clone(),
+    );
+
+    // The orbit remains a low lunar orbit over the week
+    let alt_km = llo_state.orbit.rmag_km() - moon_j2k.mean_equatorial_radius_km().unwrap();
+    println!("LLO altitude after {prop_time}: {alt_km:.3} km");
+    assert!((30.0..170.0).contains(&alt_km));
+
+    // Computing the GRAIL field in the IAU Moon frame instead of its principal axes frame leads to a different trajectory
+    let iau_state = Propagator::default(build_reference(iau_moon))
+        .with(sc, almanac)
+        .for_duration(prop_time)
+        .unwrap();
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &llo_state.orbit.to_cartesian_pos_vel(),
+        &iau_state.orbit.to_cartesian_pos_vel(),
+    );
+    println!("LLO PA vs IAU Moon frame: {err_r:.3e} km\t{err_v:.3e} km/s");
+    assert!(err_r > 1e-3, "the gravity field frame should matter in LLO");
+}
+
+#[rstest]
+fn harmonics_partials_rotation(almanac: Arc<Almanac>) {
+    use nyx::dynamics::AccelModel;
+
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let iau_moon = almanac.frame_from_uid(IAU_MOON_FRAME).unwrap();
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(
+        moon_j2k.mean_equatorial_radius_km().unwrap() + 100.0,
+        0.001,
+        90.0,
+        30.0,
+        0.0,
+        45.0,
+        dt,
+        moon_j2k,
+    );
+
+    let harmonics = Harmonics::from_stor(
+        iau_moon,
+        HarmonicsMem::from_shadr("data/Luna_jggrx_1500e_sha.tab.gz", 20, 20, true).unwrap(),
+    );
+    assert_eq!(harmonics.compute_frame(), iau_moon);
+
+    let accel = harmonics.eom(&orbit, almanac.clone()).unwrap();
+    let (dual_accel, grad) = harmonics.dual_eom(&orbit, almanac.clone()).unwrap();
+    assert!((accel - dual_accel).norm() < 1e-15);
+
+    // The partials with respect to the integration frame position match central finite differences
+    let pert_km = 1e-2;
+    for j in 0..3 {
+        let mut plus = orbit;
+        let mut minus = orbit;
+        plus.radius_km[j] += pert_km;
+        minus.radius_km[j] -= pert_km;
+        let column = (harmonics.eom(&plus, almanac.clone()).unwrap()
+            - harmonics.eom(&minus, almanac.clone()).unwrap())
+            / (2.0 * pert_km);
+        let err = (column - grad.column(j)).norm() / column.norm();
+        println!("column {j}: relative error {err:.3e}");
+        assert!(
+            err < 1e-6,
+            "harmonics partials column {j} differs from finite differences"
+        );
+    }
+}
+
+#[rstest]
+fn preset_heliocentric(almanac: Arc<Almanac>) {
+    let sun_j2k = almanac.frame_from_uid(SUN_J2000).unwrap();
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    // Cruise between the Earth and Mars
+    let orbit = Orbit::keplerian(1.25 * AU, 0.2, 1.0, 0.0, 0.0, 90.0, dt, sun_j2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 1000.0, 10.0);
+
+    let reference = SpacecraftDynamics::from_models(
+        OrbitalDynamics::new(vec![PointMasses::new(vec![
+            MERCURY,
+            VENUS,
+            EARTH,
+            MOON,
+            MARS_BARYCENTER,
+            JUPITER_BARYCENTER,
+            SATURN_BARYCENTER,
+            URANUS_BARYCENTER,
+            NEPTUNE_BARYCENTER,
+        ])]),
+        vec![SolarPressure::new(vec![], almanac.clone()).unwrap()],
+    );
+
+    check_preset(
+        "HELIOCENTRIC",
+        reference,
+        PresetOptions::default(),
+        sc,
+        30 * Unit::Day,
+        almanac,
+    );
+}
+
+#[rstest]
+fn preset_unknown(almanac: Arc<Almanac>) {
+    assert!(SpacecraftDynamics::preset("MEO", almanac, PresetOptions::default()).is_err());
+    assert_eq!(
+        "geo".parse::<DynamicsPreset>().unwrap(),
+        DynamicsPreset::Geo
+    );
+    assert_eq!(format!("{}", DynamicsPreset::LeoPrecise), "LEO_PRECISE");
+    assert_eq!(
+        "llo".parse::<DynamicsPreset>().unwrap(),
+        DynamicsPreset::LowLunarOrbit
+    );
+}
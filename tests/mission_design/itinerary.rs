@@ -0,0 +1,95 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::{EARTH_J2000, JUPITER_BARYCENTER_J2000, SUN_J2000, VENUS_J2000};
+use anise::prelude::Almanac;
+use nyx::time::{Epoch, Unit};
+use nyx::tools::itinerary::{Encounter, Itinerary};
+use nyx::State;
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn evej_itinerary(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let sun = almanac.frame_from_uid(SUN_J2000).unwrap();
+    let earth = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let venus = almanac.frame_from_uid(VENUS_J2000).unwrap();
+
+    // Encounter dates of the first part of the Galileo VEEGA trajectory, followed by a Jupiter arrival.
+    let launch = Epoch::from_gregorian_utc_at_midnight(1989, 10, 18);
+    let venus_epoch = Epoch::from_gregorian_utc_at_midnight(1990, 2, 10);
+    let earth_flyby = Epoch::from_gregorian_utc_at_midnight(1990, 12, 8);
+    let jupiter_arrival = Epoch::from_gregorian_utc_at_midnight(1995, 12, 7);
+
+    let itinerary = Itinerary::builder()
+        .central(sun)
+        .encounters(vec![
+            Encounter::new(earth, launch),
+            Encounter::new(venus, venus_epoch).with_min_periapsis_radius_km(6_351.8 + 300.0),
+            Encounter::new(earth, earth_flyby).with_min_periapsis_radius_km(6_378.1 + 300.0),
+            Encounter::new(JUPITER_BARYCENTER_J2000, jupiter_arrival),
+        ])
+        .departure_parking_radius_km(6_378.1 + 185.0)
+        .build();
+
+    let report = itinerary.solve(&almanac).unwrap();
+    println!("{report}");
+
+    assert_eq!(report.legs.len(), 3);
+    assert_eq!(report.encounters.len(), 4);
+    assert!(report.encounters[0].flyby.is_none());
+    assert!(report.encounters[3].flyby.is_none());
+
+    // Galileo was launched with a C3 of about 15 km^2/s^2.
+    assert!(
+        (10.0..25.0).contains(&report.departure_c3_km2_s2),
+        "departure C3 = {} km^2/s^2",
+        report.departure_c3_km2_s2
+    );
+    // Injection from a low Earth parking orbit is a few km/s.
+    assert!((3.0..5.0).contains(&report.departure_dv_km_s));
+
+    // The Venus flyby is unpowered in reality, with an excess velocity of a few km/s and a periapsis well above the atmosphere.
+    let venus_report = report.encounters[1];
+    let venus_flyby = venus_report.flyby.unwrap();
+    let v_inf_in = venus_report.v_inf_in.unwrap().norm();
+    let v_inf_out = venus_report.v_inf_out.unwrap().norm();
+    println!("Venus v_inf in = {v_inf_in:.3} km/s\tout = {v_inf_out:.3} km/s");
+    assert!((3.0..9.0).contains(&v_inf_in));
+    assert!((v_inf_in - v_inf_out).abs() < 1.0);
+    assert!(venus_flyby.is_feasible());
+    assert!(venus_flyby.turn_angle_deg > 0.0 && venus_flyby.turn_angle_deg < 180.0);
+
+    assert!(
+        (report.total_dv_km_s()
+            - (report.departure_dv_km_s + report.flyby_dv_km_s + report.arrival_dv_km_s))
+            .abs()
+            < f64::EPSILON
+    );
+
+    // Each leg is sampled on the conic and matches the Lambert arc at both ends.
+    let trajs = report.to_trajs(Unit::Day * 5).unwrap();
+    assert_eq!(trajs.len(), 3);
+    for (traj, leg) in trajs.iter().zip(&report.legs) {
+        assert_eq!(traj.first().epoch(), leg.departure.epoch);
+        assert_eq!(traj.last().epoch(), leg.arrival.epoch);
+        let err_km = (traj.last().orbit.radius_km - leg.arrival.radius_km).norm();
+        assert!(err_km < 1.0, "conic arrival error of {err_km} km");
+    }
+
+    // Requiring an unreasonably high Venus flyby makes it infeasible.
+    let mut infeasible = itinerary.clone();
+    infeasible.encounters[1] = infeasible.encounters[1].with_min_periapsis_radius_km(1e7);
+    let report = infeasible.solve(&almanac).unwrap();
+    assert!(!report.is_feasible());
+    let flagged = report.infeasible_flybys();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0].encounter.epoch, venus_epoch);
+}
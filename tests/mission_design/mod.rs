@@ -1,4 +1,7 @@
+mod cr3bp;
+mod dv_budget;
 mod force_models;
+mod itinerary;
 mod multishoot;
 mod orbitaldyn;
 mod targeter;
@@ -1242,3 +1242,118 @@ fn val_cislunar_dynamics(almanac_gmat: Arc<Almanac>) {
         err_v
     );
 }
+
+#[rstest]
+fn earth_j2_nodal_regression(almanac: Arc<Almanac>) {
+    use nyx::dynamics::Harmonics;
+    use nyx::io::gravity::*;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    // Only the J2 term of EGM2008
+    let earth_sph_harm =
+        HarmonicsMem::from_egm("data/EGM2008_to2190_TideFree.gz", 2, 0, true).unwrap();
+    assert_eq!(earth_sph_harm.max_degree_n(), 2);
+    assert_eq!(earth_sph_harm.max_order_m(), 0);
+    // The coefficients are normalized
+    let j2 = -earth_sph_harm.cs_nm(2, 0).0 * 5.0_f64.sqrt();
+
+    let harmonics = Harmonics::from_stor(iau_earth, earth_sph_harm);
+
+    // At J2000, the pole of the IAU Earth frame is that of the EME2000 frame.
+    let dt = Epoch::from_mjd_tai(MJD_J2000);
+    let orbit = Orbit::keplerian(7000.0, 0.001, 50.0, 30.0, 0.0, 0.0, dt, eme2k);
+
+    // Propagate for an integer number of orbits (about a day) to mostly cancel out the short period variations of the node.
+    let prop_time = 15 * orbit.period().unwrap();
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::from_model(harmonics));
+    let final_state = Propagator::default(dynamics)
+        .with(orbit.into(), almanac)
+        .for_duration(prop_time)
+        .unwrap();
+
+    let r_eq_km = iau_earth.mean_equatorial_radius_km().unwrap();
+    let p_km = orbit.semi_parameter_km().unwrap();
+    let n_rad_s = (eme2k.mu_km3_s2().unwrap() / orbit.sma_km().unwrap().powi(3)).sqrt();
+    let expected_raan_rate_deg_s = -1.5
+        * n_rad_s
+        * j2
+        * (r_eq_km / p_km).powi(2)
+        * orbit.inc_deg().unwrap().to_radians().cos();
+    let expected_delta_raan_deg = expected_raan_rate_deg_s.to_degrees() * prop_time.to_seconds();
+
+    let delta_raan_deg = final_state.orbit.raan_deg().unwrap() - orbit.raan_deg().unwrap();
+
+    println!(
+        "Node regression over {prop_time}: {delta_raan_deg:.6} deg\texpected: {expected_delta_raan_deg:.6} deg"
+    );
+    // About -4.6 degrees per day
+    assert!(expected_delta_raan_deg < -4.0);
+    assert!(
+        ((delta_raan_deg - expected_delta_raan_deg) / expected_delta_raan_deg).abs() < 0.02,
+        "nodal regression does not match J2 theory"
+    );
+}
+
+/// Checks the degree bounds of the harmonics: a J2 only field matches the analytical J2 acceleration, and the highest degree of a field is included.
+#[rstest]
+fn earth_sph_harmonics_degree_bounds(almanac: Arc<Almanac>) {
+    use nyx::dynamics::{AccelModel, Harmonics};
+    use nyx::io::gravity::*;
+    use nyx::linalg::Vector3;
+
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let dt = Epoch::from_mjd_tai(MJD_J2000);
+    // Computed in the body fixed frame directly, such that no rotation is involved.
+    let orbit = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, iau_earth,
+    );
+
+    let j2_field = HarmonicsMem::j2_jgm3();
+    assert_eq!(j2_field.max_degree_n(), 2);
+    let j2 = -j2_field.cs_nm(2, 0).0 * 5.0_f64.sqrt();
+
+    let accel = Harmonics::from_stor(iau_earth, j2_field)
+        .eom(&orbit, almanac.clone())
+        .unwrap();
+
+    let mu_km3_s2 = iau_earth.mu_km3_s2().unwrap();
+    let r_eq_km = iau_earth.mean_equatorial_radius_km().unwrap();
+    let r = orbit.radius_km;
+    let rmag = r.norm();
+    let z2_r2 = (r.z / rmag).powi(2);
+    let expected = -1.5 * j2 * mu_km3_s2 * r_eq_km.powi(2) / rmag.powi(5)
+        * Vector3::new(
+            r.x * (1.0 - 5.0 * z2_r2),
+            r.y * (1.0 - 5.0 * z2_r2),
+            r.z * (3.0 - 5.0 * z2_r2),
+        );
+
+    let rel_err = (accel - expected).norm() / expected.norm();
+    println!("J2 acceleration: {accel}\texpected: {expected}\trelative error: {rel_err:.3e}");
+    assert!(
+        rel_err < 1e-9,
+        "J2 field does not match the analytical J2 acceleration"
+    );
+
+    // The 12x12 field must include the degree 12 terms, i.e. differ from the 11x11 field.
+    let accel_11 = Harmonics::from_stor(
+        iau_earth,
+        HarmonicsMem::from_cof("data/JGM3.cof.gz", 11, 11, true).unwrap(),
+    )
+    .eom(&orbit, almanac.clone())
+    .unwrap();
+    let accel_12 = Harmonics::from_stor(
+        iau_earth,
+        HarmonicsMem::from_cof("data/JGM3.cof.gz", 12, 12, true).unwrap(),
+    )
+    .eom(&orbit, almanac)
+    .unwrap();
+    let delta = (accel_12 - accel_11).norm();
+    println!("degree 12 contribution: {delta:.3e} km/s^2");
+    assert!(delta > 1e-15, "the highest degree of the field is ignored");
+}
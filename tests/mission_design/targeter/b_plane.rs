@@ -222,3 +222,77 @@ fn tgt_b_plane_earth_gravity_assist_with_propagation(almanac: Arc<Almanac>) {
 
     tgt.apply(&sol, almanac).unwrap();
 }
+
+#[rstest]
+fn tgt_lunar_periapsis_from_eme2k(almanac: Arc<Almanac>) {
+    // Target the periapsis radius of a lunar flyby in the Moon frame while propagating in EME2000
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let luna = almanac.frame_from_uid(MOON_J2000).unwrap();
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    // Incoming hyperbola about the Moon with a periapsis radius of 5000 km, about four hours before periapsis
+    let orbit_luna = Orbit::keplerian(-10_000.0, 1.5, 30.0, 45.0, 60.0, -100.0, epoch, luna);
+    let orbit = almanac.transform_to(orbit_luna, eme2k, None).unwrap();
+
+    let spacecraft = Spacecraft::from_srp_defaults(orbit, 100.0, 0.0);
+
+    let prop = Propagator::default_dp78(SpacecraftDynamics::new(OrbitalDynamics::point_masses(
+        vec![MOON, SUN],
+    )));
+
+    let achievement_epoch = epoch + 3 * Unit::Hour;
+
+    let nominal = prop
+        .with(spacecraft, almanac.clone())
+        .until_epoch(achievement_epoch)
+        .unwrap();
+
+    let nominal_rp_km = almanac
+        .transform_to(nominal.orbit, luna, None)
+        .unwrap()
+        .periapsis_km()
+        .unwrap();
+
+    println!("nominal lunar periapsis radius: {nominal_rp_km:.3} km");
+
+    let desired_rp_km = 4_000.0;
+
+    let tgt = Targeter::delta_v(
+        &prop,
+        [Objective::within_tolerance(
+            StateParameter::PeriapsisRadius,
+            desired_rp_km,
+            0.1,
+        )],
+    )
+    .with_objective_frame(luna);
+
+    let sol = tgt
+        .try_achieve_from(spacecraft, epoch, achievement_epoch, almanac.clone())
+        .unwrap();
+
+    println!("{sol}");
+
+    // The solution is propagated in EME2000 but the objective is achieved in the Moon frame
+    assert_eq!(
+        sol.achieved_state.orbit.frame.ephemeris_id,
+        eme2k.ephemeris_id
+    );
+    assert!((sol.achieved_values[0] - desired_rp_km).abs() < 0.1);
+
+    // Applying the correction verifies the objective in the Moon frame as well
+    let xf = tgt.apply(&sol, almanac.clone()).unwrap();
+    let achieved_rp_km = almanac
+        .transform_to(xf.orbit, luna, None)
+        .unwrap()
+        .periapsis_km()
+        .unwrap();
+
+    assert!(
+        (achieved_rp_km - desired_rp_km).abs() < 0.1,
+        "achieved lunar periapsis of {achieved_rp_km:.3} km"
+    );
+}
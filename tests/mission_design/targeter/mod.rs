@@ -6,4 +6,6 @@ mod multi_oe;
 mod multi_oe_vnc;
 #[cfg(feature = "broken-donotuse")]
 mod opti_levenberg;
+mod optimizer;
+mod periodic;
 mod single_oe;
@@ -0,0 +1,100 @@
+extern crate nyx_space as nyx;
+
+use nyx::md::opti::optimizer::TargeterOptimizer;
+use nyx::md::prelude::*;
+use nyx::md::targeter::*;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn opti_min_dv_c3_decl(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let orig_dt = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    let xi_orig = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 0.0, orig_dt, eme2k);
+
+    let target_delta_t: Duration = xi_orig.period().unwrap() / 2.0;
+
+    let spacecraft = Spacecraft::from_srp_defaults(xi_orig, 100.0, 0.0);
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::default(dynamics);
+
+    // Two objectives for three velocity components: an infinite family of corrections achieves them.
+    let objectives = [
+        Objective::within_tolerance(StateParameter::Declination, 5.0, 0.1),
+        Objective::within_tolerance(StateParameter::C3, -5.0, 0.5),
+    ];
+
+    // The out-of-plane initial guess leads the plain targeter to a more expensive member of that family.
+    let tgt = Targeter::new(
+        &setup,
+        [
+            Vary::VelocityX.into(),
+            Vary::VelocityY.into(),
+            Variable::from(Vary::VelocityZ).with_initial_guess(0.5),
+        ],
+        objectives,
+    );
+
+    let tgt_sol = tgt
+        .try_achieve_from(
+            spacecraft,
+            orig_dt,
+            orig_dt + target_delta_t,
+            almanac.clone(),
+        )
+        .unwrap();
+
+    let opti = TargeterOptimizer::min_correction(tgt.clone());
+    println!("{opti}");
+
+    let opti_sol = opti
+        .try_minimize(
+            spacecraft,
+            orig_dt,
+            orig_dt + target_delta_t,
+            almanac.clone(),
+        )
+        .unwrap();
+
+    println!("{opti_sol}");
+
+    let tgt_dv = tgt_sol.correction.norm();
+    let opti_dv = opti_sol.solution.correction.norm();
+    println!(
+        "Targeter Δv = {:.3} m/s\tOptimizer Δv = {:.3} m/s",
+        tgt_dv * 1e3,
+        opti_dv * 1e3
+    );
+
+    assert!((opti_sol.targeter_cost - tgt_dv).abs() < f64::EPSILON);
+    assert!((opti_sol.cost - opti_dv).abs() < f64::EPSILON);
+    // At least one meter per second cheaper than the plain targeter
+    assert!(
+        opti_dv < tgt_dv - 1e-3,
+        "optimizer did not reduce the Δv: {opti_dv} km/s vs {tgt_dv} km/s"
+    );
+
+    // The history starts at the targeter solution and the cost decreases monotonically
+    assert!(opti_sol.history.len() > 1);
+    assert!(opti_sol
+        .history
+        .windows(2)
+        .all(|pair| pair[1].cost < pair[0].cost));
+
+    // The optimized solution still achieves the objectives
+    assert!(opti_sol.solution.objectives_met().iter().all(|met| *met));
+    tgt.apply(&opti_sol.solution, almanac).unwrap();
+}
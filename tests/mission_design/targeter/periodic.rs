@@ -0,0 +1,93 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::{IAU_MOON_FRAME, MOON_J2000};
+use nyx::md::opti::periodic::PeriodicOrbitSolver;
+use nyx::md::prelude::*;
+use nyx::md::targeter::*;
+
+use anise::prelude::Almanac;
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn frozen_lunar_orbit(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let iau_moon = almanac.frame_from_uid(IAU_MOON_FRAME).unwrap();
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+
+    // Near polar orbit at about 100 km altitude, close to the frozen conditions with the periapsis over the south pole
+    let guess = Orbit::keplerian(
+        moon_j2k.mean_equatorial_radius_km().unwrap() + 100.0,
+        0.01,
+        86.0,
+        0.0,
+        270.0,
+        0.0,
+        epoch,
+        moon_j2k,
+    );
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::new(vec![Harmonics::from_stor(
+        iau_moon,
+        HarmonicsMem::from_shadr("data/Luna_jggrx_1500e_sha.tab.gz", 10, 10, true).unwrap(),
+    )]));
+    let setup = Propagator::default(dynamics);
+
+    // A frozen orbit keeps its size, shape and orientation of the periapsis after one revolution.
+    let solver = PeriodicOrbitSolver::new(
+        &setup,
+        [
+            Vary::VelocityX.into(),
+            Vary::VelocityY.into(),
+            Vary::VelocityZ.into(),
+        ],
+        [
+            Objective::within_tolerance(StateParameter::SMA, 0.0, 1e-3),
+            Objective::within_tolerance(StateParameter::Eccentricity, 0.0, 1e-6),
+            Objective::within_tolerance(StateParameter::AoP, 0.0, 1e-3),
+        ],
+    );
+    println!("{solver}");
+
+    let solution = solver
+        .try_close(
+            Spacecraft::from(guess),
+            guess.period().unwrap(),
+            almanac.clone(),
+        )
+        .unwrap();
+
+    println!("{solution}");
+
+    assert!(solution.closure_errors.iter().all(|err| err.is_finite()));
+    // The semi major axis repeats to the meter level
+    assert!(solution.closure_errors[0].abs() <= 1e-3);
+    assert!(solution.closure_errors[1].abs() <= 1e-6);
+    assert!(solution.closure_errors[2].abs() <= 1e-3);
+    assert_eq!(solution.period_correction, Duration::ZERO);
+
+    // Check the closure by propagating the corrected initial state independently of the solver
+    let final_state = setup
+        .with(solution.initial_state, almanac)
+        .for_duration(solution.period)
+        .unwrap();
+
+    let initial_orbit = solution.initial_state.orbit;
+    assert!((final_state.orbit.sma_km().unwrap() - initial_orbit.sma_km().unwrap()).abs() <= 1e-3);
+    assert!((final_state.orbit.ecc().unwrap() - initial_orbit.ecc().unwrap()).abs() <= 1e-6);
+
+    // The monodromy matrix of a Keplerian like orbit has eigenvalues on the unit circle
+    assert_eq!(solution.eigenvalues().len(), 6);
+    let stability_index = solution.stability_index();
+    println!("stability index = {stability_index}");
+    assert!(stability_index >= 1.0 && stability_index.is_finite());
+}
@@ -74,6 +74,53 @@ fn tgt_sma_from_apo(almanac: Arc<Almanac>) {
     );
 }
 
+#[rstest]
+fn tgt_sma_achieved_report(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let orig_dt = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let xi_orig = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 180.0, orig_dt, eme2k);
+    let target_delta_t: Duration = xi_orig.period().unwrap() / 2.0;
+    let spacecraft = Spacecraft::from_srp_defaults(xi_orig, 100.0, 0.0);
+
+    let setup = Propagator::default_dp78(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+
+    let objectives = [Objective::within_tolerance(
+        StateParameter::SMA,
+        8_100.0,
+        1e-3,
+    )];
+    let tgt = Targeter::delta_v(&setup, objectives);
+
+    let solution = tgt
+        .try_achieve_from(
+            spacecraft,
+            orig_dt,
+            orig_dt + target_delta_t,
+            almanac.clone(),
+        )
+        .unwrap();
+
+    let report = format!("{solution}");
+    println!("{report}");
+    assert!(report.contains("Achieved"));
+    assert!(report.contains("SMA"));
+    assert_eq!(solution.objectives_met(), [true]);
+
+    // The reported achieved SMA is that of the propagated corrected state
+    let xf = setup
+        .with(solution.corrected_state, almanac)
+        .for_duration(target_delta_t)
+        .unwrap();
+    println!(
+        "achieved SMA {} km vs propagated {} km",
+        solution.achieved_values[0],
+        xf.orbit.sma_km().unwrap()
+    );
+    assert!((solution.achieved_values[0] - xf.orbit.sma_km().unwrap()).abs() < 1e-6);
+    assert!((solution.achieved_values[0] - 8_100.0).abs() <= 1e-3);
+}
+
 #[rstest]
 fn tgt_sma_from_peri_fd(almanac: Arc<Almanac>) {
     let _ = pretty_env_logger::try_init();
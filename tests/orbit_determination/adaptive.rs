@@ -0,0 +1,179 @@
+extern crate nyx_space as nyx;
+extern crate pretty_env_logger;
+
+use anise::constants::frames::IAU_EARTH_FRAME;
+use nyx::cosmic::{GuidanceMode, Orbit};
+use nyx::dynamics::guidance::{FiniteBurns, LocalFrame, Mnvr, Thruster};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::linalg::{SMatrix, SVector, Vector3, U2};
+use nyx::od::prelude::*;
+use nyx::propagators::{IntegratorOptions, Propagator};
+use nyx::Spacecraft;
+use nyx_space::propagators::IntegratorMethod;
+use std::collections::BTreeMap;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Returns the epoch at which the mean normalized innovation squared over a window of measurements
+/// falls back within bounds after the provided epoch, or None if it never does.
+fn recovery_epoch(residuals: &[Option<Residual<U2>>], after: Epoch) -> Option<Epoch> {
+    let window = 20;
+    let nis = residuals
+        .iter()
+        .flatten()
+        .filter(|resid| resid.epoch > after)
+        .map(|resid| (resid.epoch, resid.ratio.powi(2) / 2.0))
+        .collect::<Vec<(Epoch, f64)>>();
+
+    let mut disturbed = false;
+    for chunk in nis.windows(window) {
+        let mean_nis = chunk.iter().map(|(_, nis)| nis).sum::<f64>() / (window as f64);
+        if mean_nis > 2.0 {
+            disturbed = true;
+        } else if disturbed {
+            return Some(chunk[window - 1].0);
+        }
+    }
+    None
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn od_adaptive_snc_unmodeled_maneuver(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    // Only white noise so that the filter is consistent prior to the maneuver
+    let range_noise_km = StochasticNoise {
+        white_noise: Some(WhiteNoise {
+            mean: 0.0,
+            sigma: 2e-3,
+        }),
+        ..Default::default()
+    };
+    let doppler_noise_km_s = StochasticNoise {
+        white_noise: Some(WhiteNoise {
+            mean: 0.0,
+            sigma: 3e-6,
+        }),
+        ..Default::default()
+    };
+
+    let devices = vec![
+        GroundStation::dss65_madrid(0.0, range_noise_km, doppler_noise_km_s, iau_earth),
+        GroundStation::dss34_canberra(0.0, range_noise_km, doppler_noise_km_s, iau_earth),
+        GroundStation::dss13_goldstone(0.0, range_noise_km, doppler_noise_km_s, iau_earth),
+    ];
+
+    let configs: BTreeMap<String, TrkConfig> = devices
+        .iter()
+        .map(|device| {
+            (
+                device.name.clone(),
+                TrkConfig::from_sample_rate(60.seconds()),
+            )
+        })
+        .collect();
+
+    let prop_time = 1 * Unit::Day;
+    let opts = IntegratorOptions::with_fixed_step(10.seconds());
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_orbit = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    // The truth performs a small maneuver of about 1 cm/s which the navigation filter does not model.
+    let mnvr_start = dt + 8 * Unit::Hour;
+    let mnvr_end = mnvr_start + 100.seconds();
+    let truth_sc = Spacecraft::from_thruster(
+        initial_orbit,
+        500.0,
+        10.0,
+        Thruster {
+            thrust_N: 0.05,
+            isp_s: 300.0,
+        },
+        GuidanceMode::Coast,
+    );
+    let truth_dynamics = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        FiniteBurns::from_mnvrs(vec![Mnvr::from_time_invariant(
+            mnvr_start,
+            mnvr_end,
+            1.0,
+            Vector3::new(1.0, 0.0, 0.0),
+            LocalFrame::VNC,
+        )]),
+    );
+
+    let (_, traj) = Propagator::new(truth_dynamics, IntegratorMethod::RungeKutta4, opts)
+        .with(truth_sc, almanac.clone())
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(devices, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+
+    let setup = Propagator::new(
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        IntegratorMethod::RungeKutta4,
+        opts,
+    );
+
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6, 0.0, 0.0, 0.0,
+    ]));
+    let initial_estimate =
+        KfEstimate::from_covar(Spacecraft::from(initial_orbit).with_stm(), init_covar);
+
+    let sigma_q = 1e-11_f64.powi(2);
+    let process_noise = SNC3::from_diagonal(2 * Unit::Minute, &[sigma_q, sigma_q, sigma_q]);
+
+    let adaptive = AdaptiveSnc::builder()
+        .window(10)
+        .scale_factor(10.0)
+        .max_scale(1e8)
+        .build();
+
+    let mut recoveries = Vec::new();
+    for kf in [
+        KF::new(initial_estimate, process_noise.clone()),
+        KF::new(initial_estimate, process_noise.clone()).with_adaptive_snc(adaptive),
+    ] {
+        let adaptive_filter = kf.adaptive_snc.is_some();
+
+        let prop_est = setup.with(Spacecraft::from(initial_orbit).with_stm(), almanac.clone());
+        let mut odp = ODProcess::ekf(
+            prop_est,
+            kf,
+            EkfTrigger::new(50, 10 * Unit::Minute),
+            None,
+            almanac.clone(),
+        );
+
+        odp.process_arc::<GroundStation>(&arc).unwrap();
+
+        let recovered = recovery_epoch(&odp.residuals, mnvr_end).unwrap_or(dt + prop_time);
+        println!(
+            "adaptive = {adaptive_filter}: innovations back in bounds {} after the maneuver (final scale {:.1e})",
+            recovered - mnvr_end,
+            odp.kf.snc_scale()
+        );
+        recoveries.push(recovered - mnvr_end);
+    }
+
+    assert!(
+        recoveries[1] < recoveries[0] * 0.75,
+        "adaptive filter should recover faster than the fixed process noise filter"
+    );
+}
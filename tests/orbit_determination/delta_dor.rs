@@ -0,0 +1,227 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::IAU_EARTH_FRAME;
+use nyx::cosmic::Orbit;
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::linalg::{Const, Matrix3, SMatrix, SVector, Vector3};
+use nyx::mc::NyxRng;
+use nyx::od::noise::WhiteNoise;
+use nyx::od::prelude::*;
+use nyx::propagators::Propagator;
+use nyx::Spacecraft;
+use std::collections::BTreeMap;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Returns the variance of the position in the out-of-plane direction of the provided orbit.
+fn out_of_plane_var_km2(orbit: Orbit, covar: &SMatrix<f64, 9, 9>) -> f64 {
+    let c_hat = orbit.hvec().unwrap().normalize();
+    let pos_covar: Matrix3<f64> = covar.fixed_view::<3, 3>(0, 0).into_owned();
+    (c_hat.transpose() * pos_covar * c_hat)[(0, 0)]
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn od_delta_dor_lunar_transfer(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    // Translunar coast, nearly equatorial so that the Goldstone-Canberra baseline has a large component out of the plane of the orbit.
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 3, 1);
+    let initial_state = Orbit::keplerian(200_000.0, 0.96, 1.0, 0.0, 0.0, 165.0, epoch, eme2k);
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = setup
+        .with(initial_state.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    // Doppler only tracking: the range is effectively ignored by setting its noise to 1000 km.
+    let range_noise = StochasticNoise {
+        white_noise: Some(WhiteNoise::constant_white_noise(1e3)),
+        ..Default::default()
+    };
+    let doppler_noise = StochasticNoise {
+        white_noise: Some(WhiteNoise::constant_white_noise(1e-7)),
+        ..Default::default()
+    };
+    let elevation_mask = 10.0;
+    let stations = vec![
+        GroundStation::dss65_madrid(elevation_mask, range_noise, doppler_noise, iau_earth),
+        GroundStation::dss34_canberra(elevation_mask, range_noise, doppler_noise, iau_earth),
+        GroundStation::dss13_goldstone(elevation_mask, range_noise, doppler_noise, iau_earth),
+    ];
+
+    let mut baseline = Baseline {
+        name: "Goldstone-Canberra".to_string(),
+        station_a: stations[2].clone(),
+        station_b: stations[1].clone(),
+        quasar_ra_deg: 187.2779,
+        quasar_dec_deg: 2.0524,
+        timestamp_noise_s: None,
+        delta_dor_noise_rad: Some(StochasticNoise {
+            white_noise: Some(WhiteNoise::constant_white_noise(2e-9)),
+            ..Default::default()
+        }),
+    };
+
+    // Find the first time the spacecraft is in view of both stations of the baseline, after at least a few hours of Doppler tracking.
+    let dor_epoch = traj
+        .every_between(
+            10 * Unit::Minute,
+            epoch + 6 * Unit::Hour,
+            traj.last().epoch(),
+        )
+        .find(|state| {
+            baseline
+                .measure_instantaneous(*state, None, almanac.clone())
+                .unwrap()
+                .is_some()
+        })
+        .expect("spacecraft never in view of both stations of the baseline")
+        .epoch();
+    println!("Delta-DOR measurement @ {dor_epoch}");
+
+    // Simulate the Doppler tracking until the Delta-DOR measurement.
+    let mut configs = BTreeMap::new();
+    for station in &stations {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig::from_sample_rate(10 * Unit::Minute),
+        );
+    }
+
+    let mut arc_sim =
+        TrackingArcSim::with_seed(stations.clone(), traj.clone(), configs.clone(), 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let arc = arc_sim
+        .generate_measurements(almanac.clone())
+        .unwrap()
+        .filter_by_epoch(..dor_epoch);
+    println!("{arc}");
+
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        100.0, 100.0, 100.0, 1e-4, 1e-4, 1e-4, 0.0, 0.0, 0.0,
+    ]));
+    let initial_estimate = KfEstimate::from_covar(initial_state.into(), init_covar);
+
+    let prop_est = setup.with(Spacecraft::from(initial_state).with_stm(), almanac.clone());
+    let mut odp = ODProcess::ckf(
+        prop_est,
+        KF::no_snc(initial_estimate),
+        None,
+        almanac.clone(),
+    );
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+    odp.predict_until(1 * Unit::Minute, dor_epoch).unwrap();
+
+    let doppler_est = odp.estimates.last().unwrap().clone();
+    assert_eq!(doppler_est.epoch(), dor_epoch);
+
+    // Single Delta-DOR measurement update
+    let nominal = doppler_est.nominal_state.with_stm();
+    let mut rng = NyxRng::from_seed(0);
+    let real_msr = baseline
+        .measure_instantaneous(traj.at(dor_epoch).unwrap(), Some(&mut rng), almanac.clone())
+        .unwrap()
+        .unwrap();
+    let computed_msr = baseline
+        .measure_instantaneous(nominal, None, almanac.clone())
+        .unwrap()
+        .unwrap();
+    let midpoint = baseline
+        .location(dor_epoch, nominal.frame(), almanac.clone())
+        .unwrap();
+    let h_tilde = <Spacecraft as EstimateFrom<Spacecraft, DeltaDor>>::sensitivity(
+        &computed_msr,
+        nominal,
+        midpoint,
+    );
+    let r_mat = baseline.measurement_covar(dor_epoch).unwrap();
+
+    let mut dor_kf: KF<Spacecraft, Const<3>, Const<1>> = KF::no_snc(doppler_est.clone());
+    dor_kf.update_h_tilde(h_tilde);
+    let (dor_est, residual) = dor_kf
+        .measurement_update(
+            nominal,
+            &real_msr.observation(),
+            &computed_msr.observation(),
+            r_mat,
+            None,
+        )
+        .unwrap();
+    println!("{residual}");
+
+    // Expected covariance from the Kalman update of a single scalar measurement
+    let p_bar = doppler_est.covar;
+    let innov_var = (h_tilde * p_bar * h_tilde.transpose())[(0, 0)] + r_mat[(0, 0)];
+    let expected_covar = p_bar - p_bar * h_tilde.transpose() * h_tilde * p_bar / innov_var;
+
+    let var_before = out_of_plane_var_km2(nominal.orbit, &p_bar);
+    let var_after = out_of_plane_var_km2(nominal.orbit, &dor_est.covar);
+    let var_expected = out_of_plane_var_km2(nominal.orbit, &expected_covar);
+
+    println!(
+        "Out-of-plane 1-sigma: {:.3} m with Doppler only, {:.3} m with Delta-DOR (expected {:.3} m)",
+        var_before.sqrt() * 1e3,
+        var_after.sqrt() * 1e3,
+        var_expected.sqrt() * 1e3
+    );
+
+    assert!(
+        (var_after - var_expected).abs() < 1e-6 * var_before,
+        "covariance does not match the expected Kalman update"
+    );
+    assert!(
+        var_before.sqrt() / var_after.sqrt() > 10.0,
+        "Delta-DOR should collapse the out-of-plane uncertainty"
+    );
+}
+
+#[rstest]
+fn od_delta_dor_sensitivity_source(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 3, 1);
+
+    let sc = Spacecraft::from(Orbit::new(384_400.0, 0.0, 0.0, 0.0, 1.0, 0.0, epoch, eme2k));
+    let midpoint = Orbit::new(6_378.0, 0.0, 0.0, 0.0, 0.0, 0.0, epoch, eme2k);
+    let rho_km = sc.orbit.radius_km - midpoint.radius_km;
+
+    // The baseline direction is only known to the computed measurement: a loaded one cannot provide the sensitivity.
+    assert!(DeltaDor::sensitivity_from_computed());
+    let computed = DeltaDor::new(
+        epoch,
+        rho_km.normalize(),
+        Vector3::new(0.0, 0.0, 1.0),
+        Vector3::new(0.0, 0.0, 10_000.0),
+        0.0,
+        0.0,
+    );
+    let loaded = DeltaDor::from_observation(epoch, computed.observation());
+
+    let h_computed =
+        <Spacecraft as EstimateFrom<Spacecraft, DeltaDor>>::sensitivity(&computed, sc, midpoint);
+    let h_loaded =
+        <Spacecraft as EstimateFrom<Spacecraft, DeltaDor>>::sensitivity(&loaded, sc, midpoint);
+    assert!((h_computed[(0, 2)] - 1.0 / rho_km.norm()).abs() < 1e-15);
+    assert_eq!(h_loaded.norm(), 0.0);
+
+    // Range and Doppler sensitivities remain evaluated on the real measurement, as they were before Delta-DOR.
+    assert!(!RangeDoppler::sensitivity_from_computed());
+    let real = RangeDoppler::from_observation(epoch, SVector::<f64, 2>::new(400_000.0, 0.5));
+    let h_real =
+        <Spacecraft as EstimateFrom<Spacecraft, RangeDoppler>>::sensitivity(&real, sc, midpoint);
+    assert!((h_real[(0, 0)] - rho_km.x / 400_000.0).abs() < 1e-15);
+    assert!((h_real[(1, 1)] - 1.0 / 400_000.0).abs() < 1e-15);
+}
@@ -0,0 +1,123 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::EARTH_J2000;
+use anise::prelude::Almanac;
+use nyx::cosmic::{GuidanceMode, Orbit, Spacecraft};
+use nyx::dynamics::guidance::{GuidanceLaw, LocalFrame, ManeuverPlan, Mnvr, Thruster};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::linalg::{SVector, Vector3};
+use nyx::od::prelude::*;
+use nyx::propagators::Propagator;
+
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Executes a one minute burn along the velocity which is 2% hot and pointed 1 degree off, and checks that the reconstruction
+/// from perfect estimates before and after the burn recovers both within its formal uncertainty.
+#[rstest]
+fn maneuver_recon_hot_burn(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 10.0, 20.0, 45.0, epoch, eme2k);
+
+    let thruster = |thrust_N: f64| Thruster {
+        thrust_N,
+        isp_s: 300.0,
+    };
+
+    let planned_sc =
+        Spacecraft::from_thruster(orbit, 400.0, 100.0, thruster(10.0), GuidanceMode::Coast);
+    let truth_sc =
+        Spacecraft::from_thruster(orbit, 400.0, 100.0, thruster(10.2), GuidanceMode::Coast);
+
+    let start = epoch + 10 * Unit::Minute;
+    let end = start + 1 * Unit::Minute;
+    let post_epoch = end + 30 * Unit::Minute;
+
+    let planned_law: Arc<dyn GuidanceLaw> = Arc::new(Mnvr::from_time_invariant(
+        start,
+        end,
+        1.0,
+        Vector3::new(1.0, 0.0, 0.0),
+        LocalFrame::VNC,
+    ));
+    let (sin_err, cos_err) = 1.0_f64.to_radians().sin_cos();
+    let truth_law: Arc<dyn GuidanceLaw> = Arc::new(Mnvr::from_time_invariant(
+        start,
+        end,
+        1.0,
+        Vector3::new(cos_err, sin_err, 0.0),
+        LocalFrame::VNC,
+    ));
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+
+    // Truth, stepping onto the start of the burn such that it starts on time
+    let truth_plan = ManeuverPlan::new(vec![(start, end, truth_law)]).unwrap();
+    let truth_prop = Propagator::default(dynamics.with_guidance_law(truth_plan));
+    let mut truth_instance = truth_prop.with(truth_sc, almanac.clone());
+    truth_instance.until_epoch(start).unwrap();
+    truth_instance.until_epoch(end).unwrap();
+    let truth_post = truth_instance.until_epoch(post_epoch).unwrap();
+
+    // Perfect estimates, but with the planned thruster, with 10 m and 1 mm/s uncertainties
+    let diag = SVector::<f64, 9>::from_column_slice(&[
+        1e-4, 1e-4, 1e-4, 1e-12, 1e-12, 1e-12, 0.0, 0.0, 0.0,
+    ]);
+    let pre_burn = KfEstimate::from_diag(planned_sc, diag);
+    let mut post_state = truth_post;
+    post_state.thruster = planned_sc.thruster;
+    let post_burn = KfEstimate::from_diag(post_state, diag);
+
+    let window = ManeuverPlan::new(vec![(start, end, planned_law)])
+        .unwrap()
+        .windows[0]
+        .clone();
+
+    let reconstructor = ManeuverReconstructor::builder().build();
+    let recon = reconstructor
+        .reconstruct(
+            &pre_burn,
+            &post_burn,
+            &window,
+            dynamics.clone(),
+            almanac.clone(),
+        )
+        .unwrap();
+
+    println!("{recon}");
+    println!("postfit residual: {}", recon.postfit_residual);
+
+    // About 1.2 m/s planned
+    assert!((recon.planned_dv_km_s.norm() - 1.2e-3).abs() < 1e-5);
+
+    let efficiency_sigma = recon.efficiency_sigma();
+    assert!(efficiency_sigma < 5e-3, "efficiency sigma too large");
+    assert!(
+        (recon.efficiency() - 1.02).abs() < 3.0 * efficiency_sigma,
+        "efficiency {} not within 3 sigma of 1.02",
+        recon.efficiency()
+    );
+
+    let pointing_sigma_deg = recon.pointing_sigma_deg();
+    assert!(pointing_sigma_deg < 0.3, "pointing sigma too large");
+    assert!(
+        (recon.pointing_error_deg() - 1.0).abs() < 3.0 * pointing_sigma_deg,
+        "pointing error {} deg not within 3 sigma of 1 deg",
+        recon.pointing_error_deg()
+    );
+
+    // The estimates must bracket the window
+    assert!(reconstructor
+        .reconstruct(&post_burn, &post_burn, &window, dynamics, almanac)
+        .is_err());
+}
@@ -1,16 +1,15 @@
 extern crate nyx_space as nyx;
 
 use anise::constants::celestial_objects::{EARTH, MOON, SUN};
-use anise::constants::frames::IAU_EARTH_FRAME;
+use anise::constants::frames::{IAU_EARTH_FRAME, IAU_MARS_FRAME, IAU_MOON_FRAME, MOON_J2000};
 use anise::constants::usual_planetary_constants::MEAN_EARTH_ANGULAR_VELOCITY_DEG_S;
 use nyx::cosmic::Orbit;
 use nyx::dynamics::SpacecraftDynamics;
+use nyx::mc::NyxRng;
 use nyx::od::prelude::*;
 use nyx::time::Epoch;
 use nyx::{dynamics::OrbitalDynamics, propagators::Propagator};
 use nyx_space::propagators::IntegratorMethod;
-use rand::SeedableRng;
-use rand_pcg::Pcg64Mcg;
 
 use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
 use rstest::*;
@@ -167,7 +166,7 @@ fn val_measurements_topo(almanac: Arc<Almanac>) {
         },
     ];
 
-    let mut rng = Pcg64Mcg::from_entropy();
+    let mut rng = NyxRng::from_entropy();
     let mut traj1_msr_cnt = 0;
     for state in traj1.every(1 * Unit::Minute) {
         if dss65_madrid
@@ -269,3 +268,146 @@ fn val_measurements_topo(almanac: Arc<Almanac>) {
         );
     }
 }
+
+#[rstest]
+fn station_sez_state(almanac: Arc<Almanac>) {
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 2, 29);
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let station =
+        GroundStation::dss65_madrid(0.0, StochasticNoise::MIN, StochasticNoise::MIN, iau_earth);
+
+    // An object directly above the station is along the zenith axis of the SEZ frame.
+    let overhead = Orbit::try_latlongalt(
+        station.latitude_deg,
+        station.longitude_deg,
+        station.height_km + 500.0,
+        MEAN_EARTH_ANGULAR_VELOCITY_DEG_S,
+        epoch,
+        iau_earth,
+    )
+    .unwrap();
+
+    let sez = station.sez_state_of(overhead, &almanac).unwrap();
+    println!("overhead in SEZ: {sez}");
+    assert!(sez[0].abs() < 1e-9, "south component should be zero");
+    assert!(sez[1].abs() < 1e-9, "east component should be zero");
+    assert!(
+        (sez[2] - 500.0).abs() < 1e-9,
+        "zenith component should be the range"
+    );
+
+    // Its range must match the range computed by the almanac.
+    let aer = station
+        .azimuth_elevation_of(overhead, None, &almanac)
+        .unwrap();
+    assert!((aer.range_km - sez.fixed_rows::<3>(0).norm()).abs() < 1e-6);
+
+    // Converting to the SEZ frame and back preserves the state.
+    let rx = Orbit::keplerian(7000.0, 0.01, 51.6, 45.0, 30.0, 10.0, epoch, eme2k);
+    let sez = station.sez_state_of(rx, &almanac).unwrap();
+    let rx_back = station
+        .orbit_from_sez(&sez, epoch, eme2k, &almanac)
+        .unwrap();
+    println!("{rx}\n{rx_back}");
+
+    assert_eq!(rx_back.frame, eme2k);
+    assert!((rx.radius_km - rx_back.radius_km).norm() < 1e-8);
+    assert!((rx.velocity_km_s - rx_back.velocity_km_s).norm() < 1e-11);
+}
+
+/// Relay link between a lunar orbiter and a lander: the station lies on the surface of the Moon, in its body fixed frame.
+#[allow(clippy::identity_op)]
+#[rstest]
+fn lunar_lander_relay(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::*;
+
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let iau_moon = almanac.frame_from_uid(IAU_MOON_FRAME).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 3, 1);
+
+    let elevation_mask_deg = 5.0;
+    let mut lander = GroundStation::from_point("Lander".to_string(), 0.0, 0.0, 0.0, iau_moon);
+    lander.elevation_mask_deg = elevation_mask_deg;
+
+    // The lander is on the lunar surface, not the Earth's
+    let lander_radius_km = lander.to_orbit(epoch, &almanac).unwrap().rmag_km();
+    println!("{lander} @ {lander_radius_km:.3} km");
+    assert!((lander_radius_km - 1737.4).abs() < 1.0);
+
+    // Circular polar orbit 100 km above the lander, starting at its zenith
+    let sma_km = lander_radius_km + 100.0;
+    let v_km_s = (moon_j2k.mu_km3_s2().unwrap() / sma_km).sqrt();
+    let orbiter = almanac
+        .transform_to(
+            Orbit::cartesian(sma_km, 0.0, 0.0, 0.0, 0.0, v_km_s, epoch, iau_moon),
+            moon_j2k,
+            None,
+        )
+        .unwrap();
+    let period = orbiter.period().unwrap();
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbiter.into(), almanac.clone())
+        .for_duration_with_traj(period * 3)
+        .unwrap();
+
+    // Right above the lander: the elevation is relative to the local surface normal, and the range is the altitude.
+    let aer = lander
+        .azimuth_elevation_of(orbiter, None, &almanac)
+        .unwrap();
+    assert!(aer.elevation_deg > 89.0);
+    let msr = lander
+        .measure(epoch, &traj, None, almanac.clone())
+        .unwrap()
+        .unwrap();
+    println!("{msr:?}");
+    assert!((msr.obs[0] - 100.0).abs() < 0.1);
+
+    // The visibility windows are found in the body fixed frame of the lander
+    let traj_iau_moon = traj.to_frame(iau_moon, almanac.clone()).unwrap();
+    let arcs = traj_iau_moon.find_arcs(&&lander, almanac.clone()).unwrap();
+
+    // Central angle between the lander and the orbiter when the latter rises above the elevation mask
+    let mask_rad = elevation_mask_deg.to_radians();
+    let lambda_rad = (lander_radius_km * mask_rad.cos() / sma_km).acos() - mask_rad;
+    let expected_pass = period * (lambda_rad / std::f64::consts::PI);
+
+    let mut full_passes = 0;
+    for arc in &arcs {
+        let rise = arc.rise.state.epoch();
+        let fall = arc.fall.state.epoch();
+        println!("{rise} -> {fall} ({})", fall - rise);
+        for state in traj.every_between(
+            10 * Unit::Second,
+            rise + 1 * Unit::Second,
+            fall - 1 * Unit::Second,
+        ) {
+            let msr = lander
+                .measure(state.epoch(), &traj, None, almanac.clone())
+                .unwrap()
+                .expect("no measurement within the visibility window");
+            assert!((99.9..2000.0).contains(&msr.obs[0]), "{msr:?}");
+        }
+
+        if rise > traj.first().epoch() && fall < traj.last().epoch() {
+            full_passes += 1;
+            // The Moon barely rotates in a revolution, so the passes are almost overhead.
+            assert!(
+                ((fall - rise).to_seconds() - expected_pass.to_seconds()).abs()
+                    < 0.03 * expected_pass.to_seconds(),
+                "expected a pass of {expected_pass}"
+            );
+        }
+    }
+
+    assert_eq!(full_passes, 2, "expected one full pass per revolution");
+
+    // The rotation rate is only known for the Earth and the Moon: other bodies are rejected instead of treated as fixed.
+    assert!(lander.angular_velocity_deg_s().is_ok());
+    let mut martian = lander.clone();
+    martian.frame = IAU_MARS_FRAME;
+    assert!(martian.angular_velocity_deg_s().is_err());
+    assert!(martian.to_orbit(epoch, &almanac).is_err());
+}
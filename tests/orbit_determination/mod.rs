@@ -7,14 +7,25 @@ use nyx::Spacecraft;
 use self::nyx::od::prelude::{Estimate, Filter, KfEstimate, KF};
 use self::nyx::State;
 
+mod adaptive;
+mod delta_dor;
+mod maneuver_recon;
 mod measurements;
+mod model_mismatch;
 mod multi_body;
+mod process_noise;
+mod requirements;
 mod resid_reject;
 mod robust;
+mod setup;
 mod simulator;
 mod spacecraft;
+mod station_location;
+mod time_bias;
 mod trackingarc;
+mod truth;
 mod two_body;
+mod weighting;
 mod xhat_dev;
 
 use self::nyx::linalg::{Matrix2, SMatrix, Vector2};
@@ -0,0 +1,237 @@
+use anise::constants::celestial_objects::{MOON, SUN};
+use anise::constants::frames::{EARTH_J2000, IAU_EARTH_FRAME};
+use nyx_space::cosmic::Orbit;
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::dynamics::spacecraft::{SolarPressure, SpacecraftDynamics};
+use nyx_space::io::ExportCfg;
+use nyx_space::linalg::{Const, SMatrix, SVector};
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::{IntegratorMethod, IntegratorOptions, Propagator};
+use nyx_space::{Spacecraft, State};
+
+use anise::prelude::Almanac;
+use rstest::*;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Processes the measurements with the provided dynamics, starting from the true initial state, and analyzes the postfit residuals.
+fn postfit_mismatch(
+    arc: &TrackingArc<RangeDoppler>,
+    sc_init: Spacecraft,
+    dynamics: SpacecraftDynamics,
+    almanac: Arc<Almanac>,
+) -> ModelMismatchReport {
+    let setup = Propagator::new(
+        dynamics,
+        IntegratorMethod::RungeKutta4,
+        IntegratorOptions::with_fixed_step(10.0 * Unit::Second),
+    );
+    let prop_est = setup.with(sc_init.with_stm(), almanac.clone());
+
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1e-3_f64.powi(2),
+        1e-3_f64.powi(2),
+        1e-3_f64.powi(2),
+        1e-6_f64.powi(2),
+        1e-6_f64.powi(2),
+        1e-6_f64.powi(2),
+        0.0,
+        0.0,
+        0.0,
+    ]));
+
+    let ckf = KF::no_snc(KfEstimate::from_covar(sc_init.with_stm(), init_covar));
+
+    let mut odp = ODProcess::ckf(prop_est, ckf, None, almanac);
+    odp.process_arc::<GroundStation>(arc).unwrap();
+
+    odp.model_mismatch(sc_init.orbit.period().unwrap(), MismatchCriteria::default())
+        .unwrap()
+}
+
+#[rstest]
+fn od_model_mismatch_unmodeled_srp(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+    let prop_time = orbit.period().unwrap() * 3;
+
+    // Large area to mass ratio for a clear SRP signature
+    let sc_init = Spacecraft::from_srp_defaults(orbit, 100.0, 50.0);
+
+    let bodies = vec![MOON, SUN];
+    let truth_dynamics = SpacecraftDynamics::from_model(
+        OrbitalDynamics::point_masses(bodies.clone()),
+        SolarPressure::default(eme2k, almanac.clone()).unwrap(),
+    );
+
+    let (_, traj) = Propagator::new(
+        truth_dynamics.clone(),
+        IntegratorMethod::RungeKutta4,
+        IntegratorOptions::with_fixed_step(10.0 * Unit::Second),
+    )
+    .with(sc_init, almanac.clone())
+    .for_duration_with_traj(prop_time)
+    .unwrap();
+
+    // Noiseless simulated measurements, processed with the minimum noise
+    let sim_devices = vec![
+        GroundStation::dss65_madrid(0.0, StochasticNoise::ZERO, StochasticNoise::ZERO, iau_earth),
+        GroundStation::dss34_canberra(0.0, StochasticNoise::ZERO, StochasticNoise::ZERO, iau_earth),
+        GroundStation::dss13_goldstone(
+            0.0,
+            StochasticNoise::ZERO,
+            StochasticNoise::ZERO,
+            iau_earth,
+        ),
+    ];
+    let proc_devices = vec![
+        GroundStation::dss65_madrid(0.0, StochasticNoise::MIN, StochasticNoise::MIN, iau_earth),
+        GroundStation::dss34_canberra(0.0, StochasticNoise::MIN, StochasticNoise::MIN, iau_earth),
+        GroundStation::dss13_goldstone(0.0, StochasticNoise::MIN, StochasticNoise::MIN, iau_earth),
+    ];
+
+    let mut configs = BTreeMap::new();
+    let cfg = TrkConfig::builder()
+        .strands(vec![Strand {
+            start: epoch,
+            end: epoch + prop_time,
+        }])
+        .build();
+    for device in &sim_devices {
+        configs.insert(device.name.clone(), cfg.clone());
+    }
+
+    let mut arc_sim = TrackingArcSim::with_seed(sim_devices, traj, configs.clone(), 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let mut arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+    arc.set_devices(proc_devices, configs).unwrap();
+
+    // Well modeled: the same dynamics as the truth, the residuals are only numerical noise.
+    let well_modeled = postfit_mismatch(&arc, sc_init, truth_dynamics, almanac.clone());
+    println!("{well_modeled}");
+
+    assert!(!well_modeled.analyses.is_empty());
+    assert!(
+        !well_modeled.mismatch_detected(),
+        "no signature should be significant with the truth dynamics"
+    );
+    assert!(well_modeled.significant_signatures().is_empty());
+
+    // Mismodeled: SRP is missing from the estimation dynamics.
+    let no_srp = SpacecraftDynamics::new(OrbitalDynamics::point_masses(bodies));
+    let mismodeled = postfit_mismatch(&arc, sc_init, no_srp, almanac);
+    println!("{mismodeled}");
+
+    assert!(mismodeled.mismatch_detected());
+    assert!(mismodeled
+        .significant_signatures()
+        .contains(&ResidualSignature::OncePerRev));
+
+    let once_per_rev = mismodeled
+        .analyses
+        .iter()
+        .filter_map(|analysis| analysis.fit(ResidualSignature::OncePerRev))
+        .max_by(|a, b| a.snr.total_cmp(&b.snr))
+        .unwrap();
+
+    println!("strongest {once_per_rev}");
+    assert!(once_per_rev.significant);
+    assert!(once_per_rev.snr > 10.0);
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "od_model_mismatch.parquet",
+    ]
+    .iter()
+    .collect();
+
+    mismodeled.to_parquet(path, ExportCfg::default()).unwrap();
+}
+
+/// Residuals of a single tracker every 15 minutes over 12 hours, with the provided bias, a small alternating scatter, and a
+/// tracker noise standard deviation of 1 meter.
+fn biased_residuals(bias_km: f64) -> Vec<Option<Residual<Const<1>>>> {
+    let start = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    (0..48)
+        .map(|i| {
+            let mut resid = Residual::<Const<1>>::zeros();
+            resid.epoch = start + (15 * i) * Unit::Minute;
+            resid.postfit[0] = bias_km + if i % 2 == 0 { 1e-7 } else { -1e-7 };
+            resid.tracker_msr_noise[0] = 1e-3;
+            resid.rejected = false;
+            resid.tracker = Some("Station".to_string());
+            Some(resid)
+        })
+        .collect()
+}
+
+#[test]
+fn od_model_mismatch_noise_threshold() {
+    let components = vec!["Range (km)".to_string()];
+    let criteria = MismatchCriteria::default();
+
+    // A significant bias must exceed 10% of the noise standard deviation of 1 m, i.e. 10 cm.
+    let below = ModelMismatchReport::from_residuals(
+        &biased_residuals(5e-5),
+        &components,
+        6 * Unit::Hour,
+        criteria,
+    )
+    .unwrap();
+    println!("{below}");
+
+    let analysis = &below.analyses[0];
+    assert!((analysis.noise_sigma - 1e-3).abs() < 1e-15);
+
+    let bias = analysis.fit(ResidualSignature::Bias).unwrap();
+    assert!((bias.amplitude - 5e-5).abs() < 1e-8);
+    assert!(bias.snr > criteria.min_snr);
+    assert!(!bias.significant, "5 cm bias is below 10% of the 1 m noise");
+    assert!(!below.mismatch_detected());
+
+    let above = ModelMismatchReport::from_residuals(
+        &biased_residuals(2e-4),
+        &components,
+        6 * Unit::Hour,
+        criteria,
+    )
+    .unwrap();
+    println!("{above}");
+
+    assert!(
+        above.analyses[0]
+            .fit(ResidualSignature::Bias)
+            .unwrap()
+            .significant
+    );
+    assert_eq!(
+        above.significant_signatures(),
+        vec![ResidualSignature::Bias]
+    );
+
+    // Residuals which all share the same epoch cannot be fitted
+    let mut same_epoch = biased_residuals(2e-4);
+    for resid in same_epoch.iter_mut().flatten() {
+        resid.epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    }
+    assert!(ModelMismatchReport::from_residuals(
+        &same_epoch,
+        &components,
+        6 * Unit::Hour,
+        criteria
+    )
+    .is_err());
+}
@@ -0,0 +1,90 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::Orbit;
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::linalg::{Const, SMatrix, SVector};
+use nyx::od::prelude::*;
+use nyx::propagators::Propagator;
+use nyx::Spacecraft;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Checks that the process noise enlarges the propagated covariance between measurements, and that it grows with the
+/// acceleration sigma and decreases with the time constant of the unmodeled accelerations.
+#[rstest]
+fn process_noise_inflates_covariance(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6, 0.0, 0.0, 0.0,
+    ]));
+    let initial_estimate = KfEstimate::from_covar(Spacecraft::from(orbit).with_stm(), init_covar);
+
+    let configs = [
+        ProcessNoiseConfig::builder()
+            .accel_sigma_km_s2(1e-9)
+            .build(),
+        ProcessNoiseConfig::builder()
+            .accel_sigma_km_s2(1e-8)
+            .build(),
+        ProcessNoiseConfig::builder()
+            .accel_sigma_km_s2(1e-8)
+            .time_constant(30.seconds())
+            .build(),
+    ];
+
+    let mut filters: Vec<KF<Spacecraft, Const<3>, Const<2>>> = vec![KF::no_snc(initial_estimate)];
+    for config in &configs {
+        println!("{}", config.to_snc::<Const<3>>());
+        filters.push(KF::new(initial_estimate, config.to_snc()));
+    }
+
+    // Time updates every minute for half an hour, without any measurement
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let mut nominal = initial_estimate.nominal_state;
+    for _ in 0..30 {
+        nominal = setup
+            .with(nominal.with_stm(), almanac.clone())
+            .for_duration(1.minutes())
+            .unwrap();
+        for kf in &mut filters {
+            kf.time_update(nominal).unwrap();
+        }
+    }
+
+    let pos_var_km2 = filters
+        .iter()
+        .map(|kf| {
+            kf.previous_estimate()
+                .covar
+                .fixed_view::<3, 3>(0, 0)
+                .trace()
+        })
+        .collect::<Vec<f64>>();
+    println!("position variances (km^2): {pos_var_km2:?}");
+
+    let (no_noise, low_noise, high_noise, correlated) = (
+        pos_var_km2[0],
+        pos_var_km2[1],
+        pos_var_km2[2],
+        pos_var_km2[3],
+    );
+
+    // The process noise enlarges the propagated covariance, more so with a larger acceleration sigma
+    assert!(low_noise > no_noise);
+    assert!(high_noise > low_noise);
+    // Accelerations correlated over less than a time update partially average out
+    assert!(correlated > no_noise);
+    assert!(correlated < high_noise);
+}
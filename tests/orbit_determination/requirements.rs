@@ -0,0 +1,178 @@
+use anise::constants::frames::EARTH_J2000;
+use nyx_space::cosmic::Orbit;
+use nyx_space::dynamics::guidance::LocalFrame;
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::dynamics::SpacecraftDynamics;
+use nyx_space::io::ExportCfg;
+use nyx_space::linalg::{Matrix6, SMatrix, Vector6};
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::Propagator;
+use nyx_space::{Spacecraft, State};
+
+use anise::prelude::Almanac;
+use rstest::*;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn od_covariance_requirements(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let prop = Propagator::default(dynamics.clone());
+
+    let (_, traj) = prop
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(2.hours())
+        .unwrap();
+
+    // Synthetic covariance history, whose RIC sigmas shrink linearly from `ric_sigma` at the first estimate to 40% of it at the last.
+    let ric_sigma = Vector6::new(0.04, 0.1, 0.03, 1e-5, 1e-5, 1e-5);
+    let estimates = traj
+        .every(10.minutes())
+        .enumerate()
+        .map(|(k, sc)| {
+            let factor = 1.0 - 0.05 * k as f64;
+            let dcm = sc.orbit.dcm_from_ric_to_inertial().unwrap().state_dcm();
+            let ric_covar = Matrix6::from_diagonal(&ric_sigma.map(|s| (s * factor).powi(2)));
+            let mut covar = SMatrix::<f64, 9, 9>::zeros();
+            covar
+                .fixed_view_mut::<6, 6>(0, 0)
+                .copy_from(&(dcm * ric_covar * dcm.transpose()));
+
+            KfEstimate::from_covar(sc, covar)
+        })
+        .collect::<Vec<KfEstimate<Spacecraft>>>();
+    assert_eq!(estimates.len(), 13);
+
+    let ric_thresholds = UncertaintyThreshold::PerAxis([0.1, 0.5, 0.1]);
+    let mapped_epoch = estimates[6].epoch() + 7.minutes();
+
+    let requirements = [
+        // Met at delivery: 3-sigma of [0.048, 0.12, 0.036] km
+        CovarianceRequirement::builder()
+            .name("delivery")
+            .epochs(RequirementEpochs::At(estimates[12].epoch()))
+            .threshold(ric_thresholds)
+            .build(),
+        // Not met on the radial axis: 3-sigma of [0.108, 0.27, 0.081] km
+        CovarianceRequirement::builder()
+            .name("early")
+            .epochs(RequirementEpochs::At(estimates[2].epoch()))
+            .threshold(ric_thresholds)
+            .build(),
+        // Between two estimates
+        CovarianceRequirement::builder()
+            .name("mapped")
+            .epochs(RequirementEpochs::At(mapped_epoch))
+            .threshold(ric_thresholds)
+            .build(),
+        // Over the whole arc, only met towards its end
+        CovarianceRequirement::builder()
+            .name("arc")
+            .epochs(RequirementEpochs::Span {
+                start: estimates[0].epoch() + 3.minutes(),
+                end: estimates[12].epoch(),
+            })
+            .frame(LocalFrame::Inertial)
+            .threshold(UncertaintyThreshold::Norm(0.2))
+            .build(),
+    ];
+
+    let report =
+        RequirementsReport::new(&requirements, &estimates, dynamics, almanac.clone()).unwrap();
+    println!("{report}");
+    assert!(!report.passed());
+    assert_eq!(report.results.len(), 4);
+
+    let delivery = &report.results[0];
+    assert!(delivery.passed());
+    assert_eq!(delivery.evaluations.len(), 1);
+    let worst = delivery.worst();
+    assert_eq!(worst.mapped_from, None);
+    for (i, expected) in [0.048, 0.12, 0.036].iter().enumerate() {
+        assert!((worst.scaled_sigmas[i] - expected).abs() < 1e-9);
+    }
+    assert!((worst.margin - 0.052).abs() < 1e-9);
+    assert!((worst.margin_prct - 52.0).abs() < 1e-6);
+
+    let early = &report.results[1];
+    assert!(!early.passed());
+    assert!((early.worst().margin + 0.008).abs() < 1e-9);
+    assert!((early.worst().margin_prct + 8.0).abs() < 1e-6);
+
+    // The covariance of the nearest estimate, after the requested epoch, is mapped backward with the STM.
+    let mapped = &report.results[2];
+    let worst = mapped.worst();
+    assert_eq!(worst.epoch, mapped_epoch);
+    assert_eq!(worst.mapped_from, Some(estimates[7].epoch()));
+
+    let mapped_sc = prop
+        .with(estimates[7].state().with_stm(), almanac.clone())
+        .until_epoch(mapped_epoch)
+        .unwrap();
+    let stm = mapped_sc.orbit_stm().unwrap();
+    let covar = stm * estimates[7].covar.fixed_view::<6, 6>(0, 0) * stm.transpose();
+    let dcm = mapped_sc
+        .orbit
+        .dcm_from_ric_to_inertial()
+        .unwrap()
+        .state_dcm();
+    let ric_covar = dcm.transpose() * covar * dcm;
+    for i in 0..3 {
+        let expected = 3.0 * ric_covar[(i, i)].sqrt();
+        assert!((worst.scaled_sigmas[i] - expected).abs() < 1e-9);
+        // Three minutes of mapping changes the covariance of estimate #7
+        assert!((expected - 3.0 * ric_sigma[i] * 0.65).abs() > 1e-9);
+    }
+
+    // The span is evaluated at both of its bounds and at every estimate within, and the RSS of [0.04, 0.1, 0.03] km is
+    // above the threshold until the sigmas have shrunk below 59.6% of their initial values.
+    let arc = &report.results[3];
+    assert!(!arc.passed());
+    assert_eq!(arc.evaluations.len(), 13);
+    assert_eq!(arc.evaluations[0].mapped_from, Some(estimates[0].epoch()));
+    assert!(arc.evaluations[1..]
+        .iter()
+        .all(|eval| eval.mapped_from.is_none()));
+    assert_eq!(arc.worst().epoch, estimates[0].epoch() + 3.minutes());
+    assert_eq!(
+        arc.evaluations.iter().filter(|eval| eval.passed()).count(),
+        4
+    );
+    for eval in &arc.evaluations {
+        assert!((eval.scaled_sigmas.norm() - eval.scaled_rss).abs() < 1e-12);
+    }
+
+    // No estimates
+    assert!(RequirementsReport::new(
+        &requirements,
+        &Vec::<KfEstimate<Spacecraft>>::new(),
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        almanac
+    )
+    .is_err());
+
+    let output_dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data"].iter().collect();
+    report
+        .to_parquet(
+            output_dir.join("od_covariance_requirements.parquet"),
+            ExportCfg::default(),
+        )
+        .unwrap();
+    let csv_path = report
+        .to_csv(output_dir.join("od_covariance_requirements.csv"))
+        .unwrap();
+    let csv = std::fs::read_to_string(csv_path).unwrap();
+    assert_eq!(csv.lines().count(), 5);
+    assert!(csv.lines().nth(1).unwrap().ends_with("PASS"));
+    assert!(csv.lines().nth(2).unwrap().ends_with("FAIL"));
+}
@@ -3,8 +3,8 @@ use anise::constants::frames::{EARTH_J2000, IAU_EARTH_FRAME};
 use nyx_space::dynamics::guidance::LocalFrame;
 use pretty_env_logger::try_init;
 
+use nyx::mc::NyxRng;
 use rand_distr::Distribution;
-use rand_pcg::Pcg64Mcg;
 use rstest::*;
 
 use nyx_space::cosmic::Orbit;
@@ -148,7 +148,7 @@ fn initial_estimate(traj: Traj<Spacecraft>) -> KfEstimate<Spacecraft> {
 
     // Now, let's sample from this.
     let sc_gen = sc_estimate.to_random_variable().unwrap();
-    let mut rng = Pcg64Mcg::new(123); // Set the seed for reproducibility of test
+    let mut rng = NyxRng::from_seed(123); // Set the seed for reproducibility of test
     let mut initial_estimate = sc_estimate;
     initial_estimate.nominal_state = sc_gen.sample(&mut rng).state;
 
@@ -389,3 +389,71 @@ fn od_resid_reject_default_ckf_two_way(
     assert!(delta.velocity_km_s.y < est.covar[(4, 4)].sqrt());
     assert!(delta.velocity_km_s.z < est.covar[(5, 5)].sqrt());
 }
+
+#[rstest]
+fn mapped_range_residual_ric(epoch: Epoch, almanac: Arc<Almanac>) {
+    use nyx_space::linalg::{Const, Matrix1, SMatrix, SVector, Vector1, Vector3};
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let nominal = Spacecraft::from(Orbit::keplerian(
+        7000.0, 0.001, 45.0, 20.0, 10.0, 30.0, epoch, eme2k,
+    ))
+    .with_stm();
+
+    // Tracker on the surface, offset from the sub-satellite point, such that the line of sight is not radial.
+    let station_km =
+        nominal.orbit.radius_km.normalize() * 6378.0 + Vector3::new(500.0, -300.0, 200.0);
+    let los = (nominal.orbit.radius_km - station_km).normalize();
+    let range_km = (nominal.orbit.radius_km - station_km).norm();
+
+    // Range only sensitivity
+    let mut h_tilde = SMatrix::<f64, 1, 9>::zeros();
+    for i in 0..3 {
+        h_tilde[(0, i)] = los[i];
+    }
+
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6, 0.0, 0.0, 0.0,
+    ]));
+    let mut kf: KF<Spacecraft, Const<3>, Const<1>> =
+        KF::no_snc(KfEstimate::from_covar(nominal, init_covar));
+    kf.update_h_tilde(h_tilde);
+
+    let prefit_km = 0.05;
+    let (_, resid) = kf
+        .measurement_update(
+            nominal,
+            &Vector1::new(range_km + prefit_km),
+            &Vector1::new(range_km),
+            Matrix1::new(1e-6),
+            None,
+        )
+        .unwrap();
+
+    // In the integration frame, the mapped residual is the range residual along the line of sight.
+    let mapped = resid.mapped_prefit.unwrap();
+    assert!((mapped.fixed_rows::<3>(0) - prefit_km * los).norm() < 1e-12);
+    assert!(mapped.fixed_rows::<3>(3).norm() < 1e-15);
+
+    // In the RIC frame, it points along the line of sight expressed in the RIC frame.
+    let (ric_prefit, ric_postfit) = resid.mapped_in(LocalFrame::RIC, nominal.orbit).unwrap();
+    let dcm_inertial2ric = nominal
+        .orbit
+        .dcm_from_ric_to_inertial()
+        .unwrap()
+        .rot_mat
+        .transpose();
+    let los_ric = dcm_inertial2ric * los;
+    let ric_prefit_pos: Vector3<f64> = ric_prefit.unwrap().fixed_rows::<3>(0).into_owned();
+    println!("RIC prefit: {ric_prefit_pos}\tLOS in RIC: {los_ric}");
+    assert!(ric_prefit_pos.cross(&los_ric).norm() < 1e-12);
+    assert!(ric_prefit_pos.dot(&los_ric) > 0.0);
+    assert!((ric_prefit_pos.norm() - prefit_km).abs() < 1e-12);
+    // The line of sight is not radial, so the residual is not purely radial either.
+    assert!(ric_prefit_pos.y.abs() > 1e-3 || ric_prefit_pos.z.abs() > 1e-3);
+
+    // The postfit residual is smaller but along the same line of sight.
+    let ric_postfit_pos: Vector3<f64> = ric_postfit.unwrap().fixed_rows::<3>(0).into_owned();
+    assert!(ric_postfit_pos.norm() < ric_prefit_pos.norm());
+    assert!(ric_postfit_pos.cross(&los_ric).norm() < 1e-12);
+}
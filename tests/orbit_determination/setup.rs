@@ -0,0 +1,194 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::IAU_EARTH_FRAME;
+use nyx::cosmic::Orbit;
+use nyx::dynamics::{DynamicsPreset, PresetOptions};
+use nyx::io::ConfigRepr;
+use nyx::md::StateParameter;
+use nyx::od::prelude::*;
+use nyx::propagators::{IntegratorOptions, Propagator};
+use nyx::Spacecraft;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn od_setup_round_trip(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let devices = vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            StochasticNoise::default_range_km(),
+            StochasticNoise::default_doppler_km_s(),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            StochasticNoise::default_range_km(),
+            StochasticNoise::default_doppler_km_s(),
+            iau_earth,
+        ),
+    ];
+
+    let configs: BTreeMap<String, TrkConfig> = devices
+        .iter()
+        .map(|device| {
+            (
+                device.name.clone(),
+                TrkConfig::from_sample_rate(60.seconds()),
+            )
+        })
+        .collect();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let truth_state = Spacecraft::from(Orbit::keplerian(
+        22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k,
+    ));
+
+    // Simulate the tracking with a fixed seed using the same dynamics as the filter
+    let opts = IntegratorOptions::with_fixed_step(30.seconds());
+    let dynamics = DynamicsPreset::Geo
+        .build(
+            almanac.clone(),
+            PresetOptions::builder().harmonics_degree(2).build(),
+        )
+        .unwrap();
+    let (_, traj) = Propagator::rk89(dynamics, opts)
+        .with(truth_state, almanac.clone())
+        .for_duration_with_traj(6 * Unit::Hour)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(devices.clone(), traj, configs, 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+
+    let output_dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data"].iter().collect();
+    let arc_path = arc
+        .to_parquet_simple(output_dir.join("od_setup_arc.parquet"))
+        .unwrap();
+
+    let mut initial_state = truth_state;
+    initial_state.orbit.radius_km.x += 0.5;
+    initial_state.orbit.velocity_km_s.y -= 5e-5;
+
+    let setup = OdSetupSerde {
+        arc_path: "od_setup_arc.parquet".to_string(),
+        arc_crc32: OdSetupSerde::arc_checksum(&arc_path).unwrap(),
+        initial_state,
+        solve_for: vec![
+            SolveForSerde {
+                param: StateParameter::X,
+                sigma: 1.0,
+            },
+            SolveForSerde {
+                param: StateParameter::Y,
+                sigma: 1.0,
+            },
+            SolveForSerde {
+                param: StateParameter::Z,
+                sigma: 1.0,
+            },
+            SolveForSerde {
+                param: StateParameter::VX,
+                sigma: 1e-3,
+            },
+            SolveForSerde {
+                param: StateParameter::VY,
+                sigma: 1e-3,
+            },
+            SolveForSerde {
+                param: StateParameter::VZ,
+                sigma: 1e-3,
+            },
+            SolveForSerde {
+                param: StateParameter::Cr,
+                sigma: 0.1,
+            },
+        ],
+        dynamics: DynamicsPreset::Geo,
+        harmonics_degree: Some(2),
+        harmonics_order: None,
+        integrator: Some(opts),
+        devices: devices
+            .iter()
+            .map(|device| (device.name.clone(), device.clone()))
+            .collect(),
+        measurement_weights: BTreeMap::from([("Canberra".to_string(), 2.0)]),
+        resid_crit: Some(ResidRejectCrit::default()),
+        snc: Some(SncSerde {
+            disable_time: 2 * Unit::Minute,
+            diagonal: [1e-16; 3],
+        }),
+        ekf: Some(EkfTriggerSerde {
+            num_msrs: 100,
+            disable_time: 10 * Unit::Minute,
+        }),
+        estimates_path: Some("od_setup_estimates.parquet".to_string()),
+    };
+
+    // Round trip through the YAML file
+    let setup_path = output_dir.join("od_setup.yaml");
+    setup.save(&setup_path).unwrap();
+    let loaded = OdSetupSerde::load(&setup_path).unwrap();
+    assert_eq!(loaded, setup);
+
+    // Running from the file reproduces the estimates of the in-memory setup exactly
+    let expected = setup
+        .relative_to(&output_dir)
+        .build(almanac.clone())
+        .unwrap()
+        .run()
+        .unwrap();
+    let estimates = run_from_setup(&setup_path, almanac.clone()).unwrap();
+
+    assert!(!estimates.is_empty());
+    assert_eq!(estimates.len(), expected.len());
+    for (est, exp) in estimates.iter().zip(expected.iter()) {
+        assert_eq!(est.epoch(), exp.epoch());
+        assert_eq!(est.nominal_state.orbit, exp.nominal_state.orbit);
+        assert_eq!(est.covar, exp.covar);
+    }
+    assert!(output_dir.join("od_setup_estimates.parquet").exists());
+
+    // Validation catches inconsistent setups
+    let base = setup.relative_to(&output_dir);
+
+    let mut bad_checksum = base.clone();
+    bad_checksum.arc_crc32 = bad_checksum.arc_crc32.wrapping_add(1);
+    assert!(bad_checksum.build(almanac.clone()).is_err());
+
+    // The GEO preset has no drag, so the drag coefficient cannot be solved for
+    let mut bad_solve_for = base.clone();
+    bad_solve_for.solve_for.push(SolveForSerde {
+        param: StateParameter::Cd,
+        sigma: 0.1,
+    });
+    assert!(bad_solve_for.build(almanac.clone()).is_err());
+
+    let mut missing_orbit = base.clone();
+    missing_orbit.solve_for.remove(0);
+    assert!(missing_orbit.build(almanac.clone()).is_err());
+
+    let mut bad_weight = base.clone();
+    bad_weight
+        .measurement_weights
+        .insert("Goldstone".to_string(), 1.5);
+    assert!(bad_weight.build(almanac.clone()).is_err());
+
+    let mut missing_device = base;
+    missing_device.devices.remove("Madrid");
+    assert!(missing_device.build(almanac).is_err());
+}
@@ -0,0 +1,130 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::{EARTH_J2000, IAU_EARTH_FRAME};
+use anise::prelude::Almanac;
+use nyx::cosmic::Orbit;
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::od::noise::WhiteNoise;
+use nyx::od::prelude::*;
+use nyx::od::simulator::{TrackingArcSim, TrkConfig};
+use nyx::propagators::Propagator;
+
+use rstest::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Simulates range and Doppler measurements from a station located 10 m east of its a priori location, with a perfect
+/// trajectory, and checks that the estimation recovers this offset within its formal uncertainty.
+#[rstest]
+fn station_location_offset_east(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let white = |sigma: f64| StochasticNoise {
+        white_noise: Some(WhiteNoise { mean: 0.0, sigma }),
+        ..Default::default()
+    };
+    let apriori = GroundStation::dss65_madrid(10.0, white(5e-3), white(5e-6), iau_earth);
+
+    let offset_east_km = 0.01;
+    let truth_station = apriori
+        .with_offset_km(
+            offset_east_km * StationComponent::East.direction(&apriori),
+            &almanac,
+        )
+        .unwrap();
+    assert!(truth_station.longitude_deg > apriori.longitude_deg);
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    let mut configs = BTreeMap::new();
+    configs.insert(
+        apriori.name.clone(),
+        TrkConfig::builder().sampling(1.minutes()).build(),
+    );
+
+    let mut arc_sim =
+        TrackingArcSim::with_seed(vec![truth_station], traj.clone(), configs, 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+    println!("{arc}");
+
+    // Estimate the three local components of the station location
+    let solver = StationLocationSolver::builder()
+        .solve_for(vec![StationSolveFor::builder()
+            .station(apriori.clone())
+            .components(vec![
+                StationComponent::East,
+                StationComponent::North,
+                StationComponent::Up,
+            ])
+            .sigma_km(0.1)
+            .build()])
+        .build();
+
+    let estimates = solver.solve(&arc, &traj, almanac.clone()).unwrap();
+    assert_eq!(estimates.len(), 1);
+    let estimate = &estimates[0];
+    println!("{estimate}");
+
+    // Measurements at the elevation mask may be below it at the a priori location
+    assert!(estimate.num_msrs > arc.measurements.len() * 9 / 10);
+    let expected_km = [offset_east_km, 0.0, 0.0];
+    for i in 0..3 {
+        assert!(
+            estimate.sigmas_km[i] < 5e-3,
+            "{} poorly observed",
+            estimate.components[i]
+        );
+        assert!(
+            (estimate.corrections_km[i] - expected_km[i]).abs() < 3.0 * estimate.sigmas_km[i],
+            "{} correction of {:.3} m does not match {:.3} m within 3 sigma",
+            estimate.components[i],
+            estimate.corrections_km[i] * 1e3,
+            expected_km[i] * 1e3
+        );
+    }
+    // The post-fit residuals are at the level of the noise
+    assert!(estimate.residual_rms[0] < 2.0 * 5e-3);
+    assert!((estimate.estimated.longitude_deg - truth_station.longitude_deg).abs() < 1e-6);
+
+    // Pinning all but the up component: the correction is along the local vertical only
+    let pinned = StationLocationSolver::builder()
+        .solve_for(vec![StationSolveFor::builder()
+            .station(apriori.clone())
+            .components(vec![StationComponent::Up])
+            .sigma_km(0.1)
+            .build()])
+        .build()
+        .solve(&arc, &traj, almanac.clone())
+        .unwrap();
+    println!("{}", pinned[0]);
+
+    let up = StationComponent::Up.direction(&apriori);
+    assert_eq!(pinned[0].corrections_km.len(), 1);
+    assert!((pinned[0].offset_km - pinned[0].corrections_km[0] * up).norm() < 1e-12);
+    assert!(
+        pinned[0]
+            .offset_km
+            .dot(&StationComponent::East.direction(&apriori))
+            .abs()
+            < 1e-12
+    );
+    assert!(
+        (pinned[0].offset_covar_km2 * StationComponent::East.direction(&apriori)).norm() < 1e-15
+    );
+}
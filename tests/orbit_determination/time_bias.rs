@@ -0,0 +1,101 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::{EARTH_J2000, IAU_EARTH_FRAME};
+use anise::prelude::Almanac;
+use nyx::cosmic::Orbit;
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::od::noise::WhiteNoise;
+use nyx::od::prelude::*;
+use nyx::od::simulator::{TrackingArcSim, TrkConfig};
+use nyx::propagators::Propagator;
+
+use rstest::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Simulates a Doppler arc whose time tags are 50 ms late, with a perfect trajectory, and checks that the estimation recovers
+/// this bias within its formal uncertainty.
+#[rstest]
+fn time_tag_bias_doppler(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let white = |sigma: f64| StochasticNoise {
+        white_noise: Some(WhiteNoise { mean: 0.0, sigma }),
+        ..Default::default()
+    };
+    // The range noise is such that the bias is only observed from the Doppler data
+    let station = GroundStation::dss65_madrid(10.0, white(1e3), white(5e-7), iau_earth);
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    let mut configs = BTreeMap::new();
+    configs.insert(
+        station.name.clone(),
+        TrkConfig::builder().sampling(1.minutes()).build(),
+    );
+
+    let mut arc_sim =
+        TrackingArcSim::with_seed(vec![station.clone()], traj.clone(), configs, 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let mut arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+
+    let bias_s = 0.05;
+    for (_, msr) in arc.measurements.iter_mut() {
+        msr.set_epoch(msr.epoch() + bias_s * Unit::Second);
+    }
+
+    let mut devices = BTreeMap::new();
+    devices.insert(station.name.clone(), station.clone());
+
+    let solver = TimeTagBiasSolver::builder()
+        .devices(vec![station.name.clone()])
+        .build();
+
+    let estimates = solver
+        .solve(&arc, &mut devices, &traj, almanac.clone())
+        .unwrap();
+    assert_eq!(estimates.len(), 1);
+    let estimate = &estimates[0];
+    println!("{estimate}");
+
+    assert!(estimate.num_msrs > arc.measurements.len() * 9 / 10);
+    assert!(estimate.sigma_s < 5e-3, "bias poorly observed");
+    assert!(
+        (estimate.bias_s - bias_s).abs() < 3.0 * estimate.sigma_s,
+        "bias of {:.3} ms does not match {:.3} ms within 3 sigma",
+        estimate.bias_s * 1e3,
+        bias_s * 1e3
+    );
+    // The post-fit residuals are at the level of the noise
+    assert!(estimate.normalized_residual_rms < 2.0);
+
+    // Once corrected, the arc has no bias left
+    estimate.correct(&mut arc);
+    let corrected = solver
+        .solve(&arc, &mut devices, &traj, almanac.clone())
+        .unwrap();
+    println!("{}", corrected[0]);
+    assert!(corrected[0].bias_s.abs() < 3.0 * corrected[0].sigma_s);
+
+    // Unknown device
+    assert!(TimeTagBiasSolver::builder()
+        .devices(vec!["Unknown".to_string()])
+        .build()
+        .solve(&arc, &mut devices, &traj, almanac)
+        .is_err());
+}
@@ -1,6 +1,7 @@
 use anise::constants::frames::{EARTH_J2000, IAU_EARTH_FRAME};
 use nyx_space::io::tracking_data::DynamicTrackingArc;
 use nyx_space::io::ConfigRepr;
+use nyx_space::linalg::Vector2;
 use nyx_space::md::prelude::*;
 use nyx_space::od::msr::RangeDoppler;
 use nyx_space::od::prelude::*;
@@ -192,6 +193,59 @@ fn trk_simple(traj: Traj<Spacecraft>, devices: Vec<GroundStation>, almanac: Arc<
     assert_eq!(arc_concrete.device_cfg, arc.device_cfg);
 }
 
+/// Tests that differencing an arc against a biased copy recovers the bias
+#[rstest]
+fn trk_residuals_against(
+    traj: Traj<Spacecraft>,
+    devices: Vec<GroundStation>,
+    almanac: Arc<Almanac>,
+) {
+    let trkconfg_yaml: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "config",
+        "tracking_cfg.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let configs: BTreeMap<String, TrkConfig> = TrkConfig::load_named(trkconfg_yaml).unwrap();
+
+    let mut trk =
+        TrackingArcSim::<Spacecraft, RangeDoppler, _>::with_seed(devices, traj, configs, 12345)
+            .unwrap();
+    trk.build_schedule(almanac.clone()).unwrap();
+    let arc = trk.generate_measurements(almanac).unwrap();
+
+    // Build a biased copy, time tagged slightly later than the original
+    let bias = Vector2::new(0.25, -1e-3);
+    let mut biased = arc.clone();
+    for (_, msr) in biased.measurements.iter_mut() {
+        msr.epoch += 50.milliseconds();
+        msr.obs -= bias;
+    }
+
+    let residuals = arc.residuals_against(&biased, 1.seconds());
+    assert_eq!(residuals.len(), arc.measurements.len());
+    for ((name, epoch, resid), (orig_name, orig_msr)) in residuals.iter().zip(&arc.measurements) {
+        assert_eq!(name, orig_name);
+        assert_eq!(*epoch, orig_msr.epoch());
+        assert!((resid[0] - bias[0]).abs() < 1e-9);
+        assert!((resid[1] - bias[1]).abs() < 1e-12);
+    }
+
+    // Measurements outside of the tolerance are not matched
+    assert!(arc.residuals_against(&biased, 10.milliseconds()).is_empty());
+
+    // Measurements of different devices are not matched
+    let mut renamed = biased.clone();
+    for (name, _) in renamed.measurements.iter_mut() {
+        *name = format!("not {name}");
+    }
+    assert!(arc.residuals_against(&renamed, 1.seconds()).is_empty());
+}
+
 /// Tests that inclusion epochs work
 #[rstest]
 fn trkconfig_zero_inclusion(
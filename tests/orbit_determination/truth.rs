@@ -0,0 +1,148 @@
+use anise::constants::frames::{EARTH_J2000, MOON_J2000};
+use nyx_space::cosmic::Orbit;
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::dynamics::SpacecraftDynamics;
+use nyx_space::io::ExportCfg;
+use nyx_space::linalg::{Matrix6, SMatrix, Vector6};
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::Propagator;
+use nyx_space::{Spacecraft, State};
+
+use anise::prelude::Almanac;
+use nyx::mc::NyxRng;
+use rand_distr::{Distribution, Normal};
+use rstest::*;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Builds estimates whose RIC errors with respect to the truth are drawn with the provided sigmas, but whose covariance
+/// reports the provided sigmas scaled by `covar_sigma_scale`.
+fn estimates_from_truth(
+    truth: &[Spacecraft],
+    ric_sigma: Vector6<f64>,
+    covar_sigma_scale: f64,
+) -> Vec<KfEstimate<Spacecraft>> {
+    let mut rng = NyxRng::from_seed(0);
+    let unit = Normal::new(0.0, 1.0).unwrap();
+
+    truth
+        .iter()
+        .map(|sc| {
+            let dcm = sc.orbit.dcm_from_ric_to_inertial().unwrap().state_dcm();
+            let ric_error = Vector6::from_fn(|i, _| ric_sigma[i] * unit.sample(&mut rng));
+            let delta = dcm * ric_error;
+
+            let mut orbit = sc.orbit;
+            orbit.radius_km += delta.fixed_rows::<3>(0);
+            orbit.velocity_km_s += delta.fixed_rows::<3>(3);
+
+            let ric_covar =
+                Matrix6::from_diagonal(&ric_sigma.map(|s| (s * covar_sigma_scale).powi(2)));
+            let mut covar = SMatrix::<f64, 9, 9>::zeros();
+            covar
+                .fixed_view_mut::<6, 6>(0, 0)
+                .copy_from(&(dcm * ric_covar * dcm.transpose()));
+
+            KfEstimate::from_covar(sc.with_orbit(orbit), covar)
+        })
+        .collect()
+}
+
+#[rstest]
+fn od_truth_comparison(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let (_, truth) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(2.hours())
+        .unwrap();
+
+    // The estimates extend ten minutes past the truth, these must be ignored
+    let mut samples = truth.every(30.seconds()).collect::<Vec<Spacecraft>>();
+    for minutes in 1..=10_i64 {
+        let mut late = *truth.last();
+        late.set_epoch(late.epoch() + minutes.minutes());
+        samples.push(late);
+    }
+
+    let ric_sigma = Vector6::new(0.1, 0.5, 0.05, 1e-4, 5e-5, 5e-5);
+
+    // Covariance matching the actual errors
+    let consistent = TruthComparison::new(
+        &estimates_from_truth(&samples, ric_sigma, 1.0),
+        &truth,
+        almanac.clone(),
+    )
+    .unwrap();
+    println!("{consistent}");
+    assert_eq!(consistent.epochs.len(), samples.len() - 10);
+    assert_eq!(consistent.verdict, CovarianceConsistency::Consistent);
+    for i in 0..6 {
+        assert!((consistent.rms_error[i] / ric_sigma[i] - 1.0).abs() < 0.2);
+        assert!(consistent.within_1sigma_prct[i] > 55.0);
+        assert!(consistent.within_2sigma_prct[i] > 90.0);
+        assert!(consistent.within_3sigma_prct[i] > 98.0);
+    }
+
+    // Deliberately optimistic covariance: the formal sigmas are ten times too small
+    let optimistic = TruthComparison::new(
+        &estimates_from_truth(&samples, ric_sigma, 0.1),
+        &truth,
+        almanac.clone(),
+    )
+    .unwrap();
+    println!("{optimistic}");
+    assert_eq!(optimistic.verdict, CovarianceConsistency::Optimistic);
+    for i in 0..6 {
+        assert!(optimistic.within_3sigma_prct[i] < 50.0);
+        assert!(optimistic.rms_sigma_ratio[i] > 5.0);
+    }
+
+    // And a pessimistic one
+    let pessimistic = TruthComparison::new(
+        &estimates_from_truth(&samples, ric_sigma, 10.0),
+        &truth,
+        almanac.clone(),
+    )
+    .unwrap();
+    println!("{pessimistic}");
+    assert_eq!(pessimistic.verdict, CovarianceConsistency::Pessimistic);
+
+    // A truth in another frame is converted into the frame of the estimates
+    let moon_truth = truth
+        .to_frame(almanac.frame_from_uid(MOON_J2000).unwrap(), almanac.clone())
+        .unwrap();
+    let from_moon = TruthComparison::new(
+        &estimates_from_truth(&samples, ric_sigma, 0.1),
+        &moon_truth,
+        almanac.clone(),
+    )
+    .unwrap();
+    assert_eq!(from_moon.verdict, CovarianceConsistency::Optimistic);
+    assert!((from_moon.rms_error - optimistic.rms_error).norm() < 1e-6);
+
+    // No overlap with the truth
+    assert!(TruthComparison::new(
+        &estimates_from_truth(&samples[samples.len() - 5..], ric_sigma, 1.0),
+        &truth,
+        almanac
+    )
+    .is_err());
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "od_truth_comparison.parquet",
+    ]
+    .iter()
+    .collect();
+    optimistic.to_parquet(path, ExportCfg::default()).unwrap();
+}
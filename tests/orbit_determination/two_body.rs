@@ -1157,3 +1157,334 @@ fn od_tb_ckf_fixed_step_perfect_stations_several_snc_covar_map(
     assert!(delta.rmag_km() < 2e-16, "Position error should be zero");
     assert!(delta.vmag_km_s() < 2e-16, "Velocity error should be zero");
 }
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn od_tb_ckf_estimate_cadence(
+    almanac: Arc<Almanac>,
+    sim_devices: Vec<GroundStation>,
+    proc_devices: Vec<GroundStation>,
+) {
+    let _ = pretty_env_logger::try_init();
+
+    let cfg = TrkConfig::builder()
+        .sampling(10.seconds())
+        .scheduler(Scheduler::builder().sample_alignment(10.seconds()).build())
+        .build();
+
+    let mut configs = BTreeMap::new();
+    for device in &sim_devices {
+        configs.insert(device.name.clone(), cfg.clone());
+    }
+
+    let prop_time = 6 * Unit::Hour;
+    let opts = IntegratorOptions::with_fixed_step(10.0 * Unit::Second);
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let orbital_dyn = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::new(orbital_dyn, IntegratorMethod::RungeKutta4, opts);
+    let (_, traj) = setup
+        .with(initial_state.into(), almanac.clone())
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(sim_devices, traj, configs.clone(), 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let mut arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+    arc.set_devices(proc_devices, configs).unwrap();
+
+    let initial_state_est = Spacecraft::from(initial_state).with_stm();
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6, 0.0, 0.0, 0.0,
+    ]));
+    let initial_estimate = KfEstimate::from_covar(initial_state_est, init_covar);
+
+    // Full storage as the reference
+    let mut odp = ODProcess::ckf(
+        setup.with(initial_state_est, almanac.clone()),
+        KF::no_snc(initial_estimate),
+        None,
+        almanac.clone(),
+    );
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+    odp.predict_for(1.minutes(), 1 * Unit::Hour).unwrap();
+
+    let num_msr_updates = odp.residuals.iter().flatten().count();
+    let full_last = odp.estimates.last().unwrap().clone();
+    println!(
+        "{} estimates with {num_msr_updates} measurement updates",
+        odp.estimates.len()
+    );
+
+    // Every 10th measurement
+    let mut odp_msr = ODProcess::ckf(
+        setup.with(initial_state_est, almanac.clone()),
+        KF::no_snc(initial_estimate),
+        None,
+        almanac.clone(),
+    )
+    .with_cadence(EstimateCadence::Measurements(10));
+    odp_msr.process_arc::<GroundStation>(&arc).unwrap();
+
+    // The last estimate of the arc is stored even if it isn't the 10th measurement, but no time update is stored
+    let expected = num_msr_updates.div_ceil(10);
+    assert_eq!(odp_msr.estimates.len(), expected);
+    assert_eq!(odp_msr.residuals.len(), expected);
+    assert!(odp_msr.residuals.iter().all(|resid| resid.is_some()));
+
+    // The prediction only stores its last estimate
+    odp_msr.predict_for(1.minutes(), 1 * Unit::Hour).unwrap();
+    assert_eq!(odp_msr.estimates.len(), expected + 1);
+    assert_eq!(odp_msr.estimates.last().unwrap(), &full_last);
+
+    // Every ten minutes
+    let interval = 10 * Unit::Minute;
+    let mut odp_dt = ODProcess::ckf(
+        setup.with(initial_state_est, almanac.clone()),
+        KF::no_snc(initial_estimate),
+        None,
+        almanac.clone(),
+    )
+    .with_cadence(EstimateCadence::Interval(interval));
+    odp_dt.process_arc::<GroundStation>(&arc).unwrap();
+    odp_dt.predict_for(1.minutes(), 1 * Unit::Hour).unwrap();
+
+    assert_eq!(odp_dt.estimates.len(), odp_dt.residuals.len());
+    let max_stored = (prop_time + 1 * Unit::Hour).to_seconds() / interval.to_seconds();
+    assert!(odp_dt.estimates.len() <= max_stored as usize + 3);
+    // Only the last estimates of the arc and of the prediction may be closer than the interval to the previous one
+    let num_short = odp_dt
+        .estimates
+        .windows(2)
+        .filter(|pair| pair[1].epoch() - pair[0].epoch() < interval)
+        .count();
+    assert!(num_short <= 2, "{num_short} estimates stored too close");
+    assert_eq!(odp_dt.estimates.last().unwrap(), &full_last);
+
+    // Smoothing requires all of the estimates
+    assert!(odp_dt.smooth(SmoothingArc::All).is_err());
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn od_tb_ckf_estimate_at(
+    almanac: Arc<Almanac>,
+    sim_devices: Vec<GroundStation>,
+    proc_devices: Vec<GroundStation>,
+) {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+
+    let cfg = TrkConfig::builder()
+        .sampling(1.minutes())
+        .scheduler(Scheduler::builder().sample_alignment(1.minutes()).build())
+        .build();
+
+    let mut configs = BTreeMap::new();
+    for device in &sim_devices {
+        configs.insert(device.name.clone(), cfg.clone());
+    }
+
+    let prop_time = 6 * Unit::Hour;
+    let opts = IntegratorOptions::with_fixed_step(10.0 * Unit::Second);
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let orbital_dyn = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::new(orbital_dyn, IntegratorMethod::RungeKutta4, opts);
+    let (_, traj) = setup
+        .with(initial_state.into(), almanac.clone())
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(sim_devices, traj, configs.clone(), 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let mut arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+    arc.set_devices(proc_devices, configs).unwrap();
+
+    let initial_state_est = Spacecraft::from(initial_state).with_stm();
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6, 0.0, 0.0, 0.0,
+    ]));
+    let initial_estimate = KfEstimate::from_covar(initial_state_est, init_covar);
+
+    let mut odp = ODProcess::ckf(
+        setup.with(initial_state_est, almanac.clone()),
+        KF::no_snc(initial_estimate),
+        None,
+        almanac.clone(),
+    );
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+    odp.predict_for(7.minutes(), 1 * Unit::Hour).unwrap();
+
+    let first_epoch = odp.estimates[0].epoch();
+    let last_epoch = odp.estimates.last().unwrap().epoch();
+
+    // Outside of the arc of the estimates
+    assert!(odp.estimate_at(first_epoch - 1.seconds()).is_err());
+    assert!(odp.estimate_at(last_epoch + 1.seconds()).is_err());
+
+    for pair in odp.estimates.windows(2) {
+        // The actual estimates are reproduced exactly
+        assert_eq!(odp.estimate_at(pair[0].epoch()).unwrap(), pair[0]);
+
+        if pair[1].epoch() - pair[0].epoch() < 2.seconds() {
+            continue;
+        }
+
+        // Mid-interval, the covariance is mapped with the STM and remains positive definite
+        let mid_epoch = pair[0].epoch() + (pair[1].epoch() - pair[0].epoch()) * 0.5;
+        let mid = odp.estimate_at(mid_epoch).unwrap();
+        assert_eq!(mid.epoch(), mid_epoch);
+        assert!(mid.predicted);
+
+        let orbit_covar = mid.covar.fixed_view::<6, 6>(0, 0).into_owned();
+        assert!(
+            (orbit_covar - orbit_covar.transpose()).norm() <= 1e-12 * orbit_covar.norm(),
+            "covariance not symmetric @ {mid_epoch}"
+        );
+        assert!(
+            orbit_covar.cholesky().is_some(),
+            "covariance not positive definite @ {mid_epoch}"
+        );
+    }
+    assert_eq!(
+        odp.estimate_at(last_epoch).unwrap(),
+        *odp.estimates.last().unwrap()
+    );
+
+    // Export on the hour
+    let start_epoch = first_epoch.ceil(1 * Unit::Hour);
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "tb_ckf_hourly.parquet",
+    ]
+    .iter()
+    .collect();
+
+    let exported = odp
+        .to_parquet(
+            path,
+            ExportCfg::builder()
+                .start_epoch(start_epoch)
+                .end_epoch(last_epoch)
+                .step(1 * Unit::Hour)
+                .build(),
+        )
+        .unwrap();
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(exported).unwrap()).unwrap();
+    assert_eq!(
+        reader.metadata().file_metadata().num_rows() as usize,
+        ((last_epoch - start_epoch).to_seconds() / 3600.0) as usize + 1
+    );
+}
+
+#[rstest]
+fn od_tb_export_non_finite_covar(almanac: Arc<Almanac>) {
+    use arrow::array::{Array, Float64Array};
+    use nyx::md::StateParameter;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let initial_state_est = Spacecraft::from(initial_state).with_stm();
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6, 0.0, 0.0, 0.0,
+    ]));
+    let initial_estimate = KfEstimate::from_covar(initial_state_est, init_covar);
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let mut odp: SpacecraftODProcess = ODProcess::ckf(
+        setup.with(initial_state_est, almanac.clone()),
+        KF::no_snc(initial_estimate),
+        None,
+        almanac.clone(),
+    );
+
+    // Mimic a filter which diverged on the X variance at the second estimate.
+    let mut diverged = initial_estimate;
+    diverged.covar[(0, 0)] = f64::NAN;
+    odp.estimates = vec![initial_estimate, diverged];
+    odp.residuals = vec![None, None];
+
+    // Only the SMA is exported with its uncertainty, which depends on the X variance.
+    // Hence, the NaN is exported in the X*X covariance, the X sigma, the SMA sigma, and all of the RIC sigmas.
+    let expected_cnt = 1 + 1 + 1 + 6;
+    let fields = vec![
+        StateParameter::X,
+        StateParameter::Y,
+        StateParameter::Z,
+        StateParameter::VX,
+        StateParameter::VY,
+        StateParameter::VZ,
+        StateParameter::SMA,
+    ];
+
+    for sentinel in [Some(-999.0), None] {
+        let path: PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "output_data",
+            "tb_non_finite_covar.parquet",
+        ]
+        .iter()
+        .collect();
+
+        let cfg = match sentinel {
+            Some(sentinel) => ExportCfg::builder()
+                .fields(fields.clone())
+                .covar_sentinel(sentinel)
+                .build(),
+            None => ExportCfg::builder().fields(fields.clone()).build(),
+        };
+        let exported = odp.to_parquet(path, cfg).unwrap();
+
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(exported).unwrap()).unwrap();
+        let exported_cnt = builder
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == NON_FINITE_COVAR_KEY)
+            .and_then(|kv| kv.value.as_ref())
+            .map(|cnt| cnt.parse::<usize>().unwrap())
+            .unwrap();
+        assert_eq!(exported_cnt, expected_cnt, "sentinel {sentinel:?}");
+
+        let batch = builder.build().unwrap().next().unwrap().unwrap();
+        let mut flagged = [0, 0];
+        for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+            if !field.name().starts_with("Covariance") && !field.name().starts_with("Sigma") {
+                continue;
+            }
+            let values = column.as_any().downcast_ref::<Float64Array>().unwrap();
+            for row in 0..2 {
+                let is_flagged = match sentinel {
+                    Some(sentinel) => values.value(row) == sentinel,
+                    None => values.is_null(row),
+                };
+                if is_flagged {
+                    flagged[row] += 1;
+                } else {
+                    assert!(values.value(row).is_finite(), "{} row {row}", field.name());
+                }
+            }
+        }
+        assert_eq!(flagged, [0, expected_cnt], "sentinel {sentinel:?}");
+    }
+}
@@ -0,0 +1,190 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::IAU_EARTH_FRAME;
+use nyx::cosmic::Orbit;
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SpacecraftDynamics;
+use nyx::linalg::{SMatrix, SVector, Vector2};
+use nyx::od::prelude::*;
+use nyx::propagators::{IntegratorOptions, Propagator};
+use nyx::Spacecraft;
+use std::collections::BTreeMap;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// White noise only stochastics, scaled in variance by the provided factor.
+fn white_noise(sigma: f64, variance_scale: f64) -> StochasticNoise {
+    StochasticNoise {
+        white_noise: Some(WhiteNoise {
+            sigma: sigma * variance_scale.sqrt(),
+            ..Default::default()
+        }),
+        bias: None,
+        random_walk: None,
+    }
+}
+
+/// Builds the DSN stations with white noise whose variance is scaled by the provided factor for Canberra only.
+fn stations(almanac: Arc<Almanac>, canberra_variance_scale: f64) -> Vec<GroundStation> {
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let (range_sigma_km, doppler_sigma_km_s) = (2e-3, 3e-6);
+
+    vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            white_noise(range_sigma_km, 1.0),
+            white_noise(doppler_sigma_km_s, 1.0),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            white_noise(range_sigma_km, canberra_variance_scale),
+            white_noise(doppler_sigma_km_s, canberra_variance_scale),
+            iau_earth,
+        ),
+        GroundStation::dss13_goldstone(
+            0.0,
+            white_noise(range_sigma_km, 1.0),
+            white_noise(doppler_sigma_km_s, 1.0),
+            iau_earth,
+        ),
+    ]
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn od_device_weighting(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    // The actual noise of Canberra is three times larger than what the filter is told.
+    let sim_devices = stations(almanac.clone(), 9.0);
+    let proc_devices = stations(almanac.clone(), 1.0);
+
+    let configs: BTreeMap<String, TrkConfig> = sim_devices
+        .iter()
+        .map(|device| {
+            (
+                device.name.clone(),
+                TrkConfig::from_sample_rate(60.seconds()),
+            )
+        })
+        .collect();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Spacecraft::from(Orbit::keplerian(
+        22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k,
+    ));
+
+    let setup = Propagator::rk89(
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        IntegratorOptions::with_fixed_step(10.seconds()),
+    );
+    let prop_time = 12 * Unit::Hour;
+    let (_, traj) = setup
+        .with(initial_state, almanac.clone())
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let init_covar = SMatrix::<f64, 9, 9>::from_diagonal(&SVector::<f64, 9>::from_iterator([
+        1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6, 0.0, 0.0, 0.0,
+    ]));
+
+    // Deweight Canberra by the underestimated variance, and even more during its first hour of tracking.
+    // The weights also reference an unknown station, which is only warned about.
+    let canberra = proc_devices[1].name.clone();
+    let weights = MeasurementWeights::default()
+        .with_device(
+            &canberra,
+            DeviceWeight::new(9.0).with_override(dt, dt + 1 * Unit::Hour, 4.0),
+        )
+        .with_device("DSS-99 Kourou", DeviceWeight::new(2.0));
+
+    let mut rms_errors_km = [0.0; 2];
+
+    for seed in 0..3 {
+        let mut arc_sim =
+            TrackingArcSim::with_seed(sim_devices.clone(), traj.clone(), configs.clone(), seed)
+                .unwrap();
+        arc_sim.build_schedule(almanac.clone()).unwrap();
+        let mut arc = arc_sim.generate_measurements(almanac.clone()).unwrap();
+        arc.set_devices(proc_devices.clone(), configs.clone())
+            .unwrap();
+
+        for (run, weighted) in [false, true].iter().enumerate() {
+            let initial_estimate = KfEstimate::from_covar(initial_state.with_stm(), init_covar);
+            let odp = ODProcess::ckf(
+                setup.with(initial_state.with_stm(), almanac.clone()),
+                KF::no_snc(initial_estimate),
+                None,
+                almanac.clone(),
+            );
+            let mut odp = if *weighted {
+                odp.with_weights(weights.clone())
+            } else {
+                odp
+            };
+
+            odp.process_arc::<GroundStation>(&arc).unwrap();
+
+            // The applied weight is recorded with each residual
+            for residual in odp.residuals.iter().flatten() {
+                let expected = if residual.tracker.as_ref() != Some(&canberra) || !weighted {
+                    1.0
+                } else if residual.epoch < dt + 1 * Unit::Hour {
+                    36.0
+                } else {
+                    9.0
+                };
+                assert_eq!(residual.weight, Vector2::new(expected, expected));
+            }
+
+            // Position accuracy over the second half of the arc
+            let errors_km = odp
+                .estimates
+                .iter()
+                .filter(|est| est.epoch() >= dt + prop_time * 0.5)
+                .map(|est| {
+                    let truth = traj.at(est.epoch()).unwrap();
+                    (est.state().orbit.radius_km - truth.orbit.radius_km).norm()
+                })
+                .collect::<Vec<f64>>();
+            let rms_km = (errors_km.iter().map(|err| err.powi(2)).sum::<f64>()
+                / errors_km.len() as f64)
+                .sqrt();
+
+            println!("seed {seed}, weighted = {weighted}: position RMS = {rms_km:.6} km");
+            rms_errors_km[run] += rms_km;
+        }
+    }
+
+    println!(
+        "unweighted: {:.6} km\tweighted: {:.6} km",
+        rms_errors_km[0], rms_errors_km[1]
+    );
+    assert!(
+        rms_errors_km[1] < rms_errors_km[0],
+        "weighting the noisy station should improve the estimation accuracy"
+    );
+
+    // Invalid weights are rejected before processing
+    let mut odp = ODProcess::ckf(
+        setup.with(initial_state.with_stm(), almanac.clone()),
+        KF::no_snc(KfEstimate::from_covar(initial_state.with_stm(), init_covar)),
+        None,
+        almanac.clone(),
+    )
+    .with_weights(MeasurementWeights::default().with_device(&canberra, DeviceWeight::new(-1.0)));
+    let mut arc_sim = TrackingArcSim::with_seed(sim_devices, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(almanac.clone()).unwrap();
+    let arc = arc_sim.generate_measurements(almanac).unwrap();
+    assert!(odp.process_arc::<GroundStation>(&arc).is_err());
+}
@@ -0,0 +1,275 @@
+extern crate nyx_space as nyx;
+extern crate pretty_env_logger;
+
+use anise::constants::frames::{EARTH_J2000, IAU_EARTH_FRAME};
+use anise::prelude::Almanac;
+use hifitime::MJD_J2000;
+use nyx::cosmic::{Orbit, OrbitExt};
+use nyx::dynamics::{Harmonics, OrbitalDynamics, SpacecraftDynamics};
+use nyx::io::gravity::HarmonicsMem;
+use nyx::md::prelude::Traj;
+use nyx::propagators::{AnalyticPropagator, IntegratorOptions, Propagator};
+use nyx::time::{Epoch, Unit};
+use nyx::utils::between_pm_180;
+use nyx::{Spacecraft, State};
+use rstest::*;
+use std::sync::Arc;
+use std::time::Instant;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// Slope of the least squares line through the provided angles, unwrapped, in degrees per day
+fn secular_rate_deg_day(traj: &Traj<Spacecraft>, angle: fn(&Orbit) -> f64) -> f64 {
+    let start = traj.first().epoch();
+    let mut prev_deg = angle(&traj.first().orbit);
+    let mut unwrapped_deg = prev_deg;
+
+    let mut samples = Vec::new();
+    for state in traj.every(60 * Unit::Second) {
+        let deg = angle(&state.orbit);
+        unwrapped_deg += between_pm_180(deg - prev_deg);
+        prev_deg = deg;
+        samples.push(((state.epoch() - start).to_unit(Unit::Day), unwrapped_deg));
+    }
+
+    let n = samples.len() as f64;
+    let mean_t = samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let cov_ty = samples
+        .iter()
+        .map(|(t, y)| (t - mean_t) * (y - mean_y))
+        .sum::<f64>();
+    let var_t = samples
+        .iter()
+        .map(|(t, _)| (t - mean_t).powi(2))
+        .sum::<f64>();
+    cov_ty / var_t
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn analytic_two_body(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_mjd_tai(MJD_J2000);
+
+    for orbit in [
+        Orbit::keplerian(7000.0, 0.001, 28.5, 30.0, 40.0, 50.0, dt, eme2k),
+        Orbit::keplerian(26000.0, 0.7, 63.4, 30.0, 270.0, 10.0, dt, eme2k),
+        // Hyperbolic departure
+        Orbit::keplerian(-30000.0, 1.3, 10.0, 30.0, 40.0, 0.0, dt, eme2k),
+    ] {
+        let (num_end, _) =
+            Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+                .with(orbit.into(), almanac.clone())
+                .for_duration_with_traj(1 * Unit::Day)
+                .unwrap();
+
+        let ana_end = AnalyticPropagator::two_body(10 * Unit::Minute)
+            .with(orbit.into(), almanac.clone())
+            .for_duration(1 * Unit::Day)
+            .unwrap();
+
+        let err_km = (num_end.orbit.radius_km - ana_end.orbit.radius_km).norm();
+        let err_km_s = (num_end.orbit.velocity_km_s - ana_end.orbit.velocity_km_s).norm();
+        println!(
+            "{orbit:x}\n\t{:.3e} m\t{:.3e} m/s",
+            err_km * 1e3,
+            err_km_s * 1e3
+        );
+        assert_eq!(ana_end.epoch(), num_end.epoch());
+        assert!(err_km < 1e-3, "position error too large");
+        assert!(err_km_s < 1e-6, "velocity error too large");
+    }
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn orbit_two_body_at(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_mjd_tai(MJD_J2000);
+    let escape_km_s = (2.0 * eme2k.mu_km3_s2().unwrap() / 7000.0).sqrt();
+
+    for orbit in [
+        Orbit::cartesian(
+            -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+        ),
+        // Parabolic orbit, at periapsis
+        Orbit::cartesian(7000.0, 0.0, 0.0, 0.0, escape_km_s, 0.0, dt, eme2k),
+        // Circular equatorial orbit, where the anomalies are undefined
+        Orbit::keplerian(7000.0, 0.0, 0.0, 0.0, 0.0, 0.0, dt, eme2k),
+        Orbit::keplerian(26000.0, 0.7, 63.4, 30.0, 270.0, 10.0, dt, eme2k),
+        Orbit::keplerian(-30000.0, 1.3, 10.0, 30.0, 40.0, 0.0, dt, eme2k),
+    ] {
+        let same = orbit.two_body_at(dt).unwrap();
+        assert!((same.radius_km - orbit.radius_km).norm() < 1e-9);
+        assert!((same.velocity_km_s - orbit.velocity_km_s).norm() < 1e-12);
+
+        for prop_time in [1 * Unit::Day, -6 * Unit::Hour] {
+            let num_end = Propagator::rk89(
+                SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+                IntegratorOptions::default(),
+            )
+            .with(orbit.into(), almanac.clone())
+            .for_duration(prop_time)
+            .unwrap()
+            .orbit;
+
+            let ana_end = orbit.two_body_at(dt + prop_time).unwrap();
+
+            let err_km = (num_end.radius_km - ana_end.radius_km).norm();
+            let err_km_s = (num_end.velocity_km_s - ana_end.velocity_km_s).norm();
+            println!(
+                "{orbit:x} for {prop_time}\n\t{:.3e} m\t{:.3e} m/s",
+                err_km * 1e3,
+                err_km_s * 1e3
+            );
+            assert_eq!(ana_end.epoch, num_end.epoch);
+            assert!(err_km < 1e-3, "position error too large");
+            assert!(err_km_s < 1e-6, "velocity error too large");
+        }
+    }
+}
+
+/// The analytic propagator uses the osculating elements as mean elements, so the secular rates are only approximately those of the numerical propagation.
+/// Documented bounds for a day in LEO: 2% on the node regression rate, 10% on the apsidal rate, and 10 km on the mean semi-major axis.
+#[allow(clippy::identity_op)]
+#[rstest]
+fn analytic_j2_secular(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    // At J2000, the pole of the IAU Earth frame is that of the EME2000 frame.
+    let dt = Epoch::from_mjd_tai(MJD_J2000);
+    let orbit = Orbit::keplerian(7200.0, 0.02, 50.0, 30.0, 60.0, 0.0, dt, eme2k);
+
+    let harmonics = Harmonics::from_stor(iau_earth, HarmonicsMem::j2_jgm3());
+    let (_, num_traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::from_model(
+        harmonics,
+    )))
+    .with(orbit.into(), almanac.clone())
+    .for_duration_with_traj(1 * Unit::Day)
+    .unwrap();
+
+    let (_, ana_traj) = AnalyticPropagator::earth_j2(1 * Unit::Minute)
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    assert!(ana_traj.name.as_ref().unwrap().contains("analytic"));
+
+    for (name, angle, tol) in [
+        (
+            "RAAN",
+            (|orbit: &Orbit| orbit.raan_deg().unwrap()) as fn(&Orbit) -> f64,
+            0.02,
+        ),
+        ("AoP", |orbit: &Orbit| orbit.aop_deg().unwrap(), 0.1),
+    ] {
+        let num_rate = secular_rate_deg_day(&num_traj, angle);
+        let ana_rate = secular_rate_deg_day(&ana_traj, angle);
+        println!("{name} rate: numerical {num_rate:.6} deg/day\tanalytic {ana_rate:.6} deg/day");
+        assert!(
+            ((ana_rate - num_rate) / num_rate).abs() < tol,
+            "{name} secular rate out of bounds"
+        );
+    }
+
+    let mean_sma_km = num_traj
+        .every(60 * Unit::Second)
+        .map(|state| state.orbit.sma_km().unwrap())
+        .sum::<f64>()
+        / (num_traj.every(60 * Unit::Second).count() as f64);
+    println!("mean SMA: numerical {mean_sma_km:.3} km");
+    assert!((mean_sma_km - orbit.sma_km().unwrap()).abs() < 10.0);
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn analytic_cadence_and_backward(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let dt = Epoch::from_mjd_tai(MJD_J2000);
+    let orbit = Orbit::keplerian(7200.0, 0.02, 50.0, 30.0, 60.0, 0.0, dt, eme2k);
+
+    let prop = AnalyticPropagator::earth_j2(10 * Unit::Minute);
+
+    let (end, traj) = prop
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    // The output cadence is honored
+    assert_eq!(traj.states.len(), 145);
+    for pair in traj.states.windows(2) {
+        assert_eq!(pair[1].epoch() - pair[0].epoch(), 10 * Unit::Minute);
+    }
+    assert_eq!(traj.last().epoch(), dt + 1 * Unit::Day);
+
+    // Propagate backward to the initial epoch
+    let (back, back_traj) = prop
+        .with(end, almanac.clone())
+        .until_epoch_with_traj(dt)
+        .unwrap();
+
+    assert_eq!(back_traj.states.len(), 145);
+    assert_eq!(back_traj.first().epoch(), dt);
+    assert_eq!(back_traj.last().epoch(), end.epoch());
+
+    let err_km = (back.orbit.radius_km - orbit.radius_km).norm();
+    let err_km_s = (back.orbit.velocity_km_s - orbit.velocity_km_s).norm();
+    println!(
+        "backward error: {:.3e} m\t{:.3e} m/s",
+        err_km * 1e3,
+        err_km_s * 1e3
+    );
+    assert!(err_km < 1e-6);
+    assert!(err_km_s < 1e-9);
+
+    // The backward trajectory matches the forward one
+    for state in back_traj.states.iter() {
+        let fwd = traj.at(state.epoch()).unwrap();
+        assert!((fwd.orbit.radius_km - state.orbit.radius_km).norm() < 1e-6);
+    }
+}
+
+/// Reports the wall-clock time of the numerical and analytic propagations, which is not asserted as it depends on the machine.
+#[allow(clippy::identity_op)]
+#[rstest]
+fn analytic_timing(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let dt = Epoch::from_mjd_tai(MJD_J2000);
+    let orbit = Orbit::keplerian(7200.0, 0.02, 50.0, 30.0, 60.0, 0.0, dt, eme2k);
+
+    let span = 10 * Unit::Day;
+
+    let tick = Instant::now();
+    let harmonics = Harmonics::from_stor(iau_earth, HarmonicsMem::j2_jgm3());
+    let (_, num_traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::from_model(
+        harmonics,
+    )))
+    .with(orbit.into(), almanac.clone())
+    .for_duration_with_traj(span)
+    .unwrap();
+    let numerical = tick.elapsed();
+
+    let tick = Instant::now();
+    let (_, ana_traj) = AnalyticPropagator::earth_j2(1 * Unit::Hour)
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(span)
+        .unwrap();
+    let analytic = tick.elapsed();
+
+    println!(
+        "numerical: {numerical:?} ({} states)\tanalytic: {analytic:?} ({} states)",
+        num_traj.states.len(),
+        ana_traj.states.len()
+    );
+    assert_eq!(ana_traj.first().epoch(), num_traj.first().epoch());
+    assert_eq!(ana_traj.last().epoch(), num_traj.last().epoch());
+}
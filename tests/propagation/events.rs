@@ -171,3 +171,394 @@ fn event_tracker_true_anomaly(almanac: Arc<Almanac>) {
         });
     println!("[eclipses] {} =>\n{}", penumbra_event_loc, pretty);
 }
+
+#[rstest]
+fn event_search_bounded(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration as StdDuration, Instant};
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let dt = Epoch::from_gregorian_tai_at_noon(2020, 1, 1);
+    let state = Orbit::keplerian(7000.0, 0.01, 28.5, 35.0, 45.0, 0.0, dt, eme2k);
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::default(dynamics);
+
+    // Short trajectory for the comparison with the unbounded search
+    let (_, traj) = setup
+        .with(state.into(), almanac.clone())
+        .for_duration_with_traj(state.period().unwrap() * 5)
+        .unwrap();
+
+    let peri_event = Event::periapsis();
+    let unbounded = traj.find(&peri_event, almanac.clone()).unwrap();
+
+    // Ample budget, without and with the prefilter
+    for prefilter in [false, true] {
+        let cfg = EventSearchCfg::builder()
+            .max_evaluations(1_000_000)
+            .prefilter(prefilter)
+            .build();
+        let bounded = traj
+            .find_bounded(&peri_event, &cfg, almanac.clone())
+            .unwrap();
+        assert!(bounded.is_complete());
+        assert_eq!(bounded.events, unbounded, "prefilter = {prefilter}");
+        if prefilter {
+            assert!(bounded.skipped_chunks > 0, "prefilter skipped no chunk");
+        }
+        println!(
+            "prefilter = {prefilter}: {} evaluations, {} chunks skipped",
+            bounded.evaluations, bounded.skipped_chunks
+        );
+    }
+
+    // Tiny budget: the results are partial and flagged
+    let cfg = EventSearchCfg::builder().max_evaluations(10).build();
+    let bounded = traj
+        .find_bounded(&peri_event, &cfg, almanac.clone())
+        .unwrap();
+    assert!(bounded.budget_exhausted);
+    assert!(!bounded.cancelled);
+    assert!(bounded.evaluations <= 10);
+    assert!(bounded.events.len() < unbounded.len());
+
+    // Cancellation of a search on a long trajectory
+    let (_, long_traj) = setup
+        .with(state.into(), almanac.clone())
+        .for_duration_with_traj(30 * Unit::Day)
+        .unwrap();
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let cfg = EventSearchCfg::builder().cancel(cancel.clone()).build();
+
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(StdDuration::from_millis(20));
+        cancel.store(true, Ordering::Relaxed);
+        Instant::now()
+    });
+
+    let ta_event = Event::new(StateParameter::TrueAnomaly, 35.1);
+    let results = long_traj
+        .find_bounded(&ta_event, &cfg, almanac.clone())
+        .unwrap();
+    let returned_at = Instant::now();
+    let cancelled_at = canceller.join().unwrap();
+
+    if results.cancelled {
+        let latency = returned_at.saturating_duration_since(cancelled_at);
+        println!(
+            "cancelled after {} evaluations, returned {latency:?} after cancellation",
+            results.evaluations
+        );
+        assert!(latency < StdDuration::from_millis(100));
+    } else {
+        println!("search completed before the cancellation request");
+    }
+}
+
+#[rstest]
+fn event_search_with_values(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::*;
+    use nyx::md::EventEvaluator;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let dt = Epoch::from_gregorian_tai_at_noon(2020, 1, 1);
+    let state = Orbit::keplerian(7000.0, 0.01, 28.5, 35.0, 45.0, 0.0, dt, eme2k);
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = setup
+        .with(state.into(), almanac.clone())
+        .for_duration_with_traj(state.period().unwrap() * 5)
+        .unwrap();
+
+    for event in [
+        Event::periapsis(),
+        Event::apoapsis(),
+        Event::new(StateParameter::Rmag, 7000.0),
+        Event::new(StateParameter::TrueAnomaly, 35.1),
+    ] {
+        let found = traj.find(&event, almanac.clone()).unwrap();
+        let with_values = traj.find_all_with_values(&event, almanac.clone()).unwrap();
+        assert_eq!(found.len(), with_values.len(), "{event}");
+
+        for (details, (state, value)) in found.iter().zip(with_values.iter()) {
+            println!("{event} @ {}: {value:e}", state.epoch());
+            assert_eq!(details.state, *state);
+            assert!(
+                value.abs() <= event.value_precision().abs(),
+                "{event} value {value:e} not within {:e} of zero",
+                event.value_precision()
+            );
+        }
+    }
+}
+
+/// Event which jumps from -1 to +1 at a given epoch: it is monotone, but its value never falls within precision.
+struct StepEvent {
+    at: hifitime::Epoch,
+}
+
+impl std::fmt::Display for StepEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "step @ {}", self.at)
+    }
+}
+
+impl nyx::md::EventEvaluator<nyx::Spacecraft> for StepEvent {
+    fn eval(
+        &self,
+        state: &nyx::Spacecraft,
+        _almanac: Arc<Almanac>,
+    ) -> Result<f64, nyx::md::EventError> {
+        use nyx::State;
+        Ok(if state.epoch() < self.at { -1.0 } else { 1.0 })
+    }
+
+    fn eval_string(
+        &self,
+        state: &nyx::Spacecraft,
+        almanac: Arc<Almanac>,
+    ) -> Result<String, nyx::md::EventError> {
+        Ok(format!("{}", self.eval(state, almanac)?))
+    }
+
+    fn epoch_precision(&self) -> hifitime::Duration {
+        hifitime::Unit::Nanosecond * 1
+    }
+
+    fn value_precision(&self) -> f64 {
+        1e-3
+    }
+}
+
+#[rstest]
+fn find_bracketed_bisection_fallback(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::*;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let start = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let geo = Orbit::keplerian(42_164.0, 0.001, 0.1, 0.0, 0.0, 0.0, start, eme2k);
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(geo.into(), almanac.clone())
+        .for_duration_with_traj(30 * Unit::Day)
+        .unwrap();
+
+    // Bracketing the step down to one nanosecond over 30 days requires more halvings than the iterations of the Brent solver,
+    // so only the bisection fallback can find it.
+    let event = StepEvent {
+        at: start + 12.345_678_9 * Unit::Day,
+    };
+    let found = traj
+        .find_bracketed(traj.first().epoch(), traj.last().epoch(), &event, almanac)
+        .unwrap();
+
+    println!("{found}");
+    assert!((found.state.epoch() - event.at).abs() <= event.epoch_precision());
+}
+
+/// Discrete event which holds for a half period and then does not for the next half period, starting at a given epoch.
+struct SquareWaveEvent {
+    start: hifitime::Epoch,
+    half_period: hifitime::Duration,
+}
+
+impl std::fmt::Display for SquareWaveEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "square wave from {} every {}",
+            self.start, self.half_period
+        )
+    }
+}
+
+impl nyx::md::EventEvaluator<nyx::Spacecraft> for SquareWaveEvent {
+    fn eval(
+        &self,
+        state: &nyx::Spacecraft,
+        _almanac: Arc<Almanac>,
+    ) -> Result<f64, nyx::md::EventError> {
+        use nyx::State;
+        let phase = ((state.epoch() - self.start).to_seconds() / self.half_period.to_seconds())
+            .floor() as i64;
+        Ok(if phase.rem_euclid(2) == 0 { 1.0 } else { -1.0 })
+    }
+
+    fn eval_string(
+        &self,
+        state: &nyx::Spacecraft,
+        almanac: Arc<Almanac>,
+    ) -> Result<String, nyx::md::EventError> {
+        Ok(format!("{}", self.eval(state, almanac)?))
+    }
+
+    fn epoch_precision(&self) -> hifitime::Duration {
+        hifitime::Unit::Millisecond * 1
+    }
+
+    fn value_precision(&self) -> f64 {
+        1e-3
+    }
+
+    fn is_discrete(&self) -> bool {
+        true
+    }
+}
+
+#[rstest]
+fn find_discrete_square_wave(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::*;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let start = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let leo = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, start, eme2k);
+
+    let opts = IntegratorOptions::builder()
+        .max_step(5 * Unit::Minute)
+        .build();
+    let (_, traj) = Propagator::rk89(SpacecraftDynamics::new(OrbitalDynamics::two_body()), opts)
+        .with(leo.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let event = SquareWaveEvent {
+        start: start + 7 * Unit::Minute,
+        half_period: 23 * Unit::Minute,
+    };
+
+    let mut expected = Vec::new();
+    let mut transition = event.start;
+    while transition < traj.last().epoch() {
+        expected.push(transition);
+        transition += event.half_period;
+    }
+
+    let transitions = traj.find_transitions(&event, almanac.clone()).unwrap();
+    assert_eq!(
+        transitions.len(),
+        expected.len(),
+        "missed or spurious edges"
+    );
+    for (k, (found, expected)) in transitions.iter().zip(&expected).enumerate() {
+        // The found state is the first one past the transition
+        let delta = found.state.epoch() - *expected;
+        assert!(
+            delta >= Duration::ZERO && delta <= event.epoch_precision(),
+            "transition {k} found at {} instead of {expected}",
+            found.state.epoch()
+        );
+        let edge = if k % 2 == 0 {
+            EventEdge::Rising
+        } else {
+            EventEdge::Falling
+        };
+        assert_eq!(found.edge, edge);
+    }
+
+    // The generic search uses the same path for discrete events
+    assert_eq!(traj.find(&event, almanac.clone()).unwrap(), transitions);
+
+    // A bracketed search is a bisection too
+    let bracketed = traj
+        .find_bracketed(
+            expected[3] - 10 * Unit::Minute,
+            expected[3] + 10 * Unit::Minute,
+            &event,
+            almanac.clone(),
+        )
+        .unwrap();
+    assert!((bracketed.state.epoch() - expected[3]).abs() <= event.epoch_precision());
+
+    // Arcs are the intervals where the condition holds
+    let arcs = traj.find_arcs(&event, almanac).unwrap();
+    assert_eq!(arcs.len(), expected.len().div_ceil(2));
+    for arc in &arcs {
+        println!("{arc}");
+        let duration = arc.fall.state.epoch() - arc.rise.state.epoch();
+        if arc.fall.state.epoch() < traj.last().epoch() {
+            assert!((duration - event.half_period).abs() <= event.epoch_precision());
+        }
+    }
+}
+
+/// Event which crosses zero linearly at a given epoch.
+struct LinearEvent {
+    at: hifitime::Epoch,
+}
+
+impl std::fmt::Display for LinearEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "linear crossing @ {}", self.at)
+    }
+}
+
+impl nyx::md::EventEvaluator<nyx::Spacecraft> for LinearEvent {
+    fn eval(
+        &self,
+        state: &nyx::Spacecraft,
+        _almanac: Arc<Almanac>,
+    ) -> Result<f64, nyx::md::EventError> {
+        use nyx::State;
+        Ok((state.epoch() - self.at).to_seconds())
+    }
+
+    fn eval_string(
+        &self,
+        state: &nyx::Spacecraft,
+        almanac: Arc<Almanac>,
+    ) -> Result<String, nyx::md::EventError> {
+        Ok(format!("{}", self.eval(state, almanac)?))
+    }
+
+    fn epoch_precision(&self) -> hifitime::Duration {
+        hifitime::Unit::Millisecond * 1
+    }
+
+    fn value_precision(&self) -> f64 {
+        1e-6
+    }
+}
+
+#[rstest]
+fn find_event_at_chunk_boundary(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::*;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let start = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let leo = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, start, eme2k);
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(leo.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    // The default chunks are 1% of the trajectory: this event crosses exactly at the end of the 37th chunk.
+    let chunk = (traj.last().epoch() - traj.first().epoch()) / 100;
+    let event = LinearEvent {
+        at: start + chunk * 37,
+    };
+
+    let found = traj.find(&event, almanac.clone()).unwrap();
+    assert_eq!(found.len(), 1, "event at chunk boundary found {found:?}");
+    assert!((found[0].state.epoch() - event.at).abs() <= event.epoch_precision());
+
+    // Custom chunks, with and without overlap
+    for overlap in [0.0, 0.1, 0.5] {
+        let cfg = EventSearchCfg::builder()
+            .chunk(1 * Unit::Hour)
+            .chunk_overlap(overlap)
+            .build();
+        let event = LinearEvent {
+            at: start + 5 * Unit::Hour,
+        };
+        let results = traj.find_bounded(&event, &cfg, almanac.clone()).unwrap();
+        assert!(results.is_complete());
+        assert_eq!(results.events.len(), 1, "overlap = {overlap}");
+        assert!((results.events[0].state.epoch() - event.at).abs() <= event.epoch_precision());
+    }
+}
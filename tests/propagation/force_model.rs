@@ -0,0 +1,147 @@
+extern crate nyx_space as nyx;
+
+use std::fmt;
+use std::sync::Arc;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use nyx::cosmic::{GuidanceMode, Orbit, Spacecraft};
+use nyx::dynamics::guidance::{FiniteBurns, LocalFrame, Mnvr, Thruster};
+use nyx::dynamics::{DynamicsError, ForceModel, OrbitalDynamics, SpacecraftDynamics};
+use nyx::linalg::{Vector3, Vector6};
+use nyx::propagators::{IntegratorOptions, Propagator};
+use nyx::time::{Epoch, Unit};
+use nyx::State;
+use rstest::*;
+
+use crate::propagation::GMAT_EARTH_GM;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// A user defined constant thrust along the velocity vector, which does not provide its partials.
+struct ConstantAlongTrack {
+    thrust_newtons: f64,
+}
+
+impl fmt::Display for ConstantAlongTrack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "constant along-track thrust of {} N",
+            self.thrust_newtons
+        )
+    }
+}
+
+impl ForceModel for ConstantAlongTrack {
+    fn eom(&self, ctx: &Spacecraft, _almanac: Arc<Almanac>) -> Result<Vector3<f64>, DynamicsError> {
+        // Force in kN such that dividing by the mass in kg yields km/s^2
+        Ok(ctx.orbit.velocity_km_s.normalize() * self.thrust_newtons * 1e-3)
+    }
+}
+
+#[rstest]
+fn custom_force_model_along_track(almanac: Arc<Almanac>) {
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(GMAT_EARTH_GM);
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+    let orbit = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_time, eme2k,
+    );
+
+    let thrust_newtons = 10.0;
+    let monoprop = Thruster {
+        thrust_N: thrust_newtons,
+        isp_s: 300.0,
+    };
+    let sc_state = Spacecraft::from_thruster(orbit, 1e3, 756.0, monoprop, GuidanceMode::Coast);
+
+    let prop_time = 50.0 * Unit::Minute;
+    let end_time = start_time + prop_time;
+    let opts = IntegratorOptions::with_fixed_step(10.0 * Unit::Second);
+
+    // Reference: the same thrust implemented as a finite burn in the VNC frame, without mass depletion.
+    let schedule = FiniteBurns::from_mnvrs(vec![Mnvr::from_time_invariant(
+        start_time,
+        end_time,
+        1.0,
+        Vector3::x(),
+        LocalFrame::VNC,
+    )]);
+
+    let ref_dynamics =
+        SpacecraftDynamics::from_guidance_law_no_decr(OrbitalDynamics::two_body(), schedule);
+
+    let ref_state = Propagator::rk89(ref_dynamics, opts)
+        .with(sc_state, almanac.clone())
+        .for_duration(prop_time)
+        .unwrap();
+
+    // User defined force model added to the built-in dynamics.
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body())
+        .with_force_model(Arc::new(ConstantAlongTrack { thrust_newtons }));
+
+    assert_eq!(dynamics.force_models.len(), 1);
+    // The partials of this model are computed by finite differencing.
+    assert!(!dynamics.force_models[0].has_partials());
+
+    let setup = Propagator::rk89(dynamics, opts);
+
+    let custom_state = setup
+        .with(sc_state, almanac.clone())
+        .for_duration(prop_time)
+        .unwrap();
+
+    println!("reference: {}", ref_state.orbit);
+    println!("custom:    {}", custom_state.orbit);
+
+    let pos_err_km = (custom_state.orbit.radius_km - ref_state.orbit.radius_km).norm();
+    let vel_err_km_s = (custom_state.orbit.velocity_km_s - ref_state.orbit.velocity_km_s).norm();
+    assert!(pos_err_km < 1e-6, "position error {pos_err_km} km");
+    assert!(vel_err_km_s < 1e-9, "velocity error {vel_err_km_s} km/s");
+
+    // The thrust must have raised the orbit
+    assert!(custom_state.orbit.sma_km().unwrap() > orbit.sma_km().unwrap() + 1.0);
+
+    // The partials of the custom model are computed by finite differencing when propagating the STM.
+    let stm_prop_time = 10.0 * Unit::Minute;
+    let with_stm = setup
+        .with(sc_state.with_stm(), almanac.clone())
+        .for_duration(stm_prop_time)
+        .unwrap();
+
+    let nominal = setup
+        .with(sc_state, almanac.clone())
+        .for_duration(stm_prop_time)
+        .unwrap();
+
+    assert!((with_stm.orbit.radius_km - nominal.orbit.radius_km).norm() < 1e-9);
+    assert!((with_stm.orbit.velocity_km_s - nominal.orbit.velocity_km_s).norm() < 1e-12);
+
+    // Check the STM by perturbing the initial position by ten meters along X.
+    let mut perturbed = sc_state;
+    perturbed.orbit.radius_km.x += 1e-2;
+    let perturbed = setup
+        .with(perturbed, almanac)
+        .for_duration(stm_prop_time)
+        .unwrap();
+
+    let stm = with_stm.stm().unwrap().fixed_resize::<6, 6>(0.0);
+    let mut init_dev = Vector6::zeros();
+    init_dev[0] = 1e-2;
+
+    let predicted_dev = stm * init_dev;
+    let actual_dev = perturbed.orbit.to_cartesian_pos_vel() - nominal.orbit.to_cartesian_pos_vel();
+
+    println!("predicted deviation: {predicted_dev}");
+    println!("actual deviation:    {actual_dev}");
+
+    let dev_err = (predicted_dev - actual_dev).fixed_rows::<3>(0).norm();
+    assert!(dev_err < 1e-5, "STM mapped deviation off by {dev_err} km");
+}
@@ -0,0 +1,156 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::EARTH_J2000;
+use anise::prelude::Almanac;
+use nyx::cosmic::{Orbit, Spacecraft};
+use nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use nyx::io::jsonl::{Backpressure, JsonLinesCfg, JsonLinesSender, JsonLinesSink, JsonLinesWriter};
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+use rstest::*;
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::mpsc::TrySendError;
+use std::sync::Arc;
+use std::thread;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+fn check_line(line: &str) {
+    let obj: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert!(obj["epoch"].as_str().unwrap().ends_with("UTC"));
+    assert!(obj["frame"].as_str().is_some());
+    assert_eq!(obj["position_km"].as_array().unwrap().len(), 3);
+    assert_eq!(obj["velocity_km_s"].as_array().unwrap().len(), 3);
+    assert!(obj["sma"].as_f64().unwrap() > 6778.0);
+    assert!(obj["ecc"].as_f64().is_some());
+}
+
+#[rstest]
+fn jsonl_tcp_stream(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let leo = Orbit::keplerian(7000.0, 0.01, 30.0, 0.0, 0.0, 0.0, epoch, eme2k);
+
+    // Local listener acting as the visualizer
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let visualizer = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        BufReader::new(stream)
+            .lines()
+            .map(|line| line.unwrap())
+            .collect::<Vec<String>>()
+    });
+
+    let cfg = JsonLinesCfg::builder()
+        .sink(JsonLinesSink::Tcp(addr.to_string()))
+        .backpressure(Backpressure::Block)
+        .build()
+        .with_headers(&["sma", "ecc"])
+        .unwrap();
+
+    let (tx, writer) = JsonLinesWriter::new(cfg).unwrap().spawn::<Spacecraft>();
+
+    let (tx_count, rx_count) = std::sync::mpsc::channel();
+    Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(Spacecraft::from(leo), almanac.clone())
+        .for_duration_with_channel(30 * Unit::Minute, tx_count)
+        .unwrap();
+
+    // Forward the propagated states to the writer, counting them.
+    let mut sent = 0;
+    for state in rx_count {
+        tx.send(state).unwrap();
+        sent += 1;
+    }
+    drop(tx);
+
+    let stats = writer.join().unwrap().unwrap();
+    assert_eq!(stats.written, sent);
+    assert_eq!(stats.dropped, 0);
+
+    let lines = visualizer.join().unwrap();
+    assert_eq!(lines.len(), sent);
+    for line in &lines {
+        check_line(line);
+    }
+
+    // And to a file, which is appended to.
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "stream.jsonl"]
+        .iter()
+        .collect();
+    let _ = std::fs::remove_file(&path);
+
+    for _ in 0..2 {
+        let cfg = JsonLinesCfg::builder()
+            .sink(JsonLinesSink::File(path.clone()))
+            .build()
+            .with_headers(&["sma", "ecc"])
+            .unwrap();
+        let mut writer = JsonLinesWriter::new(cfg).unwrap();
+        writer.write_state(&Spacecraft::from(leo)).unwrap();
+        assert_eq!(writer.stats().written, 1);
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 2);
+    contents.lines().for_each(check_line);
+
+    // Unknown parameters are rejected
+    assert!(JsonLinesCfg::builder()
+        .sink(JsonLinesSink::File(path))
+        .build()
+        .with_headers(&["not_a_param"])
+        .is_err());
+}
+
+#[rstest]
+fn jsonl_block_stalls_producer(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let leo = Spacecraft::from(Orbit::keplerian(
+        7000.0, 0.01, 30.0, 0.0, 0.0, 0.0, epoch, eme2k,
+    ));
+
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "stall.jsonl"]
+        .iter()
+        .collect();
+    let _ = std::fs::remove_file(&path);
+
+    let cfg = JsonLinesCfg::builder()
+        .sink(JsonLinesSink::File(path.clone()))
+        .backpressure(Backpressure::Block)
+        .max_pending(2)
+        .build();
+    let writer = JsonLinesWriter::new(cfg).unwrap();
+    let (tx, rx) = writer.channel::<Spacecraft>();
+
+    // Nothing is consumed yet: the producer may only get ahead by `max_pending` states.
+    let JsonLinesSender::Bounded(sync_tx) = tx.clone() else {
+        panic!("blocking backpressure must use a bounded channel")
+    };
+    sync_tx.try_send(leo).unwrap();
+    sync_tx.try_send(leo).unwrap();
+    assert!(matches!(sync_tx.try_send(leo), Err(TrySendError::Full(_))));
+
+    // The producer resumes once the writer consumes the pending states.
+    let consumer = thread::spawn(move || writer.consume(rx));
+    for _ in 0..10 {
+        tx.send(leo).unwrap();
+    }
+    drop(tx);
+    drop(sync_tx);
+
+    let stats = consumer.join().unwrap().unwrap();
+    assert_eq!(stats.written, 12);
+    assert_eq!(stats.dropped, 0);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 12);
+}
@@ -6,7 +6,7 @@ use nyx::cosmic::{assert_orbit_eq_or_abs, Orbit};
 use nyx::dynamics::orbital::OrbitalDynamics;
 use nyx::dynamics::SpacecraftDynamics;
 use nyx::propagators::error_ctrl::ErrorControl;
-use nyx::time::{Epoch, Unit};
+use nyx::time::{Duration, Epoch, Unit};
 use nyx::utils::rss_orbit_errors;
 use nyx::{propagators::*, Spacecraft};
 
@@ -475,3 +475,241 @@ fn gmat_val_leo_day_fixed(almanac: Arc<Almanac>) {
         println!();
     }
 }
+
+#[rstest]
+fn step_history_eccentric(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    // Molniya-like orbit starting at apogee
+    let orbit = Orbit::keplerian(26_600.0, 0.74, 63.4, 0.0, 270.0, 180.0, epoch, eme2k);
+    let period = orbit.period().unwrap();
+
+    let opts = IntegratorOptions::builder()
+        .max_step(30.0 * Unit::Minute)
+        .tolerance(1e-10)
+        .record_steps(true)
+        .build();
+
+    let setup = Propagator::rk89(SpacecraftDynamics::new(OrbitalDynamics::two_body()), opts);
+    let mut prop = setup.with(orbit.into(), almanac.clone());
+    prop.for_duration(period).unwrap();
+
+    let history = prop.step_history();
+    assert!(history.len() > 10);
+    // Steps are contiguous
+    for pair in history.windows(2) {
+        assert!((pair[0].epoch + pair[0].step - pair[1].epoch).abs() < 1 * Unit::Microsecond);
+    }
+    assert!(history
+        .iter()
+        .all(|rec| rec.error <= opts.tolerance || rec.step == opts.min_step));
+
+    // Perigee is half a period after apogee: the steps around it must be the smallest.
+    let perigee = epoch + period * 0.5;
+    let near_perigee = history
+        .iter()
+        .filter(|rec| (rec.epoch - perigee).abs() < 10 * Unit::Minute)
+        .map(|rec| rec.step)
+        .max()
+        .unwrap();
+    let near_apogee = history
+        .iter()
+        .filter(|rec| (rec.epoch - perigee).abs() > period * 0.4)
+        .map(|rec| rec.step)
+        .min()
+        .unwrap();
+    assert!(
+        near_perigee * 5 < near_apogee,
+        "steps should shrink near perigee: {near_perigee} vs {near_apogee}"
+    );
+
+    // Nothing is recorded by default
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let mut prop = setup.with(orbit.into(), almanac);
+    prop.for_duration(1 * Unit::Hour).unwrap();
+    assert!(prop.step_history().is_empty());
+}
+
+#[rstest]
+fn prop_stats_eccentric(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::ExportCfg;
+    use std::path::PathBuf;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    // Molniya-like orbit starting at apogee
+    let orbit = Orbit::keplerian(26_600.0, 0.74, 63.4, 0.0, 270.0, 180.0, epoch, eme2k);
+    let period = orbit.period().unwrap();
+
+    let opts = IntegratorOptions::builder()
+        .max_step(30.0 * Unit::Minute)
+        .tolerance(1e-10)
+        .record_steps(true)
+        .build();
+
+    let setup = Propagator::rk89(SpacecraftDynamics::new(OrbitalDynamics::two_body()), opts);
+    let mut prop = setup.with(orbit.into(), almanac.clone());
+    prop.for_duration(period).unwrap();
+
+    let stats = prop.stats();
+    println!("{stats}");
+
+    // Totals are consistent with the step log
+    assert_eq!(stats.accepted_steps, prop.step_history().len());
+    assert_eq!(stats.rejected_steps, prop.rejected_steps.len());
+    assert_eq!(
+        stats.function_evals,
+        (stats.accepted_steps + stats.rejected_steps) * IntegratorMethod::RungeKutta89.stages()
+    );
+    assert!((stats.total_step - period).abs() < 1 * Unit::Microsecond);
+    assert!(stats.wall_time > Duration::ZERO);
+
+    let min_step = stats.min_step.unwrap();
+    let max_step = stats.max_step.unwrap();
+    let mean_step = stats.mean_step().unwrap();
+    assert!(min_step < mean_step && mean_step < max_step);
+    assert!(max_step <= opts.max_step);
+
+    // The smallest adaptive step is taken near perigee, half a period after apogee (the final step to the stop epoch is excluded)
+    let history = prop.step_history();
+    let smallest = history[..history.len() - 1]
+        .iter()
+        .min_by_key(|rec| rec.step)
+        .unwrap();
+    let perigee = epoch + period * 0.5;
+    assert!(
+        (smallest.epoch - perigee).abs() < 30 * Unit::Minute,
+        "smallest step at {} instead of near perigee {perigee}",
+        smallest.epoch
+    );
+
+    // Rejected attempts are always larger than the accepted step which follows them
+    for rejected in &prop.rejected_steps {
+        assert!(!rejected.accepted);
+        assert!(rejected.error > opts.tolerance);
+        let accepted = history
+            .iter()
+            .find(|rec| rec.epoch == rejected.epoch)
+            .unwrap();
+        assert!(accepted.step < rejected.step);
+    }
+
+    let log = prop.step_log();
+    assert_eq!(log.len(), stats.accepted_steps + stats.rejected_steps);
+    assert!(log.windows(2).all(|pair| pair[0].epoch <= pair[1].epoch));
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "step_log_eccentric.parquet",
+    ]
+    .iter()
+    .collect();
+    prop.step_log_to_parquet(path, ExportCfg::default())
+        .unwrap();
+
+    // Statistics accumulate over subsequent propagations
+    prop.for_duration(1 * Unit::Hour).unwrap();
+    assert!(prop.stats().accepted_steps > stats.accepted_steps);
+
+    // Statistics are collected even without the step log
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let mut prop = setup.with(orbit.into(), almanac);
+    prop.for_duration(1 * Unit::Hour).unwrap();
+    assert!(prop.step_log().is_empty());
+    assert!(prop.stats().accepted_steps > 0);
+}
+
+#[rstest]
+fn pinned_epochs_eccentric(almanac: Arc<Almanac>) {
+    use nyx::State;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    // Molniya-like orbit starting at apogee
+    let orbit = Orbit::keplerian(26_600.0, 0.74, 63.4, 0.0, 270.0, 180.0, epoch, eme2k);
+    let period = orbit.period().unwrap();
+
+    let opts = IntegratorOptions::builder()
+        .max_step(30.0 * Unit::Minute)
+        .tolerance(1e-10)
+        .build();
+
+    let setup = Propagator::rk89(SpacecraftDynamics::new(OrbitalDynamics::two_body()), opts);
+
+    // Measurement-like epochs that are not aligned with the adaptive steps, one of them near perigee
+    let pinned: Vec<Epoch> = (1..8)
+        .map(|i| epoch + period * (f64::from(i) / 8.0) + 17.123_456_789 * Unit::Second)
+        .collect();
+
+    let (_, traj) = setup
+        .with(orbit.into(), almanac.clone())
+        .with_pinned_epochs(pinned.clone())
+        .for_duration_with_traj(period)
+        .unwrap();
+
+    for (i, pinned_epoch) in pinned.iter().enumerate() {
+        // The trajectory has a state exactly at each pinned epoch
+        let pinned_state = traj
+            .states
+            .iter()
+            .find(|state| state.epoch() == *pinned_epoch)
+            .unwrap_or_else(|| panic!("no state at pinned epoch {pinned_epoch}"));
+
+        // Independently propagate to that epoch, pinning the previous epochs only
+        let expected = setup
+            .with(orbit.into(), almanac.clone())
+            .with_pinned_epochs(pinned[..i].iter().copied())
+            .until_epoch(*pinned_epoch)
+            .unwrap();
+
+        assert_eq!(pinned_state.orbit.radius_km, expected.orbit.radius_km);
+        assert_eq!(
+            pinned_state.orbit.velocity_km_s,
+            expected.orbit.velocity_km_s
+        );
+    }
+
+    // Without pinning, the states at these epochs are interpolated and differ at the interpolation error level
+    let (_, unpinned_traj) = setup
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(period)
+        .unwrap();
+
+    let mut max_err_km = 0.0_f64;
+    for pinned_epoch in &pinned {
+        assert!(unpinned_traj
+            .states
+            .iter()
+            .all(|state| state.epoch() != *pinned_epoch));
+        let pinned_state = traj.at(*pinned_epoch).unwrap();
+        let interp_state = unpinned_traj.at(*pinned_epoch).unwrap();
+        let err_km = (pinned_state.orbit.radius_km - interp_state.orbit.radius_km).norm();
+        max_err_km = max_err_km.max(err_km);
+    }
+    println!("max interpolation error: {max_err_km:e} km");
+    assert!(max_err_km > 0.0);
+    assert!(max_err_km < 1e-2);
+
+    // Pinning also works when propagating backward
+    let end_state = setup
+        .with(orbit.into(), almanac.clone())
+        .for_duration(period)
+        .unwrap();
+
+    let mut prop = setup
+        .with(end_state, almanac.clone())
+        .with_pinned_epochs(pinned.clone());
+    let (tx, rx) = std::sync::mpsc::channel();
+    prop.for_duration_with_channel(-period, tx).unwrap();
+    let states: Vec<Spacecraft> = rx.into_iter().collect();
+    for pinned_epoch in &pinned {
+        assert!(
+            states.iter().any(|state| state.epoch() == *pinned_epoch),
+            "no state at pinned epoch {pinned_epoch} when propagating backward"
+        );
+    }
+    assert!(states
+        .windows(2)
+        .all(|pair| pair[1].epoch() < pair[0].epoch()));
+}
@@ -413,3 +413,111 @@ fn sc_set_unset_static(almanac: Arc<Almanac>) {
 
     assert_eq!(init_sc, init2);
 }
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn stm_step_and_accumulated(almanac: Arc<Almanac>) {
+    use anise::constants::frames::IAU_EARTH_FRAME;
+    use nyx::dynamics::sph_harmonics::Harmonics;
+    use nyx::io::gravity::HarmonicsMem;
+    use nyx::linalg::SMatrix;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let harmonics = Harmonics::from_stor(iau_earth, HarmonicsMem::j2_jgm3());
+    let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::from_model(
+        harmonics,
+    )));
+
+    let init = Spacecraft::from(Orbit::keplerian(
+        7000.0, 0.01, 51.6, 30.0, 45.0, 10.0, epoch, eme2k,
+    ));
+
+    let mut instance = prop.with(init.with_stm(), almanac.clone()).quiet();
+    assert!(instance.stm(StmKind::Step).is_err());
+
+    // Accumulate the step STMs over a day: Φ(t_n, t_0) = Φ(t_n, t_{n-1}) ... Φ(t_1, t_0)
+    let mut product = SMatrix::<f64, 9, 9>::identity();
+    let mut wrong_order = SMatrix::<f64, 9, 9>::identity();
+    while instance.state.epoch() < epoch + 1 * Unit::Day {
+        instance.single_step().unwrap();
+        let step_stm = instance.stm(StmKind::Step).unwrap();
+        product = step_stm * product;
+        wrong_order *= step_stm;
+    }
+    let duration = instance.state.epoch() - epoch;
+
+    let accumulated = instance.stm(StmKind::Traj).unwrap();
+    assert_eq!(accumulated, instance.state.stm().unwrap());
+    assert!(
+        (accumulated - product).norm() < 1e-10 * accumulated.norm(),
+        "accumulated STM differs from the product of the step STMs"
+    );
+    assert!((accumulated - wrong_order).norm() > 1e-3 * accumulated.norm());
+
+    // The trajectory stores the accumulated STM
+    let (end_state, traj) = prop
+        .with(init.with_stm(), almanac.clone())
+        .for_duration_with_traj(duration)
+        .unwrap();
+    assert_eq!(traj.last().stm.unwrap(), end_state.stm.unwrap());
+    assert!((end_state.stm.unwrap() - accumulated).norm() < 1e-8 * accumulated.norm());
+
+    // Compare the orbital block with central finite differences
+    let accumulated_6x6: Matrix6<f64> = accumulated.fixed_view::<6, 6>(0, 0).into_owned();
+    for i in 0..6 {
+        let pert = if i < 3 { 1e-3 } else { 1e-6 };
+        let mut plus = init;
+        let mut minus = init;
+        if i < 3 {
+            plus.orbit.radius_km[i] += pert;
+            minus.orbit.radius_km[i] -= pert;
+        } else {
+            plus.orbit.velocity_km_s[i - 3] += pert;
+            minus.orbit.velocity_km_s[i - 3] -= pert;
+        }
+
+        let plus = prop
+            .with(plus, almanac.clone())
+            .for_duration(duration)
+            .unwrap();
+        let minus = prop
+            .with(minus, almanac.clone())
+            .for_duration(duration)
+            .unwrap();
+
+        let column =
+            (plus.orbit.to_cartesian_pos_vel() - minus.orbit.to_cartesian_pos_vel()) / (2.0 * pert);
+        let err = (column - accumulated_6x6.column(i)).norm() / column.norm();
+        println!("column {i}: relative error {err:.3e}");
+        assert!(err < 1e-4, "STM column {i} differs from finite differences");
+    }
+}
+
+#[rstest]
+fn orbit_to_spacecraft_with_stm(almanac: Arc<Almanac>) {
+    use nyx::cosmic::OrbitExt;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 28.5, 10.0, 20.0, 30.0, epoch, eme2k);
+
+    let init = orbit.to_spacecraft_with_stm();
+    assert_eq!(init.orbit, orbit);
+    assert_eq!(init.orbit_stm().unwrap(), Matrix6::identity());
+    // Without the STM enabled, there is no STM to fetch
+    assert!(Spacecraft::from(orbit).orbit_stm().is_err());
+
+    let end = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(init, almanac)
+        .for_duration(10 * Unit::Minute)
+        .unwrap();
+
+    let stm = end.orbit_stm().unwrap();
+    println!("{stm}");
+    assert!((stm - Matrix6::identity()).norm() > 1.0);
+    // The position sensitivity to the initial velocity is about the elapsed time over this short arc
+    assert!((stm[(0, 3)] - 600.0).abs() < 100.0);
+}
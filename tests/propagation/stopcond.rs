@@ -394,3 +394,76 @@ fn event_and_combination(almanac: Arc<Almanac>) {
         }
     }
 }
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn suborbital_impact(almanac: Arc<Almanac>) {
+    use nyx::dynamics::Drag;
+    use nyx::propagators::{InvalidStateCheck, PropagationError};
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let r_eq_km = eme2k.mean_equatorial_radius_km().unwrap();
+
+    let epoch = Epoch::from_gregorian_utc_at_noon(2024, 1, 1);
+    // Apoapsis at about 220 km of altitude and periapsis well below the surface: start at apoapsis and fall.
+    let orbit = Orbit::keplerian(6000.0, 0.1, 45.0, 0.0, 0.0, 180.0, epoch, eme2k);
+    let sc = Spacecraft::builder()
+        .orbit(orbit)
+        .dry_mass_kg(500.0)
+        .build()
+        .with_drag(1.0, 2.2);
+
+    let dynamics = SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        Drag::earth_exp(almanac.clone()).unwrap(),
+    );
+
+    // Small steps such that the trajectory includes states between the surface and the floor 50 km below it.
+    let opts = IntegratorOptions::builder()
+        .max_step(10 * Unit::Second)
+        .min_altitude_km(-50.0)
+        .build();
+
+    let setup = Propagator::rk89(dynamics, opts);
+    let mut prop = setup.with(sc, almanac.clone());
+    let (traj, err) = prop.for_duration_with_partial_traj(1 * Unit::Hour);
+
+    let (epoch_invalid, last_valid) = match err {
+        Some(PropagationError::InvalidState {
+            check:
+                InvalidStateCheck::BelowMinRadius {
+                    rmag_km,
+                    min_rmag_km,
+                },
+            epoch,
+            last_valid,
+        }) => {
+            println!("stopped at {epoch}: {rmag_km} km < {min_rmag_km} km");
+            assert!(rmag_km < min_rmag_km);
+            assert!((min_rmag_km - (r_eq_km - 50.0)).abs() < 1e-9);
+            (epoch, last_valid)
+        }
+        other => panic!("expected an invalid state below the minimum radius, got {other:?}"),
+    };
+
+    // The propagator is reset to the last valid state, which is the last state of the partial trajectory.
+    assert_eq!(prop.state.orbit, last_valid);
+    assert_eq!(traj.last().orbit, last_valid);
+    assert!(traj.last().epoch() < epoch_invalid);
+    assert!(last_valid.rmag_km() >= r_eq_km - 50.0);
+    assert!(traj.states.iter().all(|state| state
+        .orbit
+        .to_cartesian_pos_vel()
+        .iter()
+        .all(|x| x.is_finite())));
+
+    // The impact on the surface is in the partial trajectory.
+    let impact = Event::impact(eme2k, 0.0).unwrap();
+    let impacts = traj.find(&impact, almanac).unwrap();
+    assert_eq!(impacts.len(), 1);
+    println!("impact @ {}", impacts[0].state.epoch());
+    assert!((impacts[0].state.orbit.rmag_km() - r_eq_km).abs() < 1e-2);
+    assert!(impacts[0].state.epoch() < epoch_invalid);
+}
@@ -0,0 +1,119 @@
+extern crate nyx_space as nyx;
+
+use anise::constants::frames::EARTH_J2000;
+use anise::prelude::Almanac;
+use hifitime::TimeUnits;
+use nyx::cosmic::{GuidanceMode, Orbit, Spacecraft};
+use nyx::dynamics::guidance::{FiniteBurns, LocalFrame, Mnvr, Thruster};
+use nyx::dynamics::{Dynamics, OrbitalDynamics, SpacecraftDynamics};
+use nyx::linalg::Vector3;
+use nyx::propagators::*;
+use nyx::time::Epoch;
+use nyx::State;
+use std::sync::Arc;
+
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn timeline_mass_drop(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 28.5, 10.0, 20.0, 30.0, start, eme2k);
+
+    let dry_mass_kg = 1000.0;
+    let fuel_mass_kg = 500.0;
+    let sc = Spacecraft::from_thruster(
+        orbit,
+        dry_mass_kg,
+        fuel_mass_kg,
+        Thruster {
+            thrust_N: 10.0,
+            isp_s: 300.0,
+        },
+        GuidanceMode::Thrust,
+    );
+
+    // Constant thrust along the velocity for the whole propagation, without decrementing the fuel mass
+    let burn = Mnvr::from_time_invariant(
+        start,
+        start + 3.hours(),
+        1.0,
+        Vector3::new(1.0, 0.0, 0.0),
+        LocalFrame::VNC,
+    );
+    let dynamics = SpacecraftDynamics::from_guidance_law_no_decr(
+        OrbitalDynamics::two_body(),
+        FiniteBurns::from_mnvrs(vec![burn]),
+    );
+    let coast_dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+
+    let drop_epoch = start + 1.hours();
+    let timeline = Timeline::from_elapsed(
+        start,
+        &[
+            (
+                1.hours(),
+                ConfigChangeKind::DryMassDelta { delta_kg: -50.0 },
+            ),
+            (2.hours(), ConfigChangeKind::DragArea { area_m2: 12.0 }),
+            // Outside of the propagation span: ignored with a warning
+            (5.hours(), ConfigChangeKind::Cd { cd: 2.5 }),
+        ],
+    )
+    .unwrap();
+
+    let (end_sc, traj) = Propagator::default(dynamics.clone())
+        .with(sc, almanac.clone())
+        .for_duration_with_timeline(3.hours(), &timeline)
+        .unwrap();
+
+    println!("{traj}");
+    for (epoch, note) in &traj.annotations {
+        println!("{epoch}: {note}");
+    }
+
+    assert_eq!(traj.annotations.len(), 2);
+    assert_eq!(traj.annotations[0].0, drop_epoch);
+    assert_eq!(end_sc.epoch(), start + 3.hours());
+    assert_eq!(end_sc.dry_mass_kg, dry_mass_kg - 50.0);
+    assert_eq!(end_sc.drag.area_m2, 12.0);
+    assert_eq!(end_sc.drag.cd, sc.drag.cd);
+
+    // The propagator stepped exactly to the epoch of the drop, and the trajectory stores the state after the drop
+    let post_drop = traj
+        .states
+        .iter()
+        .find(|state| state.epoch() == drop_epoch)
+        .expect("no state at the drop epoch");
+    assert_eq!(post_drop.dry_mass_kg, dry_mass_kg - 50.0);
+    let pre_drop = traj.at(drop_epoch - 1.seconds()).unwrap();
+    assert_eq!(pre_drop.dry_mass_kg, dry_mass_kg);
+
+    // Acceleration from the thrust only, removing the gravity from the total acceleration
+    let thrust_accel = |state: &Spacecraft| -> f64 {
+        let total = dynamics
+            .eom(0.0, &state.to_vector(), state, almanac.clone())
+            .unwrap();
+        let gravity = coast_dynamics
+            .eom(0.0, &state.to_vector(), state, almanac.clone())
+            .unwrap();
+        (total - gravity).fixed_rows::<3>(3).norm()
+    };
+
+    let pre_accel = thrust_accel(&pre_drop);
+    let post_accel = thrust_accel(post_drop);
+    let expected_ratio = (dry_mass_kg + fuel_mass_kg) / (dry_mass_kg - 50.0 + fuel_mass_kg);
+    println!(
+        "thrust acceleration: {pre_accel:e} km/s^2 before the drop, {post_accel:e} km/s^2 after (ratio {})",
+        post_accel / pre_accel
+    );
+    assert!((pre_accel - 10.0 / 1500.0 * 1e-3).abs() < 1e-12);
+    assert!((post_accel / pre_accel - expected_ratio).abs() < 1e-9);
+}
@@ -5,13 +5,15 @@ use anise::constants::frames::{EARTH_J2000, MOON_J2000};
 use hifitime::TimeUnits;
 use nyx::cosmic::eclipse::EclipseLocator;
 use nyx::cosmic::{GuidanceMode, Orbit, Spacecraft};
-use nyx::dynamics::guidance::{GuidanceLaw, Ruggiero, Thruster};
+use nyx::dynamics::guidance::{FiniteBurns, GuidanceLaw, LocalFrame, Mnvr, Ruggiero, Thruster};
 use nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
-use nyx::io::trajectory_data::TrajectoryLoader;
-use nyx::md::prelude::{ExportCfg, Objective};
+use nyx::io::trajectory_data::{TrajReadCfg, TrajectoryLoader};
+use nyx::io::EpochRepr;
+use nyx::linalg::Vector3;
+use nyx::md::prelude::{ExportCfg, Objective, Traj};
 use nyx::md::StateParameter;
 use nyx::propagators::*;
-use nyx::time::{Epoch, TimeSeries, Unit};
+use nyx::time::{Duration, Epoch, TimeScale, TimeSeries, Unit};
 use nyx::State;
 use std::path::PathBuf;
 use std::sync::mpsc::channel;
@@ -544,3 +546,1434 @@ fn traj_ephem_backward(almanac: Arc<Almanac>) {
         "Maximum state in interpolation is too high!"
     );
 }
+
+#[rstest]
+fn traj_arc_length_revolutions(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    // Near circular orbit (the periapsis must be defined), so the arc length is 2*pi*a per revolution.
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let sma = eme2k.mean_equatorial_radius_km().unwrap() + 900.0;
+    let orbit = Orbit::keplerian(sma, 1e-4, 28.5, 0.0, 0.0, 45.0, start_dt, eme2k);
+    let period = orbit.period().unwrap();
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = setup
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(period * 2.5)
+        .unwrap();
+
+    let arc_length_km = traj.arc_length(10 * Unit::Second).unwrap();
+    let expected_km = 2.5 * 2.0 * std::f64::consts::PI * sma;
+    println!("arc length: {arc_length_km:.6} km\texpected: {expected_km:.6} km");
+    assert!((arc_length_km - expected_km).abs() / expected_km < 1e-6);
+
+    let revs = traj
+        .revolutions(
+            StateParameter::Periapsis,
+            Unit::Millisecond,
+            almanac.clone(),
+        )
+        .unwrap();
+    println!("revolutions: {revs:.6}");
+    assert!((revs - 2.5).abs() < 1e-3);
+
+    // Counted from the ascending node, the trajectory starts at an argument of latitude of 45 degrees.
+    let revs = traj
+        .revolutions(StateParameter::AoL, Unit::Millisecond, almanac.clone())
+        .unwrap();
+    println!("revolutions from the ascending node: {revs:.6}");
+    assert!((revs - 2.5).abs() < 1e-3);
+    assert!(traj
+        .revolutions(StateParameter::Rmag, Unit::Millisecond, almanac.clone())
+        .is_err());
+
+    // The count does not depend on whether the trajectory starts exactly at periapsis or just after it.
+    for start_ta_deg in [0.0, 1e-6, 1e-3] {
+        let start = Orbit::keplerian(
+            20_000.0,
+            0.3,
+            28.5,
+            30.0,
+            45.0,
+            start_ta_deg,
+            start_dt,
+            eme2k,
+        );
+        let (_, traj) = setup
+            .with(start.into(), almanac.clone())
+            .for_duration_with_traj(start.period().unwrap() * 2.0)
+            .unwrap();
+        let revs = traj
+            .revolutions(
+                StateParameter::Periapsis,
+                Unit::Millisecond,
+                almanac.clone(),
+            )
+            .unwrap();
+        println!("revolutions from TA = {start_ta_deg} deg: {revs:.9}");
+        assert!((revs - 2.0).abs() < 1e-6, "TA = {start_ta_deg} deg");
+    }
+
+    // Sparse samples of an eccentric orbit, several per revolution, over ten revolutions.
+    let eccentric = Orbit::keplerian(20_000.0, 0.3, 28.5, 30.0, 45.0, 10.0, start_dt, eme2k);
+    let ecc_period = eccentric.period().unwrap();
+    let mut sparse = Traj::new();
+    for epoch in TimeSeries::inclusive(start_dt, start_dt + ecc_period * 10, ecc_period * 0.25) {
+        sparse
+            .states
+            .push(Spacecraft::from(eccentric.at_epoch(epoch).unwrap()));
+    }
+    sparse.finalize();
+    let revs = sparse
+        .revolutions(
+            StateParameter::Periapsis,
+            Unit::Millisecond,
+            almanac.clone(),
+        )
+        .unwrap();
+    assert!((revs - 10.0).abs() < 1e-6, "sparse revolutions: {revs}");
+
+    // Hyperbolic arc through its periapsis: a single passage, and the fraction of the true anomaly swept.
+    let hyperbolic_peri = Orbit::cartesian(7000.0, 0.0, 0.0, 0.0, 12.0, 0.0, start_dt, eme2k);
+    let (hyperbolic_start, _) = setup
+        .with(hyperbolic_peri.into(), almanac.clone())
+        .for_duration_with_traj(-1 * Unit::Hour)
+        .unwrap();
+    let (_, hyperbolic) = setup
+        .with(hyperbolic_start, almanac.clone())
+        .for_duration_with_traj(2 * Unit::Hour)
+        .unwrap();
+    let swept_deg = (hyperbolic.last().orbit.ta_deg().unwrap()
+        - hyperbolic.first().orbit.ta_deg().unwrap())
+    .rem_euclid(360.0);
+    let revs = hyperbolic
+        .revolutions(
+            StateParameter::Periapsis,
+            Unit::Millisecond,
+            almanac.clone(),
+        )
+        .unwrap();
+    assert!(revs < 1.0);
+    assert!(
+        (revs - swept_deg / 360.0).abs() < 1e-9,
+        "hyperbolic revolutions: {revs}"
+    );
+
+    // No burn, so no delta-v.
+    assert_eq!(traj.accumulated_dv().unwrap(), 0.0);
+
+    // Now thrust along the velocity for ten minutes.
+    let lowt = Thruster {
+        thrust_N: 10.0,
+        isp_s: 300.0,
+    };
+    let start_state = Spacecraft::from_thruster(orbit, 300.0, 67.0, lowt, GuidanceMode::Thrust);
+    let mnvr = Mnvr::from_time_invariant(
+        start_dt,
+        start_dt + 10 * Unit::Minute,
+        1.0,
+        Vector3::x(),
+        LocalFrame::VNC,
+    );
+
+    let dynamics = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        FiniteBurns::from_mnvrs(vec![mnvr]),
+    );
+    let setup = Propagator::default(dynamics.clone());
+    let (end_state, traj) = setup
+        .with(start_state, almanac.clone())
+        .for_duration_with_traj(20 * Unit::Minute)
+        .unwrap();
+
+    let dv_km_s = traj.accumulated_dv().unwrap();
+    let expected_dv_km_s =
+        lowt.exhaust_velocity_m_s() * 1e-3 * (start_state.mass_kg() / end_state.mass_kg()).ln();
+    println!("accumulated dv: {dv_km_s:.9} km/s\texpected: {expected_dv_km_s:.9} km/s");
+    assert!((dv_km_s - expected_dv_km_s).abs() < 1e-9);
+
+    // Integrating the thrust acceleration of the dynamics matches the rocket equation
+    let integrated_dv_km_s = traj
+        .integrated_dv(&dynamics, 1 * Unit::Second, almanac.clone())
+        .unwrap();
+    println!("integrated dv: {integrated_dv_km_s:.9} km/s");
+    assert!((integrated_dv_km_s - expected_dv_km_s).abs() / expected_dv_km_s < 1e-3);
+    // Sanity check against the constant acceleration approximation
+    assert!((dv_km_s - 1e-3 * 10.0 / 367.0 * 600.0).abs() / dv_km_s < 1e-2);
+}
+
+#[rstest]
+fn traj_param_stats_height(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    // Slightly eccentric equatorial LEO, so the geodetic height is the radius minus the equatorial radius.
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let radius_km = eme2k.mean_equatorial_radius_km().unwrap();
+    let sma = radius_km + 600.0;
+    let ecc = 0.01;
+    let orbit = Orbit::keplerian(sma, ecc, 0.0, 0.0, 0.0, 90.0, start_dt, eme2k);
+    let period = orbit.period().unwrap();
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = setup
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(period)
+        .unwrap();
+
+    let stats = traj
+        .param_stats(StateParameter::Height, 10 * Unit::Second)
+        .unwrap();
+    println!("{stats}");
+
+    // Starting at a true anomaly of 90 degrees, both apsides are crossed exactly once.
+    let ma_deg = orbit.ma_deg().unwrap();
+    let perigee_epoch = start_dt + period * ((360.0 - ma_deg) / 360.0);
+    let apogee_epoch = start_dt + period * ((180.0 - ma_deg) / 360.0);
+
+    assert!((stats.min - (sma * (1.0 - ecc) - radius_km)).abs() < 1e-2);
+    assert!((stats.max - (sma * (1.0 + ecc) - radius_km)).abs() < 1e-2);
+    assert!((stats.min_epoch - perigee_epoch).abs() <= 10 * Unit::Second);
+    assert!((stats.max_epoch - apogee_epoch).abs() <= 10 * Unit::Second);
+
+    // The time average of the radius is a(1 + e^2/2), and its standard deviation is about a*e/sqrt(2).
+    assert!(stats.min < stats.mean && stats.mean < stats.max);
+    assert!((stats.mean - (sma * (1.0 + ecc.powi(2) / 2.0) - radius_km)).abs() < 1.0);
+    assert!((stats.std_dev - sma * ecc / 2.0_f64.sqrt()).abs() < 2.0);
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn traj_to_frame_covariance(almanac: Arc<Almanac>) {
+    use anise::constants::frames::IAU_EARTH_FRAME;
+    use nyx::linalg::{Matrix3, Matrix6, Vector6};
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::keplerian(7000.0, 0.01, 51.6, 30.0, 45.0, 10.0, start_dt, eme2k);
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(Spacecraft::from(start_state).with_stm(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+
+    // Diagonal inertial covariance at the start of the trajectory
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 2.0, 3.0, 1e-6, 2e-6, 3e-6));
+    let inertial_covars = traj.orbit_covariances(&init_covar).unwrap();
+
+    // Convert into the body fixed frame, and map the rotated initial covariance with the rotated STMs.
+    let traj_fixed = traj.to_frame(iau_earth, almanac.clone()).unwrap();
+    let dcm_t0 = almanac
+        .rotate(eme2k, iau_earth, start_dt)
+        .unwrap()
+        .state_dcm();
+    let fixed_covars = traj_fixed
+        .orbit_covariances(&(dcm_t0 * init_covar * dcm_t0.transpose()))
+        .unwrap();
+
+    assert_eq!(inertial_covars.len(), fixed_covars.len());
+
+    let pos_trace = |covar: &Matrix6<f64>| -> f64 {
+        let pos: Matrix3<f64> = covar.fixed_view::<3, 3>(0, 0).into_owned();
+        pos.trace()
+    };
+
+    for ((epoch, inertial), (fixed_epoch, fixed)) in inertial_covars.iter().zip(&fixed_covars) {
+        assert_eq!(epoch, fixed_epoch);
+        let dcm_t = almanac
+            .rotate(eme2k, iau_earth, *epoch)
+            .unwrap()
+            .state_dcm();
+        let expected = dcm_t * inertial * dcm_t.transpose();
+        assert!(
+            (expected - fixed).norm() < 1e-9 * inertial.norm(),
+            "covariance not rotated @ {epoch}"
+        );
+        // The position block is rotated orthogonally, so its trace is preserved.
+        assert!((pos_trace(inertial) - pos_trace(fixed)).abs() < 1e-9 * pos_trace(inertial));
+    }
+
+    // In the RIC frame, the position block of the covariance matches the rotation into the radial, in-track, cross-track axes
+    // computed independently from the position and velocity.
+    let last = traj.last();
+    let (_, last_covar) = inertial_covars.last().unwrap();
+    let dcm_inertial2ric = last
+        .orbit
+        .dcm_from_ric_to_inertial()
+        .unwrap()
+        .transpose()
+        .state_dcm();
+    let ric_covar = dcm_inertial2ric * last_covar * dcm_inertial2ric.transpose();
+
+    let r_hat = last.orbit.radius_km.normalize();
+    let c_hat = last
+        .orbit
+        .radius_km
+        .cross(&last.orbit.velocity_km_s)
+        .normalize();
+    let i_hat = c_hat.cross(&r_hat);
+    let rot = Matrix3::from_rows(&[r_hat.transpose(), i_hat.transpose(), c_hat.transpose()]);
+    let pos_covar: Matrix3<f64> = last_covar.fixed_view::<3, 3>(0, 0).into_owned();
+    let expected_pos = rot * pos_covar * rot.transpose();
+    let ric_pos: Matrix3<f64> = ric_covar.fixed_view::<3, 3>(0, 0).into_owned();
+    assert!(
+        (expected_pos - ric_pos).norm() < 1e-9 * pos_covar.norm(),
+        "RIC position covariance differs from the independent rotation:\n{expected_pos:.6e}\n{ric_pos:.6e}"
+    );
+    // The radial variance is the projection of the covariance on the radial direction.
+    let radial_var = (r_hat.transpose() * pos_covar * r_hat)[(0, 0)];
+    assert!((ric_pos[(0, 0)] - radial_var).abs() < 1e-9 * radial_var);
+    assert!((pos_trace(last_covar) - pos_trace(&ric_covar)).abs() < 1e-9 * pos_trace(last_covar));
+    println!("RIC covariance at {}:\n{ric_covar:.6e}", last.epoch());
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn traj_node_drift(almanac: Arc<Almanac>) {
+    use anise::constants::frames::IAU_EARTH_FRAME;
+    use nyx::utils::between_pm_180;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    // Near repeat orbit: slightly more than 15 revolutions per sidereal day.
+    let sidereal_day_s = 360.0 / 360.985_623_5 * 86_400.0;
+    let period_s = sidereal_day_s / 15.0 * (1.0 - 1e-4);
+    let mu_km3_s2 = eme2k.mu_km3_s2().unwrap();
+    let sma_km = (mu_km3_s2 * (period_s / std::f64::consts::TAU).powi(2)).cbrt();
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2024, 3, 1);
+    let start_state = Orbit::keplerian(sma_km, 0.001, 97.0, 30.0, 45.0, 10.0, start_dt, eme2k);
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(start_state.into(), almanac.clone())
+        .for_duration_with_traj(17.0 * period_s * Unit::Second)
+        .unwrap();
+
+    let report = traj.node_drift(iau_earth, almanac.clone()).unwrap();
+    println!("{report}");
+
+    assert!(report.crossings.len() >= 16);
+
+    // Without node precession, the nodal period is the Keplerian period, so the node moves west by the Earth rotation over one period.
+    let expected_drift_deg = -360.985_623_5 / 86_400.0 * period_s;
+    assert!(
+        (report.mean_drift_deg() - expected_drift_deg).abs() < 1e-3,
+        "mean drift {} != {expected_drift_deg}",
+        report.mean_drift_deg()
+    );
+    // The drift is constant, including across the anti-meridian
+    for drift_deg in &report.drift_deg {
+        assert!((drift_deg - expected_drift_deg).abs() < 1e-3);
+    }
+    assert!(report.drift_rate_deg().abs() < 1e-5);
+
+    // The ground track almost repeats after 15 revolutions, shifted by the mismatch with the Earth rotation.
+    let unwrapped = report.unwrapped_longitudes_deg();
+    let repeat_offset_deg = between_pm_180(unwrapped[15] - unwrapped[0]);
+    let expected_offset_deg = between_pm_180(15.0 * expected_drift_deg);
+    assert!((repeat_offset_deg - expected_offset_deg).abs() < 1e-2);
+    assert!(repeat_offset_deg.abs() < 0.1);
+
+    // The node longitude parameter matches the geodetic longitude at the crossings.
+    let traj_fixed = traj.to_frame(iau_earth, almanac).unwrap();
+    for crossing in &report.crossings {
+        let state = traj_fixed.at(crossing.epoch).unwrap();
+        assert!(state.orbit.radius_km.z.abs() < 1e-2);
+        assert!(between_pm_180(state.orbit.longitude_deg() - crossing.longitude_deg).abs() < 1e-3);
+    }
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "node_drift.parquet",
+    ]
+    .iter()
+    .collect();
+    report.to_parquet(path, ExportCfg::default()).unwrap();
+}
+
+#[rstest]
+fn traj_diff_parquet(almanac: Arc<Almanac>) {
+    use polars::prelude::*;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(7200.0, 0.01, 28.5, 30.0, 45.0, 10.0, start_dt, eme2k);
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = setup
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(2 * Unit::Hour)
+        .unwrap();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "traj_self_diff.parquet",
+    ]
+    .iter()
+    .collect();
+
+    let cfg = ExportCfg::builder().step(5 * Unit::Minute).build();
+    let out_path = traj.diff_to_parquet(&traj, path, cfg).unwrap();
+
+    let df = ParquetReader::new(File::open(out_path).unwrap())
+        .finish()
+        .unwrap();
+
+    // Two hours sampled every five minutes, inclusive
+    assert_eq!(df.height(), 25);
+
+    let diff_cols = df
+        .columns([
+            "Delta X (km)",
+            "Delta Y (km)",
+            "Delta Z (km)",
+            "Delta Vx (km/s)",
+            "Delta Vy (km/s)",
+            "Delta Vz (km/s)",
+            "RSS position (km)",
+            "RSS velocity (km/s)",
+        ])
+        .unwrap();
+
+    for series in diff_cols {
+        for value in series.f64().unwrap().into_iter() {
+            assert_eq!(value, Some(0.0), "{} is not zero", series.name());
+        }
+    }
+
+    // Trajectories in different frames cannot be diffed directly.
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let traj_moon = traj.to_frame(moon_j2k, almanac).unwrap();
+    assert!(traj
+        .diff_to_parquet(
+            &traj_moon,
+            [
+                env!("CARGO_MANIFEST_DIR"),
+                "output_data",
+                "traj_bad_diff.parquet"
+            ]
+            .iter()
+            .collect::<PathBuf>(),
+            ExportCfg::default()
+        )
+        .is_err());
+}
+
+#[rstest]
+fn traj_sliding_windows(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 0.0, 0.0, 45.0, start_dt, eme2k);
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbit.into(), almanac)
+        .for_duration_with_traj(70 * Unit::Minute)
+        .unwrap();
+
+    let width = 20 * Unit::Minute;
+    let step = 15 * Unit::Minute;
+    let windows = traj.windows(width, step).collect::<Vec<(Epoch, Epoch)>>();
+    for (start, end) in &windows {
+        println!("{start} -> {end}");
+    }
+
+    // Windows start at 0, 15, 30 and 45 minutes, and a last window ends on the last state.
+    assert_eq!(windows.len(), 5);
+    assert_eq!(windows[0].0, traj.first().epoch());
+    assert_eq!(windows[4].1, traj.last().epoch());
+    assert_eq!(windows[4].0, traj.last().epoch() - width);
+
+    for (start, end) in &windows {
+        assert_eq!(
+            *end - *start,
+            width,
+            "all windows should have the requested width"
+        );
+    }
+
+    for pair in windows.windows(2) {
+        // No gap between consecutive windows
+        assert!(pair[1].0 <= pair[0].1);
+    }
+
+    for pair in windows[..4].windows(2) {
+        assert_eq!(pair[1].0 - pair[0].0, step);
+        assert_eq!(pair[0].1 - pair[1].0, width - step, "unexpected overlap");
+    }
+
+    // The bounds can be used to sample the trajectory within each window
+    let (start, end) = windows[1];
+    let samples = traj.every_between(60 * Unit::Second, start, end).count();
+    assert_eq!(samples, 21);
+
+    // A window wider than the trajectory spans it entirely
+    let all = traj
+        .windows(2 * Unit::Hour, step)
+        .collect::<Vec<(Epoch, Epoch)>>();
+    assert_eq!(all, vec![(traj.first().epoch(), traj.last().epoch())]);
+
+    // Invalid steps do not yield any window
+    assert_eq!(traj.windows(width, Duration::ZERO).count(), 0);
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn traj_parquet_frame_metadata(almanac: Arc<Almanac>) {
+    let luna = almanac.frame_from_uid(MOON_J2000).unwrap();
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(2100.0, 1e-3, 85.0, 10.0, 0.0, 45.0, start_dt, luna);
+
+    let (_, mut traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+    traj.name = Some("LLO".to_string());
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "traj_frame_metadata.parquet",
+    ]
+    .iter()
+    .collect();
+
+    let exported_path = traj.to_parquet_simple(path, almanac).unwrap();
+
+    let loader = TrajectoryLoader::from_parquet(exported_path).unwrap();
+    println!("{loader}");
+    assert_eq!(loader.name(), Some("LLO"));
+    assert_eq!(loader.frame_name(), Some(format!("{luna}").as_str()));
+
+    let loaded = loader.to_traj::<Spacecraft>().unwrap();
+    assert_eq!(loaded.name, traj.name);
+    assert_eq!(format!("{}", loaded.first().orbit.frame), format!("{luna}"));
+    assert_eq!(loaded.first().orbit.frame.ephemeris_id, luna.ephemeris_id);
+    assert_eq!(
+        loaded.first().orbit.frame.orientation_id,
+        luna.orientation_id
+    );
+    assert_eq!(loaded.states.len(), traj.states.len());
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn traj_parquet_chunked_read(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 10.0, 20.0, 45.0, start_dt, eme2k);
+
+    // A large-ish trajectory of 200k states, built analytically to be quick
+    let step = 10 * Unit::Second;
+    let (_, traj) = AnalyticPropagator::two_body(step)
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(2_000_000 * Unit::Second)
+        .unwrap();
+    assert_eq!(traj.states.len(), 200_001);
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "traj_chunked_read.parquet",
+    ]
+    .iter()
+    .collect();
+
+    let cfg = ExportCfg::builder()
+        .fields(vec![
+            StateParameter::X,
+            StateParameter::Y,
+            StateParameter::Z,
+            StateParameter::VX,
+            StateParameter::VY,
+            StateParameter::VZ,
+            StateParameter::FuelMass,
+        ])
+        .time_scale(TimeScale::TDB)
+        .epoch_repr(EpochRepr::SecondsPastJ2000)
+        .row_group_size(10_000)
+        .build();
+
+    let exported_path = traj.to_parquet_with_cfg(path, cfg, almanac).unwrap();
+    let loader = TrajectoryLoader::from_parquet(exported_path).unwrap();
+
+    // Read back a one hour window, whose bounds do not fall on a state
+    let window_start = start_dt + 10 * Unit::Day + 5 * Unit::Second;
+    let window_end = window_start + 1 * Unit::Hour;
+    let window = loader
+        .to_traj_with_cfg::<Spacecraft>(
+            TrajReadCfg::builder()
+                .start_epoch(window_start)
+                .end_epoch(window_end)
+                .build(),
+        )
+        .unwrap();
+
+    let expected = traj
+        .states
+        .iter()
+        .filter(|state| (window_start..=window_end).contains(&state.epoch()))
+        .collect::<Vec<&Spacecraft>>();
+    assert_eq!(expected.len(), 360);
+    assert_eq!(window.states.len(), expected.len());
+
+    for (loaded, orig) in window.states.iter().zip(expected) {
+        assert!((loaded.epoch() - orig.epoch()).abs() < 1 * Unit::Microsecond);
+        assert!((window_start..=window_end).contains(&loaded.epoch()));
+        assert_eq!(
+            loaded.orbit.to_cartesian_pos_vel(),
+            orig.orbit.to_cartesian_pos_vel()
+        );
+        assert_eq!(loaded.orbit.frame, eme2k);
+    }
+
+    // Quick-look loading of every thousandth state
+    let quick_look = loader
+        .to_traj_with_cfg::<Spacecraft>(TrajReadCfg::builder().every_nth(1_000).build())
+        .unwrap();
+    assert_eq!(quick_look.states.len(), 201);
+    for (ii, loaded) in quick_look.states.iter().enumerate() {
+        let orig = traj.states[ii * 1_000];
+        assert!((loaded.epoch() - orig.epoch()).abs() < 1 * Unit::Microsecond);
+        assert_eq!(
+            loaded.orbit.to_cartesian_pos_vel(),
+            orig.orbit.to_cartesian_pos_vel()
+        );
+    }
+
+    // Reading the whole file matches the original trajectory
+    assert_eq!(
+        loader.to_traj::<Spacecraft>().unwrap().states.len(),
+        200_001
+    );
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn traj_rotating_frame_interpolation(almanac: Arc<Almanac>) {
+    use anise::constants::frames::IAU_EARTH_FRAME;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    // Inclined and eccentric geosynchronous orbit, sampled every two hours for two days from its exact conic.
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let geo = Orbit::keplerian(42_164.0, 0.05, 15.0, 30.0, 45.0, 10.0, start_dt, eme2k);
+
+    let mut traj = Traj::new();
+    for epoch in TimeSeries::inclusive(start_dt, start_dt + 2 * Unit::Day, 2 * Unit::Hour) {
+        traj.states
+            .push(Spacecraft::from(geo.at_epoch(epoch).unwrap()));
+    }
+    traj.finalize();
+
+    // Converting into a rotating frame sets up the interpolation in the inertial orientation of that frame.
+    let traj_fixed = traj.to_frame(iau_earth, almanac.clone()).unwrap();
+    assert!(traj_fixed.inertial_interp.is_some());
+    // Same trajectory, interpolated directly in the rotating frame
+    let mut traj_fixed_naive = traj_fixed.clone();
+    traj_fixed_naive.inertial_interp = None;
+
+    let mut max_err_km = 0.0_f64;
+    let mut max_naive_err_km = 0.0_f64;
+    for state in traj_fixed.every(17 * Unit::Minute) {
+        let truth = almanac
+            .transform_to(geo.at_epoch(state.epoch()).unwrap(), iau_earth, None)
+            .unwrap();
+        assert_eq!(state.orbit.frame, iau_earth);
+        max_err_km = max_err_km.max((state.orbit.radius_km - truth.radius_km).norm());
+
+        let naive = traj_fixed_naive.at(state.epoch()).unwrap();
+        max_naive_err_km = max_naive_err_km.max((naive.orbit.radius_km - truth.radius_km).norm());
+    }
+
+    println!("max error: {max_err_km:.3e} km (naive interpolation: {max_naive_err_km:.3e} km)");
+    assert!(max_err_km < 1e-3, "interpolation error of {max_err_km} km");
+
+    // Resampling keeps the interpolation through the inertial frame.
+    assert!(traj_fixed
+        .resample(1 * Unit::Hour)
+        .unwrap()
+        .inertial_interp
+        .is_some());
+
+    // Converting back into an inertial frame does not need it.
+    assert!(traj_fixed
+        .to_frame(eme2k, almanac.clone())
+        .unwrap()
+        .inertial_interp
+        .is_none());
+}
+
+#[rstest]
+fn traj_join_gap_policy(almanac: Arc<Almanac>) {
+    use nyx::md::trajectory::GapPolicy;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    // Two arcs of the same conic with a two hour gap between them.
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(7_000.0, 0.01, 28.5, 30.0, 45.0, 10.0, start_dt, eme2k);
+
+    let arc = |start: Epoch, end: Epoch| {
+        let mut traj = Traj::new();
+        for epoch in TimeSeries::inclusive(start, end, 1 * Unit::Minute) {
+            traj.states
+                .push(Spacecraft::from(orbit.at_epoch(epoch).unwrap()));
+        }
+        traj.finalize();
+        traj
+    };
+
+    let gap_start = start_dt + 3 * Unit::Hour;
+    let gap_end = gap_start + 2 * Unit::Hour;
+    let first = arc(start_dt, gap_start);
+    let second = arc(gap_end, gap_end + 3 * Unit::Hour);
+
+    // The error policy rejects the gapped join, but not contiguous trajectories.
+    assert!(first.join_with_policy(&second, GapPolicy::Error).is_err());
+    assert!(first
+        .join_with_policy(&arc(gap_start, gap_end), GapPolicy::Error)
+        .is_ok());
+
+    // The default policy, used by the addition, interpolates across the gap.
+    let interpolated = first
+        .join_with_policy(&second, GapPolicy::WarnAndInterpolate)
+        .unwrap();
+    assert_eq!(interpolated, (&first + &second).unwrap());
+    assert!(interpolated.gaps.is_empty());
+    assert!(interpolated.at(gap_start + 1 * Unit::Hour).is_ok());
+
+    // The marker policy records the gap and refuses to interpolate within it.
+    let marked = first
+        .join_with_policy(&second, GapPolicy::InsertBoundaryMarkers)
+        .unwrap();
+    assert_eq!(marked.gaps, vec![(gap_start, gap_end)]);
+    assert_eq!(
+        marked.states.len(),
+        first.states.len() + second.states.len()
+    );
+    assert!(marked.at(gap_start + 1 * Unit::Hour).is_err());
+    assert!(marked.at(gap_start + 1 * Unit::Second).is_err());
+    assert!(marked.at(gap_end - 1 * Unit::Second).is_err());
+
+    // The boundaries themselves and states near them are available, and only interpolated from their own arc.
+    for epoch in [
+        gap_start,
+        gap_end,
+        gap_start - 30 * Unit::Second,
+        gap_end + 30 * Unit::Second,
+    ] {
+        let state = marked.at(epoch).unwrap();
+        let truth = orbit.at_epoch(epoch).unwrap();
+        assert!((state.orbit.radius_km - truth.radius_km).norm() < 1e-3);
+    }
+
+    // Iterating over the trajectory skips the gap.
+    let samples = marked.every(10 * Unit::Minute).collect::<Vec<Spacecraft>>();
+    assert_eq!(samples.len(), 19 + 19);
+    assert!(samples
+        .iter()
+        .all(|state| state.epoch() <= gap_start || state.epoch() >= gap_end));
+}
+
+#[rstest]
+fn traj_every_anomaly(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    // Eccentric orbit, starting just after periapsis, propagated for five and a half revolutions.
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(20_000.0, 0.3, 28.5, 30.0, 45.0, 10.0, start_dt, eme2k);
+    let period = orbit.period().unwrap();
+    let duration = period * 5.5;
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = setup
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(duration)
+        .unwrap();
+
+    let revs = traj
+        .revolutions(
+            StateParameter::Periapsis,
+            Unit::Millisecond,
+            almanac.clone(),
+        )
+        .unwrap();
+
+    // One state per revolution at periapsis: one per full revolution flown.
+    let periapses = traj
+        .every_anomaly(1, StateParameter::Periapsis, almanac.clone())
+        .unwrap();
+    assert_eq!(periapses.len(), revs.floor() as usize);
+    for state in &periapses {
+        let ta_deg = state.orbit.ta_deg().unwrap();
+        assert!(ta_deg.min(360.0 - ta_deg) < 2e-3, "TA = {ta_deg} deg");
+    }
+
+    // One state per revolution at apoapsis: the first one is reached after half a revolution.
+    let apoapses = traj
+        .every_anomaly(1, StateParameter::Apoapsis, almanac.clone())
+        .unwrap();
+    assert_eq!(apoapses.len(), 6);
+    for state in &apoapses {
+        assert!((state.orbit.ta_deg().unwrap() - 180.0).abs() < 2e-3);
+    }
+    // Consecutive apoapses are one period apart, to within the event precision (1e-3 deg is about 0.14 s at apoapsis).
+    for pair in apoapses.windows(2) {
+        assert!(((pair[1].epoch() - pair[0].epoch()) - period).abs() < 300 * Unit::Millisecond);
+    }
+
+    // Four states per revolution in true anomaly, from TA = 10 deg until TA = 5 revolutions + 186 deg: 22 states.
+    let quarters = traj
+        .every_anomaly(4, StateParameter::TrueAnomaly, almanac.clone())
+        .unwrap();
+    assert_eq!(quarters.len(), 22);
+    for state in &quarters {
+        let ta_deg = state.orbit.ta_deg().unwrap();
+        let from_quarter_deg = (ta_deg + 45.0).rem_euclid(90.0) - 45.0;
+        assert!(from_quarter_deg.abs() < 2e-3, "TA = {ta_deg} deg");
+    }
+    // The samples are not equally spaced in time on an eccentric orbit...
+    let dt_to_apo = quarters[1].epoch() - quarters[0].epoch();
+    let dt_to_peri = quarters[3].epoch() - quarters[2].epoch();
+    assert!(dt_to_peri * 2 < dt_to_apo);
+
+    // ... unless they are equally spaced in mean anomaly.
+    let mean_octants = traj
+        .every_anomaly(8, StateParameter::MeanAnomaly, almanac.clone())
+        .unwrap();
+    assert_eq!(mean_octants.len(), 44);
+    for pair in mean_octants.windows(2) {
+        assert!(((pair[1].epoch() - pair[0].epoch()) - period / 8).abs() < 300 * Unit::Millisecond);
+    }
+
+    // Only angles with a periapsis can anchor the samples.
+    assert!(traj
+        .every_anomaly(1, StateParameter::Rmag, almanac.clone())
+        .is_err());
+
+    // Sampling during the propagation finds the same states without storing the trajectory.
+    let (_, prop_quarters) = setup
+        .with(orbit.into(), almanac.clone())
+        .for_duration_every_anomaly(duration, 4, StateParameter::TrueAnomaly)
+        .unwrap();
+    assert_eq!(prop_quarters.len(), quarters.len());
+    for (prop_state, traj_state) in prop_quarters.iter().zip(quarters.iter()) {
+        assert!((prop_state.epoch() - traj_state.epoch()).abs() < 300 * Unit::Millisecond);
+    }
+}
+
+#[rstest]
+fn traj_look_angles_overhead_pass(almanac: Arc<Almanac>) {
+    use anise::constants::frames::IAU_EARTH_FRAME;
+    use anise::constants::usual_planetary_constants::MEAN_EARTH_ANGULAR_VELOCITY_DEG_S;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let iau_earth = almanac.frame_from_uid(IAU_EARTH_FRAME).unwrap();
+
+    // Station on the equator, and an equatorial circular orbit passing right above it at the epoch of closest approach.
+    let tca = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let station = Orbit::try_latlongalt(
+        0.0,
+        10.0,
+        0.0,
+        MEAN_EARTH_ANGULAR_VELOCITY_DEG_S,
+        tca,
+        iau_earth,
+    )
+    .unwrap();
+    let overhead_fixed = Orbit::try_latlongalt(
+        0.0,
+        10.0,
+        500.0,
+        MEAN_EARTH_ANGULAR_VELOCITY_DEG_S,
+        tca,
+        iau_earth,
+    )
+    .unwrap();
+    let radius_km = almanac
+        .transform_to(overhead_fixed, eme2k, None)
+        .unwrap()
+        .radius_km;
+    let velocity_km_s = (eme2k.mu_km3_s2().unwrap() / radius_km.norm()).sqrt()
+        * Vector3::z().cross(&radius_km).normalize();
+    let overhead = Orbit::new(
+        radius_km.x,
+        radius_km.y,
+        radius_km.z,
+        velocity_km_s.x,
+        velocity_km_s.y,
+        velocity_km_s.z,
+        tca,
+        eme2k,
+    );
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = setup
+        .with(
+            overhead.at_epoch(tca - 10 * Unit::Minute).unwrap().into(),
+            almanac.clone(),
+        )
+        .for_duration_with_traj(20 * Unit::Minute)
+        .unwrap();
+
+    let look_angles = traj
+        .look_angles(station, iau_earth, 10 * Unit::Second, almanac.clone())
+        .unwrap();
+    assert_eq!(look_angles.len(), 121);
+
+    let (peak_epoch, _, peak_el_deg, peak_range_km) = look_angles
+        .iter()
+        .copied()
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+        .unwrap();
+    println!("peak elevation of {peak_el_deg:.3} deg at {peak_epoch} ({peak_range_km:.3} km)");
+
+    // The elevation peaks near the zenith at the closest approach, at the orbit altitude.
+    assert!(peak_el_deg > 89.0);
+    assert!((peak_epoch - tca).abs() <= 10 * Unit::Second);
+    assert!((peak_range_km - 500.0).abs() < 1.0);
+    for (_, _, el_deg, range_km) in &look_angles {
+        assert!(*range_km >= peak_range_km - 1e-6);
+        assert!(*el_deg <= peak_el_deg);
+    }
+
+    // The spacecraft rises in the West and sets in the East.
+    let (_, rise_az_deg, rise_el_deg, _) = look_angles[0];
+    let (_, set_az_deg, set_el_deg, _) = look_angles[120];
+    println!("rise: az = {rise_az_deg:.3} deg, el = {rise_el_deg:.3} deg");
+    println!("set: az = {set_az_deg:.3} deg, el = {set_el_deg:.3} deg");
+    assert!((rise_az_deg - 270.0).abs() < 5.0);
+    assert!((set_az_deg - 90.0).abs() < 5.0);
+    assert!(rise_el_deg < 45.0 && set_el_deg < 45.0);
+}
+
+#[rstest]
+fn traj_czml(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    // An orbit about the Moon, converted to the Earth centered inertial frame of CZML
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(2000.0, 0.01, 60.0, 10.0, 20.0, 30.0, epoch, moon_j2k);
+
+    let (_, mut traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+    traj.name = Some("LunarSat".to_string());
+
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "lunar_sat.czml"]
+        .iter()
+        .collect();
+    let step = 1 * Unit::Minute;
+    let exported = traj.to_czml(path, step, almanac.clone()).unwrap();
+
+    let czml: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(exported).unwrap()).unwrap();
+    let packets = czml.as_array().unwrap();
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[0]["id"], "document");
+    assert_eq!(packets[0]["version"], "1.0");
+    let current_time = packets[0]["clock"]["currentTime"].as_str().unwrap();
+    assert!(current_time.starts_with("2024-01-01T00:00:00") && current_time.ends_with('Z'));
+
+    let position = &packets[1]["position"];
+    assert_eq!(packets[1]["id"], "LunarSat");
+    assert_eq!(position["referenceFrame"], "INERTIAL");
+    let cartesian = position["cartesian"].as_array().unwrap();
+    let num_samples = traj.every(step).count();
+    assert_eq!(num_samples, 61);
+    assert_eq!(cartesian.len(), 4 * num_samples);
+
+    // Each sample is the offset in seconds then the position in meters about the Earth
+    for (i, sample) in cartesian.chunks(4).enumerate() {
+        assert_eq!(sample[0].as_f64().unwrap(), 60.0 * i as f64);
+        let radius_m = sample[1..]
+            .iter()
+            .map(|x| x.as_f64().unwrap().powi(2))
+            .sum::<f64>()
+            .sqrt();
+        assert!((3.5e8..4.1e8).contains(&radius_m), "{radius_m} m");
+    }
+}
+
+#[rstest]
+fn traj_parquet_tdb_nanoseconds(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    // Thirty years after J2000, where seconds stored as 64 bit floats are only precise to about 100 ns
+    let start_dt = Epoch::from_gregorian_utc_hms(2030, 1, 1, 0, 0, 0) + 123 * Unit::Nanosecond;
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 10.0, 20.0, 45.0, start_dt, eme2k);
+
+    let (_, traj) = AnalyticPropagator::two_body(7 * Unit::Second)
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "traj_tdb_nanoseconds.parquet",
+    ]
+    .iter()
+    .collect();
+
+    let cfg = ExportCfg::builder()
+        .time_scale(TimeScale::TDB)
+        .epoch_repr(EpochRepr::NanosecondsPastJ2000)
+        .legacy_seconds_column(true)
+        .row_group_size(1_000)
+        .build();
+
+    let exported_path = traj.to_parquet_with_cfg(path, cfg, almanac).unwrap();
+    let loader = TrajectoryLoader::from_parquet(exported_path).unwrap();
+
+    // The epochs are read back exactly, from the nanoseconds column
+    let loaded = loader.to_traj::<Spacecraft>().unwrap();
+    assert_eq!(loaded.states.len(), traj.states.len());
+    for (loaded, orig) in loaded.states.iter().zip(&traj.states) {
+        assert_eq!(loaded.epoch(), orig.epoch());
+    }
+
+    // And a window is sliced by these epochs
+    let window_start = start_dt + 2 * Unit::Hour;
+    let window_end = window_start + 1 * Unit::Hour;
+    let window = loader
+        .to_traj_with_cfg::<Spacecraft>(
+            TrajReadCfg::builder()
+                .start_epoch(window_start)
+                .end_epoch(window_end)
+                .build(),
+        )
+        .unwrap();
+
+    let expected = traj
+        .states
+        .iter()
+        .filter(|state| (window_start..=window_end).contains(&state.epoch()))
+        .collect::<Vec<&Spacecraft>>();
+    assert_eq!(window.states.len(), expected.len());
+    for (loaded, orig) in window.states.iter().zip(expected) {
+        assert_eq!(loaded.epoch(), orig.epoch());
+    }
+}
+
+#[rstest]
+fn traj_to_frame_short(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_dt = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 10.0, 20.0, 45.0, start_dt, eme2k);
+
+    // Only four states
+    let (_, traj) = Propagator::rk89(
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        IntegratorOptions::with_fixed_step(1 * Unit::Minute),
+    )
+    .with(orbit.into(), almanac.clone())
+    .for_duration_with_traj(3 * Unit::Minute)
+    .unwrap();
+    assert_eq!(traj.states.len(), 4);
+
+    // The conversion does not query the trajectory past its last state: all states are converted, and the
+    // converted trajectory is queried up to and including its last state.
+    let traj_luna = traj.to_frame(MOON_J2000, almanac.clone()).unwrap();
+    assert_eq!(traj_luna.states.len(), traj.states.len());
+    assert_eq!(traj_luna.first().epoch(), traj.first().epoch());
+    assert_eq!(traj_luna.last().epoch(), traj.last().epoch());
+    assert_eq!(traj_luna.every(30 * Unit::Second).count(), 7);
+
+    let traj_back = traj_luna.to_frame(eme2k, almanac).unwrap();
+    for (orig, back) in traj.states.iter().zip(&traj_back.states) {
+        assert!((orig.orbit.radius_km - back.orbit.radius_km).norm() < 1e-6);
+    }
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn traj_parquet_field_annotations(almanac: Arc<Almanac>) {
+    use nyx::md::prelude::Event;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let moon_j2k = almanac.frame_from_uid(MOON_J2000).unwrap();
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(7200.0, 0.01, 28.5, 30.0, 45.0, 10.0, start_dt, eme2k);
+
+    let (_, traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(orbit.into(), almanac.clone())
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "traj_field_annotations.parquet",
+    ]
+    .iter()
+    .collect();
+
+    // The SMA with respect to the Moon is evaluated in a different frame than the trajectory
+    let moon_sma = Event::in_frame(StateParameter::SMA, 0.0, moon_j2k);
+
+    let exported_path = traj
+        .to_parquet(path, Some(vec![&moon_sma]), ExportCfg::default(), almanac)
+        .unwrap();
+
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(&exported_path).unwrap()).unwrap();
+    let schema = builder.schema();
+
+    let x_meta = schema.field_with_name("x (km)").unwrap().metadata();
+    assert_eq!(x_meta["parameter"], "X");
+    assert_eq!(x_meta["unit"], "km");
+    assert_eq!(x_meta["frame"], format!("{eme2k}"));
+
+    let ecc_meta = schema.field_with_name("ecc").unwrap().metadata();
+    assert_eq!(ecc_meta["parameter"], "Eccentricity");
+    assert_eq!(ecc_meta["frame"], format!("{eme2k}"));
+
+    // Spacecraft parameters do not depend on the frame
+    let fuel_meta = schema.field_with_name("fuel_mass (kg)").unwrap().metadata();
+    assert_eq!(fuel_meta["parameter"], "FuelMass");
+    assert_eq!(fuel_meta["unit"], "kg");
+    assert!(!fuel_meta.contains_key("frame"));
+    assert!(!fuel_meta.contains_key("Frame"));
+
+    let event_meta = schema
+        .field_with_name(&format!("{moon_sma}"))
+        .unwrap()
+        .metadata();
+    assert_eq!(event_meta["unit"], "km");
+    assert_eq!(event_meta["frame"], format!("{moon_j2k}"));
+
+    // The trajectory is still read back from the annotated columns
+    let loaded = TrajectoryLoader::from_parquet(exported_path)
+        .unwrap()
+        .to_traj::<Spacecraft>()
+        .unwrap();
+    assert_eq!(loaded.states.len(), traj.states.len());
+}
+
+#[rstest]
+fn traj_from_csv_state_table(almanac: Arc<Almanac>) {
+    use nyx::io::csv_traj::{CsvColumn, CsvDelimiter, CsvFrame, CsvTrajCfg, LengthUnit};
+    use std::collections::HashMap;
+    use std::io::Cursor;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let cfg = CsvTrajCfg::builder()
+        .epoch_column("MJD_UTC")
+        .epoch_repr(EpochRepr::Mjd)
+        .position_columns(["X_m".into(), "Y_m".into(), "Z_m".into()])
+        .velocity_columns(["VX_m_s".into(), "VY_m_s".into(), "VZ_m_s".into()])
+        .length_unit(LengthUnit::Meter)
+        .frame(CsvFrame::Column {
+            column: "FRAME".into(),
+            frames: HashMap::from([("EME2000".to_string(), eme2k)]),
+        })
+        .build();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "csv",
+        "leo_mjd_utc_m.csv",
+    ]
+    .iter()
+    .collect();
+
+    let traj = cfg.load(&path).unwrap();
+    println!("{traj}");
+
+    assert_eq!(traj.states.len(), 5);
+    let start = Epoch::from_mjd_utc(60310.0);
+    assert_eq!(traj.first().epoch(), start);
+    assert!((traj.last().epoch() - (start + 4 * Unit::Minute)).abs() < 1 * Unit::Millisecond);
+
+    let first = traj.first().orbit;
+    assert_eq!(first.frame, eme2k);
+    assert!((first.radius_km - Vector3::new(7000.0, 0.0, 0.0)).norm() < 1e-9);
+    assert!((first.velocity_km_s - Vector3::new(0.0, 4.687214249, 5.913792590)).norm() < 1e-12);
+
+    let second = traj.states[1].orbit;
+    assert!(
+        (second.radius_km - Vector3::new(6985.362638895, 281.036803479, 354.580199990)).norm()
+            < 1e-9
+    );
+    assert!(
+        (second.velocity_km_s - Vector3::new(-0.487741924, 4.677413042, 5.901426544)).norm()
+            < 1e-12
+    );
+
+    // The same states in a gzipped whitespace delimited table without headers, mapped by index, and in a fixed frame
+    let ws_cfg = CsvTrajCfg::builder()
+        .epoch_column(CsvColumn::Index(6))
+        .epoch_repr(EpochRepr::Mjd)
+        .position_columns([
+            CsvColumn::Index(3),
+            CsvColumn::Index(4),
+            CsvColumn::Index(5),
+        ])
+        .velocity_columns([
+            CsvColumn::Index(0),
+            CsvColumn::Index(1),
+            CsvColumn::Index(2),
+        ])
+        .length_unit(LengthUnit::Meter)
+        .frame(CsvFrame::Fixed(eme2k))
+        .delimiter(CsvDelimiter::Whitespace)
+        .has_headers(false)
+        .build();
+
+    let gz_traj = ws_cfg
+        .load(path.with_file_name("leo_mjd_utc_m.txt.gz"))
+        .unwrap();
+    assert_eq!(gz_traj.states.len(), traj.states.len());
+    for (gz_state, state) in gz_traj.states.iter().zip(traj.states.iter()) {
+        assert_eq!(gz_state.epoch(), state.epoch());
+        assert_eq!(gz_state.orbit.radius_km, state.orbit.radius_km);
+        assert_eq!(gz_state.orbit.velocity_km_s, state.orbit.velocity_km_s);
+    }
+
+    // A corrupted value is reported with its line and column
+    let corrupted = "MJD_UTC,X_m,Y_m,Z_m,VX_m_s,VY_m_s,VZ_m_s,FRAME\n\
+        60310.0,7000000.0,0.0,0.0,0.0,4687.214249,5913.792590,EME2000\n\
+        60310.000694444447,6985362.638895,28103x6.803479,354580.199990,-487.741924,4677.413042,5901.426544,EME2000\n";
+    let err = cfg.parse(Cursor::new(corrupted)).unwrap_err().to_string();
+    println!("{err}");
+    assert!(err.contains("line 3"), "{err}");
+    assert!(err.contains("column `Y_m`"), "{err}");
+    assert!(err.contains("28103x6.803479"), "{err}");
+
+    // And so is an unknown frame
+    let unknown_frame = "MJD_UTC,X_m,Y_m,Z_m,VX_m_s,VY_m_s,VZ_m_s,FRAME\n\
+        60310.0,7000000.0,0.0,0.0,0.0,4687.214249,5913.792590,GCRF\n";
+    let err = cfg
+        .parse(Cursor::new(unknown_frame))
+        .unwrap_err()
+        .to_string();
+    println!("{err}");
+    assert!(
+        err.contains("line 2") && err.contains("column `FRAME`"),
+        "{err}"
+    );
+}
+
+#[rstest]
+fn traj_interpolation_method(almanac: Arc<Almanac>) {
+    use nyx::md::trajectory::InterpMethod;
+
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    // Eccentric LEO sampled every five minutes over two orbits from its exact conic.
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let leo = Orbit::keplerian(8_000.0, 0.1, 28.5, 30.0, 45.0, 10.0, start_dt, eme2k);
+    let step = 5 * Unit::Minute;
+
+    let mut traj = Traj::new();
+    for epoch in TimeSeries::inclusive(start_dt, start_dt + 4 * Unit::Hour, step) {
+        traj.states
+            .push(Spacecraft::from(leo.at_epoch(epoch).unwrap()));
+    }
+    traj.finalize();
+    assert_eq!(traj.interpolation, InterpMethod::Hermite);
+
+    let mut traj_lagrange = traj.clone();
+    traj_lagrange.set_interpolation(InterpMethod::Lagrange);
+    // The method is kept when resampling.
+    assert_eq!(
+        traj_lagrange.resample(step).unwrap().interpolation,
+        InterpMethod::Lagrange
+    );
+
+    let mut max_hermite_err_km = 0.0_f64;
+    let mut max_lagrange_err_km = 0.0_f64;
+    for epoch in TimeSeries::exclusive(start_dt, start_dt + 4 * Unit::Hour, step) {
+        // Both methods reproduce the samples exactly.
+        let truth = leo.at_epoch(epoch).unwrap();
+        for traj in [&traj, &traj_lagrange] {
+            let state = traj.at(epoch).unwrap();
+            assert_eq!(state.orbit.radius_km, truth.radius_km);
+            assert_eq!(state.orbit.velocity_km_s, truth.velocity_km_s);
+        }
+
+        // Compare at the midpoint between the samples.
+        let mid_epoch = epoch + step / 2;
+        let truth = leo.at_epoch(mid_epoch).unwrap();
+
+        let hermite = traj.at(mid_epoch).unwrap();
+        let lagrange = traj_lagrange.at(mid_epoch).unwrap();
+        assert_eq!(hermite.epoch(), mid_epoch);
+        assert_eq!(lagrange.epoch(), mid_epoch);
+
+        max_hermite_err_km =
+            max_hermite_err_km.max((hermite.orbit.radius_km - truth.radius_km).norm());
+        max_lagrange_err_km =
+            max_lagrange_err_km.max((lagrange.orbit.radius_km - truth.radius_km).norm());
+    }
+
+    println!(
+        "max midpoint error: Hermite {max_hermite_err_km:.3e} km, Lagrange {max_lagrange_err_km:.3e} km"
+    );
+    assert!(max_hermite_err_km < 1e-3);
+    assert!(max_lagrange_err_km < 1.0);
+    assert!(max_hermite_err_km < max_lagrange_err_km);
+}
+
+#[rstest]
+fn traj_max_states(almanac: Arc<Almanac>) {
+    let _ = pretty_env_logger::try_init();
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    // Eccentric LEO sampled every thirty seconds over two orbits from its exact conic.
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let end_dt = start_dt + 4 * Unit::Hour;
+    let leo = Orbit::keplerian(8_000.0, 0.1, 28.5, 30.0, 45.0, 10.0, start_dt, eme2k);
+
+    let mut traj = Traj::new();
+    for epoch in TimeSeries::inclusive(start_dt, end_dt, 30 * Unit::Second) {
+        traj.states
+            .push(Spacecraft::from(leo.at_epoch(epoch).unwrap()));
+    }
+    traj.finalize();
+    assert_eq!(traj.states.len(), 481);
+
+    let max_states = 100;
+    let traj = traj.with_max_states(max_states);
+    println!("decimated to {} states", traj.states.len());
+    assert!(traj.states.len() <= max_states);
+    assert!(traj.states.len() > max_states / 2);
+    assert_eq!(traj.first().epoch(), start_dt);
+    assert_eq!(traj.last().epoch(), end_dt);
+
+    // The decimated trajectory still interpolates the conic accurately
+    let mut max_err_km = 0.0_f64;
+    for epoch in TimeSeries::inclusive(start_dt, end_dt, 7 * Unit::Second) {
+        let truth = leo.at_epoch(epoch).unwrap();
+        let state = traj.at(epoch).unwrap();
+        max_err_km = max_err_km.max((state.orbit.radius_km - truth.radius_km).norm());
+    }
+    println!("max interpolation error: {max_err_km:.3e} km");
+    assert!(max_err_km < 1e-3);
+
+    // The cap is enforced when the trajectory grows again
+    let mut extended = traj.clone();
+    for epoch in TimeSeries::inclusive(end_dt, end_dt + 4 * Unit::Hour, 30 * Unit::Second) {
+        extended
+            .states
+            .push(Spacecraft::from(leo.at_epoch(epoch).unwrap()));
+    }
+    extended.finalize();
+    assert!(extended.states.len() <= max_states);
+    assert_eq!(extended.first().epoch(), start_dt);
+    assert_eq!(extended.last().epoch(), end_dt + 4 * Unit::Hour);
+}
+
+#[rstest]
+fn traj_bincode_round_trip(almanac: Arc<Almanac>) {
+    use nyx::md::trajectory::InterpMethod;
+
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_dt = Epoch::from_gregorian_utc_hms(2030, 1, 1, 0, 0, 0) + 123 * Unit::Nanosecond;
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 10.0, 20.0, 45.0, start_dt, eme2k);
+    let sc = Spacecraft::from_thruster(
+        orbit,
+        500.0,
+        75.0,
+        Thruster {
+            thrust_N: 0.5,
+            isp_s: 1800.0,
+        },
+        GuidanceMode::Coast,
+    );
+
+    let (_, mut traj) = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(sc, almanac.clone())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+    traj.name = Some("bincode".to_string());
+    traj.annotations
+        .push((start_dt + 1 * Unit::Hour, "annotated".to_string()));
+    traj.set_interpolation(InterpMethod::Lagrange);
+
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "traj.bin"]
+        .iter()
+        .collect();
+    let saved_path = traj.save_bin(path).unwrap();
+    let loaded = Traj::<Spacecraft>::load_bin(&saved_path).unwrap();
+
+    assert_eq!(loaded.name, traj.name);
+    assert_eq!(loaded.annotations, traj.annotations);
+    assert_eq!(loaded.gaps, traj.gaps);
+    assert_eq!(loaded.interpolation, InterpMethod::Lagrange);
+    assert_eq!(loaded.states.len(), traj.states.len());
+    for (loaded, orig) in loaded.states.iter().zip(&traj.states) {
+        assert_eq!(loaded.epoch(), orig.epoch());
+        assert_eq!(loaded.orbit.radius_km, orig.orbit.radius_km);
+        assert_eq!(loaded.orbit.velocity_km_s, orig.orbit.velocity_km_s);
+        assert_eq!(loaded.orbit.frame, orig.orbit.frame);
+        assert_eq!(loaded.dry_mass_kg, orig.dry_mass_kg);
+        assert_eq!(loaded.fuel_mass_kg, orig.fuel_mass_kg);
+        assert_eq!(loaded.srp, orig.srp);
+        assert_eq!(loaded.drag, orig.drag);
+        assert_eq!(loaded.mode, orig.mode);
+        assert_eq!(
+            loaded.thruster.map(|thr| (thr.thrust_N, thr.isp_s)),
+            orig.thruster.map(|thr| (thr.thrust_N, thr.isp_s))
+        );
+    }
+
+    // The loaded frame carries its gravitational parameter, so the loaded trajectory is usable without the Almanac
+    let mid_epoch = start_dt + 12 * Unit::Hour + 17 * Unit::Second;
+    let interpolated = loaded.at(mid_epoch).unwrap();
+    assert_eq!(interpolated.orbit, traj.at(mid_epoch).unwrap().orbit);
+    assert!((interpolated.orbit.sma_km().unwrap() - 7000.0).abs() < 1e-3);
+
+    // Other files are rejected
+    let parquet_path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "traj_bin.parquet",
+    ]
+    .iter()
+    .collect();
+    let parquet_path = traj.to_parquet_simple(parquet_path, almanac).unwrap();
+    assert!(Traj::<Spacecraft>::load_bin(parquet_path).is_err());
+}
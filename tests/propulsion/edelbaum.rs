@@ -0,0 +1,164 @@
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::{GuidanceMode, Orbit, Spacecraft};
+use self::nyx::dynamics::guidance::{Objective, Ruggiero, StateParameter, Thruster};
+use self::nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use self::nyx::propagators::{IntegratorMethod, IntegratorOptions, Propagator};
+use self::nyx::time::{Epoch, Unit};
+use self::nyx::tools::edelbaum::EdelbaumTransfer;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+use std::sync::Arc;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[rstest]
+fn edelbaum_analytic(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let mu_km3_s2 = eme2k.mu_km3_s2().unwrap();
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    // Classic transfer from a 300 km LEO inclined at 28.5 deg to GEO, with an initial yaw of 21.34 deg
+    let leo = Orbit::keplerian(6678.0, 0.0, 28.5, 0.0, 0.0, 0.0, epoch, eme2k);
+    let geo = Orbit::keplerian(42164.0, 0.0, 0.0, 0.0, 0.0, 0.0, epoch, eme2k);
+    let transfer = EdelbaumTransfer::from_orbits(leo, geo).unwrap();
+    assert!((transfer.inclination_change_deg - 28.5).abs() < 1e-9);
+
+    let dv_km_s = transfer.delta_v_km_s();
+    println!(
+        "LEO to GEO: {dv_km_s:.6} km/s, initial yaw {:.3} deg",
+        transfer.initial_yaw_deg()
+    );
+    assert!((dv_km_s - 5.9508).abs() < 1e-3);
+    assert!((transfer.initial_yaw_deg() - 21.3376).abs() < 1e-3);
+    // More expensive than the coplanar spiral
+    let coplanar = transfer.initial_velocity_km_s() - transfer.final_velocity_km_s();
+    assert!(dv_km_s > coplanar);
+
+    // The steering law achieves the final orbit once the delta-v is spent
+    assert!((transfer.velocity_km_s(0.0) - transfer.initial_velocity_km_s()).abs() < 1e-12);
+    assert!((transfer.velocity_km_s(dv_km_s) - transfer.final_velocity_km_s()).abs() < 1e-9);
+    assert!(transfer.inclination_changed_deg(0.0).abs() < 1e-9);
+    assert!((transfer.inclination_changed_deg(dv_km_s) - 28.5).abs() < 1e-6);
+    assert!((transfer.yaw_deg(0.0) - transfer.initial_yaw_deg()).abs() < 1e-9);
+    // The yaw increases as the orbit grows, where plane changes are cheaper
+    assert!(transfer.yaw_deg(dv_km_s) > transfer.initial_yaw_deg());
+
+    // Pure plane change at constant radius: Δv = 2 v sin(π Δi / 4)
+    let plane_change = EdelbaumTransfer::new(mu_km3_s2, 7000.0, 7000.0, 10.0).unwrap();
+    let v_km_s = plane_change.initial_velocity_km_s();
+    assert!(
+        (plane_change.delta_v_km_s()
+            - 2.0 * v_km_s * (std::f64::consts::FRAC_PI_4 * 10.0_f64.to_radians()).sin())
+        .abs()
+            < 1e-12
+    );
+    assert!((plane_change.initial_yaw_deg() - 90.0).abs() < 1e-9);
+
+    // Coplanar descent: the thrust opposes the velocity
+    let descent = EdelbaumTransfer::new(mu_km3_s2, 8000.0, 7000.0, 0.0).unwrap();
+    assert!((descent.initial_yaw_deg() - 180.0).abs() < 1e-9);
+    assert!(
+        (descent.delta_v_km_s()
+            - (descent.final_velocity_km_s() - descent.initial_velocity_km_s()))
+        .abs()
+            < 1e-12
+    );
+
+    // Forward and inverse modes are consistent
+    let report = transfer.with_thrust(0.5, 1800.0, 1000.0).unwrap();
+    println!("{report}");
+    assert!(
+        (report.final_mass_kg() - 1000.0 * (-dv_km_s / (1800.0 * 9.80665e-3)).exp()).abs() < 1e-9
+    );
+    let inverse = transfer
+        .required_thrust(report.transfer_time, 1800.0, 1000.0)
+        .unwrap();
+    assert!((inverse.thrust_N - 0.5).abs() < 1e-9);
+    assert!((inverse.propellant_mass_kg - report.propellant_mass_kg).abs() < 1e-12);
+    // Twice as fast requires twice the thrust
+    let faster = transfer
+        .required_thrust(report.transfer_time * 0.5, 1800.0, 1000.0)
+        .unwrap();
+    assert!((faster.thrust_N - 1.0).abs() < 1e-6);
+
+    // Invalid inputs
+    assert!(transfer.with_thrust(0.0, 1800.0, 1000.0).is_err());
+    assert!(transfer.with_thrust(0.5, -1.0, 1000.0).is_err());
+    assert!(transfer
+        .required_thrust(Unit::Second * 0, 1800.0, 1000.0)
+        .is_err());
+    assert!(EdelbaumTransfer::new(mu_km3_s2, -7000.0, 7000.0, 0.0).is_err());
+    assert!(EdelbaumTransfer::new(mu_km3_s2, 7000.0, 7000.0, 200.0).is_err());
+    let gto = Orbit::keplerian(24396.0, 0.7, 28.5, 0.0, 0.0, 0.0, epoch, eme2k);
+    assert!(EdelbaumTransfer::from_orbits(gto, geo).is_err());
+}
+
+/// Compares the Edelbaum estimate of a coplanar spiral to the closed loop Ruggiero guidance, cf. `rugg_sma`.
+#[rstest]
+fn edelbaum_vs_ruggiero_spiral(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let orbit = Orbit::keplerian(24396.0, 0.0, 0.0, 0.0, 0.0, 0.0, start_time, eme2k);
+    let target = Orbit::keplerian(42164.0, 0.0, 0.0, 0.0, 0.0, 0.0, start_time, eme2k);
+
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+    let fuel_mass = 67.0;
+    let dry_mass = 300.0;
+
+    let report = EdelbaumTransfer::from_orbits(orbit, target)
+        .unwrap()
+        .with_thrust(lowt.thrust_N, lowt.isp_s, dry_mass + fuel_mass)
+        .unwrap();
+    println!("{report}");
+
+    let objectives = &[Objective::within_tolerance(
+        StateParameter::SMA,
+        42_164.0,
+        1.0,
+    )];
+
+    let guid_law = Ruggiero::simple(objectives, orbit.into()).unwrap();
+    let sc_state =
+        Spacecraft::from_thruster(orbit, dry_mass, fuel_mass, lowt, GuidanceMode::Thrust);
+    let sc = SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), guid_law);
+
+    let prop = Propagator::new(
+        sc.clone(),
+        IntegratorMethod::RungeKutta4,
+        IntegratorOptions::with_fixed_step(10.0 * Unit::Second),
+    );
+
+    // Not achieved 5% before the Edelbaum transfer time, but achieved 5% after it
+    let early_state = prop
+        .with(sc_state, almanac.clone())
+        .for_duration(report.transfer_time * 0.95)
+        .unwrap();
+    assert!(!sc.guidance_achieved(&early_state).unwrap());
+
+    let final_state = prop
+        .with(early_state, almanac)
+        .for_duration(report.transfer_time * 0.1)
+        .unwrap();
+    assert!(
+        sc.guidance_achieved(&final_state).unwrap(),
+        "objective not achieved"
+    );
+
+    let fuel_usage = fuel_mass - final_state.fuel_mass_kg;
+    println!(
+        "Ruggiero fuel usage: {fuel_usage:.3} kg (Edelbaum: {:.3} kg)",
+        report.propellant_mass_kg
+    );
+    assert!((fuel_usage / report.propellant_mass_kg - 1.0).abs() < 0.05);
+}
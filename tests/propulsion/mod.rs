@@ -1,3 +1,6 @@
 mod closedloop_multi_oe_ruggiero;
 mod closedloop_single_oe_ruggiero;
+mod edelbaum;
+mod power_table;
 mod schedule;
+mod thruster_limits;
@@ -0,0 +1,127 @@
+extern crate nyx_space as nyx;
+use self::nyx::cosmic::{GuidanceMode, Orbit, Spacecraft, STD_GRAVITY};
+use self::nyx::dynamics::guidance::{
+    FiniteBurns, LocalFrame, Mnvr, PowerPoint, PowerSource, Thruster, ThrusterPowerTable,
+};
+use self::nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use self::nyx::linalg::Vector3;
+use self::nyx::propagators::{IntegratorOptions, Propagator};
+use self::nyx::time::{Epoch, Unit};
+use std::sync::Arc;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+#[allow(non_snake_case)]
+#[rstest]
+fn sep_power_table_burn(almanac: Arc<Almanac>) {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, start_time, eme2k);
+
+    // The thrust and Isp of this thruster are overridden by the power table.
+    let hall = Thruster {
+        thrust_N: 1.0,
+        isp_s: 300.0,
+    };
+    let dry_mass_kg = 100.0;
+    let fuel_mass_kg = 50.0;
+    let sc =
+        Spacecraft::from_thruster(orbit, dry_mass_kg, fuel_mass_kg, hall, GuidanceMode::Thrust);
+
+    let burn_duration = 60 * Unit::Second;
+
+    let mnvr = Mnvr::from_time_invariant(
+        start_time,
+        start_time + burn_duration,
+        1.0,
+        Vector3::new(1.0, 0.0, 0.0),
+        LocalFrame::VNC,
+    );
+
+    let points = vec![
+        PowerPoint {
+            power_kW: 0.5,
+            thrust_N: 0.02,
+            isp_s: 1200.0,
+        },
+        PowerPoint {
+            power_kW: 1.5,
+            thrust_N: 0.06,
+            isp_s: 1600.0,
+        },
+        PowerPoint {
+            power_kW: 4.5,
+            thrust_N: 0.24,
+            isp_s: 2500.0,
+        },
+    ];
+
+    let opts = IntegratorOptions::with_fixed_step(Unit::Second);
+
+    let coast = Propagator::rk89(SpacecraftDynamics::new(OrbitalDynamics::two_body()), opts)
+        .with(sc.with_guidance_mode(GuidanceMode::Coast), almanac.clone())
+        .for_duration(burn_duration)
+        .unwrap();
+
+    let mut prev_fuel_usage_kg = 0.0;
+    // Expected thrust and Isp at 1.0 kW and 3.0 kW, linearly interpolated in the table.
+    for (power_kW, thrust_N, isp_s) in [(1.0, 0.04, 1400.0), (3.0, 0.15, 2050.0)] {
+        let power_table =
+            ThrusterPowerTable::new(points.clone(), PowerSource::Constant { power_kW }).unwrap();
+
+        let dynamics = SpacecraftDynamics::from_guidance_law(
+            OrbitalDynamics::two_body(),
+            FiniteBurns::from_mnvrs(vec![mnvr]),
+        )
+        .with_power_table(power_table);
+
+        let burned = Propagator::rk89(dynamics.clone(), opts)
+            .with(sc, almanac.clone())
+            .for_duration(burn_duration)
+            .unwrap();
+
+        // The power table provides the thruster, so the spacecraft does not need one.
+        let mut no_thruster_sc = sc;
+        no_thruster_sc.thruster = None;
+        let no_thruster_burned = Propagator::rk89(dynamics, opts)
+            .with(no_thruster_sc, almanac.clone())
+            .for_duration(burn_duration)
+            .unwrap();
+        assert_eq!(no_thruster_burned.fuel_mass_kg, burned.fuel_mass_kg);
+        assert_eq!(no_thruster_burned.orbit, burned.orbit);
+
+        // The mass flow is constant during the burn.
+        let mass_flow_kg_s = thrust_N / (isp_s * STD_GRAVITY);
+        let fuel_usage_kg = sc.fuel_mass_kg - burned.fuel_mass_kg;
+        println!(
+            "{power_kW} kW: used {fuel_usage_kg:.9} kg, expected {:.9} kg",
+            mass_flow_kg_s * burn_duration.to_seconds()
+        );
+        assert!(
+            (fuel_usage_kg - mass_flow_kg_s * burn_duration.to_seconds()).abs() / fuel_usage_kg
+                < 1e-9
+        );
+
+        // The delta-v imparted follows the rocket equation with the interpolated Isp.
+        let dv_km_s = (burned.orbit.velocity_km_s - coast.orbit.velocity_km_s).norm();
+        let expected_dv_km_s =
+            isp_s * STD_GRAVITY * 1e-3 * ((dry_mass_kg + fuel_mass_kg) / burned.mass_kg()).ln();
+        println!(
+            "{power_kW} kW: delta-v {:.6} m/s, expected {:.6} m/s",
+            dv_km_s * 1e3,
+            expected_dv_km_s * 1e3
+        );
+        assert!((dv_km_s - expected_dv_km_s).abs() / expected_dv_km_s < 1e-2);
+
+        assert!(fuel_usage_kg > prev_fuel_usage_kg);
+        prev_fuel_usage_kg = fuel_usage_kg;
+    }
+}
@@ -1,11 +1,15 @@
 extern crate nyx_space as nyx;
-use self::nyx::cosmic::{GuidanceMode, Orbit, Spacecraft};
-use self::nyx::dynamics::guidance::{FiniteBurns, Mnvr, Thruster};
+use self::nyx::cosmic::{GuidanceMode, Orbit, Spacecraft, STD_GRAVITY};
+use self::nyx::dynamics::guidance::{
+    FiniteBurns, GuidanceLaw, ManeuverPlan, Mnvr, TableGap, ThrustDirection, ThrustSegment,
+    ThrustTable, Thruster,
+};
 use self::nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
 use self::nyx::linalg::Vector3;
 use self::nyx::propagators::{IntegratorOptions, Propagator};
 use self::nyx::time::{Epoch, Unit};
 use self::nyx::utils::rss_orbit_vec_errors;
+use self::nyx::State;
 use crate::propagation::GMAT_EARTH_GM;
 use nyx::dynamics::guidance::LocalFrame;
 use std::sync::Arc;
@@ -371,3 +375,258 @@ fn val_transfer_single_maneuver_depl(almanac: Arc<Almanac>) {
         err_v
     );
 }
+
+#[rstest]
+fn thrust_table_pitch_program(almanac: Arc<Almanac>) {
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(GMAT_EARTH_GM);
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+    let orbit = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_time, eme2k,
+    );
+
+    let monoprop = Thruster {
+        thrust_N: 10.0,
+        isp_s: 300.0,
+    };
+    let dry_mass = 1e3;
+    let sc_state = Spacecraft::from_thruster(orbit, dry_mass, 0.0, monoprop, GuidanceMode::Coast);
+
+    // Thrust along +V for one minute, then at 30 degrees pitch for one minute.
+    let seg_duration = 1 * Unit::Minute;
+    let pitch_deg = 30.0_f64;
+    let table = ThrustTable::from_elapsed(
+        start_time,
+        &[
+            (
+                0 * Unit::Second,
+                seg_duration,
+                ThrustDirection::Vnc {
+                    pitch_deg: 0.0,
+                    yaw_deg: 0.0,
+                },
+                1.0,
+            ),
+            (
+                seg_duration,
+                seg_duration,
+                ThrustDirection::Vnc {
+                    pitch_deg,
+                    yaw_deg: 0.0,
+                },
+                1.0,
+            ),
+        ],
+        TableGap::Coast,
+    )
+    .unwrap();
+
+    // The table must be serializable to live in scenario files.
+    let yaml = serde_yaml::to_string(table.as_ref()).unwrap();
+    println!("{yaml}");
+
+    // Constant mass, so the delta-v of each segment is F/m * duration
+    let dv_seg_km_s = 1e-3 * monoprop.thrust_N / dry_mass * seg_duration.to_seconds();
+
+    let thrust_prop = Propagator::rk89(
+        SpacecraftDynamics::from_guidance_law_no_decr(OrbitalDynamics::two_body(), table),
+        IntegratorOptions::with_fixed_step(1.0 * Unit::Second),
+    );
+    let coast_prop = Propagator::rk89(
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        IntegratorOptions::with_fixed_step(1.0 * Unit::Second),
+    );
+
+    for (seg_no, pitch) in [0.0, pitch_deg.to_radians()].iter().enumerate() {
+        let seg_start = start_time + seg_duration * (seg_no as f64);
+        let init = thrust_prop
+            .with(sc_state, almanac.clone())
+            .until_epoch(seg_start)
+            .unwrap();
+
+        let thrust_end = thrust_prop
+            .with(init, almanac.clone())
+            .for_duration(seg_duration)
+            .unwrap();
+        let coast_end = coast_prop
+            .with(init, almanac.clone())
+            .for_duration(seg_duration)
+            .unwrap();
+
+        // Express the difference in velocity in the VNC frame at the middle of the segment
+        let mid = coast_prop
+            .with(init, almanac.clone())
+            .for_duration(seg_duration * 0.5)
+            .unwrap();
+        let dcm = mid.orbit.dcm_from_vnc_to_inertial().unwrap().rot_mat;
+        let dv_vnc =
+            dcm.transpose() * (thrust_end.orbit.velocity_km_s - coast_end.orbit.velocity_km_s);
+
+        let expected = Vector3::new(pitch.cos(), 0.0, pitch.sin()) * dv_seg_km_s;
+        println!("segment #{seg_no}: dv VNC = {dv_vnc}\texpected {expected}");
+        assert!(
+            (dv_vnc - expected).norm() / dv_seg_km_s < 1e-3,
+            "segment #{seg_no} delta-v mismatch"
+        );
+    }
+}
+
+#[allow(clippy::identity_op)]
+#[rstest]
+fn maneuver_plan_two_burns(almanac: Arc<Almanac>) {
+    let eme2k = almanac
+        .frame_from_uid(EARTH_J2000)
+        .unwrap()
+        .with_mu_km3_s2(GMAT_EARTH_GM);
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 10.0, 20.0, 0.0, start_time, eme2k);
+
+    let monoprop = Thruster {
+        thrust_N: 10.0,
+        isp_s: 300.0,
+    };
+    let fuel_mass = 100.0;
+    let sc_state = Spacecraft::from_thruster(orbit, 1e3, fuel_mass, monoprop, GuidanceMode::Coast);
+
+    // Raise the orbit along +V, and then change its plane at half throttle along +N.
+    let burn_duration = 5 * Unit::Minute;
+    let burns = [
+        (
+            start_time + 10 * Unit::Minute,
+            Vector3::new(1.0, 0.0, 0.0),
+            1.0,
+        ),
+        (
+            start_time + 45 * Unit::Minute,
+            Vector3::new(0.0, 1.0, 0.0),
+            0.5,
+        ),
+    ];
+
+    let mnvrs = burns
+        .iter()
+        .map(|(start, dir, throttle)| {
+            Mnvr::from_time_invariant(
+                *start,
+                *start + burn_duration,
+                *throttle,
+                *dir,
+                LocalFrame::VNC,
+            )
+        })
+        .collect::<Vec<Mnvr>>();
+
+    let plan = ManeuverPlan::new(
+        mnvrs
+            .iter()
+            .map(|mnvr| {
+                (
+                    mnvr.start,
+                    mnvr.end,
+                    Arc::new(*mnvr) as Arc<dyn GuidanceLaw>,
+                )
+            })
+            .collect(),
+    )
+    .unwrap();
+    println!("{plan}");
+
+    // Overlapping windows are rejected
+    assert!(ManeuverPlan::new(vec![
+        (
+            mnvrs[0].start,
+            mnvrs[1].end,
+            Arc::new(mnvrs[0]) as Arc<dyn GuidanceLaw>
+        ),
+        (
+            mnvrs[1].start,
+            mnvrs[1].end,
+            Arc::new(mnvrs[1]) as Arc<dyn GuidanceLaw>
+        ),
+    ])
+    .is_err());
+
+    let opts = IntegratorOptions::with_fixed_step(1.0 * Unit::Second);
+    let prop_time = 1 * Unit::Hour;
+
+    let (final_state, traj) = Propagator::rk89(
+        SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), plan),
+        opts,
+    )
+    .with(sc_state, almanac.clone())
+    .for_duration_with_traj(prop_time)
+    .unwrap();
+
+    // The fuel usage only depends on the burn durations and throttles
+    let mass_flow_kg_s = monoprop.thrust_N / (monoprop.isp_s * STD_GRAVITY);
+    let expected_usage_kg = burns
+        .iter()
+        .map(|(_, _, throttle)| throttle * mass_flow_kg_s * burn_duration.to_seconds())
+        .sum::<f64>();
+    let usage_kg = fuel_mass - final_state.fuel_mass_kg;
+    println!("fuel usage = {usage_kg} kg\texpected {expected_usage_kg} kg");
+    assert!((usage_kg - expected_usage_kg).abs() < 1e-9);
+
+    // No thrust outside of the windows: the fuel mass is constant and the spacecraft coasts
+    for (from, to) in [
+        (start_time, mnvrs[0].start),
+        (mnvrs[0].end, mnvrs[1].start),
+        (mnvrs[1].end, start_time + prop_time),
+    ] {
+        let fuel_at_start = traj.at(from).unwrap().fuel_mass_kg;
+        for state in traj
+            .states
+            .iter()
+            .filter(|state| (from..to).contains(&state.epoch()))
+        {
+            assert!(
+                (state.fuel_mass_kg - fuel_at_start).abs() < 1e-12,
+                "thrusting at {} outside of the plan",
+                state.epoch()
+            );
+            assert_eq!(state.mode(), GuidanceMode::Coast);
+        }
+    }
+
+    // The final orbit matches the equivalent thrust table
+    let table = ThrustTable::new(
+        burns
+            .iter()
+            .map(|(start, dir, throttle)| ThrustSegment {
+                start: *start,
+                duration: burn_duration,
+                direction: ThrustDirection::Vnc {
+                    pitch_deg: 0.0,
+                    yaw_deg: dir.y.asin().to_degrees(),
+                },
+                throttle: *throttle,
+            })
+            .collect(),
+        TableGap::Coast,
+    )
+    .unwrap();
+    let expected = Propagator::rk89(
+        SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), table),
+        opts,
+    )
+    .with(sc_state, almanac.clone())
+    .for_duration(prop_time)
+    .unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &final_state.orbit.to_cartesian_pos_vel(),
+        &expected.orbit.to_cartesian_pos_vel(),
+    );
+    println!("RSS errors:\tpos = {err_r:.5e} km\tvel = {err_v:.5e} km/s");
+    assert!(err_r < 1e-9, "final position differs: {err_r:.5e} km");
+    assert!(err_v < 1e-12, "final velocity differs: {err_v:.5e} km/s");
+    assert!((final_state.fuel_mass_kg - expected.fuel_mass_kg).abs() < 1e-12);
+
+    // Both burns changed the orbit: the first one raised it, the second one changed its plane
+    assert!(final_state.orbit.sma_km().unwrap() > orbit.sma_km().unwrap() + 1.0);
+    assert!((final_state.orbit.inc_deg().unwrap() - orbit.inc_deg().unwrap()).abs() > 5e-3);
+}
@@ -0,0 +1,222 @@
+extern crate nyx_space as nyx;
+use self::nyx::cosmic::{GuidanceMode, Orbit, Spacecraft, STD_GRAVITY};
+use self::nyx::dynamics::guidance::{
+    FiniteBurns, GuidanceLaw, LocalFrame, MinThrottlePolicy, Mnvr, MnvrOutcome, Thruster,
+    ThrusterLimits,
+};
+use self::nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use self::nyx::linalg::Vector3;
+use self::nyx::propagators::{IntegratorOptions, Propagator};
+use self::nyx::time::{Epoch, Unit};
+use std::sync::Arc;
+
+use anise::{constants::frames::EARTH_J2000, prelude::Almanac};
+use rstest::*;
+
+#[fixture]
+fn almanac() -> Arc<Almanac> {
+    use crate::test_almanac_arcd;
+    test_almanac_arcd()
+}
+
+/// A one Newton thruster with a specific impulse of 220 s
+fn thruster() -> Thruster {
+    Thruster {
+        thrust_N: 1.0,
+        isp_s: 220.0,
+    }
+}
+
+/// Propagates a spacecraft with the provided thruster limits and guidance law for one minute, returning the fuel usage in kg.
+fn fuel_usage_kg(limits: ThrusterLimits, law: Arc<dyn GuidanceLaw>, almanac: Arc<Almanac>) -> f64 {
+    let eme2k = almanac.frame_from_uid(EARTH_J2000).unwrap();
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2024, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 28.5, 10.0, 20.0, 0.0, start_time, eme2k);
+
+    let fuel_mass_kg = 50.0;
+    let mut sc =
+        Spacecraft::from_thruster(orbit, 500.0, fuel_mass_kg, thruster(), GuidanceMode::Coast);
+    sc.thruster_limits = Some(limits);
+
+    let final_state = Propagator::rk89(
+        SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), law),
+        IntegratorOptions::with_fixed_step(0.1 * Unit::Second),
+    )
+    .with(sc, almanac)
+    .for_duration(1 * Unit::Minute)
+    .unwrap();
+
+    fuel_mass_kg - final_state.fuel_mass_kg
+}
+
+/// A very small along-track correction starting 10 seconds after the start of the propagation
+fn small_correction(duration_s: f64, throttle: f64) -> Mnvr {
+    let start = Epoch::from_gregorian_tai_at_midnight(2024, 1, 1) + 10 * Unit::Second;
+    Mnvr::from_time_invariant(
+        start,
+        start + duration_s * Unit::Second,
+        throttle,
+        Vector3::x(),
+        LocalFrame::VNC,
+    )
+}
+
+#[test]
+fn thruster_builder_validation() {
+    let built = Thruster::builder()
+        .thrust_N(1.0)
+        .isp_s(220.0)
+        .build()
+        .unwrap();
+    assert_eq!(built, thruster());
+
+    let limits = ThrusterLimits::builder()
+        .min_throttle(0.1)
+        .min_impulse_bit_N_s(0.5)
+        .max_firing_duration(10 * Unit::Second)
+        .cooldown(5 * Unit::Second)
+        .build()
+        .unwrap();
+    assert_eq!(limits.min_throttle, Some(0.1));
+    assert_eq!(limits.min_throttle_policy, MinThrottlePolicy::ClampToZero);
+    assert_eq!(limits.limited_throttle(0.05), 0.0);
+    assert_eq!(limits.limited_throttle(0.5), 0.5);
+
+    // Limits are optional
+    let plain = ThrusterLimits::builder().build().unwrap();
+    assert_eq!(plain, ThrusterLimits::default());
+    assert_eq!(plain.limited_throttle(1e-6), 1e-6);
+
+    // Invalid configurations are rejected
+    assert!(Thruster::builder()
+        .thrust_N(-1.0)
+        .isp_s(220.0)
+        .build()
+        .is_err());
+    assert!(Thruster::builder()
+        .thrust_N(1.0)
+        .isp_s(0.0)
+        .build()
+        .is_err());
+    assert!(ThrusterLimits::builder().min_throttle(1.5).build().is_err());
+    assert!(ThrusterLimits::builder()
+        .min_impulse_bit_N_s(-0.1)
+        .build()
+        .is_err());
+    assert!(ThrusterLimits::builder()
+        .max_firing_duration(0 * Unit::Second)
+        .build()
+        .is_err());
+}
+
+#[rstest]
+fn thruster_min_throttle_policies(almanac: Arc<Almanac>) {
+    let mnvr = small_correction(20.0, 0.05);
+
+    // Clamped to zero: the maneuver is skipped and recorded as such
+    let to_zero = ThrusterLimits::builder().min_throttle(0.1).build().unwrap();
+
+    let schedule = FiniteBurns::with_thruster(vec![mnvr], &thruster(), &to_zero);
+    assert!(schedule.mnvrs.is_empty());
+    assert_eq!(
+        schedule.records[0].outcome,
+        MnvrOutcome::SkippedBelowMinThrottle {
+            throttle: 0.05,
+            min_throttle: 0.1
+        }
+    );
+    println!("{}", schedule.records[0]);
+    assert_eq!(fuel_usage_kg(to_zero, schedule, almanac.clone()), 0.0);
+
+    // The dynamics also apply the policy to any other guidance law
+    assert_eq!(fuel_usage_kg(to_zero, Arc::new(mnvr), almanac.clone()), 0.0);
+
+    // Clamped to the minimum: the maneuver is executed at the minimum throttle
+    let to_min = ThrusterLimits {
+        min_throttle_policy: MinThrottlePolicy::ClampToMin,
+        ..to_zero
+    };
+
+    let schedule = FiniteBurns::with_thruster(vec![mnvr], &thruster(), &to_min);
+    assert_eq!(schedule.records[0].outcome, MnvrOutcome::Nominal);
+    assert_eq!(schedule.records[0].throttle, 0.1);
+    assert_eq!(schedule.mnvrs[0].thrust_prct, 0.1);
+    println!("{}", schedule.records[0]);
+
+    let mass_flow_kg_s = thruster().thrust_N / (thruster().isp_s * STD_GRAVITY);
+    let expected_kg = 0.1 * mass_flow_kg_s * mnvr.duration().to_seconds();
+    for law in [schedule as Arc<dyn GuidanceLaw>, Arc::new(mnvr)] {
+        let usage_kg = fuel_usage_kg(to_min, law, almanac.clone());
+        println!("fuel usage = {usage_kg:.6e} kg\texpected {expected_kg:.6e} kg");
+        assert!((usage_kg - expected_kg).abs() < 0.01 * expected_kg);
+    }
+}
+
+#[rstest]
+fn thruster_min_impulse_bit(almanac: Arc<Almanac>) {
+    let limits = ThrusterLimits::builder()
+        .min_impulse_bit_N_s(5.0)
+        .build()
+        .unwrap();
+
+    // A two second burn at full thrust only provides 2 N s
+    let too_short = small_correction(2.0, 1.0);
+    let long_enough = small_correction(10.0, 1.0);
+
+    let schedule = FiniteBurns::with_thruster(vec![too_short], &thruster(), &limits);
+    assert!(schedule.mnvrs.is_empty());
+    assert!(schedule.records[0].executed.is_empty());
+    match schedule.records[0].outcome {
+        MnvrOutcome::SkippedBelowMinImpulseBit {
+            impulse_N_s,
+            min_impulse_bit_N_s,
+        } => {
+            assert!((impulse_N_s - 2.0).abs() < 1e-9);
+            assert_eq!(min_impulse_bit_N_s, 5.0);
+        }
+        outcome => panic!("expected the burn to be skipped, got {outcome:?}"),
+    }
+    println!("{}", schedule.records[0]);
+    assert_eq!(fuel_usage_kg(limits, schedule, almanac.clone()), 0.0);
+
+    let schedule = FiniteBurns::with_thruster(vec![long_enough], &thruster(), &limits);
+    assert_eq!(schedule.records[0].outcome, MnvrOutcome::Nominal);
+    assert_eq!(schedule.mnvrs.len(), 1);
+    assert!(fuel_usage_kg(limits, schedule, almanac) > 0.0);
+}
+
+#[rstest]
+fn thruster_max_firing_duration(almanac: Arc<Almanac>) {
+    let limits = ThrusterLimits::builder()
+        .max_firing_duration(10 * Unit::Second)
+        .cooldown(5 * Unit::Second)
+        .build()
+        .unwrap();
+
+    let mnvr = small_correction(25.0, 1.0);
+    let schedule = FiniteBurns::with_thruster(vec![mnvr], &thruster(), &limits);
+    println!("{}", schedule.records[0]);
+
+    assert_eq!(
+        schedule.records[0].outcome,
+        MnvrOutcome::Split {
+            firings: 3,
+            cooldown: 5 * Unit::Second
+        }
+    );
+    // Firings of 10 s, 10 s and 5 s, separated by 5 s of cooldown
+    let expected = [(0.0, 10.0), (15.0, 25.0), (30.0, 35.0)];
+    assert_eq!(schedule.mnvrs.len(), expected.len());
+    for (firing, (start_s, end_s)) in schedule.mnvrs.iter().zip(expected) {
+        assert_eq!(firing.start, mnvr.start + start_s * Unit::Second);
+        assert_eq!(firing.end, mnvr.start + end_s * Unit::Second);
+        assert_eq!(firing.direction(), mnvr.direction());
+    }
+
+    // The total burn duration, and therefore the fuel usage, is unchanged
+    let mass_flow_kg_s = thruster().thrust_N / (thruster().isp_s * STD_GRAVITY);
+    let expected_kg = mass_flow_kg_s * mnvr.duration().to_seconds();
+    let usage_kg = fuel_usage_kg(limits, schedule, almanac);
+    println!("fuel usage = {usage_kg:.6e} kg\texpected {expected_kg:.6e} kg");
+    assert!((usage_kg - expected_kg).abs() < 0.01 * expected_kg);
+}